@@ -0,0 +1,115 @@
+//! Property-based round-trip tests for the CC serializers.
+//!
+//! Each CC implements [`CCArbitrary`]. The generated [`CC::arbitrary`] strategy combines all of them,
+//! so new CCs are automatically covered by [`test_cc_roundtrip`].
+
+use crate::prelude::*;
+use proptest::prelude::*;
+use proptest::strategy::{BoxedStrategy, Union};
+use zwave_core::prelude::*;
+use zwave_core::security::{S0_NONCE_SIZE, S0Nonce};
+
+/// Generates arbitrary instances of a CC for the round-trip tests.
+pub(crate) trait CCArbitrary: Sized {
+    /// Returns a strategy that generates instances which survive a serialize/parse round-trip,
+    /// or `None` if the CC cannot be round-tripped.
+    ///
+    /// CCs which encapsulate other CCs use `encapsulated` to generate those. It is `None`
+    /// for the innermost CC, in which case encapsulating CCs must also return `None`.
+    fn arbitrary(encapsulated: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>>;
+}
+
+impl CC {
+    /// Returns a strategy generating all CCs which can be round-tripped, including encapsulated ones
+    pub(crate) fn arbitrary() -> BoxedStrategy<CC> {
+        Union::new(CC::arbitrary_variants(None))
+            .prop_recursive(2, 4, 1, |inner| {
+                Union::new(CC::arbitrary_variants(Some(inner.boxed())))
+            })
+            .boxed()
+    }
+}
+
+// Strategies for the values defined in zwave-core. These only generate the canonical
+// representation, since other representations are normalized during serialization.
+
+pub(crate) fn level_set() -> impl Strategy<Value = LevelSet> {
+    prop_oneof![
+        (0u8..=LEVEL_MAX).prop_map(LevelSet::Level),
+        Just(LevelSet::On)
+    ]
+}
+
+pub(crate) fn level_report() -> impl Strategy<Value = LevelReport> {
+    prop_oneof![
+        (0u8..=LEVEL_MAX).prop_map(LevelReport::Level),
+        Just(LevelReport::Unknown)
+    ]
+}
+
+pub(crate) fn binary_set() -> impl Strategy<Value = BinarySet> {
+    prop_oneof![Just(BinarySet::Off), Just(BinarySet::On)]
+}
+
+pub(crate) fn binary_report() -> impl Strategy<Value = BinaryReport> {
+    prop_oneof![
+        Just(BinaryReport::Off),
+        Just(BinaryReport::On),
+        Just(BinaryReport::Unknown)
+    ]
+}
+
+pub(crate) fn duration_set() -> impl Strategy<Value = DurationSet> {
+    prop_oneof![
+        (0u8..=127).prop_map(DurationSet::Seconds),
+        (1u8..=127).prop_map(DurationSet::Minutes),
+        Just(DurationSet::Default)
+    ]
+}
+
+pub(crate) fn duration_report() -> impl Strategy<Value = DurationReport> {
+    prop_oneof![
+        (0u8..=127).prop_map(DurationReport::Seconds),
+        (1u8..=126).prop_map(DurationReport::Minutes),
+        Just(DurationReport::Unknown)
+    ]
+}
+
+pub(crate) fn version_major_minor() -> impl Strategy<Value = Version> {
+    (any::<u8>(), any::<u8>()).prop_map(|(major, minor)| Version {
+        major,
+        minor,
+        patch: None,
+    })
+}
+
+pub(crate) fn version_major_minor_patch() -> impl Strategy<Value = Version> {
+    (any::<u8>(), any::<u8>(), any::<u8>()).prop_map(|(major, minor, patch)| Version {
+        major,
+        minor,
+        patch: Some(patch),
+    })
+}
+
+pub(crate) fn command_class() -> impl Strategy<Value = CommandClasses> {
+    prop::sample::select(CommandClasses::all_ccs())
+}
+
+pub(crate) fn library_type() -> impl Strategy<Value = ZWaveLibraryType> {
+    (0u8..=ZWaveLibraryType::AvDevice as u8)
+        .prop_map(|t| ZWaveLibraryType::try_from(t).expect("library type is in range"))
+}
+
+pub(crate) fn s0_nonce() -> impl Strategy<Value = S0Nonce> {
+    prop::array::uniform::<_, S0_NONCE_SIZE>(any::<u8>()).prop_map(|n| S0Nonce::new(&n))
+}
+
+proptest! {
+    #[test]
+    fn test_cc_roundtrip(cc in CC::arbitrary()) {
+        let raw = cc.as_raw(&CCEncodingContext::default()).as_bytes();
+        let parsed = CCRaw::parse(&mut raw.clone())
+            .and_then(|raw| CC::try_from_raw(raw, CCParsingContext::default()));
+        prop_assert_eq!(parsed, Ok(cc), "raw: 0x{}", hex::encode(&raw));
+    }
+}
@@ -50,6 +50,14 @@ impl CCSequence for NonSequenced {
     }
 }
 
+/// Sends the given CC as-is, even if it would normally require sequencing
+pub(crate) fn non_sequenced(cc: WithAddress<CC>) -> Box<dyn CCSequence + Sync + Send> {
+    Box::new(NonSequenced {
+        cc,
+        finished: false,
+    })
+}
+
 impl IntoCCSequence for WithAddress<CC> {
     fn into_cc_sequence(self) -> Box<dyn CCSequence + Sync + Send> {
         let address = self.address().clone();
@@ -57,10 +65,7 @@ impl IntoCCSequence for WithAddress<CC> {
             CC::SecurityCCCommandEncapsulation(security_cc) => {
                 security_cc.with_address(address).into_cc_sequence()
             }
-            cc => Box::new(NonSequenced {
-                cc: cc.with_address(address),
-                finished: false,
-            }),
+            cc => non_sequenced(cc.with_address(address)),
         }
     }
 }
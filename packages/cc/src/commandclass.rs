@@ -1,7 +1,10 @@
 use crate::commandclass_raw::CCRaw;
-use bytes::Bytes;
+use crate::values::{CCValue, CCValueOptions, StaticCCValue};
+use crate::spec_deviation::{ParsingStrictness, SpecDeviation, ToleratedDeviations};
+use bytes::{Bytes, BytesMut};
 use core::ops::{Deref, DerefMut};
 use enum_dispatch::enum_dispatch;
+use thiserror::Error;
 use typed_builder::TypedBuilder;
 use zwave_core::{cache::CacheValue, value_id::ValueId};
 use zwave_core::{
@@ -12,18 +15,50 @@ use zwave_pal::prelude::*;
 
 pub use crate::cc_sequence::*;
 
-#[derive(Default, TypedBuilder)]
+/// The security managers that are available for encoding and parsing CCs.
+/// The driver shares a single instance behind an [`Arc`], which is only replaced
+/// when a security manager is (re-)initialized.
+#[derive(Default, Clone)]
+pub struct SecurityManagers {
+    pub s0: Option<SecurityManager>,
+    pub s2: Option<SecurityManager2>,
+}
+
+#[derive(Default, Clone, TypedBuilder)]
 #[builder(field_defaults(default))]
 pub struct CCEncodingContext {
     node_id: NodeId,
     own_node_id: NodeId,
     #[builder(default, setter(into))]
-    security_manager: Option<SecurityManager>,
+    security: Arc<SecurityManagers>,
+    /// The highest security class that was negotiated with the destination, if any
+    #[builder(default, setter(into))]
+    security_class: Option<SecurityClass>,
+    /// The CCs the serialized CC is encapsulated in, from the outermost to the innermost
     #[builder(default, setter(into))]
-    security_manager2: Option<SecurityManager2>,
+    encapsulation: Vec<CommandClasses>,
+}
+
+impl CCEncodingContext {
+    /// The highest security class that was negotiated with the destination, if any
+    pub fn security_class(&self) -> Option<SecurityClass> {
+        self.security_class
+    }
+
+    /// The CCs the serialized CC is encapsulated in, from the outermost to the innermost
+    pub fn encapsulation(&self) -> &[CommandClasses] {
+        &self.encapsulation
+    }
+
+    /// Returns the context to serialize a CC that is encapsulated in the given CC
+    pub fn encapsulated_in(&self, cc: CommandClasses) -> Self {
+        let mut ret = self.clone();
+        ret.encapsulation.push(cc);
+        ret
+    }
 }
 
-#[derive(Default, TypedBuilder)]
+#[derive(Default, Clone, TypedBuilder)]
 #[builder(field_defaults(default))]
 pub struct CCParsingContext {
     pub(crate) source_node_id: NodeId,
@@ -31,9 +66,131 @@ pub struct CCParsingContext {
     #[builder(default, setter(into))]
     pub(crate) frame_addressing: Option<FrameAddressing>,
     #[builder(default, setter(into))]
-    pub(crate) security_manager: Option<SecurityManager>,
-    #[builder(default, setter(into))]
-    pub(crate) security_manager2: Option<SecurityManager2>,
+    pub(crate) security: Arc<SecurityManagers>,
+    pub(crate) strictness: ParsingStrictness,
+    /// Collects the deviations from the specification that were tolerated during parsing
+    pub(crate) deviations: ToleratedDeviations,
+}
+
+impl CCParsingContext {
+    /// The deviations from the specification that were tolerated while parsing with this context
+    pub fn deviations(&self) -> &ToleratedDeviations {
+        &self.deviations
+    }
+
+    /// Handles a deviation from the specification. In strict mode, parsing fails.
+    /// Otherwise the deviation is recorded and parsing continues.
+    pub(crate) fn tolerate(&self, deviation: SpecDeviation) -> ParseResult<()> {
+        match self.strictness {
+            ParsingStrictness::Strict => zwave_core::parse::fail_validation(format!(
+                "The CC deviates from the specification: {}",
+                deviation
+            )),
+            ParsingStrictness::Lenient => {
+                self.deviations.push(deviation);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+/// Defines the reasons why a CC cannot be serialized (yet)
+pub enum CCEncodingError {
+    #[error("Secure commands (S0) can only be serialized when the network key is set")]
+    MissingS0Key,
+    #[error("The nonce of the receiver must be known before the command can be encrypted")]
+    MissingNonce,
+    #[error("{0} cannot be serialized in its current state")]
+    InvalidState(&'static str),
+    #[error("The payload is {size} bytes long, but the controller only supports {max} bytes")]
+    PayloadTooLarge { size: usize, max: usize },
+    #[error("{0} was negotiated with the node, so commands must not be sent with S0")]
+    SecurityClassMismatch(SecurityClass),
+}
+
+// Most CCs can always be serialized. Those with preconditions are checked here.
+impl TrySerializableWith<&CCEncodingContext> for CC {
+    type Error = CCEncodingError;
+
+    fn try_serialize(
+        &self,
+        output: &mut BytesMut,
+        ctx: &CCEncodingContext,
+    ) -> Result<(), CCEncodingError> {
+        match self {
+            CC::SecurityCCCommandEncapsulation(me) => me.try_serialize(output, ctx),
+            _ => {
+                self.serialize(output, ctx);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl CC {
+    /// Returns the CCs this CC encapsulates, starting with itself if it is an encapsulation CC
+    pub fn encapsulation_chain(&self) -> Vec<CommandClasses> {
+        let mut ret = Vec::new();
+        let mut cc = self;
+        while let CC::SecurityCCCommandEncapsulation(encapsulation) = cc {
+            ret.push(CommandClasses::Security);
+            let Some(encapsulated) = encapsulation.encapsulated() else {
+                break;
+            };
+            cc = encapsulated;
+        }
+        ret
+    }
+
+    /// Returns the security class this CC is sent with, given the context it is serialized in
+    pub fn security_class(&self, ctx: &CCEncodingContext) -> Option<SecurityClass> {
+        match self {
+            CC::SecurityCCCommandEncapsulation(_) => Some(SecurityClass::S0Legacy),
+            _ if ctx.encapsulation.contains(&CommandClasses::Security) => {
+                Some(SecurityClass::S0Legacy)
+            }
+            _ => None,
+        }
+    }
+
+    /// Like [`as_raw`](Self::as_raw), but returns an error instead of panicking
+    /// when the preconditions for serializing the CC are not met
+    pub fn try_as_raw(&self, ctx: &CCEncodingContext) -> Result<CCRaw, CCEncodingError> {
+        Ok(CCRaw {
+            cc_id: self.cc_id(),
+            cc_command: self.cc_command(),
+            payload: self.try_as_bytes(ctx)?,
+        })
+    }
+}
+
+/// Returns the values with a static property the given CC may expose
+pub fn static_cc_values(cc: CommandClasses) -> Vec<&'static StaticCCValue> {
+    match cc {
+        CommandClasses::Basic => BasicCCValues::static_values(),
+        CommandClasses::BinarySwitch => BinarySwitchCCValues::static_values(),
+        CommandClasses::ManufacturerSpecific => ManufacturerSpecificCCValues::static_values(),
+        CommandClasses::MultilevelSwitch => MultilevelSwitchCCValues::static_values(),
+        CommandClasses::Version => VersionCCValues::static_values(),
+        CommandClasses::WakeUp => WakeUpCCValues::static_values(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the instances of the values of a CC with a dynamic property,
+/// given the IDs of the values that were reported for the same endpoint
+pub fn dynamic_cc_values(
+    cc: CommandClasses,
+    reported: &[ValueId],
+) -> Vec<(CCValue, &'static CCValueOptions)> {
+    match cc {
+        CommandClasses::ManufacturerSpecific => {
+            ManufacturerSpecificCCValues::dynamic_values(reported)
+        }
+        CommandClasses::Version => VersionCCValues::dynamic_values(reported),
+        _ => Vec::new(),
+    }
 }
 
 pub trait CCParsable
@@ -64,10 +221,17 @@ pub trait CCId: CCBase {
 
 #[enum_dispatch(CC)]
 pub trait CCValues {
+    /// Returns the stateful values contained in this CC, which should be stored in the value cache
     fn to_values(&self) -> Vec<(ValueId, CacheValue)> {
         // CCs which carry values should implement this. For all others, this is a no-op.
         vec![]
     }
+
+    /// Returns the stateless values (events) contained in this CC. These are not stored,
+    /// but every occurrence should be passed to the application.
+    fn to_events(&self) -> Vec<(ValueId, CacheValue)> {
+        vec![]
+    }
 }
 
 #[enum_dispatch(CC)]
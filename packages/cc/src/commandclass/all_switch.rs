@@ -0,0 +1,287 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{bytes::be_u8, combinators::map_res};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum AllSwitchCCCommand {
+    Set = 0x01,
+    Get = 0x02,
+    Report = 0x03,
+    On = 0x04,
+    Off = 0x05,
+}
+
+/// Which of the All On and All Off commands a node reacts to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum AllSwitchMode {
+    ExcludedFromAll = 0x00,
+    ExcludedFromAllOn = 0x01,
+    ExcludedFromAllOff = 0x02,
+    #[default]
+    IncludedInAll = 0xff,
+}
+
+impl Display for AllSwitchMode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AllSwitchMode::ExcludedFromAll => write!(f, "excluded from all"),
+            AllSwitchMode::ExcludedFromAllOn => write!(f, "excluded from All On"),
+            AllSwitchMode::ExcludedFromAllOff => write!(f, "excluded from All Off"),
+            AllSwitchMode::IncludedInAll => write!(f, "included in all"),
+        }
+    }
+}
+
+impl AllSwitchMode {
+    fn parse(i: &mut Bytes) -> zwave_core::parse::ParseResult<Self> {
+        map_res(be_u8, Self::try_from).parse(i)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct AllSwitchCCSet {
+    pub mode: AllSwitchMode,
+}
+
+impl CCBase for AllSwitchCCSet {}
+
+impl CCId for AllSwitchCCSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::AllSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(AllSwitchCCCommand::Set as _)
+    }
+}
+
+impl CCParsable for AllSwitchCCSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let mode = AllSwitchMode::parse(i)?;
+
+        Ok(Self { mode })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for AllSwitchCCSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(self.mode as u8).serialize(output)
+    }
+}
+
+impl ToLogPayload for AllSwitchCCSet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("mode", self.mode.to_string())
+            .into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct AllSwitchCCGet {}
+
+impl CCBase for AllSwitchCCGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::AllSwitchCCReport(_))
+    }
+}
+
+impl CCId for AllSwitchCCGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::AllSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(AllSwitchCCCommand::Get as _)
+    }
+}
+
+impl CCParsable for AllSwitchCCGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for AllSwitchCCGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for AllSwitchCCGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct AllSwitchCCReport {
+    pub mode: AllSwitchMode,
+}
+
+impl CCBase for AllSwitchCCReport {}
+
+impl CCId for AllSwitchCCReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::AllSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(AllSwitchCCCommand::Report as _)
+    }
+}
+
+impl CCParsable for AllSwitchCCReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let mode = AllSwitchMode::parse(i)?;
+
+        Ok(Self { mode })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for AllSwitchCCReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(self.mode as u8).serialize(output)
+    }
+}
+
+impl ToLogPayload for AllSwitchCCReport {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("mode", self.mode.to_string())
+            .into()
+    }
+}
+
+/// Turns on all switches that are not excluded from All On.
+/// This is usually broadcast to the entire network.
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct AllSwitchCCOn {}
+
+impl CCBase for AllSwitchCCOn {}
+
+impl CCId for AllSwitchCCOn {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::AllSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(AllSwitchCCCommand::On as _)
+    }
+}
+
+impl CCParsable for AllSwitchCCOn {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for AllSwitchCCOn {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for AllSwitchCCOn {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+/// Turns off all switches that are not excluded from All Off.
+/// This is usually broadcast to the entire network.
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct AllSwitchCCOff {}
+
+impl CCBase for AllSwitchCCOff {}
+
+impl CCId for AllSwitchCCOff {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::AllSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(AllSwitchCCCommand::Off as _)
+    }
+}
+
+impl CCParsable for AllSwitchCCOff {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for AllSwitchCCOff {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for AllSwitchCCOff {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn all_switch_mode() -> impl Strategy<Value = AllSwitchMode> {
+        prop_oneof![
+            Just(AllSwitchMode::ExcludedFromAll),
+            Just(AllSwitchMode::ExcludedFromAllOn),
+            Just(AllSwitchMode::ExcludedFromAllOff),
+            Just(AllSwitchMode::IncludedInAll),
+        ]
+    }
+
+    impl CCArbitrary for AllSwitchCCSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(all_switch_mode().prop_map(|mode| Self { mode }).boxed())
+        }
+    }
+
+    impl CCArbitrary for AllSwitchCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for AllSwitchCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(all_switch_mode().prop_map(|mode| Self { mode }).boxed())
+        }
+    }
+
+    impl CCArbitrary for AllSwitchCCOn {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for AllSwitchCCOff {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+}
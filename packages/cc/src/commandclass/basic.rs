@@ -18,11 +18,12 @@ enum BasicCCProperties {
     TargetValue = 0x01,
     Duration = 0x02,
     RestorePrevious = 0x03,
+    Event = 0x04,
 }
 
 impl From<BasicCCProperties> for ValueIdProperties {
     fn from(val: BasicCCProperties) -> Self {
-        Self::new(val as u32, None)
+        Self::new(val as u16, None)
     }
 }
 
@@ -75,6 +76,26 @@ impl BasicCCValues {
         ),
         CCValueOptions::default()
     );
+
+    // Basic CC Set commands sent by a node, e.g. when a button is pressed.
+    // Only nodes that send events have this value, so it is created with the first one.
+    cc_value_static_property!(
+        Basic,
+        Event,
+        ValueMetadata::LevelSet(ValueMetadataCommon::default_readonly().label("Event value")),
+        CCValueOptions::default().stateful(false).auto_create(false)
+    );
+
+    /// The values with a static property, e.g. to create them before they are first reported
+    pub fn static_values() -> Vec<&'static StaticCCValue> {
+        vec![
+            Self::current_value(),
+            Self::target_value(),
+            Self::duration(),
+            Self::restore_previous(),
+            Self::event(),
+        ]
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
@@ -87,6 +108,7 @@ pub enum BasicCCCommand {
 
 #[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
 pub struct BasicCCSet {
+    #[cc_value(BasicCCValues::event)]
     pub target_value: LevelSet,
 }
 
@@ -189,13 +211,22 @@ impl CCId for BasicCCReport {
 }
 
 impl CCParsable for BasicCCReport {
-    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+    fn parse(i: &mut Bytes, ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
         let current_value = LevelReport::parse(i)?;
         let (target_value, duration) = map(opt((LevelReport::parse, DurationReport::parse)), |x| {
             x.unzip()
         })
         .parse(i)?;
 
+        // Some devices send the target value without the duration that must accompany it
+        let target_value = match target_value {
+            None if !i.is_empty() => {
+                ctx.tolerate(SpecDeviation::MissingDuration)?;
+                Some(LevelReport::parse(i)?)
+            }
+            target_value => target_value,
+        };
+
         Ok(Self {
             current_value,
             target_value,
@@ -233,6 +264,43 @@ impl ToLogPayload for BasicCCReport {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    impl CCArbitrary for BasicCCSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(
+                level_set()
+                    .prop_map(|target_value| Self { target_value })
+                    .boxed(),
+            )
+        }
+    }
+
+    impl CCArbitrary for BasicCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for BasicCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            // The target value and duration are either both present or both missing
+            let strategy = (
+                level_report(),
+                proptest::option::of((level_report(), duration_report())),
+            )
+                .prop_map(|(current_value, target)| {
+                    let (target_value, duration) = target.unzip();
+                    Self {
+                        current_value,
+                        target_value,
+                        duration,
+                    }
+                });
+            Some(strategy.boxed())
+        }
+    }
 
     #[test]
     fn test_basic_cc_values() {
@@ -245,4 +313,21 @@ mod test {
         let duration = BasicCCValues::duration();
         assert!(duration.is(&duration.id));
     }
+
+    #[test]
+    fn test_report_without_duration() {
+        let payload = Bytes::from_static(&[0x00, 0x63]);
+
+        let ctx = CCParsingContext::default();
+        let deviations = ctx.deviations().clone();
+        let report = BasicCCReport::parse(&mut payload.clone(), ctx).unwrap();
+        assert_eq!(report.target_value, Some(LevelReport::Level(99)));
+        assert_eq!(report.duration, None);
+        assert_eq!(deviations.take(), vec![SpecDeviation::MissingDuration]);
+
+        let ctx = CCParsingContext::builder()
+            .strictness(ParsingStrictness::Strict)
+            .build();
+        assert!(BasicCCReport::parse(&mut payload.clone(), ctx).is_err());
+    }
 }
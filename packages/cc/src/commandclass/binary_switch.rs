@@ -23,7 +23,7 @@ enum BinarySwitchCCProperties {
 
 impl From<BinarySwitchCCProperties> for ValueIdProperties {
     fn from(val: BinarySwitchCCProperties) -> Self {
-        Self::new(val as u32, None)
+        Self::new(val as u16, None)
     }
 }
 
@@ -68,6 +68,15 @@ impl BinarySwitchCCValues {
         ),
         CCValueOptions::default().min_version(2)
     );
+
+    /// The values with a static property, e.g. to create them before they are first reported
+    pub fn static_values() -> Vec<&'static StaticCCValue> {
+        vec![
+            Self::current_value(),
+            Self::target_value(),
+            Self::duration(),
+        ]
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
@@ -194,7 +203,7 @@ impl CCId for BinarySwitchCCReport {
 }
 
 impl CCParsable for BinarySwitchCCReport {
-    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+    fn parse(i: &mut Bytes, ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
         let current_value = BinaryReport::parse(i)?;
         let (target_value, duration) =
             map(opt((BinaryReport::parse, DurationReport::parse)), |x| {
@@ -202,6 +211,15 @@ impl CCParsable for BinarySwitchCCReport {
             })
             .parse(i)?;
 
+        // Some devices send the target value without the duration that must accompany it
+        let target_value = match target_value {
+            None if !i.is_empty() => {
+                ctx.tolerate(SpecDeviation::MissingDuration)?;
+                Some(BinaryReport::parse(i)?)
+            }
+            target_value => target_value,
+        };
+
         Ok(Self {
             current_value,
             target_value,
@@ -235,3 +253,70 @@ impl ToLogPayload for BinarySwitchCCReport {
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use crate::test_vectors::cc_test_vectors;
+    use proptest::prelude::*;
+
+    cc_test_vectors! {
+        set_on: "2501ff" <=> BinarySwitchCCSet {
+            target_value: BinarySet::On,
+            duration: None,
+        },
+        log: ["target value: on"];
+
+        get: "2502" <=> BinarySwitchCCGet {},
+        log: [];
+
+        report_with_target: "250300ff05" <=> BinarySwitchCCReport {
+            current_value: BinaryReport::Off,
+            target_value: Some(BinaryReport::On),
+            duration: Some(DurationReport::Seconds(5)),
+        },
+        log: [
+            "current value: off",
+            "target value:  on",
+            "duration:      5 seconds",
+        ];
+    }
+
+    impl CCArbitrary for BinarySwitchCCSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (binary_set(), proptest::option::of(duration_set())).prop_map(
+                |(target_value, duration)| Self {
+                    target_value,
+                    duration,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for BinarySwitchCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for BinarySwitchCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            // The target value and duration are either both present or both missing
+            let strategy = (
+                binary_report(),
+                proptest::option::of((binary_report(), duration_report())),
+            )
+                .prop_map(|(current_value, target)| {
+                    let (target_value, duration) = target.unzip();
+                    Self {
+                        current_value,
+                        target_value,
+                        duration,
+                    }
+                });
+            Some(strategy.boxed())
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use ux::{u3, u5};
+use zwave_core::parse::{bits, bytes::be_u8, combinators::map_res};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum ClockCCCommand {
+    Set = 0x04,
+    Get = 0x05,
+    Report = 0x06,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum Weekday {
+    Unknown = 0,
+    Monday = 1,
+    Tuesday = 2,
+    Wednesday = 3,
+    Thursday = 4,
+    Friday = 5,
+    Saturday = 6,
+    Sunday = 7,
+}
+
+impl Display for Weekday {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Weekday::Unknown => write!(f, "unknown"),
+            Weekday::Monday => write!(f, "Monday"),
+            Weekday::Tuesday => write!(f, "Tuesday"),
+            Weekday::Wednesday => write!(f, "Wednesday"),
+            Weekday::Thursday => write!(f, "Thursday"),
+            Weekday::Friday => write!(f, "Friday"),
+            Weekday::Saturday => write!(f, "Saturday"),
+            Weekday::Sunday => write!(f, "Sunday"),
+        }
+    }
+}
+
+/// The local time of a node's clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockTime {
+    pub weekday: Weekday,
+    /// 0-23
+    pub hour: u8,
+    /// 0-59
+    pub minute: u8,
+}
+
+impl ClockTime {
+    fn parse(i: &mut Bytes) -> zwave_core::parse::ParseResult<Self> {
+        let (weekday, hour) = bits::bits((
+            map_res(bits::take(3usize), |x: u8| Weekday::try_from(x)),
+            u5::parse,
+        ))
+        .parse(i)?;
+        let minute = be_u8(i)?;
+
+        Ok(Self {
+            weekday,
+            hour: hour.into(),
+            minute,
+        })
+    }
+
+    fn serialize(&self, output: &mut BytesMut) {
+        use serialize::{bits::bits, bytes::be_u8};
+
+        bits(move |bo| {
+            u3::new(self.weekday as u8).write(bo);
+            u5::new(self.hour.min(23)).write(bo);
+        })
+        .serialize(output);
+        be_u8(self.minute.min(59)).serialize(output);
+    }
+}
+
+impl Display for ClockTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}, {:02}:{:02}", self.weekday, self.hour, self.minute)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ClockCCSet {
+    pub time: ClockTime,
+}
+
+impl CCBase for ClockCCSet {}
+
+impl CCId for ClockCCSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Clock
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ClockCCCommand::Set as _)
+    }
+}
+
+impl CCParsable for ClockCCSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let time = ClockTime::parse(i)?;
+
+        Ok(Self { time })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ClockCCSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.time.serialize(output);
+    }
+}
+
+impl ToLogPayload for ClockCCSet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("time", self.time.to_string())
+            .into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct ClockCCGet {}
+
+impl CCBase for ClockCCGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::ClockCCReport(_))
+    }
+}
+
+impl CCId for ClockCCGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Clock
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ClockCCCommand::Get as _)
+    }
+}
+
+impl CCParsable for ClockCCGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ClockCCGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for ClockCCGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ClockCCReport {
+    pub time: ClockTime,
+}
+
+impl CCBase for ClockCCReport {}
+
+impl CCId for ClockCCReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Clock
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ClockCCCommand::Report as _)
+    }
+}
+
+impl CCParsable for ClockCCReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let time = ClockTime::parse(i)?;
+
+        Ok(Self { time })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ClockCCReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.time.serialize(output);
+    }
+}
+
+impl ToLogPayload for ClockCCReport {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("time", self.time.to_string())
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn clock_time() -> impl Strategy<Value = ClockTime> {
+        (0u8..=7, 0u8..=23, 0u8..=59).prop_map(|(weekday, hour, minute)| ClockTime {
+            weekday: Weekday::try_from(weekday).unwrap(),
+            hour,
+            minute,
+        })
+    }
+
+    impl CCArbitrary for ClockCCSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(clock_time().prop_map(|time| Self { time }).boxed())
+        }
+    }
+
+    impl CCArbitrary for ClockCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for ClockCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(clock_time().prop_map(|time| Self { time }).boxed())
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use crate::prelude::*;
+use crate::values::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{
+    bytes::{be_u8, be_u16, complete::take},
+    combinators::opt,
+    fail_validation, validate,
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum ConfigurationCCCommand {
+    PropertiesGet = 0x0e,
+    PropertiesReport = 0x0f,
+}
+
+/// How the value of a configuration parameter is interpreted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum ConfigValueFormat {
+    #[default]
+    SignedInteger = 0x00,
+    UnsignedInteger = 0x01,
+    Enumerated = 0x02,
+    BitField = 0x03,
+}
+
+impl ConfigValueFormat {
+    fn is_signed(&self) -> bool {
+        matches!(self, Self::SignedInteger)
+    }
+}
+
+impl Display for ConfigValueFormat {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConfigValueFormat::SignedInteger => write!(f, "signed integer"),
+            ConfigValueFormat::UnsignedInteger => write!(f, "unsigned integer"),
+            ConfigValueFormat::Enumerated => write!(f, "enumerated"),
+            ConfigValueFormat::BitField => write!(f, "bit field"),
+        }
+    }
+}
+
+fn parse_config_value(
+    i: &mut Bytes,
+    size: u8,
+    format: ConfigValueFormat,
+) -> zwave_core::parse::ParseResult<i64> {
+    let raw = take(size).parse(i)?;
+    let value = raw.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+    if format.is_signed() {
+        // Sign-extend the value to 64 bits
+        let shift = 64 - 8 * size as u32;
+        Ok(((value << shift) as i64) >> shift)
+    } else {
+        Ok(value as i64)
+    }
+}
+
+fn serialize_config_value(output: &mut BytesMut, size: u8, value: i64) {
+    use serialize::bytes::slice;
+    let bytes = (value as u64).to_be_bytes();
+    slice(&bytes[bytes.len() - size as usize..]).serialize(output);
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ConfigurationCCPropertiesGet {
+    pub parameter: u16,
+}
+
+impl CCBase for ConfigurationCCPropertiesGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(
+            response,
+            CC::ConfigurationCCPropertiesReport(report) if report.parameter == self.parameter
+        )
+    }
+}
+
+impl CCId for ConfigurationCCPropertiesGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Configuration
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ConfigurationCCCommand::PropertiesGet as _)
+    }
+}
+
+impl CCParsable for ConfigurationCCPropertiesGet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let parameter = be_u16(i)?;
+
+        Ok(Self { parameter })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ConfigurationCCPropertiesGet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u16;
+        be_u16(self.parameter).serialize(output)
+    }
+}
+
+impl ToLogPayload for ConfigurationCCPropertiesGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("parameter", self.parameter)
+            .into()
+    }
+}
+
+/// Describes a configuration parameter. Reported by nodes supporting Configuration CC V3+.
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ConfigurationCCPropertiesReport {
+    pub parameter: u16,
+    /// The size of the parameter value in bytes. `0` means the parameter does not exist.
+    pub value_size: u8,
+    pub value_format: ConfigValueFormat,
+    #[builder(default)]
+    pub min_value: i64,
+    #[builder(default)]
+    pub max_value: i64,
+    #[builder(default)]
+    pub default_value: i64,
+    /// The next parameter the node supports, or `0` if this is the last one
+    #[builder(default)]
+    pub next_parameter: u16,
+    /// V4+: Whether the parameter cannot be changed
+    #[builder(default)]
+    pub read_only: bool,
+    /// V4+: Whether changing the parameter alters the node's capabilities
+    #[builder(default)]
+    pub altering_capabilities: bool,
+    /// V4+: Whether the parameter is only meant for advanced users
+    #[builder(default)]
+    pub advanced: bool,
+    /// V4+: Whether the parameter cannot be accessed with the bulk commands
+    #[builder(default)]
+    pub no_bulk_support: bool,
+}
+
+impl ConfigurationCCPropertiesReport {
+    /// Returns the metadata the node reports for the parameter,
+    /// or `None` if the parameter does not exist
+    pub fn reported_metadata(&self) -> Option<ValueMetadataNumeric> {
+        if self.value_size == 0 {
+            return None;
+        }
+
+        let common = if self.read_only {
+            ValueMetadataCommon::default_readonly()
+        } else {
+            ValueMetadataCommon::default()
+        };
+        Some(
+            ValueMetadataNumeric::default()
+                .common(common)
+                .min(self.min_value)
+                .max(self.max_value)
+                .default_value(self.default_value),
+        )
+    }
+
+    /// Returns the metadata for the parameter, combining the reported metadata with
+    /// the definition from a device config file, if there is one.
+    /// See [`ValueMetadataConfiguration::merge`] for which source takes precedence.
+    pub fn metadata(&self, from_file: Option<&ConfigParamFileMetadata>) -> ValueMetadata {
+        let reported = self.reported_metadata();
+        ValueMetadata::Configuration(ValueMetadataConfiguration::merge(
+            from_file,
+            reported.as_ref(),
+        ))
+    }
+}
+
+impl CCBase for ConfigurationCCPropertiesReport {}
+
+impl CCId for ConfigurationCCPropertiesReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Configuration
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ConfigurationCCCommand::PropertiesReport as _)
+    }
+}
+
+impl CCParsable for ConfigurationCCPropertiesReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let parameter = be_u16(i)?;
+        let flags = be_u8(i)?;
+        let altering_capabilities = flags & 0b1000_0000 != 0;
+        let read_only = flags & 0b0100_0000 != 0;
+        let Ok(value_format) = ConfigValueFormat::try_from((flags >> 3) & 0b111) else {
+            return fail_validation("Unknown configuration value format");
+        };
+        let value_size = flags & 0b111;
+        validate(
+            matches!(value_size, 0 | 1 | 2 | 4),
+            format!("Invalid configuration value size {}", value_size),
+        )?;
+
+        let (min_value, max_value, default_value) = if value_size > 0 {
+            (
+                parse_config_value(i, value_size, value_format)?,
+                parse_config_value(i, value_size, value_format)?,
+                parse_config_value(i, value_size, value_format)?,
+            )
+        } else {
+            (0, 0, 0)
+        };
+        let next_parameter = be_u16(i)?;
+        // V3 nodes do not send the last byte
+        let flags = opt(be_u8).parse(i)?.unwrap_or_default();
+        let no_bulk_support = flags & 0b10 != 0;
+        let advanced = flags & 0b1 != 0;
+
+        Ok(Self {
+            parameter,
+            value_size,
+            value_format,
+            min_value,
+            max_value,
+            default_value,
+            next_parameter,
+            read_only,
+            altering_capabilities,
+            advanced,
+            no_bulk_support,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ConfigurationCCPropertiesReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::{be_u8, be_u16};
+
+        be_u16(self.parameter).serialize(output);
+        let flags = (self.altering_capabilities as u8) << 7
+            | (self.read_only as u8) << 6
+            | (self.value_format as u8) << 3
+            | (self.value_size & 0b111);
+        be_u8(flags).serialize(output);
+        if self.value_size > 0 {
+            serialize_config_value(output, self.value_size, self.min_value);
+            serialize_config_value(output, self.value_size, self.max_value);
+            serialize_config_value(output, self.value_size, self.default_value);
+        }
+        be_u16(self.next_parameter).serialize(output);
+        be_u8((self.no_bulk_support as u8) << 1 | self.advanced as u8).serialize(output);
+    }
+}
+
+impl ToLogPayload for ConfigurationCCPropertiesReport {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new().with_entry("parameter", self.parameter);
+        if self.value_size == 0 {
+            ret = ret.with_entry("supported", false);
+        } else {
+            ret = ret
+                .with_entry("value size", self.value_size)
+                .with_entry("value format", self.value_format.to_string())
+                .with_entry("min value", self.min_value)
+                .with_entry("max value", self.max_value)
+                .with_entry("default value", self.default_value)
+                .with_entry("read-only", self.read_only)
+                .with_entry("advanced", self.advanced);
+        }
+        ret = ret.with_entry("next parameter", self.next_parameter);
+        ret.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use crate::test_vectors::cc_test_vectors;
+    use proptest::prelude::*;
+
+    cc_test_vectors! {
+        properties_get: "700e0001" <=> ConfigurationCCPropertiesGet { parameter: 1 },
+        log: ["parameter: 1"];
+
+        properties_report: "700f000109006332000200" <=> ConfigurationCCPropertiesReport {
+            parameter: 1,
+            value_size: 1,
+            value_format: ConfigValueFormat::UnsignedInteger,
+            min_value: 0,
+            max_value: 99,
+            default_value: 50,
+            next_parameter: 2,
+            read_only: false,
+            altering_capabilities: false,
+            advanced: false,
+            no_bulk_support: false,
+        };
+
+        properties_report_signed: "700f000202ffff0000ffff000000" <=> ConfigurationCCPropertiesReport {
+            parameter: 2,
+            value_size: 2,
+            value_format: ConfigValueFormat::SignedInteger,
+            min_value: -1,
+            max_value: 0,
+            default_value: -1,
+            next_parameter: 0,
+            read_only: false,
+            altering_capabilities: false,
+            advanced: false,
+            no_bulk_support: false,
+        };
+    }
+
+    #[test]
+    fn test_reported_metadata_is_merged_with_the_config_file() {
+        let report = ConfigurationCCPropertiesReport::builder()
+            .parameter(1)
+            .value_size(1)
+            .value_format(ConfigValueFormat::UnsignedInteger)
+            .min_value(0)
+            .max_value(99)
+            .default_value(50)
+            .build();
+        let from_file = ConfigParamFileMetadata {
+            metadata: ValueMetadataNumeric::default()
+                .label("LED brightness")
+                .min(0)
+                .max(10),
+            override_ranges: false,
+        };
+
+        let ValueMetadata::Configuration(merged) = report.metadata(Some(&from_file)) else {
+            panic!("expected configuration metadata");
+        };
+        assert_eq!(
+            merged.numeric.common.label.as_deref(),
+            Some("LED brightness")
+        );
+        assert_eq!(merged.provenance.label, Some(MetadataSource::DeviceConfig));
+        assert_eq!(
+            (merged.numeric.min, merged.numeric.max),
+            (Some(0), Some(99))
+        );
+        assert_eq!(merged.provenance.range, Some(MetadataSource::Device));
+
+        // Parameters the node does not have are described by the file alone
+        let unsupported = ConfigurationCCPropertiesReport::builder()
+            .parameter(1)
+            .value_size(0)
+            .value_format(ConfigValueFormat::SignedInteger)
+            .build();
+        assert!(unsupported.reported_metadata().is_none());
+        let ValueMetadata::Configuration(merged) = unsupported.metadata(Some(&from_file)) else {
+            panic!("expected configuration metadata");
+        };
+        assert_eq!(merged.provenance.range, Some(MetadataSource::DeviceConfig));
+    }
+
+    fn config_value(size: u8, format: ConfigValueFormat) -> BoxedStrategy<i64> {
+        let bits = 8 * size as u32;
+        if format.is_signed() {
+            (-(1i64 << (bits - 1))..(1i64 << (bits - 1))).boxed()
+        } else {
+            (0i64..(1i64 << bits)).boxed()
+        }
+    }
+
+    impl CCArbitrary for ConfigurationCCPropertiesGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = any::<u16>().prop_map(|parameter| Self { parameter });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for ConfigurationCCPropertiesReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let format = prop_oneof![
+                Just(ConfigValueFormat::SignedInteger),
+                Just(ConfigValueFormat::UnsignedInteger),
+                Just(ConfigValueFormat::Enumerated),
+                Just(ConfigValueFormat::BitField),
+            ];
+            let values = (prop_oneof![Just(1u8), Just(2u8), Just(4u8)], format).prop_flat_map(
+                |(size, format)| {
+                    (
+                        Just(size),
+                        Just(format),
+                        config_value(size, format),
+                        config_value(size, format),
+                        config_value(size, format),
+                    )
+                },
+            );
+            let strategy = (any::<u16>(), values, any::<u16>(), any::<[bool; 4]>()).prop_map(
+                |(
+                    parameter,
+                    (value_size, value_format, min_value, max_value, default_value),
+                    next_parameter,
+                    [read_only, altering_capabilities, advanced, no_bulk_support],
+                )| Self {
+                    parameter,
+                    value_size,
+                    value_format,
+                    min_value,
+                    max_value,
+                    default_value,
+                    next_parameter,
+                    read_only,
+                    altering_capabilities,
+                    advanced,
+                    no_bulk_support,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::bytes::{be_u8, rest};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+// Controller Replication CC is used to transfer application data, like groups, from the
+// including controller to a new secondary controller. The node table itself is replicated by
+// the protocol.
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum ControllerReplicationCCCommand {
+    TransferGroup = 0x31,
+    TransferGroupName = 0x32,
+}
+
+/// Transfers that a node is part of a group
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ControllerReplicationCCTransferGroup {
+    #[builder(default)]
+    pub sequence_number: u8,
+    pub group_id: u8,
+    pub node_id: NodeId,
+}
+
+impl CCBase for ControllerReplicationCCTransferGroup {}
+
+impl CCId for ControllerReplicationCCTransferGroup {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::ControllerReplication
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ControllerReplicationCCCommand::TransferGroup as _)
+    }
+}
+
+impl CCParsable for ControllerReplicationCCTransferGroup {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let sequence_number = be_u8(i)?;
+        let group_id = be_u8(i)?;
+        let node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+
+        Ok(Self {
+            sequence_number,
+            group_id,
+            node_id,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ControllerReplicationCCTransferGroup {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+        tuple((be_u8(self.sequence_number), be_u8(self.group_id))).serialize(output);
+        self.node_id.serialize(output, NodeIdType::NodeId8Bit);
+    }
+}
+
+impl ToLogPayload for ControllerReplicationCCTransferGroup {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("sequence number", self.sequence_number)
+            .with_entry("group", self.group_id)
+            .with_entry("node id", self.node_id.to_string())
+            .into()
+    }
+}
+
+/// Transfers the name of a group
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ControllerReplicationCCTransferGroupName {
+    #[builder(default)]
+    pub sequence_number: u8,
+    pub group_id: u8,
+    #[builder(setter(into))]
+    pub name: String,
+}
+
+impl CCBase for ControllerReplicationCCTransferGroupName {}
+
+impl CCId for ControllerReplicationCCTransferGroupName {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::ControllerReplication
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ControllerReplicationCCCommand::TransferGroupName as _)
+    }
+}
+
+impl CCParsable for ControllerReplicationCCTransferGroupName {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let sequence_number = be_u8(i)?;
+        let group_id = be_u8(i)?;
+        let name = String::from_utf8_lossy(&rest(i)?).into_owned();
+
+        Ok(Self {
+            sequence_number,
+            group_id,
+            name,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ControllerReplicationCCTransferGroupName {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::{
+            bytes::{be_u8, slice},
+            sequence::tuple,
+        };
+        tuple((
+            be_u8(self.sequence_number),
+            be_u8(self.group_id),
+            slice(self.name.as_bytes()),
+        ))
+        .serialize(output);
+    }
+}
+
+impl ToLogPayload for ControllerReplicationCCTransferGroupName {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("sequence number", self.sequence_number)
+            .with_entry("group", self.group_id)
+            .with_entry("name", self.name.clone())
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    impl CCArbitrary for ControllerReplicationCCTransferGroup {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (any::<u8>(), any::<u8>(), 1u8..=232).prop_map(
+                |(sequence_number, group_id, node_id)| Self {
+                    sequence_number,
+                    group_id,
+                    node_id: NodeId::new(node_id),
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for ControllerReplicationCCTransferGroupName {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (any::<u8>(), any::<u8>(), "[a-zA-Z0-9 ]{0,16}").prop_map(
+                |(sequence_number, group_id, name)| Self {
+                    sequence_number,
+                    group_id,
+                    name,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+}
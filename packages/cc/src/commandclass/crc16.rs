@@ -5,7 +5,7 @@ use proc_macros::{CCValues, TryFromRepr};
 use zwave_core::checksum::crc16_incremental;
 use zwave_core::parse::{
     bytes::{be_u16, complete::take},
-    validate,
+    validate_checksum,
 };
 use zwave_core::prelude::*;
 use zwave_core::serialize::{self, Serializable};
@@ -70,7 +70,7 @@ impl CCParsable for Crc16CCCommandEncapsulation {
             .update(&payload)
             .get();
 
-        validate(
+        validate_checksum(
             checksum == expected_checksum,
             format!(
                 "checksum mismatch: expected {:#06x}, got {:#06x}",
@@ -111,3 +111,16 @@ impl ToLogPayload for Crc16CCCommandEncapsulation {
             .into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    impl CCArbitrary for Crc16CCCommandEncapsulation {
+        fn arbitrary(encapsulated: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(encapsulated?.prop_map(Self::new).boxed())
+        }
+    }
+}
@@ -0,0 +1,795 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{
+    bytes::{be_u8, be_u32, complete::take},
+    combinators::map_res,
+    multi::{length_data, many_0},
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_pal::prelude::*;
+
+// Network Management Installation and Maintenance CC gives access to the routing information
+// and the statistics a node collects about its communication with other nodes.
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum InstallationMaintenanceCCCommand {
+    PriorityRouteSet = 0x01,
+    PriorityRouteGet = 0x02,
+    PriorityRouteReport = 0x03,
+    StatisticsGet = 0x04,
+    StatisticsReport = 0x05,
+    StatisticsClear = 0x06,
+    RssiGet = 0x07,
+    RssiReport = 0x08,
+}
+
+/// How many repeaters a route may contain
+pub const MAX_REPEATERS: usize = 4;
+
+/// Where a route to a node comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum RouteKind {
+    /// There is no route to the node
+    None = 0x00,
+    /// The last route that worked
+    LastWorkingRoute = 0x01,
+    /// The route that worked before the last working route
+    NextToLastWorkingRoute = 0x02,
+    /// The route was assigned by the application
+    Application = 0x10,
+}
+
+impl Display for RouteKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RouteKind::None => write!(f, "none"),
+            RouteKind::LastWorkingRoute => write!(f, "last working route"),
+            RouteKind::NextToLastWorkingRoute => write!(f, "next to last working route"),
+            RouteKind::Application => write!(f, "set by application"),
+        }
+    }
+}
+
+fn parse_repeaters(i: &mut Bytes) -> zwave_core::parse::ParseResult<Vec<NodeId>> {
+    let repeaters = take(MAX_REPEATERS).parse(i)?;
+    // Unused repeater slots are zero. The route ends at the first one.
+    Ok(repeaters
+        .iter()
+        .take_while(|&&node_id| node_id != 0)
+        .map(|&node_id| NodeId::new(node_id))
+        .collect())
+}
+
+fn serialize_repeaters(repeaters: &[NodeId], output: &mut BytesMut) {
+    let mut raw = [0u8; MAX_REPEATERS];
+    for (slot, repeater) in raw.iter_mut().zip(repeaters) {
+        *slot = u16::from(*repeater) as u8;
+    }
+    serialize::bytes::slice(raw).serialize(output);
+}
+
+fn format_route(repeaters: &[NodeId]) -> String {
+    if repeaters.is_empty() {
+        return "direct".to_string();
+    }
+    repeaters
+        .iter()
+        .map(|repeater| repeater.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// Assigns the route a node uses to reach another node
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct InstallationMaintenanceCCPriorityRouteSet {
+    #[builder(setter(into))]
+    pub node_id: NodeId,
+    /// The repeaters between the nodes. Empty for direct communication.
+    #[builder(default)]
+    pub repeaters: Vec<NodeId>,
+    pub speed: DataRate,
+}
+
+impl CCBase for InstallationMaintenanceCCPriorityRouteSet {}
+
+impl CCId for InstallationMaintenanceCCPriorityRouteSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::PriorityRouteSet as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCPriorityRouteSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+        let repeaters = parse_repeaters(i)?;
+        let speed = DataRate::parse(i)?;
+
+        Ok(Self {
+            node_id,
+            repeaters,
+            speed,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCPriorityRouteSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.node_id.serialize(output, NodeIdType::NodeId8Bit);
+        serialize_repeaters(&self.repeaters, output);
+        self.speed.serialize(output);
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCPriorityRouteSet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("node id", self.node_id.to_string())
+            .with_entry("repeaters", format_route(&self.repeaters))
+            .with_entry("speed", self.speed.to_string())
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct InstallationMaintenanceCCPriorityRouteGet {
+    #[builder(setter(into))]
+    pub node_id: NodeId,
+}
+
+impl CCBase for InstallationMaintenanceCCPriorityRouteGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(
+            response,
+            CC::InstallationMaintenanceCCPriorityRouteReport(report)
+                if report.node_id == self.node_id
+        )
+    }
+}
+
+impl CCId for InstallationMaintenanceCCPriorityRouteGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::PriorityRouteGet as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCPriorityRouteGet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+
+        Ok(Self { node_id })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCPriorityRouteGet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.node_id.serialize(output, NodeIdType::NodeId8Bit);
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCPriorityRouteGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("node id", self.node_id.to_string())
+            .into()
+    }
+}
+
+/// The route a node uses to reach another node
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct InstallationMaintenanceCCPriorityRouteReport {
+    #[builder(setter(into))]
+    pub node_id: NodeId,
+    pub kind: RouteKind,
+    /// The repeaters between the nodes. Empty for direct communication.
+    #[builder(default)]
+    pub repeaters: Vec<NodeId>,
+    pub speed: DataRate,
+}
+
+impl CCBase for InstallationMaintenanceCCPriorityRouteReport {}
+
+impl CCId for InstallationMaintenanceCCPriorityRouteReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::PriorityRouteReport as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCPriorityRouteReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+        let kind = map_res(be_u8, RouteKind::try_from).parse(i)?;
+        let repeaters = parse_repeaters(i)?;
+        let speed = DataRate::parse(i)?;
+
+        Ok(Self {
+            node_id,
+            kind,
+            repeaters,
+            speed,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCPriorityRouteReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u8;
+        self.node_id.serialize(output, NodeIdType::NodeId8Bit);
+        be_u8(self.kind as u8).serialize(output);
+        serialize_repeaters(&self.repeaters, output);
+        self.speed.serialize(output);
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCPriorityRouteReport {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new()
+            .with_entry("node id", self.node_id.to_string())
+            .with_entry("route kind", self.kind.to_string());
+        if self.kind != RouteKind::None {
+            ret = ret
+                .with_entry("repeaters", format_route(&self.repeaters))
+                .with_entry("speed", self.speed.to_string());
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct InstallationMaintenanceCCStatisticsGet {
+    #[builder(setter(into))]
+    pub node_id: NodeId,
+}
+
+impl CCBase for InstallationMaintenanceCCStatisticsGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(
+            response,
+            CC::InstallationMaintenanceCCStatisticsReport(report)
+                if report.node_id == self.node_id
+        )
+    }
+}
+
+impl CCId for InstallationMaintenanceCCStatisticsGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::StatisticsGet as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCStatisticsGet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+
+        Ok(Self { node_id })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCStatisticsGet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.node_id.serialize(output, NodeIdType::NodeId8Bit);
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCStatisticsGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("node id", self.node_id.to_string())
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+enum StatisticType {
+    RouteChanges = 0x00,
+    TransmissionCount = 0x01,
+    Neighbors = 0x02,
+    PacketErrorCount = 0x03,
+    TransmissionTimeSum = 0x04,
+    TransmissionTimeSquaredSum = 0x05,
+}
+
+/// A node in direct range of the node that reported it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Neighbor {
+    pub node_id: NodeId,
+    /// Whether the neighbor can repeat frames
+    pub repeater: bool,
+    pub speed: DataRate,
+}
+
+impl Neighbor {
+    fn parse(i: &mut Bytes) -> zwave_core::parse::ParseResult<Self> {
+        let node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+        let flags = be_u8(i)?;
+        let speed = DataRate::try_from(flags & 0b0001_1111)?;
+
+        Ok(Self {
+            node_id,
+            repeater: flags & 0b1000_0000 != 0,
+            speed,
+        })
+    }
+
+    fn serialize(&self, output: &mut BytesMut) {
+        use serialize::bytes::be_u8;
+        self.node_id.serialize(output, NodeIdType::NodeId8Bit);
+        be_u8(((self.repeater as u8) << 7) | self.speed as u8).serialize(output);
+    }
+}
+
+/// The statistics a node collected about its communication with another node.
+/// Each statistic is only present if the node reported it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkStatistics {
+    /// How often a new route had to be found
+    pub route_changes: Option<u8>,
+    /// How many frames were transmitted
+    pub transmission_count: Option<u8>,
+    /// The nodes in direct range of the other node
+    pub neighbors: Option<Vec<Neighbor>>,
+    /// How many frames were not acknowledged, even after retransmissions
+    pub packet_error_count: Option<u8>,
+    /// The sum of the transmission times in milliseconds
+    pub transmission_time_sum: Option<u32>,
+    /// The sum of the squared transmission times in milliseconds squared
+    pub transmission_time_squared_sum: Option<u32>,
+}
+
+impl LinkStatistics {
+    /// The average time it took to transmit a frame, if the node reported enough data
+    pub fn average_transmission_time(&self) -> Option<core::time::Duration> {
+        let count = self.transmission_count.filter(|&count| count > 0)?;
+        let sum = self.transmission_time_sum?;
+        Some(core::time::Duration::from_millis(
+            (sum / count as u32) as u64,
+        ))
+    }
+
+    fn parse(i: &mut Bytes) -> zwave_core::parse::ParseResult<Self> {
+        let mut ret = Self::default();
+        // The statistics are encoded as type, length and value. Unknown types are skipped.
+        for (statistic_type, mut value) in many_0((be_u8, length_data(be_u8))).parse(i)? {
+            match StatisticType::try_from(statistic_type) {
+                Ok(StatisticType::RouteChanges) => ret.route_changes = Some(be_u8(&mut value)?),
+                Ok(StatisticType::TransmissionCount) => {
+                    ret.transmission_count = Some(be_u8(&mut value)?)
+                }
+                Ok(StatisticType::Neighbors) => {
+                    ret.neighbors = Some(many_0(Neighbor::parse).parse(&mut value)?)
+                }
+                Ok(StatisticType::PacketErrorCount) => {
+                    ret.packet_error_count = Some(be_u8(&mut value)?)
+                }
+                Ok(StatisticType::TransmissionTimeSum) => {
+                    ret.transmission_time_sum = Some(be_u32(&mut value)?)
+                }
+                Ok(StatisticType::TransmissionTimeSquaredSum) => {
+                    ret.transmission_time_squared_sum = Some(be_u32(&mut value)?)
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(ret)
+    }
+
+    fn serialize(&self, output: &mut BytesMut) {
+        use serialize::bytes::{be_u8, be_u32};
+        let mut entry = |statistic_type: StatisticType, value: BytesMut| {
+            be_u8(statistic_type as u8).serialize(output);
+            be_u8(value.len() as u8).serialize(output);
+            serialize::bytes::slice(value).serialize(output);
+        };
+        let u8_entry = |value: u8| be_u8(value).as_bytes_mut();
+        let u32_entry = |value: u32| be_u32(value).as_bytes_mut();
+
+        if let Some(route_changes) = self.route_changes {
+            entry(StatisticType::RouteChanges, u8_entry(route_changes));
+        }
+        if let Some(transmission_count) = self.transmission_count {
+            entry(
+                StatisticType::TransmissionCount,
+                u8_entry(transmission_count),
+            );
+        }
+        if let Some(neighbors) = &self.neighbors {
+            let mut value = BytesMut::new();
+            for neighbor in neighbors {
+                neighbor.serialize(&mut value);
+            }
+            entry(StatisticType::Neighbors, value);
+        }
+        if let Some(packet_error_count) = self.packet_error_count {
+            entry(
+                StatisticType::PacketErrorCount,
+                u8_entry(packet_error_count),
+            );
+        }
+        if let Some(sum) = self.transmission_time_sum {
+            entry(StatisticType::TransmissionTimeSum, u32_entry(sum));
+        }
+        if let Some(sum) = self.transmission_time_squared_sum {
+            entry(StatisticType::TransmissionTimeSquaredSum, u32_entry(sum));
+        }
+    }
+}
+
+/// The statistics a node collected about its communication with another node
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct InstallationMaintenanceCCStatisticsReport {
+    #[builder(setter(into))]
+    pub node_id: NodeId,
+    #[builder(default)]
+    pub statistics: LinkStatistics,
+}
+
+impl CCBase for InstallationMaintenanceCCStatisticsReport {}
+
+impl CCId for InstallationMaintenanceCCStatisticsReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::StatisticsReport as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCStatisticsReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+        let statistics = LinkStatistics::parse(i)?;
+
+        Ok(Self {
+            node_id,
+            statistics,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCStatisticsReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.node_id.serialize(output, NodeIdType::NodeId8Bit);
+        self.statistics.serialize(output);
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCStatisticsReport {
+    fn to_log_payload(&self) -> LogPayload {
+        let statistics = &self.statistics;
+        let mut ret = LogPayloadDict::new().with_entry("node id", self.node_id.to_string());
+        if let Some(route_changes) = statistics.route_changes {
+            ret = ret.with_entry("route changes", route_changes);
+        }
+        if let Some(transmission_count) = statistics.transmission_count {
+            ret = ret.with_entry("transmission count", transmission_count);
+        }
+        if let Some(neighbors) = &statistics.neighbors {
+            let neighbors = neighbors
+                .iter()
+                .map(|neighbor| neighbor.node_id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            ret = ret.with_entry("neighbors", neighbors);
+        }
+        if let Some(packet_error_count) = statistics.packet_error_count {
+            ret = ret.with_entry("packet error count", packet_error_count);
+        }
+        if let Some(average) = statistics.average_transmission_time() {
+            ret = ret.with_entry(
+                "avg. transmission time",
+                format!("{} ms", average.as_millis()),
+            );
+        }
+        ret.into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct InstallationMaintenanceCCStatisticsClear {}
+
+impl CCBase for InstallationMaintenanceCCStatisticsClear {}
+
+impl CCId for InstallationMaintenanceCCStatisticsClear {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::StatisticsClear as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCStatisticsClear {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCStatisticsClear {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCStatisticsClear {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct InstallationMaintenanceCCRssiGet {}
+
+impl CCBase for InstallationMaintenanceCCRssiGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::InstallationMaintenanceCCRssiReport(_))
+    }
+}
+
+impl CCId for InstallationMaintenanceCCRssiGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::RssiGet as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCRssiGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCRssiGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCRssiGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+/// The background noise a node measures on each channel
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct InstallationMaintenanceCCRssiReport {
+    pub channels: Vec<RSSI>,
+}
+
+impl CCBase for InstallationMaintenanceCCRssiReport {}
+
+impl CCId for InstallationMaintenanceCCRssiReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(InstallationMaintenanceCCCommand::RssiReport as _)
+    }
+}
+
+impl CCParsable for InstallationMaintenanceCCRssiReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let channels = many_0(RSSI::parse).parse(i)?;
+
+        Ok(Self { channels })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for InstallationMaintenanceCCRssiReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        for rssi in &self.channels {
+            rssi.serialize(output);
+        }
+    }
+}
+
+impl ToLogPayload for InstallationMaintenanceCCRssiReport {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        for (channel, rssi) in self.channels.iter().enumerate() {
+            ret = ret.with_entry(format!("channel {}", channel), rssi.to_string());
+        }
+        ret.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn node_id() -> impl Strategy<Value = NodeId> {
+        (1u8..=232).prop_map(NodeId::new)
+    }
+
+    fn data_rate() -> impl Strategy<Value = DataRate> {
+        prop_oneof![
+            Just(DataRate::DataRate_9k6),
+            Just(DataRate::DataRate_40k),
+            Just(DataRate::DataRate_100k),
+        ]
+    }
+
+    fn repeaters() -> impl Strategy<Value = Vec<NodeId>> {
+        proptest::collection::vec(node_id(), 0..=MAX_REPEATERS)
+    }
+
+    fn rssi() -> impl Strategy<Value = RSSI> {
+        any::<i8>().prop_map(RSSI::from)
+    }
+
+    fn link_statistics() -> impl Strategy<Value = LinkStatistics> {
+        let neighbor =
+            (node_id(), any::<bool>(), data_rate()).prop_map(|(node_id, repeater, speed)| {
+                Neighbor {
+                    node_id,
+                    repeater,
+                    speed,
+                }
+            });
+        (
+            proptest::option::of(any::<u8>()),
+            proptest::option::of(any::<u8>()),
+            proptest::option::of(proptest::collection::vec(neighbor, 0..8)),
+            proptest::option::of(any::<u8>()),
+            proptest::option::of(any::<u32>()),
+            proptest::option::of(any::<u32>()),
+        )
+            .prop_map(
+                |(
+                    route_changes,
+                    transmission_count,
+                    neighbors,
+                    packet_error_count,
+                    transmission_time_sum,
+                    transmission_time_squared_sum,
+                )| LinkStatistics {
+                    route_changes,
+                    transmission_count,
+                    neighbors,
+                    packet_error_count,
+                    transmission_time_sum,
+                    transmission_time_squared_sum,
+                },
+            )
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCPriorityRouteSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy =
+                (node_id(), repeaters(), data_rate()).prop_map(|(node_id, repeaters, speed)| {
+                    Self {
+                        node_id,
+                        repeaters,
+                        speed,
+                    }
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCPriorityRouteGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(node_id().prop_map(|node_id| Self { node_id }).boxed())
+        }
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCPriorityRouteReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let kind = prop_oneof![
+                Just(RouteKind::None),
+                Just(RouteKind::LastWorkingRoute),
+                Just(RouteKind::NextToLastWorkingRoute),
+                Just(RouteKind::Application),
+            ];
+            let strategy = (node_id(), kind, repeaters(), data_rate()).prop_map(
+                |(node_id, kind, repeaters, speed)| Self {
+                    node_id,
+                    kind,
+                    repeaters,
+                    speed,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCStatisticsGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(node_id().prop_map(|node_id| Self { node_id }).boxed())
+        }
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCStatisticsReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (node_id(), link_statistics()).prop_map(|(node_id, statistics)| Self {
+                node_id,
+                statistics,
+            });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCStatisticsClear {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCRssiGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for InstallationMaintenanceCCRssiReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy =
+                proptest::collection::vec(rssi(), 0..=4).prop_map(|channels| Self { channels });
+            Some(strategy.boxed())
+        }
+    }
+
+    #[test]
+    fn test_statistics_report_skips_unknown_statistics() {
+        let mut raw = Bytes::from_static(&[
+            0x05, // node ID
+            0x01, 0x01, 0x0a, // transmission count
+            0x7f, 0x02, 0xff, 0xff, // unknown statistic
+            0x04, 0x04, 0x00, 0x00, 0x00, 0xc8, // sum of transmission times
+        ]);
+        let report =
+            InstallationMaintenanceCCStatisticsReport::parse(&mut raw, CCParsingContext::default())
+                .unwrap();
+        assert_eq!(report.node_id, NodeId::new(5u8));
+        assert_eq!(report.statistics.transmission_count, Some(10));
+        assert_eq!(report.statistics.route_changes, None);
+        assert_eq!(
+            report.statistics.average_transmission_time(),
+            Some(core::time::Duration::from_millis(20))
+        );
+    }
+}
@@ -70,7 +70,7 @@ fn test_device_id_value() {
     let value = ManufacturerSpecificCCValues::device_id();
     let value_id = ValueId::new(
         CommandClasses::ManufacturerSpecific,
-        0x03u32,
+        0x03u16,
         Some(DeviceIdType::SerialNumber as u32),
     );
     assert!(value.is(&value_id));
@@ -123,6 +123,33 @@ impl ManufacturerSpecificCCValues {
             .supports_endpoints(false)
             .min_version(2)
     );
+
+    /// The instances of values with a dynamic property, given the IDs of the reported values.
+    /// Which device IDs exist is only known once they are reported.
+    pub fn dynamic_values(reported: &[ValueId]) -> Vec<(CCValue, &'static CCValueOptions)> {
+        let device_id = Self::device_id();
+        reported
+            .iter()
+            .filter_map(|value_id| {
+                match ManufacturerSpecificCCProperties::try_from(ValueIdProperties::from(*value_id))
+                {
+                    Ok(ManufacturerSpecificCCProperties::DeviceId(device_id_type)) => {
+                        Some((device_id.eval((device_id_type,)), &device_id.options))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// The values with a static property, e.g. to create them before they are first reported
+    pub fn static_values() -> Vec<&'static StaticCCValue> {
+        vec![
+            Self::manufacturer_id(),
+            Self::product_type(),
+            Self::product_id(),
+        ]
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
@@ -287,12 +314,15 @@ impl CCId for ManufacturerSpecificCCDeviceSpecificGet {
 }
 
 impl CCParsable for ManufacturerSpecificCCDeviceSpecificGet {
-    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
-        let (_reserved73, device_id_type) = bits::bits((
+    fn parse(i: &mut Bytes, ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let (reserved73, device_id_type) = bits::bits((
             u5::parse,
             map_res(bits::take(3usize), |x: u8| DeviceIdType::try_from(x)),
         ))
         .parse(i)?;
+        if reserved73 != u5::new(0) {
+            ctx.tolerate(SpecDeviation::ReservedBitsSet)?;
+        }
 
         Ok(Self { device_id_type })
     }
@@ -348,12 +378,15 @@ impl CCId for ManufacturerSpecificCCDeviceSpecificReport {
 }
 
 impl CCParsable for ManufacturerSpecificCCDeviceSpecificReport {
-    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
-        let (_reserved73, device_id_type) = bits::bits((
+    fn parse(i: &mut Bytes, ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let (reserved73, device_id_type) = bits::bits((
             u5::parse,
             map_res(bits::take(3usize), |x: u8| DeviceIdType::try_from(x)),
         ))
         .parse(i)?;
+        if reserved73 != u5::new(0) {
+            ctx.tolerate(SpecDeviation::ReservedBitsSet)?;
+        }
         let (_data_format, data_len) = bits::bits((u3::parse, u5::parse)).parse(i)?;
         let device_id = take(u8::from(data_len)).parse(i)?;
 
@@ -365,8 +398,20 @@ impl CCParsable for ManufacturerSpecificCCDeviceSpecificReport {
 }
 
 impl SerializableWith<&CCEncodingContext> for ManufacturerSpecificCCDeviceSpecificReport {
-    fn serialize(&self, _output: &mut BytesMut, ctx: &CCEncodingContext) {
-        todo!("ERROR: ManufacturerSpecificCCDeviceSpecificReport::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, ctx: &CCEncodingContext) {
+        use serialize::{bits::bits, bytes::slice};
+
+        // The length is a 5-bit field
+        let device_id = &self.device_id[..self.device_id.len().min(0b11111)];
+        bits(move |bo| {
+            u5::new(0).write(bo);
+            u3::new(((self.device_id_type) as u8) & 0b0000_0111).write(bo);
+            // Device IDs are stored as raw buffers, so we always use the binary data format
+            u3::new(0x01).write(bo);
+            u5::new(device_id.len() as u8).write(bo);
+        })
+        .serialize(output);
+        slice(device_id).serialize(output);
     }
 }
 
@@ -378,3 +423,62 @@ impl ToLogPayload for ManufacturerSpecificCCDeviceSpecificReport {
             .into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn device_id_type() -> impl Strategy<Value = DeviceIdType> {
+        prop_oneof![
+            Just(DeviceIdType::FactoryDefault),
+            Just(DeviceIdType::SerialNumber),
+            Just(DeviceIdType::PseudoRandom)
+        ]
+    }
+
+    impl CCArbitrary for ManufacturerSpecificCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for ManufacturerSpecificCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy =
+                any::<(u16, u16, u16)>().prop_map(|(manufacturer_id, product_type, product_id)| {
+                    Self {
+                        manufacturer_id,
+                        product_type,
+                        product_id,
+                    }
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for ManufacturerSpecificCCDeviceSpecificGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(
+                device_id_type()
+                    .prop_map(|device_id_type| Self { device_id_type })
+                    .boxed(),
+            )
+        }
+    }
+
+    impl CCArbitrary for ManufacturerSpecificCCDeviceSpecificReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (
+                device_id_type(),
+                proptest::collection::vec(any::<u8>(), 0..=31),
+            )
+                .prop_map(|(device_id_type, device_id)| Self {
+                    device_id_type,
+                    device_id,
+                });
+            Some(strategy.boxed())
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use crate::prelude::*;
+use crate::values::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::cache::CacheValue;
+use zwave_core::parse::combinators::{map, opt};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_core::value_id::{ValueId, ValueIdProperties};
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+// FIXME: Create derive macro to implement
+// From<...> for ValueIdProperties and TryFrom<ValueIdProperties>
+// for static-only CC properties
+enum MultilevelSwitchCCProperties {
+    CurrentValue = 0x00,
+    TargetValue = 0x01,
+    Duration = 0x02,
+}
+
+impl From<MultilevelSwitchCCProperties> for ValueIdProperties {
+    fn from(val: MultilevelSwitchCCProperties) -> Self {
+        Self::new(val as u16, None)
+    }
+}
+
+impl TryFrom<ValueIdProperties> for MultilevelSwitchCCProperties {
+    type Error = ();
+
+    fn try_from(val: ValueIdProperties) -> Result<Self, Self::Error> {
+        match (Self::try_from(val.property() as u8), val.property_key()) {
+            (Ok(prop), None) => Ok(prop),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct MultilevelSwitchCCValues;
+impl MultilevelSwitchCCValues {
+    cc_value_static_property!(
+        MultilevelSwitch,
+        CurrentValue,
+        ValueMetadata::LevelReport(ValueMetadataCommon::default_readonly().label("Current value")),
+        CCValueOptions::default()
+    );
+
+    cc_value_static_property!(
+        MultilevelSwitch,
+        TargetValue,
+        ValueMetadata::LevelSet(
+            ValueMetadataCommon::default().label("Target value") // TODO: valueChangeOptions: ["transitionDuration"]
+        ),
+        CCValueOptions::default()
+    );
+
+    cc_value_static_property!(
+        MultilevelSwitch,
+        Duration,
+        ValueMetadata::DurationReport(
+            ValueMetadataCommon::default_readonly().label("Remaining duration"),
+        ),
+        CCValueOptions::default().min_version(2)
+    );
+
+    /// The values with a static property, e.g. to create them before they are first reported
+    pub fn static_values() -> Vec<&'static StaticCCValue> {
+        vec![
+            Self::current_value(),
+            Self::target_value(),
+            Self::duration(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum MultilevelSwitchCCCommand {
+    Set = 0x01,
+    Get = 0x02,
+    Report = 0x03,
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct MultilevelSwitchCCSet {
+    pub target_value: LevelSet,
+    #[builder(default, setter(into))]
+    pub duration: Option<DurationSet>,
+}
+
+impl CCBase for MultilevelSwitchCCSet {}
+
+impl CCId for MultilevelSwitchCCSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::MultilevelSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(MultilevelSwitchCCCommand::Set as _)
+    }
+}
+
+impl CCParsable for MultilevelSwitchCCSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let target_value = LevelSet::parse(i)?;
+        let duration = opt(DurationSet::parse).parse(i)?;
+
+        Ok(Self {
+            target_value,
+            duration,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for MultilevelSwitchCCSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::sequence::tuple;
+        tuple((self.target_value, self.duration)).serialize(output)
+    }
+}
+
+impl ToLogPayload for MultilevelSwitchCCSet {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret =
+            LogPayloadDict::new().with_entry("target value", self.target_value.to_string());
+
+        if let Some(duration) = self.duration {
+            ret = ret.with_entry("duration", duration.to_string());
+        }
+
+        ret.into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct MultilevelSwitchCCGet {}
+
+impl CCBase for MultilevelSwitchCCGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::MultilevelSwitchCCReport(_))
+    }
+}
+
+impl CCId for MultilevelSwitchCCGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::MultilevelSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(MultilevelSwitchCCCommand::Get as _)
+    }
+}
+
+impl CCParsable for MultilevelSwitchCCGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for MultilevelSwitchCCGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for MultilevelSwitchCCGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct MultilevelSwitchCCReport {
+    #[cc_value(MultilevelSwitchCCValues::current_value)]
+    pub current_value: LevelReport,
+    #[cc_value(MultilevelSwitchCCValues::target_value)]
+    pub target_value: Option<LevelReport>,
+    #[cc_value(MultilevelSwitchCCValues::duration)]
+    pub duration: Option<DurationReport>,
+}
+
+impl CCBase for MultilevelSwitchCCReport {}
+
+impl CCId for MultilevelSwitchCCReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::MultilevelSwitch
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(MultilevelSwitchCCCommand::Report as _)
+    }
+}
+
+impl CCParsable for MultilevelSwitchCCReport {
+    fn parse(i: &mut Bytes, ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let current_value = LevelReport::parse(i)?;
+        let (target_value, duration) = map(opt((LevelReport::parse, DurationReport::parse)), |x| {
+            x.unzip()
+        })
+        .parse(i)?;
+
+        // Some devices send the target value without the duration that must accompany it
+        let target_value = match target_value {
+            None if !i.is_empty() => {
+                ctx.tolerate(SpecDeviation::MissingDuration)?;
+                Some(LevelReport::parse(i)?)
+            }
+            target_value => target_value,
+        };
+
+        Ok(Self {
+            current_value,
+            target_value,
+            duration,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for MultilevelSwitchCCReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.current_value.serialize(output);
+
+        if let Some(target_value) = self.target_value {
+            target_value.serialize(output);
+            self.duration.unwrap_or_default().serialize(output);
+        }
+    }
+}
+
+impl ToLogPayload for MultilevelSwitchCCReport {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret =
+            LogPayloadDict::new().with_entry("current value", self.current_value.to_string());
+        if let Some(target_value) = self.target_value {
+            ret = ret.with_entry("target value", target_value.to_string());
+        }
+        if let Some(duration) = self.duration {
+            ret = ret.with_entry("duration", duration.to_string());
+        }
+
+        ret.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    impl CCArbitrary for MultilevelSwitchCCSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (level_set(), proptest::option::of(duration_set())).prop_map(
+                |(target_value, duration)| Self {
+                    target_value,
+                    duration,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for MultilevelSwitchCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for MultilevelSwitchCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            // The target value and duration are either both present or both missing
+            let strategy = (
+                level_report(),
+                proptest::option::of((level_report(), duration_report())),
+            )
+                .prop_map(|(current_value, target)| {
+                    let (target_value, duration) = target.unzip();
+                    Self {
+                        current_value,
+                        target_value,
+                        duration,
+                    }
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    #[test]
+    fn test_multilevel_switch_cc_values() {
+        let current_value = MultilevelSwitchCCValues::current_value();
+        assert!(current_value.is(&current_value.id));
+
+        let target_value = MultilevelSwitchCCValues::target_value();
+        assert!(target_value.is(&target_value.id));
+        assert!(!target_value.is(&current_value.id));
+
+        let duration = MultilevelSwitchCCValues::duration();
+        assert!(duration.is(&duration.id));
+    }
+}
@@ -39,3 +39,16 @@ impl ToLogPayload for NoOperationCC {
         LogPayload::empty()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    impl CCArbitrary for NoOperationCC {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+}
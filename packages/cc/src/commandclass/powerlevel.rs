@@ -0,0 +1,442 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{
+    bytes::{be_u8, be_u16},
+    combinators::map_res,
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum PowerlevelCCCommand {
+    Set = 0x01,
+    Get = 0x02,
+    Report = 0x03,
+    TestNodeSet = 0x04,
+    TestNodeGet = 0x05,
+    TestNodeReport = 0x06,
+}
+
+/// The power a node transmits with, relative to its normal power
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum NodePowerlevel {
+    #[default]
+    NormalPower = 0x00,
+    Minus1dBm = 0x01,
+    Minus2dBm = 0x02,
+    Minus3dBm = 0x03,
+    Minus4dBm = 0x04,
+    Minus5dBm = 0x05,
+    Minus6dBm = 0x06,
+    Minus7dBm = 0x07,
+    Minus8dBm = 0x08,
+    Minus9dBm = 0x09,
+}
+
+impl Display for NodePowerlevel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NodePowerlevel::NormalPower => write!(f, "normal power"),
+            reduced => write!(f, "-{} dBm", *reduced as u8),
+        }
+    }
+}
+
+impl NodePowerlevel {
+    fn parse(i: &mut Bytes) -> zwave_core::parse::ParseResult<Self> {
+        map_res(be_u8, Self::try_from).parse(i)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum PowerlevelTestStatus {
+    Failed = 0x00,
+    Success = 0x01,
+    InProgress = 0x02,
+}
+
+impl Display for PowerlevelTestStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PowerlevelTestStatus::Failed => write!(f, "failed"),
+            PowerlevelTestStatus::Success => write!(f, "success"),
+            PowerlevelTestStatus::InProgress => write!(f, "in progress"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct PowerlevelCCSet {
+    pub powerlevel: NodePowerlevel,
+    /// For how many seconds the powerlevel is used before the node returns to normal power
+    pub timeout: u8,
+}
+
+impl CCBase for PowerlevelCCSet {}
+
+impl CCId for PowerlevelCCSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Powerlevel
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(PowerlevelCCCommand::Set as _)
+    }
+}
+
+impl CCParsable for PowerlevelCCSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let powerlevel = NodePowerlevel::parse(i)?;
+        let timeout = be_u8(i)?;
+
+        Ok(Self {
+            powerlevel,
+            timeout,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for PowerlevelCCSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+        tuple((be_u8(self.powerlevel as u8), be_u8(self.timeout))).serialize(output)
+    }
+}
+
+impl ToLogPayload for PowerlevelCCSet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("powerlevel", self.powerlevel.to_string())
+            .with_entry("timeout", format!("{} seconds", self.timeout))
+            .into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct PowerlevelCCGet {}
+
+impl CCBase for PowerlevelCCGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::PowerlevelCCReport(_))
+    }
+}
+
+impl CCId for PowerlevelCCGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Powerlevel
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(PowerlevelCCCommand::Get as _)
+    }
+}
+
+impl CCParsable for PowerlevelCCGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for PowerlevelCCGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for PowerlevelCCGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct PowerlevelCCReport {
+    pub powerlevel: NodePowerlevel,
+    /// For how many more seconds the powerlevel is used. Meaningless at normal power.
+    pub timeout: u8,
+}
+
+impl CCBase for PowerlevelCCReport {}
+
+impl CCId for PowerlevelCCReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Powerlevel
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(PowerlevelCCCommand::Report as _)
+    }
+}
+
+impl CCParsable for PowerlevelCCReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let powerlevel = NodePowerlevel::parse(i)?;
+        let timeout = be_u8(i)?;
+
+        Ok(Self {
+            powerlevel,
+            timeout,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for PowerlevelCCReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+        tuple((be_u8(self.powerlevel as u8), be_u8(self.timeout))).serialize(output)
+    }
+}
+
+impl ToLogPayload for PowerlevelCCReport {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new().with_entry("powerlevel", self.powerlevel.to_string());
+        if self.powerlevel != NodePowerlevel::NormalPower {
+            ret = ret.with_entry("timeout", format!("{} seconds", self.timeout));
+        }
+        ret.into()
+    }
+}
+
+/// Instructs a node to send test frames to another node with the given powerlevel
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct PowerlevelCCTestNodeSet {
+    /// The node the test frames are sent to
+    #[builder(setter(into))]
+    pub test_node_id: NodeId,
+    pub powerlevel: NodePowerlevel,
+    pub test_frame_count: u16,
+}
+
+impl CCBase for PowerlevelCCTestNodeSet {}
+
+impl CCId for PowerlevelCCTestNodeSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Powerlevel
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(PowerlevelCCCommand::TestNodeSet as _)
+    }
+}
+
+impl CCParsable for PowerlevelCCTestNodeSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let test_node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+        let powerlevel = NodePowerlevel::parse(i)?;
+        let test_frame_count = be_u16(i)?;
+
+        Ok(Self {
+            test_node_id,
+            powerlevel,
+            test_frame_count,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for PowerlevelCCTestNodeSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::{be_u8, be_u16};
+        self.test_node_id.serialize(output, NodeIdType::NodeId8Bit);
+        be_u8(self.powerlevel as u8).serialize(output);
+        be_u16(self.test_frame_count).serialize(output);
+    }
+}
+
+impl ToLogPayload for PowerlevelCCTestNodeSet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("test node id", self.test_node_id.to_string())
+            .with_entry("powerlevel", self.powerlevel.to_string())
+            .with_entry("test frame count", self.test_frame_count)
+            .into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct PowerlevelCCTestNodeGet {}
+
+impl CCBase for PowerlevelCCTestNodeGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::PowerlevelCCTestNodeReport(_))
+    }
+}
+
+impl CCId for PowerlevelCCTestNodeGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Powerlevel
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(PowerlevelCCCommand::TestNodeGet as _)
+    }
+}
+
+impl CCParsable for PowerlevelCCTestNodeGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for PowerlevelCCTestNodeGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for PowerlevelCCTestNodeGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+/// The result of the last test started with [`PowerlevelCCTestNodeSet`]
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct PowerlevelCCTestNodeReport {
+    #[builder(setter(into))]
+    pub test_node_id: NodeId,
+    pub status: PowerlevelTestStatus,
+    /// How many test frames were acknowledged by the test node
+    pub acknowledged_frames: u16,
+}
+
+impl CCBase for PowerlevelCCTestNodeReport {}
+
+impl CCId for PowerlevelCCTestNodeReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Powerlevel
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(PowerlevelCCCommand::TestNodeReport as _)
+    }
+}
+
+impl CCParsable for PowerlevelCCTestNodeReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let test_node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+        let status = map_res(be_u8, PowerlevelTestStatus::try_from).parse(i)?;
+        let acknowledged_frames = be_u16(i)?;
+
+        Ok(Self {
+            test_node_id,
+            status,
+            acknowledged_frames,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for PowerlevelCCTestNodeReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::{be_u8, be_u16};
+        self.test_node_id.serialize(output, NodeIdType::NodeId8Bit);
+        be_u8(self.status as u8).serialize(output);
+        be_u16(self.acknowledged_frames).serialize(output);
+    }
+}
+
+impl ToLogPayload for PowerlevelCCTestNodeReport {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("test node id", self.test_node_id.to_string())
+            .with_entry("status", self.status.to_string())
+            .with_entry("acknowledged frames", self.acknowledged_frames)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn node_powerlevel() -> impl Strategy<Value = NodePowerlevel> {
+        (0u8..=9).prop_map(|level| NodePowerlevel::try_from(level).unwrap())
+    }
+
+    fn node_id() -> impl Strategy<Value = NodeId> {
+        (1u8..=232).prop_map(NodeId::new)
+    }
+
+    impl CCArbitrary for PowerlevelCCSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy =
+                (node_powerlevel(), any::<u8>()).prop_map(|(powerlevel, timeout)| Self {
+                    powerlevel,
+                    timeout,
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for PowerlevelCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for PowerlevelCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy =
+                (node_powerlevel(), any::<u8>()).prop_map(|(powerlevel, timeout)| Self {
+                    powerlevel,
+                    timeout,
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for PowerlevelCCTestNodeSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (node_id(), node_powerlevel(), any::<u16>()).prop_map(
+                |(test_node_id, powerlevel, test_frame_count)| Self {
+                    test_node_id,
+                    powerlevel,
+                    test_frame_count,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for PowerlevelCCTestNodeGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for PowerlevelCCTestNodeReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let status = prop_oneof![
+                Just(PowerlevelTestStatus::Failed),
+                Just(PowerlevelTestStatus::Success),
+                Just(PowerlevelTestStatus::InProgress),
+            ];
+            let strategy = (node_id(), status, any::<u16>()).prop_map(
+                |(test_node_id, status, acknowledged_frames)| Self {
+                    test_node_id,
+                    status,
+                    acknowledged_frames,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+}
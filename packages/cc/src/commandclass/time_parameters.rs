@@ -0,0 +1,245 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::bytes::{be_u8, be_u16};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum TimeParametersCCCommand {
+    Set = 0x01,
+    Get = 0x02,
+    Report = 0x03,
+}
+
+/// A date and time in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    pub year: u16,
+    /// 1-12
+    pub month: u8,
+    /// 1-31
+    pub day: u8,
+    /// 0-23
+    pub hour: u8,
+    /// 0-59
+    pub minute: u8,
+    /// 0-59
+    pub second: u8,
+}
+
+impl UtcDateTime {
+    fn parse(i: &mut Bytes) -> zwave_core::parse::ParseResult<Self> {
+        let year = be_u16(i)?;
+        let month = be_u8(i)?;
+        let day = be_u8(i)?;
+        let hour = be_u8(i)?;
+        let minute = be_u8(i)?;
+        let second = be_u8(i)?;
+
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    fn serialize(&self, output: &mut BytesMut) {
+        use serialize::{
+            bytes::{be_u8, be_u16},
+            sequence::tuple,
+        };
+        tuple((
+            be_u16(self.year),
+            be_u8(self.month),
+            be_u8(self.day),
+            be_u8(self.hour),
+            be_u8(self.minute),
+            be_u8(self.second),
+        ))
+        .serialize(output)
+    }
+}
+
+impl Display for UtcDateTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct TimeParametersCCSet {
+    pub date_and_time: UtcDateTime,
+}
+
+impl CCBase for TimeParametersCCSet {}
+
+impl CCId for TimeParametersCCSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TimeParameters
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TimeParametersCCCommand::Set as _)
+    }
+}
+
+impl CCParsable for TimeParametersCCSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let date_and_time = UtcDateTime::parse(i)?;
+
+        Ok(Self { date_and_time })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TimeParametersCCSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.date_and_time.serialize(output);
+    }
+}
+
+impl ToLogPayload for TimeParametersCCSet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("date and time", self.date_and_time.to_string())
+            .into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct TimeParametersCCGet {}
+
+impl CCBase for TimeParametersCCGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::TimeParametersCCReport(_))
+    }
+}
+
+impl CCId for TimeParametersCCGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TimeParameters
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TimeParametersCCCommand::Get as _)
+    }
+}
+
+impl CCParsable for TimeParametersCCGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TimeParametersCCGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for TimeParametersCCGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct TimeParametersCCReport {
+    pub date_and_time: UtcDateTime,
+}
+
+impl CCBase for TimeParametersCCReport {}
+
+impl CCId for TimeParametersCCReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TimeParameters
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TimeParametersCCCommand::Report as _)
+    }
+}
+
+impl CCParsable for TimeParametersCCReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let date_and_time = UtcDateTime::parse(i)?;
+
+        Ok(Self { date_and_time })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TimeParametersCCReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        self.date_and_time.serialize(output);
+    }
+}
+
+impl ToLogPayload for TimeParametersCCReport {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("date and time", self.date_and_time.to_string())
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn date_time() -> impl Strategy<Value = UtcDateTime> {
+        (
+            any::<u16>(),
+            1u8..=12,
+            1u8..=31,
+            0u8..=23,
+            0u8..=59,
+            0u8..=59,
+        )
+            .prop_map(|(year, month, day, hour, minute, second)| UtcDateTime {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+            })
+    }
+
+    impl CCArbitrary for TimeParametersCCSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = date_time().prop_map(|date_and_time| Self { date_and_time });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for TimeParametersCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for TimeParametersCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = date_time().prop_map(|date_and_time| Self { date_and_time });
+            Some(strategy.boxed())
+        }
+    }
+}
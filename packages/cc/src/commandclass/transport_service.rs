@@ -0,0 +1,521 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::checksum::crc16_incremental;
+use zwave_core::parse::{
+    bytes::{be_u8, be_u16, complete::take},
+    validate_checksum,
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_pal::prelude::*;
+
+/// The bytes a first segment adds to its part of the datagram:
+/// CC, command and datagram size, session ID and the checksum
+pub const FIRST_SEGMENT_OVERHEAD: usize = 6;
+/// The bytes a subsequent segment adds to its part of the datagram:
+/// CC, command and datagram size, session ID and datagram offset, and the checksum
+pub const SUBSEQUENT_SEGMENT_OVERHEAD: usize = 7;
+/// The largest datagram that can be transferred, limited by the 11 bit datagram size
+pub const MAX_DATAGRAM_SIZE: usize = 0x7ff;
+
+// The segment commands carry the upper 3 bits of the datagram size in the command byte.
+// CCRaw moves them to the start of the payload, so the commands can be matched as usual.
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum TransportServiceCCCommand {
+    FirstSegment = 0xc0,
+    SegmentComplete = 0xe8,
+    SegmentRequest = 0xc8,
+    SegmentWait = 0xf0,
+    SubsequentSegment = 0xe0,
+}
+
+/// Splits a serialized CC into segments whose frames are at most `max_payload` bytes long.
+/// Returns `None` if the datagram is too large to be transferred or the segments could not
+/// carry any data.
+pub fn segment_datagram(datagram: &[u8], session_id: u8, max_payload: usize) -> Option<Vec<CC>> {
+    if datagram.len() > MAX_DATAGRAM_SIZE || max_payload <= SUBSEQUENT_SEGMENT_OVERHEAD {
+        return None;
+    }
+    let datagram_size = datagram.len() as u16;
+    let session_id = session_id & 0x0f;
+
+    let (first, mut rest) =
+        datagram.split_at((max_payload - FIRST_SEGMENT_OVERHEAD).min(datagram.len()));
+    let mut segments = vec![CC::from(TransportServiceCCFirstSegment {
+        datagram_size,
+        session_id,
+        payload: Bytes::copy_from_slice(first),
+    })];
+    while !rest.is_empty() {
+        let datagram_offset = (datagram.len() - rest.len()) as u16;
+        let (segment, remaining) =
+            rest.split_at((max_payload - SUBSEQUENT_SEGMENT_OVERHEAD).min(rest.len()));
+        segments.push(CC::from(TransportServiceCCSubsequentSegment {
+            datagram_size,
+            session_id,
+            datagram_offset,
+            payload: Bytes::copy_from_slice(segment),
+        }));
+        rest = remaining;
+    }
+    Some(segments)
+}
+
+/// Parses the datagram size, whose upper bits are stored in the first payload byte
+fn parse_datagram_size(i: &mut Bytes) -> zwave_core::parse::ParseResult<u16> {
+    let upper = be_u8(i)? & 0x07;
+    let lower = be_u8(i)?;
+    Ok(((upper as u16) << 8) | lower as u16)
+}
+
+/// Parses the segment data and validates the checksum, which covers the entire segment
+fn parse_segment_data(i: &mut Bytes, header: &[u8]) -> zwave_core::parse::ParseResult<Bytes> {
+    let payload = take(i.len().saturating_sub(2)).parse(i)?;
+    let checksum = be_u16(i)?;
+
+    let expected_checksum = crc16_incremental()
+        .update(&[CommandClasses::TransportService as u8])
+        .update(header)
+        .update(&payload)
+        .get();
+    validate_checksum(
+        checksum == expected_checksum,
+        format!(
+            "checksum mismatch: expected {:#06x}, got {:#06x}",
+            expected_checksum, checksum
+        ),
+    )?;
+
+    Ok(payload)
+}
+
+/// Writes the segment data, followed by the checksum over the entire segment
+fn serialize_segment_data(output: &mut BytesMut, header: &[u8], payload: &[u8]) {
+    use serialize::bytes::{be_u16, slice};
+
+    let checksum = crc16_incremental()
+        .update(&[CommandClasses::TransportService as u8])
+        .update(header)
+        .update(payload)
+        .get();
+    slice(payload).serialize(output);
+    be_u16(checksum).serialize(output);
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct TransportServiceCCFirstSegment {
+    /// The size of the entire datagram, which is split across the segments
+    pub datagram_size: u16,
+    pub session_id: u8,
+    pub payload: Bytes,
+}
+
+impl TransportServiceCCFirstSegment {
+    /// The command byte and the other header fields that are covered by the checksum
+    fn header(&self) -> [u8; 3] {
+        let [upper, lower] = self.datagram_size.to_be_bytes();
+        [
+            TransportServiceCCCommand::FirstSegment as u8 | (upper & 0x07),
+            lower,
+            (self.session_id & 0x0f) << 4,
+        ]
+    }
+}
+
+impl CCBase for TransportServiceCCFirstSegment {}
+
+impl CCId for TransportServiceCCFirstSegment {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TransportService
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TransportServiceCCCommand::FirstSegment as _)
+    }
+}
+
+impl CCParsable for TransportServiceCCFirstSegment {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let datagram_size = parse_datagram_size(i)?;
+        let header_byte = be_u8(i)?;
+        let session_id = header_byte >> 4;
+        let has_extension = header_byte & 0b1000 != 0;
+        let mut header = vec![
+            TransportServiceCCCommand::FirstSegment as u8 | (datagram_size >> 8) as u8,
+            datagram_size as u8,
+            header_byte,
+        ];
+        if has_extension {
+            let len = be_u8(i)?;
+            let extension = take(len as usize).parse(i)?;
+            header.push(len);
+            header.extend_from_slice(&extension);
+        }
+        let payload = parse_segment_data(i, &header)?;
+
+        Ok(Self {
+            datagram_size,
+            session_id,
+            payload,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TransportServiceCCFirstSegment {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::{be_u8, slice};
+
+        let header = self.header();
+        // CCRaw merges the upper bits of the datagram size into the command byte
+        be_u8(header[0] & 0x07).serialize(output);
+        slice(&header[1..]).serialize(output);
+        serialize_segment_data(output, &header, &self.payload);
+    }
+}
+
+impl ToLogPayload for TransportServiceCCFirstSegment {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("session ID", self.session_id)
+            .with_entry("datagram size", self.datagram_size)
+            .with_entry(
+                "byte range",
+                format!("0...{}", self.payload.len().saturating_sub(1)),
+            )
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct TransportServiceCCSubsequentSegment {
+    /// The size of the entire datagram, which is split across the segments
+    pub datagram_size: u16,
+    pub session_id: u8,
+    /// Where this segment's data starts in the datagram
+    pub datagram_offset: u16,
+    pub payload: Bytes,
+}
+
+impl TransportServiceCCSubsequentSegment {
+    /// The command byte and the other header fields that are covered by the checksum
+    fn header(&self) -> [u8; 4] {
+        let [size_upper, size_lower] = self.datagram_size.to_be_bytes();
+        let [offset_upper, offset_lower] = self.datagram_offset.to_be_bytes();
+        [
+            TransportServiceCCCommand::SubsequentSegment as u8 | (size_upper & 0x07),
+            size_lower,
+            ((self.session_id & 0x0f) << 4) | (offset_upper & 0x07),
+            offset_lower,
+        ]
+    }
+}
+
+impl CCBase for TransportServiceCCSubsequentSegment {}
+
+impl CCId for TransportServiceCCSubsequentSegment {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TransportService
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TransportServiceCCCommand::SubsequentSegment as _)
+    }
+}
+
+impl CCParsable for TransportServiceCCSubsequentSegment {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let datagram_size = parse_datagram_size(i)?;
+        let header_byte = be_u8(i)?;
+        let offset_lower = be_u8(i)?;
+        let session_id = header_byte >> 4;
+        let has_extension = header_byte & 0b1000 != 0;
+        let datagram_offset = (((header_byte & 0x07) as u16) << 8) | offset_lower as u16;
+        let mut header = vec![
+            TransportServiceCCCommand::SubsequentSegment as u8 | (datagram_size >> 8) as u8,
+            datagram_size as u8,
+            header_byte,
+            offset_lower,
+        ];
+        if has_extension {
+            let len = be_u8(i)?;
+            let extension = take(len as usize).parse(i)?;
+            header.push(len);
+            header.extend_from_slice(&extension);
+        }
+        let payload = parse_segment_data(i, &header)?;
+
+        Ok(Self {
+            datagram_size,
+            session_id,
+            datagram_offset,
+            payload,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TransportServiceCCSubsequentSegment {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::{be_u8, slice};
+
+        let header = self.header();
+        // CCRaw merges the upper bits of the datagram size into the command byte
+        be_u8(header[0] & 0x07).serialize(output);
+        slice(&header[1..]).serialize(output);
+        serialize_segment_data(output, &header, &self.payload);
+    }
+}
+
+impl ToLogPayload for TransportServiceCCSubsequentSegment {
+    fn to_log_payload(&self) -> LogPayload {
+        let start = self.datagram_offset as usize;
+        let end = (start + self.payload.len()).saturating_sub(1);
+        LogPayloadDict::new()
+            .with_entry("session ID", self.session_id)
+            .with_entry("datagram size", self.datagram_size)
+            .with_entry("byte range", format!("{}...{}", start, end))
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct TransportServiceCCSegmentRequest {
+    pub session_id: u8,
+    /// Where the missing segment starts in the datagram
+    pub datagram_offset: u16,
+}
+
+impl CCBase for TransportServiceCCSegmentRequest {}
+
+impl CCId for TransportServiceCCSegmentRequest {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TransportService
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TransportServiceCCCommand::SegmentRequest as _)
+    }
+}
+
+impl CCParsable for TransportServiceCCSegmentRequest {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let header_byte = be_u8(i)?;
+        let offset_lower = be_u8(i)?;
+
+        Ok(Self {
+            session_id: header_byte >> 4,
+            datagram_offset: (((header_byte & 0x07) as u16) << 8) | offset_lower as u16,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TransportServiceCCSegmentRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+
+        let [offset_upper, offset_lower] = self.datagram_offset.to_be_bytes();
+        tuple((
+            be_u8(((self.session_id & 0x0f) << 4) | (offset_upper & 0x07)),
+            be_u8(offset_lower),
+        ))
+        .serialize(output);
+    }
+}
+
+impl ToLogPayload for TransportServiceCCSegmentRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("session ID", self.session_id)
+            .with_entry("offset", self.datagram_offset)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct TransportServiceCCSegmentComplete {
+    pub session_id: u8,
+}
+
+impl CCBase for TransportServiceCCSegmentComplete {}
+
+impl CCId for TransportServiceCCSegmentComplete {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TransportService
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TransportServiceCCCommand::SegmentComplete as _)
+    }
+}
+
+impl CCParsable for TransportServiceCCSegmentComplete {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let session_id = be_u8(i)? >> 4;
+
+        Ok(Self { session_id })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TransportServiceCCSegmentComplete {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8((self.session_id & 0x0f) << 4).serialize(output);
+    }
+}
+
+impl ToLogPayload for TransportServiceCCSegmentComplete {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("session ID", self.session_id)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct TransportServiceCCSegmentWait {
+    /// How many segments the receiver still has to process before it can accept new ones
+    pub pending_segments: u8,
+}
+
+impl CCBase for TransportServiceCCSegmentWait {}
+
+impl CCId for TransportServiceCCSegmentWait {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::TransportService
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(TransportServiceCCCommand::SegmentWait as _)
+    }
+}
+
+impl CCParsable for TransportServiceCCSegmentWait {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let pending_segments = be_u8(i)?;
+
+        Ok(Self { pending_segments })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for TransportServiceCCSegmentWait {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8(self.pending_segments).serialize(output);
+    }
+}
+
+impl ToLogPayload for TransportServiceCCSegmentWait {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("pending segments", self.pending_segments)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use crate::commandclass_raw::CCRaw;
+    use proptest::prelude::*;
+
+    fn payload() -> impl Strategy<Value = Bytes> {
+        prop::collection::vec(any::<u8>(), 1..40).prop_map(Bytes::from)
+    }
+
+    impl CCArbitrary for TransportServiceCCFirstSegment {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (0u16..=0x7ff, 0u8..=15, payload()).prop_map(
+                |(datagram_size, session_id, payload)| Self {
+                    datagram_size,
+                    session_id,
+                    payload,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for TransportServiceCCSubsequentSegment {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (0u16..=0x7ff, 0u8..=15, 0u16..=0x7ff, payload()).prop_map(
+                |(datagram_size, session_id, datagram_offset, payload)| Self {
+                    datagram_size,
+                    session_id,
+                    datagram_offset,
+                    payload,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for TransportServiceCCSegmentRequest {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy =
+                (0u8..=15, 0u16..=0x7ff).prop_map(|(session_id, datagram_offset)| Self {
+                    session_id,
+                    datagram_offset,
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for TransportServiceCCSegmentComplete {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(
+                (0u8..=15)
+                    .prop_map(|session_id| Self { session_id })
+                    .boxed(),
+            )
+        }
+    }
+
+    impl CCArbitrary for TransportServiceCCSegmentWait {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = any::<u8>().prop_map(|pending_segments| Self { pending_segments });
+            Some(strategy.boxed())
+        }
+    }
+
+    #[test]
+    fn test_segment_datagram() {
+        let datagram: Vec<u8> = (0..100).collect();
+        let segments = segment_datagram(&datagram, 0x12, 46).unwrap();
+        let ctx = CCEncodingContext::default();
+
+        let frames: Vec<_> = segments
+            .iter()
+            .map(|cc| cc.as_raw(&ctx).as_bytes())
+            .collect();
+        assert!(frames.iter().all(|frame| frame.len() <= 46));
+        // The upper bits of the datagram size are part of the command byte
+        assert_eq!(frames[0][..4], [0x55, 0xc0, 100, 0x20]);
+        assert_eq!(frames[1][..5], [0x55, 0xe0, 100, 0x20, 40]);
+
+        let mut reassembled = Vec::new();
+        for frame in frames {
+            let raw = CCRaw::parse(&mut frame.clone()).unwrap();
+            match CC::try_from_raw(raw, CCParsingContext::default()).unwrap() {
+                CC::TransportServiceCCFirstSegment(segment) => {
+                    reassembled.extend_from_slice(&segment.payload)
+                }
+                CC::TransportServiceCCSubsequentSegment(segment) => {
+                    assert_eq!(segment.datagram_offset as usize, reassembled.len());
+                    reassembled.extend_from_slice(&segment.payload)
+                }
+                other => panic!("unexpected CC {:?}", other),
+            }
+        }
+        assert_eq!(reassembled, datagram);
+    }
+
+    #[test]
+    fn test_segment_datagram_too_large() {
+        assert!(segment_datagram(&[0u8; MAX_DATAGRAM_SIZE + 1], 0, 46).is_none());
+    }
+}
@@ -251,6 +251,48 @@ impl VersionCCValues {
             .min_version(3)
             .supports_endpoints(false)
     );
+
+    /// The instances of values with a dynamic property, given the IDs of the reported values.
+    /// The firmware version of the Z-Wave chip always exists, the others once they are reported.
+    pub fn dynamic_values(reported: &[ValueId]) -> Vec<(CCValue, &'static CCValueOptions)> {
+        let firmware_version = Self::firmware_version();
+        let mut chips: Vec<u8> = reported
+            .iter()
+            .filter_map(
+                |value_id| match VersionCCProperties::try_from(ValueIdProperties::from(*value_id)) {
+                    Ok(VersionCCProperties::FirmwareVersion(chip)) => Some(chip),
+                    _ => None,
+                },
+            )
+            .collect();
+        chips.push(0);
+        chips.sort_unstable();
+        chips.dedup();
+
+        chips
+            .into_iter()
+            .map(|chip| (firmware_version.eval((chip,)), &firmware_version.options))
+            .collect()
+    }
+
+    /// The values with a static property, e.g. to create them before they are first reported
+    pub fn static_values() -> Vec<&'static StaticCCValue> {
+        vec![
+            Self::library_type(),
+            Self::protocol_version(),
+            Self::hardware_version(),
+            Self::supports_zwave_software_get(),
+            Self::sdk_version(),
+            Self::application_framework_api_version(),
+            Self::application_framework_build_number(),
+            Self::serial_api_version(),
+            Self::serial_api_build_number(),
+            Self::zwave_protocol_version(),
+            Self::zwave_protocol_build_number(),
+            Self::application_version(),
+            Self::application_build_number(),
+        ]
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
@@ -390,8 +432,39 @@ impl CCParsable for VersionCCReport {
 }
 
 impl SerializableWith<&CCEncodingContext> for VersionCCReport {
-    fn serialize(&self, _output: &mut BytesMut, ctx: &CCEncodingContext) {
-        todo!("ERROR: VersionCCReport::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        fn major_minor(version: Version) -> impl Serializable {
+            use serialize::sequence::tuple;
+            tuple((be_u8(version.major), be_u8(version.minor)))
+        }
+
+        self.library_type.serialize(output);
+        major_minor(self.protocol_version).serialize(output);
+
+        let (firmware_0_version, additional_firmware_versions) =
+            match self.firmware_versions.split_first() {
+                Some((first, rest)) => (*first, rest),
+                None => (
+                    Version {
+                        major: 0,
+                        minor: 0,
+                        patch: None,
+                    },
+                    &[][..],
+                ),
+            };
+        major_minor(firmware_0_version).serialize(output);
+
+        // Additional firmware versions can only be encoded after the hardware version
+        if self.hardware_version.is_some() || !additional_firmware_versions.is_empty() {
+            be_u8(self.hardware_version.unwrap_or_default()).serialize(output);
+            be_u8(additional_firmware_versions.len() as u8).serialize(output);
+            for version in additional_firmware_versions {
+                major_minor(*version).serialize(output);
+            }
+        }
     }
 }
 
@@ -418,7 +491,7 @@ impl ToLogPayload for VersionCCReport {
 
 #[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
 pub struct VersionCCCommandClassGet {
-    requested_cc: CommandClasses,
+    pub requested_cc: CommandClasses,
 }
 
 impl CCBase for VersionCCCommandClassGet {
@@ -763,8 +836,38 @@ impl CCParsable for VersionCCZWaveSoftwareReport {
 }
 
 impl SerializableWith<&CCEncodingContext> for VersionCCZWaveSoftwareReport {
-    fn serialize(&self, _output: &mut BytesMut, ctx: &CCEncodingContext) {
-        todo!("ERROR: VersionCCZWaveSoftwareReport::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, ctx: &CCEncodingContext) {
+        use serialize::{
+            bytes::{be_u8, be_u16},
+            sequence::tuple,
+        };
+
+        fn major_minor_patch(version: Version) -> impl Serializable {
+            tuple((
+                be_u8(version.major),
+                be_u8(version.minor),
+                be_u8(version.patch.unwrap_or_default()),
+            ))
+        }
+
+        fn opt_version_and_build_number(value: &Option<(Version, u16)>) -> impl Serializable {
+            // Missing versions are encoded as 0.0.0 with build number 0
+            let (version, build_number) = value.unwrap_or((
+                Version {
+                    major: 0,
+                    minor: 0,
+                    patch: Some(0),
+                },
+                0,
+            ));
+            tuple((major_minor_patch(version), be_u16(build_number)))
+        }
+
+        major_minor_patch(self.sdk_version).serialize(output);
+        opt_version_and_build_number(&self.application_framework_version).serialize(output);
+        opt_version_and_build_number(&self.host_interface_version).serialize(output);
+        opt_version_and_build_number(&self.zwave_protocol_version).serialize(output);
+        opt_version_and_build_number(&self.application_version).serialize(output);
     }
 }
 
@@ -795,3 +898,128 @@ impl ToLogPayload for VersionCCZWaveSoftwareReport {
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn opt_version_and_build_number() -> impl Strategy<Value = Option<(Version, u16)>> {
+        // Version 0.0.0 is used to encode missing versions
+        proptest::option::of((
+            version_major_minor_patch().prop_filter("0.0.0 means missing", |v| {
+                (v.major, v.minor, v.patch) != (0, 0, Some(0))
+            }),
+            any::<u16>(),
+        ))
+    }
+
+    impl CCArbitrary for VersionCCGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for VersionCCReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            // Additional firmware versions can only be present with a hardware version
+            let strategy = (
+                library_type(),
+                version_major_minor(),
+                version_major_minor(),
+                proptest::option::of((
+                    any::<u8>(),
+                    proptest::collection::vec(version_major_minor(), 0..4),
+                )),
+            )
+                .prop_map(
+                    |(library_type, protocol_version, firmware_0_version, additional)| {
+                        let (hardware_version, additional_firmware_versions) = additional.unzip();
+                        let mut firmware_versions = vec![firmware_0_version];
+                        firmware_versions.extend(additional_firmware_versions.unwrap_or_default());
+                        Self {
+                            library_type,
+                            protocol_version,
+                            firmware_versions,
+                            hardware_version,
+                        }
+                    },
+                );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for VersionCCCommandClassGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(
+                command_class()
+                    .prop_map(|requested_cc| Self { requested_cc })
+                    .boxed(),
+            )
+        }
+    }
+
+    impl CCArbitrary for VersionCCCommandClassReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy =
+                (command_class(), any::<u8>()).prop_map(|(requested_cc, version)| Self {
+                    requested_cc,
+                    version,
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for VersionCCCapabilitiesGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for VersionCCCapabilitiesReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(
+                any::<bool>()
+                    .prop_map(|supports_zwave_software_get| Self {
+                        supports_zwave_software_get,
+                    })
+                    .boxed(),
+            )
+        }
+    }
+
+    impl CCArbitrary for VersionCCZWaveSoftwareGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for VersionCCZWaveSoftwareReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (
+                version_major_minor_patch(),
+                opt_version_and_build_number(),
+                opt_version_and_build_number(),
+                opt_version_and_build_number(),
+                opt_version_and_build_number(),
+            )
+                .prop_map(
+                    |(
+                        sdk_version,
+                        application_framework_version,
+                        host_interface_version,
+                        zwave_protocol_version,
+                        application_version,
+                    )| Self {
+                        sdk_version,
+                        application_framework_version,
+                        host_interface_version,
+                        zwave_protocol_version,
+                        application_version,
+                    },
+                );
+            Some(strategy.boxed())
+        }
+    }
+}
@@ -0,0 +1,629 @@
+use zwave_pal::prelude::*;
+use crate::prelude::*;
+use crate::values::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::cache::CacheValue;
+use zwave_core::parse::{
+    bytes::{be_u8, be_u24},
+    combinators::{map, opt},
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+use zwave_core::value_id::{ValueId, ValueIdProperties};
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+enum WakeUpCCProperties {
+    WakeUpInterval = 0x00,
+    ControllerNodeId = 0x01,
+    MinWakeUpInterval = 0x02,
+    MaxWakeUpInterval = 0x03,
+    DefaultWakeUpInterval = 0x04,
+    WakeUpIntervalSteps = 0x05,
+    WakeUpOnDemandSupported = 0x06,
+}
+
+impl From<WakeUpCCProperties> for ValueIdProperties {
+    fn from(val: WakeUpCCProperties) -> Self {
+        Self::new(val as u16, None)
+    }
+}
+
+impl TryFrom<ValueIdProperties> for WakeUpCCProperties {
+    type Error = ();
+
+    fn try_from(val: ValueIdProperties) -> Result<Self, Self::Error> {
+        match (Self::try_from(val.property() as u8), val.property_key()) {
+            (Ok(prop), None) => Ok(prop),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The largest wakeup interval that can be expressed, in seconds
+pub const WAKE_UP_INTERVAL_MAX: u32 = 0xff_ffff;
+
+pub struct WakeUpCCValues;
+impl WakeUpCCValues {
+    cc_value_static_property!(
+        WakeUp,
+        WakeUpInterval,
+        ValueMetadata::Numeric(
+            ValueMetadataNumeric::default()
+                .label("Wake Up interval")
+                .min(0)
+                .max(WAKE_UP_INTERVAL_MAX as i64)
+                .unit("seconds")
+        ),
+        CCValueOptions::default().supports_endpoints(false)
+    );
+
+    cc_value_static_property!(
+        WakeUp,
+        ControllerNodeId,
+        ValueMetadata::Numeric(
+            ValueMetadataNumeric::default()
+                .readonly()
+                .label("Node ID of the controller")
+        ),
+        CCValueOptions::default().supports_endpoints(false)
+    );
+
+    cc_value_static_property!(
+        WakeUp,
+        MinWakeUpInterval,
+        ValueMetadata::Numeric(ValueMetadataNumeric::default().readonly()),
+        CCValueOptions::default()
+            .min_version(2)
+            .supports_endpoints(false)
+            .internal()
+    );
+
+    cc_value_static_property!(
+        WakeUp,
+        MaxWakeUpInterval,
+        ValueMetadata::Numeric(ValueMetadataNumeric::default().readonly()),
+        CCValueOptions::default()
+            .min_version(2)
+            .supports_endpoints(false)
+            .internal()
+    );
+
+    cc_value_static_property!(
+        WakeUp,
+        DefaultWakeUpInterval,
+        ValueMetadata::Numeric(ValueMetadataNumeric::default().readonly()),
+        CCValueOptions::default()
+            .min_version(2)
+            .supports_endpoints(false)
+            .internal()
+    );
+
+    cc_value_static_property!(
+        WakeUp,
+        WakeUpIntervalSteps,
+        ValueMetadata::Numeric(ValueMetadataNumeric::default().readonly()),
+        CCValueOptions::default()
+            .min_version(2)
+            .supports_endpoints(false)
+            .internal()
+    );
+
+    cc_value_static_property!(
+        WakeUp,
+        WakeUpOnDemandSupported,
+        ValueMetadata::Boolean(ValueMetadataBoolean::default().readonly()),
+        CCValueOptions::default()
+            .min_version(3)
+            .supports_endpoints(false)
+            .internal()
+    );
+
+    /// The values with a static property, e.g. to create them before they are first reported
+    pub fn static_values() -> Vec<&'static StaticCCValue> {
+        vec![
+            Self::wake_up_interval(),
+            Self::controller_node_id(),
+            Self::min_wake_up_interval(),
+            Self::max_wake_up_interval(),
+            Self::default_wake_up_interval(),
+            Self::wake_up_interval_steps(),
+            Self::wake_up_on_demand_supported(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum WakeUpCCCommand {
+    IntervalSet = 0x04,
+    IntervalGet = 0x05,
+    IntervalReport = 0x06,
+    Notification = 0x07,
+    NoMoreInformation = 0x08,
+    IntervalCapabilitiesGet = 0x09,
+    IntervalCapabilitiesReport = 0x0a,
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct WakeUpCCIntervalSet {
+    /// The wakeup interval in seconds. Only the lower 24 bits are used.
+    pub wake_up_interval: u32,
+    /// The node that is notified when the node wakes up
+    #[builder(setter(into))]
+    pub controller_node_id: NodeId,
+}
+
+impl CCBase for WakeUpCCIntervalSet {}
+
+impl CCId for WakeUpCCIntervalSet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(WakeUpCCCommand::IntervalSet as _)
+    }
+}
+
+impl CCParsable for WakeUpCCIntervalSet {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let wake_up_interval = be_u24(i)?;
+        let controller_node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+
+        Ok(Self {
+            wake_up_interval,
+            controller_node_id,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for WakeUpCCIntervalSet {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        serialize::bytes::be_u24(self.wake_up_interval).serialize(output);
+        self.controller_node_id
+            .serialize(output, NodeIdType::NodeId8Bit);
+    }
+}
+
+impl ToLogPayload for WakeUpCCIntervalSet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry(
+                "wake-up interval",
+                format!("{} seconds", self.wake_up_interval),
+            )
+            .with_entry("controller node id", self.controller_node_id.to_string())
+            .into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct WakeUpCCIntervalGet {}
+
+impl CCBase for WakeUpCCIntervalGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::WakeUpCCIntervalReport(_))
+    }
+}
+
+impl CCId for WakeUpCCIntervalGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(WakeUpCCCommand::IntervalGet as _)
+    }
+}
+
+impl CCParsable for WakeUpCCIntervalGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for WakeUpCCIntervalGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for WakeUpCCIntervalGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder)]
+pub struct WakeUpCCIntervalReport {
+    /// The wakeup interval in seconds
+    pub wake_up_interval: u32,
+    /// The node that is notified when the node wakes up
+    #[builder(setter(into))]
+    pub controller_node_id: NodeId,
+}
+
+impl CCBase for WakeUpCCIntervalReport {}
+
+impl CCValues for WakeUpCCIntervalReport {
+    fn to_values(&self) -> Vec<(ValueId, CacheValue)> {
+        vec![
+            (
+                WakeUpCCValues::wake_up_interval().id,
+                CacheValue::from(self.wake_up_interval),
+            ),
+            (
+                WakeUpCCValues::controller_node_id().id,
+                CacheValue::from(u16::from(self.controller_node_id)),
+            ),
+        ]
+    }
+}
+
+impl CCId for WakeUpCCIntervalReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(WakeUpCCCommand::IntervalReport as _)
+    }
+}
+
+impl CCParsable for WakeUpCCIntervalReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let wake_up_interval = be_u24(i)?;
+        let controller_node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+
+        Ok(Self {
+            wake_up_interval,
+            controller_node_id,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for WakeUpCCIntervalReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        serialize::bytes::be_u24(self.wake_up_interval).serialize(output);
+        self.controller_node_id
+            .serialize(output, NodeIdType::NodeId8Bit);
+    }
+}
+
+impl ToLogPayload for WakeUpCCIntervalReport {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry(
+                "wake-up interval",
+                format!("{} seconds", self.wake_up_interval),
+            )
+            .with_entry("controller node id", self.controller_node_id.to_string())
+            .into()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct WakeUpCCNotification {}
+
+impl CCBase for WakeUpCCNotification {}
+
+impl CCId for WakeUpCCNotification {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(WakeUpCCCommand::Notification as _)
+    }
+}
+
+impl CCParsable for WakeUpCCNotification {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for WakeUpCCNotification {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for WakeUpCCNotification {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct WakeUpCCNoMoreInformation {}
+
+impl CCBase for WakeUpCCNoMoreInformation {}
+
+impl CCId for WakeUpCCNoMoreInformation {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(WakeUpCCCommand::NoMoreInformation as _)
+    }
+}
+
+impl CCParsable for WakeUpCCNoMoreInformation {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for WakeUpCCNoMoreInformation {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for WakeUpCCNoMoreInformation {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CCValues)]
+pub struct WakeUpCCIntervalCapabilitiesGet {}
+
+impl CCBase for WakeUpCCIntervalCapabilitiesGet {
+    fn expects_response(&self) -> bool {
+        true
+    }
+
+    fn test_response(&self, response: &CC) -> bool {
+        matches!(response, CC::WakeUpCCIntervalCapabilitiesReport(_))
+    }
+}
+
+impl CCId for WakeUpCCIntervalCapabilitiesGet {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(WakeUpCCCommand::IntervalCapabilitiesGet as _)
+    }
+}
+
+impl CCParsable for WakeUpCCIntervalCapabilitiesGet {
+    fn parse(_i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for WakeUpCCIntervalCapabilitiesGet {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for WakeUpCCIntervalCapabilitiesGet {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct WakeUpCCIntervalCapabilitiesReport {
+    #[cc_value(WakeUpCCValues::min_wake_up_interval)]
+    pub min_wake_up_interval: u32,
+    #[cc_value(WakeUpCCValues::max_wake_up_interval)]
+    pub max_wake_up_interval: u32,
+    #[cc_value(WakeUpCCValues::default_wake_up_interval)]
+    pub default_wake_up_interval: u32,
+    #[cc_value(WakeUpCCValues::wake_up_interval_steps)]
+    pub wake_up_interval_steps: u32,
+    #[builder(default, setter(into))]
+    #[cc_value(WakeUpCCValues::wake_up_on_demand_supported)]
+    pub wake_up_on_demand_supported: Option<bool>,
+}
+
+impl WakeUpCCIntervalCapabilitiesReport {
+    /// Returns the supported interval closest to the given one
+    pub fn clamp_interval(&self, interval: u32) -> u32 {
+        let min = self.min_wake_up_interval;
+        let max = self.max_wake_up_interval.max(min);
+        let interval = interval.clamp(min, max);
+        if self.wake_up_interval_steps == 0 {
+            return interval;
+        }
+
+        // Round to the nearest step, without leaving the valid range
+        let steps = self.wake_up_interval_steps;
+        let rounded = min + (interval - min + steps / 2) / steps * steps;
+        if rounded > max {
+            rounded - steps
+        } else {
+            rounded
+        }
+    }
+}
+
+impl CCBase for WakeUpCCIntervalCapabilitiesReport {}
+
+impl CCId for WakeUpCCIntervalCapabilitiesReport {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(WakeUpCCCommand::IntervalCapabilitiesReport as _)
+    }
+}
+
+impl CCParsable for WakeUpCCIntervalCapabilitiesReport {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let min_wake_up_interval = be_u24(i)?;
+        let max_wake_up_interval = be_u24(i)?;
+        let default_wake_up_interval = be_u24(i)?;
+        let wake_up_interval_steps = be_u24(i)?;
+        // Version 3 adds a bit mask with the Wake Up On Demand support
+        let wake_up_on_demand_supported = opt(map(be_u8, |flags| flags & 0x01 != 0)).parse(i)?;
+
+        Ok(Self {
+            min_wake_up_interval,
+            max_wake_up_interval,
+            default_wake_up_interval,
+            wake_up_interval_steps,
+            wake_up_on_demand_supported,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for WakeUpCCIntervalCapabilitiesReport {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::{be_u8, be_u24};
+
+        be_u24(self.min_wake_up_interval).serialize(output);
+        be_u24(self.max_wake_up_interval).serialize(output);
+        be_u24(self.default_wake_up_interval).serialize(output);
+        be_u24(self.wake_up_interval_steps).serialize(output);
+        if let Some(on_demand) = self.wake_up_on_demand_supported {
+            be_u8(on_demand as u8).serialize(output);
+        }
+    }
+}
+
+impl ToLogPayload for WakeUpCCIntervalCapabilitiesReport {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new()
+            .with_entry(
+                "default interval",
+                format!("{} seconds", self.default_wake_up_interval),
+            )
+            .with_entry(
+                "minimum interval",
+                format!("{} seconds", self.min_wake_up_interval),
+            )
+            .with_entry(
+                "maximum interval",
+                format!("{} seconds", self.max_wake_up_interval),
+            )
+            .with_entry(
+                "interval steps",
+                format!("{} seconds", self.wake_up_interval_steps),
+            );
+        if let Some(on_demand) = self.wake_up_on_demand_supported {
+            ret = ret.with_entry("wake up on demand supported", on_demand);
+        }
+
+        ret.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn wake_up_interval() -> impl Strategy<Value = u32> {
+        0..=WAKE_UP_INTERVAL_MAX
+    }
+
+    fn node_id() -> impl Strategy<Value = NodeId> {
+        (1u8..=232).prop_map(NodeId::new)
+    }
+
+    impl CCArbitrary for WakeUpCCIntervalSet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (wake_up_interval(), node_id()).prop_map(
+                |(wake_up_interval, controller_node_id)| Self {
+                    wake_up_interval,
+                    controller_node_id,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for WakeUpCCIntervalGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for WakeUpCCIntervalReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (wake_up_interval(), node_id()).prop_map(
+                |(wake_up_interval, controller_node_id)| Self {
+                    wake_up_interval,
+                    controller_node_id,
+                },
+            );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for WakeUpCCNotification {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for WakeUpCCNoMoreInformation {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for WakeUpCCIntervalCapabilitiesGet {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            Some(Just(Self {}).boxed())
+        }
+    }
+
+    impl CCArbitrary for WakeUpCCIntervalCapabilitiesReport {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (
+                wake_up_interval(),
+                wake_up_interval(),
+                wake_up_interval(),
+                wake_up_interval(),
+                proptest::option::of(any::<bool>()),
+            )
+                .prop_map(|(min, max, default, steps, on_demand)| Self {
+                    min_wake_up_interval: min,
+                    max_wake_up_interval: max,
+                    default_wake_up_interval: default,
+                    wake_up_interval_steps: steps,
+                    wake_up_on_demand_supported: on_demand,
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    #[test]
+    fn test_clamp_interval() {
+        let capabilities = WakeUpCCIntervalCapabilitiesReport::builder()
+            .min_wake_up_interval(300)
+            .max_wake_up_interval(86400)
+            .default_wake_up_interval(3600)
+            .wake_up_interval_steps(60)
+            .build();
+
+        assert_eq!(capabilities.clamp_interval(3600), 3600);
+        assert_eq!(capabilities.clamp_interval(10), 300);
+        assert_eq!(capabilities.clamp_interval(100_000), 86400);
+        // Rounded to the nearest step
+        assert_eq!(capabilities.clamp_interval(3629), 3600);
+        assert_eq!(capabilities.clamp_interval(3630), 3660);
+    }
+}
@@ -0,0 +1,484 @@
+use zwave_pal::prelude::*;
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::{CCValues, TryFromRepr};
+use typed_builder::TypedBuilder;
+use ux::{u2, u5};
+use zwave_core::bitvec::build_bitmask;
+use zwave_core::parse::{
+    bits::{bits, bool},
+    bytes::{be_u8, be_u32},
+    combinators::map_res,
+    multi::{fixed_length_bitmask_u8, supported_and_controlled_cc_list},
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize::{self, Serializable};
+
+// The Z-Wave Protocol CC is used by the Z-Wave protocol itself and never reaches the host
+// through the Serial API. These commands can only appear in captured traffic, e.g. from a
+// Zniffer, and are decoded for diagnostic purposes. There is no API to send them.
+
+#[derive(Debug, Clone, Copy, PartialEq, TryFromRepr)]
+#[repr(u8)]
+pub enum ZWaveProtocolCCCommand {
+    NodeInformationFrame = 0x01,
+    AssignIDs = 0x03,
+    FindNodesInRange = 0x04,
+}
+
+/// How long a node needs to be woken up before it can receive the frames of a
+/// [`ZWaveProtocolCCFindNodesInRange`] command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum WakeUpTime {
+    None = 0x00,
+    WakeUp1000ms = 0x01,
+    WakeUp100ms = 0x02,
+}
+
+impl core::fmt::Display for WakeUpTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WakeUpTime::None => write!(f, "none"),
+            WakeUpTime::WakeUp1000ms => write!(f, "1000 ms"),
+            WakeUpTime::WakeUp100ms => write!(f, "100 ms"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ZWaveProtocolCCNodeInformationFrame {
+    pub protocol_data: NodeInformationProtocolData,
+    #[builder(default)]
+    pub supported_command_classes: Vec<CommandClasses>,
+    #[builder(default)]
+    pub controlled_command_classes: Vec<CommandClasses>,
+}
+
+impl CCBase for ZWaveProtocolCCNodeInformationFrame {}
+
+impl CCId for ZWaveProtocolCCNodeInformationFrame {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::ZWaveProtocol
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ZWaveProtocolCCCommand::NodeInformationFrame as _)
+    }
+}
+
+impl CCParsable for ZWaveProtocolCCNodeInformationFrame {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let protocol_data = NodeInformationProtocolData::parse(i)?;
+        // Unlike the NIF received through the Serial API, the CC list has no length prefix
+        let (supported_command_classes, controlled_command_classes) =
+            supported_and_controlled_cc_list(i)?;
+
+        Ok(Self {
+            protocol_data,
+            supported_command_classes,
+            controlled_command_classes,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ZWaveProtocolCCNodeInformationFrame {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        self.protocol_data.serialize(output);
+        for cc in &self.supported_command_classes {
+            cc.serialize(output);
+        }
+        if !self.controlled_command_classes.is_empty() {
+            be_u8(COMMAND_CLASS_SUPPORT_CONTROL_MARK).serialize(output);
+            for cc in &self.controlled_command_classes {
+                cc.serialize(output);
+            }
+        }
+    }
+}
+
+impl ToLogPayload for ZWaveProtocolCCNodeInformationFrame {
+    fn to_log_payload(&self) -> LogPayload {
+        let info = &self.protocol_data;
+        let mut ret = LogPayloadDict::new()
+            .with_entry("basic device class", info.basic_device_type.to_string())
+            .with_entry(
+                "generic device class",
+                format!("0x{:02x}", info.generic_device_class),
+            );
+
+        if let Some(specific) = info.specific_device_class {
+            ret = ret.with_entry("specific device class", format!("0x{:02x}", specific))
+        }
+
+        ret = ret
+            .with_entry("node type", info.node_type.to_string())
+            .with_entry("listening", info.listening)
+            .with_entry("can route", info.routing)
+            .with_entry("supports security", info.supports_security)
+            .with_entry("protocol version", info.protocol_version.to_string());
+
+        if !self.supported_command_classes.is_empty() {
+            ret = ret.with_entry(
+                "supported CCs",
+                self.supported_command_classes
+                    .iter()
+                    .map(|cc| cc.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+        }
+        if !self.controlled_command_classes.is_empty() {
+            ret = ret.with_entry(
+                "controlled CCs",
+                self.controlled_command_classes
+                    .iter()
+                    .map(|cc| cc.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+        }
+
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ZWaveProtocolCCAssignIDs {
+    /// The node ID assigned to the receiving node. 0 means the node is excluded.
+    #[builder(setter(into))]
+    pub new_node_id: NodeId,
+    #[builder(setter(into))]
+    pub home_id: Id32,
+}
+
+impl CCBase for ZWaveProtocolCCAssignIDs {}
+
+impl CCId for ZWaveProtocolCCAssignIDs {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::ZWaveProtocol
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ZWaveProtocolCCCommand::AssignIDs as _)
+    }
+}
+
+impl CCParsable for ZWaveProtocolCCAssignIDs {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let new_node_id = NodeId::parse(i, NodeIdType::NodeId8Bit)?;
+        let home_id = be_u32(i)?;
+
+        Ok(Self {
+            new_node_id,
+            home_id: home_id.into(),
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ZWaveProtocolCCAssignIDs {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::bytes::be_u32;
+
+        self.new_node_id.serialize(output, NodeIdType::NodeId8Bit);
+        be_u32(self.home_id.into()).serialize(output);
+    }
+}
+
+impl ToLogPayload for ZWaveProtocolCCAssignIDs {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("new node ID", self.new_node_id.to_string())
+            .with_entry("home ID", format!("0x{:08x}", self.home_id))
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CCValues)]
+pub struct ZWaveProtocolCCFindNodesInRange {
+    /// The nodes the receiving node should try to reach
+    pub candidate_node_ids: Vec<NodeId>,
+    #[builder(default = WakeUpTime::None)]
+    pub wake_up_time: WakeUpTime,
+    #[builder(default = DataRate::DataRate_9k6)]
+    pub data_rate: DataRate,
+}
+
+impl CCBase for ZWaveProtocolCCFindNodesInRange {}
+
+impl CCId for ZWaveProtocolCCFindNodesInRange {
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::ZWaveProtocol
+    }
+
+    fn cc_command(&self) -> Option<u8> {
+        Some(ZWaveProtocolCCCommand::FindNodesInRange as _)
+    }
+}
+
+impl CCParsable for ZWaveProtocolCCFindNodesInRange {
+    fn parse(i: &mut Bytes, _ctx: CCParsingContext) -> zwave_core::parse::ParseResult<Self> {
+        let (speed_present, _reserved, bitmask_len) =
+            bits((bool, u2::parse, u5::parse)).parse(i)?;
+        let candidate_node_ids = fixed_length_bitmask_u8(i, 1, u8::from(bitmask_len) as usize)?
+            .into_iter()
+            .map(NodeId::new)
+            .collect();
+
+        let data_rate =
+            |i: &mut Bytes| map_res(be_u8, |rate| DataRate::try_from(rate & 0b111)).parse(i);
+        let (wake_up_time, data_rate) = if speed_present {
+            // The wake up time is only present if the nodes need to be woken up first
+            match i.len() {
+                1 => (WakeUpTime::None, data_rate(i)?),
+                _ => (
+                    map_res(be_u8, WakeUpTime::try_from).parse(i)?,
+                    data_rate(i)?,
+                ),
+            }
+        } else {
+            (WakeUpTime::None, DataRate::DataRate_9k6)
+        };
+
+        Ok(Self {
+            candidate_node_ids,
+            wake_up_time,
+            data_rate,
+        })
+    }
+}
+
+impl SerializableWith<&CCEncodingContext> for ZWaveProtocolCCFindNodesInRange {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CCEncodingContext) {
+        use serialize::{
+            bits::bits,
+            bytes::{be_u8, slice},
+        };
+
+        let indizes = self
+            .candidate_node_ids
+            .iter()
+            .map(|node_id| u16::from(*node_id) as usize - 1)
+            .collect::<Vec<_>>();
+        let bit_len = indizes.iter().max().map_or(0, |max| max + 1);
+        let bitmask = build_bitmask(&indizes, bit_len);
+
+        let speed_present =
+            self.wake_up_time != WakeUpTime::None || self.data_rate != DataRate::DataRate_9k6;
+        bits(|bo| {
+            speed_present.write(bo);
+            u2::new(0).write(bo);
+            u5::new(bitmask.len() as u8).write(bo);
+        })
+        .serialize(output);
+        slice(&bitmask).serialize(output);
+
+        if self.wake_up_time != WakeUpTime::None {
+            be_u8(self.wake_up_time as u8).serialize(output);
+        }
+        if speed_present {
+            be_u8(self.data_rate as u8).serialize(output);
+        }
+    }
+}
+
+impl ToLogPayload for ZWaveProtocolCCFindNodesInRange {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry(
+                "candidate nodes",
+                self.candidate_node_ids
+                    .iter()
+                    .map(|node_id| node_id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .with_entry("wake up time", self.wake_up_time.to_string())
+            .with_entry("data rate", self.data_rate.to_string())
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::arbitrary::*;
+    use proptest::prelude::*;
+
+    fn node_id() -> impl Strategy<Value = NodeId> {
+        (1u8..=232).prop_map(NodeId::new)
+    }
+
+    fn data_rate() -> impl Strategy<Value = DataRate> {
+        prop_oneof![
+            Just(DataRate::DataRate_9k6),
+            Just(DataRate::DataRate_40k),
+            Just(DataRate::DataRate_100k)
+        ]
+    }
+
+    fn protocol_data() -> impl Strategy<Value = NodeInformationProtocolData> {
+        (
+            (any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>()),
+            (any::<bool>(), any::<bool>(), any::<bool>()),
+            prop_oneof![
+                Just(None),
+                Just(Some(Beam::Beam250ms)),
+                Just(Some(Beam::Beam1000ms))
+            ],
+            prop_oneof![
+                Just(ProtocolVersion::V2),
+                Just(ProtocolVersion::V5),
+                Just(ProtocolVersion::V6)
+            ],
+            (any::<bool>(), any::<bool>(), any::<bool>()),
+            (1u8..=4).prop_map(|t| BasicDeviceType::try_from(t).unwrap()),
+            any::<u8>(),
+            proptest::option::of(any::<u8>()),
+        )
+            .prop_map(
+                |(
+                    (listening, routing, optional_functionality, end_node),
+                    (supports_security, beaming, _),
+                    frequent_listening,
+                    protocol_version,
+                    (speed_100k, speed_40k, speed_9k6),
+                    basic_device_type,
+                    generic_device_class,
+                    specific_device_class,
+                )| {
+                    // Parsing returns the data rates from fastest to slowest
+                    let mut supported_data_rates = Vec::new();
+                    if speed_100k {
+                        supported_data_rates.push(DataRate::DataRate_100k);
+                    }
+                    if speed_40k {
+                        supported_data_rates.push(DataRate::DataRate_40k);
+                    }
+                    if speed_9k6 {
+                        supported_data_rates.push(DataRate::DataRate_9k6);
+                    }
+                    NodeInformationProtocolData {
+                        listening,
+                        frequent_listening,
+                        routing,
+                        supported_data_rates: supported_data_rates.into_iter().collect(),
+                        protocol_version,
+                        optional_functionality,
+                        node_type: if end_node {
+                            NodeType::EndNode
+                        } else {
+                            NodeType::Controller
+                        },
+                        supports_security,
+                        beaming,
+                        basic_device_type,
+                        generic_device_class,
+                        specific_device_class,
+                    }
+                },
+            )
+    }
+
+    impl CCArbitrary for ZWaveProtocolCCNodeInformationFrame {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (
+                protocol_data(),
+                proptest::collection::vec(command_class(), 0..4),
+                proptest::collection::vec(command_class(), 0..4),
+            )
+                .prop_map(
+                    |(protocol_data, supported_command_classes, controlled_command_classes)| Self {
+                        protocol_data,
+                        supported_command_classes,
+                        controlled_command_classes,
+                    },
+                );
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for ZWaveProtocolCCAssignIDs {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (any::<u8>(), any::<u32>()).prop_map(|(new_node_id, home_id)| Self {
+                new_node_id: NodeId::new(new_node_id),
+                home_id: home_id.into(),
+            });
+            Some(strategy.boxed())
+        }
+    }
+
+    impl CCArbitrary for ZWaveProtocolCCFindNodesInRange {
+        fn arbitrary(_: Option<BoxedStrategy<CC>>) -> Option<BoxedStrategy<Self>> {
+            let strategy = (
+                proptest::collection::btree_set(node_id(), 0..8),
+                prop_oneof![
+                    Just(WakeUpTime::None),
+                    Just(WakeUpTime::WakeUp1000ms),
+                    Just(WakeUpTime::WakeUp100ms)
+                ],
+                data_rate(),
+            )
+                .prop_map(|(candidate_node_ids, wake_up_time, data_rate)| Self {
+                    candidate_node_ids: candidate_node_ids.into_iter().collect(),
+                    wake_up_time,
+                    data_rate,
+                });
+            Some(strategy.boxed())
+        }
+    }
+
+    #[test]
+    fn test_parse_captured_frames() {
+        // A NIF of a routing end node (binary switch) supporting 3 CCs and controlling Basic
+        let raw = hex::decode("0101d39c0104100125725eef20").unwrap();
+        let cc = CCRaw::parse(&mut Bytes::from(raw))
+            .and_then(|raw| CC::try_from_raw(raw, CCParsingContext::default()))
+            .unwrap();
+        let CC::ZWaveProtocolCCNodeInformationFrame(nif) = cc else {
+            panic!("Unexpected CC: {:?}", cc);
+        };
+        assert!(nif.protocol_data.listening);
+        assert_eq!(nif.protocol_data.generic_device_class, 0x10);
+        assert_eq!(nif.protocol_data.specific_device_class, Some(0x01));
+        assert_eq!(
+            nif.supported_command_classes,
+            vec![
+                CommandClasses::BinarySwitch,
+                CommandClasses::ManufacturerSpecific,
+                CommandClasses::ZWavePlusInfo,
+            ]
+        );
+        assert_eq!(nif.controlled_command_classes, vec![CommandClasses::Basic]);
+
+        // Node 5 is assigned in home 0xcafebabe
+        let raw = hex::decode("010305cafebabe").unwrap();
+        let cc = CCRaw::parse(&mut Bytes::from(raw))
+            .and_then(|raw| CC::try_from_raw(raw, CCParsingContext::default()))
+            .unwrap();
+        assert_eq!(
+            cc,
+            ZWaveProtocolCCAssignIDs::builder()
+                .new_node_id(5u8)
+                .home_id(0xcafebabe)
+                .build()
+                .into()
+        );
+
+        // Find nodes 1, 2 and 10 at 40 kbit/s
+        let raw = hex::decode("010482030202").unwrap();
+        let cc = CCRaw::parse(&mut Bytes::from(raw))
+            .and_then(|raw| CC::try_from_raw(raw, CCParsingContext::default()))
+            .unwrap();
+        assert_eq!(
+            cc,
+            ZWaveProtocolCCFindNodesInRange::builder()
+                .candidate_node_ids(vec![NodeId::new(1u8), NodeId::new(2u8), NodeId::new(10u8)])
+                .data_rate(DataRate::DataRate_40k)
+                .build()
+                .into()
+        );
+    }
+}
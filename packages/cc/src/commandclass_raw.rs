@@ -24,6 +24,19 @@ impl Parsable for CCRaw {
         };
         let payload = rest(i)?;
 
+        // The Transport Service segments store the upper bits of the datagram size in the
+        // command byte. Move them to the payload, so the command can be identified.
+        if let Some(command) = cc_command.filter(|cmd| is_segment_command(cc_id, *cmd)) {
+            let mut with_size = BytesMut::with_capacity(payload.len() + 1);
+            with_size.extend_from_slice(&[command & 0x07]);
+            with_size.extend_from_slice(&payload);
+            return Ok(Self {
+                cc_id,
+                cc_command: Some(command & 0xf8),
+                payload: with_size.freeze(),
+            });
+        }
+
         Ok(Self {
             cc_id,
             cc_command,
@@ -32,12 +45,30 @@ impl Parsable for CCRaw {
     }
 }
 
+/// Whether the given command is a Transport Service segment,
+/// which carries part of the datagram size in its lower 3 bits
+fn is_segment_command(cc_id: CommandClasses, cc_command: u8) -> bool {
+    cc_id == CommandClasses::TransportService && matches!(cc_command & 0xf8, 0xc0 | 0xe0)
+}
+
 impl Serializable for CCRaw {
     fn serialize(&self, output: &mut BytesMut) {
         use serialize::{
             bytes::{be_u8, empty, slice},
             sequence::tuple,
         };
+
+        if let Some(command) = self.cc_command.filter(|cmd| is_segment_command(self.cc_id, *cmd)) {
+            if let Some((size_upper, payload)) = self.payload.split_first() {
+                return tuple((
+                    self.cc_id,
+                    be_u8(command | (size_upper & 0x07)),
+                    slice(payload),
+                ))
+                .serialize(output);
+            }
+        }
+
         tuple((
             self.cc_id,
             move |out: &mut BytesMut| match self.cc_command {
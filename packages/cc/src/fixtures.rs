@@ -0,0 +1,173 @@
+//! Regression tests for CCs captured from real devices.
+//!
+//! The captured bytes live in the `fixtures` directory of this crate, one file per device.
+//! See its README for the file format and how to add fixtures.
+
+use crate::commandclass::basic::{BasicCCReport, BasicCCValues};
+use crate::commandclass::{
+    BinarySwitchCCReport, BinarySwitchCCValues, ManufacturerSpecificCCReport,
+    ManufacturerSpecificCCValues, MultilevelSwitchCCReport, MultilevelSwitchCCValues,
+    VersionCCReport, VersionCCValues, WakeUpCCIntervalReport, WakeUpCCNotification, WakeUpCCValues,
+};
+use crate::prelude::*;
+use crate::test_vectors;
+use zwave_core::cache::CacheValue;
+use zwave_core::prelude::*;
+use zwave_core::value_id::ValueId;
+
+/// Declares a test for each of the given fixtures in a fixture file, which asserts that the
+/// captured bytes are parsed as the given CC, that the CC is serialized to the same bytes and,
+/// if given, which values the CC contains.
+macro_rules! cc_fixture_tests {
+    ($file:literal => {
+        $(
+            $name:ident: $cc:expr
+            $(, values: [$($value_id:expr => $value:expr),* $(,)?])?
+        );* $(;)?
+    }) => {
+        $(
+            #[test]
+            fn $name() {
+                let fixtures =
+                    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/", $file));
+                $crate::fixtures::assert_cc_fixture(
+                    fixtures,
+                    stringify!($name),
+                    CC::from($cc),
+                    cc_fixture_tests!(@values $([$($value_id => $value),*])?),
+                );
+            }
+        )*
+    };
+
+    (@values) => {
+        None
+    };
+    (@values [$($value_id:expr => $value:expr),*]) => {
+        Some(vec![$(($value_id, CacheValue::from($value))),*])
+    };
+}
+
+/// Returns the hex-encoded bytes of the fixture with the given name from the contents of a
+/// fixture file
+fn fixture_hex(fixtures: &str, name: &str) -> String {
+    fixtures
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .find(|(fixture_name, _)| fixture_name.trim() == name)
+        .map(|(_, hex)| hex.split_whitespace().collect::<String>())
+        .unwrap_or_else(|| panic!("fixture {} does not exist", name))
+}
+
+fn assert_cc_fixture(
+    fixtures: &str,
+    name: &str,
+    expected: CC,
+    values: Option<Vec<(ValueId, CacheValue)>>,
+) {
+    let hex = fixture_hex(fixtures, name);
+
+    test_vectors::assert_parses(&hex, expected.clone());
+    test_vectors::assert_serializes(expected.clone(), &hex);
+
+    if let Some(values) = values {
+        assert_eq!(
+            expected.to_values(),
+            values,
+            "fixture {} has unexpected values",
+            name
+        );
+    }
+}
+
+mod fibaro_fgs213 {
+    use super::*;
+
+    cc_fixture_tests!("fibaro_fgs213.txt" => {
+        manufacturer_specific_report: ManufacturerSpecificCCReport {
+            manufacturer_id: 0x010f,
+            product_type: 0x0403,
+            product_id: 0x1000,
+        },
+        values: [
+            ManufacturerSpecificCCValues::manufacturer_id().id => 0x010fu16,
+            ManufacturerSpecificCCValues::product_type().id => 0x0403u16,
+            ManufacturerSpecificCCValues::product_id().id => 0x1000u16,
+        ];
+
+        version_report: VersionCCReport {
+            library_type: ZWaveLibraryType::EnhancedSlave,
+            protocol_version: Version { major: 4, minor: 5, patch: None },
+            firmware_versions: vec![Version { major: 3, minor: 3, patch: None }],
+            hardware_version: None,
+        },
+        values: [
+            VersionCCValues::library_type().id => ZWaveLibraryType::EnhancedSlave as u8,
+            VersionCCValues::protocol_version().id => String::from("4.5"),
+            VersionCCValues::firmware_version().eval((0,)).id => String::from("3.3"),
+        ];
+
+        binary_switch_report: BinarySwitchCCReport {
+            current_value: BinaryReport::On,
+            target_value: None,
+            duration: None,
+        },
+        values: [BinarySwitchCCValues::current_value().id => BinaryReport::On];
+    });
+}
+
+mod aeotec_zw100 {
+    use super::*;
+
+    cc_fixture_tests!("aeotec_zw100.txt" => {
+        manufacturer_specific_report: ManufacturerSpecificCCReport {
+            manufacturer_id: 0x0086,
+            product_type: 0x0002,
+            product_id: 0x0064,
+        };
+
+        wake_up_notification: WakeUpCCNotification {},
+        values: [];
+
+        wake_up_interval_report: WakeUpCCIntervalReport {
+            wake_up_interval: 3600,
+            controller_node_id: NodeId::new(1u8),
+        },
+        values: [
+            WakeUpCCValues::wake_up_interval().id => 3600u32,
+            WakeUpCCValues::controller_node_id().id => 1u16,
+        ];
+    });
+}
+
+mod qubino_zmnhdd {
+    use super::*;
+
+    cc_fixture_tests!("qubino_zmnhdd.txt" => {
+        manufacturer_specific_report: ManufacturerSpecificCCReport {
+            manufacturer_id: 0x0159,
+            product_type: 0x0001,
+            product_id: 0x0051,
+        };
+
+        multilevel_switch_report: MultilevelSwitchCCReport {
+            current_value: LevelReport::Level(50),
+            target_value: Some(LevelReport::Level(50)),
+            duration: Some(DurationReport::Seconds(0)),
+        },
+        values: [
+            MultilevelSwitchCCValues::current_value().id => LevelReport::Level(50),
+            MultilevelSwitchCCValues::target_value().id => LevelReport::Level(50),
+            MultilevelSwitchCCValues::duration().id => DurationReport::Seconds(0),
+        ];
+
+        basic_report: BasicCCReport {
+            current_value: LevelReport::Level(50),
+            target_value: None,
+            duration: None,
+        },
+        values: [BasicCCValues::current_value().id => LevelReport::Level(50)];
+    });
+}
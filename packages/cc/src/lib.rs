@@ -1,7 +1,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(test)]
+mod arbitrary;
 mod cc_sequence;
 pub mod commandclass;
+#[cfg(test)]
+mod fixtures;
 pub mod commandclass_raw;
 pub mod prelude;
+pub mod spec_deviation;
+#[cfg(test)]
+mod test_vectors;
 pub mod values;
@@ -1,5 +1,6 @@
 pub use crate::commandclass::{
-    CC, CCAddress, CCAddressable, CCBase, CCEncodingContext, CCId, CCInfo, CCParsable,
-    CCParsingContext, CCValues, Destination, WithAddress,
+    CC, CCAddress, CCAddressable, CCBase, CCEncodingContext, CCEncodingError, CCId, CCInfo,
+    CCParsable, CCParsingContext, CCValues, Destination, SecurityManagers, WithAddress,
 };
 pub use crate::commandclass_raw::CCRaw;
+pub use crate::spec_deviation::{ParsingStrictness, SpecDeviation};
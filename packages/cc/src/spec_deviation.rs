@@ -0,0 +1,65 @@
+use core::fmt::Display;
+use zwave_pal::prelude::*;
+use zwave_pal::sync::Locked;
+
+/// How strictly received CCs are checked against the specification
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParsingStrictness {
+    /// Reject CCs that deviate from the specification, e.g. for certification testing
+    Strict,
+    /// Accept CCs with known deviations from the specification, but record them
+    #[default]
+    Lenient,
+}
+
+/// A known way in which devices deviate from the specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SpecDeviation {
+    /// Bits that are reserved by the specification were set
+    ReservedBitsSet,
+    /// A report contains a target value without the duration that must accompany it
+    MissingDuration,
+}
+
+impl SpecDeviation {
+    /// A stable code that identifies the deviation in logs and statistics
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ReservedBitsSet => "reserved-bits-set",
+            Self::MissingDuration => "missing-duration",
+        }
+    }
+}
+
+impl Display for SpecDeviation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let description = match self {
+            Self::ReservedBitsSet => "reserved bits are set",
+            Self::MissingDuration => "the target value is not followed by a duration",
+        };
+        write!(f, "{} ({})", description, self.code())
+    }
+}
+
+/// The deviations that were tolerated while parsing a CC.
+/// Clones share the same list, so deviations can be collected
+/// from every parser the parsing context is passed to.
+#[derive(Clone)]
+pub struct ToleratedDeviations(Arc<Locked<Vec<SpecDeviation>>>);
+
+impl Default for ToleratedDeviations {
+    fn default() -> Self {
+        Self(Arc::new(Locked::new(Vec::new())))
+    }
+}
+
+impl ToleratedDeviations {
+    pub(crate) fn push(&self, deviation: SpecDeviation) {
+        self.0.update(|deviations| deviations.push(deviation));
+    }
+
+    /// Returns the tolerated deviations and clears the list
+    pub fn take(&self) -> Vec<SpecDeviation> {
+        self.0.update(core::mem::take)
+    }
+}
@@ -0,0 +1,97 @@
+//! Test vectors for the CC implementations.
+//!
+//! Each CC module should declare vectors for its commands with [`cc_test_vectors!`]. Unlike the
+//! [fixtures](crate::fixtures), which were captured from real devices, these are written by hand,
+//! e.g. from the examples in the specification.
+
+use crate::prelude::*;
+use bytes::Bytes;
+use zwave_core::log::NormalizeLogPayload;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+/// Declares a module for each test vector with tests asserting that the hex-encoded bytes are
+/// parsed as the given CC, that the CC is serialized to the same bytes and, if given,
+/// which lines the CC is logged as.
+///
+/// ```ignore
+/// cc_test_vectors! {
+///     set_on: "2501ff" <=> BinarySwitchCCSet { target_value: BinarySet::On, duration: None },
+///     log: ["target value: on"];
+/// }
+/// ```
+macro_rules! cc_test_vectors {
+    (
+        $(
+            $name:ident: $hex:literal <=> $cc:expr
+            $(, log: [$($line:literal),* $(,)?])?
+        );* $(;)?
+    ) => {
+        $(
+            mod $name {
+                use super::*;
+
+                #[test]
+                fn parse() {
+                    $crate::test_vectors::assert_parses($hex, CC::from($cc));
+                }
+
+                #[test]
+                fn serialize() {
+                    $crate::test_vectors::assert_serializes(CC::from($cc), $hex);
+                }
+
+                #[test]
+                fn log() {
+                    $crate::test_vectors::assert_logs(
+                        CC::from($cc),
+                        $crate::test_vectors::cc_test_vectors!(@log $([$($line),*])?),
+                    );
+                }
+            }
+        )*
+    };
+
+    (@log) => {
+        None
+    };
+    (@log [$($line:literal),*]) => {
+        Some::<&[&str]>(&[$($line),*])
+    };
+}
+
+pub(crate) use cc_test_vectors;
+
+fn decode(hex: &str) -> Bytes {
+    hex::decode(hex)
+        .unwrap_or_else(|e| panic!("test vector {} is not valid hex: {}", hex, e))
+        .into()
+}
+
+pub(crate) fn assert_parses(hex: &str, expected: CC) {
+    let parsed = CCRaw::parse(&mut decode(hex))
+        .and_then(|raw| CC::try_from_raw(raw, CCParsingContext::default()));
+    assert_eq!(parsed, Ok(expected), "{} was parsed incorrectly", hex);
+}
+
+pub(crate) fn assert_serializes(cc: CC, hex: &str) {
+    let serialized = cc
+        .try_as_raw(&CCEncodingContext::default())
+        .unwrap_or_else(|e| panic!("{:?} could not be serialized: {}", cc, e))
+        .as_bytes();
+    assert_eq!(
+        hex::encode(&serialized),
+        hex.to_lowercase(),
+        "{:?} was serialized incorrectly",
+        cc
+    );
+}
+
+pub(crate) fn assert_logs(cc: CC, expected: Option<&[&str]>) {
+    // Even without expected lines, this catches panics while formatting the log
+    let logged = cc.to_log_payload().normalize(0);
+    if let Some(expected) = expected {
+        let lines: Vec<&str> = logged.lines.iter().map(|line| line.as_ref()).collect();
+        assert_eq!(lines, expected, "{:?} was logged incorrectly", cc);
+    }
+}
@@ -1,3 +1,4 @@
+use zwave_core::definitions::EndpointIndex;
 use zwave_core::value_id::ValueId;
 use zwave_pal::prelude::*;
 
@@ -75,15 +76,101 @@ pub enum ValueMetadata {
     BinarySet(ValueMetadataCommon<()>),
     // ...the BinaryReport, which has a defined "unknown" state
     BinaryReport(ValueMetadataCommon<()>),
-    // TODO: Configuration
+
+    // Configuration parameters combine metadata from multiple sources
+    Configuration(ValueMetadataConfiguration),
 }
 
 impl ValueMetadata {
     pub fn duration_set(common: ValueMetadataCommon<()>) -> Self {
         Self::DurationSet(common)
     }
+
+    /// Whether the value represents a state (`true`) or a notification/event (`false`)
+    pub fn is_stateful(&self) -> bool {
+        match self {
+            Self::Numeric(m) => m.common.stateful,
+            Self::Boolean(m) => m.common.stateful,
+            Self::String(m) => m.common.stateful,
+            Self::Buffer(m) => m.common.stateful,
+            Self::DurationSet(common) | Self::DurationReport(common) => common.stateful,
+            Self::LevelSet(common) | Self::LevelReport(common) => common.stateful,
+            Self::BinarySet(common) | Self::BinaryReport(common) => common.stateful,
+            Self::Configuration(m) => m.numeric.common.stateful,
+        }
+    }
+
+    pub(crate) fn set_stateful(&mut self, stateful: bool) {
+        match self {
+            Self::Numeric(m) => m.common.stateful = stateful,
+            Self::Boolean(m) => m.common.stateful = stateful,
+            Self::String(m) => m.common.stateful = stateful,
+            Self::Buffer(m) => m.common.stateful = stateful,
+            Self::DurationSet(common) | Self::DurationReport(common) => common.stateful = stateful,
+            Self::LevelSet(common) | Self::LevelReport(common) => common.stateful = stateful,
+            Self::BinarySet(common) | Self::BinaryReport(common) => common.stateful = stateful,
+            Self::Configuration(m) => m.numeric.common.stateful = stateful,
+        }
+    }
+
+    pub(crate) fn set_message_key(&mut self, message_key: &'static str) {
+        match self {
+            Self::Numeric(m) => m.common.message_key = Some(message_key),
+            Self::Boolean(m) => m.common.message_key = Some(message_key),
+            Self::String(m) => m.common.message_key = Some(message_key),
+            Self::Buffer(m) => m.common.message_key = Some(message_key),
+            Self::DurationSet(common) | Self::DurationReport(common) => {
+                common.message_key = Some(message_key)
+            }
+            Self::LevelSet(common) | Self::LevelReport(common) => {
+                common.message_key = Some(message_key)
+            }
+            Self::BinarySet(common) | Self::BinaryReport(common) => {
+                common.message_key = Some(message_key)
+            }
+            Self::Configuration(m) => m.numeric.common.message_key = Some(message_key),
+        }
+    }
+
+    /// Returns a copy of this metadata with the label, description and state names
+    /// resolved through the given translator
+    pub fn translated(&self, translator: &dyn MetadataTranslator) -> Self {
+        let mut ret = self.clone();
+        match &mut ret {
+            Self::Numeric(m) => m.common.translate(translator),
+            Self::Boolean(m) => m.common.translate(translator),
+            Self::String(m) => m.common.translate(translator),
+            Self::Buffer(m) => m.common.translate(translator),
+            Self::DurationSet(common) | Self::DurationReport(common) => {
+                common.translate(translator)
+            }
+            Self::LevelSet(common) | Self::LevelReport(common) => common.translate(translator),
+            Self::BinarySet(common) | Self::BinaryReport(common) => common.translate(translator),
+            Self::Configuration(m) => m.numeric.common.translate(translator),
+        }
+        ret
+    }
+}
+
+/// Resolves the human-readable texts of value metadata, so applications can localize them.
+///
+/// The texts are identified by stable message keys, which are derived from the key of the value,
+/// e.g. `BinarySwitch.CurrentValue.label`, `BinarySwitch.CurrentValue.description` or
+/// `BinarySwitch.CurrentValue.states.true` for the name of a state.
+pub trait MetadataTranslator {
+    /// Returns the translation of the text with the given message key, or `None` to keep the
+    /// default (English) text
+    fn translate(&self, key: &str, text: &str) -> Option<Cow<'static, str>> {
+        let _ = (key, text);
+        None
+    }
 }
 
+/// A [`MetadataTranslator`] which keeps the default texts
+pub struct PassthroughTranslator;
+
+impl MetadataTranslator for PassthroughTranslator {}
+
 #[derive(Debug, Clone)]
 pub struct ValueMetadataCommon<T> {
     /// A human-readable name for the value
@@ -105,6 +192,14 @@ pub struct ValueMetadataCommon<T> {
     /// If missing, applications should assume this to be `true` if no `states` are defined and `false` if `states` are defined.
     // FIXME: Set this automatically and remove the Option
     pub allow_manual_entry: Option<bool>,
+
+    /// Whether the value represents a state (`true`) or a notification/event (`false`).
+    /// Stateless values are not stored. This is set from the value's [`CCValueOptions`].
+    pub stateful: bool,
+
+    /// The stable key from which the message keys for translating the texts are derived,
+    /// e.g. `BinarySwitch.CurrentValue`. This is set by the CC value macros.
+    pub message_key: Option<&'static str>,
 }
 
 impl<T> Default for ValueMetadataCommon<T> {
@@ -116,6 +211,8 @@ impl<T> Default for ValueMetadataCommon<T> {
             writeable: true,
             allow_manual_entry: Some(true),
             states: None,
+            stateful: true,
+            message_key: None,
         }
     }
 }
@@ -170,6 +267,29 @@ impl<T> ValueMetadataCommon<T> {
     }
 }
 
+impl<T: core::fmt::Debug> ValueMetadataCommon<T> {
+    fn translate(&mut self, translator: &dyn MetadataTranslator) {
+        let Some(message_key) = self.message_key else {
+            return;
+        };
+        let translate = |suffix: String, text: &mut Cow<'static, str>| {
+            let key = format!("{}.{}", message_key, suffix);
+            if let Some(translated) = translator.translate(&key, text) {
+                *text = translated;
+            }
+        };
+        if let Some(label) = &mut self.label {
+            translate("label".into(), label);
+        }
+        if let Some(description) = &mut self.description {
+            translate("description".into(), description);
+        }
+        for (value, name) in self.states.iter_mut().flatten() {
+            translate(format!("states.{:?}", value), name);
+        }
+    }
+}
+
 macro_rules! impl_common_metadata_accessors {
     ($t:ty) => {
         pub fn label(mut self, label: impl Into<Cow<'static, str>>) -> Self {
@@ -353,6 +473,144 @@ impl ValueMetadataBuffer {
     }
 }
 
+/// Where a piece of configuration parameter metadata came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataSource {
+    /// The device config file
+    DeviceConfig,
+    /// The device itself, through Configuration CC (V3+) reports
+    Device,
+}
+
+/// Tracks which source each part of a configuration parameter's metadata was taken from
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigMetadataProvenance {
+    pub label: Option<MetadataSource>,
+    pub description: Option<MetadataSource>,
+    pub unit: Option<MetadataSource>,
+    pub states: Option<MetadataSource>,
+    /// Covers `min`, `max` and `steps`, which are always taken from the same source
+    pub range: Option<MetadataSource>,
+    pub default: Option<MetadataSource>,
+}
+
+/// Metadata for a configuration parameter as defined in a device config file
+#[derive(Default, Debug, Clone)]
+pub struct ConfigParamFileMetadata {
+    pub metadata: ValueMetadataNumeric,
+    /// Whether the range and default value from the file should be preferred
+    /// over the ones reported by the device
+    pub override_ranges: bool,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct ValueMetadataConfiguration {
+    pub numeric: ValueMetadataNumeric,
+    pub provenance: ConfigMetadataProvenance,
+}
+
+impl ValueMetadataConfiguration {
+    /// Merges the metadata from a device config file and the metadata reported by the device.
+    ///
+    /// Labels, descriptions, units and states are taken from the config file if it defines them.
+    /// The range and default value are taken from the device, unless the config file overrides them.
+    /// Parts that are only defined by one of the sources are always taken from that source.
+    pub fn merge(
+        from_file: Option<&ConfigParamFileMetadata>,
+        from_device: Option<&ValueMetadataNumeric>,
+    ) -> Self {
+        use MetadataSource::*;
+
+        fn pick<'a, T>(
+            preferred: (MetadataSource, Option<&'a T>),
+            fallback: (MetadataSource, Option<&'a T>),
+        ) -> (Option<&'a T>, Option<MetadataSource>) {
+            match (preferred, fallback) {
+                ((source, Some(value)), _) | (_, (source, Some(value))) => {
+                    (Some(value), Some(source))
+                }
+                _ => (None, None),
+            }
+        }
+
+        let file = from_file.map(|f| &f.metadata);
+        let file_overrides_ranges = from_file.is_some_and(|f| f.override_ranges);
+
+        let mut ret = Self::default();
+        // Device-reported flags are authoritative, config files cannot know better
+        if let Some(common) = from_device.or(file).map(|m| &m.common) {
+            ret.numeric.common.readable = common.readable;
+            ret.numeric.common.writeable = common.writeable;
+        }
+
+        let (label, source) = pick(
+            (DeviceConfig, file.and_then(|m| m.common.label.as_ref())),
+            (Device, from_device.and_then(|m| m.common.label.as_ref())),
+        );
+        ret.numeric.common.label = label.cloned();
+        ret.provenance.label = source;
+
+        let (description, source) = pick(
+            (
+                DeviceConfig,
+                file.and_then(|m| m.common.description.as_ref()),
+            ),
+            (
+                Device,
+                from_device.and_then(|m| m.common.description.as_ref()),
+            ),
+        );
+        ret.numeric.common.description = description.cloned();
+        ret.provenance.description = source;
+
+        let (unit, source) = pick(
+            (DeviceConfig, file.and_then(|m| m.unit.as_ref())),
+            (Device, from_device.and_then(|m| m.unit.as_ref())),
+        );
+        ret.numeric.unit = unit.copied();
+        ret.provenance.unit = source;
+
+        // States and the manual entry flag belong together
+        let (states_meta, source) = pick(
+            (DeviceConfig, file.filter(|m| m.common.states.is_some())),
+            (Device, from_device.filter(|m| m.common.states.is_some())),
+        );
+        if let Some(meta) = states_meta {
+            ret.numeric.common.states = meta.common.states.clone();
+            ret.numeric.common.allow_manual_entry = meta.common.allow_manual_entry;
+        }
+        ret.provenance.states = source;
+
+        let has_range = |m: &&ValueMetadataNumeric| m.min.is_some() || m.max.is_some();
+        let (file_range, device_range) = (file.filter(has_range), from_device.filter(has_range));
+        let (range_meta, source) = if file_overrides_ranges {
+            pick((DeviceConfig, file_range), (Device, device_range))
+        } else {
+            pick((Device, device_range), (DeviceConfig, file_range))
+        };
+        if let Some(meta) = range_meta {
+            ret.numeric.min = meta.min;
+            ret.numeric.max = meta.max;
+            ret.numeric.steps = meta.steps;
+        }
+        ret.provenance.range = source;
+
+        let (file_default, device_default) = (
+            file.and_then(|m| m.default.as_ref()),
+            from_device.and_then(|m| m.default.as_ref()),
+        );
+        let (default, source) = if file_overrides_ranges {
+            pick((DeviceConfig, file_default), (Device, device_default))
+        } else {
+            pick((Device, device_default), (DeviceConfig, file_default))
+        };
+        ret.numeric.default = default.copied();
+        ret.provenance.default = source;
+
+        ret
+    }
+}
+
 pub struct CCValueOptions {
     /// Whether the CC value is internal. Internal values are not exposed to the user.
     pub internal: bool,
@@ -386,6 +644,12 @@ impl Default for CCValueOptions {
 }
 
 impl CCValueOptions {
+    /// Whether a value with these options exists on the given endpoint
+    /// if it supports the given CC version
+    pub fn is_defined_for(&self, endpoint: EndpointIndex, version: u8) -> bool {
+        self.min_version <= version && (endpoint == EndpointIndex::Root || self.supports_endpoints)
+    }
+
     pub fn internal(mut self) -> Self {
         self.internal = true;
         self
@@ -476,8 +740,10 @@ macro_rules! cc_value_static_property {
                         id.property() == property_and_key.property()
                             && id.property_key() == property_and_key.property_key()
                     });
-                    let metadata = $metadata;
-                    let options = $options;
+                    let mut metadata: ValueMetadata = $metadata;
+                    let options: CCValueOptions = $options;
+                    metadata.set_stateful(options.stateful);
+                    metadata.set_message_key(concat!(stringify!($cc), ".", stringify!($property_name)));
 
                     StaticCCValue {
                         id: value_id,
@@ -582,6 +848,8 @@ macro_rules! cc_value_dynamic_property {
 
                 static RET: OnceLock<DynamicCCValue<($($type,)*)>> = OnceLock::new();
                 RET.get_or_init(|| {
+                    let options: CCValueOptions = $options;
+                    let stateful = options.stateful;
                     let is = Box::new(move |id: &ValueId| {
                         // Test if the value ID can be converted back to the correct enum variant
                         let properties = ValueIdProperties::from(*id);
@@ -590,19 +858,20 @@ macro_rules! cc_value_dynamic_property {
                         };
                         matches!(prop, [<$cc CCProperties>]::$property_name(..))
                     });
-                    let eval = Box::new(|args: Box<dyn core::any::Any>| {
+                    let eval = Box::new(move |args: Box<dyn core::any::Any>| {
                         let ($($param,)*) = *args.downcast::<($($type,)*)>().expect("Arguments should be of the correct type");
 
                         let property_and_key: ValueIdProperties = [<$cc CCProperties>]::$property_name($($param),*).into();
                         let value_id = property_and_key.with_cc(CommandClasses::$cc);
-                        let metadata = $metadata;
+                        let mut metadata: ValueMetadata = $metadata;
+                        metadata.set_stateful(stateful);
+                        metadata.set_message_key(concat!(stringify!($cc), ".", stringify!($property_name)));
 
                         CCValue {
                             id: value_id,
                             metadata,
                         }
                     });
-                    let options = $options;
 
                     DynamicCCValue::new(eval, is, options)
                 })
@@ -611,3 +880,118 @@ macro_rules! cc_value_dynamic_property {
     };
 }
 pub(crate) use cc_value_dynamic_property;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::commandclass::BasicCCValues;
+
+    fn file_metadata(override_ranges: bool) -> ConfigParamFileMetadata {
+        ConfigParamFileMetadata {
+            metadata: ValueMetadataNumeric::default()
+                .label("LED brightness")
+                .min(0)
+                .max(10)
+                .default_value(5)
+                .unit("%"),
+            override_ranges,
+        }
+    }
+
+    fn device_metadata() -> ValueMetadataNumeric {
+        ValueMetadataNumeric::default()
+            .label("Parameter 1")
+            .description("Reported by the device")
+            .min(1)
+            .max(99)
+            .default_value(50)
+    }
+
+    #[test]
+    fn test_merge_config_metadata() {
+        let merged = ValueMetadataConfiguration::merge(
+            Some(&file_metadata(false)),
+            Some(&device_metadata()),
+        );
+
+        assert_eq!(
+            merged.numeric.common.label.as_deref(),
+            Some("LED brightness")
+        );
+        assert_eq!(merged.provenance.label, Some(MetadataSource::DeviceConfig));
+        // The file does not define a description, so the device's is used
+        assert_eq!(
+            merged.numeric.common.description.as_deref(),
+            Some("Reported by the device")
+        );
+        assert_eq!(merged.provenance.description, Some(MetadataSource::Device));
+        assert_eq!(merged.numeric.unit, Some("%"));
+
+        assert_eq!(
+            (merged.numeric.min, merged.numeric.max),
+            (Some(1), Some(99))
+        );
+        assert_eq!(merged.numeric.default, Some(50));
+        assert_eq!(merged.provenance.range, Some(MetadataSource::Device));
+        assert_eq!(merged.provenance.default, Some(MetadataSource::Device));
+    }
+
+    #[test]
+    fn test_merge_config_metadata_override_ranges() {
+        let merged =
+            ValueMetadataConfiguration::merge(Some(&file_metadata(true)), Some(&device_metadata()));
+
+        assert_eq!(
+            (merged.numeric.min, merged.numeric.max),
+            (Some(0), Some(10))
+        );
+        assert_eq!(merged.numeric.default, Some(5));
+        assert_eq!(merged.provenance.range, Some(MetadataSource::DeviceConfig));
+        assert_eq!(
+            merged.provenance.default,
+            Some(MetadataSource::DeviceConfig)
+        );
+    }
+
+    #[test]
+    fn test_merge_config_metadata_single_source() {
+        let merged = ValueMetadataConfiguration::merge(None, Some(&device_metadata()));
+        assert_eq!(merged.numeric.common.label.as_deref(), Some("Parameter 1"));
+        assert_eq!(merged.provenance.label, Some(MetadataSource::Device));
+        assert_eq!(merged.provenance.unit, None);
+
+        let merged = ValueMetadataConfiguration::merge(Some(&file_metadata(false)), None);
+        assert_eq!(
+            (merged.numeric.min, merged.numeric.max),
+            (Some(0), Some(10))
+        );
+        assert_eq!(merged.provenance.range, Some(MetadataSource::DeviceConfig));
+    }
+
+    #[test]
+    fn test_translate_metadata() {
+        struct German;
+        impl MetadataTranslator for German {
+            fn translate(&self, key: &str, _text: &str) -> Option<Cow<'static, str>> {
+                match key {
+                    "Basic.CurrentValue.label" => Some("Aktueller Wert".into()),
+                    _ => None,
+                }
+            }
+        }
+
+        let metadata = &BasicCCValues::current_value().metadata;
+        let ValueMetadata::LevelReport(translated) = metadata.translated(&German) else {
+            panic!("unexpected metadata {:?}", metadata);
+        };
+        assert_eq!(translated.message_key, Some("Basic.CurrentValue"));
+        assert_eq!(translated.label.as_deref(), Some("Aktueller Wert"));
+
+        // Metadata without a message key is not translated
+        let metadata = ValueMetadata::Numeric(ValueMetadataNumeric::default().label("Custom"));
+        let ValueMetadata::Numeric(translated) = metadata.translated(&German) else {
+            unreachable!();
+        };
+        assert_eq!(translated.common.label.as_deref(), Some("Custom"));
+    }
+}
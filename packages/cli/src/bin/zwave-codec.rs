@@ -0,0 +1,398 @@
+//! Decodes and encodes Serial API frames and CCs without a controller, e.g. to analyze logs.
+
+use anyhow::{Context, anyhow, bail};
+use bytes::Bytes;
+use std::borrow::Cow;
+use zwave_cc::commandclass::{CCSession, CcOrRaw};
+use zwave_cc::prelude::*;
+use zwave_core::log::Loglevel;
+use zwave_core::prelude::*;
+use zwave_core::security::{
+    NetworkKey, S0_NONCE_SIZE, S0Nonce, SecurityManager, SecurityManagerOptions,
+};
+use zwave_logging::loggers::base::BaseLogger;
+use zwave_logging::{Direction, LogInfo, Logger};
+use zwave_serial::command::SendDataRequest;
+use zwave_serial::prelude::*;
+
+const USAGE: &str = "\
+Usage:
+  zwave-codec decode [options] <hex>
+      Decodes a Serial API frame, including the CC it contains
+  zwave-codec decode --cc [options] <hex>
+      Decodes a CC
+  zwave-codec encode [options] <node-id> <hex>
+      Wraps a CC in a SendData Serial API frame for the given node
+
+Options:
+  --cc                    Treat the input as a CC instead of a Serial API frame
+  --source <node-id>      The node that sent the CC (default: 2, only with --cc)
+  --own-node-id <node-id> The node ID of the controller (default: 1)
+  --outbound              The frame was sent to the controller instead of by it
+  --16bit                 The Serial API uses 16-bit node IDs
+  --s0-key <hex>          The S0 network key, to decrypt S0 encapsulated CCs
+  --s0-nonce <hex>        The nonce the receiver issued for the S0 encapsulated CC
+  --callback-id <id>      The callback ID of the encoded frame (default: 1)";
+
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Decode,
+    Encode { node_id: NodeId },
+}
+
+#[derive(Debug)]
+struct Args {
+    mode: Mode,
+    input: Bytes,
+    is_cc: bool,
+    origin: MessageOrigin,
+    source_node_id: NodeId,
+    own_node_id: NodeId,
+    node_id_type: NodeIdType,
+    s0_key: Option<NetworkKey>,
+    s0_nonce: Option<S0Nonce>,
+    callback_id: u8,
+}
+
+fn parse_hex(input: &str) -> anyhow::Result<Bytes> {
+    // Accept the formats that are commonly found in logs, e.g. 0x01 02 03 or 01:02:03
+    let input = input.trim().trim_start_matches("0x");
+    let input: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect();
+    let bytes = hex::decode(input).map_err(|e| anyhow!("invalid hex string: {}", e))?;
+    Ok(bytes.into())
+}
+
+fn parse_node_id(input: &str) -> anyhow::Result<NodeId> {
+    let node_id: u16 = input
+        .parse()
+        .with_context(|| format!("invalid node ID: {}", input))?;
+    Ok(NodeId::new(node_id))
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Args> {
+    let mut args = args.into_iter();
+    let mode = args.next().ok_or_else(|| anyhow!("no command given"))?;
+
+    let mut is_cc = false;
+    let mut origin = MessageOrigin::Controller;
+    let mut source_node_id = NodeId::new(2u8);
+    let mut own_node_id = NodeId::new(1u8);
+    let mut node_id_type = NodeIdType::NodeId8Bit;
+    let mut s0_key = None;
+    let mut s0_nonce = None;
+    let mut callback_id = 1;
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--cc" => is_cc = true,
+            "--outbound" => origin = MessageOrigin::Host,
+            "--16bit" => node_id_type = NodeIdType::NodeId16Bit,
+            "--source" => source_node_id = parse_node_id(&value()?)?,
+            "--own-node-id" => own_node_id = parse_node_id(&value()?)?,
+            "--s0-key" => {
+                let key = parse_hex(&value()?)?;
+                s0_key = Some(NetworkKey::try_from(key.as_ref())?);
+            }
+            "--s0-nonce" => {
+                let nonce = parse_hex(&value()?)?;
+                if nonce.len() != S0_NONCE_SIZE {
+                    bail!("S0 nonce must be {} bytes long", S0_NONCE_SIZE);
+                }
+                s0_nonce = Some(S0Nonce::new(&nonce));
+            }
+            "--callback-id" => {
+                let value = value()?;
+                callback_id = value
+                    .parse()
+                    .with_context(|| format!("invalid callback ID: {}", value))?;
+            }
+            _ if arg.starts_with("--") => bail!("unknown option {}", arg),
+            _ => positional.push(arg),
+        }
+    }
+
+    let (mode, input) = match (mode.as_str(), positional.as_slice()) {
+        ("decode", [input]) => (Mode::Decode, input),
+        ("encode", [node_id, input]) => (
+            Mode::Encode {
+                node_id: parse_node_id(node_id)?,
+            },
+            input,
+        ),
+        ("decode" | "encode", _) => bail!("wrong number of arguments for {}", mode),
+        _ => bail!("unknown command {}", mode),
+    };
+    if s0_nonce.is_some() && s0_key.is_none() {
+        bail!("--s0-nonce requires --s0-key");
+    }
+
+    Ok(Args {
+        mode,
+        input: parse_hex(input)?,
+        is_cc,
+        origin,
+        source_node_id,
+        own_node_id,
+        node_id_type,
+        s0_key,
+        s0_nonce,
+        callback_id,
+    })
+}
+
+/// Creates the context to parse a CC that was sent from `source` to `receiver`
+fn cc_parsing_context(args: &Args, source: NodeId, receiver: NodeId) -> CCParsingContext {
+    // The S0 nonce was issued by the receiver, so the security manager takes its perspective
+    let s0 = args.s0_key.as_ref().map(|network_key| {
+        let sec_man = SecurityManager::new(SecurityManagerOptions {
+            own_node_id: receiver,
+            network_key: network_key.clone(),
+        });
+        if let Some(nonce) = &args.s0_nonce {
+            sec_man.set_nonce(receiver, source, nonce.clone(), false);
+        }
+        sec_man
+    });
+
+    CCParsingContext::builder()
+        .source_node_id(source)
+        .own_node_id(receiver)
+        .security(SecurityManagers { s0, s2: None })
+        .build()
+}
+
+fn decode_cc(raw: CcOrRaw, ctx: impl Fn() -> CCParsingContext) -> anyhow::Result<CC> {
+    let mut cc = raw.try_as_cc(ctx()).context("failed to parse CC")?;
+    // Encapsulated CCs like S0 are only decoded completely when the session is merged
+    if cc.session_id().is_some() {
+        cc.merge_session(ctx(), Vec::new())
+            .context("failed to decode encapsulated CC")?;
+    }
+    Ok(cc)
+}
+
+fn decode_command(args: &Args) -> anyhow::Result<Command> {
+    let raw = CommandRaw::parse(&mut args.input.clone()).context("invalid Serial API frame")?;
+    let ctx = CommandParsingContext::builder()
+        .own_node_id(args.own_node_id)
+        .node_id_type(args.node_id_type)
+        .build();
+    let mut command = Command::try_from_raw_with_origin(raw, ctx, args.origin)
+        .context("failed to parse command")?;
+
+    // Decode the CC contained in the command, if there is one
+    match &mut command {
+        Command::ApplicationCommandRequest(cmd) => {
+            let (address, cc) = cmd.command.as_parts_mut();
+            let source = address.source_node_id;
+            *cc = decode_cc(cc.clone(), || {
+                cc_parsing_context(args, source, args.own_node_id)
+            })?
+            .into();
+        }
+        Command::BridgeApplicationCommandRequest(cmd) => {
+            let (address, cc) = cmd.command.as_parts_mut();
+            let source = address.source_node_id;
+            *cc = decode_cc(cc.clone(), || {
+                cc_parsing_context(args, source, args.own_node_id)
+            })?
+            .into();
+        }
+        Command::SendDataRequest(cmd) => {
+            let receiver = cmd.node_id;
+            cmd.command = decode_cc(cmd.command.clone(), || {
+                cc_parsing_context(args, args.own_node_id, receiver)
+            })?
+            .into();
+        }
+        _ => {}
+    }
+
+    Ok(command)
+}
+
+fn encode_command(args: &Args, node_id: NodeId) -> anyhow::Result<Bytes> {
+    let cc = CCRaw::parse(&mut args.input.clone()).context("invalid CC")?;
+    let mut command = SendDataRequest::builder()
+        .node_id(node_id)
+        .command(cc.into())
+        .build();
+    command.set_callback_id(Some(args.callback_id));
+
+    let ctx = CommandEncodingContext::builder()
+        .own_node_id(args.own_node_id)
+        .node_id_type(args.node_id_type)
+        .build();
+    Ok(command.as_raw(&ctx).as_bytes())
+}
+
+fn log(logger: &mut BaseLogger, primary_tags: Vec<Cow<'static, str>>, payload: LogPayload) {
+    let log = LogInfo::builder()
+        .label("CODEC")
+        .primary_tags(primary_tags)
+        .direction(Direction::None)
+        .payload(LogPayloadText::new("").with_nested(payload).into())
+        .build();
+    logger.log(log, Loglevel::Debug);
+    println!();
+}
+
+fn run(args: Args) -> anyhow::Result<()> {
+    let mut logger = BaseLogger {
+        level: Loglevel::Debug,
+        writer: Box::new(termcolor::StandardStream::stdout(
+            termcolor::ColorChoice::Auto,
+        )),
+        formatter: Box::new(zwave_logging::formatters::DefaultFormatter::new()),
+    };
+
+    match args.mode {
+        Mode::Decode if args.is_cc => {
+            let cc = decode_cc(CCRaw::parse(&mut args.input.clone())?.into(), || {
+                cc_parsing_context(&args, args.source_node_id, args.own_node_id)
+            })?;
+            let tags = vec![format!("Node {:0>3}", args.source_node_id).into()];
+            log(&mut logger, tags, cc.to_log_payload());
+        }
+        Mode::Decode => {
+            let command = decode_command(&args)?;
+            let type_tag = if command.command_type() == CommandType::Request {
+                "REQ"
+            } else {
+                "RES"
+            };
+            let tags = vec![
+                type_tag.into(),
+                format!("{:?}", command.function_type()).into(),
+            ];
+            log(&mut logger, tags, command.to_log_payload());
+        }
+        Mode::Encode { node_id } => {
+            println!("{}", hex::encode(encode_command(&args, node_id)?));
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let result = parse_args(std::env::args().skip(1)).and_then(run);
+    if let Err(e) = result {
+        eprintln!("Error: {:#}\n\n{}", e, USAGE);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Args {
+        parse_args(args.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_decode_application_command() {
+        // Basic CC Report from node 2
+        let args = args(&["decode", "01 09 00 04 00 02 03 20 03 ff 2f"]);
+        let Command::ApplicationCommandRequest(cmd) = decode_command(&args).unwrap() else {
+            panic!("expected an ApplicationCommandRequest");
+        };
+        assert_eq!(cmd.command.address().source_node_id, NodeId::new(2u8));
+        assert!(matches!(
+            cmd.command.as_parts().1,
+            CcOrRaw::CC(CC::BasicCCReport(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let encoded = encode_command(&args(&["encode", "5", "2002"]), NodeId::new(5u8)).unwrap();
+        assert_eq!(hex::encode(&encoded), "01090013050220022501e4");
+
+        let args = args(&["decode", "--outbound", &hex::encode(&encoded)]);
+        let Command::SendDataRequest(cmd) = decode_command(&args).unwrap() else {
+            panic!("expected a SendDataRequest");
+        };
+        assert_eq!(cmd.node_id, NodeId::new(5u8));
+        assert!(matches!(cmd.command, CcOrRaw::CC(CC::BasicCCGet(_))));
+    }
+
+    #[test]
+    fn test_decrypt_s0() {
+        use zwave_cc::commandclass::{
+            BasicCCSet, IntoCCSequence, SecurityCCCommandEncapsulation, SecurityCCNonceReport,
+        };
+
+        let network_key = [0x11u8; 16];
+        let nonce = [1u8, 2, 3, 4, 5, 6, 7, 8];
+
+        // Node 2 encrypts a command for the controller, using the nonce the controller issued
+        let sender = SecurityManager::new(SecurityManagerOptions {
+            own_node_id: NodeId::new(2u8),
+            network_key: NetworkKey::from(network_key),
+        });
+        let cc = BasicCCSet::builder()
+            .target_value(zwave_core::values::LevelSet::On)
+            .build();
+        let mut sequence = CC::from(SecurityCCCommandEncapsulation::new(cc.into()))
+            .with_destination(NodeId::new(1u8).into())
+            .into_cc_sequence();
+        let ctx = CCEncodingContext::builder()
+            .own_node_id(NodeId::new(2u8))
+            .node_id(NodeId::new(1u8))
+            .security(SecurityManagers {
+                s0: Some(sender),
+                s2: None,
+            })
+            .build();
+        // The first command of the sequence requests the nonce
+        sequence.next(&ctx);
+        sequence.handle_response(
+            &SecurityCCNonceReport {
+                nonce: S0Nonce::new(&nonce),
+            }
+            .into(),
+        );
+        let encrypted = sequence.next(&ctx).unwrap().as_raw(&ctx).as_bytes();
+
+        let args = args(&[
+            "decode",
+            "--cc",
+            "--s0-key",
+            &hex::encode(network_key),
+            "--s0-nonce",
+            &hex::encode(nonce),
+            &hex::encode(&encrypted),
+        ]);
+        let cc = decode_cc(
+            CCRaw::parse(&mut args.input.clone()).unwrap().into(),
+            || cc_parsing_context(&args, args.source_node_id, args.own_node_id),
+        )
+        .unwrap();
+        let CC::SecurityCCCommandEncapsulation(encapsulation) = cc else {
+            panic!("expected a SecurityCCCommandEncapsulation");
+        };
+        assert!(matches!(
+            encapsulation.encapsulated(),
+            Some(CC::BasicCCSet(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+        assert!(parse(&["decode"]).is_err());
+        assert!(parse(&["decode", "zz"]).is_err());
+        assert!(parse(&["encode", "2002"]).is_err());
+        assert!(parse(&["decode", "--s0-nonce", "0102030405060708", "2002"]).is_err());
+    }
+}
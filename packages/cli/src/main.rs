@@ -1,11 +1,15 @@
 use std::time::Duration;
 use zwave_cc::{commandclass, prelude::CCAddressable};
 use zwave_core::log::Loglevel;
-use zwave_driver::{Controller, SecurityKeys};
+use zwave_driver::api::v1::runtime::SerialApi;
+use zwave_driver::api::v1::{
+    Controller, Driver, DriverOptions, Ready, SecurityKeys, SelfTestOptions,
+};
 use zwave_logging::loggers::base::BaseLogger;
 
 mod port;
 mod rt;
+use port::{SerialPortOptions, ZWavePort};
 use rt::Runtime;
 
 #[cfg(target_os = "linux")]
@@ -25,6 +29,13 @@ fn main() -> Result<(), anyhow::Error> {
                 0x0E, 0x0F, 0x10,
             ])
             .build();
+        let self_test_options = SelfTestOptions {
+            s0_key: security_keys.s0_legacy.clone(),
+            ..Default::default()
+        };
+
+        let port = DriverOptions::builder().port(PORT).build().resolve_port()?;
+        let port = ZWavePort::open(&port, &SerialPortOptions::default()).await?;
 
         let logger = BaseLogger {
             level: Loglevel::Debug,
@@ -36,13 +47,12 @@ fn main() -> Result<(), anyhow::Error> {
 
         let (log_tx, log_rx) = zwave_pal::channel::channel(16);
 
-        let (serial_api, serial_api_actor, serial_api_adapter) =
-            zwave_driver::SerialApi::new(log_tx.clone());
+        let (serial_api, serial_api_actor, serial_api_adapter) = SerialApi::new(log_tx.clone());
         let (driver, driver_actor, driver_adapter) =
-            zwave_driver::Driver::new(&serial_api, log_tx, security_keys);
+            Driver::new(&serial_api, log_tx, security_keys);
 
         let runtime = Runtime::new(
-            PORT,
+            port,
             logger,
             log_rx,
             driver_actor,
@@ -52,26 +62,37 @@ fn main() -> Result<(), anyhow::Error> {
         )
         .await?;
 
-        let runtime_task = runtime.spawn(&local);
+        let mut runtime_task = runtime.spawn(&local);
 
-        let controller = Controller::new(&driver);
-        let _controller: Controller<'_, zwave_driver::Ready> =
-            controller.interview().await.unwrap();
+        zwave_pal::select_biased! {
+            // If the driver stops, waiting for it is pointless
+            result = &mut runtime_task => result?,
+            result = run(&driver, &self_test_options) => {
+                let _ = runtime_task.cancel().await;
+                result?
+            },
+        }
+
+        Ok(())
+    }))
+}
 
-        smol::Timer::after(Duration::from_secs(1)).await;
+async fn run(driver: &Driver, self_test_options: &SelfTestOptions) -> Result<(), anyhow::Error> {
+    let controller = Controller::new(driver);
+    controller.self_test(self_test_options).await?;
+    let _controller: Controller<'_, Ready> = controller.interview().await?;
 
-        let cc = commandclass::BasicCCSet::builder()
-            .target_value(zwave_core::values::LevelSet::On)
-            .build()
-            .with_destination(11u8.into());
-        let result = driver.exec_node_command(&cc.into(), None).await;
-        println!("result: {:?}", result);
+    smol::Timer::after(Duration::from_secs(1)).await;
 
-        smol::Timer::after(Duration::from_secs(1)).await;
-        println!("Bye");
+    let cc = commandclass::BasicCCSet::builder()
+        .target_value(zwave_core::values::LevelSet::On)
+        .build()
+        .with_destination(11u8.into());
+    let result = driver.exec_node_command(&cc.into(), None).await;
+    println!("result: {:?}", result);
 
-        let _ = runtime_task.cancel().await;
+    smol::Timer::after(Duration::from_secs(1)).await;
+    println!("Bye");
 
-        Ok(())
-    }))
+    Ok(())
 }
@@ -19,6 +19,24 @@ type SerialReceiver = async_channel::Receiver<RawSerialFrame>;
 
 const CHANNEL_CAPACITY: usize = 16;
 const SERIAL_PORT_TIMEOUT: Duration = Duration::from_secs(1);
+const DTR_TOGGLE_DELAY: Duration = Duration::from_millis(100);
+
+/// How a serial port is set up after opening it. Some sticks need special treatment
+/// before they start communicating.
+#[derive(Debug, Clone, Default)]
+pub struct SerialPortOptions {
+    /// The state of the DTR line after opening the port. `None` keeps the platform default,
+    /// which is asserted on Linux and macOS, but not on Windows.
+    pub dtr: Option<bool>,
+    /// The state of the RTS line after opening the port. `None` keeps the platform default.
+    pub rts: Option<bool>,
+    /// Whether to pull DTR low and back high after opening the port.
+    /// Some sticks (e.g. the UZB) need this to start communicating.
+    pub toggle_dtr: bool,
+    /// The latency timer of FTDI adapters in milliseconds. Lower values reduce the delay of
+    /// incoming frames. Only supported on Linux, and ignored for other adapters.
+    pub latency_timer: Option<u8>,
+}
 
 /// A `SerialBinding` backed by a TCP stream (e.g. for tcp:// serial bridges).
 pub(crate) struct TcpBinding {
@@ -66,8 +84,17 @@ pub enum ZWavePort {
 }
 
 impl ZWavePort {
-    pub fn open_serial(path: &str) -> io::Result<Self> {
-        Ok(Self::Serial(SerialThreadPort::open(path)?))
+    /// Opens the given serial port, or connects to a serial bridge if the path starts with `tcp://`
+    pub async fn open(path: &str, options: &SerialPortOptions) -> io::Result<Self> {
+        if let Some(addr) = path.strip_prefix("tcp://") {
+            Self::open_tcp(addr).await
+        } else {
+            Self::open_serial(path, options)
+        }
+    }
+
+    pub fn open_serial(path: &str, options: &SerialPortOptions) -> io::Result<Self> {
+        Ok(Self::Serial(SerialThreadPort::open(path, options)?))
     }
 
     pub async fn open_tcp(addr: &str) -> io::Result<Self> {
@@ -103,8 +130,8 @@ pub struct SerialThreadPort {
 }
 
 impl SerialThreadPort {
-    pub fn open(path: &str) -> io::Result<Self> {
-        let write_port = open_serial_port(path)?;
+    pub fn open(path: &str, options: &SerialPortOptions) -> io::Result<Self> {
+        let write_port = open_serial_port(path, options)?;
         let read_port = write_port.try_clone().map_err(io::Error::from)?;
 
         let (outbound_tx, outbound_rx) = async_channel::bounded(CHANNEL_CAPACITY);
@@ -242,29 +269,74 @@ fn channel_closed(message: &'static str) -> zwave_serial::error::Error {
     zwave_serial::error::Error::Io(message.into())
 }
 
+fn open_serial_port(
+    path: &str,
+    options: &SerialPortOptions,
+) -> io::Result<Box<dyn serialport::SerialPort>> {
+    let mut builder = serialport::new(path, 115_200).timeout(SERIAL_PORT_TIMEOUT);
+    if let Some(dtr) = options.dtr {
+        builder = builder.dtr_on_open(dtr);
+    }
+    let mut port = open_native_port(builder)?;
+
+    if let Some(rts) = options.rts {
+        port.write_request_to_send(rts).map_err(io::Error::from)?;
+    }
+    if options.toggle_dtr {
+        port.write_data_terminal_ready(false)
+            .map_err(io::Error::from)?;
+        thread::sleep(DTR_TOGGLE_DELAY);
+        port.write_data_terminal_ready(options.dtr.unwrap_or(true))
+            .map_err(io::Error::from)?;
+    }
+    if let Some(latency_timer) = options.latency_timer {
+        // Not being able to tune the adapter is no reason to fail opening the port
+        let _ = set_latency_timer(path, latency_timer);
+    }
+
+    Ok(port)
+}
+
 #[cfg(unix)]
-fn open_serial_port(path: &str) -> io::Result<Box<dyn serialport::SerialPort>> {
-    let mut port = serialport::new(path, 115_200)
-        .timeout(SERIAL_PORT_TIMEOUT)
-        .open_native()
-        .map_err(io::Error::from)?;
+fn open_native_port(
+    builder: serialport::SerialPortBuilder,
+) -> io::Result<Box<dyn serialport::SerialPort>> {
+    let mut port = builder.open_native().map_err(io::Error::from)?;
     port.set_exclusive(false).map_err(io::Error::from)?;
     Ok(Box::new(port))
 }
 
 #[cfg(windows)]
-fn open_serial_port(path: &str) -> io::Result<Box<dyn serialport::SerialPort>> {
-    let port = serialport::new(path, 115_200)
-        .timeout(SERIAL_PORT_TIMEOUT)
-        .open_native()
-        .map_err(io::Error::from)?;
+fn open_native_port(
+    builder: serialport::SerialPortBuilder,
+) -> io::Result<Box<dyn serialport::SerialPort>> {
+    let port = builder.open_native().map_err(io::Error::from)?;
     Ok(Box::new(port))
 }
 
 #[cfg(not(any(unix, windows)))]
-fn open_serial_port(path: &str) -> io::Result<Box<dyn serialport::SerialPort>> {
-    serialport::new(path, 115_200)
-        .timeout(SERIAL_PORT_TIMEOUT)
-        .open()
-        .map_err(io::Error::from)
+fn open_native_port(
+    builder: serialport::SerialPortBuilder,
+) -> io::Result<Box<dyn serialport::SerialPort>> {
+    builder.open().map_err(io::Error::from)
+}
+
+/// Configures the latency timer of FTDI adapters, which the kernel exposes through sysfs
+#[cfg(target_os = "linux")]
+fn set_latency_timer(path: &str, milliseconds: u8) -> io::Result<()> {
+    // Resolve links like /dev/serial/by-id/... to the actual device, e.g. /dev/ttyUSB0
+    let device = std::fs::canonicalize(path)?;
+    let Some(name) = device.file_name() else {
+        return Err(ErrorKind::NotFound.into());
+    };
+    let sysfs_path = std::path::Path::new("/sys/bus/usb-serial/devices")
+        .join(name)
+        .join("latency_timer");
+    std::fs::write(sysfs_path, milliseconds.to_string())
+}
+
+/// On Windows and macOS, the latency timer can only be changed through the FTDI driver settings
+#[cfg(not(target_os = "linux"))]
+fn set_latency_timer(_path: &str, _milliseconds: u8) -> io::Result<()> {
+    Err(ErrorKind::Unsupported.into())
 }
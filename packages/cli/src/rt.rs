@@ -1,8 +1,9 @@
 use crate::port::ZWavePort;
 use smol::LocalExecutor;
-use zwave_driver::{
-    DriverActor, DriverAdapter, DriverInput, LogReceiver, SerialApiActor, SerialApiAdapter,
+use zwave_driver::api::v1::runtime::{
+    DriverActor, DriverAdapter, DriverInput, SerialApiActor, SerialApiAdapter, SerialApiEvent,
 };
+use zwave_driver::api::v1::{DriverEvent, FatalError, LogReceiver};
 use zwave_logging::{Logger, loggers::base::BaseLogger};
 use zwave_serial::binding::SerialBinding;
 use zwave_serial::frame::RawSerialFrame;
@@ -19,7 +20,7 @@ pub struct Runtime {
 
 impl Runtime {
     pub async fn new(
-        path: &str,
+        port: ZWavePort,
         logger: BaseLogger,
         log_rx: LogReceiver,
         driver: DriverActor,
@@ -27,17 +28,6 @@ impl Runtime {
         serial_api: SerialApiActor,
         serial_api_adapter: SerialApiAdapter,
     ) -> Result<Self, anyhow::Error> {
-        let open_port_result = if let Some(addr) = path.strip_prefix("tcp://") {
-            ZWavePort::open_tcp(addr).await
-        } else {
-            ZWavePort::open_serial(path)
-        };
-
-        let port = match open_port_result {
-            Ok(port) => port,
-            Err(e) => return Err(e.into()),
-        };
-
         Ok(Self {
             logger,
             log_rx,
@@ -49,7 +39,9 @@ impl Runtime {
         })
     }
 
-    pub fn spawn(self, local: &LocalExecutor<'_>) -> smol::Task<()> {
+    /// Runs the actors and moves data between them and the serial port. The returned task ends
+    /// with the error that stopped the driver, or successfully if the port was closed.
+    pub fn spawn(self, local: &LocalExecutor<'_>) -> smol::Task<Result<(), FatalError>> {
         let Self {
             logger,
             port,
@@ -61,13 +53,14 @@ impl Runtime {
         } = self;
 
         // Start the driver and serial API actors.
-        let driver_task = local.spawn(async move {
+        // The actors log fatal errors themselves
+        let mut driver_task = local.spawn(async move {
             let mut driver = driver;
-            driver.run().await;
+            driver.run().await
         });
         let serial_api_task = local.spawn(async move {
             let mut serial_api = serial_api;
-            serial_api.run().await;
+            let _ = serial_api.run().await;
         });
 
         local.spawn(async move {
@@ -76,6 +69,7 @@ impl Runtime {
             let mut log_rx = log_rx;
             let mut driver_adapter = driver_adapter;
             let mut serial_api_adapter = serial_api_adapter;
+            let mut result = Ok(());
 
             loop {
                 zwave_pal::select_biased! {
@@ -108,15 +102,25 @@ impl Runtime {
                             break;
                         };
                         match event {
-                            zwave_driver::SerialApiEvent::Unsolicited { command } => {
+                            SerialApiEvent::Unsolicited { command } => {
                                 // Forward unsolicited commands to the driver
                                 if !forward_unsolicited(&mut driver_adapter, command) {
                                     // Channel probably closed => quit
                                     break;
                                 }
                             }
+                            SerialApiEvent::ControllerUnresponsive
+                            | SerialApiEvent::ControllerRecovered
+                            | SerialApiEvent::ChecksumMismatch { .. } => {
+                                // The serial API already logs these
+                            }
                         }
                     },
+                    // Quit when the driver stops
+                    fatal_error = driver_stopped(&mut driver_adapter, &mut driver_task) => {
+                        result = fatal_error.map_or(Ok(()), Err);
+                        break;
+                    },
                     // And finally if there is something to log, do that.
                     log = log_rx.recv() => {
                         let Some((log, level)) = log else {
@@ -130,10 +134,34 @@ impl Runtime {
 
             let _ = driver_task.cancel().await;
             let _ = serial_api_task.cancel().await;
+            result
         })
     }
 }
 
+/// Waits until the driver stops and returns the error that stopped it. The other events of the
+/// driver are not interesting here, so they are discarded.
+async fn driver_stopped(
+    driver_adapter: &mut DriverAdapter,
+    driver_task: &mut smol::Task<Result<(), FatalError>>,
+) -> Option<FatalError> {
+    let fatal_error_event = async {
+        while let Some(event) = driver_adapter.event_rx.recv().await {
+            if let DriverEvent::FatalError { error } = event {
+                return Some(error);
+            }
+        }
+        // Channel closed
+        None
+    };
+
+    zwave_pal::select_biased! {
+        error = fatal_error_event => error,
+        // The event is dropped if the channel is full, but the result of the task is reliable
+        result = driver_task => result.err(),
+    }
+}
+
 fn forward_serial_frame(serial_api_adapter: &mut SerialApiAdapter, frame: RawSerialFrame) -> bool {
     match serial_api_adapter.serial_in.try_send(frame) {
         Ok(()) => true,
@@ -3,7 +3,7 @@ use paste::paste;
 use zwave_pal::prelude::*;
 
 /// Defines the possible values that can be stored in the cache
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CacheValue {
     // Primitives
     Bool(bool),
@@ -6,12 +6,14 @@ submodule!(command_classes);
 submodule!(command_type);
 submodule!(controller_role);
 submodule!(data_rate);
+submodule!(device_class);
 submodule!(device_fingerprint);
 submodule!(device_type);
 submodule!(endpoint_index);
 submodule!(frame_info);
 submodule!(function_type);
 submodule!(id);
+submodule!(long_range_channel);
 submodule!(message_origin);
 submodule!(node_id_type);
 submodule!(node_id);
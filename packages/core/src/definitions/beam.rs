@@ -6,8 +6,9 @@ use crate::prelude::*;
 use bytes::Bytes;
 use proc_macros::TryFromRepr;
 use core::fmt::Display;
+use core::time::Duration;
 
-#[derive(Debug, Clone, PartialEq, TryFromRepr)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
 #[repr(u8)]
 pub enum Beam {
     Beam250ms = 1,
@@ -34,6 +35,14 @@ impl BitParsable for Beam {
 }
 
 impl Beam {
+    /// How long the wakeup beam needs to be sent before a node in this mode notices it
+    pub fn duration(&self) -> Duration {
+        match self {
+            Beam::Beam250ms => Duration::from_millis(250),
+            Beam::Beam1000ms => Duration::from_millis(1000),
+        }
+    }
+
     pub fn parse_opt(i: &mut (Bytes, usize)) -> crate::parse::ParseResult<Option<Self>> {
         context(
             "Beam",
@@ -145,11 +145,12 @@ pub enum CommandClasses {
 
 impl CommandClasses {
     pub fn is_extended_cc(&self) -> bool {
-        *self as u16 > 0xf1u16
+        Self::is_extended(*self as u16)
     }
 
+    /// Tests whether the given CC ID, or the first byte of it, belongs to an extended (2-byte) CC
     pub fn is_extended<T: Into<u16>>(val: T) -> bool {
-        val.into() > 0xf1u16
+        val.into() >= 0xf1u16
     }
 
     /// Returns an iterator over all defined command classes
@@ -495,6 +496,15 @@ fn test_non_application_ccs() {
     assert!(nac.contains(&CommandClasses::Association));
 }
 
+#[test]
+fn test_roundtrip_all_ccs() {
+    for cc in CommandClasses::all_ccs() {
+        let mut raw = cc.as_bytes();
+        assert_eq!(CommandClasses::parse(&mut raw), Ok(*cc), "{}", cc);
+        assert!(raw.is_empty());
+    }
+}
+
 impl Parsable for CommandClasses {
     fn parse(i: &mut bytes::Bytes) -> crate::parse::ParseResult<Self> {
         let cc_id = peek(be_u8).parse(i)?;
@@ -582,6 +592,11 @@ impl PartialCommandClassInfo {
         self
     }
 
+    pub fn not_supported(mut self) -> Self {
+        self.supported = Some(false);
+        self
+    }
+
     pub fn controlled(mut self) -> Self {
         self.controlled = Some(true);
         self
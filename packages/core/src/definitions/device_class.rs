@@ -0,0 +1,342 @@
+use crate::prelude::*;
+use core::fmt::Display;
+
+macro_rules! device_classes {
+    (
+        $(
+            $generic:ident = $generic_key:literal, $generic_label:literal {
+                $( $specific:ident = $specific_key:literal, $specific_label:literal; )*
+            }
+        )*
+    ) => {
+        /// The generic device class of a node or endpoint, which describes its main functionality
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum GenericDeviceClass {
+            $( $generic, )*
+            /// A generic device class that is not defined by the specification
+            Unknown(u8),
+        }
+
+        impl GenericDeviceClass {
+            /// The human-readable name of the device class
+            pub fn label(&self) -> &'static str {
+                match self {
+                    $( Self::$generic => $generic_label, )*
+                    Self::Unknown(_) => "Unknown",
+                }
+            }
+        }
+
+        impl From<u8> for GenericDeviceClass {
+            fn from(key: u8) -> Self {
+                match key {
+                    $( $generic_key => Self::$generic, )*
+                    _ => Self::Unknown(key),
+                }
+            }
+        }
+
+        impl From<GenericDeviceClass> for u8 {
+            fn from(class: GenericDeviceClass) -> Self {
+                match class {
+                    $( GenericDeviceClass::$generic => $generic_key, )*
+                    GenericDeviceClass::Unknown(key) => key,
+                }
+            }
+        }
+
+        /// The specific device class of a node or endpoint, which refines its generic device class
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum SpecificDeviceClass {
+            $( $( $specific, )* )*
+            /// A specific device class that is not defined by the specification
+            /// for the generic device class
+            Unknown(u8),
+        }
+
+        impl SpecificDeviceClass {
+            /// Looks up the specific device class with the given key within the generic device class.
+            /// Returns `None` if the key indicates that no specific device class is used.
+            pub fn new(generic: GenericDeviceClass, key: u8) -> Option<Self> {
+                if key == 0 {
+                    return None;
+                }
+                let ret = match (generic, key) {
+                    $( $( (GenericDeviceClass::$generic, $specific_key) => Self::$specific, )* )*
+                    _ => Self::Unknown(key),
+                };
+                Some(ret)
+            }
+
+            /// The human-readable name of the device class
+            pub fn label(&self) -> &'static str {
+                match self {
+                    $( $( Self::$specific => $specific_label, )* )*
+                    Self::Unknown(_) => "Unknown",
+                }
+            }
+        }
+
+        impl From<SpecificDeviceClass> for u8 {
+            fn from(class: SpecificDeviceClass) -> Self {
+                match class {
+                    $( $( SpecificDeviceClass::$specific => $specific_key, )* )*
+                    SpecificDeviceClass::Unknown(key) => key,
+                }
+            }
+        }
+    };
+}
+
+device_classes! {
+    GenericController = 0x01, "Generic Controller" {
+        PortableRemoteController = 0x01, "Portable Remote Controller";
+        PortableSceneController = 0x02, "Portable Scene Controller";
+        PortableInstallerTool = 0x03, "Portable Installer Tool";
+        RemoteControlAV = 0x04, "Remote Control AV";
+        RemoteControlSimple = 0x06, "Remote Control Simple";
+    }
+    StaticController = 0x02, "Static Controller" {
+        PCController = 0x01, "PC Controller";
+        SceneController = 0x02, "Scene Controller";
+        StaticInstallerTool = 0x03, "Static Installer Tool";
+        SetTopBox = 0x04, "Set Top Box";
+        SubSystemController = 0x05, "Sub System Controller";
+        TV = 0x06, "TV";
+        Gateway = 0x07, "Gateway";
+    }
+    AVControlPoint = 0x03, "AV Control Point" {
+        SatelliteReceiver = 0x04, "Satellite Receiver";
+        SatelliteReceiverV2 = 0x11, "Satellite Receiver V2";
+        Doorbell = 0x12, "Doorbell";
+    }
+    Display = 0x04, "Display" {
+        SimpleDisplay = 0x01, "Simple Display";
+    }
+    NetworkExtender = 0x05, "Network Extender" {
+        SecureExtender = 0x01, "Secure Extender";
+    }
+    Appliance = 0x06, "Appliance" {
+        GeneralAppliance = 0x01, "General Appliance";
+        KitchenAppliance = 0x02, "Kitchen Appliance";
+        LaundryAppliance = 0x03, "Laundry Appliance";
+    }
+    NotificationSensor = 0x07, "Notification Sensor" {
+        SpecificNotificationSensor = 0x01, "Notification Sensor";
+    }
+    Thermostat = 0x08, "Thermostat" {
+        HeatingThermostat = 0x01, "Heating Thermostat";
+        GeneralThermostat = 0x02, "General Thermostat";
+        SetbackScheduleThermostat = 0x03, "Setback Schedule Thermostat";
+        SetpointThermostat = 0x04, "Setpoint Thermostat";
+        SetbackThermostat = 0x05, "Setback Thermostat";
+        GeneralThermostatV2 = 0x06, "General Thermostat V2";
+    }
+    WindowCovering = 0x09, "Window Covering" {
+        SimpleWindowCovering = 0x01, "Simple Window Covering";
+    }
+    RepeaterEndNode = 0x0f, "Repeater End Node" {
+        BasicRepeaterEndNode = 0x01, "Basic Repeater End Node";
+        VirtualNode = 0x02, "Virtual Node";
+    }
+    BinarySwitch = 0x10, "Binary Switch" {
+        BinaryPowerSwitch = 0x01, "Binary Power Switch";
+        BinaryTunableColorLight = 0x02, "Binary Tunable Color Light";
+        BinarySceneSwitch = 0x03, "Binary Scene Switch";
+        PowerStrip = 0x04, "Power Strip";
+        Siren = 0x05, "Siren";
+        ValveOpenClose = 0x06, "Valve (open/close)";
+        IrrigationController = 0x07, "Irrigation Controller";
+    }
+    MultilevelSwitch = 0x11, "Multilevel Switch" {
+        MultilevelPowerSwitch = 0x01, "Multilevel Power Switch";
+        MultilevelTunableColorLight = 0x02, "Multilevel Tunable Color Light";
+        MultipositionMotor = 0x03, "Multiposition Motor";
+        MultilevelSceneSwitch = 0x04, "Multilevel Scene Switch";
+        MotorControlClassA = 0x05, "Motor Control Class A";
+        MotorControlClassB = 0x06, "Motor Control Class B";
+        MotorControlClassC = 0x07, "Motor Control Class C";
+        FanSwitch = 0x08, "Fan Switch";
+    }
+    RemoteSwitch = 0x12, "Remote Switch" {
+        BinaryRemoteSwitch = 0x01, "Binary Remote Switch";
+        MultilevelRemoteSwitch = 0x02, "Multilevel Remote Switch";
+        BinaryToggleRemoteSwitch = 0x03, "Binary Toggle Remote Switch";
+        MultilevelToggleRemoteSwitch = 0x04, "Multilevel Toggle Remote Switch";
+    }
+    ToggleSwitch = 0x13, "Toggle Switch" {
+        BinaryToggleSwitch = 0x01, "Binary Toggle Switch";
+        MultilevelToggleSwitch = 0x02, "Multilevel Toggle Switch";
+    }
+    ZipNode = 0x15, "Z/IP Node" {
+        ZipTunnelingNode = 0x01, "Z/IP Tunneling Node";
+        ZipAdvancedNode = 0x02, "Z/IP Advanced Node";
+    }
+    Ventilation = 0x16, "Ventilation" {
+        ResidentialHeatRecoveryVentilation = 0x01, "Residential Heat Recovery Ventilation";
+    }
+    SecurityPanel = 0x17, "Security Panel" {
+        ZonedSecurityPanel = 0x01, "Zoned Security Panel";
+    }
+    WallController = 0x18, "Wall Controller" {
+        BasicWallController = 0x01, "Basic Wall Controller";
+    }
+    BinarySensor = 0x20, "Binary Sensor" {
+        RoutingBinarySensor = 0x01, "Routing Binary Sensor";
+    }
+    MultilevelSensor = 0x21, "Multilevel Sensor" {
+        RoutingMultilevelSensor = 0x01, "Routing Multilevel Sensor";
+        ChimneyFan = 0x02, "Chimney Fan";
+    }
+    PulseMeter = 0x30, "Pulse Meter" {}
+    Meter = 0x31, "Meter" {
+        SimpleMeter = 0x01, "Simple Meter";
+        AdvancedEnergyControl = 0x02, "Advanced Energy Control";
+        WholeHomeMeterSimple = 0x03, "Whole Home Meter (simple)";
+    }
+    EntryControl = 0x40, "Entry Control" {
+        DoorLock = 0x01, "Door Lock";
+        AdvancedDoorLock = 0x02, "Advanced Door Lock";
+        SecureKeypadDoorLock = 0x03, "Secure Keypad Door Lock";
+        SecureKeypadDoorLockDeadbolt = 0x04, "Secure Keypad Door Lock Deadbolt";
+        SecureDoor = 0x05, "Secure Door";
+        SecureGate = 0x06, "Secure Gate";
+        SecureBarrierAddon = 0x07, "Secure Barrier Add-on";
+        SecureBarrierOpenOnly = 0x08, "Secure Barrier Open only";
+        SecureBarrierCloseOnly = 0x09, "Secure Barrier Close only";
+        SecureLockbox = 0x0a, "Secure Lockbox";
+        SecureKeypad = 0x0b, "Secure Keypad";
+    }
+    SemiInteroperable = 0x50, "Semi Interoperable" {
+        EnergyProduction = 0x01, "Energy Production";
+    }
+    AlarmSensor = 0xa1, "Alarm Sensor" {
+        BasicRoutingAlarmSensor = 0x01, "Basic Routing Alarm Sensor";
+        RoutingAlarmSensor = 0x02, "Routing Alarm Sensor";
+        BasicZensorNetAlarmSensor = 0x03, "Basic Zensor Net Alarm Sensor";
+        ZensorNetAlarmSensor = 0x04, "Zensor Net Alarm Sensor";
+        AdvancedZensorNetAlarmSensor = 0x05, "Advanced Zensor Net Alarm Sensor";
+        BasicRoutingSmokeSensor = 0x06, "Basic Routing Smoke Sensor";
+        RoutingSmokeSensor = 0x07, "Routing Smoke Sensor";
+        BasicZensorNetSmokeSensor = 0x08, "Basic Zensor Net Smoke Sensor";
+        ZensorNetSmokeSensor = 0x09, "Zensor Net Smoke Sensor";
+        AdvancedZensorNetSmokeSensor = 0x0a, "Advanced Zensor Net Smoke Sensor";
+        SpecificAlarmSensor = 0x0b, "Alarm Sensor";
+    }
+    NonInteroperable = 0xff, "Non-Interoperable" {}
+}
+
+impl GenericDeviceClass {
+    /// The CC that Basic CC commands of devices with this class correspond to, if any.
+    /// Reports of the Basic CC are mapped to this CC if the device supports it.
+    pub fn basic_cc_mapping(&self) -> Option<CommandClasses> {
+        match self {
+            Self::BinarySwitch => Some(CommandClasses::BinarySwitch),
+            Self::MultilevelSwitch | Self::WindowCovering => Some(CommandClasses::MultilevelSwitch),
+            Self::EntryControl => Some(CommandClasses::DoorLock),
+            Self::BinarySensor => Some(CommandClasses::BinarySensor),
+            Self::MultilevelSensor => Some(CommandClasses::MultilevelSensor),
+            _ => None,
+        }
+    }
+
+    /// Whether devices with this class may support the Basic CC. Controllers do not,
+    /// even if they list it as supported.
+    pub fn may_support_basic_cc(&self) -> bool {
+        !matches!(
+            self,
+            Self::GenericController | Self::StaticController | Self::NonInteroperable
+        )
+    }
+}
+
+impl Display for GenericDeviceClass {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unknown(key) => write!(f, "Unknown (0x{:02x})", key),
+            _ => write!(f, "{}", self.label()),
+        }
+    }
+}
+
+impl Display for SpecificDeviceClass {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unknown(key) => write!(f, "Unknown (0x{:02x})", key),
+            _ => write!(f, "{}", self.label()),
+        }
+    }
+}
+
+/// The device class of a node or endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceClass {
+    pub basic: BasicDeviceType,
+    pub generic: GenericDeviceClass,
+    pub specific: Option<SpecificDeviceClass>,
+}
+
+impl DeviceClass {
+    /// Looks up the device class from the keys used in the node information
+    pub fn new(basic: BasicDeviceType, generic: u8, specific: Option<u8>) -> Self {
+        let generic = GenericDeviceClass::from(generic);
+        Self {
+            basic,
+            generic,
+            specific: specific.and_then(|key| SpecificDeviceClass::new(generic, key)),
+        }
+    }
+}
+
+impl Display for DeviceClass {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.specific {
+            Some(specific) => write!(f, "{} / {} ({})", self.generic, specific, self.basic),
+            None => write!(f, "{} ({})", self.generic, self.basic),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let class = DeviceClass::new(BasicDeviceType::EndNode, 0x40, Some(0x03));
+        assert_eq!(class.generic, GenericDeviceClass::EntryControl);
+        assert_eq!(
+            class.specific,
+            Some(SpecificDeviceClass::SecureKeypadDoorLock)
+        );
+        assert_eq!(
+            class.to_string(),
+            "Entry Control / Secure Keypad Door Lock (End Node)"
+        );
+        assert_eq!(u8::from(class.generic), 0x40);
+        assert_eq!(class.specific.map(u8::from), Some(0x03));
+
+        // The same specific key means different things in different generic classes
+        assert_eq!(
+            SpecificDeviceClass::new(GenericDeviceClass::BinarySwitch, 0x03),
+            Some(SpecificDeviceClass::BinarySceneSwitch)
+        );
+        // 0 means that no specific device class is used
+        assert_eq!(
+            SpecificDeviceClass::new(GenericDeviceClass::BinarySwitch, 0x00),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unknown() {
+        let class = DeviceClass::new(BasicDeviceType::EndNode, 0x7e, Some(0x42));
+        assert_eq!(class.generic, GenericDeviceClass::Unknown(0x7e));
+        assert_eq!(class.specific, Some(SpecificDeviceClass::Unknown(0x42)));
+        assert_eq!(
+            class.to_string(),
+            "Unknown (0x7e) / Unknown (0x42) (End Node)"
+        );
+        assert_eq!(u8::from(class.generic), 0x7e);
+    }
+}
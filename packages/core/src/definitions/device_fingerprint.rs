@@ -22,4 +22,20 @@ impl DeviceFingerprint {
             firmware_version,
         }
     }
+
+    pub fn manufacturer_id(&self) -> Id16 {
+        self.manufacturer_id
+    }
+
+    pub fn product_type(&self) -> Id16 {
+        self.product_type
+    }
+
+    pub fn product_id(&self) -> Id16 {
+        self.product_id
+    }
+
+    pub fn firmware_version(&self) -> Version {
+        self.firmware_version
+    }
 }
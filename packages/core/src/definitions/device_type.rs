@@ -5,7 +5,7 @@ use crate::prelude::*;
 use proc_macros::TryFromRepr;
 use core::fmt::Display;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromRepr)]
 #[repr(u8)]
 pub enum BasicDeviceType {
     PortableController = 0x01,
@@ -3,7 +3,7 @@ use crate::parse::{
     combinators::{context, map_res},
 };
 use crate::prelude::*;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use proc_macros::TryFromRepr;
 use ux::{u1, u2};
 
@@ -76,3 +76,19 @@ impl Parsable for FrameInfo {
         })
     }
 }
+
+impl Serializable for FrameInfo {
+    fn serialize(&self, output: &mut BytesMut) {
+        use crate::serialize::bits::bits;
+        bits(move |bo| {
+            self.foreign_home_id.write(bo);
+            self.foreign_target_node.write(bo);
+            self.explorer_frame.write(bo);
+            self.frame_addressing.write(bo);
+            u1::new(0).write(bo);
+            self.low_power.write(bo);
+            u1::new(0).write(bo);
+        })
+        .serialize(output)
+    }
+}
@@ -36,7 +36,7 @@ pub enum FunctionType {
     SetRFReceiveMode = 0x10, // Power the RF section of the stick down/up
     UNKNOWN_FUNC_SET_SLEEP_MODE = 0x11, // Set the CPU into sleep mode
 
-    FUNC_ID_ZW_SEND_NODE_INFORMATION = 0x12, // Send Node Information Frame of the stick
+    SendNodeInformation = 0x12, // Send Node Information Frame of the stick
 
     SendData = 0x13,          // Send data
     SendDataMulticast = 0x14, // Send data using multicast
@@ -86,8 +86,8 @@ pub enum FunctionType {
     HardReset = 0x42,           // Reset controller and node info to default (original) values
 
     FUNC_ID_ZW_NEW_CONTROLLER = 0x43, // Not implemented
-    FUNC_ID_ZW_REPLICATION_COMMAND_COMPLETE = 0x44, // Replication send data complete
-    FUNC_ID_ZW_REPLICATION_SEND_DATA = 0x45, // Replication send data
+    ReplicationCommandComplete = 0x44, // Replication send data complete
+    ReplicationSendData = 0x45, // Replication send data
     AssignReturnRoute = 0x46, // Assign a return route from the source node to the destination node
     DeleteReturnRoute = 0x47, // Delete all return routes from the specified node
     RequestNodeNeighborUpdate = 0x48, // Ask the specified node to update its neighbors (then read them from the controller)
@@ -101,7 +101,7 @@ pub enum FunctionType {
 
     AssignPriorityReturnRoute = 0x4f, // Assign a priority route between two nodes
 
-    FUNC_ID_ZW_SET_LEARN_MODE = 0x50, // Put a controller into learn mode for replication/ receipt of configuration info
+    SetLearnMode = 0x50, // Put a controller into learn mode for replication/ receipt of configuration info
     AssignSUCReturnRoute = 0x51,      // Assign a return route to the SUC
     FUNC_ID_ZW_ENABLE_SUC = 0x52,     // Make a controller a Static Update Controller
     FUNC_ID_ZW_REQUEST_NETWORK_UPDATE = 0x53, // Network update for a SUC(?)
@@ -178,6 +178,9 @@ pub enum FunctionType {
     UNKNOWN_FUNC_UNKNOWN_0xD4 = 0xd4, // ??
 
     Shutdown = 0xd9, // Instruct the Z-Wave API to shut down in order to safely remove the power
+    GetLongRangeNodes = 0xda, // Get the Long Range node IDs in the network
+    GetLongRangeChannel = 0xdb, // Get the channel used for Long Range communication
+    SetLongRangeChannel = 0xdc, // Set the channel used for Long Range communication
 
     UNKNOWN_FUNC_UNKNOWN_0xEF = 0xef, // ??
 
@@ -0,0 +1,43 @@
+use crate::parse::{
+    bytes::be_u8,
+    combinators::{context, map_res},
+};
+use crate::prelude::*;
+use crate::serialize::{self, Serializable};
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::TryFromRepr;
+
+/// The channel a controller uses for Z-Wave Long Range communication
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum LongRangeChannel {
+    Unsupported = 0x00,
+    A = 0x01,
+    B = 0x02,
+    Auto = 0xff,
+}
+
+impl Display for LongRangeChannel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LongRangeChannel::Unsupported => write!(f, "Unsupported"),
+            LongRangeChannel::A => write!(f, "Channel A (912 MHz)"),
+            LongRangeChannel::B => write!(f, "Channel B (920 MHz)"),
+            LongRangeChannel::Auto => write!(f, "Automatic"),
+        }
+    }
+}
+
+impl Parsable for LongRangeChannel {
+    fn parse(i: &mut Bytes) -> crate::parse::ParseResult<Self> {
+        context("LongRangeChannel", map_res(be_u8, Self::try_from)).parse(i)
+    }
+}
+
+impl Serializable for LongRangeChannel {
+    fn serialize(&self, output: &mut BytesMut) {
+        use serialize::bytes::be_u8;
+        be_u8(*self as u8).serialize(output)
+    }
+}
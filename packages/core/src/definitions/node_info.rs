@@ -3,12 +3,13 @@ use crate::parse::{
     bits::{bits, bool},
     bytes::be_u8,
     combinators::cond,
-    multi::fixed_length_cc_list_only_supported,
+    multi::fixed_length_cc_list,
 };
 use crate::prelude::*;
-use bytes::Bytes;
+use crate::serialize;
+use bytes::{Bytes, BytesMut};
 use tinyvec::TinyVec;
-use ux::{u1, u2, u5};
+use ux::{u1, u2, u3, u5};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct NodeInformationProtocolData {
@@ -38,6 +39,17 @@ pub struct NodeInformationProtocolData {
     pub specific_device_class: Option<u8>,
 }
 
+impl NodeInformationProtocolData {
+    /// The device class of this node
+    pub fn device_class(&self) -> DeviceClass {
+        DeviceClass::new(
+            self.basic_device_type,
+            self.generic_device_class,
+            self.specific_device_class,
+        )
+    }
+}
+
 impl Parsable for NodeInformationProtocolData {
     fn parse(i: &mut Bytes) -> crate::parse::ParseResult<Self> {
         let (listening, routing, _reserved5, speed_40k, speed_9k6, protocol_version) = bits((
@@ -98,6 +110,40 @@ impl Parsable for NodeInformationProtocolData {
     }
 }
 
+impl Serializable for NodeInformationProtocolData {
+    fn serialize(&self, output: &mut BytesMut) {
+        use serialize::{bits::bits, bytes::be_u8};
+
+        let supports = |rate| self.supported_data_rates.contains(&rate);
+        let end_node = self.node_type == NodeType::EndNode;
+        bits(move |bo| {
+            self.listening.write(bo);
+            self.routing.write(bo);
+            u1::new(0).write(bo);
+            supports(DataRate::DataRate_40k).write(bo);
+            supports(DataRate::DataRate_9k6).write(bo);
+            u3::new(self.protocol_version as u8).write(bo);
+
+            self.optional_functionality.write(bo);
+            u2::new(self.frequent_listening.map(|beam| beam as u8).unwrap_or(0)).write(bo);
+            self.beaming.write(bo);
+            end_node.write(bo);
+            self.specific_device_class.is_some().write(bo);
+            (!end_node).write(bo);
+            self.supports_security.write(bo);
+
+            u5::new(0).write(bo);
+            u2::new(0).write(bo);
+            supports(DataRate::DataRate_100k).write(bo);
+        })
+        .serialize(output);
+
+        self.basic_device_type.serialize(output);
+        be_u8(self.generic_device_class).serialize(output);
+        self.specific_device_class.map(be_u8).serialize(output);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct NodeInformationApplicationData {
     /// The basic device type of this node
@@ -108,6 +154,19 @@ pub struct NodeInformationApplicationData {
     pub specific_device_class: u8,
     /// Which command classes are supported by this node
     pub supported_command_classes: Vec<CommandClasses>,
+    /// Which command classes are controlled by this node
+    pub controlled_command_classes: Vec<CommandClasses>,
+}
+
+impl NodeInformationApplicationData {
+    /// The device class of this node
+    pub fn device_class(&self) -> DeviceClass {
+        DeviceClass::new(
+            self.basic_device_type,
+            self.generic_device_class,
+            Some(self.specific_device_class),
+        )
+    }
 }
 
 impl Parsable for NodeInformationApplicationData {
@@ -117,14 +176,62 @@ impl Parsable for NodeInformationApplicationData {
         let basic_device_type = BasicDeviceType::parse(i)?;
         let generic_device_class = be_u8(i)?;
         let specific_device_class = be_u8(i)?;
-        let supported_command_classes =
-            fixed_length_cc_list_only_supported(i, (remaining_len - 3) as usize)?;
+        let (supported_command_classes, controlled_command_classes) =
+            fixed_length_cc_list(i, (remaining_len - 3) as usize)?;
 
         Ok(Self {
             basic_device_type,
             generic_device_class,
             specific_device_class,
             supported_command_classes,
+            controlled_command_classes,
         })
     }
 }
+
+impl Serializable for NodeInformationApplicationData {
+    fn serialize(&self, output: &mut BytesMut) {
+        use serialize::bytes::{be_u8, slice};
+
+        let mut cc_list = BytesMut::new();
+        for cc in &self.supported_command_classes {
+            cc.serialize(&mut cc_list);
+        }
+        if !self.controlled_command_classes.is_empty() {
+            be_u8(COMMAND_CLASS_SUPPORT_CONTROL_MARK).serialize(&mut cc_list);
+            for cc in &self.controlled_command_classes {
+                cc.serialize(&mut cc_list);
+            }
+        }
+
+        // See parse() - the length includes the device class bytes
+        be_u8(cc_list.len() as u8 + 3).serialize(output);
+        self.basic_device_type.serialize(output);
+        be_u8(self.generic_device_class).serialize(output);
+        be_u8(self.specific_device_class).serialize(output);
+        slice(cc_list).serialize(output);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_application_data_roundtrip() {
+        let node_info = NodeInformationApplicationData {
+            basic_device_type: BasicDeviceType::RoutingEndNode,
+            generic_device_class: 0x10,
+            specific_device_class: 0x01,
+            supported_command_classes: vec![CommandClasses::BinarySwitch, CommandClasses::Version],
+            controlled_command_classes: vec![CommandClasses::Basic],
+        };
+        let raw = node_info.as_bytes();
+        assert_eq!(
+            &raw,
+            [0x07, 0x04, 0x10, 0x01, 0x25, 0x86, 0xef, 0x20].as_slice()
+        );
+        let parsed = NodeInformationApplicationData::parse(&mut raw.clone()).unwrap();
+        assert_eq!(parsed, node_info);
+    }
+}
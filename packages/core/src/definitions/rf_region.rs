@@ -48,6 +48,56 @@ impl Display for RfRegion {
     }
 }
 
+/// What a controller may do in an RF region
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RfRegionCapabilities {
+    /// The highest TX power the controller may use for Z-Wave, in dBm
+    pub max_tx_power: f32,
+    /// The highest TX power the controller may use for Z-Wave Long Range, in dBm,
+    /// or `None` if Long Range is not available
+    pub max_long_range_tx_power: Option<f32>,
+    /// The frequencies of the Z-Wave channels, in MHz
+    pub channels: &'static [f32],
+}
+
+impl RfRegionCapabilities {
+    pub fn supports_long_range(&self) -> bool {
+        self.max_long_range_tx_power.is_some()
+    }
+}
+
+impl RfRegion {
+    /// Returns what a controller may do in this region, or `None` if the region is unknown
+    pub fn capabilities(&self) -> Option<RfRegionCapabilities> {
+        let (max_tx_power, max_long_range_tx_power, channels): (f32, Option<f32>, &[f32]) =
+            match self {
+                RfRegion::EU | RfRegion::Default => (14.0, None, &[868.4, 868.42]),
+                RfRegion::US => (14.0, None, &[908.42, 916.0]),
+                RfRegion::ANZ => (14.0, None, &[919.8, 921.4]),
+                RfRegion::HK => (14.0, None, &[919.8]),
+                RfRegion::IN => (14.0, None, &[865.2]),
+                RfRegion::IL => (14.0, None, &[916.0]),
+                RfRegion::RU => (14.0, None, &[869.0]),
+                RfRegion::CN => (14.0, None, &[868.4]),
+                RfRegion::US_LongRange => (14.0, Some(20.0), &[908.42, 916.0]),
+                RfRegion::JP => (10.0, None, &[922.5, 923.9, 926.3]),
+                RfRegion::KR => (10.0, None, &[920.9, 921.7, 923.1]),
+                RfRegion::Unknown => return None,
+            };
+        Some(RfRegionCapabilities {
+            max_tx_power,
+            max_long_range_tx_power,
+            channels,
+        })
+    }
+
+    /// Whether Z-Wave Long Range can be used in this region
+    pub fn supports_long_range(&self) -> bool {
+        self.capabilities()
+            .is_some_and(|capabilities| capabilities.supports_long_range())
+    }
+}
+
 impl Parsable for RfRegion {
     fn parse(i: &mut Bytes) -> crate::parse::ParseResult<Self> {
         context("RfRegion", map_res(be_u8, Self::try_from)).parse(i)
@@ -60,3 +110,20 @@ impl Serializable for RfRegion {
         be_u8(*self as u8).serialize(output)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        assert!(RfRegion::US_LongRange.supports_long_range());
+        assert!(!RfRegion::US.supports_long_range());
+        assert_eq!(
+            RfRegion::Default.capabilities(),
+            RfRegion::EU.capabilities()
+        );
+        assert_eq!(RfRegion::JP.capabilities().unwrap().channels.len(), 3);
+        assert!(RfRegion::Unknown.capabilities().is_none());
+    }
+}
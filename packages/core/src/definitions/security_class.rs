@@ -1,3 +1,5 @@
+use core::fmt::Display;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u8)]
 pub enum SecurityClass {
@@ -6,3 +8,21 @@ pub enum SecurityClass {
     S2AccessControl = 2,
     S0Legacy = 7,
 }
+
+impl SecurityClass {
+    /// Whether this is one of the S2 security classes
+    pub fn is_s2(&self) -> bool {
+        !matches!(self, SecurityClass::S0Legacy)
+    }
+}
+
+impl Display for SecurityClass {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SecurityClass::S2Unauthenticated => write!(f, "S2 Unauthenticated"),
+            SecurityClass::S2Authenticated => write!(f, "S2 Authenticated"),
+            SecurityClass::S2AccessControl => write!(f, "S2 Access Control"),
+            SecurityClass::S0Legacy => write!(f, "S0 Legacy"),
+        }
+    }
+}
@@ -183,9 +183,145 @@ impl TransmitReport {
     }
 }
 
+/// The route a command took to its destination, as described by the transmit report
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingAttempt {
+    /// The nodes along the route, from the source to the destination
+    pub route: Vec<NodeId>,
+    /// Transmission speed used in the last attempt
+    pub route_speed: ProtocolDataRate,
+    /// How many routing attempts have been made to transmit the payload
+    pub attempts: u8,
+    /// When the route failed, where the failure occurred along the route
+    pub route_fail_location: Option<RouteFailLocation>,
+}
+
+/// Formats the route compactly, e.g. `1 → 7 → 12, 100 kbit/s, 2 attempts`
+impl Display for RoutingAttempt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (index, node_id) in self.route.iter().enumerate() {
+            if index > 0 {
+                write!(f, " → ")?;
+            }
+            write!(f, "{:?}", node_id)?;
+        }
+        match self.route_speed {
+            ProtocolDataRate::ZWave(rate) => write!(f, ", {}", rate)?,
+            ProtocolDataRate::ZWaveLongRange => write!(f, ", 100 kbit/s (LR)")?,
+        }
+        match self.attempts {
+            1 => write!(f, ", 1 attempt")?,
+            attempts => write!(f, ", {} attempts", attempts)?,
+        }
+        if let Some(location) = &self.route_fail_location {
+            write!(f, ", failed at {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+impl TransmitReport {
+    /// Describes the route of a transmission from the given source to the given destination
+    pub fn routing_attempt(&self, source: NodeId, destination: NodeId) -> RoutingAttempt {
+        let mut route = vec![source];
+        route.extend(
+            self.repeaters
+                .iter()
+                .map(|repeater| NodeId::new(repeater.node_id)),
+        );
+        route.push(destination);
+
+        RoutingAttempt {
+            route,
+            route_speed: self.route_speed,
+            attempts: self.routing_attempts,
+            route_fail_location: self.route_fail_location.clone(),
+        }
+    }
+}
+
 impl Serializable for TransmitReport {
-    fn serialize(&self, _output: &mut BytesMut) {
-        todo!("ERROR: TransmitReport::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut) {
+        use crate::serialize::{
+            bits::bits,
+            bytes::{be_i8, be_u8, be_u16},
+        };
+
+        be_u16(self.tx_ticks).serialize(output);
+        be_u8(self.repeaters.len() as u8).serialize(output);
+        self.ack_rssi
+            .unwrap_or(RSSI::NotAvailable)
+            .serialize(output);
+        for index in 0..4 {
+            self.repeaters
+                .get(index)
+                .and_then(|repeater| repeater.ack_rssi)
+                .unwrap_or(RSSI::NotAvailable)
+                .serialize(output);
+        }
+        be_u8(self.ack_channel_no.unwrap_or(0)).serialize(output);
+        be_u8(self.tx_channel_no).serialize(output);
+        self.routing_scheme.serialize(output);
+        for index in 0..4 {
+            be_u8(
+                self.repeaters
+                    .get(index)
+                    .map_or(0, |repeater| repeater.node_id),
+            )
+            .serialize(output);
+        }
+        bits(move |bo| {
+            u1::new(0).write(bo);
+            u2::new(self.beam.map_or(0, |beam| beam as u8)).write(bo);
+            u2::new(0).write(bo);
+            self.route_speed.write(bo);
+        })
+        .serialize(output);
+        be_u8(self.routing_attempts).serialize(output);
+
+        // The optional fields are only parsed if the previous ones are present,
+        // so missing fields before the last present one are filled with placeholders
+        let optional_fields = [
+            self.route_fail_location.is_some(),
+            self.tx_power.is_some(),
+            self.measured_noise_floor.is_some(),
+            self.destination_ack_tx_power.is_some(),
+            self.destination_ack_measured_rssi.is_some(),
+            self.destination_ack_measured_noise_floor.is_some(),
+        ];
+        let num_optional_fields = optional_fields
+            .iter()
+            .rposition(|present| *present)
+            .map_or(0, |index| index + 1);
+
+        if num_optional_fields > 0 {
+            // 0 means that there is no route fail location
+            let location = self.route_fail_location.as_ref();
+            be_u8(location.map_or(0, |l| l.last_functional_node_id)).serialize(output);
+            be_u8(location.map_or(0, |l| l.first_non_functional_node_id)).serialize(output);
+        }
+        if num_optional_fields > 1 {
+            // 127 is outside the valid TX power range
+            be_i8(self.tx_power.unwrap_or(127)).serialize(output);
+        }
+        if num_optional_fields > 2 {
+            self.measured_noise_floor
+                .unwrap_or(RSSI::NotAvailable)
+                .serialize(output);
+        }
+        if num_optional_fields > 3 {
+            be_i8(self.destination_ack_tx_power.unwrap_or(127)).serialize(output);
+        }
+        if num_optional_fields > 4 {
+            self.destination_ack_measured_rssi
+                .unwrap_or(RSSI::NotAvailable)
+                .serialize(output);
+        }
+        if num_optional_fields > 5 {
+            self.destination_ack_measured_noise_floor
+                .unwrap_or(RSSI::NotAvailable)
+                .serialize(output);
+        }
     }
 }
 
@@ -262,3 +398,59 @@ impl TransmitReport {
         ret
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let raw = Bytes::from_static(&[
+            0x00, 0x0a, // TX ticks
+            0x01, // 1 repeater
+            0xb0, // ACK RSSI
+            0xc0, 0x7f, 0x7f, 0x7f, // repeater RSSI
+            0x00, // ACK channel
+            0x01, // TX channel
+            0x03, // routing scheme: LWR
+            0x05, 0x00, 0x00, 0x00, // repeater node IDs
+            0x03, // 100 kbit/s, no beam
+            0x02, // routing attempts
+            0x00, 0x00, // no route fail location
+            0xfe, // TX power
+            0xa0, // noise floor
+        ]);
+        let report = TransmitReport::parse(&mut raw.clone(), true).unwrap();
+        assert_eq!(report.repeaters.len(), 1);
+        assert_eq!(report.route_fail_location, None);
+        assert_eq!(report.tx_power, Some(-2));
+        assert_eq!(report.destination_ack_tx_power, None);
+
+        assert_eq!(report.as_bytes(), raw);
+    }
+
+    #[test]
+    fn test_routing_attempt_display() {
+        let attempt = RoutingAttempt {
+            route: vec![NodeId::new(3u8), NodeId::new(7u8), NodeId::new(12u8)],
+            route_speed: ProtocolDataRate::ZWave(DataRate::DataRate_100k),
+            attempts: 2,
+            route_fail_location: None,
+        };
+        assert_eq!(attempt.to_string(), "3 → 7 → 12, 100 kbit/s, 2 attempts");
+
+        let attempt = RoutingAttempt {
+            route: vec![NodeId::new(1u8), NodeId::new(5u8)],
+            route_speed: ProtocolDataRate::ZWave(DataRate::DataRate_40k),
+            attempts: 1,
+            route_fail_location: Some(RouteFailLocation {
+                last_functional_node_id: 1,
+                first_non_functional_node_id: 5,
+            }),
+        };
+        assert_eq!(
+            attempt.to_string(),
+            "1 → 5, 40 kbit/s, 1 attempt, failed at 1 ↯ 5"
+        );
+    }
+}
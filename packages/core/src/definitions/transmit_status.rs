@@ -8,6 +8,7 @@ use crate::{
 use bytes::{BytesMut, Bytes};
 use crate::serialize::{self, Serializable};
 use proc_macros::TryFromRepr;
+use core::fmt::Display;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
 #[repr(u8)]
@@ -19,6 +20,18 @@ pub enum TransmitStatus {
     NoRoute = 0x04,
 }
 
+impl Display for TransmitStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TransmitStatus::Ok => write!(f, "OK"),
+            TransmitStatus::NoAck => write!(f, "no ACK"),
+            TransmitStatus::Fail => write!(f, "failed"),
+            TransmitStatus::NotIdle => write!(f, "not idle"),
+            TransmitStatus::NoRoute => write!(f, "no route"),
+        }
+    }
+}
+
 impl Parsable for TransmitStatus {
     fn parse(i: &mut Bytes) -> crate::parse::ParseResult<Self> {
         context("TransmitStatus", map_res(be_u8, TransmitStatus::try_from)).parse(i)
@@ -7,12 +7,20 @@ use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
 use core::fmt::{self, Display};
 
+/// The version of the Z-Wave API (Serial API) the controller implements.
+/// Values below 10 use the legacy numbering, which was replaced by the official one starting at 1.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ZWaveApiVersion {
     Official(u8),
     Legacy(u8),
 }
 
+impl ZWaveApiVersion {
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, Self::Legacy(_))
+    }
+}
+
 impl Display for ZWaveApiVersion {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -143,3 +143,12 @@ impl_int!(i8, 1);
 impl_int!(i16, 2);
 impl_int!(i32, 4);
 impl_int!(i64, 8);
+
+/// Parses a 24-bit big-endian unsigned integer
+pub fn be_u24(input: &mut Bytes) -> ParseResult<u32> {
+    if input.remaining() < 3 {
+        Err(ParseError::Incomplete(Needed::Size(3)))
+    } else {
+        Ok(input.get_uint(3) as u32)
+    }
+}
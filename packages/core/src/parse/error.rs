@@ -14,6 +14,8 @@ pub enum ErrorContext {
     String(Cow<'static, str>),
     NotImplemented(Cow<'static, str>),
     Validation(Cow<'static, str>),
+    /// The data is corrupted, because a checksum did not match
+    Checksum(Cow<'static, str>),
 }
 
 impl Display for ErrorContext {
@@ -22,6 +24,7 @@ impl Display for ErrorContext {
             ErrorContext::None => write!(f, "No context"),
             ErrorContext::String(s)
             | ErrorContext::Validation(s)
+            | ErrorContext::Checksum(s)
             | ErrorContext::NotImplemented(s) => write!(f, "{}", s),
         }
     }
@@ -82,6 +85,15 @@ impl ParseError {
         ParseError::Final(ErrorContext::Validation(ctx.into()))
     }
 
+    pub fn checksum_mismatch(ctx: impl Into<Cow<'static, str>>) -> Self {
+        ParseError::Final(ErrorContext::Checksum(ctx.into()))
+    }
+
+    /// Whether parsing failed because the data is corrupted
+    pub fn is_checksum_mismatch(&self) -> bool {
+        matches!(self, ParseError::Final(ErrorContext::Checksum(_)))
+    }
+
     pub fn context(&self) -> Option<ErrorContext> {
         match self {
             ParseError::Recoverable(ctx) | ParseError::Final(ctx) => Some(ctx.clone()),
@@ -102,6 +114,19 @@ pub fn validate(condition: bool, message: impl Into<Cow<'static, str>>) -> Parse
     }
 }
 
+/// Validates that a checksum matches, otherwise results in a
+/// Parse error indicating that the data is corrupted.
+pub fn validate_checksum(
+    condition: bool,
+    message: impl Into<Cow<'static, str>>,
+) -> ParseResult<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(ParseError::checksum_mismatch(message))
+    }
+}
+
 /// Returns a Parse error indicating that a validation failed.
 pub fn fail_validation<T>(message: impl Into<Cow<'static, str>>) -> ParseResult<T> {
     Err(ParseError::validation_failure(message))
@@ -4,7 +4,7 @@ use super::{
         be_u8,
         complete::{literal, take},
     },
-    combinators::map_parser,
+    combinators::{map_parser, opt},
 };
 use crate::bitvec::iter_ones;
 use crate::prelude::*;
@@ -147,6 +147,22 @@ pub fn fixed_length_bitmask_u8(
     Ok(ret)
 }
 
+/// Parses a list of supported CCs, optionally followed by the support/control mark
+/// and a list of controlled CCs
+pub fn supported_and_controlled_cc_list(
+    i: &mut Bytes,
+) -> ParseResult<(
+    Vec<CommandClasses>, // supported
+    Vec<CommandClasses>, // controlled
+)> {
+    let supported = many_0(CommandClasses::parse).parse(i)?;
+    let controlled = match opt(literal(COMMAND_CLASS_SUPPORT_CONTROL_MARK)).parse(i)? {
+        Some(_) => many_0(CommandClasses::parse).parse(i)?,
+        None => Vec::new(),
+    };
+    Ok((supported, controlled))
+}
+
 /// Parses a list of supported and controlled CCs that starts with a length byte
 pub fn variable_length_cc_list(
     i: &mut Bytes,
@@ -154,15 +170,7 @@ pub fn variable_length_cc_list(
     Vec<CommandClasses>, // supported
     Vec<CommandClasses>, // controlled
 )> {
-    map_parser(
-        length_data(be_u8),
-        separated_pair(
-            many_0(CommandClasses::parse),
-            literal(COMMAND_CLASS_SUPPORT_CONTROL_MARK),
-            many_0(CommandClasses::parse),
-        ),
-    )
-    .parse(i)
+    map_parser(length_data(be_u8), supported_and_controlled_cc_list).parse(i)
 }
 
 /// Parses a list of supported and controlled CCs with the given length
@@ -173,15 +181,7 @@ pub fn fixed_length_cc_list(
     Vec<CommandClasses>, // supported
     Vec<CommandClasses>, // controlled
 )> {
-    map_parser(
-        take(len),
-        separated_pair(
-            many_0(CommandClasses::parse),
-            literal(COMMAND_CLASS_SUPPORT_CONTROL_MARK),
-            many_0(CommandClasses::parse),
-        ),
-    )
-    .parse(i)
+    map_parser(take(len), supported_and_controlled_cc_list).parse(i)
 }
 
 /// Parses a list of supported (NOT controlled) CCs with the given length
@@ -3,5 +3,7 @@ pub use crate::log::{
     LogPayload, LogPayloadDict, LogPayloadDictValue, LogPayloadList, LogPayloadText, ToLogPayload,
 };
 pub use crate::parse::{BitParsable, Parsable, ParseError, ParseResult, Parser, TryFromReprError};
-pub use crate::serialize::{BitOutput, BitSerializable, Serializable, SerializableWith};
+pub use crate::serialize::{
+    BitOutput, BitSerializable, Serializable, SerializableWith, TrySerializableWith,
+};
 pub use crate::values::*;
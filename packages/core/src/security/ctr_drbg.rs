@@ -7,6 +7,7 @@ use zwave_pal::prelude::*;
 pub const KEY_LEN: usize = 16;
 pub const BLOCK_LEN: usize = 16;
 pub const SEED_LEN: usize = ENTROPY_SIZE;
+pub const STATE_LEN: usize = KEY_LEN + BLOCK_LEN;
 
 // Warning: This code expects ctr_len to equal BLOCK_LEN.
 // See specification on how to handle other cases
@@ -74,6 +75,24 @@ impl CtrDrbg {
         self.v.copy_from_slice(v);
     }
 
+    /// Restores a generator from its exported internal state
+    pub fn from_state(state: [u8; STATE_LEN]) -> Self {
+        let (key, v) = state.split_at(KEY_LEN);
+        let key: [u8; KEY_LEN] = key.try_into().unwrap();
+        Self {
+            v: v.try_into().unwrap(),
+            key: key.into(),
+        }
+    }
+
+    /// Exports the internal state of the generator. Anyone who knows it can predict the output.
+    pub fn export_state(&self) -> [u8; STATE_LEN] {
+        let mut state = [0; STATE_LEN];
+        state[..KEY_LEN].copy_from_slice(self.key.as_ref());
+        state[KEY_LEN..].copy_from_slice(&self.v);
+        state
+    }
+
     pub fn generate(&mut self, bytes: usize) -> Vec<u8> {
         // Additional input is not used
         let num_blocks = bytes / BLOCK_LEN + if bytes % BLOCK_LEN == 0 { 0 } else { 1 };
@@ -102,53 +102,94 @@ fn generate_enc_key(network_key: &NetworkKey) -> AesKey {
     encrypt_aes_ecb(&ENC_KEY_BASE, &network_key).into()
 }
 
+/// The keys used for S0 encryption and authentication, derived from a network key.
+/// Deriving them is relatively expensive, so this is only done once per network key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct S0Keys {
+    auth_key: AesKey,
+    enc_key: AesKey,
+}
+
+impl S0Keys {
+    pub fn derive(network_key: &NetworkKey) -> Self {
+        Self {
+            auth_key: generate_auth_key(network_key),
+            enc_key: generate_enc_key(network_key),
+        }
+    }
+
+    pub fn auth_key(&self) -> &AesKey {
+        &self.auth_key
+    }
+
+    pub fn enc_key(&self) -> &AesKey {
+        &self.enc_key
+    }
+}
+
+/// Counters for the nonces handled by a [`SecurityManager`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct S0NonceStatistics {
+    /// How many nonces were generated for other nodes
+    pub generated: u32,
+    /// How many nonces were received from other nodes
+    pub received: u32,
+    /// How many nonces were successfully retrieved to en- or decrypt a command
+    pub used: u32,
+    /// How many nonces were requested, but were unknown or had already been used
+    pub missing: u32,
+}
+
 struct SecurityManagerState {
+    /// The current network key
+    network_key: NetworkKey,
+    /// The keys of the current network key
+    keys: S0Keys,
+    /// Keys of previous network keys that are still accepted while decrypting, newest first
+    previous_keys: Vec<S0Keys>,
+    /// Keys that are used instead of the current keys for commands sent to specific nodes
+    temp_keys: BTreeMap<NodeId, S0Keys>,
     nonce_store: BTreeMap<NonceKey, NonceEntry>,
     free_nonces: BTreeMap<NodeId, NonceKey>,
     receiver_nonces: BTreeMap<NodeId, NonceKey>,
+    statistics: S0NonceStatistics,
 }
 
-pub struct SecurityManagerStorage {
+struct SecurityManagerInner {
     own_node_id: NodeId,
-    network_key: NetworkKey,
-    auth_key: AesKey,
-    enc_key: AesKey,
     state: Locked<SecurityManagerState>,
 }
 
-impl SecurityManagerStorage {
-    pub fn new(options: SecurityManagerOptions) -> Self {
-        let auth_key = generate_auth_key(&options.network_key);
-        let enc_key = generate_enc_key(&options.network_key);
+/// Manages the keys and nonces for S0. Clones share the same state.
+#[derive(Clone)]
+pub struct SecurityManager {
+    inner: Arc<SecurityManagerInner>,
+}
 
-        Self {
+impl SecurityManager {
+    pub fn new(options: SecurityManagerOptions) -> Self {
+        let inner = SecurityManagerInner {
             own_node_id: options.own_node_id,
-            network_key: options.network_key,
-            auth_key,
-            enc_key,
             state: Locked::new(SecurityManagerState {
+                keys: S0Keys::derive(&options.network_key),
+                network_key: options.network_key,
+                previous_keys: Vec::new(),
+                temp_keys: BTreeMap::new(),
                 nonce_store: BTreeMap::new(),
                 free_nonces: BTreeMap::new(),
                 receiver_nonces: BTreeMap::new(),
+                statistics: S0NonceStatistics::default(),
             }),
+        };
+        Self {
+            inner: Arc::new(inner),
         }
     }
-}
-
-#[derive(Clone)]
-pub struct SecurityManager {
-    storage: Arc<SecurityManagerStorage>,
-}
-
-impl SecurityManager {
-    pub fn new(storage: Arc<SecurityManagerStorage>) -> Self {
-        Self { storage }
-    }
 
     fn has_nonce(&self, nonce_id: u8) -> bool {
-        self.storage.state.inspect(|state| {
+        self.inner.state.inspect(|state| {
             state.nonce_store.contains_key(&NonceKey {
-                issuer: self.storage.own_node_id,
+                issuer: self.inner.own_node_id,
                 nonce_id,
             })
         })
@@ -164,18 +205,29 @@ impl SecurityManager {
         };
 
         // Store it
-        self.set_nonce(self.storage.own_node_id, receiver, nonce.clone(), false);
+        self.store_nonce(self.inner.own_node_id, receiver, nonce.clone(), false);
+        self.inner
+            .state
+            .update(|state| state.statistics.generated += 1);
 
         nonce
     }
 
+    /// Stores a nonce that was received from another node
     pub fn set_nonce(&self, issuer: NodeId, receiver: NodeId, nonce: S0Nonce, free: bool) {
+        self.store_nonce(issuer, receiver, nonce, free);
+        self.inner
+            .state
+            .update(|state| state.statistics.received += 1);
+    }
+
+    fn store_nonce(&self, issuer: NodeId, receiver: NodeId, nonce: S0Nonce, free: bool) {
         let key = NonceKey {
             issuer,
             nonce_id: nonce.id(),
         };
 
-        self.storage.state.update(|state| {
+        self.inner.state.update(|state| {
             // If there is an existing nonce for the same receiver, remove it
             if let Some(existing_key) = state.receiver_nonces.get(&receiver) {
                 state.nonce_store.remove(existing_key);
@@ -199,7 +251,7 @@ impl SecurityManager {
     /// Deletes a specific nonce if it exists
     fn delete_nonce(&self, issuer: NodeId, nonce_id: u8) {
         let key = NonceKey { issuer, nonce_id };
-        self.storage.state.update(|state| {
+        self.inner.state.update(|state| {
             // Remove the entry from the nonce store
             let old = state.nonce_store.remove(&key);
 
@@ -219,7 +271,7 @@ impl SecurityManager {
     /// Deletes the nonce stored for a given receiver
     pub fn delete_nonce_for_receiver(&self, receiver: NodeId) {
         let key = self
-            .storage
+            .inner
             .state
             .update(|state| state.receiver_nonces.remove(&receiver));
         if let Some(NonceKey { issuer, nonce_id }) = key {
@@ -229,41 +281,203 @@ impl SecurityManager {
 
     /// Deletes a nonce that was issued by ourselves
     pub fn delete_own_nonce(&self, nonce_id: u8) {
-        self.delete_nonce(self.storage.own_node_id, nonce_id);
+        self.delete_nonce(self.inner.own_node_id, nonce_id);
     }
 
     /// Tries to retrieve a specific nonce issued by ourselves. The same nonce
     /// can only be retrieved once.
     pub fn try_get_own_nonce(&self, nonce_id: u8) -> Option<S0Nonce> {
-        self.try_get_nonce(self.storage.own_node_id, nonce_id)
+        self.try_get_nonce(self.inner.own_node_id, nonce_id)
     }
 
     /// Tries to retrieve a specific nonce by ID for a given node. The same nonce
     /// can only be retrieved once.
     pub fn try_get_nonce(&self, issuer: NodeId, nonce_id: u8) -> Option<S0Nonce> {
         let key = NonceKey { issuer, nonce_id };
-        self.storage.state.update(|state| {
+        self.inner.state.update(|state| {
             // If the nonce was previously free, it no longer is
             state.free_nonces.remove(&issuer);
             // And return the nonce if it was found
-            state.nonce_store.remove(&key).map(|entry| entry.nonce)
+            let nonce = state.nonce_store.remove(&key).map(|entry| entry.nonce);
+            if nonce.is_some() {
+                state.statistics.used += 1;
+            } else {
+                state.statistics.missing += 1;
+            }
+            nonce
         })
     }
 
     /// Tries to claim a nonce that is not reserved for a specific transaction.
     /// If a nonce is found, it is no longer considered free afterwards
     pub fn try_claim_nonce(&self, issuer: NodeId) -> Option<S0Nonce> {
-        self.storage.state.update(|state| {
-            let key = state.free_nonces.remove(&issuer)?;
-            state.nonce_store.get(&key).map(|entry| entry.nonce.clone())
+        self.inner.state.update(|state| {
+            let nonce = state
+                .free_nonces
+                .remove(&issuer)
+                .and_then(|key| state.nonce_store.get(&key))
+                .map(|entry| entry.nonce.clone());
+            if nonce.is_some() {
+                state.statistics.used += 1;
+            } else {
+                state.statistics.missing += 1;
+            }
+            nonce
         })
     }
 
-    pub fn auth_key(&self) -> &AesKey {
-        &self.storage.auth_key
+    /// Returns the current network key
+    pub fn network_key(&self) -> NetworkKey {
+        self.inner.state.inspect(|state| state.network_key.clone())
     }
 
-    pub fn enc_key(&self) -> &AesKey {
-        &self.storage.enc_key
+    /// Returns the keys that are used to encrypt and authenticate commands
+    pub fn keys(&self) -> S0Keys {
+        self.inner.state.inspect(|state| state.keys)
+    }
+
+    /// Returns the keys that are used to encrypt and authenticate commands for the given node.
+    /// These are the current keys, unless a temporary key was set for the node.
+    pub fn keys_for(&self, node_id: NodeId) -> S0Keys {
+        self.inner
+            .state
+            .inspect(|state| state.temp_keys.get(&node_id).copied().unwrap_or(state.keys))
+    }
+
+    /// Encrypts the commands for the given node with a temporary network key until
+    /// [`delete_temp_key`](Self::delete_temp_key) is called. This is needed during the
+    /// S0 bootstrapping, where the network key is sent encrypted with a key of all zeros.
+    pub fn set_temp_key(&self, node_id: NodeId, network_key: &NetworkKey) {
+        let keys = S0Keys::derive(network_key);
+        self.inner.state.update(|state| {
+            state.temp_keys.insert(node_id, keys);
+        });
+    }
+
+    /// Goes back to encrypting the commands for the given node with the current network key
+    pub fn delete_temp_key(&self, node_id: NodeId) {
+        self.inner.state.update(|state| {
+            state.temp_keys.remove(&node_id);
+        });
+    }
+
+    /// Returns all keys that may be used to decrypt commands: The current keys first,
+    /// followed by the keys of previous network keys, newest first
+    pub fn decryption_keys(&self) -> Vec<S0Keys> {
+        self.inner.state.inspect(|state| {
+            core::iter::once(state.keys)
+                .chain(state.previous_keys.iter().copied())
+                .collect()
+        })
+    }
+
+    /// Switches to a new network key. Commands encrypted with the previous key are
+    /// still accepted until [`clear_previous_keys`](Self::clear_previous_keys) is called.
+    pub fn rotate_network_key(&self, network_key: &NetworkKey) {
+        let keys = S0Keys::derive(network_key);
+        self.inner.state.update(|state| {
+            if state.keys == keys {
+                return;
+            }
+            state.network_key = network_key.clone();
+            let previous = core::mem::replace(&mut state.keys, keys);
+            state.previous_keys.retain(|k| *k != keys);
+            state.previous_keys.insert(0, previous);
+        });
+    }
+
+    /// Stops accepting commands that were encrypted with a previous network key
+    pub fn clear_previous_keys(&self) {
+        self.inner
+            .state
+            .update(|state| state.previous_keys.clear());
+    }
+
+    pub fn nonce_statistics(&self) -> S0NonceStatistics {
+        self.inner.state.inspect(|state| state.statistics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sec_man(own_node_id: u8, network_key: &NetworkKey) -> SecurityManager {
+        SecurityManager::new(SecurityManagerOptions {
+            own_node_id: NodeId::new(own_node_id),
+            network_key: network_key.clone(),
+        })
+    }
+
+    #[test]
+    fn test_clones_share_state() {
+        let sec_man = sec_man(1, &NetworkKey::from([0x11; 16]));
+        let clone = sec_man.clone();
+        let nonce = sec_man.generate_nonce(NodeId::new(2u8));
+
+        assert_eq!(clone.try_get_own_nonce(nonce.id()), Some(nonce.clone()));
+        assert_eq!(sec_man.try_get_own_nonce(nonce.id()), None);
+    }
+
+    #[test]
+    fn test_nonce_statistics() {
+        let sec_man = sec_man(1, &NetworkKey::from([0x11; 16]));
+        let own = sec_man.generate_nonce(NodeId::new(2u8));
+        sec_man.set_nonce(NodeId::new(2u8), NodeId::new(1u8), S0Nonce::random(), true);
+
+        assert!(sec_man.try_get_own_nonce(own.id()).is_some());
+        assert!(sec_man.try_get_own_nonce(own.id()).is_none());
+        assert!(sec_man.try_claim_nonce(NodeId::new(2u8)).is_some());
+
+        assert_eq!(
+            sec_man.nonce_statistics(),
+            S0NonceStatistics {
+                generated: 1,
+                received: 1,
+                used: 2,
+                missing: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_rotate_network_key() {
+        let old_key = NetworkKey::from([0x11; 16]);
+        let new_key = NetworkKey::from([0x22; 16]);
+        let sec_man = sec_man(1, &old_key);
+
+        sec_man.rotate_network_key(&new_key);
+        assert_eq!(sec_man.keys(), S0Keys::derive(&new_key));
+        assert_eq!(
+            sec_man.decryption_keys(),
+            vec![S0Keys::derive(&new_key), S0Keys::derive(&old_key)]
+        );
+
+        // Rotating back does not duplicate keys
+        sec_man.rotate_network_key(&old_key);
+        assert_eq!(
+            sec_man.decryption_keys(),
+            vec![S0Keys::derive(&old_key), S0Keys::derive(&new_key)]
+        );
+
+        sec_man.clear_previous_keys();
+        assert_eq!(sec_man.decryption_keys(), vec![S0Keys::derive(&old_key)]);
+        assert_eq!(sec_man.network_key(), old_key);
+    }
+
+    #[test]
+    fn test_temp_key() {
+        let network_key = NetworkKey::from([0x11; 16]);
+        let temp_key = NetworkKey::from([0x00; 16]);
+        let sec_man = sec_man(1, &network_key);
+
+        sec_man.set_temp_key(NodeId::new(2u8), &temp_key);
+        assert_eq!(sec_man.keys_for(NodeId::new(2u8)), S0Keys::derive(&temp_key));
+        assert_eq!(sec_man.keys_for(NodeId::new(3u8)), S0Keys::derive(&network_key));
+        // Received commands are always decrypted with the network key
+        assert_eq!(sec_man.decryption_keys(), vec![S0Keys::derive(&network_key)]);
+
+        sec_man.delete_temp_key(NodeId::new(2u8));
+        assert_eq!(sec_man.keys_for(NodeId::new(2u8)), S0Keys::derive(&network_key));
     }
 }
@@ -1,8 +1,8 @@
 use super::{
     AES_CCM_NONCE_SIZE, AesCcmNonce, AesKey, DerivedNetworkKeys, DerivedTempKeys,
     ENTROPY_INPUT_SIZE, Entropy, EntropyInput, PERSONALIZATION_STRING_SIZE, PersonalizationString,
-    compute_nonce_prk, ctr_drbg::CtrDrbg, derive_mei, derive_network_keys, encrypt_aes_ecb,
-    network_key::NETWORK_KEY_SIZE, network_key::NetworkKey,
+    compute_nonce_prk, ctr_drbg, ctr_drbg::CtrDrbg, derive_mei, derive_network_keys,
+    encrypt_aes_ecb, network_key::NETWORK_KEY_SIZE, network_key::NetworkKey,
 };
 use crate::{
     definitions::{NodeId, SecurityClass},
@@ -19,6 +19,7 @@ pub const S2_ENTROPY_INPUT_SIZE: usize = ENTROPY_INPUT_SIZE;
 pub const S2_NONCE_SIZE: usize = AES_CCM_NONCE_SIZE;
 pub const S2_MPAN_STATE_SIZE: usize = NETWORK_KEY_SIZE;
 pub const S2_PERSONALIZATION_STRING_SIZE: usize = PERSONALIZATION_STRING_SIZE;
+pub const S2_SPAN_STATE_SIZE: usize = ctr_drbg::STATE_LEN;
 
 const SINGLECAST_MAX_SEQ_NUMS: usize = 1;
 const SINGLECAST_NONCE_EXPIRY: Duration = Duration::from_millis(500);
@@ -208,6 +209,24 @@ impl MPANTableEntry {
     }
 }
 
+/// An established SPAN with a node, which can be restored after a restart, so the nonces don't
+/// need to be resynchronized. The state is as secret as the network keys and must be stored
+/// with the same care.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PersistedSpan {
+    pub security_class: SecurityClass,
+    pub state: [u8; S2_SPAN_STATE_SIZE],
+}
+
+impl core::fmt::Debug for PersistedSpan {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PersistedSpan")
+            .field("security_class", &self.security_class)
+            .field("state", &"<redacted>")
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MulticastGroup {
     pub node_ids: Vec<NodeId>,
@@ -535,6 +554,111 @@ impl SecurityManager2 {
         })
     }
 
+    /// Returns the sequence numbers that were last used for outgoing messages to each node,
+    /// so they can be persisted
+    pub fn own_sequence_numbers(&self) -> BTreeMap<NodeId, u8> {
+        self.storage
+            .state
+            .inspect(|state| state.own_sequence_numbers.clone())
+    }
+
+    /// Restores persisted sequence numbers for outgoing messages.
+    /// The sequence continues after the restored numbers.
+    pub fn restore_own_sequence_numbers(&self, sequence_numbers: &BTreeMap<NodeId, u8>) {
+        self.storage.state.update(|state| {
+            state
+                .own_sequence_numbers
+                .extend(sequence_numbers.iter().map(|(k, v)| (*k, *v)));
+        });
+    }
+
+    /// Returns the SPANs that were established with each node using the permanent network keys,
+    /// so they can be persisted. SPANs that use the temporary keys only exist during bootstrapping
+    /// and are not included.
+    pub fn export_spans(&self) -> BTreeMap<NodeId, PersistedSpan> {
+        self.storage.state.inspect(|state| {
+            state
+                .span_table
+                .iter()
+                .filter_map(|(node_id, entry)| match entry {
+                    SPANTableEntry::SPAN {
+                        key: SecurityKey::Key(security_class),
+                        rng,
+                        ..
+                    } => Some((
+                        *node_id,
+                        PersistedSpan {
+                            security_class: *security_class,
+                            state: rng.export_state(),
+                        },
+                    )),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+
+    /// Restores persisted SPANs. SPANs for security classes without a configured key are ignored,
+    /// and SPANs that were established since the start are kept.
+    pub fn restore_spans(&self, spans: &BTreeMap<NodeId, PersistedSpan>) {
+        self.storage.state.update(|state| {
+            for (node_id, span) in spans {
+                if !is_s2_security_class(span.security_class)
+                    || !state.network_keys.contains_key(&span.security_class)
+                    || state.span_table.contains_key(node_id)
+                {
+                    continue;
+                }
+                state.span_table.insert(
+                    *node_id,
+                    SPANTableEntry::SPAN {
+                        key: SecurityKey::Key(span.security_class),
+                        rng: CtrDrbg::from_state(span.state),
+                        current_span: None,
+                    },
+                );
+            }
+        });
+    }
+
+    /// Returns the MPANs that are in sync with each node and multicast group, so they can be
+    /// persisted. Like the network keys, they must be stored securely.
+    pub fn export_peer_mpans(&self) -> BTreeMap<NodeId, BTreeMap<u8, MpanState>> {
+        self.storage.state.inspect(|state| {
+            state
+                .peer_mpans
+                .iter()
+                .map(|(node_id, groups)| {
+                    let groups: BTreeMap<_, _> = groups
+                        .iter()
+                        .filter_map(|(group_id, entry)| match entry {
+                            MPANTableEntry::MPAN { current_mpan } => {
+                                Some((*group_id, *current_mpan))
+                            }
+                            MPANTableEntry::OutOfSync => None,
+                        })
+                        .collect();
+                    (*node_id, groups)
+                })
+                .filter(|(_, groups)| !groups.is_empty())
+                .collect()
+        })
+    }
+
+    /// Restores persisted MPANs. MPANs that are known since the start are kept.
+    pub fn restore_peer_mpans(&self, mpans: &BTreeMap<NodeId, BTreeMap<u8, MpanState>>) {
+        self.storage.state.update(|state| {
+            for (node_id, groups) in mpans {
+                let entries = state.peer_mpans.entry(*node_id).or_default();
+                for (group_id, current_mpan) in groups {
+                    entries.entry(*group_id).or_insert(MPANTableEntry::MPAN {
+                        current_mpan: *current_mpan,
+                    });
+                }
+            }
+        });
+    }
+
     /// Creates or reuses a multicast group for the given node IDs and remembers the security class.
     ///
     /// The returned value is the group ID to be used in multicast commands.
@@ -845,6 +969,72 @@ mod test {
         assert!(manager.is_duplicate_singlecast(2.into(), 2));
     }
 
+    #[test]
+    fn own_sequence_numbers_can_be_restored() {
+        let manager = create_manager();
+        let first = manager.next_sequence_number(2.into());
+        let exported = manager.own_sequence_numbers();
+
+        let restored = create_manager();
+        restored.restore_own_sequence_numbers(&exported);
+        assert_eq!(
+            restored.next_sequence_number(2.into()),
+            first.wrapping_add(1)
+        );
+    }
+
+    #[test]
+    fn restored_spans_continue_the_nonce_sequence() {
+        let manager = create_manager();
+        manager.set_key(SecurityClass::S2Authenticated, s2_key(1));
+        manager.initialize_span(
+            2.into(),
+            SecurityClass::S2Authenticated,
+            entropy(2),
+            entropy(3),
+        );
+        manager.set_temp_keys(
+            3.into(),
+            derive_temp_keys(&AesKey::from([7; NETWORK_KEY_SIZE])).into(),
+        );
+        manager.initialize_temp_span(3.into(), entropy(2), entropy(3));
+        manager.next_nonce(2.into(), false);
+        let exported = manager.export_spans();
+        // Only the SPAN using the permanent keys is exported
+        assert_eq!(exported.keys().copied().collect::<Vec<_>>(), vec![NodeId::new(2u8)]);
+
+        let restored = create_manager();
+        restored.set_key(SecurityClass::S2Authenticated, s2_key(1));
+        restored.restore_spans(&exported);
+        assert_eq!(
+            restored.next_nonce(2.into(), false),
+            manager.next_nonce(2.into(), false)
+        );
+
+        // Without the network key, the SPAN cannot be used
+        let without_key = create_manager();
+        without_key.restore_spans(&exported);
+        assert_eq!(without_key.get_span_state(2.into()), None);
+    }
+
+    #[test]
+    fn restored_peer_mpans_skip_out_of_sync_entries() {
+        let manager = create_manager();
+        let mpan: MpanState = [1; S2_MPAN_STATE_SIZE].into();
+        manager.store_peer_mpan(2.into(), 1, MPANTableEntry::OutOfSync);
+        manager.store_peer_mpan(2.into(), 2, MPANTableEntry::MPAN { current_mpan: mpan });
+        manager.store_peer_mpan(3.into(), 1, MPANTableEntry::OutOfSync);
+
+        let restored = create_manager();
+        restored.restore_peer_mpans(&manager.export_peer_mpans());
+        assert_eq!(restored.get_peer_mpan(2.into(), 1), None);
+        assert_eq!(
+            restored.get_peer_mpan(2.into(), 2),
+            Some(MPANTableEntry::MPAN { current_mpan: mpan })
+        );
+        assert_eq!(restored.get_peer_mpan(3.into(), 1), None);
+    }
+
     #[test]
     fn create_multicast_group_reuses_existing_node_set() {
         let manager = create_manager();
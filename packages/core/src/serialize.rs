@@ -67,6 +67,22 @@ pub trait SerializableWith<Context> {
     }
 }
 
+/// Like [`SerializableWith`], for values that can only be serialized when certain preconditions
+/// are met, e.g. that a nonce is known. Instead of panicking, serialization returns an error
+/// the caller can react to.
+pub trait TrySerializableWith<Context> {
+    type Error;
+
+    /// Write the value into the given buffer
+    fn try_serialize(&self, output: &mut BytesMut, ctx: Context) -> Result<(), Self::Error>;
+
+    fn try_as_bytes(&self, ctx: Context) -> Result<Bytes, Self::Error> {
+        let mut output = BytesMut::with_capacity(DEFAULT_CAPACITY);
+        self.try_serialize(&mut output, ctx)?;
+        Ok(output.freeze())
+    }
+}
+
 // NOTE on BytesMut usage:
 // One key difference from Vec<u8> is that most operations do not implicitly grow the buffer. This
 // means that calling my_bytes.put("hello world"); could panic if my_bytes does not have enough capacity.
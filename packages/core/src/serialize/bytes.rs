@@ -43,6 +43,15 @@ impl_int!(i16, 2);
 impl_int!(i32, 4);
 impl_int!(i64, 8);
 
+/// Serializes the lower 24 bits of the given value as a big-endian unsigned integer
+pub fn be_u24(val: u32) -> impl Serializable {
+    use bytes::BufMut;
+    move |output: &mut BytesMut| {
+        ensure_capacity(output, 3);
+        output.put_uint(val as u64, 3);
+    }
+}
+
 pub fn slice<S>(data: S) -> impl Serializable
 where
     S: AsRef<[u8]>,
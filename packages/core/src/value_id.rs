@@ -1,64 +1,121 @@
 use crate::prelude::*;
-use typed_builder::TypedBuilder;
+use core::fmt::Display;
+use core::num::NonZeroU64;
+use zwave_pal::prelude::*;
 
-/// Uniquely identifies which CC and property a value belongs to
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TypedBuilder)]
-pub struct ValueId {
-    command_class: CommandClasses,
-    #[builder(setter(into))]
-    property: u32,
-    #[builder(default, setter(into))]
-    property_key: Option<u32>,
+// Value IDs are used as keys in the value cache, so they are packed into a single u64 to keep
+// them small and fast to compare and hash. From the most to the least significant bits:
+// - 8 bits: CC ID + 1, so the packed value is never 0
+// - 7 bits: endpoint index (0 for the root endpoint)
+// - 1 bit:  whether there is a property key
+// - 16 bits: property
+// - 32 bits: property key
+const CC_SHIFT: u32 = 56;
+const ENDPOINT_SHIFT: u32 = 49;
+const ENDPOINT_MASK: u64 = 0x7f << ENDPOINT_SHIFT;
+const HAS_PROPERTY_KEY: u64 = 1 << 48;
+const PROPERTY_SHIFT: u32 = 32;
+
+fn pack_cc(cc: CommandClasses) -> u64 {
+    let id = cc as u16;
+    // All CCs with values have single-byte IDs. The only exception is the Security Mark,
+    // which is stored using the extended CC prefix 0xF1 that no other CC uses.
+    let byte = if id > 0xff { id >> 8 } else { id };
+    (byte as u64 + 1) << CC_SHIFT
+}
+
+fn unpack_cc(packed: u64) -> CommandClasses {
+    let byte = ((packed >> CC_SHIFT) - 1) as u16;
+    CommandClasses::try_from(byte)
+        .or_else(|_| CommandClasses::try_from(byte << 8))
+        .expect("packed value IDs only contain valid CCs")
+}
+
+fn pack_endpoint(endpoint: EndpointIndex) -> u64 {
+    match endpoint.to_canonical() {
+        EndpointIndex::Root => 0,
+        EndpointIndex::Endpoint(index) => {
+            debug_assert!(index <= 0x7f, "Z-Wave nodes have at most 127 endpoints");
+            ((index as u64) << ENDPOINT_SHIFT) & ENDPOINT_MASK
+        }
+    }
+}
+
+fn unpack_endpoint(packed: u64) -> EndpointIndex {
+    match (packed & ENDPOINT_MASK) >> ENDPOINT_SHIFT {
+        0 => EndpointIndex::Root,
+        index => EndpointIndex::Endpoint(index as u8),
+    }
 }
 
+/// Uniquely identifies which CC and property a value belongs to
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValueId(NonZeroU64);
+
 impl ValueId {
     pub fn new(
         command_class: CommandClasses,
-        property: impl Into<u32>,
+        property: impl Into<u16>,
         property_key: Option<u32>,
     ) -> Self {
-        Self {
-            command_class,
-            property: property.into(),
-            property_key,
+        let mut packed = pack_cc(command_class) | (property.into() as u64) << PROPERTY_SHIFT;
+        if let Some(property_key) = property_key {
+            packed |= HAS_PROPERTY_KEY | property_key as u64;
         }
+        Self(NonZeroU64::new(packed).expect("the packed CC is never 0"))
     }
 
     pub fn command_class(&self) -> CommandClasses {
-        self.command_class
+        unpack_cc(self.0.get())
     }
 
-    pub fn property(&self) -> u32 {
-        self.property
+    pub fn property(&self) -> u16 {
+        (self.0.get() >> PROPERTY_SHIFT) as u16
     }
 
     pub fn property_key(&self) -> Option<u32> {
-        self.property_key
+        let packed = self.0.get();
+        (packed & HAS_PROPERTY_KEY != 0).then_some(packed as u32)
     }
 
     pub fn with_node_id(&self, node_id: &NodeId) -> EndpointValueId {
-        EndpointValueId {
-            node_id: *node_id,
-            endpoint: EndpointIndex::Root,
-            value_id: *self,
+        EndpointValueId::new(*node_id, EndpointIndex::Root, *self)
+    }
+}
+
+impl core::fmt::Debug for ValueId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ValueId")
+            .field("command_class", &self.command_class())
+            .field("property", &self.property())
+            .field("property_key", &self.property_key())
+            .finish()
+    }
+}
+
+impl Display for ValueId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}, property {}", self.command_class(), self.property())?;
+        if let Some(property_key) = self.property_key() {
+            write!(f, ", key {}", property_key)?;
         }
+        Ok(())
     }
 }
 
 /// Uniquely identifies which Node, endpoint, CC and property a value belongs to
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct EndpointValueId {
     node_id: NodeId,
-    endpoint: EndpointIndex,
-    value_id: ValueId,
+    // The value ID with the endpoint index included
+    packed: NonZeroU64,
 }
 
 impl EndpointValueId {
     pub fn new(node_id: NodeId, endpoint: EndpointIndex, value_id: ValueId) -> Self {
         Self {
             node_id,
-            endpoint,
-            value_id,
+            packed: value_id.0 | pack_endpoint(endpoint),
         }
     }
 
@@ -67,42 +124,119 @@ impl EndpointValueId {
     }
 
     pub fn with_endpoint(&self, endpoint: EndpointIndex) -> Self {
-        Self { endpoint, ..*self }
+        Self::new(self.node_id, endpoint, self.value_id())
     }
 
     pub fn endpoint(&self) -> EndpointIndex {
-        self.endpoint
+        unpack_endpoint(self.packed.get())
+    }
+
+    /// Returns the value ID without the node ID and endpoint
+    pub fn value_id(&self) -> ValueId {
+        let packed = self.packed.get() & !ENDPOINT_MASK;
+        ValueId(NonZeroU64::new(packed).expect("the packed CC is never 0"))
     }
 
     pub fn command_class(&self) -> CommandClasses {
-        self.value_id.command_class
+        self.value_id().command_class()
     }
 
-    pub fn property(&self) -> u32 {
-        self.value_id.property
+    pub fn property(&self) -> u16 {
+        self.value_id().property()
     }
 
     pub fn property_key(&self) -> Option<u32> {
-        self.value_id.property_key
+        self.value_id().property_key()
+    }
+
+    /// Formats the value ID like zwave-js does: `cc-endpoint-property[-propertyKey]`.
+    /// The CC is given by its numeric ID. Properties are numeric, where zwave-js uses names.
+    pub fn to_value_id_string(&self) -> String {
+        let endpoint = match self.endpoint() {
+            EndpointIndex::Root => 0,
+            EndpointIndex::Endpoint(index) => index,
+        };
+        let mut ret = format!(
+            "{}-{}-{}",
+            self.command_class() as u16,
+            endpoint,
+            self.property()
+        );
+        if let Some(property_key) = self.property_key() {
+            ret.push_str(&format!("-{}", property_key));
+        }
+        ret
+    }
+
+    /// Parses a value ID string in the format of [to_value_id_string](Self::to_value_id_string)
+    pub fn parse_value_id_string(node_id: NodeId, value: &str) -> Result<Self, ParseError> {
+        let invalid = || ParseError::validation_failure(format!("Invalid value ID {}", value));
+
+        let parts: Vec<_> = value.split('-').collect();
+        if !(3..=4).contains(&parts.len()) {
+            return Err(invalid());
+        }
+        let cc = parts[0]
+            .parse::<u16>()
+            .ok()
+            .and_then(|cc| CommandClasses::try_from(cc).ok())
+            .ok_or_else(invalid)?;
+        let endpoint = match parts[1].parse::<u8>().map_err(|_| invalid())? {
+            0 => EndpointIndex::Root,
+            index if index <= 0x7f => EndpointIndex::Endpoint(index),
+            _ => return Err(invalid()),
+        };
+        let property = parts[2].parse::<u16>().map_err(|_| invalid())?;
+        let property_key = parts
+            .get(3)
+            .map(|key| key.parse::<u32>())
+            .transpose()
+            .map_err(|_| invalid())?;
+
+        Ok(Self::new(
+            node_id,
+            endpoint,
+            ValueId::new(cc, property, property_key),
+        ))
+    }
+}
+
+impl core::fmt::Debug for EndpointValueId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EndpointValueId")
+            .field("node_id", &self.node_id)
+            .field("endpoint", &self.endpoint())
+            .field("value_id", &self.value_id())
+            .finish()
+    }
+}
+
+impl Display for EndpointValueId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Node {}", self.node_id)?;
+        if let EndpointIndex::Endpoint(index) = self.endpoint() {
+            write!(f, ", endpoint {}", index)?;
+        }
+        write!(f, ", {}", self.value_id())
     }
 }
 
 /// A subset of [ValueId] used for matching
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ValueIdProperties {
-    property: u32,
+    property: u16,
     property_key: Option<u32>,
 }
 
 impl ValueIdProperties {
-    pub fn new(property: impl Into<u32>, property_key: Option<u32>) -> Self {
+    pub fn new(property: impl Into<u16>, property_key: Option<u32>) -> Self {
         Self {
             property: property.into(),
             property_key,
         }
     }
 
-    pub fn property(&self) -> u32 {
+    pub fn property(&self) -> u16 {
         self.property
     }
 
@@ -118,17 +252,89 @@ impl ValueIdProperties {
 impl From<ValueId> for ValueIdProperties {
     fn from(value: ValueId) -> Self {
         Self {
-            property: value.property,
-            property_key: value.property_key,
+            property: value.property(),
+            property_key: value.property_key(),
         }
     }
 }
 
-impl From<(u32, Option<u32>)> for ValueIdProperties {
-    fn from(value: (u32, Option<u32>)) -> Self {
+impl From<(u16, Option<u32>)> for ValueIdProperties {
+    fn from(value: (u16, Option<u32>)) -> Self {
         Self {
             property: value.0,
             property_key: value.1,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_packed_size() {
+        assert_eq!(size_of::<ValueId>(), 8);
+        assert_eq!(size_of::<Option<ValueId>>(), 8);
+        assert_eq!(size_of::<EndpointValueId>(), 16);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for (cc, property, property_key) in [
+            (CommandClasses::NoOperation, 0u16, None),
+            (CommandClasses::Basic, 1, None),
+            (CommandClasses::Configuration, 0xffff, Some(0xffff_ffff)),
+            (CommandClasses::ManufacturerSpecific, 3, Some(0)),
+            (CommandClasses::SecurityMark, 2, Some(7)),
+        ] {
+            let value_id = ValueId::new(cc, property, property_key);
+            assert_eq!(value_id.command_class(), cc);
+            assert_eq!(value_id.property(), property);
+            assert_eq!(value_id.property_key(), property_key);
+
+            for endpoint in [EndpointIndex::Root, EndpointIndex::Endpoint(127)] {
+                let id = EndpointValueId::new(NodeId::new(5u8), endpoint, value_id);
+                assert_eq!(id.endpoint(), endpoint);
+                assert_eq!(id.value_id(), value_id);
+                assert_eq!(id.command_class(), cc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_endpoint_zero_is_root() {
+        let value_id = ValueId::new(CommandClasses::Basic, 1u16, None);
+        assert_eq!(
+            EndpointValueId::new(NodeId::new(2u8), EndpointIndex::Endpoint(0), value_id),
+            value_id.with_node_id(&NodeId::new(2u8))
+        );
+    }
+
+    #[test]
+    fn test_value_id_string() {
+        let node_id = NodeId::new(2u8);
+        for (string, endpoint, value_id) in [
+            (
+                "37-0-0",
+                EndpointIndex::Root,
+                ValueId::new(CommandClasses::BinarySwitch, 0u16, None),
+            ),
+            (
+                "112-3-5-255",
+                EndpointIndex::Endpoint(3),
+                ValueId::new(CommandClasses::Configuration, 5u16, Some(255)),
+            ),
+        ] {
+            let id = EndpointValueId::new(node_id, endpoint, value_id);
+            assert_eq!(id.to_value_id_string(), string);
+            assert_eq!(
+                EndpointValueId::parse_value_id_string(node_id, string),
+                Ok(id)
+            );
+        }
+
+        for invalid in ["", "37", "37-0", "37-128-0", "256-0-0", "37-0-0-1-2", "37-0-x"] {
+            assert!(EndpointValueId::parse_value_id_string(node_id, invalid).is_err());
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::submodule;
 submodule!(level);
 submodule!(binary);
 submodule!(duration);
+submodule!(units);
 
 pub trait Canonical {
     /// Converts the value to its canonical representation, eliminating illegal values
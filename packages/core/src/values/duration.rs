@@ -1,14 +1,49 @@
 use core::fmt::Display;
+use core::time::Duration;
 
 use crate::serialize::{self, Serializable};
 use crate::parse::{bytes::be_u8, combinators::map_res};
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
 use num_traits::clamp;
+use thiserror::Error;
 
 const MINUTES_MASK: u8 = 0b1000_0000;
 const SECONDS_MASK: u8 = 0b0111_1111;
 
+/// The duration of a state transition, as accepted by the CC APIs.
+/// It gets encoded as a [`DurationSet`] when sending the command.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionDuration {
+    /// Use the duration configured on the device
+    #[default]
+    Default,
+    Duration(Duration),
+}
+
+impl From<Duration> for TransitionDuration {
+    fn from(duration: Duration) -> Self {
+        Self::Duration(duration)
+    }
+}
+
+impl Display for TransitionDuration {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::Duration(duration) => write!(f, "{:?}", duration),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("Duration {requested:?} cannot be encoded, the maximum is {:?}", DurationSet::MAX)]
+pub struct DurationOutOfRange {
+    pub requested: Duration,
+    /// The closest duration that can be encoded
+    pub clamped: DurationSet,
+}
+
 #[derive(Default, Debug, Clone, Copy)]
 pub enum DurationSet {
     Seconds(u8),
@@ -17,6 +52,40 @@ pub enum DurationSet {
     Default,
 }
 
+impl DurationSet {
+    /// The longest duration a Set command can encode
+    pub const MAX: Duration = Duration::from_secs(127 * 60);
+
+    /// Returns the duration this value represents, or `None` for the device default
+    pub fn to_duration(&self) -> Option<Duration> {
+        match self.to_canonical() {
+            Self::Seconds(s) => Some(Duration::from_secs(s as u64)),
+            Self::Minutes(m) => Some(Duration::from_secs(m as u64 * 60)),
+            Self::Default => None,
+        }
+    }
+}
+
+impl TryFrom<TransitionDuration> for DurationSet {
+    type Error = DurationOutOfRange;
+
+    /// Encodes the given duration, rounding to the nearest representable value.
+    /// Durations that are too long fail with the clamped value.
+    fn try_from(value: TransitionDuration) -> Result<Self, Self::Error> {
+        let TransitionDuration::Duration(requested) = value else {
+            return Ok(Self::Default);
+        };
+        match from_duration(requested, 127) {
+            Ok((seconds, 0)) => Ok(Self::Seconds(seconds)),
+            Ok((_, minutes)) => Ok(Self::Minutes(minutes)),
+            Err(minutes) => Err(DurationOutOfRange {
+                requested,
+                clamped: Self::Minutes(minutes),
+            }),
+        }
+    }
+}
+
 impl TryFrom<u8> for DurationSet {
     type Error = TryFromReprError<u8>;
 
@@ -96,6 +165,28 @@ pub enum DurationReport {
     Unknown,
 }
 
+impl DurationReport {
+    /// Returns the duration this value represents, or `None` if it is unknown
+    pub fn to_duration(&self) -> Option<Duration> {
+        match self.to_canonical() {
+            Self::Seconds(s) => Some(Duration::from_secs(s as u64)),
+            Self::Minutes(m) => Some(Duration::from_secs(m as u64 * 60)),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl From<Duration> for DurationReport {
+    /// Encodes the given duration, rounding to the nearest representable value.
+    /// Durations that are too long are clamped.
+    fn from(duration: Duration) -> Self {
+        match from_duration(duration, 126) {
+            Ok((seconds, 0)) => Self::Seconds(seconds),
+            Ok((_, minutes)) | Err(minutes) => Self::Minutes(minutes),
+        }
+    }
+}
+
 impl Display for DurationReport {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -168,9 +259,25 @@ impl PartialEq for DurationReport {
     }
 }
 
+/// Rounds the duration to either seconds (up to 127) or minutes (at least 1).
+/// Returns the number of seconds and minutes, or the clamped number of minutes if
+/// the duration exceeds `max_minutes`.
+fn from_duration(duration: Duration, max_minutes: u8) -> Result<(u8, u8), u8> {
+    let seconds = (duration.as_millis() + 500) / 1000;
+    if seconds <= 127 {
+        return Ok((seconds as u8, 0));
+    }
+    let minutes = (seconds + 30) / 60;
+    if minutes > max_minutes as u128 {
+        return Err(max_minutes);
+    }
+    Ok((0, minutes as u8))
+}
+
 #[cfg(test)]
 mod test {
     use crate::prelude::*;
+    use core::time::Duration;
 
     #[test]
     fn test_duration_report() {
@@ -252,4 +359,43 @@ mod test {
             DurationSet::Minutes(127)
         );
     }
+
+    #[test]
+    fn test_transition_duration() {
+        let encode =
+            |secs: u64| DurationSet::try_from(TransitionDuration::from(Duration::from_secs(secs)));
+        assert_eq!(encode(0), Ok(DurationSet::Seconds(0)));
+        assert_eq!(encode(127), Ok(DurationSet::Seconds(127)));
+        assert_eq!(encode(150), Ok(DurationSet::Minutes(3)));
+        assert_eq!(
+            DurationSet::try_from(TransitionDuration::from(Duration::from_millis(1499))),
+            Ok(DurationSet::Seconds(1))
+        );
+        assert_eq!(
+            DurationSet::try_from(TransitionDuration::Default),
+            Ok(DurationSet::Default)
+        );
+        assert_eq!(
+            encode(3 * 3600),
+            Err(DurationOutOfRange {
+                requested: Duration::from_secs(3 * 3600),
+                clamped: DurationSet::Minutes(127),
+            })
+        );
+
+        assert_eq!(
+            DurationSet::Minutes(3).to_duration(),
+            Some(Duration::from_secs(180))
+        );
+        assert_eq!(DurationSet::Default.to_duration(), None);
+        assert_eq!(
+            DurationReport::try_from(129).unwrap().to_duration(),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(DurationReport::Unknown.to_duration(), None);
+        assert_eq!(
+            DurationReport::from(Duration::from_secs(3 * 3600)),
+            DurationReport::Minutes(126)
+        );
+    }
 }
@@ -0,0 +1,181 @@
+use core::fmt::Display;
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+
+/// The physical quantity a [`Unit`] measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Temperature,
+    Power,
+    Energy,
+    Illuminance,
+}
+
+/// A unit in which sensors and meters report their values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Celsius,
+    Fahrenheit,
+    Watt,
+    Kilowatt,
+    WattHour,
+    KilowattHour,
+    Lux,
+}
+
+impl Unit {
+    pub fn quantity(&self) -> Quantity {
+        match self {
+            Self::Celsius | Self::Fahrenheit => Quantity::Temperature,
+            Self::Watt | Self::Kilowatt => Quantity::Power,
+            Self::WattHour | Self::KilowattHour => Quantity::Energy,
+            Self::Lux => Quantity::Illuminance,
+        }
+    }
+
+    /// Converts a value in this unit to the given unit.
+    /// Returns `None` if the units measure different quantities.
+    pub fn convert(&self, value: f32, to: Unit) -> Option<f32> {
+        if self.quantity() != to.quantity() {
+            return None;
+        }
+        // Convert to the base unit of the quantity first
+        let base = match self {
+            Self::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            Self::Kilowatt | Self::KilowattHour => value * 1000.0,
+            Self::Celsius | Self::Watt | Self::WattHour | Self::Lux => value,
+        };
+        Some(match to {
+            Self::Fahrenheit => base * 9.0 / 5.0 + 32.0,
+            Self::Kilowatt | Self::KilowattHour => base / 1000.0,
+            Self::Celsius | Self::Watt | Self::WattHour | Self::Lux => base,
+        })
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let symbol = match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+            Self::Watt => "W",
+            Self::Kilowatt => "kW",
+            Self::WattHour => "Wh",
+            Self::KilowattHour => "kWh",
+            Self::Lux => "lux",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// Sums up the energy that was consumed according to a series of power readings
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct EnergyAccumulator {
+    watt_hours: f32,
+}
+
+impl EnergyAccumulator {
+    /// Adds the energy that was consumed with the given power over the given time.
+    /// The power is assumed to be constant until the next reading.
+    pub fn add(&mut self, power: f32, unit: Unit, elapsed: Duration) {
+        let Some(watts) = unit.convert(power, Unit::Watt) else {
+            return;
+        };
+        self.watt_hours += watts * elapsed.as_secs_f32() / 3600.0;
+    }
+
+    /// Returns the consumed energy in the given unit, or `None` if it is no energy unit
+    pub fn energy(&self, unit: Unit) -> Option<f32> {
+        Unit::WattHour.convert(self.watt_hours, unit)
+    }
+}
+
+/// The units reported values should be converted to. Quantities without a preferred unit are
+/// kept in the unit they were reported in.
+#[derive(TypedBuilder, Debug, Clone, PartialEq)]
+pub struct PreferredUnits {
+    #[builder(default, setter(strip_option))]
+    pub temperature: Option<Unit>,
+    #[builder(default, setter(strip_option))]
+    pub power: Option<Unit>,
+    #[builder(default, setter(strip_option))]
+    pub energy: Option<Unit>,
+}
+
+impl Default for PreferredUnits {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl PreferredUnits {
+    fn preferred(&self, quantity: Quantity) -> Option<Unit> {
+        match quantity {
+            Quantity::Temperature => self.temperature,
+            Quantity::Power => self.power,
+            Quantity::Energy => self.energy,
+            Quantity::Illuminance => None,
+        }
+    }
+
+    /// Converts a reported value to the preferred unit of its quantity, keeping the raw value
+    pub fn normalize(&self, value: f32, unit: Unit) -> NormalizedValue {
+        let (converted, converted_unit) = self
+            .preferred(unit.quantity())
+            .and_then(|preferred| Some((unit.convert(value, preferred)?, preferred)))
+            .unwrap_or((value, unit));
+        NormalizedValue {
+            raw: value,
+            raw_unit: unit,
+            value: converted,
+            unit: converted_unit,
+        }
+    }
+}
+
+/// A reported value together with its conversion to the preferred unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalizedValue {
+    /// The value as it was reported
+    pub raw: f32,
+    pub raw_unit: Unit,
+    /// The value in the preferred unit
+    pub value: f32,
+    pub unit: Unit,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_convert_temperature() {
+        assert_eq!(Unit::Celsius.convert(100.0, Unit::Fahrenheit), Some(212.0));
+        assert_eq!(Unit::Fahrenheit.convert(-40.0, Unit::Celsius), Some(-40.0));
+        assert_eq!(Unit::Celsius.convert(20.0, Unit::Watt), None);
+    }
+
+    #[test]
+    fn test_accumulate_energy() {
+        let mut energy = EnergyAccumulator::default();
+        energy.add(500.0, Unit::Watt, Duration::from_secs(3600));
+        energy.add(1.5, Unit::Kilowatt, Duration::from_secs(1800));
+        assert_eq!(energy.energy(Unit::KilowattHour), Some(1.25));
+        assert_eq!(energy.energy(Unit::Celsius), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let preferred = PreferredUnits::builder().temperature(Unit::Celsius).build();
+        let normalized = preferred.normalize(68.0, Unit::Fahrenheit);
+        assert_eq!(
+            (normalized.raw, normalized.raw_unit),
+            (68.0, Unit::Fahrenheit)
+        );
+        assert_eq!((normalized.value, normalized.unit), (20.0, Unit::Celsius));
+
+        // Quantities without a preferred unit are kept
+        let normalized = preferred.normalize(250.0, Unit::Lux);
+        assert_eq!((normalized.value, normalized.unit), (250.0, Unit::Lux));
+    }
+}
@@ -49,6 +49,27 @@ where
         self.value = next;
         self.value
     }
+
+    /// Returns the value that was returned last, or zero if the counter was not incremented yet.
+    /// This can be persisted and passed to [`restore`](Self::restore) after a restart.
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Continues counting after the given value. Values above the maximum make the counter start over.
+    pub fn restore(&mut self, value: T) {
+        self.value = match self.max {
+            Some(max) if value > max => T::zero(),
+            _ => value,
+        };
+    }
+
+    /// Skips the given number of values
+    pub fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            self.increment();
+        }
+    }
 }
 
 #[test]
@@ -68,3 +89,45 @@ fn test_increment() {
     assert_eq!(counter.increment(), 4);
     assert_eq!(counter.increment(), 1);
 }
+
+#[test]
+fn test_wraps_at_type_boundary() {
+    let mut counter = WrappingCounter::<u8>::new();
+    counter.restore(u8::MAX - 1);
+    assert_eq!(counter.increment(), u8::MAX);
+    // Zero is skipped
+    assert_eq!(counter.increment(), 1);
+    assert_eq!(counter.value(), 1);
+}
+
+#[test]
+fn test_restore() {
+    let mut counter = WrappingCounter::new_with_max(5u8);
+    assert_eq!(counter.value(), 0);
+    counter.increment();
+    counter.increment();
+
+    let mut restored = WrappingCounter::new_with_max(5u8);
+    restored.restore(counter.value());
+    assert_eq!(restored.increment(), 3);
+
+    // Restoring the maximum wraps around on the next increment
+    restored.restore(5);
+    assert_eq!(restored.increment(), 1);
+
+    // Values out of range start over
+    restored.restore(200);
+    assert_eq!(restored.increment(), 1);
+    restored.restore(0);
+    assert_eq!(restored.increment(), 1);
+}
+
+#[test]
+fn test_skip() {
+    let mut counter = WrappingCounter::new_with_max(5u8);
+    counter.restore(3);
+    counter.skip(4);
+    // 4, 5, 1, 2 were skipped
+    assert_eq!(counter.value(), 2);
+    assert_eq!(counter.increment(), 3);
+}
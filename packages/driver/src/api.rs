@@ -0,0 +1,8 @@
+//! Versioned, stable views of the public API.
+//!
+//! The crate root exports everything that is public, including types that may change between
+//! minor releases while the driver evolves. Applications that want to upgrade without breakage
+//! should import from a versioned module like [`v1`] instead. Items are only ever added to a
+//! versioned module. Removing or changing one requires a new major version of this crate.
+
+pub mod v1;
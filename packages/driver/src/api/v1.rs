@@ -0,0 +1,40 @@
+//! Version 1 of the stable API: the driver handle, the controller, node and endpoint APIs,
+//! values and events.
+//!
+//! ```ignore
+//! use zwave_driver::api::v1::*;
+//! ```
+
+// The driver
+pub use crate::error::{Error, FatalError, Result};
+pub use crate::{Driver, DriverEvent, DriverOptions, LogReceiver, LogSender, SecurityKeys};
+
+// The controller
+pub use crate::{
+    Controller, ControllerCommandError, ControllerCommandResult, ExclusionOptions,
+    InclusionOptions, InclusionState, Init, Ready, SelfTestOptions, SelfTestReport,
+};
+
+// Nodes and endpoints
+pub use crate::{
+    Endpoint, EndpointLike, ExecNodeCommandError, ExecNodeCommandOptions, ExecNodeCommandResult,
+    InterviewStage, Node, NodeStatistics, NodeStatus, NodeUserMetadata, SendPriority,
+};
+
+// Command class APIs, accessed through `Node::cc_api` and `Endpoint::cc_api`
+pub use crate::{CCAPI, CCAPIError, CCAPIResult, CCAPIs};
+
+// Values
+pub use crate::{ValueFilter, ValueUpdate, ValueUpdateKind, ValueWatch};
+pub use zwave_cc::values::ValueMetadata;
+pub use zwave_core::cache::CacheValue;
+pub use zwave_core::value_id::{EndpointValueId, ValueId};
+
+/// What is needed to run the driver on an async runtime: the actors must be polled,
+/// and the adapters connect them to the serial port and each other.
+pub mod runtime {
+    pub use crate::{
+        DriverActor, DriverAdapter, DriverInput, SerialApi, SerialApiActor, SerialApiAdapter,
+        SerialApiEvent,
+    };
+}
@@ -1,6 +1,7 @@
 use crate::{
-    ControllerCommandError, ControllerCommandResult, Driver, NodeStorage,
+    ControllerCommandError, ControllerCommandResult, Driver, ExclusionOptions, InclusionState, NodeStorage, ReachabilityReport, SweepOptions,
 };
+use core::time::Duration;
 use zwave_pal::prelude::*;
 use alloc::collections::BTreeMap;
 use zwave_core::{definitions::*, log::Loglevel, submodule};
@@ -10,6 +11,14 @@ use zwave_serial::command::SerialApiSetupCommand;
 submodule!(storage);
 submodule!(node_api);
 submodule!(state);
+submodule!(inclusion);
+submodule!(startup);
+submodule!(security);
+submodule!(learn_mode);
+submodule!(region);
+submodule!(benchmark);
+submodule!(broadcast);
+submodule!(self_test);
 // submodule!(node_commands);
 
 /// The controller API can be in one of multiple states, each of which has a different set of capabilities.
@@ -67,29 +76,32 @@ impl<'a> Controller<'a, Init> {
         {
             driver.get_protocol_version(command_options).await?.version
         } else {
-            parse_libary_version(&version_info.library_version).map_err(|e| {
+            let version = parse_libary_version(&version_info.library_version).map_err(|e| {
                 ControllerCommandError::Unexpected(format!("Failed to parse library version: {e}"))
-            })?
+            })?;
+            driver.set_sdk_version(&version);
+            version
         };
 
-        let supported_serial_api_setup_commands = if api_capabilities
+        let supported_serial_api_setup_commands = if !api_capabilities
             .supported_function_types
             .contains(&FunctionType::SerialApiSetup)
         {
+            vec![]
+        } else if let Some(commands) = driver.sdk_quirks().implicit_serial_api_setup_commands {
+            // 500 series controllers cannot report which setup commands they support
+            commands.to_vec()
+        } else {
             driver
                 .get_supported_serial_api_setup_commands(command_options)
                 .await?
-        } else {
-            vec![]
         };
 
         // Switch to 16 bit node IDs if supported. We need to do this here, as a controller may still be
         // in 16 bit mode when Z-Wave starts up. This would lead to an invalid node ID being reported.
-        if supported_serial_api_setup_commands.contains(&SerialApiSetupCommand::SetNodeIDType) {
-            let _ = driver
-                .set_node_id_type(NodeIdType::NodeId16Bit, command_options)
-                .await;
-        }
+        driver
+            .negotiate_node_id_type(&supported_serial_api_setup_commands, command_options)
+            .await;
 
         // Afterwards, execute the commands that parse node IDs
         let ids = driver.get_controller_id(command_options).await?;
@@ -99,15 +111,51 @@ impl<'a> Controller<'a, Init> {
         // Read the protocol info for each node and store it
         // FIXME: Read this from cache where possible when we have one
         {
+            let supports_routing_info = api_capabilities
+                .supported_function_types
+                .contains(&FunctionType::GetRoutingInfo);
             for node_id in &init_data.node_ids {
                 let protocol_info = driver
                     .get_node_protocol_info(node_id, command_options)
                     .await?;
-                let storage = NodeStorage::new(protocol_info);
+                let mut storage = NodeStorage::new(protocol_info);
+                // The neighbors help deciding how commands to the node are routed
+                if supports_routing_info && *node_id != ids.own_node_id {
+                    storage.neighbors = driver
+                        .get_node_neighbors(*node_id, command_options)
+                        .await
+                        .ok();
+                }
                 nodes.insert(*node_id, storage);
             }
         }
 
+        // Long Range nodes are not part of the init data and must be queried separately
+        let long_range_nodes = if api_capabilities
+            .supported_function_types
+            .contains(&FunctionType::GetLongRangeNodes)
+        {
+            driver.get_long_range_nodes(command_options).await?
+        } else {
+            vec![]
+        };
+        // They can only be communicated with using 16 bit node IDs
+        for node_id in &long_range_nodes {
+            if driver.ensure_addressable(*node_id).is_err() {
+                driver.controller_log().warn(|| {
+                    format!(
+                        "Long Range node {} cannot be addressed with 8 bit node IDs, ignoring it",
+                        node_id
+                    )
+                });
+                continue;
+            }
+            let protocol_info = driver
+                .get_node_protocol_info(node_id, command_options)
+                .await?;
+            nodes.insert(*node_id, NodeStorage::new(protocol_info));
+        }
+
         // Not the most logical spot to do this, but now we have everything we need to initialize
         // the security managers
         driver.init_security_managers();
@@ -135,13 +183,21 @@ impl<'a> Controller<'a, Init> {
             .supported_function_types(api_capabilities.supported_function_types)
             .supported_serial_api_setup_commands(supported_serial_api_setup_commands)
             .supports_timers(init_data.supports_timers)
+            .long_range_nodes(long_range_nodes)
             .build();
 
+        // The driver shares the node storage, so it knows how to communicate with each node
+        let shared_nodes = driver.storage.nodes().clone();
+        shared_nodes.set(nodes);
+        // ...and the controller information for diagnostics
+        let controller = Arc::new(Locked::new(controller));
+        driver.storage.controller().set(Some(controller.clone()));
+
         Ok(Controller {
             driver,
             state: Ready {
-                storage: Arc::new(Locked::new(controller)),
-                nodes: Arc::new(Locked::new(nodes)),
+                storage: controller,
+                nodes: shared_nodes,
             },
         })
     }
@@ -151,18 +207,69 @@ impl Controller<'_, Ready> {
     pub(crate) async fn configure(&self) -> ControllerCommandResult<()> {
         let driver = self.driver;
 
+        let settings = driver.storage.controller_settings().get();
+
         // Get the currently configured RF region and remember it.
         // If it differs from the desired region, change it afterwards.
         if self.supports_serial_api_setup_command(SerialApiSetupCommand::GetRFRegion) {
-            let _region = driver.get_rf_region(None).await?;
-            // FIXME: set region if desired
+            let region = driver.get_rf_region(None).await?;
+            self.state
+                .storage
+                .update(|storage| storage.rf_region = Some(region));
+            if let Some(desired) = settings.rf_region.filter(|desired| *desired != region) {
+                if let Err(e) = self.apply_rf_region(desired).await {
+                    driver
+                        .controller_log()
+                        .warn(|| format!("failed to restore RF region {}: {}", desired, e));
+                }
+            }
+            self.check_expected_rf_region(&settings);
         }
 
         // Get the currently configured powerlevel and remember it.
         // If it differs from the desired powerlevel, change it afterwards.
         if self.supports_serial_api_setup_command(SerialApiSetupCommand::GetPowerlevel) {
-            let _powerlevel = driver.get_powerlevel(None).await?;
-            // FIXME: set powerlevel if desired
+            let powerlevel = driver.get_powerlevel(None).await?;
+            self.state
+                .storage
+                .update(|storage| storage.powerlevel = Some(powerlevel));
+            if let Some(desired) = settings
+                .powerlevel
+                .filter(|desired| !powerlevel_matches(desired, &powerlevel))
+            {
+                let result = match self.check_powerlevel(&desired) {
+                    Ok(()) => self.apply_powerlevel(desired).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    driver
+                        .controller_log()
+                        .warn(|| format!("failed to restore powerlevel {}: {}", desired, e));
+                }
+            }
+        }
+
+        // Remember how large the commands to the nodes may be
+        if self.supports_serial_api_setup_command(SerialApiSetupCommand::GetMaximumPayloadSize) {
+            let size = driver.get_maximum_payload_size(None).await?;
+            self.state
+                .storage
+                .update(|storage| storage.max_payload_size = Some(size));
+        }
+        if self.supports_serial_api_setup_command(SerialApiSetupCommand::GetLRMaximumPayloadSize) {
+            let size = driver.get_lr_maximum_payload_size(None).await?;
+            self.state
+                .storage
+                .update(|storage| storage.max_lr_payload_size = Some(size));
+        }
+
+        // Remember which channel is used for Long Range communication
+        if self.supports_function(FunctionType::GetLongRangeChannel) {
+            let response = driver.get_long_range_channel(None).await?;
+            self.set_long_range_channel_info(
+                response.channel,
+                response.supports_auto_channel_selection,
+            );
         }
 
         // Enable TX status reports if supported
@@ -288,6 +395,21 @@ impl<'a> Controller<'a, Ready> {
             .update(|storage| storage.sis_present = sis_present);
     }
 
+    /// The version of the Z-Wave API the controller implements
+    pub fn api_version(&self) -> ZWaveApiVersion {
+        self.state.storage.inspect(|storage| storage.api_version)
+    }
+
+    /// Whether the module runs the controller or the end node API
+    pub fn node_type(&self) -> NodeType {
+        self.state.storage.inspect(|storage| storage.node_type)
+    }
+
+    /// Whether the controller supports the timer functions of the Serial API
+    pub fn supports_timers(&self) -> bool {
+        self.state.storage.inspect(|storage| storage.supports_timers)
+    }
+
     pub fn role(&self) -> ControllerRole {
         self.state.storage.inspect(|storage| storage.role)
     }
@@ -300,18 +422,242 @@ impl<'a> Controller<'a, Ready> {
         self.state.storage.inspect(|storage| storage.rf_region)
     }
 
-    pub(crate) fn set_rf_region(&mut self, region: Option<RfRegion>) {
-        self.state.storage.update(|storage| storage.rf_region = region);
+    /// Changes the RF region of the controller and verifies that the change was applied.
+    /// The region is remembered and applied again when the controller is configured after a reset.
+    pub async fn set_rf_region(&self, region: RfRegion) -> ControllerCommandResult<()> {
+        if region == RfRegion::Unknown {
+            return Err(ControllerCommandError::NotAllowed(
+                "The RF region must be known".to_string(),
+            ));
+        }
+        self.apply_rf_region(region).await?;
+        self.driver
+            .storage
+            .controller_settings()
+            .update(|settings| settings.rf_region = Some(region));
+        Ok(())
+    }
+
+    async fn apply_rf_region(&self, region: RfRegion) -> ControllerCommandResult<()> {
+        if !self.supports_serial_api_setup_command(SerialApiSetupCommand::SetRFRegion) {
+            return Err(ControllerCommandError::Unsupported(
+                "SerialApiSetup::SetRFRegion".to_string(),
+            ));
+        }
+        if !self.driver.set_rf_region(region, None).await? {
+            return Err(ControllerCommandError::Unsuccessful);
+        }
+
+        // Make sure the controller actually uses the new region
+        let actual = self.driver.get_rf_region(None).await?;
+        self.state
+            .storage
+            .update(|storage| storage.rf_region = Some(actual));
+        if actual != region {
+            return Err(ControllerCommandError::Unexpected(format!(
+                "The controller is using RF region {} instead of {}",
+                actual, region
+            )));
+        }
+        Ok(())
     }
 
     pub fn powerlevel(&self) -> Option<Powerlevel> {
         self.state.storage.inspect(|storage| storage.powerlevel)
     }
 
-    pub(crate) fn set_powerlevel(&mut self, powerlevel: Option<Powerlevel>) {
+    /// Changes the powerlevel of the controller and verifies that the change was applied.
+    /// The powerlevel is remembered and applied again when the controller is configured after a reset.
+    /// Powerlevels that exceed the limit of the current RF region are refused.
+    pub async fn set_powerlevel(&self, powerlevel: Powerlevel) -> ControllerCommandResult<()> {
+        self.check_powerlevel(&powerlevel)?;
+        self.apply_powerlevel(powerlevel).await?;
+        self.driver
+            .storage
+            .controller_settings()
+            .update(|settings| settings.powerlevel = Some(powerlevel));
+        Ok(())
+    }
+
+    async fn apply_powerlevel(&self, powerlevel: Powerlevel) -> ControllerCommandResult<()> {
+        if !self.supports_serial_api_setup_command(SerialApiSetupCommand::SetPowerlevel) {
+            return Err(ControllerCommandError::Unsupported(
+                "SerialApiSetup::SetPowerlevel".to_string(),
+            ));
+        }
+        if !self.driver.set_powerlevel(powerlevel, None).await? {
+            return Err(ControllerCommandError::Unsuccessful);
+        }
+
+        // Make sure the controller actually uses the new powerlevel
+        let actual = self.driver.get_powerlevel(None).await?;
+        self.state
+            .storage
+            .update(|storage| storage.powerlevel = Some(actual));
+        if !powerlevel_matches(&actual, &powerlevel) {
+            return Err(ControllerCommandError::Unexpected(format!(
+                "The controller is using powerlevel {} instead of {}",
+                actual, powerlevel
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the IDs of all Long Range nodes in the network
+    pub fn long_range_nodes(&self) -> Vec<NodeId> {
+        self.state
+            .storage
+            .inspect(|storage| storage.long_range_nodes.clone())
+    }
+
+    /// Checks whether the given node is a Long Range node
+    pub fn is_long_range_node(&self, node_id: NodeId) -> bool {
+        self.state
+            .storage
+            .inspect(|storage| storage.long_range_nodes.contains(&node_id))
+    }
+
+    /// Returns the channel used for Long Range communication, if known
+    pub fn long_range_channel(&self) -> Option<LongRangeChannel> {
         self.state
             .storage
-            .update(|storage| storage.powerlevel = powerlevel);
+            .inspect(|storage| storage.long_range_channel)
+    }
+
+    /// Whether the controller can automatically select the Long Range channel
+    pub fn supports_long_range_auto_channel_selection(&self) -> bool {
+        self.state
+            .storage
+            .inspect(|storage| storage.supports_long_range_auto_channel_selection)
+    }
+
+    fn set_long_range_channel_info(&self, channel: LongRangeChannel, supports_auto: bool) {
+        self.state.storage.update(|storage| {
+            storage.long_range_channel = Some(channel);
+            storage.supports_long_range_auto_channel_selection = supports_auto;
+        });
+    }
+
+    /// Queries the channel used for Long Range communication from the controller
+    pub async fn get_long_range_channel(&self) -> ControllerCommandResult<LongRangeChannel> {
+        let response = self.driver.get_long_range_channel(None).await?;
+        self.set_long_range_channel_info(
+            response.channel,
+            response.supports_auto_channel_selection,
+        );
+        Ok(response.channel)
+    }
+
+    /// Configures the channel used for Long Range communication.
+    /// Long Range channels are refused if the current RF region does not support Long Range.
+    pub async fn set_long_range_channel(
+        &self,
+        channel: LongRangeChannel,
+    ) -> ControllerCommandResult<()> {
+        self.check_long_range_channel(channel)?;
+        self.driver.set_long_range_channel(channel, None).await?;
+        self.state
+            .storage
+            .update(|storage| storage.long_range_channel = Some(channel));
+        Ok(())
+    }
+
+    /// Sends the controller's node information frame to the given node, or to all nodes in
+    /// range when [`NodeId::broadcast`] is given. Some pairing flows require this.
+    pub async fn send_node_information(&self, destination: NodeId) -> ControllerCommandResult<()> {
+        if !self.supports_function(FunctionType::SendNodeInformation) {
+            return Err(ControllerCommandError::Unsupported("SendNodeInformation".to_string()));
+        }
+        self.driver.send_node_information(destination, None).await
+    }
+
+    /// Whether the controller is currently including or excluding a node
+    pub fn inclusion_state(&self) -> InclusionState {
+        self.driver.inclusion_state()
+    }
+
+    /// Excludes a node from the network. Returns the ID of the removed node,
+    /// or `None` if no node was found within the configured timeout.
+    pub async fn exclude_node(
+        &self,
+        options: &ExclusionOptions,
+    ) -> ControllerCommandResult<Option<NodeId>> {
+        self.driver.exclude_node(options).await
+    }
+
+    /// Pings all nodes that do not sleep and reports which of them are reachable.
+    /// The status of each node is updated accordingly.
+    pub async fn sweep_network(
+        &self,
+        options: &SweepOptions,
+    ) -> ControllerCommandResult<ReachabilityReport> {
+        self.driver.sweep_network(options).await
+    }
+
+    /// Sweeps the network in the given interval until an error occurs.
+    /// The report of each sweep is emitted as an event.
+    pub async fn sweep_network_periodically(
+        &self,
+        options: &SweepOptions,
+        interval: Duration,
+    ) -> ControllerCommandResult<()> {
+        self.driver
+            .sweep_network_periodically(options, interval)
+            .await
+    }
+}
+
+#[cfg(test)]
+impl<'a> Controller<'a, Ready> {
+    /// Creates a controller that is ready to use, without interviewing it
+    pub(crate) fn mock(driver: &'a Driver) -> Self {
+        let storage = ControllerStorage::builder()
+            .home_id(0xdeadbeef)
+            .own_node_id(NodeId::new(1u8))
+            .suc_node_id(None)
+            .fingerprint(DeviceFingerprint::new(
+                0u16,
+                0u16,
+                0u16,
+                Version {
+                    major: 1,
+                    minor: 0,
+                    patch: None,
+                },
+            ))
+            .library_type(ZWaveLibraryType::StaticController)
+            .api_version(ZWaveApiVersion::Official(10))
+            .protocol_version(Version {
+                major: 7,
+                minor: 21,
+                patch: Some(0),
+            })
+            .sdk_version(Version {
+                major: 7,
+                minor: 21,
+                patch: Some(0),
+            })
+            .node_type(NodeType::Controller)
+            .role(ControllerRole::Primary)
+            .started_this_network(true)
+            .sis_present(true)
+            .is_sis(true)
+            .is_suc(true)
+            .supported_function_types(vec![])
+            .supported_serial_api_setup_commands(vec![])
+            .supports_timers(false)
+            .long_range_nodes(vec![])
+            .build();
+        let storage = Arc::new(Locked::new(storage));
+        driver.storage.controller().set(Some(storage.clone()));
+
+        Controller {
+            driver,
+            state: Ready {
+                storage,
+                nodes: driver.storage.nodes().clone(),
+            },
+        }
     }
 }
 
@@ -323,3 +669,9 @@ impl Clone for Controller<'_, Ready> {
         }
     }
 }
+
+/// Compares powerlevels with the precision the controller stores them in
+fn powerlevel_matches(a: &Powerlevel, b: &Powerlevel) -> bool {
+    let close = |a: f32, b: f32| a - b < 0.05 && b - a < 0.05;
+    close(a.tx_power, b.tx_power) && close(a.measured_at_0_dbm, b.measured_at_0_dbm)
+}
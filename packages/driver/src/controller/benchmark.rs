@@ -0,0 +1,158 @@
+use super::{Controller, Ready};
+use crate::LatencyHistogram;
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_pal::prelude::*;
+use zwave_pal::time::{Instant, Timer};
+use zwave_serial::command::GetControllerIdRequest;
+
+/// How the serial link to the controller is benchmarked
+#[derive(TypedBuilder, Clone)]
+pub struct BenchmarkOptions {
+    /// How many commands are sent. Default: 100
+    #[builder(default = 100)]
+    pub commands: usize,
+    /// How long to wait between two commands. Default: none
+    #[builder(default)]
+    pub interval: Duration,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// The result of benchmarking the serial link to the controller
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchmarkReport {
+    /// How many commands were sent
+    pub commands: usize,
+    /// How many commands failed
+    pub failed: usize,
+    /// How long the benchmark took in total
+    pub duration: Duration,
+    /// How long the controller took to acknowledge the commands
+    pub ack_latencies: LatencyHistogram,
+    /// How long the controller took to respond to the commands
+    pub response_latencies: LatencyHistogram,
+    /// How often commands had to be sent again
+    pub retransmissions: u64,
+    /// How many CAN frames were received while waiting for an ACK
+    pub can_count: u64,
+    /// How many NAK frames were received while waiting for an ACK
+    pub nak_count: u64,
+}
+
+impl BenchmarkReport {
+    /// How many commands were completed per second
+    pub fn commands_per_second(&self) -> f64 {
+        let seconds = self.duration.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        (self.commands - self.failed) as f64 / seconds
+    }
+
+    /// The share of commands that failed, between 0 and 1
+    pub fn error_rate(&self) -> f64 {
+        if self.commands == 0 {
+            return 0.0;
+        }
+        self.failed as f64 / self.commands as f64
+    }
+}
+
+impl Controller<'_, Ready> {
+    /// Sends a burst of lightweight commands to the controller and measures the throughput,
+    /// latency and error rate of the serial link. This helps to identify bad USB hubs or cables.
+    ///
+    /// The commands are sent one after another, like normal traffic. Meanwhile, other commands
+    /// are delayed, so this should not be done while the network is busy.
+    pub async fn benchmark(&self, options: &BenchmarkOptions) -> BenchmarkReport {
+        let log = self.driver.controller_log();
+        log.info(|| {
+            format!(
+                "benchmarking the serial link with {} commands...",
+                options.commands
+            )
+        });
+
+        let mut report = BenchmarkReport {
+            commands: options.commands,
+            ..Default::default()
+        };
+        let started = Instant::now();
+        for i in 0..options.commands {
+            if i > 0 && !options.interval.is_zero() {
+                Timer::after(options.interval).await;
+            }
+
+            let outcome = self
+                .driver
+                .exec_controller_command_with_metadata(GetControllerIdRequest::default(), None)
+                .await;
+            if outcome.result.is_err() {
+                report.failed += 1;
+            }
+
+            let metadata = outcome.metadata;
+            if let Some(ack) = metadata.ack_duration() {
+                report.ack_latencies.record(ack);
+            }
+            if let Some(response) = metadata.response_duration() {
+                report.response_latencies.record(response);
+            }
+            report.retransmissions += metadata.attempts.saturating_sub(1) as u64;
+            report.can_count += metadata.can_count as u64;
+            report.nak_count += metadata.nak_count as u64;
+        }
+        report.duration = Instant::now()
+            .checked_duration_since(started)
+            .unwrap_or_default();
+
+        log.info(|| {
+            format!(
+                "benchmark completed: {:.1} commands/s, {:.1} % failed, ACK after {:?} on average",
+                report.commands_per_second(),
+                report.error_rate() * 100.0,
+                report.ack_latencies.mean().unwrap_or_default()
+            )
+        });
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_benchmark() {
+        let controller = MockController::new().on(FunctionType::GetControllerId, |_, _| {
+            vec![MockController::raw(
+                CommandType::Response,
+                FunctionType::GetControllerId,
+                vec![0xde, 0xad, 0xbe, 0xef, 0x01],
+            )]
+        });
+        // The first command is cancelled once and must be repeated
+        controller.cancel_next(1);
+        let report = run_with_mock_controller(&controller, |driver| async move {
+            let controller = Controller::mock(&driver);
+            controller
+                .benchmark(&BenchmarkOptions::builder().commands(5).build())
+                .await
+        });
+
+        assert_eq!(report.commands, 5);
+        assert_eq!(report.failed, 0);
+        assert_eq!(report.error_rate(), 0.0);
+        assert_eq!(report.ack_latencies.count(), 5);
+        assert_eq!(report.response_latencies.count(), 5);
+        assert_eq!(report.can_count, 1);
+        assert_eq!(report.retransmissions, 1);
+    }
+}
@@ -0,0 +1,66 @@
+use super::{Controller, Ready};
+use crate::ExecNodeCommandResult;
+use zwave_cc::commandclass::all_switch::{AllSwitchCCOff, AllSwitchCCOn};
+use zwave_cc::commandclass::{BasicCCSet, CC, CCAddressable, Destination};
+use zwave_core::prelude::*;
+
+// Broadcasts are sent once to all nodes in direct range of the controller. They are neither
+// acknowledged nor routed, so nodes that are out of range, asleep or just busy miss them without
+// anyone noticing. Secure nodes ignore them entirely, because broadcasts cannot be encrypted.
+// Use these helpers for quick reactions like a "panic off", not to reliably control devices.
+impl Controller<'_, Ready> {
+    /// Broadcasts a Basic CC Set command with the given level to all nodes in direct range.
+    /// Nodes which do not support the Basic CC or are not in range are not affected.
+    pub async fn broadcast_basic_set(&self, level: LevelSet) -> ExecNodeCommandResult<()> {
+        let cc = BasicCCSet::builder().target_value(level).build();
+        self.broadcast(cc.into()).await
+    }
+
+    /// Broadcasts an All Switch CC On command, which turns on all switches in direct range,
+    /// unless they are excluded from All On
+    pub async fn all_on(&self) -> ExecNodeCommandResult<()> {
+        self.broadcast(AllSwitchCCOn::default().into()).await
+    }
+
+    /// Broadcasts an All Switch CC Off command, which turns off all switches in direct range,
+    /// unless they are excluded from All Off
+    pub async fn all_off(&self) -> ExecNodeCommandResult<()> {
+        self.broadcast(AllSwitchCCOff::default().into()).await
+    }
+
+    async fn broadcast(&self, cc: CC) -> ExecNodeCommandResult<()> {
+        let cc = cc.with_destination(Destination::Broadcast);
+        self.driver.exec_node_command(&cc, None).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Controller;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_broadcasts_are_not_acknowledged() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            let controller = Controller::mock(&driver);
+            controller.broadcast_basic_set(LevelSet::Off).await.unwrap();
+            controller.all_off().await.unwrap();
+        });
+
+        let sent: Vec<_> = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            .collect();
+        assert_eq!(sent.len(), 2);
+        let no_ack = TransmitOptions::default_no_ack().as_bytes()[0];
+        // Broadcast node ID, payload length, CC, command, then the transmit options
+        assert_eq!(sent[0].payload[..6], [0xff, 3, 0x20, 0x01, 0x00, no_ack]);
+        assert_eq!(sent[1].payload[..5], [0xff, 2, 0x27, 0x05, no_ack]);
+    }
+}
@@ -0,0 +1,199 @@
+use super::{Controller, Ready};
+use crate::{
+    ControllerCommandResult, DriverEvent, InclusionOptions, InterviewStage, LinkQualityOptions,
+    NodeStorage,
+};
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+impl Controller<'_, Ready> {
+    /// Includes a node into the network. Returns the ID of the new node,
+    /// or `None` if no node was found within the configured timeout.
+    ///
+    /// The new node is added with its protocol information and a [`DriverEvent::NodeAdded`]
+    /// event is emitted. Unless disabled in the options, the security bootstrapping and the
+    /// interview follow once the controller has left inclusion mode, and [`DriverEvent::NodeReady`]
+    /// is emitted when they are done. Failures during the interview do not fail the inclusion.
+    pub async fn include_node(
+        &self,
+        options: &InclusionOptions,
+    ) -> ControllerCommandResult<Option<NodeId>> {
+        let Some((node_id, node_info)) = self.driver.include_node_with_info(options).await? else {
+            return Ok(None);
+        };
+
+        self.add_included_node(node_id, node_info).await?;
+        if let Some(link_quality_options) = &options.link_quality_check {
+            self.check_included_node_link_quality(node_id, link_quality_options)
+                .await;
+        }
+        if options.auto_interview {
+            self.interview_included_node(node_id).await;
+        } else {
+            self.driver.controller_log().info(|| {
+                format!(
+                    "automatic interview is disabled, node {} must be interviewed manually",
+                    node_id
+                )
+            });
+        }
+
+        Ok(Some(node_id))
+    }
+
+    /// Remembers a newly included node, along with its protocol information
+    /// and the node information it sent during the inclusion
+    async fn add_included_node(
+        &self,
+        node_id: NodeId,
+        node_info: Option<NodeInformationApplicationData>,
+    ) -> ControllerCommandResult<()> {
+        let protocol_data = self.driver.get_node_protocol_info(&node_id, None).await?;
+        self.state.nodes.update(|nodes| {
+            nodes.insert(node_id, NodeStorage::new(protocol_data));
+        });
+
+        // The node information does not need to be queried again during the interview
+        if let Some(node_info) = node_info {
+            let node = self.node_state(node_id);
+            let root = node.endpoint(EndpointIndex::Root);
+            for cc in node_info.supported_command_classes {
+                root.merge_command_class_info(cc, &PartialCommandClassInfo::default().supported());
+            }
+            for cc in node_info.controlled_command_classes {
+                root.merge_command_class_info(cc, &PartialCommandClassInfo::default().controlled());
+            }
+            node.set_interview_stage(InterviewStage::CommandClasses);
+        }
+
+        self.driver.emit_event(DriverEvent::NodeAdded { node_id });
+        Ok(())
+    }
+
+    /// Checks the link quality of the new node while it can still be repositioned easily
+    async fn check_included_node_link_quality(
+        &self,
+        node_id: NodeId,
+        options: &LinkQualityOptions,
+    ) {
+        let Some(node) = self.node(node_id) else {
+            return;
+        };
+        if let Err(e) = node.check_link_quality(options).await {
+            self.driver.controller_log().warn(|| {
+                format!("checking the link quality of node {} failed: {}", node_id, e)
+            });
+        }
+    }
+
+    async fn interview_included_node(&self, node_id: NodeId) {
+        let Some(node) = self.node(node_id) else {
+            return;
+        };
+        let log = self.driver.controller_log();
+
+        if let Err(e) = node.bootstrap_security().await {
+            log.warn(|| format!("security bootstrapping of node {} failed: {}", node_id, e));
+        }
+        if let Err(e) = node.interview().await {
+            log.warn(|| format!("the interview of node {} failed: {}", node_id, e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+
+    const STOP: u8 = 0x05;
+
+    /// Simulates the inclusion of node 5, which supports no command classes
+    fn mock_controller() -> MockController {
+        MockController::new()
+            .on(FunctionType::AddNodeToNetwork, |_, request| {
+                if request.payload[0] & 0x0f == STOP {
+                    return vec![];
+                }
+                let callback_id = request.payload[1];
+                [
+                    &[0x01, 0x00, 0x00][..],
+                    &[0x02, 0x00, 0x00],
+                    &[0x03, 0x05, 0x03, 0x04, 0x10, 0x01],
+                    &[0x05, 0x05, 0x00],
+                ]
+                .into_iter()
+                .map(|status| {
+                    let mut payload = vec![callback_id];
+                    payload.extend_from_slice(status);
+                    MockController::raw(
+                        CommandType::Request,
+                        FunctionType::AddNodeToNetwork,
+                        payload,
+                    )
+                })
+                .collect()
+            })
+            .on(FunctionType::GetNodeProtocolInfo, |_, _| {
+                vec![MockController::raw(
+                    CommandType::Response,
+                    FunctionType::GetNodeProtocolInfo,
+                    mock_protocol_data(true).as_bytes(),
+                )]
+            })
+    }
+
+    fn inclusion_options(auto_interview: bool) -> InclusionOptions {
+        InclusionOptions::builder()
+            .timeout(core::time::Duration::from_millis(50))
+            .protocol_timeout(core::time::Duration::from_millis(50))
+            .auto_interview(auto_interview)
+            .build()
+    }
+
+    #[test]
+    fn test_included_node_is_interviewed() {
+        let controller = mock_controller();
+        let stage = run_with_mock_controller(&controller, |driver| async move {
+            let controller = Controller::mock(&driver);
+            let node_id = controller
+                .include_node(&inclusion_options(true))
+                .await
+                .unwrap();
+            assert_eq!(node_id, Some(NodeId::new(5u8)));
+
+            let node = controller.node(NodeId::new(5u8)).unwrap();
+            assert_eq!(node.protocol_data(), &mock_protocol_data(true));
+            node.interview_stage()
+        });
+
+        assert_eq!(stage, InterviewStage::Done);
+        assert!(matches!(
+            &controller.take_events()[..],
+            [
+                DriverEvent::NodeAdded { node_id: added },
+                DriverEvent::NodeReady { node_id: ready },
+            ] if *added == NodeId::new(5u8) && *ready == NodeId::new(5u8)
+        ));
+    }
+
+    #[test]
+    fn test_auto_interview_can_be_disabled() {
+        let controller = mock_controller();
+        let stage = run_with_mock_controller(&controller, |driver| async move {
+            let controller = Controller::mock(&driver);
+            controller
+                .include_node(&inclusion_options(false))
+                .await
+                .unwrap();
+            controller.node(NodeId::new(5u8)).unwrap().interview_stage()
+        });
+
+        // The node information from the inclusion is remembered for the interview
+        assert_eq!(stage, InterviewStage::CommandClasses);
+        assert!(matches!(
+            &controller.take_events()[..],
+            [DriverEvent::NodeAdded { .. }]
+        ));
+    }
+}
@@ -0,0 +1,62 @@
+use super::{Controller, Ready};
+use crate::{ControllerCommandResult, DriverEvent, LearnModeOptions, LearnModeResult};
+
+impl Controller<'_, Ready> {
+    /// Puts the controller into learn mode, so another controller can include it into its network
+    /// or exclude it from its current one. Returns how the network changed, or `None` if nothing
+    /// happened in time or the learn mode was stopped using
+    /// [`stop_learn_mode`](Self::stop_learn_mode).
+    ///
+    /// When the network changed, everything that was known about the previous network is
+    /// forgotten and the controller is interviewed again, e.g. to learn its new role and the
+    /// nodes of the new network. Afterwards, [`DriverEvent::JoinedNetwork`] or
+    /// [`DriverEvent::LeftNetwork`] is emitted. The nodes are not interviewed automatically.
+    pub async fn begin_learn_mode(
+        &self,
+        options: &LearnModeOptions,
+    ) -> ControllerCommandResult<Option<LearnModeResult>> {
+        let Some(result) = self.driver.begin_learn_mode(options).await? else {
+            return Ok(None);
+        };
+
+        self.driver.forget_network();
+        self.reinterview().await?;
+
+        let home_id = self.home_id();
+        self.driver.emit_event(match result {
+            LearnModeResult::Joined { own_node_id } => DriverEvent::JoinedNetwork {
+                home_id,
+                own_node_id,
+            },
+            LearnModeResult::Left => DriverEvent::LeftNetwork { home_id },
+        });
+        Ok(Some(result))
+    }
+
+    /// Stops the learn mode that was started with [`begin_learn_mode`](Self::begin_learn_mode).
+    /// Returns whether the controller was in learn mode.
+    pub fn stop_learn_mode(&self) -> bool {
+        self.driver.stop_learn_mode()
+    }
+
+    /// Interviews the controller again after its network changed
+    async fn reinterview(&self) -> ControllerCommandResult<()> {
+        self.driver
+            .controller_log()
+            .info(|| "interviewing the controller again...");
+        let interviewed = Controller::new(self.driver).interview().await?;
+
+        // Other handles to this controller share its storage, so it is updated in place
+        interviewed.state.storage.update(|interviewed| {
+            self.state
+                .storage
+                .update(|storage| core::mem::swap(storage, interviewed))
+        });
+        self.driver
+            .storage
+            .controller()
+            .set(Some(self.state.storage.clone()));
+
+        self.configure().await
+    }
+}
@@ -0,0 +1,107 @@
+use super::{Controller, ControllerSettings, Ready};
+use crate::{ControllerCommandError, ControllerCommandResult};
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+impl Controller<'_, Ready> {
+    /// Returns what the controller may do in its current RF region, if the region is known
+    pub fn rf_region_capabilities(&self) -> Option<RfRegionCapabilities> {
+        self.rf_region()?.capabilities()
+    }
+
+    /// Refuses powerlevels that exceed the limit of the current RF region
+    pub(super) fn check_powerlevel(&self, powerlevel: &Powerlevel) -> ControllerCommandResult<()> {
+        let Some(capabilities) = self.rf_region_capabilities() else {
+            return Ok(());
+        };
+        if powerlevel.tx_power > capabilities.max_tx_power {
+            return Err(ControllerCommandError::NotAllowed(format!(
+                "The TX power of {:.1} dBm exceeds the limit of {:.1} dBm in RF region {}",
+                powerlevel.tx_power,
+                capabilities.max_tx_power,
+                self.rf_region().unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuses Long Range channels if the current RF region does not support Long Range
+    pub(super) fn check_long_range_channel(
+        &self,
+        channel: LongRangeChannel,
+    ) -> ControllerCommandResult<()> {
+        let Some(region) = self.rf_region() else {
+            return Ok(());
+        };
+        if channel != LongRangeChannel::Unsupported && !region.supports_long_range() {
+            return Err(ControllerCommandError::NotAllowed(format!(
+                "Long Range is not available in RF region {}",
+                region
+            )));
+        }
+        Ok(())
+    }
+
+    /// Warns if the controller does not use the RF region the application expects
+    pub(super) fn check_expected_rf_region(&self, settings: &ControllerSettings) {
+        let (Some(expected), Some(actual)) = (settings.expected_rf_region, self.rf_region()) else {
+            return;
+        };
+        if expected != actual {
+            self.driver.controller_log().warn(|| {
+                format!(
+                    "the controller is using RF region {}, but {} was expected",
+                    actual, expected
+                )
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+
+    fn set_region(controller: &Controller<'_, Ready>, region: RfRegion) {
+        controller
+            .state
+            .storage
+            .update(|storage| storage.rf_region = Some(region));
+    }
+
+    #[test]
+    fn test_settings_are_validated_against_the_region() {
+        let mock = MockController::new();
+        run_with_mock_controller(&mock, |driver| async move {
+            let controller = Controller::mock(&driver);
+            set_region(&controller, RfRegion::JP);
+
+            let powerlevel = Powerlevel {
+                tx_power: 12.0,
+                measured_at_0_dbm: 0.0,
+            };
+            assert!(matches!(
+                controller.set_powerlevel(powerlevel).await,
+                Err(ControllerCommandError::NotAllowed(_))
+            ));
+            assert!(matches!(
+                controller.set_long_range_channel(LongRangeChannel::A).await,
+                Err(ControllerCommandError::NotAllowed(_))
+            ));
+
+            set_region(&controller, RfRegion::US_LongRange);
+            assert!(controller.check_powerlevel(&powerlevel).is_ok());
+            assert!(
+                controller
+                    .check_long_range_channel(LongRangeChannel::A)
+                    .is_ok()
+            );
+            // The refused powerlevel was not remembered
+            assert_eq!(driver.controller_settings().powerlevel, None);
+        });
+
+        // Nothing was sent to the controller
+        assert!(mock.received().is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use super::{Controller, Ready};
+use crate::EndpointLike;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+impl Controller<'_, Ready> {
+    /// Queries again which CCs the nodes only support securely and updates the cached CC
+    /// information, so the driver starts using encryption where possible. This is meant to be
+    /// used after the network keys were added to an existing network.
+    ///
+    /// Only S0 is supported so far. Sleeping nodes must be awake to be reassessed.
+    /// Returns the nodes that communicate securely.
+    pub async fn reassess_security(&self) -> Vec<NodeId> {
+        let log = self.driver.controller_log();
+        if self.driver.storage.security_manager().is_none() {
+            log.warn(|| "cannot reassess the security of the nodes without an S0 network key");
+            return Vec::new();
+        }
+
+        let mut ret = Vec::new();
+        for node in self.nodes() {
+            if !node.supports_cc(CommandClasses::Security) {
+                continue;
+            }
+            match node.reassess_security().await {
+                Ok(true) => ret.push(node.id()),
+                // The node was likely included before the network key was known
+                Ok(false) => log.warn(|| {
+                    format!(
+                        "node {} did not respond securely, it must be re-included to use encryption",
+                        node.id()
+                    )
+                }),
+                Err(e) => log.warn(|| {
+                    format!(
+                        "failed to reassess the security of node {}: {}",
+                        node.id(),
+                        e
+                    )
+                }),
+            }
+        }
+
+        log.info(|| format!("{} nodes communicate securely", ret.len()));
+        ret
+    }
+}
@@ -0,0 +1,381 @@
+use super::{Controller, Init};
+use core::fmt::Display;
+use core::time::Duration;
+use thiserror::Error;
+use typed_builder::TypedBuilder;
+use zwave_core::definitions::{ZWaveLibraryType, parse_libary_version};
+use zwave_core::security::{AesIV, NetworkKey, S0Keys, decrypt_aes_ofb, encrypt_aes_ofb};
+use zwave_pal::prelude::*;
+use zwave_serial::command::{Command, GetControllerVersionRequest, GetControllerVersionResponse};
+
+/// Which checks the startup self-test performs and how it reacts to failures
+#[derive(TypedBuilder, Clone)]
+pub struct SelfTestOptions {
+    /// How many commands are sent to check the serial link. Default: 3
+    #[builder(default = 3)]
+    pub serial_link_commands: usize,
+    /// Above which average ACK latency the serial link is considered degraded. Default: 100 ms
+    #[builder(default = Duration::from_millis(100))]
+    pub max_ack_latency: Duration,
+    /// The S0 network key to check. Default: none
+    #[builder(default, setter(into, strip_option))]
+    pub s0_key: Option<NetworkKey>,
+    /// The directory the application stores the network state in, which must be writable.
+    /// Default: none
+    #[cfg(feature = "std")]
+    #[builder(default, setter(into, strip_option))]
+    pub storage_dir: Option<std::path::PathBuf>,
+    /// Whether the driver must not be started if a check failed. Default: true
+    #[builder(default = true)]
+    pub abort_on_failure: bool,
+}
+
+impl Default for SelfTestOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// The checks of the startup self-test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTestCheck {
+    /// The controller acknowledges and answers commands in time
+    SerialLink,
+    /// The controller reports a plausible firmware version
+    ControllerVersion,
+    /// The configured security keys can be used to encrypt and decrypt commands
+    SecurityKeys,
+    /// The application's storage is writable
+    Storage,
+}
+
+/// The outcome of a single self-test check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelfTestOutcome {
+    Passed,
+    /// The check found a problem, which does not prevent the driver from working
+    Warning(String),
+    /// The check found a problem the driver cannot work with
+    Failed(String),
+    /// The check was not performed, because it is not configured
+    Skipped,
+}
+
+/// The result of the startup self-test
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelfTestReport {
+    pub results: Vec<(SelfTestCheck, SelfTestOutcome)>,
+    /// How long the controller took to acknowledge commands on average
+    pub ack_latency: Option<Duration>,
+}
+
+impl SelfTestReport {
+    /// The checks that failed, together with the reason
+    pub fn failures(&self) -> impl Iterator<Item = (SelfTestCheck, &str)> {
+        self.results
+            .iter()
+            .filter_map(|(check, outcome)| match outcome {
+                SelfTestOutcome::Failed(reason) => Some((*check, reason.as_str())),
+                _ => None,
+            })
+    }
+
+    /// Whether no check failed. Warnings are allowed.
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Returns the outcome of the given check
+    pub fn outcome(&self, check: SelfTestCheck) -> Option<&SelfTestOutcome> {
+        self.results
+            .iter()
+            .find(|(c, _)| *c == check)
+            .map(|(_, outcome)| outcome)
+    }
+}
+
+impl Display for SelfTestReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, (check, reason)) in self.failures().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}: {}", check, reason)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("The startup self-test failed: {0}")]
+pub struct SelfTestError(pub SelfTestReport);
+
+impl<'a> Controller<'a, Init> {
+    /// Checks whether the environment allows the driver to work, before the controller is
+    /// interviewed. This detects bad serial connections, incompatible firmwares, invalid
+    /// security keys and read-only storage early, instead of failing later in obscure ways.
+    ///
+    /// If a check failed and [`SelfTestOptions::abort_on_failure`] is set, an error containing
+    /// the report is returned, so the application can refuse to continue.
+    pub async fn self_test(
+        &self,
+        options: &SelfTestOptions,
+    ) -> Result<SelfTestReport, SelfTestError> {
+        let log = self.driver.controller_log();
+        log.info(|| "performing startup self-test...");
+
+        let mut report = SelfTestReport::default();
+        let version = self.check_serial_link(options, &mut report).await;
+
+        let outcome = match version {
+            Some(version) => check_controller_version(&version),
+            None => SelfTestOutcome::Failed("the controller did not report its version".into()),
+        };
+        report
+            .results
+            .push((SelfTestCheck::ControllerVersion, outcome));
+
+        let outcome = match &options.s0_key {
+            Some(key) => check_s0_key(key),
+            None => SelfTestOutcome::Skipped,
+        };
+        report.results.push((SelfTestCheck::SecurityKeys, outcome));
+
+        #[cfg(feature = "std")]
+        let outcome = match &options.storage_dir {
+            Some(dir) => check_storage_dir(dir),
+            None => SelfTestOutcome::Skipped,
+        };
+        #[cfg(not(feature = "std"))]
+        let outcome = SelfTestOutcome::Skipped;
+        report.results.push((SelfTestCheck::Storage, outcome));
+
+        for (check, outcome) in &report.results {
+            match outcome {
+                SelfTestOutcome::Warning(reason) => {
+                    log.warn(|| format!("self-test {:?}: {}", check, reason))
+                }
+                SelfTestOutcome::Failed(reason) => {
+                    log.error(|| format!("self-test {:?} failed: {}", check, reason))
+                }
+                _ => {}
+            }
+        }
+
+        if report.passed() {
+            log.info(|| "self-test passed");
+        } else if options.abort_on_failure {
+            return Err(SelfTestError(report));
+        }
+        Ok(report)
+    }
+
+    /// Sends a few commands to the controller and measures how fast they are acknowledged.
+    /// Returns the last version the controller reported.
+    async fn check_serial_link(
+        &self,
+        options: &SelfTestOptions,
+        report: &mut SelfTestReport,
+    ) -> Option<GetControllerVersionResponse> {
+        let commands = options.serial_link_commands.max(1);
+        let mut version = None;
+        let mut failed = 0;
+        let mut ack_latencies = Vec::new();
+        for _ in 0..commands {
+            let outcome = self
+                .driver
+                .exec_controller_command_with_metadata(GetControllerVersionRequest::default(), None)
+                .await;
+            if let Some(ack) = outcome.metadata.ack_duration() {
+                ack_latencies.push(ack);
+            }
+            match outcome.result {
+                Ok(Some(Command::GetControllerVersionResponse(response))) => {
+                    version = Some(response)
+                }
+                _ => failed += 1,
+            }
+        }
+        if !ack_latencies.is_empty() {
+            report.ack_latency =
+                Some(ack_latencies.iter().sum::<Duration>() / ack_latencies.len() as u32);
+        }
+
+        let outcome = if failed == commands {
+            SelfTestOutcome::Failed(format!(
+                "the controller did not respond to any of {} commands",
+                commands
+            ))
+        } else if failed > 0 {
+            SelfTestOutcome::Warning(format!(
+                "{} of {} commands to the controller failed",
+                failed, commands
+            ))
+        } else {
+            match report.ack_latency {
+                Some(latency) if latency > options.max_ack_latency => {
+                    SelfTestOutcome::Warning(format!(
+                        "the controller took {} ms on average to acknowledge commands",
+                        latency.as_millis()
+                    ))
+                }
+                _ => SelfTestOutcome::Passed,
+            }
+        };
+        report.results.push((SelfTestCheck::SerialLink, outcome));
+        version
+    }
+}
+
+fn check_controller_version(version: &GetControllerVersionResponse) -> SelfTestOutcome {
+    let Ok(parsed) = parse_libary_version(&version.library_version) else {
+        return SelfTestOutcome::Failed(format!(
+            "the controller reported an invalid library version \"{}\"",
+            version.library_version
+        ));
+    };
+    if parsed.major == 0 {
+        return SelfTestOutcome::Failed(format!(
+            "the controller reported an implausible library version {}",
+            parsed
+        ));
+    }
+    match version.library_type {
+        ZWaveLibraryType::StaticController | ZWaveLibraryType::BridgeController => {
+            SelfTestOutcome::Passed
+        }
+        ZWaveLibraryType::Unknown | ZWaveLibraryType::NotApplicable => {
+            SelfTestOutcome::Failed(format!(
+                "the controller reported an unknown library type ({})",
+                version.library_type
+            ))
+        }
+        other => SelfTestOutcome::Warning(format!(
+            "the controller firmware is a {} library, which is not meant to control a network",
+            other
+        )),
+    }
+}
+
+/// Encrypts a test payload with the keys derived from the S0 key and makes sure it can be decrypted
+fn check_s0_key(key: &NetworkKey) -> SelfTestOutcome {
+    if key.iter().all(|b| *b == 0) {
+        return SelfTestOutcome::Warning("the S0 network key consists of zeros only".into());
+    }
+
+    const PLAINTEXT: &[u8] = b"zwave-rs self-test";
+    let keys = S0Keys::derive(key);
+    let iv = AesIV::from([0xa5; 16]);
+    let ciphertext = encrypt_aes_ofb(PLAINTEXT, keys.enc_key(), &iv);
+    if ciphertext == PLAINTEXT
+        || decrypt_aes_ofb(&ciphertext, keys.enc_key(), &iv) != PLAINTEXT
+        || keys.auth_key() == keys.enc_key()
+    {
+        return SelfTestOutcome::Failed("the S0 encryption round trip failed".into());
+    }
+    SelfTestOutcome::Passed
+}
+
+#[cfg(feature = "std")]
+fn check_storage_dir(dir: &std::path::Path) -> SelfTestOutcome {
+    let probe = dir.join(".zwave-self-test");
+    let result = std::fs::write(&probe, b"ok")
+        .and_then(|_| std::fs::read(&probe))
+        .and_then(|content| {
+            std::fs::remove_file(&probe)?;
+            Ok(content)
+        });
+    match result {
+        Ok(content) if content == b"ok" => SelfTestOutcome::Passed,
+        Ok(_) => SelfTestOutcome::Failed(format!(
+            "the storage directory {} does not retain data",
+            dir.display()
+        )),
+        Err(e) => SelfTestOutcome::Failed(format!(
+            "the storage directory {} is not writable: {}",
+            dir.display(),
+            e
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use zwave_core::prelude::*;
+
+    fn mock_controller(library_version: &'static str) -> MockController {
+        MockController::new().on(FunctionType::GetControllerVersion, move |_, _| {
+            let mut payload = library_version.as_bytes().to_vec();
+            // Zero terminator, static controller library
+            payload.extend_from_slice(&[0x00, 0x01]);
+            vec![MockController::raw(
+                CommandType::Response,
+                FunctionType::GetControllerVersion,
+                payload,
+            )]
+        })
+    }
+
+    #[test]
+    fn test_self_test_passes() {
+        let controller = mock_controller("Z-Wave 7.19");
+        let storage_dir = std::env::temp_dir();
+        let report = run_with_mock_controller(&controller, |driver| async move {
+            let options = SelfTestOptions::builder()
+                .s0_key(NetworkKey::from([0x01; 16]))
+                .storage_dir(storage_dir)
+                .build();
+            Controller::new(&driver).self_test(&options).await
+        })
+        .unwrap();
+
+        assert!(report.passed());
+        for check in [
+            SelfTestCheck::SerialLink,
+            SelfTestCheck::ControllerVersion,
+            SelfTestCheck::SecurityKeys,
+            SelfTestCheck::Storage,
+        ] {
+            assert_eq!(report.outcome(check), Some(&SelfTestOutcome::Passed));
+        }
+        assert!(report.ack_latency.is_some());
+        assert_eq!(controller.received().len(), 3);
+    }
+
+    #[test]
+    fn test_self_test_failure() {
+        let controller = mock_controller("Z-Wave 0.0");
+        let (strict, lenient) = run_with_mock_controller(&controller, |driver| async move {
+            let storage_dir = std::env::temp_dir().join("zwave-self-test-missing");
+            let options = SelfTestOptions::builder()
+                .serial_link_commands(1)
+                .storage_dir(storage_dir)
+                .build();
+            let strict = Controller::new(&driver).self_test(&options).await;
+            let options = SelfTestOptions {
+                abort_on_failure: false,
+                ..options
+            };
+            let lenient = Controller::new(&driver).self_test(&options).await;
+            (strict, lenient)
+        });
+
+        let Err(SelfTestError(report)) = strict else {
+            panic!("the self-test should have failed");
+        };
+        let failed: Vec<_> = report.failures().map(|(check, _)| check).collect();
+        assert_eq!(
+            failed,
+            vec![SelfTestCheck::ControllerVersion, SelfTestCheck::Storage]
+        );
+        assert_eq!(
+            report.outcome(SelfTestCheck::SecurityKeys),
+            Some(&SelfTestOutcome::Skipped)
+        );
+
+        let report = lenient.unwrap();
+        assert!(!report.passed());
+    }
+}
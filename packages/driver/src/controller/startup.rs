@@ -0,0 +1,162 @@
+use super::{Controller, Ready};
+use crate::{DriverEvent, InterviewStage, error::Result};
+use futures::stream::{self, StreamExt};
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::CC;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+/// How the nodes are interviewed after the driver was started
+#[derive(TypedBuilder, Clone)]
+pub struct StartupOptions {
+    /// How many listening nodes are interviewed at the same time. The endpoints of each node are
+    /// always interviewed one after the other. Default: 4
+    #[builder(default = 4)]
+    pub concurrency: usize,
+    /// Nodes that are interviewed before all others, in the given order. Default: none
+    #[builder(default, setter(into))]
+    pub priority: Vec<NodeId>,
+}
+
+impl Default for StartupOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl Controller<'_, Ready> {
+    /// Interviews all nodes whose interview is not complete yet, without flooding the network.
+    ///
+    /// Listening nodes are interviewed first, with bounded concurrency and the nodes of the
+    /// priority list first. Afterwards, [`DriverEvent::NetworkReady`] is emitted. The interviews
+    /// of sleeping nodes are deferred until they wake up, so this only completes once all of them
+    /// have been interviewed. Failed interviews are logged and resume on the next attempt.
+    pub async fn interview_nodes(&self, options: &StartupOptions) -> Result<()> {
+        let own_node_id = self.own_node_id();
+        let (mut sleeping, mut listening): (Vec<_>, Vec<_>) = self
+            .nodes()
+            .into_iter()
+            .filter(|node| node.id() != own_node_id)
+            .filter(|node| node.interview_stage() != InterviewStage::Done)
+            .partition(|node| node.can_sleep());
+        listening.sort_by_key(|node| priority_rank(&options.priority, node.id()));
+        sleeping.sort_by_key(|node| priority_rank(&options.priority, node.id()));
+
+        let log = self.driver.controller_log();
+        log.info(|| {
+            format!(
+                "interviewing {} listening nodes, deferring {} sleeping nodes until they wake up",
+                listening.len(),
+                sleeping.len()
+            )
+        });
+
+        stream::iter(listening)
+            .for_each_concurrent(options.concurrency.max(1), |node| async move {
+                if let Err(e) = node.interview().await {
+                    self.driver
+                        .controller_log()
+                        .warn(|| format!("the interview of node {} failed: {}", node.id(), e));
+                }
+            })
+            .await;
+
+        let mut deferred: Vec<NodeId> = sleeping.iter().map(|node| node.id()).collect();
+        self.driver.emit_event(DriverEvent::NetworkReady {
+            deferred_nodes: deferred.clone(),
+        });
+
+        while !deferred.is_empty() {
+            let waiting_for = deferred.clone();
+            let notification = self
+                .driver
+                .register_awaited_cc(
+                    Box::new(move |cc| {
+                        matches!(cc.as_ref(), CC::WakeUpCCNotification(_))
+                            && waiting_for.contains(&cc.address().source_node_id)
+                    }),
+                    None,
+                )
+                .try_await()
+                .await?;
+            let node_id = notification.address().source_node_id;
+            let Some(node) = self.node(node_id) else {
+                deferred.retain(|id| *id != node_id);
+                continue;
+            };
+
+            log.info(|| format!("node {} woke up, continuing its interview", node_id));
+            match node.interview().await {
+                Ok(()) => deferred.retain(|id| *id != node_id),
+                // The interview is resumed when the node wakes up the next time
+                Err(e) => log.warn(|| format!("the interview of node {} failed: {}", node_id, e)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Sorts the nodes of the priority list before all others, in the order they are listed
+fn priority_rank(priority: &[NodeId], node_id: NodeId) -> (usize, NodeId) {
+    let rank = priority
+        .iter()
+        .position(|id| *id == node_id)
+        .unwrap_or(priority.len());
+    (rank, node_id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use core::time::Duration;
+    use zwave_cc::commandclass::{CCAddressable, WakeUpCCNotification};
+    use zwave_pal::time::Timer;
+
+    #[test]
+    fn test_staged_startup() {
+        let controller = MockController::new();
+        run_with_mock_controller(&controller, |driver| async move {
+            // The nodes support no CCs, so their interviews need no communication
+            driver.storage.nodes().update(|nodes| {
+                for (node_id, listening) in [(2u8, true), (3, true), (4, false)] {
+                    let mut node = NodeStorage::new(mock_protocol_data(listening));
+                    node.interview_stage = InterviewStage::CommandClasses;
+                    nodes.insert(NodeId::new(node_id), node);
+                }
+            });
+            let controller = Controller::mock(&driver);
+            let options = StartupOptions::builder()
+                .concurrency(1)
+                .priority(vec![NodeId::new(3u8)])
+                .build();
+
+            let wake_up = async {
+                Timer::after(Duration::from_millis(10)).await;
+                let notification = CC::from(WakeUpCCNotification::default())
+                    .with_destination(NodeId::new(1u8).into())
+                    .with_source_node_id(NodeId::new(4u8));
+                let callback = driver.storage.awaited_ccs().take_matching(&notification);
+                callback.unwrap().send(notification).unwrap();
+            };
+            let (result, ()) = futures::join!(controller.interview_nodes(&options), wake_up);
+            result.unwrap();
+        });
+
+        let node_ids = |ids: &[u8]| ids.iter().map(|id| NodeId::new(*id)).collect::<Vec<_>>();
+        let events = controller.take_events();
+        assert!(matches!(
+            &events[..],
+            [
+                DriverEvent::NodeReady { node_id: first },
+                DriverEvent::NodeReady { node_id: second },
+                DriverEvent::NetworkReady { deferred_nodes },
+                DriverEvent::NodeReady { node_id: woken_up },
+            ] if [*first, *second] == node_ids(&[3, 2])[..]
+                && *deferred_nodes == node_ids(&[4])
+                && *woken_up == NodeId::new(4u8)
+        ));
+    }
+}
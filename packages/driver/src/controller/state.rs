@@ -1,6 +1,9 @@
 use zwave_pal::prelude::*;
 use super::{Controller, Ready};
-use crate::{EndpointStorage, InterviewStage};
+use crate::{
+    EndpointCCInheritance, EndpointStorage, InterviewStage, LinkQuality, NodeEncryptionPolicy,
+    NodeStatistics, NodeStatus, NodeUserMetadata, OptimisticUpdates, WakeUpRefreshPolicy,
+};
 use zwave_core::prelude::*;
 
 #[derive(Clone, Copy)]
@@ -68,6 +71,149 @@ impl<'a> NodeStateRef<'a> {
         })
     }
 
+    pub(crate) fn user_metadata(self) -> Option<NodeUserMetadata> {
+        self.controller.state.nodes.inspect(|nodes| {
+            nodes
+                .get(&self.node_id)
+                .map(|storage| storage.user_metadata.clone())
+        })
+    }
+
+    pub(crate) fn status(self) -> Option<NodeStatus> {
+        self.controller
+            .state
+            .nodes
+            .inspect(|nodes| nodes.get(&self.node_id).map(|storage| storage.status))
+    }
+
+    pub(crate) fn last_transmit_report(self) -> Option<TransmitReport> {
+        self.controller.state.nodes.inspect(|nodes| {
+            nodes
+                .get(&self.node_id)
+                .and_then(|storage| storage.last_transmit_report.clone())
+        })
+    }
+
+    pub(crate) fn statistics(self) -> Option<NodeStatistics> {
+        self.controller
+            .state
+            .nodes
+            .inspect(|nodes| nodes.get(&self.node_id).map(|storage| storage.statistics.clone()))
+    }
+
+    pub(crate) fn link_quality(self) -> Option<LinkQuality> {
+        self.controller
+            .state
+            .nodes
+            .inspect(|nodes| nodes.get(&self.node_id).and_then(|storage| storage.link_quality))
+    }
+
+    pub(crate) fn set_link_quality(self, link_quality: LinkQuality) {
+        self.controller.state.nodes.update(|nodes| {
+            if let Some(storage) = nodes.get_mut(&self.node_id) {
+                storage.link_quality = Some(link_quality);
+            }
+        });
+    }
+
+    pub(crate) fn optimistic_updates(self) -> Option<OptimisticUpdates> {
+        self.controller.state.nodes.inspect(|nodes| {
+            nodes
+                .get(&self.node_id)
+                .and_then(|storage| storage.optimistic_updates)
+        })
+    }
+
+    pub(crate) fn set_optimistic_updates(
+        self,
+        optimistic_updates: Option<OptimisticUpdates>,
+    ) -> bool {
+        self.controller.state.nodes.update(|nodes| {
+            let Some(storage) = nodes.get_mut(&self.node_id) else {
+                return false;
+            };
+            storage.optimistic_updates = optimistic_updates;
+            true
+        })
+    }
+
+    pub(crate) fn wake_up_refresh_policy(self) -> Option<WakeUpRefreshPolicy> {
+        self.controller.state.nodes.inspect(|nodes| {
+            nodes
+                .get(&self.node_id)
+                .and_then(|storage| storage.wake_up_refresh_policy.clone())
+        })
+    }
+
+    pub(crate) fn set_wake_up_refresh_policy(self, policy: Option<WakeUpRefreshPolicy>) -> bool {
+        self.controller.state.nodes.update(|nodes| {
+            let Some(storage) = nodes.get_mut(&self.node_id) else {
+                return false;
+            };
+            storage.wake_up_refresh_policy = policy;
+            true
+        })
+    }
+
+    pub(crate) fn encryption_policy(self) -> Option<NodeEncryptionPolicy> {
+        self.controller.state.nodes.inspect(|nodes| {
+            nodes
+                .get(&self.node_id)
+                .map(|storage| storage.encryption_policy.clone())
+        })
+    }
+
+    pub(crate) fn update_encryption_policy(
+        self,
+        update: impl FnOnce(&mut NodeEncryptionPolicy),
+    ) -> bool {
+        self.controller.state.nodes.update(|nodes| {
+            let Some(storage) = nodes.get_mut(&self.node_id) else {
+                return false;
+            };
+            update(&mut storage.encryption_policy);
+            true
+        })
+    }
+
+    pub(crate) fn endpoint_cc_inheritance(self) -> Option<EndpointCCInheritance> {
+        self.controller.state.nodes.inspect(|nodes| {
+            nodes
+                .get(&self.node_id)
+                .map(|storage| storage.endpoint_cc_inheritance.clone())
+        })
+    }
+
+    pub(crate) fn update_endpoint_cc_inheritance(
+        self,
+        update: impl FnOnce(&mut EndpointCCInheritance),
+    ) -> bool {
+        self.controller.state.nodes.update(|nodes| {
+            let Some(storage) = nodes.get_mut(&self.node_id) else {
+                return false;
+            };
+            update(&mut storage.endpoint_cc_inheritance);
+            true
+        })
+    }
+
+    /// Updates the user metadata of the node. Returns the new metadata if it was changed.
+    pub(crate) fn update_user_metadata(
+        self,
+        update: impl FnOnce(&mut NodeUserMetadata),
+    ) -> Option<NodeUserMetadata> {
+        self.controller.state.nodes.update(|nodes| {
+            let storage = nodes.get_mut(&self.node_id)?;
+            let mut metadata = storage.user_metadata.clone();
+            update(&mut metadata);
+            if metadata == storage.user_metadata {
+                return None;
+            }
+            storage.user_metadata = metadata.clone();
+            Some(metadata)
+        })
+    }
+
     pub(crate) fn endpoint_exists(self, endpoint_index: EndpointIndex) -> bool {
         self.endpoint(endpoint_index).exists()
     }
@@ -35,9 +35,31 @@ pub(crate) struct ControllerStorage {
     pub(crate) supported_function_types: Vec<FunctionType>,
     pub(crate) supported_serial_api_setup_commands: Vec<SerialApiSetupCommand>,
     pub(crate) supports_timers: bool,
+    pub(crate) long_range_nodes: Vec<NodeId>,
 
     #[builder(setter(skip), default)]
     pub(crate) rf_region: Option<RfRegion>,
     #[builder(setter(skip), default)]
     pub(crate) powerlevel: Option<Powerlevel>,
+    #[builder(setter(skip), default)]
+    pub(crate) long_range_channel: Option<LongRangeChannel>,
+    #[builder(setter(skip), default)]
+    pub(crate) supports_long_range_auto_channel_selection: bool,
+    /// The maximum size of the CC payload in a SendData command, if queried
+    #[builder(setter(skip), default)]
+    pub(crate) max_payload_size: Option<u8>,
+    /// The maximum size of the CC payload in a SendData command to a Long Range node, if queried
+    #[builder(setter(skip), default)]
+    pub(crate) max_lr_payload_size: Option<u8>,
+}
+
+/// Settings the application wants the controller to use. They are kept by the driver,
+/// so they can be applied again when the controller is configured after a reset.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ControllerSettings {
+    pub rf_region: Option<RfRegion>,
+    pub powerlevel: Option<Powerlevel>,
+    /// The RF region the application expects the controller to use. Unlike `rf_region`,
+    /// it is not applied. A warning is logged if the controller uses a different region.
+    pub expected_rf_region: Option<RfRegion>,
 }
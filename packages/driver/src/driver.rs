@@ -1,33 +1,89 @@
-use crate::LogSender;
-use crate::error::Result;
+use crate::{
+    ControllerSettings, LinkQuality, LogSender, NodeStatus, NodeUserMetadata,
+    UnknownCommandStatistics,
+};
+use crate::error::{FatalError, Result};
 use crate::serial_api::SerialApi;
 use zwave_pal::prelude::*;
+use alloc::collections::BTreeMap;
 use awaited::Predicate;
 use core::time::Duration;
 use storage::DriverStorage;
 use typed_builder::TypedBuilder;
 use zwave_cc::prelude::*;
+use zwave_cc::values::ValueMetadata;
+use zwave_core::cache::CacheValue;
+use zwave_core::definitions::{Id32, NodeId, NodeInformationApplicationData};
 use zwave_core::log::Loglevel;
 use zwave_core::security::NetworkKey;
 use zwave_core::submodule;
+use zwave_core::value_id::EndpointValueId;
 use zwave_logging::LogInfo;
 use zwave_pal::channel::{Receiver, Sender};
 use zwave_pal::time::Instant;
 use zwave_serial::prelude::*;
 
 pub(crate) mod awaited;
+pub use awaited::AwaitedStatistics;
 pub(crate) mod cache;
-mod storage;
+pub(crate) mod storage;
 
+submodule!(cancellation);
+submodule!(clock_sync);
+submodule!(command_delays);
+submodule!(counters);
 submodule!(exec_controller_command);
 submodule!(controller_commands);
+submodule!(controller_identity);
+submodule!(spec_deviations);
 submodule!(exec_node_command);
+submodule!(interview_hooks);
+submodule!(manager);
+submodule!(network_management);
+submodule!(neighbors);
+submodule!(network_sweep);
+submodule!(node_info);
+submodule!(nvm);
+submodule!(replication);
+submodule!(route_repair);
+submodule!(s2_state);
+submodule!(optimistic_updates);
+submodule!(persisted_state);
+submodule!(ping);
+submodule!(raw_commands);
+submodule!(rate_limiter);
+submodule!(reachability);
+submodule!(report_flood);
+submodule!(scheduler);
+submodule!(transactions);
+submodule!(transport_service);
+submodule!(value_metadata);
+submodule!(value_watch);
+mod transitions;
+submodule!(virtual_endpoints);
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::*;
+#[cfg(feature = "network-export")]
+mod network_export;
+#[cfg(feature = "network-export")]
+pub use network_export::*;
 submodule!(actor);
+mod basic_mapping;
 submodule!(handle);
 
+/// The handle applications use to interact with the driver.
+///
+/// It is cheap to clone and can be shared between threads, e.g. in the state of a web server.
+/// All interaction with the actors happens through channels and all shared state is behind locks,
+/// so the futures returned by the driver, as well as by the [`Controller`](crate::Controller) and
+/// [`Node`](crate::Node) APIs built on top of it, are `Send`. They only complete while the
+/// [`DriverActor`] and the Serial API actor are running, which may happen on any thread.
 #[derive(Clone)]
 pub struct Driver {
     cmd_tx: DriverInputSender,
+    event_tx: DriverEventSender,
     serial_api: SerialApi,
     pub(crate) storage: Arc<DriverStorage>,
 }
@@ -38,6 +94,8 @@ pub struct DriverActor {
     input_tx: DriverInputSender,
     input_rx: DriverInputReceiver,
     event_tx: DriverEventSender,
+    /// Receives the errors the Serial API actor cannot recover from
+    supervision_rx: Receiver<FatalError>,
 
     // Handles to lower layers
     serial_api: SerialApi,
@@ -47,6 +105,10 @@ pub struct DriverActor {
 
     security_keys: SecurityKeys,
     awaited_ccs: Vec<AwaitedCC>,
+    /// When obsolete awaited entries are removed next
+    next_awaited_sweep: Instant,
+    /// The error that stops this actor, if any
+    fatal_error: Option<FatalError>,
 }
 
 pub struct DriverAdapter {
@@ -62,11 +124,14 @@ impl Driver {
     ) -> (Self, DriverActor, DriverAdapter) {
         let (input_tx, input_rx) = zwave_pal::channel::channel(16);
         let (event_tx, event_rx) = zwave_pal::channel::channel(16);
+        let (supervision_tx, supervision_rx) = zwave_pal::channel::channel(16);
+        serial_api.storage.supervisor().set(Some(supervision_tx));
 
         let storage = Arc::new(DriverStorage::new());
 
         let driver = Driver {
             cmd_tx: input_tx.clone(),
+            event_tx: event_tx.clone(),
             serial_api: serial_api.clone(),
             storage: storage.clone(),
         };
@@ -81,16 +146,28 @@ impl Driver {
             input_tx,
             input_rx,
             event_tx,
+            supervision_rx,
             serial_api: serial_api.clone(),
             storage,
             security_keys,
             awaited_ccs: Vec::new(),
+            next_awaited_sweep: Instant::now() + awaited::AWAITED_SWEEP_INTERVAL,
+            fatal_error: None,
         };
 
         (driver, actor, adapter)
     }
 }
 
+/// Emits an event to the application after passing it to the value watchers.
+/// Events are dropped if the application does not keep up with them.
+fn emit_event(storage: &DriverStorage, event_tx: &DriverEventSender, event: DriverEvent) {
+    storage
+        .value_watchers()
+        .update(|watchers| watchers.dispatch(&event));
+    let _ = event_tx.try_send(event);
+}
+
 pub enum DriverInput {
     /// An unsolicited command needs to be handled
     Unsolicited { command: Command },
@@ -109,6 +186,101 @@ pub enum DriverInput {
 
 pub enum DriverEvent {
     // FIXME: Add command to forward unhandled commands to the application
+    /// A stateful value was changed, either by a report from a node or optimistically by a Set
+    /// command that was sent to it
+    ValueUpdated {
+        value_id: EndpointValueId,
+        value: CacheValue,
+        kind: ValueUpdateKind,
+    },
+    /// A Set command was sent to a node, but the value it changes is only updated once the node
+    /// confirms it. Until then, the value should be displayed as pending.
+    ValuePending {
+        value_id: EndpointValueId,
+        value: CacheValue,
+    },
+    /// A value is known to exist, e.g. because the node supports the CC it belongs to.
+    /// It has no value until the node reports one.
+    ValueAdded {
+        value_id: EndpointValueId,
+        metadata: ValueMetadata,
+    },
+    /// A node sent an event (stateless value). These are not stored and
+    /// are emitted every time they are received, even if nothing changed.
+    ValueNotification {
+        value_id: EndpointValueId,
+        value: CacheValue,
+    },
+    /// The user metadata (name, location, ...) of a node was changed
+    NodeUserMetadataChanged {
+        node_id: NodeId,
+        metadata: NodeUserMetadata,
+    },
+    /// The controller was included into another network during learn mode
+    JoinedNetwork { home_id: Id32, own_node_id: NodeId },
+    /// The controller was excluded from its network during learn mode and now has a network of
+    /// its own
+    LeftNetwork { home_id: Id32 },
+    /// A node was added to the network
+    NodeAdded { node_id: NodeId },
+    /// The interview of a node was completed, so it can be used
+    NodeReady { node_id: NodeId },
+    /// The listening nodes were interviewed after startup, so the network can be used.
+    /// The interviews of the sleeping nodes in `deferred_nodes` continue when they wake up.
+    NetworkReady { deferred_nodes: Vec<NodeId> },
+    /// A node sent node information that differs from the cached one, e.g. after a firmware
+    /// update. The application should persist it, see [`Driver::export_state`].
+    NodeInfoChanged {
+        node_id: NodeId,
+        node_info: NodeInformationApplicationData,
+    },
+    /// A link quality check showed that a node is barely reachable.
+    /// It should be moved closer to the controller or to a repeater.
+    MarginalLinkQuality {
+        node_id: NodeId,
+        link_quality: LinkQuality,
+    },
+    /// The status of a node changed, e.g. because it stopped acknowledging commands
+    NodeStatusChanged { node_id: NodeId, status: NodeStatus },
+    /// A command from a node was discarded, because it was corrupted on the way.
+    /// This indicates a problem with the RF communication.
+    CorruptedCommand { node_id: NodeId, data: Vec<u8> },
+    /// A node repeatedly could not be reached due to routing problems,
+    /// so the driver started to repair its routes
+    RouteRepairStarted { node_id: NodeId, failures: u8 },
+    /// The driver finished repairing the routes to a node
+    RouteRepairFinished {
+        node_id: NodeId,
+        result: RouteRepairResult,
+    },
+    /// A periodic network sweep was completed
+    NetworkSwept { report: ReachabilityReport },
+    /// A scheduled command was sent
+    ScheduledCommandSent { id: ScheduledCommandId },
+    /// A scheduled command could not be sent
+    ScheduledCommandFailed {
+        id: ScheduledCommandId,
+        error: ExecNodeCommandError,
+    },
+    /// A node sent more commands than allowed by the [`ReportFloodOptions`], e.g. because a
+    /// sensor is broken. Its excess commands are discarded until it calms down.
+    ReportFloodStarted { node_id: NodeId },
+    /// A node stopped flooding the network. `discarded` commands were discarded in the meantime.
+    ReportFloodEnded { node_id: NodeId, discarded: u64 },
+    /// One of the actors encountered an error it cannot recover from, so the driver stopped.
+    /// Pending and future operations fail. The application should shut down or restart it.
+    FatalError { error: FatalError },
+}
+
+/// Where the new value of a [`DriverEvent::ValueUpdated`] event comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueUpdateKind {
+    /// The node reported the value
+    Reported,
+    /// The value was assumed after a Set command. The node has not confirmed it yet.
+    Optimistic,
+    /// The value was already cached when a [`ValueWatch`] was started
+    Snapshot,
 }
 
 type DriverInputSender = Sender<DriverInput>;
@@ -127,6 +299,80 @@ struct AwaitedCC {
 pub struct DriverOptions {
     #[builder(default)]
     security_keys: SecurityKeys,
+    /// The serial port the controller is connected to. Default: detected automatically
+    #[builder(default, setter(into))]
+    port: PortSelection,
+    /// How the controller identifies itself when nodes query it. Default: like the controller
+    #[builder(default)]
+    controller_identity: ControllerIdentity,
+    /// How strictly received CCs are checked against the specification. Default: lenient
+    #[builder(default)]
+    parsing_strictness: ParsingStrictness,
+    /// Customizes the interview of nodes. Default: none
+    #[builder(default, setter(strip_option))]
+    interview_hooks: Option<Arc<dyn InterviewHooks>>,
+    /// Minimum delays between commands to nodes that cannot handle them back to back.
+    /// Default: none
+    #[builder(default)]
+    command_delays: BTreeMap<NodeId, CommandDelays>,
+}
+
+/// How the serial port of the controller is determined
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PortSelection {
+    /// Use the port with the given path
+    Path(String),
+    /// Use the only Z-Wave stick that is connected to this system
+    #[default]
+    AutoDetect,
+}
+
+impl From<&str> for PortSelection {
+    fn from(path: &str) -> Self {
+        Self::Path(path.to_string())
+    }
+}
+
+impl From<String> for PortSelection {
+    fn from(path: String) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl DriverOptions {
+    /// Returns options that use the only Z-Wave stick connected to this system
+    pub fn auto_detect_port() -> Self {
+        Self::builder().port(PortSelection::AutoDetect).build()
+    }
+
+    pub fn port(&self) -> &PortSelection {
+        &self.port
+    }
+
+    pub fn controller_identity(&self) -> &ControllerIdentity {
+        &self.controller_identity
+    }
+
+    pub fn parsing_strictness(&self) -> ParsingStrictness {
+        self.parsing_strictness
+    }
+
+    pub fn interview_hooks(&self) -> Option<&Arc<dyn InterviewHooks>> {
+        self.interview_hooks.as_ref()
+    }
+
+    pub fn command_delays(&self) -> &BTreeMap<NodeId, CommandDelays> {
+        &self.command_delays
+    }
+
+    /// Returns the path of the serial port to open, detecting the stick if necessary
+    #[cfg(feature = "list-ports")]
+    pub fn resolve_port(&self) -> core::result::Result<String, zwave_serial::DetectPortError> {
+        match &self.port {
+            PortSelection::Path(path) => Ok(path.clone()),
+            PortSelection::AutoDetect => zwave_serial::auto_detect_port(),
+        }
+    }
 }
 
 #[derive(Default, Clone, TypedBuilder)]
@@ -140,3 +386,76 @@ pub struct SecurityKeys {
     #[builder(default, setter(into, strip_option))]
     pub s2_access_control: Option<NetworkKey>,
 }
+
+/// How the driver configures the wakeup of sleeping nodes during the interview
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct WakeUpOptions {
+    /// The wakeup interval in seconds. It is adjusted to what the node supports. Default: 3600
+    #[builder(default = 3600)]
+    pub interval: u32,
+    /// Whether to keep the node's wakeup interval and destination as they are. Default: false
+    #[builder(default = false)]
+    pub keep_device_config: bool,
+}
+
+impl Default for WakeUpOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// How the driver queries the CC versions during the interview
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct VersionQueryOptions {
+    /// How many version queries are sent back to back. Default: 8
+    #[builder(default = 8)]
+    pub batch_size: usize,
+    /// How long to pause between two batches of version queries, so other commands
+    /// are not delayed by a long interview. Default: no pause
+    #[builder(default, setter(strip_option))]
+    pub batch_delay: Option<Duration>,
+}
+
+impl Default for VersionQueryOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl Driver {
+    /// Changes how the CC versions are queried during future interviews
+    pub fn set_version_query_options(&self, options: VersionQueryOptions) {
+        self.storage.version_query_options().set(options);
+    }
+
+    pub fn version_query_options(&self) -> VersionQueryOptions {
+        self.storage.version_query_options().get()
+    }
+
+    /// Changes how the wakeup of sleeping nodes is configured during future interviews
+    pub fn set_wake_up_options(&self, options: WakeUpOptions) {
+        self.storage.wake_up_options().set(options);
+    }
+
+    pub fn wake_up_options(&self) -> WakeUpOptions {
+        self.storage.wake_up_options().get()
+    }
+
+    /// Returns the controller settings that were changed through the API,
+    /// so the application can persist them
+    pub fn controller_settings(&self) -> ControllerSettings {
+        self.storage.controller_settings().get()
+    }
+
+    /// Restores previously persisted controller settings.
+    /// They are applied the next time the controller is configured.
+    pub fn restore_controller_settings(&self, settings: ControllerSettings) {
+        self.storage.controller_settings().set(settings);
+    }
+
+    /// Returns how often commands were received that are not implemented by the library,
+    /// including some of their payloads, most frequent first
+    pub fn unknown_commands(&self) -> Vec<UnknownCommandStatistics> {
+        self.serial_api.unknown_commands()
+    }
+}
@@ -1,14 +1,18 @@
-use super::{AwaitedCC, DriverActor, DriverInput};
-use crate::error::{Error, Result};
+use super::awaited::AWAITED_SWEEP_INTERVAL;
+use super::{AwaitedCC, DriverActor, DriverEvent, DriverInput, ValueUpdateKind};
+use crate::{SerialApiInput, UnknownCommandType};
+use alloc::collections::BTreeMap;
+use crate::error::{ActorKind, Error, FatalError, Result};
 use zwave_pal::prelude::*;
-use zwave_cc::commandclass::{CCSession, CcOrRaw};
+use zwave_cc::commandclass::security::SecurityCCNonceReport;
+use zwave_cc::commandclass::{CCSession, CcOrRaw, NotImplemented};
 use zwave_cc::prelude::*;
 use zwave_core::prelude::*;
 use zwave_core::security::{
     SecurityManager, SecurityManager2, SecurityManager2Storage, SecurityManagerOptions,
-    SecurityManagerStorage,
 };
 use zwave_core::log::Loglevel;
+use zwave_core::value_id::EndpointValueId;
 use zwave_pal::time::MaybeSleep;
 use zwave_logging::loggers::node::NodeLogger;
 use zwave_logging::{
@@ -16,22 +20,39 @@ use zwave_logging::{
     Direction, LocalImmutableLogger, LogInfo,
 };
 use zwave_pal::time::Instant;
+use zwave_serial::command::SendDataRequest;
 use zwave_serial::prelude::*;
 
 impl DriverActor {
-    pub async fn run(&mut self) {
+    /// Runs the actor until it or the Serial API actor encounters an error they cannot recover
+    /// from. Both actors are stopped then.
+    pub async fn run(&mut self) -> core::result::Result<(), FatalError> {
         loop {
-            // Figure out if there is a timeout we need to wait for
-            let min_sleep_duration = self
+            if let Some(error) = self.fatal_error.take() {
+                self.stop(&error);
+                return Err(error);
+            }
+
+            // Figure out if there is a timeout we need to wait for.
+            // Obsolete awaited entries are removed regularly.
+            let wake_up_at = self
                 .awaited_ccs
                 .iter()
                 .filter_map(|cc| cc.timeout)
-                .min()
-                .map(|t| t - Instant::now());
-            let maybe_sleep = MaybeSleep::new(min_sleep_duration);
+                .fold(self.next_awaited_sweep, |min, t| min.min(t));
+            let min_sleep_duration = wake_up_at
+                .checked_duration_since(Instant::now())
+                .unwrap_or_default();
+            let maybe_sleep = MaybeSleep::new(Some(min_sleep_duration));
 
             zwave_pal::select_biased! {
                 // Handle inputs
+                // Errors of lower layers take precedence, because they prevent everything else
+                error = self.supervision_rx.recv() => {
+                    if let Some(error) = error {
+                        self.fatal_error.get_or_insert(error);
+                    }
+                },
                 input = self.input_rx.recv() => {
                     if let Some(input) = input {
                         self.handle_input(input);
@@ -40,6 +61,7 @@ impl DriverActor {
                 // before timeouts
                 _ = maybe_sleep => {
                     self.handle_timeouts();
+                    self.sweep_awaited_if_due();
                 }
             }
         }
@@ -65,9 +87,10 @@ impl DriverActor {
             }
 
             DriverInput::Log { log, level } => {
-                self.log_queue
-                    .try_send((log, level))
-                    .expect("Failed to log message");
+                #[cfg(feature = "diagnostics")]
+                self.serial_api.storage.record_log(&log, level);
+                // Like our own logs, these are dropped if the logger does not keep up
+                let _ = self.log_queue.try_send((log, level));
             }
 
             DriverInput::AwaitCC {
@@ -97,29 +120,57 @@ impl DriverActor {
             if timed_out {
                 // This CC has timed out, send an error to the callback
                 let _ = cc.callback.send(Err(Error::Timeout));
-            } else {
-                // Preserve the awaited CCs that haven't timed out yet
+            } else if !cc.callback.is_canceled() {
+                // Preserve the awaited CCs that haven't timed out yet and are still awaited
                 remaining.push(cc);
             }
         }
         self.awaited_ccs = remaining;
     }
 
+    /// Removes the awaited entries nobody waits for anymore, if it is time to do so
+    fn sweep_awaited_if_due(&mut self) {
+        let now = Instant::now();
+        if now < self.next_awaited_sweep {
+            return;
+        }
+        self.next_awaited_sweep = now + AWAITED_SWEEP_INTERVAL;
+
+        let max_age = self.storage.awaited_max_age().get();
+        let removed_ccs = self.storage.awaited_ccs().sweep(max_age);
+        let removed_commands = self.storage.awaited_commands().sweep(max_age);
+        if removed_ccs + removed_commands > 0 {
+            self.driver_log().warn(|| {
+                format!(
+                    "removed {} awaited CCs and {} awaited commands nobody was waiting for",
+                    removed_ccs, removed_commands
+                )
+            });
+        }
+    }
+
     fn take_matching_awaited_cc(
         &mut self,
         cc: &WithAddress<CC>,
-    ) -> Option<zwave_pal::channel::oneshot::Sender<Result<WithAddress<CC>>>> {
+    ) -> Option<AwaitedCallback> {
+        // Entries registered through the shared storage take precedence,
+        // since they were registered before the request was sent
+        if let Some(channel) = self.storage.awaited_ccs().take_matching(cc) {
+            return Some(AwaitedCallback::Registry(channel));
+        }
+        // Whoever awaited a CC without a timeout may have stopped waiting
+        self.awaited_ccs.retain(|a| !a.callback.is_canceled());
         let index = self.awaited_ccs.iter().position(|a| (a.predicate)(cc));
-        index.map(|i| self.awaited_ccs.remove(i).callback)
+        index.map(|i| AwaitedCallback::Input(self.awaited_ccs.remove(i).callback))
     }
 
     fn get_cc_parsing_context(&self, address: &CCAddress) -> CCParsingContext {
         CCParsingContext::builder()
             .source_node_id(address.source_node_id)
             .frame_addressing(Some((&address.destination).into()))
-            .own_node_id(self.serial_api.storage.own_node_id().get())
-            .security_manager(self.storage.security_manager().cloned())
-            .security_manager2(self.storage.security_manager2().cloned())
+            .own_node_id(self.serial_api.storage.own_node_id())
+            .security(self.storage.security_managers())
+            .strictness(self.storage.parsing_strictness().get())
             .build()
     }
 
@@ -136,11 +187,27 @@ impl DriverActor {
             let (address, cc_or_raw) = cc.as_parts_mut();
 
             let ctx = self.get_cc_parsing_context(address);
+            let deviations = ctx.deviations().clone();
             match cc_or_raw.clone().try_as_cc(ctx) {
                 Ok(parsed_cc) => {
+                    for deviation in deviations.take() {
+                        self.handle_spec_deviation(
+                            address.source_node_id,
+                            parsed_cc.cc_id(),
+                            deviation,
+                        );
+                    }
                     // Update the command, so it gets logged correctly
                     *cc_or_raw = CcOrRaw::CC(parsed_cc);
                 }
+                Err(e) if e.is_checksum_mismatch() => {
+                    let data = cc_or_raw
+                        .as_raw(&CCEncodingContext::default())
+                        .as_bytes()
+                        .to_vec();
+                    self.handle_corrupted_command(address.source_node_id, data);
+                    return;
+                }
                 Err(e) => {
                     self.driver_log()
                         .error(|| format!("failed to parse CC: {}", e));
@@ -149,51 +216,235 @@ impl DriverActor {
             };
 
             // TODO: This back and forth is pretty awkward
-            let CcOrRaw::CC(cc) = cc_or_raw else {
-                panic!("The CC should have been parsed already")
-            };
-            let mut cc = cc.clone().with_address(address.clone());
-
-            // Check if there is someone waiting for this CC
-            if let Some(callback) = self.take_matching_awaited_cc(&cc) {
-                self.node_log(cc.address().source_node_id, cc.address().endpoint_index)
-                    .command(&command, Direction::Inbound);
-
-                let _ = callback.send(Ok(cc));
+            let CcOrRaw::CC(parsed_cc) = cc_or_raw else {
+                self.escalate("a CC was not parsed after parsing succeeded".to_string());
                 return;
+            };
+            if let CC::NotImplemented(unknown) = &*parsed_cc {
+                self.handle_unknown_cc(address.source_node_id, unknown);
             }
+            let mut cc = parsed_cc.clone().with_address(address.clone());
 
-            let node_logger =
-                self.node_log(cc.address().source_node_id, cc.address().endpoint_index);
-
-            // Check if the CC is split across multiple partial CCs
+            // Check if the CC is split across multiple partial CCs. This needs to happen before
+            // anything else, so awaited responses can be matched against the complete CC.
             if let Some(_session_id) = cc.session_id() {
                 // FIXME: Look up other partial CCs and pass them to merge_session
                 // If so, try to merge it
                 let ctx = self.get_cc_parsing_context(cc.address());
                 if let Err(e) = cc.merge_session(ctx, alloc::vec![]) {
-                    node_logger.error(|| format!("failed to merge partial CCs: {}", e));
+                    self.node_log(cc.address().source_node_id, cc.address().endpoint_index)
+                        .error(|| format!("failed to merge partial CCs: {}", e));
                     return;
                 }
+                // Update the command, so the merged CC gets logged
+                *parsed_cc = (*cc).clone();
+            }
+
+            // Responses someone is waiting for are processed even if the node floods the network
+            let awaited = self.take_matching_awaited_cc(&cc);
+            let accepted = self.record_report(cc.address().source_node_id).is_accepted();
+            if awaited.is_none() && !accepted {
+                return;
+            }
+
+            self.handle_cc_values(&cc);
+            self.storage
+                .track_transition(cc.address().source_node_id, &cc);
+            if let CC::WakeUpCCNotification(_) = *cc {
+                self.handle_wake_up_notification(cc.address().source_node_id);
             }
 
-            node_logger.command(&command, Direction::Inbound);
+            // Check if there is someone waiting for this CC
+            if let Some(callback) = awaited {
+                self.node_log(cc.address().source_node_id, cc.address().endpoint_index)
+                    .command(&command, Direction::Inbound);
+
+                match callback {
+                    AwaitedCallback::Registry(channel) => {
+                        let _ = channel.send(cc);
+                    }
+                    AwaitedCallback::Input(channel) => {
+                        let _ = channel.send(Ok(cc));
+                    }
+                }
+                return;
+            }
+
+            self.node_log(cc.address().source_node_id, cc.address().endpoint_index)
+                .command(&command, Direction::Inbound);
+
+            if let CC::SecurityCCNonceGet(_) = *cc {
+                self.handle_nonce_get(cc.address().source_node_id);
+            } else if cc.cc_id() == CommandClasses::ControllerReplication {
+                self.handle_replication_cc(&cc);
+            } else if !self.handle_virtual_endpoint_cc(&cc) {
+                self.handle_controller_query(&cc);
+            }
         } else {
             self.controller_log().command(&command, Direction::Inbound);
+
+            // Check if there is someone waiting for this command
+            if let Some(channel) = self.storage.awaited_commands().take_matching(&command) {
+                let _ = channel.send(command);
+            }
+        }
+    }
+
+    fn handle_unknown_cc(&self, node_id: NodeId, cc: &NotImplemented) {
+        let command = UnknownCommandType::CC {
+            cc_id: cc.cc_id,
+            cc_command: cc.cc_command,
+        };
+        if self
+            .serial_api
+            .storage
+            .record_unknown_command(command, &cc.payload)
+        {
+            self.node_log(node_id, EndpointIndex::Root).warn(|| {
+                format!(
+                    "received a command that is not implemented: {}, payload: 0x{}",
+                    command,
+                    hex::encode(&cc.payload)
+                )
+            });
         }
     }
 
+    /// Responds to a node's request for a nonce, which it needs to send us a secure (S0) command
+    fn handle_nonce_get(&self, node_id: NodeId) {
+        let Some(sec_man) = self.storage.security_manager() else {
+            self.node_log(node_id, EndpointIndex::Root).warn(|| {
+                "cannot respond to the nonce request, no S0 network key is configured"
+            });
+            return;
+        };
+
+        let nonce = sec_man.generate_nonce(node_id);
+        // The nonce is only valid for a short time, so this must not wait in the send queue
+        let report = SecurityCCNonceReport::builder().nonce(nonce).build().into();
+        self.serial_api
+            .dispatch_serial_api_command(self.send_data_request(node_id, report), true);
+    }
+
+    /// Sends a CC to a node from within the actor, without waiting for the result
+    pub(super) fn send_cc(&self, node_id: NodeId, cc: CC) {
+        self.serial_api
+            .dispatch_serial_api_command(self.send_data_request(node_id, cc), false);
+    }
+
+    fn send_data_request(&self, node_id: NodeId, cc: CC) -> SendDataRequest {
+        let ctx = CCEncodingContext::builder()
+            .own_node_id(self.serial_api.storage.own_node_id())
+            .node_id(node_id)
+            .build();
+        SendDataRequest::builder()
+            .node_id(node_id)
+            .command(cc.as_raw(&ctx).into())
+            .build()
+    }
+
+    /// Stores the stateful values of a received CC and passes changed values
+    /// and all events on to the application
+    fn handle_corrupted_command(&self, node_id: NodeId, data: Vec<u8>) {
+        self.node_log(node_id, EndpointIndex::Root).warn(|| {
+            format!(
+                "discarding command with invalid checksum: 0x{}",
+                hex::encode(&data)
+            )
+        });
+        self.storage.nodes().update(|nodes| {
+            if let Some(node) = nodes.get_mut(&node_id) {
+                node.statistics.crc16_errors += 1;
+            }
+        });
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_corrupted_command(
+            self.serial_api.storage.label().cloned().as_deref(),
+            node_id,
+        );
+        self.emit_event(DriverEvent::CorruptedCommand { node_id, data });
+    }
+
+    fn handle_cc_values(&self, cc: &WithAddress<CC>) {
+        // The values of mapped Basic CC reports are stored in the CC they correspond to
+        if let Some(mapped) = self.map_basic_cc(cc) {
+            return self.handle_cc_values(&mapped);
+        }
+
+        let address = cc.address();
+        let endpoint_value_id = |value_id| {
+            EndpointValueId::new(address.source_node_id, address.endpoint_index, value_id)
+        };
+
+        let now = Instant::now();
+        for (value_id, value) in cc.to_values() {
+            let value_id = endpoint_value_id(value_id);
+            self.storage
+                .value_timestamps()
+                .update(|timestamps| timestamps.insert(value_id, now));
+            let changed = self.storage.value_cache().update(|cache| {
+                cache.insert(value_id, value.clone()).as_ref() != Some(&value)
+            });
+            // A pending value is resolved by the report, even if the Set had no effect
+            let was_pending = self
+                .storage
+                .pending_values()
+                .update(|pending| pending.remove(&value_id).is_some());
+            if changed || was_pending {
+                self.emit_event(DriverEvent::ValueUpdated {
+                    value_id,
+                    value,
+                    kind: ValueUpdateKind::Reported,
+                });
+            }
+        }
+
+        // Events are not stored, so receiving the same event twice is not mistaken for no change
+        for (value_id, value) in cc.to_events() {
+            self.emit_event(DriverEvent::ValueNotification {
+                value_id: endpoint_value_id(value_id),
+                value,
+            });
+        }
+    }
+
+    /// Reports an error this actor cannot recover from. The actor stops afterwards.
+    fn escalate(&mut self, reason: String) {
+        let error = FatalError {
+            actor: ActorKind::Driver,
+            reason,
+        };
+        self.driver_log().error(|| error.to_string());
+        self.fatal_error.get_or_insert(error);
+    }
+
+    /// Tears down both actors after a fatal error and notifies the application about it
+    fn stop(&mut self, error: &FatalError) {
+        // The Serial API actor may already have stopped, if the error originated there
+        self.serial_api.dispatch(SerialApiInput::Stop);
+        // Nobody can receive the awaited CCs anymore
+        for awaited in self.awaited_ccs.drain(..) {
+            let _ = awaited.callback.send(Err(Error::Stopped));
+        }
+        self.emit_event(DriverEvent::FatalError {
+            error: error.clone(),
+        });
+    }
+
+    pub(super) fn emit_event(&self, event: DriverEvent) {
+        super::emit_event(&self.storage, &self.event_tx, event);
+    }
+
     fn init_security_managers(&mut self) {
         let logger = self.driver_log();
 
         if let Some(ref s0_key) = self.security_keys.s0_legacy {
             logger.info(|| "Network key for S0 configured, enabling S0 security manager...");
-            let storage = SecurityManagerStorage::new(SecurityManagerOptions {
-                own_node_id: self.serial_api.storage.own_node_id().get(),
+            let sec_man = SecurityManager::new(SecurityManagerOptions {
+                own_node_id: self.serial_api.storage.own_node_id(),
                 network_key: s0_key.clone(),
             });
-            let sec_man = SecurityManager::new(Arc::new(storage));
-            let _ = self.storage.security_manager().replace(Some(sec_man));
+            self.storage.set_security_manager(Some(sec_man));
         } else {
             logger.warn(|| "No network key for S0 configured, communication with secure (S0) devices won't work!");
         }
@@ -218,8 +469,17 @@ impl DriverActor {
             if let Some(ref key) = self.security_keys.s2_access_control {
                 sec_man.set_key(SecurityClass::S2AccessControl, key.clone());
             }
+            sec_man.restore_own_sequence_numbers(
+                &self
+                    .storage
+                    .pending_s2_sequence_numbers()
+                    .replace(BTreeMap::new()),
+            );
+            let s2_state = self.storage.pending_s2_state().replace(Default::default());
+            sec_man.restore_spans(&s2_state.spans);
+            sec_man.restore_peer_mpans(&s2_state.peer_mpans);
 
-            let _ = self.storage.security_manager2().replace(Some(sec_man));
+            self.storage.set_security_manager2(Some(sec_man));
         } else {
             logger.warn(|| "No network keys for S2 configured, communication with secure (S2) devices won't work!");
         }
@@ -228,6 +488,8 @@ impl DriverActor {
 
 impl LocalImmutableLogger for DriverActor {
     fn log(&self, log: LogInfo, level: Loglevel) {
+        #[cfg(feature = "diagnostics")]
+        self.serial_api.storage.record_log(&log, level);
         let _ = self.log_queue.clone().try_send((log, level));
     }
 
@@ -239,3 +501,369 @@ impl LocalImmutableLogger for DriverActor {
         todo!()
     }
 }
+
+/// Where the awaited CC needs to be delivered to
+enum AwaitedCallback {
+    /// Registered in the shared storage by an API handle
+    Registry(zwave_pal::channel::oneshot::Sender<WithAddress<CC>>),
+    /// Registered using [`DriverInput::AwaitCC`]
+    Input(zwave_pal::channel::oneshot::Sender<Result<WithAddress<CC>>>),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockDriver, mock_driver, mock_protocol_data};
+    use bytes::Bytes;
+    use core::time::Duration;
+    use futures::executor::block_on;
+    use futures::FutureExt;
+    use zwave_cc::commandclass::basic::{BasicCCGet, BasicCCReport, BasicCCSet, BasicCCValues};
+    use zwave_cc::commandclass::BinarySwitchCCValues;
+    use zwave_core::cache::{Cache, CacheValue};
+    use zwave_serial::command::ApplicationCommandRequest;
+
+    fn basic_report_from(node_id: NodeId) -> Command {
+        let report = BasicCCReport {
+            current_value: LevelReport::Level(50),
+            target_value: None,
+            duration: None,
+        };
+        command_from(node_id, report.into())
+    }
+
+    fn command_from(node_id: NodeId, cc: CC) -> Command {
+        command_from_cc_or_raw(node_id, CcOrRaw::CC(cc))
+    }
+
+    fn command_from_cc_or_raw(node_id: NodeId, cc: CcOrRaw) -> Command {
+        let address = CCAddress {
+            source_node_id: node_id,
+            destination: Destination::Singlecast(NodeId::new(1u8)),
+            endpoint_index: EndpointIndex::Root,
+        };
+        ApplicationCommandRequest {
+            frame_info: FrameInfo {
+                low_power: false,
+                frame_addressing: FrameAddressing::Singlecast,
+                explorer_frame: false,
+                foreign_target_node: false,
+                foreign_home_id: false,
+            },
+            address: address.clone(),
+            command: cc.with_address(address),
+            rssi: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_early_response_is_not_lost() {
+        let MockDriver {
+            driver, mut actor, ..
+        } = mock_driver();
+
+        let request = CC::from(BasicCCGet::default()).with_destination(NodeId::new(2u8).into());
+        let awaited = driver.register_awaited_cc(
+            Box::new(move |recv| {
+                recv.address().source_node_id == NodeId::new(2u8)
+                    && request.test_response(recv)
+            }),
+            Some(Duration::from_secs(1)),
+        );
+
+        // The report is received while the transaction is still waiting for its callback
+        actor.handle_input(DriverInput::Unsolicited {
+            command: basic_report_from(NodeId::new(2u8)),
+        });
+
+        let response = block_on(awaited.try_await()).unwrap();
+        assert_eq!(response.address().source_node_id, NodeId::new(2u8));
+        assert!(matches!(&*response, CC::BasicCCReport(_)));
+    }
+
+    #[test]
+    fn test_abandoned_awaited_ccs_do_not_consume_reports() {
+        let MockDriver { mut actor, .. } = mock_driver();
+
+        let mut receivers = Vec::new();
+        for _ in 0..2 {
+            let (tx, rx) = zwave_pal::channel::oneshot::channel();
+            actor.handle_input(DriverInput::AwaitCC {
+                predicate: Box::new(|recv| recv.address().source_node_id == NodeId::new(2u8)),
+                timeout: None,
+                callback: tx,
+            });
+            receivers.push(rx);
+        }
+        // Whoever waited first is no longer interested
+        drop(receivers.remove(0));
+
+        actor.handle_input(DriverInput::Unsolicited {
+            command: basic_report_from(NodeId::new(2u8)),
+        });
+
+        let response = block_on(receivers.remove(0)).unwrap().unwrap();
+        assert_eq!(response.address().source_node_id, NodeId::new(2u8));
+        assert!(actor.awaited_ccs.is_empty());
+    }
+
+    #[test]
+    fn test_unrelated_reports_are_not_consumed() {
+        let MockDriver {
+            driver, mut actor, ..
+        } = mock_driver();
+
+        let awaited = driver.register_awaited_cc(
+            Box::new(|recv| recv.address().source_node_id == NodeId::new(2u8)),
+            Some(Duration::from_millis(10)),
+        );
+
+        actor.handle_input(DriverInput::Unsolicited {
+            command: basic_report_from(NodeId::new(3u8)),
+        });
+
+        assert!(matches!(block_on(awaited.try_await()), Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn test_unknown_ccs_are_counted() {
+        let MockDriver {
+            driver, mut actor, ..
+        } = mock_driver();
+
+        // The Meter CC is not implemented
+        for payload in [&[0x01][..], &[0x02]] {
+            let raw = CCRaw {
+                cc_id: CommandClasses::Meter,
+                cc_command: Some(0x02),
+                payload: Bytes::copy_from_slice(payload),
+            };
+            actor.handle_input(DriverInput::Unsolicited {
+                command: command_from_cc_or_raw(NodeId::new(2u8), raw.into()),
+            });
+        }
+
+        let unknown = driver.unknown_commands();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(
+            unknown[0].command,
+            crate::UnknownCommandType::CC {
+                cc_id: CommandClasses::Meter,
+                cc_command: Some(0x02),
+            }
+        );
+        assert_eq!(unknown[0].count, 2);
+        assert_eq!(unknown[0].samples.len(), 2);
+    }
+
+    #[test]
+    fn test_corrupted_commands_are_counted() {
+        let MockDriver {
+            driver,
+            mut actor,
+            mut adapter,
+            ..
+        } = mock_driver();
+
+        let node_id = NodeId::new(2u8);
+        driver.storage.nodes().update(|nodes| {
+            let protocol_data = mock_protocol_data(true);
+            nodes.insert(node_id, NodeStorage::new(protocol_data));
+        });
+
+        // A CRC-16 encapsulated Basic Get with an invalid checksum
+        let raw = CCRaw {
+            cc_id: CommandClasses::CRC16Encapsulation,
+            cc_command: Some(0x01),
+            payload: Bytes::from_static(&[0x20, 0x02, 0x00, 0x00]),
+        };
+        actor.handle_input(DriverInput::Unsolicited {
+            command: command_from_cc_or_raw(node_id, raw.into()),
+        });
+
+        assert_eq!(
+            driver
+                .storage
+                .nodes()
+                .inspect(|nodes| nodes[&node_id].statistics.crc16_errors),
+            1
+        );
+        assert!(matches!(
+            adapter.event_rx.recv().now_or_never(),
+            Some(Some(DriverEvent::CorruptedCommand { node_id: id, data }))
+                if id == node_id && data == [0x56, 0x01, 0x20, 0x02, 0x00, 0x00]
+        ));
+    }
+
+    #[test]
+    fn test_reported_values_are_stored() {
+        let MockDriver {
+            driver,
+            mut actor,
+            mut adapter,
+            ..
+        } = mock_driver();
+
+        let node_id = NodeId::new(2u8);
+        let value_id = BasicCCValues::current_value().id.with_node_id(&node_id);
+        for _ in 0..2 {
+            actor.handle_input(DriverInput::Unsolicited {
+                command: basic_report_from(node_id),
+            });
+        }
+
+        assert_eq!(
+            driver.value_cache().read(&value_id),
+            Some(CacheValue::LevelReport(LevelReport::Level(50)))
+        );
+        // Only the first report changed the value
+        assert!(matches!(
+            adapter.event_rx.recv().now_or_never(),
+            Some(Some(DriverEvent::ValueUpdated { value_id: id, .. })) if id == value_id
+        ));
+        assert!(adapter.event_rx.recv().now_or_never().is_none());
+    }
+
+    #[test]
+    fn test_reports_resolve_pending_values() {
+        let MockDriver {
+            driver,
+            mut actor,
+            mut adapter,
+            ..
+        } = mock_driver();
+
+        let node_id = NodeId::new(2u8);
+        let value_id = BasicCCValues::current_value().id.with_node_id(&node_id);
+        actor.handle_input(DriverInput::Unsolicited {
+            command: basic_report_from(node_id),
+        });
+        let _ = adapter.event_rx.recv().now_or_never();
+
+        // The Set had no effect, but the report still confirms the value
+        driver.storage.pending_values().update(|pending| {
+            pending.insert(value_id, CacheValue::LevelReport(LevelReport::Level(99)))
+        });
+        actor.handle_input(DriverInput::Unsolicited {
+            command: basic_report_from(node_id),
+        });
+
+        assert!(matches!(
+            adapter.event_rx.recv().now_or_never(),
+            Some(Some(DriverEvent::ValueUpdated {
+                value_id: id,
+                kind: ValueUpdateKind::Reported,
+                ..
+            })) if id == value_id
+        ));
+        assert!(
+            driver
+                .storage
+                .pending_values()
+                .inspect(|pending| pending.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_events_are_not_stored() {
+        let MockDriver {
+            driver,
+            mut actor,
+            mut adapter,
+            ..
+        } = mock_driver();
+
+        let node_id = NodeId::new(2u8);
+        let value_id = BasicCCValues::event().id.with_node_id(&node_id);
+        assert!(!BasicCCValues::event().metadata.is_stateful());
+
+        let set = BasicCCSet::builder().target_value(LevelSet::Level(99)).build();
+        for _ in 0..2 {
+            actor.handle_input(DriverInput::Unsolicited {
+                command: command_from(node_id, set.clone().into()),
+            });
+        }
+
+        assert_eq!(driver.value_cache().read(&value_id), None);
+        // Identical events are passed on every time
+        for _ in 0..2 {
+            assert!(matches!(
+                adapter.event_rx.recv().now_or_never(),
+                Some(Some(DriverEvent::ValueNotification { value_id: id, .. })) if id == value_id
+            ));
+        }
+    }
+
+    #[test]
+    fn test_basic_reports_are_mapped() {
+        let MockDriver {
+            driver, mut actor, ..
+        } = mock_driver();
+
+        // A binary switch, whose Basic CC corresponds to the Binary Switch CC
+        let node_id = NodeId::new(2u8);
+        driver.storage.nodes().update(|nodes| {
+            let protocol_data = mock_protocol_data(true);
+            let mut node = NodeStorage::new(protocol_data);
+            node.endpoints
+                .get_mut(&EndpointIndex::Root)
+                .unwrap()
+                .cc_info
+                .insert(
+                    CommandClasses::BinarySwitch,
+                    PartialCommandClassInfo::default().supported().into(),
+                );
+            nodes.insert(node_id, node);
+        });
+
+        actor.handle_input(DriverInput::Unsolicited {
+            command: basic_report_from(node_id),
+        });
+
+        let basic_value_id = BasicCCValues::current_value().id.with_node_id(&node_id);
+        let switch_value_id = BinarySwitchCCValues::current_value()
+            .id
+            .with_node_id(&node_id);
+        assert_eq!(driver.value_cache().read(&basic_value_id), None);
+        assert_eq!(
+            driver.value_cache().read(&switch_value_id),
+            Some(CacheValue::BinaryReport(BinaryReport::On))
+        );
+    }
+
+    #[test]
+    fn test_fatal_errors_stop_the_driver() {
+        let MockDriver {
+            driver,
+            mut actor,
+            mut adapter,
+            mut serial_api_actor,
+            ..
+        } = mock_driver();
+
+        let (tx, rx) = zwave_pal::channel::oneshot::channel();
+        actor.handle_input(DriverInput::AwaitCC {
+            predicate: Box::new(|_| true),
+            timeout: None,
+            callback: tx,
+        });
+
+        let error = FatalError {
+            actor: ActorKind::SerialApi,
+            reason: "the serial port was closed".to_string(),
+        };
+        let supervisor = driver.serial_api.storage.supervisor().cloned().unwrap();
+        supervisor.try_send(error.clone()).unwrap();
+
+        assert_eq!(block_on(actor.run()), Err(error.clone()));
+        assert!(matches!(block_on(rx), Ok(Err(Error::Stopped))));
+        assert!(matches!(
+            block_on(adapter.event_rx.recv()),
+            Some(DriverEvent::FatalError { error: e }) if e == error
+        ));
+        // The Serial API actor is stopped too
+        assert_eq!(block_on(serial_api_actor.run()), Ok(()));
+    }
+}
@@ -1,9 +1,15 @@
 use crate::error::{Error, Result};
 use zwave_pal::prelude::*;
 use core::sync::atomic::{AtomicI32, Ordering};
-use zwave_pal::time::MaybeSleep;
+use core::time::Duration;
+use zwave_pal::time::{Instant, MaybeSleep};
 use zwave_pal::channel::oneshot;
 
+/// How long an entry may be registered without being awaited before it is considered leaked
+pub(crate) const DEFAULT_AWAITED_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+/// How often obsolete entries are removed
+pub(crate) const AWAITED_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
 pub type Predicate<T> = Box<dyn Fn(&T) -> bool + Sync + Send>;
 
 /// A registry of `Awaited` values, each of which is associated with a predicate that determines
@@ -11,20 +17,53 @@ pub type Predicate<T> = Box<dyn Fn(&T) -> bool + Sync + Send>;
 ///
 /// Adding an entry hands out an `AwaitedRef`, which is used to receive the value when it is
 /// available. The `AwaitedRef` is automatically removed from the registry when it is dropped.
+/// Entries that are leaked nonetheless are removed by [`sweep`](AwaitedRegistry::sweep).
 pub struct AwaitedRegistry<T> {
     next_id: AtomicI32,
-    store: zwave_pal::sync::Mutex<Vec<Awaited<T>>>,
+    store: zwave_pal::sync::Mutex<AwaitedStore<T>>,
+}
+
+struct AwaitedStore<T> {
+    entries: Vec<Awaited<T>>,
+    completed: u64,
+    obsolete_removed: u64,
+}
+
+impl<T> AwaitedStore<T> {
+    /// Removes the entries matching the given predicate, returning how many were removed
+    fn remove_obsolete(&mut self, is_obsolete: impl Fn(&Awaited<T>) -> bool) -> usize {
+        let len = self.entries.len();
+        self.entries.retain(|a| !is_obsolete(a));
+        let removed = len - self.entries.len();
+        self.obsolete_removed += removed as u64;
+        removed
+    }
 }
 
 impl<T> Default for AwaitedRegistry<T> {
     fn default() -> Self {
         Self {
             next_id: AtomicI32::new(0),
-            store: zwave_pal::sync::Mutex::default(),
+            store: zwave_pal::sync::Mutex::new(AwaitedStore {
+                entries: Vec::new(),
+                completed: 0,
+                obsolete_removed: 0,
+            }),
         }
     }
 }
 
+/// How many entries of an [`AwaitedRegistry`] are active and what happened to the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AwaitedStatistics {
+    /// How many entries are still waiting for a value
+    pub active: usize,
+    /// How many entries received their value
+    pub completed: u64,
+    /// How many entries were removed because nobody was waiting for their value anymore
+    pub obsolete_removed: u64,
+}
+
 impl<T> AwaitedRegistry<T> {
     /// Adds an entry to the registry with a given predicate, returning an `AwaitedRef` that can be
     /// used to receive the value when it is available.
@@ -39,25 +78,62 @@ impl<T> AwaitedRegistry<T> {
             id,
             predicate,
             channel: tx,
+            registered_at: Instant::now(),
+            awaiting: false,
         };
-        self.store.lock(|vec| vec.push(awaited));
+        self.store.lock(|store| store.entries.push(awaited));
         AwaitedRef::new(id, self.clone(), timeout, rx)
     }
 
     /// Finds the first entry in the registry that matches the given value, returning the channel
     /// that can be used to receive the value when it is available.
-    /// The entry is removed from the registry.
+    /// The entry is removed from the registry. Entries whose receiver is gone are skipped, so
+    /// they cannot swallow the value.
     pub fn take_matching(self: &Arc<Self>, value: &T) -> Option<oneshot::Sender<T>> {
-        self.store.lock(|vec| {
-            let index = vec.iter().position(|a| (a.predicate)(value));
-            index.map(|i| vec.remove(i).channel)
+        self.store.lock(|store| {
+            store.remove_obsolete(|a| a.channel.is_canceled());
+            let index = store.entries.iter().position(|a| (a.predicate)(value))?;
+            store.completed += 1;
+            Some(store.entries.remove(index).channel)
+        })
+    }
+
+    /// Removes the entries nobody waits for anymore, i.e. whose receiver is gone or which were
+    /// not awaited within `max_age` after they were registered. Returns how many were removed.
+    pub fn sweep(&self, max_age: Duration) -> usize {
+        let now = Instant::now();
+        self.store.lock(|store| {
+            store.remove_obsolete(|a| {
+                a.channel.is_canceled()
+                    || (!a.awaiting
+                        && now
+                            .checked_duration_since(a.registered_at)
+                            .is_some_and(|age| age >= max_age))
+            })
+        })
+    }
+
+    pub fn statistics(&self) -> AwaitedStatistics {
+        self.store.lock(|store| AwaitedStatistics {
+            active: store.entries.len(),
+            completed: store.completed,
+            obsolete_removed: store.obsolete_removed,
         })
     }
 
+    /// Remembers that the value of the given entry is being awaited
+    fn set_awaiting(&self, id: i32) {
+        self.store.lock(|store| {
+            if let Some(awaited) = store.entries.iter_mut().find(|a| a.id == id) {
+                awaited.awaiting = true;
+            }
+        });
+    }
+
     /// Removes an entry from the registry using the given `AwaitedRef`.
     pub fn remove(self: &Arc<Self>, awaited: &AwaitedRef<T>) {
-        self.store.lock(|vec| {
-            vec.retain(|a| a.id != awaited.id);
+        self.store.lock(|store| {
+            store.entries.retain(|a| a.id != awaited.id);
         });
     }
 }
@@ -66,6 +142,10 @@ pub struct Awaited<T> {
     pub id: i32,
     pub predicate: Predicate<T>,
     pub channel: oneshot::Sender<T>,
+    registered_at: Instant,
+    /// Whether the `AwaitedRef` is being awaited. Only entries that are not can become obsolete
+    /// by age, since awaiting them has its own timeout.
+    awaiting: bool,
 }
 
 pub struct AwaitedRef<T> {
@@ -97,6 +177,7 @@ impl<T> AwaitedRef<T> {
             .channel
             .take()
             .expect("try_await may only be called once");
+        self.registry.set_awaiting(self.id);
         zwave_pal::select_biased! {
             result = receiver => result.map_err(|_| Error::Internal),
             _ = sleep => Err(Error::Timeout),
@@ -115,3 +196,53 @@ impl<T> Drop for AwaitedRef<T> {
         self.registry.remove(self);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::FutureExt;
+
+    #[test]
+    fn test_dropped_awaiters_are_removed() {
+        let registry = Arc::new(AwaitedRegistry::<u32>::default());
+        for i in 0..5000 {
+            let awaited = registry.add(Box::new(move |value| *value == i), None);
+            // Drop half of the awaiters while they are awaited, the others before that
+            if i % 2 == 0 {
+                let mut awaiting = Box::pin(awaited.try_await());
+                assert!(awaiting.as_mut().now_or_never().is_none());
+            }
+        }
+        assert_eq!(registry.statistics(), AwaitedStatistics::default());
+    }
+
+    #[test]
+    fn test_sweep_removes_leaked_awaiters() {
+        let registry = Arc::new(AwaitedRegistry::<u32>::default());
+        for i in 0..1000 {
+            core::mem::forget(registry.add(Box::new(move |value| *value == i), None));
+        }
+        // Awaiters without a timeout are never considered leaked while they are awaited
+        let mut awaiting = Box::pin(
+            registry
+                .add(Box::new(|value| *value == 1000), None)
+                .try_await(),
+        );
+        assert!(awaiting.as_mut().now_or_never().is_none());
+        assert_eq!(registry.statistics().active, 1001);
+
+        assert_eq!(registry.sweep(Duration::from_secs(3600)), 0);
+        assert_eq!(registry.sweep(Duration::ZERO), 1000);
+
+        registry.take_matching(&1000).unwrap().send(1000).ok();
+        assert_eq!(awaiting.now_or_never().unwrap().unwrap(), 1000);
+        assert_eq!(
+            registry.statistics(),
+            AwaitedStatistics {
+                active: 0,
+                completed: 1,
+                obsolete_removed: 1000,
+            }
+        );
+    }
+}
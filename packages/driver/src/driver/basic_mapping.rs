@@ -0,0 +1,57 @@
+use super::DriverActor;
+use zwave_cc::commandclass::{
+    BinarySwitchCCReport, CC, CCAddressable, MultilevelSwitchCCReport, WithAddress,
+};
+use zwave_core::prelude::*;
+
+impl DriverActor {
+    /// Maps a Basic CC Report to the CC it corresponds to according to the device class
+    /// of the node, so its values are stored there. This only happens if the node
+    /// supports that CC, in which case the Basic CC is hidden during the interview.
+    pub(super) fn map_basic_cc(&self, cc: &WithAddress<CC>) -> Option<WithAddress<CC>> {
+        let CC::BasicCCReport(report) = &**cc else {
+            return None;
+        };
+        let address = cc.address();
+        let target_cc = self.storage.nodes().inspect(|nodes| {
+            let node = nodes.get(&address.source_node_id)?;
+            let target_cc = node
+                .protocol_data
+                .device_class()
+                .generic
+                .basic_cc_mapping()?;
+            let supported = node
+                .endpoints
+                .get(&address.endpoint_index)?
+                .cc_info
+                .get(&target_cc)
+                .is_some_and(|info| info.supported);
+            supported.then_some(target_cc)
+        })?;
+
+        // FIXME: Map to the other CCs once they are implemented
+        let mapped: CC = match target_cc {
+            CommandClasses::BinarySwitch => {
+                let to_binary = |level: LevelReport| match level {
+                    LevelReport::Level(0) => BinaryReport::Off,
+                    LevelReport::Level(_) => BinaryReport::On,
+                    LevelReport::Unknown => BinaryReport::Unknown,
+                };
+                BinarySwitchCCReport {
+                    current_value: to_binary(report.current_value),
+                    target_value: report.target_value.map(to_binary),
+                    duration: report.duration,
+                }
+                .into()
+            }
+            CommandClasses::MultilevelSwitch => MultilevelSwitchCCReport {
+                current_value: report.current_value,
+                target_value: report.target_value,
+                duration: report.duration,
+            }
+            .into(),
+            _ => return None,
+        };
+        Some(mapped.with_address(address.clone()))
+    }
+}
@@ -1,8 +1,10 @@
 use super::{storage::DriverStorage, Driver};
 use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
 use zwave_core::{
     cache::{Cache, CacheValue},
-    value_id::EndpointValueId,
+    definitions::{EndpointIndex, NodeId},
+    value_id::{EndpointValueId, ValueId},
 };
 
 pub struct ValueCache<'a> {
@@ -13,6 +15,17 @@ impl<'a> ValueCache<'a> {
     pub(crate) fn new(storage: &'a Arc<DriverStorage>) -> Self {
         Self { storage }
     }
+
+    /// Returns the IDs of the cached values of the given endpoint
+    pub(crate) fn value_ids(&self, node_id: NodeId, endpoint: EndpointIndex) -> Vec<ValueId> {
+        self.storage.value_cache().inspect(|cache| {
+            cache
+                .keys()
+                .filter(|key| key.node_id() == node_id && key.endpoint() == endpoint)
+                .map(|key| key.value_id())
+                .collect()
+        })
+    }
 }
 
 impl Cache<EndpointValueId> for ValueCache<'_> {
@@ -45,4 +58,11 @@ impl Driver {
     pub(crate) fn value_cache(&self) -> ValueCache<'_> {
         ValueCache::new(&self.storage)
     }
+
+    /// Returns when the given value was last reported by its node
+    pub fn value_timestamp(&self, value_id: &EndpointValueId) -> Option<Instant> {
+        self.storage
+            .value_timestamps()
+            .inspect(|timestamps| timestamps.get(value_id).copied())
+    }
 }
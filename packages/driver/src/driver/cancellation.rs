@@ -0,0 +1,98 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures::future::{AbortHandle, Abortable, Aborted};
+use zwave_pal::prelude::*;
+
+/// The reason a [`Cancellable`] operation failed after it was cancelled.
+/// The error types of the driver API can be created from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+/// Cancels the operation it was created for. It can be cloned and sent to other tasks or threads.
+#[derive(Debug, Clone)]
+pub struct CancelHandle(AbortHandle);
+
+impl CancelHandle {
+    /// Cancels the operation. This has no effect if the operation has already completed.
+    ///
+    /// The operation is aborted the next time it is polled. Pending protocol operations are
+    /// stopped cleanly, e.g. a cancelled inclusion takes the controller out of inclusion mode.
+    pub fn cancel(&self) {
+        self.0.abort();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
+/// A long-running operation, like an inclusion, a network sweep or an interview, that resolves
+/// with a [`Cancelled`] error when it is cancelled through its [`CancelHandle`].
+pub struct Cancellable<F> {
+    /// The operation is dropped as soon as it was cancelled, so it can clean up right away
+    inner: Option<Pin<Box<Abortable<F>>>>,
+}
+
+impl<F, T, E> Future for Cancellable<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: From<Cancelled>,
+{
+    type Output = Result<T, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(inner) = self.inner.as_mut() else {
+            return Poll::Ready(Err(Cancelled.into()));
+        };
+        match inner.as_mut().poll(cx) {
+            Poll::Ready(Ok(result)) => Poll::Ready(result),
+            Poll::Ready(Err(Aborted)) => {
+                self.inner = None;
+                Poll::Ready(Err(Cancelled.into()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub trait CancellableExt: Future + Sized {
+    /// Makes this operation cancellable. Returns the operation, which must be awaited
+    /// like before, and a handle to cancel it.
+    fn cancellable(self) -> (Cancellable<Self>, CancelHandle) {
+        let (handle, registration) = AbortHandle::new_pair();
+        let operation = Cancellable {
+            inner: Some(Box::pin(Abortable::new(self, registration))),
+        };
+        (operation, CancelHandle(handle))
+    }
+}
+
+impl<F: Future> CancellableExt for F {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ControllerCommandError;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_cancel_before_completion() {
+        let (operation, handle) =
+            futures::future::pending::<Result<(), ControllerCommandError>>().cancellable();
+        handle.cancel();
+        assert!(handle.is_cancelled());
+        assert!(matches!(
+            block_on(operation),
+            Err(ControllerCommandError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_completed_operation_is_not_affected() {
+        let (operation, handle) =
+            futures::future::ready(Ok::<_, ControllerCommandError>(5)).cancellable();
+        assert!(matches!(block_on(operation), Ok(5)));
+        handle.cancel();
+    }
+}
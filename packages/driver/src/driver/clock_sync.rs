@@ -0,0 +1,306 @@
+use super::{Driver, ExecNodeCommandResult};
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::{
+    CC, CCAddressable, ClockCCSet, ClockTime, TimeParametersCCSet, UtcDateTime, Weekday,
+};
+use zwave_core::definitions::{CommandClasses, EndpointIndex, NodeId};
+use zwave_pal::prelude::*;
+use zwave_pal::time::{DateTime, Instant, WallClock};
+
+/// How often the scheduler checks whether a clock needs to be set again,
+/// e.g. because daylight saving time started or ended
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// When the clocks of nodes supporting the Clock or Time Parameters CC are set
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct ClockSyncOptions {
+    /// Whether the clocks are set after the interview and kept in sync afterwards.
+    /// This requires [`run_scheduler`](Driver::run_scheduler) to be running. Default: false
+    #[builder(default)]
+    pub enabled: bool,
+    /// How often the clocks are set again. They are also set when the UTC offset of the
+    /// local time changes. Default: weekly
+    #[builder(default = Duration::from_secs(7 * 24 * 60 * 60))]
+    pub interval: Duration,
+}
+
+impl Default for ClockSyncOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// When the clock of a node was last set
+#[derive(Debug, Clone, Copy)]
+struct LastClockSync {
+    at: Instant,
+    utc_offset_minutes: i32,
+}
+
+/// Keeps track of which clocks are kept in sync and when they were last set
+#[derive(Default)]
+pub(crate) struct ClockSyncState {
+    options: ClockSyncOptions,
+    /// The nodes whose clocks must not be set automatically
+    opted_out: BTreeSet<NodeId>,
+    last_syncs: BTreeMap<NodeId, LastClockSync>,
+}
+
+impl ClockSyncState {
+    /// Whether the clock of the given node needs to be set at the given wall-clock time
+    fn needs_sync(&self, node_id: NodeId, now: Instant, clock: &WallClock) -> bool {
+        let Some(last) = self.last_syncs.get(&node_id) else {
+            return true;
+        };
+        last.utc_offset_minutes != clock.utc_offset_minutes
+            || now
+                .checked_duration_since(last.at)
+                .is_some_and(|elapsed| elapsed >= self.options.interval)
+    }
+}
+
+fn clock_time(local: &DateTime) -> ClockTime {
+    ClockTime {
+        weekday: Weekday::try_from(local.weekday).unwrap_or(Weekday::Unknown),
+        hour: local.hour,
+        minute: local.minute,
+    }
+}
+
+fn utc_date_time(utc: &DateTime) -> UtcDateTime {
+    UtcDateTime {
+        year: utc.year,
+        month: utc.month,
+        day: utc.day,
+        hour: utc.hour,
+        minute: utc.minute,
+        second: utc.second,
+    }
+}
+
+impl Driver {
+    /// Changes whether and how often the clocks of nodes are set automatically
+    pub fn set_clock_sync_options(&self, options: ClockSyncOptions) {
+        self.storage
+            .clock_sync()
+            .update(|state| state.options = options);
+    }
+
+    pub fn clock_sync_options(&self) -> ClockSyncOptions {
+        self.storage.clock_sync().inspect(|state| state.options)
+    }
+
+    /// Configures whether the clock of the given node is set automatically
+    pub fn set_clock_sync_enabled(&self, node_id: NodeId, enabled: bool) {
+        self.storage.clock_sync().update(|state| {
+            if enabled {
+                state.opted_out.remove(&node_id);
+            } else {
+                state.opted_out.insert(node_id);
+                state.last_syncs.remove(&node_id);
+            }
+        });
+    }
+
+    /// Whether the clock of the given node is set automatically, if the node supports it
+    pub fn clock_sync_enabled(&self, node_id: NodeId) -> bool {
+        self.storage
+            .clock_sync()
+            .inspect(|state| state.options.enabled && !state.opted_out.contains(&node_id))
+    }
+
+    /// Sets the clock of the given node to the current time, using the Clock CC for the local
+    /// time and the Time Parameters CC for the UTC time, depending on what the node supports.
+    /// Returns whether a clock was set, which is not possible without a system clock.
+    pub async fn sync_clock(&self, node_id: NodeId) -> ExecNodeCommandResult<bool> {
+        let (clock, time_parameters) = self.storage.nodes().inspect(|nodes| {
+            let Some(root) = nodes
+                .get(&node_id)
+                .and_then(|node| node.endpoints.get(&EndpointIndex::Root))
+            else {
+                return (false, false);
+            };
+            let supports = |cc| root.cc_info.get(&cc).is_some_and(|info| info.supported);
+            (
+                supports(CommandClasses::Clock),
+                supports(CommandClasses::TimeParameters),
+            )
+        });
+        if !clock && !time_parameters {
+            return Ok(false);
+        }
+        let Some(now) = WallClock::now() else {
+            self.node_log(node_id, EndpointIndex::Root)
+                .warn(|| "cannot set the clock, because the system time is unknown");
+            return Ok(false);
+        };
+
+        self.node_log(node_id, EndpointIndex::Root)
+            .info(|| "setting the clock...");
+        if time_parameters {
+            let cc = CC::from(TimeParametersCCSet {
+                date_and_time: utc_date_time(&now.utc),
+            });
+            self.exec_node_command(&cc.with_destination(node_id.into()), None)
+                .await?;
+        }
+        if clock {
+            let cc = CC::from(ClockCCSet {
+                time: clock_time(&now.local),
+            });
+            self.exec_node_command(&cc.with_destination(node_id.into()), None)
+                .await?;
+        }
+
+        let last = LastClockSync {
+            at: Instant::now(),
+            utc_offset_minutes: now.utc_offset_minutes,
+        };
+        self.storage
+            .clock_sync()
+            .update(|state| state.last_syncs.insert(node_id, last));
+        Ok(true)
+    }
+
+    /// Sets the clock of a node whose interview was completed and keeps it in sync
+    pub(crate) async fn start_clock_sync(&self, node_id: NodeId) {
+        if !self.clock_sync_enabled(node_id) {
+            return;
+        }
+        match self.sync_clock(node_id).await {
+            Ok(false) => return,
+            Ok(true) => {}
+            Err(e) => {
+                self.node_log(node_id, EndpointIndex::Root)
+                    .warn(|| format!("failed to set the clock: {}", e));
+            }
+        }
+        self.schedule_clock_check(node_id);
+    }
+
+    fn schedule_clock_check(&self, node_id: NodeId) {
+        let now = Instant::now();
+        let due = now.checked_add(CLOCK_CHECK_INTERVAL).unwrap_or(now);
+        self.storage
+            .scheduler()
+            .update(|scheduler| scheduler.add_clock_check(node_id, due));
+    }
+
+    /// Sets the clock of the given node again if it is due, and schedules the next check.
+    /// Called by the scheduler.
+    pub(super) async fn check_clock(&self, node_id: NodeId) {
+        if !self.clock_sync_enabled(node_id)
+            || !self
+                .storage
+                .nodes()
+                .inspect(|nodes| nodes.contains_key(&node_id))
+        {
+            return;
+        }
+        if let Some(clock) = WallClock::now() {
+            let now = Instant::now();
+            let needs_sync = self
+                .storage
+                .clock_sync()
+                .inspect(|state| state.needs_sync(node_id, now, &clock));
+            if needs_sync {
+                if let Err(e) = self.sync_clock(node_id).await {
+                    self.node_log(node_id, EndpointIndex::Root)
+                        .warn(|| format!("failed to set the clock: {}", e));
+                }
+            }
+        }
+        self.schedule_clock_check(node_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use zwave_core::prelude::*;
+
+    fn wall_clock(utc_offset_minutes: i32) -> WallClock {
+        let time = DateTime {
+            year: 2024,
+            month: 3,
+            day: 31,
+            weekday: 7,
+            hour: 3,
+            minute: 5,
+            second: 0,
+        };
+        WallClock {
+            local: time,
+            utc: time,
+            utc_offset_minutes,
+        }
+    }
+
+    #[test]
+    fn test_needs_sync() {
+        let node_id = NodeId::new(2u8);
+        let mut state = ClockSyncState::default();
+        let start = Instant::now();
+        assert!(state.needs_sync(node_id, start, &wall_clock(60)));
+
+        state.last_syncs.insert(
+            node_id,
+            LastClockSync {
+                at: start,
+                utc_offset_minutes: 60,
+            },
+        );
+        assert!(!state.needs_sync(node_id, start, &wall_clock(60)));
+        // Daylight saving time started
+        assert!(state.needs_sync(node_id, start, &wall_clock(120)));
+        let next_week = start.checked_add(state.options.interval).unwrap();
+        assert!(state.needs_sync(node_id, next_week, &wall_clock(60)));
+    }
+
+    #[test]
+    fn test_clock_is_set_after_interview() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            let mut node = NodeStorage::new(mock_protocol_data(true));
+            let root = node.endpoints.get_mut(&EndpointIndex::Root).unwrap();
+            root.cc_info.insert(
+                CommandClasses::Clock,
+                CommandClassInfo {
+                    supported: true,
+                    ..Default::default()
+                },
+            );
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(node_id, node);
+            });
+
+            // Disabled by default
+            driver.start_clock_sync(node_id).await;
+            driver.set_clock_sync_options(ClockSyncOptions::builder().enabled(true).build());
+            driver.set_clock_sync_enabled(node_id, false);
+            driver.start_clock_sync(node_id).await;
+            assert_eq!(driver.storage.scheduler().inspect(|s| s.len()), 0);
+
+            driver.set_clock_sync_enabled(node_id, true);
+            driver.start_clock_sync(node_id).await;
+            // The clock is checked again later
+            assert_eq!(driver.storage.scheduler().inspect(|s| s.len()), 1);
+        });
+
+        let sent: Vec<_> = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            .collect();
+        assert_eq!(sent.len(), 1);
+        // Node ID, payload length, then the Clock CC Set
+        assert_eq!(sent[0].payload[2..4], [CommandClasses::Clock as u8, 0x04]);
+    }
+}
@@ -0,0 +1,172 @@
+use super::Driver;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_core::definitions::{EndpointIndex, NodeId};
+use zwave_pal::time::{Instant, Timer};
+
+/// Minimum delays between the commands sent to a node.
+/// Some devices drop frames when commands arrive back to back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TypedBuilder)]
+pub struct CommandDelays {
+    /// How long to wait after a command to the node before sending the next one. Default: none
+    #[builder(default)]
+    pub between_commands: Duration,
+    /// How long to wait after a command without response, e.g. a Set command, before sending
+    /// one that expects a response, e.g. the Get verifying the Set. Default: none
+    #[builder(default)]
+    pub after_set: Duration,
+}
+
+impl Default for CommandDelays {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// The last command that was sent to a node
+#[derive(Debug, Clone, Copy)]
+struct LastCommand {
+    sent_at: Instant,
+    expects_response: bool,
+}
+
+/// Keeps track of the configured delays and when commands were last sent to each node
+#[derive(Default)]
+pub(crate) struct CommandDelayTracker {
+    delays: BTreeMap<NodeId, CommandDelays>,
+    last_commands: BTreeMap<NodeId, LastCommand>,
+}
+
+impl CommandDelayTracker {
+    /// Returns when the next command may be sent to the given node
+    fn next_command_at(&self, node_id: NodeId, expects_response: bool) -> Option<Instant> {
+        let delays = self.delays.get(&node_id)?;
+        let last = self.last_commands.get(&node_id)?;
+        let mut delay = delays.between_commands;
+        if expects_response && !last.expects_response {
+            delay = delay.max(delays.after_set);
+        }
+        last.sent_at.checked_add(delay)
+    }
+}
+
+impl Driver {
+    /// Configures the minimum delays between commands to the given node. `None` removes them.
+    pub fn set_command_delays(&self, node_id: NodeId, delays: Option<CommandDelays>) {
+        self.storage
+            .command_delays()
+            .update(|tracker| match delays {
+                Some(delays) => tracker.delays.insert(node_id, delays),
+                None => tracker.delays.remove(&node_id),
+            });
+    }
+
+    /// Returns the minimum delays between commands to the given node
+    pub fn command_delays(&self, node_id: NodeId) -> CommandDelays {
+        self.storage
+            .command_delays()
+            .inspect(|tracker| tracker.delays.get(&node_id).copied())
+            .unwrap_or_default()
+    }
+
+    /// Waits until the configured delay since the last command to the given node has passed
+    pub(crate) async fn wait_for_command_delay(&self, node_id: NodeId, expects_response: bool) {
+        let next_command_at = self
+            .storage
+            .command_delays()
+            .inspect(|tracker| tracker.next_command_at(node_id, expects_response));
+        let Some(wait) = next_command_at.and_then(|at| at.checked_duration_since(Instant::now()))
+        else {
+            return;
+        };
+        if wait.is_zero() {
+            return;
+        }
+        self.node_log(node_id, EndpointIndex::Root).debug(|| {
+            format!(
+                "delaying the command by {} ms, so the node can keep up",
+                wait.as_millis()
+            )
+        });
+        Timer::after(wait).await;
+    }
+
+    /// Remembers that the transmission of a command to the given node was completed
+    pub(crate) fn record_command_sent(&self, node_id: NodeId, expects_response: bool) {
+        self.storage.command_delays().update(|tracker| {
+            tracker.last_commands.insert(
+                node_id,
+                LastCommand {
+                    sent_at: Instant::now(),
+                    expects_response,
+                },
+            )
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use zwave_cc::commandclass::NoOperationCC;
+    use zwave_cc::commandclass::basic::BasicCCSet;
+    use zwave_cc::prelude::*;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_next_command_at() {
+        let node_id = NodeId::new(2u8);
+        let mut tracker = CommandDelayTracker::default();
+        let sent_at = Instant::now();
+        tracker.last_commands.insert(
+            node_id,
+            LastCommand {
+                sent_at,
+                expects_response: false,
+            },
+        );
+        // Without configured delays, commands are not held back
+        assert_eq!(tracker.next_command_at(node_id, true), None);
+
+        tracker.delays.insert(
+            node_id,
+            CommandDelays::builder()
+                .between_commands(Duration::from_millis(100))
+                .after_set(Duration::from_millis(500))
+                .build(),
+        );
+        let after = |millis| sent_at.checked_add(Duration::from_millis(millis));
+        assert_eq!(tracker.next_command_at(node_id, false), after(100));
+        assert_eq!(tracker.next_command_at(node_id, true), after(500));
+        assert_eq!(tracker.next_command_at(NodeId::new(3u8), true), None);
+    }
+
+    #[test]
+    fn test_commands_are_delayed() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            let delays = CommandDelays::builder()
+                .between_commands(Duration::from_millis(50))
+                .build();
+            driver.set_command_delays(node_id, Some(delays));
+            assert_eq!(driver.command_delays(node_id), delays);
+
+            let set = CC::from(BasicCCSet {
+                target_value: LevelSet::Level(99),
+            })
+            .with_destination(node_id.into());
+            let no_operation = CC::from(NoOperationCC {}).with_destination(node_id.into());
+
+            let start = Instant::now();
+            driver.exec_node_command(&set, None).await.unwrap();
+            driver.exec_node_command(&no_operation, None).await.unwrap();
+            assert!(start.elapsed() >= Duration::from_millis(50));
+        });
+        assert_eq!(controller.received().len(), 2);
+    }
+}
@@ -4,16 +4,21 @@ use super::{
     ExecControllerCommandOptions,
 };
 use zwave_core::log::Loglevel;
+use zwave_serial::quirks::SdkQuirks;
 use zwave_core::prelude::*;
 use zwave_serial::command::{
-    ApplicationUpdateRequest, ApplicationUpdateRequestPayload, Command, CommandBase,
+    ApplicationUpdateRequest, ApplicationUpdateRequestPayload, AssignReturnRouteRequest, Command,
+    CommandBase,
     GetControllerCapabilitiesRequest, GetControllerCapabilitiesResponse, GetControllerIdRequest,
     GetControllerIdResponse, GetControllerVersionRequest, GetControllerVersionResponse,
+    GetLongRangeChannelRequest, GetLongRangeChannelResponse, GetLongRangeNodesRequest,
     GetNodeProtocolInfoRequest, GetProtocolVersionRequest, GetProtocolVersionResponse,
+    GetRoutingInfoRequest,
     GetSerialApiCapabilitiesRequest, GetSerialApiCapabilitiesResponse, GetSerialApiInitDataRequest,
     GetSerialApiInitDataResponse, GetSucNodeIdRequest, RequestNodeInfoRequest,
+    RequestNodeNeighborUpdateRequest, SendNodeInformationRequest,
     SerialApiSetupCommand, SerialApiSetupRequest, SerialApiSetupResponsePayload,
-    SetSucNodeIdRequest,
+    SetLongRangeChannelRequest, SetSucNodeIdRequest,
 };
 
 impl Driver {
@@ -127,6 +132,9 @@ impl Driver {
             });
         }
 
+        // Remember our own node ID
+        self.serial_api.storage.set_own_node_id(ids.own_node_id);
+
         Ok(ids)
     }
 
@@ -150,11 +158,8 @@ impl Driver {
             });
         }
 
-        // Remember the protocol version
-        self.serial_api
-            .storage
-            .sdk_version()
-            .set(Some(protocol_version.version));
+        // Remember the SDK version, which determines the quirks of the Serial API
+        self.set_sdk_version(&protocol_version.version);
 
         Ok(protocol_version)
     }
@@ -239,17 +244,69 @@ impl Driver {
 
         // Remember the node ID type
         if success {
-            self.serial_api.storage.node_id_type().set(node_id_type);
+            self.serial_api.storage.set_node_id_type(node_id_type);
         }
 
         Ok(success)
     }
 
+    /// Switches the serial API to 16-bit node IDs if the controller supports it.
+    /// If that is not supported or fails, the driver continues using 8-bit node IDs,
+    /// which means that Long Range nodes cannot be addressed.
+    pub async fn negotiate_node_id_type(
+        &self,
+        supported_serial_api_setup_commands: &[SerialApiSetupCommand],
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> NodeIdType {
+        if supported_serial_api_setup_commands.contains(&SerialApiSetupCommand::SetNodeIDType)
+            && matches!(
+                self.set_node_id_type(NodeIdType::NodeId16Bit, options).await,
+                Ok(true)
+            )
+        {
+            return NodeIdType::NodeId16Bit;
+        }
+
+        self.controller_log().warn(|| {
+            "the controller does not support 16-bit node IDs, continuing with 8-bit node IDs. Long Range nodes cannot be reached!"
+        });
+        self.serial_api
+            .storage
+            .set_node_id_type(NodeIdType::NodeId8Bit);
+        NodeIdType::NodeId8Bit
+    }
+
+    /// Remembers the SDK version the controller firmware is based on
+    pub(crate) fn set_sdk_version(&self, protocol_version: &Version) {
+        self.serial_api
+            .storage
+            .set_sdk_version(Some(protocol_version_to_sdk_version(protocol_version)));
+    }
+
+    /// Returns the deviations of the controller's Serial API from the specification
+    pub fn sdk_quirks(&self) -> SdkQuirks {
+        self.serial_api.storage.command_context().quirks()
+    }
+
+    /// Returns the node ID type that is used to communicate with the controller
+    pub fn node_id_type(&self) -> NodeIdType {
+        self.serial_api.storage.node_id_type()
+    }
+
+    /// Ensures that the given node can be addressed using the current node ID type
+    pub(crate) fn ensure_addressable(&self, node_id: NodeId) -> ControllerCommandResult<()> {
+        if self.node_id_type() == NodeIdType::NodeId8Bit && u16::from(node_id) > 0xff {
+            return Err(ControllerCommandError::NodeIdNotAddressable(node_id));
+        }
+        Ok(())
+    }
+
     pub async fn get_node_protocol_info(
         &self,
         node_id: &NodeId,
         options: Option<&ExecControllerCommandOptions>,
     ) -> ControllerCommandResult<NodeInformationProtocolData> {
+        self.ensure_addressable(*node_id)?;
         let log = self.node_log(*node_id, EndpointIndex::Root);
         log.info(|| "querying protocol info...");
 
@@ -288,6 +345,8 @@ impl Driver {
 
         self.controller_log()
             .info(|| format!("the controller is using RF region {}", rf_region));
+        // The allowed duty cycle depends on the region
+        self.apply_region_rate_limit(rf_region);
 
         Ok(rf_region)
     }
@@ -317,6 +376,197 @@ impl Driver {
         Ok(powerlevel)
     }
 
+    /// Queries the maximum size of the CC payload in a SendData command
+    pub async fn get_maximum_payload_size(
+        &self,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<u8> {
+        self.controller_log()
+            .info(|| "querying maximum payload size...");
+        let response = self
+            .exec_controller_command(SerialApiSetupRequest::get_maximum_payload_size(), options)
+            .await;
+        let response = expect_controller_command_result!(response, SerialApiSetupResponse);
+
+        let size = expect_serial_api_setup_result!(
+            response.payload,
+            SerialApiSetupResponsePayload::GetMaximumPayloadSize { size } => size
+        )?;
+
+        self.controller_log()
+            .info(|| format!("maximum payload size: {} bytes", size));
+
+        Ok(size)
+    }
+
+    /// Queries the maximum size of the CC payload in a SendData command to a Long Range node
+    pub async fn get_lr_maximum_payload_size(
+        &self,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<u8> {
+        self.controller_log()
+            .info(|| "querying Long Range maximum payload size...");
+        let response = self
+            .exec_controller_command(SerialApiSetupRequest::get_lr_maximum_payload_size(), options)
+            .await;
+        let response = expect_controller_command_result!(response, SerialApiSetupResponse);
+
+        let size = expect_serial_api_setup_result!(
+            response.payload,
+            SerialApiSetupResponsePayload::GetLRMaximumPayloadSize { size } => size
+        )?;
+
+        self.controller_log()
+            .info(|| format!("Long Range maximum payload size: {} bytes", size));
+
+        Ok(size)
+    }
+
+    pub async fn set_rf_region(
+        &self,
+        region: RfRegion,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<bool> {
+        self.controller_log()
+            .info(|| format!("setting RF region to {}...", region));
+        let response = self
+            .exec_controller_command(SerialApiSetupRequest::set_rf_region(region), options)
+            .await;
+        let response = expect_controller_command_result!(response, SerialApiSetupResponse);
+
+        let success = expect_serial_api_setup_result!(
+            response.payload,
+            SerialApiSetupResponsePayload::SetRFRegion { success } => success
+        )?;
+
+        self.controller_log().message(
+            || {
+                format!(
+                    "setting RF region {}",
+                    if success { "succeeded" } else { "failed" }
+                )
+            },
+            if success {
+                Loglevel::Info
+            } else {
+                Loglevel::Warn
+            },
+        );
+
+        Ok(success)
+    }
+
+    pub async fn set_powerlevel(
+        &self,
+        powerlevel: Powerlevel,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<bool> {
+        self.controller_log()
+            .info(|| format!("setting powerlevel to {}...", powerlevel));
+        let response = self
+            .exec_controller_command(SerialApiSetupRequest::set_powerlevel(powerlevel), options)
+            .await;
+        let response = expect_controller_command_result!(response, SerialApiSetupResponse);
+
+        let success = expect_serial_api_setup_result!(
+            response.payload,
+            SerialApiSetupResponsePayload::SetPowerlevel { success } => success
+        )?;
+
+        self.controller_log().message(
+            || {
+                format!(
+                    "setting powerlevel {}",
+                    if success { "succeeded" } else { "failed" }
+                )
+            },
+            if success {
+                Loglevel::Info
+            } else {
+                Loglevel::Warn
+            },
+        );
+
+        Ok(success)
+    }
+
+    pub async fn get_long_range_nodes(
+        &self,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<Vec<NodeId>> {
+        self.controller_log()
+            .info(|| "querying Long Range nodes...");
+
+        // The node bitmask is too large for a single response, so it is split into segments
+        let mut node_ids = Vec::new();
+        let mut segment_number = 0u8;
+        loop {
+            let cmd = GetLongRangeNodesRequest::builder()
+                .segment_number(segment_number)
+                .build();
+            let response = self.exec_controller_command(cmd, options).await;
+            let response = expect_controller_command_result!(response, GetLongRangeNodesResponse);
+
+            node_ids.extend(response.node_ids);
+            if !response.more_nodes {
+                break;
+            }
+            segment_number = match segment_number.checked_add(1) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        self.controller_log().info(|| {
+            format!(
+                "the controller has {} Long Range node{}",
+                node_ids.len(),
+                if node_ids.len() == 1 { "" } else { "s" }
+            )
+        });
+
+        Ok(node_ids)
+    }
+
+    pub async fn get_long_range_channel(
+        &self,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<GetLongRangeChannelResponse> {
+        self.controller_log()
+            .info(|| "querying Long Range channel...");
+        let response = self
+            .exec_controller_command(GetLongRangeChannelRequest::default(), options)
+            .await;
+        let response = expect_controller_command_result!(response, GetLongRangeChannelResponse);
+
+        self.controller_log().info(|| {
+            format!(
+                "the controller is using Long Range channel {}",
+                response.channel
+            )
+        });
+
+        Ok(response)
+    }
+
+    pub async fn set_long_range_channel(
+        &self,
+        channel: LongRangeChannel,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<()> {
+        self.controller_log()
+            .info(|| format!("setting Long Range channel to {}...", channel));
+        let cmd = SetLongRangeChannelRequest::builder().channel(channel).build();
+        let response = self.exec_controller_command(cmd, options).await;
+        // A failure is reported as an unsuccessful response
+        expect_controller_command_result!(response, SetLongRangeChannelResponse);
+
+        self.controller_log()
+            .info(|| format!("the Long Range channel was set to {}", channel));
+
+        Ok(())
+    }
+
     pub async fn set_tx_status_report(
         &self,
         enabled: bool,
@@ -361,6 +611,7 @@ impl Driver {
         enable_sis: bool,
         options: Option<&ExecControllerCommandOptions>,
     ) -> ControllerCommandResult<bool> {
+        self.ensure_addressable(node_id)?;
         let cmd = SetSucNodeIdRequest::builder()
             .own_node_id(own_node_id)
             .suc_node_id(node_id)
@@ -398,6 +649,7 @@ impl Driver {
         node_id: &NodeId,
         options: Option<&ExecControllerCommandOptions>,
     ) -> ControllerCommandResult<NodeInformationApplicationData> {
+        self.ensure_addressable(*node_id)?;
         let log = self.controller_log();
 
         log.info(|| format!("querying node info for node {}...", node_id));
@@ -429,6 +681,140 @@ impl Driver {
 
         Ok(application_data)
     }
+
+    /// Assigns a return route from the given node to the destination node,
+    /// so the node can reach the destination even if the network topology changed
+    pub async fn assign_return_route(
+        &self,
+        node_id: NodeId,
+        destination_node_id: NodeId,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<()> {
+        self.ensure_addressable(node_id)?;
+        let log = self.node_log(node_id, EndpointIndex::Root);
+
+        log.info(|| format!("assigning a return route to node {}...", destination_node_id));
+        let cmd = AssignReturnRouteRequest::builder()
+            .node_id(node_id)
+            .destination_node_id(destination_node_id)
+            .build();
+        let response = self.exec_controller_command(cmd, options).await;
+
+        match response {
+            Ok(Some(Command::AssignReturnRouteCallback(_))) => {
+                log.info(|| "the return route was assigned");
+                Ok(())
+            }
+            Ok(_) => Err(ControllerCommandError::Unexpected(
+                "expected AssignReturnRouteCallback".to_string(),
+            )),
+            Err(e) => {
+                log.warn(|| "assigning the return route failed");
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Asks a node to discover its neighbors, so the controller can calculate new routes to it
+    pub async fn request_node_neighbor_update(
+        &self,
+        node_id: NodeId,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<()> {
+        self.ensure_addressable(node_id)?;
+        let log = self.node_log(node_id, EndpointIndex::Root);
+
+        log.info(|| "requesting a neighbor update...");
+        let response = self
+            .exec_controller_command(RequestNodeNeighborUpdateRequest::new(node_id), options)
+            .await;
+
+        match response {
+            Ok(Some(Command::RequestNodeNeighborUpdateCallback(_))) => {
+                log.info(|| "the neighbors were updated");
+                Ok(())
+            }
+            Ok(_) => Err(ControllerCommandError::Unexpected(
+                "expected RequestNodeNeighborUpdateCallback".to_string(),
+            )),
+            Err(e) => {
+                log.warn(|| "updating the neighbors failed");
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Asks the controller which nodes are in direct range of the given node
+    pub async fn get_node_neighbors(
+        &self,
+        node_id: NodeId,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<Vec<NodeId>> {
+        self.ensure_addressable(node_id)?;
+        let log = self.node_log(node_id, EndpointIndex::Root);
+        log.info(|| "querying neighbors...");
+
+        let cmd = GetRoutingInfoRequest::builder().node_id(node_id).build();
+        let response = self.exec_controller_command(cmd, options).await;
+        let response = expect_controller_command_result!(response, GetRoutingInfoResponse);
+
+        log.info(|| {
+            let neighbors = response
+                .neighbors
+                .iter()
+                .map(|node_id| node_id.to_string())
+                .collect::<Vec<_>>();
+            format!("neighbors: {}", neighbors.join(", "))
+        });
+        Ok(response.neighbors)
+    }
+
+    /// Sends the controller's node information frame to the given node.
+    /// Use [`NodeId::broadcast`] to send it to all nodes in range.
+    pub async fn send_node_information(
+        &self,
+        destination: NodeId,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<()> {
+        let broadcast = destination == NodeId::broadcast();
+        if !broadcast {
+            self.ensure_addressable(destination)?;
+        }
+        let log = self.controller_log();
+
+        log.info(|| {
+            if broadcast {
+                "broadcasting the node information...".to_string()
+            } else {
+                format!("sending the node information to node {}...", destination)
+            }
+        });
+        // Broadcasts cannot be acknowledged
+        let transmit_options = if broadcast {
+            TransmitOptions::default_no_ack()
+        } else {
+            TransmitOptions::default()
+        };
+        let cmd = SendNodeInformationRequest::builder()
+            .destination_node_id(destination)
+            .transmit_options(transmit_options)
+            .build();
+        let response = self.exec_controller_command(cmd, options).await;
+
+        match response {
+            Ok(Some(Command::SendNodeInformationCallback(_))) => {
+                log.info(|| "the node information was sent");
+                Ok(())
+            }
+            Ok(_) => Err(ControllerCommandError::Unexpected(
+                "expected SendNodeInformationCallback".to_string(),
+            )),
+            Err(e) => {
+                log.warn(|| "sending the node information failed");
+                Err(e.into())
+            }
+        }
+    }
 }
 
 macro_rules! expect_serial_api_setup_result {
@@ -445,3 +831,223 @@ macro_rules! expect_serial_api_setup_result {
     };
 }
 pub(crate) use expect_serial_api_setup_result;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use crate::{ExecNodeCommandError, ExecNodeCommandResult};
+    use zwave_cc::commandclass::{CC, CCAddressable, NoOperationCC};
+    use zwave_serial::command::GetLongRangeNodesResponse;
+    use zwave_serial::prelude::*;
+
+    const LR_NODE_ID: u16 = 257;
+
+    fn mock_controller(supports_16_bit: bool) -> MockController {
+        MockController::new()
+            .on(FunctionType::SerialApiSetup, move |controller, _| {
+                if supports_16_bit {
+                    controller.set_node_id_type(NodeIdType::NodeId16Bit);
+                }
+                let payload = vec![
+                    SerialApiSetupCommand::SetNodeIDType.into(),
+                    supports_16_bit as u8,
+                ];
+                vec![MockController::raw(
+                    CommandType::Response,
+                    FunctionType::SerialApiSetup,
+                    payload,
+                )]
+            })
+            .on(FunctionType::GetControllerId, |controller, _| {
+                vec![controller.encode(&GetControllerIdResponse {
+                    home_id: 0xdeadbeef.into(),
+                    own_node_id: NodeId::new(1u8),
+                })]
+            })
+            .on(FunctionType::SendData, |_, request| {
+                MockController::send_data_ok(request)
+            })
+    }
+
+    async fn send_no_operation(
+        driver: &Driver,
+        node_id: u16,
+    ) -> ExecNodeCommandResult<Option<CC>> {
+        let cc = CC::from(NoOperationCC {}).with_destination(NodeId::new(node_id).into());
+        driver.exec_node_command(&cc, None).await
+    }
+
+    fn sent_data_requests(controller: &MockController) -> Vec<CommandRaw> {
+        controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            .collect()
+    }
+
+    #[test]
+    fn test_16_bit_node_ids() {
+        let controller = mock_controller(true);
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id_type = driver
+                .negotiate_node_id_type(&[SerialApiSetupCommand::SetNodeIDType], None)
+                .await;
+            assert_eq!(node_id_type, NodeIdType::NodeId16Bit);
+            assert_eq!(driver.node_id_type(), NodeIdType::NodeId16Bit);
+
+            let ids = driver.get_controller_id(None).await.unwrap();
+            assert_eq!(ids.own_node_id, NodeId::new(1u8));
+
+            send_no_operation(&driver, LR_NODE_ID).await.unwrap();
+        });
+
+        let sent = sent_data_requests(&controller);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(&sent[0].payload[..2], &LR_NODE_ID.to_be_bytes());
+    }
+
+    #[test]
+    fn test_fallback_when_switching_fails() {
+        let controller = mock_controller(false);
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id_type = driver
+                .negotiate_node_id_type(&[SerialApiSetupCommand::SetNodeIDType], None)
+                .await;
+            assert_eq!(node_id_type, NodeIdType::NodeId8Bit);
+
+            let ids = driver.get_controller_id(None).await.unwrap();
+            assert_eq!(ids.own_node_id, NodeId::new(1u8));
+
+            assert!(matches!(
+                send_no_operation(&driver, LR_NODE_ID).await,
+                Err(ExecNodeCommandError::Controller(
+                    ControllerCommandError::NodeIdNotAddressable(node_id)
+                )) if node_id == NodeId::new(LR_NODE_ID)
+            ));
+            send_no_operation(&driver, 2).await.unwrap();
+        });
+
+        // Only the command to the classic node was sent, using an 8-bit node ID
+        let sent = sent_data_requests(&controller);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].payload[0], 2);
+    }
+
+    #[test]
+    fn test_set_rf_region() {
+        let controller = MockController::new().on(FunctionType::SerialApiSetup, |_, request| {
+            let payload = match SerialApiSetupCommand::from(request.payload[0]) {
+                SerialApiSetupCommand::SetRFRegion => vec![request.payload[0], 0x01],
+                SerialApiSetupCommand::GetRFRegion => vec![request.payload[0], RfRegion::US as u8],
+                _ => vec![request.payload[0], 0x00],
+            };
+            vec![MockController::raw(
+                CommandType::Response,
+                FunctionType::SerialApiSetup,
+                payload,
+            )]
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            assert!(driver.set_rf_region(RfRegion::US, None).await.unwrap());
+            assert_eq!(driver.get_rf_region(None).await.unwrap(), RfRegion::US);
+        });
+
+        let requests: Vec<_> = controller
+            .received()
+            .into_iter()
+            .map(|cmd| cmd.payload.to_vec())
+            .collect();
+        assert_eq!(
+            requests,
+            vec![
+                vec![SerialApiSetupCommand::SetRFRegion.into(), RfRegion::US as u8],
+                vec![SerialApiSetupCommand::GetRFRegion.into()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fallback_when_unsupported() {
+        let controller = mock_controller(true);
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id_type = driver.negotiate_node_id_type(&[], None).await;
+            assert_eq!(node_id_type, NodeIdType::NodeId8Bit);
+            assert!(matches!(
+                driver.get_node_protocol_info(&NodeId::new(LR_NODE_ID), None).await,
+                Err(ControllerCommandError::NodeIdNotAddressable(_))
+            ));
+        });
+
+        assert!(controller
+            .received()
+            .iter()
+            .all(|cmd| cmd.function_type != FunctionType::SerialApiSetup));
+    }
+
+    #[test]
+    fn test_long_range_nodes_are_queried_in_segments() {
+        let controller =
+            MockController::new().on(FunctionType::GetLongRangeNodes, |controller, request| {
+                let segment_number = request.payload[0];
+                let node_ids = match segment_number {
+                    0 => vec![256u16, 300],
+                    _ => vec![1280u16],
+                };
+                vec![controller.encode(&GetLongRangeNodesResponse {
+                    more_nodes: segment_number == 0,
+                    segment_number,
+                    node_ids: node_ids.into_iter().map(NodeId::new).collect(),
+                })]
+            });
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_ids = driver.get_long_range_nodes(None).await.unwrap();
+            assert_eq!(
+                node_ids,
+                vec![256u16, 300, 1280]
+                    .into_iter()
+                    .map(NodeId::new)
+                    .collect::<Vec<_>>()
+            );
+        });
+
+        let segments: Vec<u8> = controller
+            .received()
+            .iter()
+            .filter(|cmd| cmd.function_type == FunctionType::GetLongRangeNodes)
+            .map(|cmd| cmd.payload[0])
+            .collect();
+        assert_eq!(segments, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_broadcast_node_information() {
+        let controller =
+            MockController::new().on(FunctionType::SendNodeInformation, |_, request| {
+                let callback_id = *request.payload.last().unwrap();
+                vec![
+                    MockController::raw(
+                        CommandType::Response,
+                        FunctionType::SendNodeInformation,
+                        vec![0x01],
+                    ),
+                    MockController::raw(
+                        CommandType::Request,
+                        FunctionType::SendNodeInformation,
+                        vec![callback_id, 0x00],
+                    ),
+                ]
+            });
+        run_with_mock_controller(&controller, |driver| async move {
+            driver
+                .send_node_information(NodeId::broadcast(), None)
+                .await
+                .unwrap();
+        });
+
+        let received = controller.received();
+        assert_eq!(received.len(), 1);
+        // Broadcast node ID, no ACK, but auto-route and explore
+        assert_eq!(received[0].payload[..2], [0xff, 0b0010_0100]);
+    }
+}
@@ -0,0 +1,182 @@
+use super::{Driver, DriverActor};
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::{
+    ManufacturerSpecificCCReport, VersionCCCommandClassReport, VersionCCReport,
+};
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+/// How the controller identifies itself when nodes or certification tools query it.
+/// Everything that is not configured is taken from the controller.
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct ControllerIdentity {
+    #[builder(default, setter(strip_option))]
+    pub manufacturer_id: Option<u16>,
+    #[builder(default, setter(strip_option))]
+    pub product_type: Option<u16>,
+    #[builder(default, setter(strip_option))]
+    pub product_id: Option<u16>,
+    /// The version of the application, which is reported as the firmware version
+    #[builder(default, setter(strip_option))]
+    pub firmware_version: Option<Version>,
+    #[builder(default, setter(strip_option))]
+    pub hardware_version: Option<u8>,
+}
+
+impl Default for ControllerIdentity {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// The versions of the CCs the controller answers queries for
+fn supported_cc_version(cc: CommandClasses) -> u8 {
+    match cc {
+        CommandClasses::Version => 2,
+        CommandClasses::ManufacturerSpecific => 1,
+        _ => 0,
+    }
+}
+
+impl Driver {
+    /// Changes how the controller identifies itself when it is queried
+    pub fn set_controller_identity(&self, identity: ControllerIdentity) {
+        self.storage.controller_identity().set(identity);
+    }
+
+    pub fn controller_identity(&self) -> ControllerIdentity {
+        self.storage.controller_identity().get()
+    }
+}
+
+impl DriverActor {
+    /// Answers the queries of nodes for the identity of the controller
+    pub(super) fn handle_controller_query(&self, cc: &WithAddress<CC>) {
+        let address = cc.address();
+        let own_node_id = self.serial_api.storage.own_node_id();
+        if address.destination != Destination::Singlecast(own_node_id) {
+            return;
+        }
+
+        let response = match &**cc {
+            CC::VersionCCGet(_) => self.version_report().map(CC::from),
+            CC::VersionCCCommandClassGet(get) => Some(
+                VersionCCCommandClassReport::builder()
+                    .requested_cc(get.requested_cc)
+                    .version(supported_cc_version(get.requested_cc))
+                    .build()
+                    .into(),
+            ),
+            CC::ManufacturerSpecificCCGet(_) => self.manufacturer_specific_report().map(CC::from),
+            _ => return,
+        };
+
+        match response {
+            Some(response) => self.send_cc(address.source_node_id, response),
+            None => self
+                .node_log(address.source_node_id, address.endpoint_index)
+                .warn(|| "cannot answer the query, the controller was not interviewed yet"),
+        }
+    }
+
+    fn version_report(&self) -> Option<VersionCCReport> {
+        let identity = self.storage.controller_identity().get();
+        let controller = self.storage.controller().inspect(|c| c.clone())?;
+        controller
+            .inspect(|controller| {
+                let firmware_version = identity
+                    .firmware_version
+                    .unwrap_or(controller.fingerprint.firmware_version());
+                VersionCCReport::builder()
+                    .library_type(controller.library_type)
+                    .protocol_version(controller.protocol_version)
+                    .firmware_versions(vec![firmware_version])
+                    .hardware_version(identity.hardware_version)
+                    .build()
+            })
+            .into()
+    }
+
+    fn manufacturer_specific_report(&self) -> Option<ManufacturerSpecificCCReport> {
+        let identity = self.storage.controller_identity().get();
+        let fingerprint = self
+            .storage
+            .controller()
+            .inspect(|c| c.clone())
+            .map(|controller| controller.inspect(|controller| controller.fingerprint.clone()));
+
+        let pick = |configured: Option<u16>, reported: fn(&DeviceFingerprint) -> Id16| {
+            configured.or_else(|| fingerprint.as_ref().map(|f| reported(f).into()))
+        };
+        Some(
+            ManufacturerSpecificCCReport::builder()
+                .manufacturer_id(pick(
+                    identity.manufacturer_id,
+                    DeviceFingerprint::manufacturer_id,
+                )?)
+                .product_type(pick(
+                    identity.product_type,
+                    DeviceFingerprint::product_type,
+                )?)
+                .product_id(pick(identity.product_id, DeviceFingerprint::product_id)?)
+                .build(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use core::time::Duration;
+    use zwave_cc::commandclass::NoOperationCC;
+    use zwave_pal::time::Timer;
+
+    /// Sends a command to node 2, which then sends the given CC to the controller.
+    /// Returns the payload of the controller's answer.
+    fn query_controller(identity: ControllerIdentity, query: &'static [u8]) -> Vec<u8> {
+        let controller =
+            MockController::new().on(FunctionType::SendData, move |controller, request| {
+                let mut ret = MockController::send_data_ok(request);
+                if controller.received().len() == 1 {
+                    ret.push(MockController::application_command(2, query));
+                }
+                ret
+            });
+        run_with_mock_controller(&controller, |driver| async move {
+            driver.serial_api.storage.set_own_node_id(NodeId::new(1u8));
+            driver.set_controller_identity(identity);
+            let cc = CC::from(NoOperationCC {}).with_destination(NodeId::new(2u8).into());
+            driver.exec_node_command(&cc, None).await.unwrap();
+            Timer::after(Duration::from_millis(50)).await;
+        });
+
+        let sent = controller.received();
+        assert_eq!(sent.len(), 2, "the controller did not answer");
+        // Skip the destination node ID and the CC length
+        let payload = &sent[1].payload[2..];
+        payload[..sent[1].payload[1] as usize].to_vec()
+    }
+
+    #[test]
+    fn test_manufacturer_specific_get() {
+        let identity = ControllerIdentity::builder()
+            .manufacturer_id(0x0466)
+            .product_type(0x0001)
+            .product_id(0x0002)
+            .build();
+        assert_eq!(
+            query_controller(identity, &[0x72, 0x04]),
+            vec![0x72, 0x05, 0x04, 0x66, 0x00, 0x01, 0x00, 0x02]
+        );
+    }
+
+    #[test]
+    fn test_version_command_class_get() {
+        assert_eq!(
+            query_controller(ControllerIdentity::default(), &[0x86, 0x13, 0x72]),
+            vec![0x86, 0x14, 0x72, 0x01]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use super::Driver;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use zwave_core::prelude::*;
+
+/// Exports that are at most this old are considered a quick restart. IDs that were handed out
+/// after such an export may still be in use by the nodes.
+const QUICK_RESTART_WINDOW: Duration = Duration::from_secs(300);
+/// How many values each counter skips when restored after a quick restart
+const QUICK_RESTART_SKIP: u8 = 32;
+
+/// The state of the counters the driver uses to identify commands. Persisting them lets the
+/// driver continue where it left off after a restart, so the IDs don't clash with ones the
+/// controller or the nodes still remember.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersistedCounters {
+    /// The last callback ID used for Serial API commands
+    pub callback_id: u8,
+    /// The last S2 sequence number used for commands to each node
+    pub s2_sequence_numbers: BTreeMap<NodeId, u8>,
+}
+
+impl Driver {
+    /// Exports the state of the counters
+    pub(crate) fn export_counters(&self) -> PersistedCounters {
+        let s2_sequence_numbers = self
+            .storage
+            .security_manager2()
+            .map(|sec_man| sec_man.own_sequence_numbers())
+            .unwrap_or_else(|| self.storage.pending_s2_sequence_numbers().cloned());
+        PersistedCounters {
+            callback_id: self.serial_api.storage.callback_id().inspect(|c| c.value()),
+            s2_sequence_numbers,
+        }
+    }
+
+    /// Restores previously exported counters. `elapsed` is the time that has passed since the export.
+    ///
+    /// The counters may have advanced after the export. After a quick restart, the IDs that
+    /// were handed out in the meantime may still be in use, so the counters skip some values.
+    pub(crate) fn restore_counters(&self, counters: &PersistedCounters, elapsed: Duration) {
+        let skip = if elapsed < QUICK_RESTART_WINDOW {
+            QUICK_RESTART_SKIP
+        } else {
+            0
+        };
+
+        self.serial_api.storage.callback_id().update(|counter| {
+            counter.restore(counters.callback_id);
+            counter.skip(skip as usize);
+        });
+
+        let s2_sequence_numbers: BTreeMap<_, _> = counters
+            .s2_sequence_numbers
+            .iter()
+            .map(|(node_id, sequence_number)| (*node_id, sequence_number.wrapping_add(skip)))
+            .collect();
+        // The security manager may not have been created yet. If so, it picks up the numbers later.
+        match self.storage.security_manager2() {
+            Some(sec_man) => sec_man.restore_own_sequence_numbers(&s2_sequence_numbers),
+            None => self
+                .storage
+                .pending_s2_sequence_numbers()
+                .set(s2_sequence_numbers),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::mock_driver;
+
+    fn counters() -> PersistedCounters {
+        PersistedCounters {
+            callback_id: 250,
+            s2_sequence_numbers: [(NodeId::new(2u8), 240)].into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn test_counters_continue_after_restart() {
+        let driver = mock_driver().driver;
+        driver.restore_counters(&counters(), Duration::from_secs(3600));
+        assert_eq!(driver.export_counters(), counters());
+    }
+
+    #[test]
+    fn test_counters_skip_ahead_after_quick_restart() {
+        let driver = mock_driver().driver;
+        driver.restore_counters(&counters(), Duration::from_secs(5));
+
+        let exported = driver.export_counters();
+        // Zero is not a valid callback ID and is skipped
+        assert_eq!(exported.callback_id, 27);
+        assert_eq!(exported.s2_sequence_numbers[&NodeId::new(2u8)], 16);
+    }
+}
@@ -0,0 +1,290 @@
+use super::Driver;
+use crate::ControllerStorage;
+use serde::Serialize;
+use zwave_core::prelude::*;
+use zwave_logging::LogFormatter;
+use zwave_logging::formatters::DefaultFormatter;
+use zwave_pal::prelude::*;
+
+/// A snapshot of the driver state that users can attach to bug reports
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticDump {
+    /// Information about the controller, if it was interviewed already
+    pub controller: Option<ControllerDiagnostics>,
+    pub nodes: Vec<NodeDiagnostics>,
+    pub queue: QueueDiagnostics,
+    pub statistics: StatisticsDiagnostics,
+    /// Received commands that are not implemented by the library, most frequent first
+    pub unknown_commands: Vec<UnknownCommandDiagnostics>,
+    /// The most recent log entries, oldest first
+    pub logs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ControllerDiagnostics {
+    pub home_id: String,
+    pub own_node_id: u16,
+    pub suc_node_id: Option<u16>,
+    pub fingerprint: String,
+    pub library_type: String,
+    pub protocol_version: String,
+    pub sdk_version: String,
+    pub role: String,
+    pub is_suc: bool,
+    pub is_sis: bool,
+    pub rf_region: Option<String>,
+    pub powerlevel: Option<String>,
+    pub supported_function_types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeDiagnostics {
+    pub node_id: u16,
+    pub interview_stage: String,
+    pub status: String,
+    pub listening: bool,
+    pub frequent_listening: bool,
+    pub endpoints: Vec<EndpointDiagnostics>,
+    pub crc16_errors: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointDiagnostics {
+    pub index: u8,
+    pub command_classes: Vec<CommandClassDiagnostics>,
+}
+
+/// An entry in the CC support table of an endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandClassDiagnostics {
+    pub name: String,
+    pub id: u16,
+    pub supported: bool,
+    pub controlled: bool,
+    pub secure: bool,
+    pub version: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueDiagnostics {
+    /// The Serial API commands waiting to be executed, starting with the current one
+    pub serial_api_commands: Vec<String>,
+    /// How many responses from nodes are being waited for
+    pub awaited_ccs: usize,
+    /// How many unsolicited controller commands are being waited for
+    pub awaited_commands: usize,
+    /// How many awaited CCs and commands were removed because nobody waited for them anymore
+    pub obsolete_awaited_removed: u64,
+    pub scheduled_commands: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatisticsDiagnostics {
+    pub checksum_errors: u64,
+    pub frames_sent: u64,
+    pub frames_deferred: u64,
+    pub time_deferred_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnknownCommandDiagnostics {
+    pub command: String,
+    pub count: u64,
+    /// Hex-encoded payloads of the first received commands
+    pub samples: Vec<String>,
+}
+
+impl DiagnosticDump {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Failed to serialize the diagnostic dump")
+    }
+
+    /// Writes the dump as JSON to the given file
+    pub fn write_to_file(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+}
+
+impl Driver {
+    /// Creates a snapshot of the driver state for bug reports.
+    /// The Serial API must be running, so the queued commands can be retrieved.
+    pub async fn diagnostic_dump(&self) -> DiagnosticDump {
+        let controller = self.storage.controller().inspect(|controller| {
+            controller
+                .as_ref()
+                .map(|c| c.inspect(controller_diagnostics))
+        });
+
+        let nodes = self.storage.nodes().inspect(|nodes| {
+            nodes
+                .iter()
+                .map(|(node_id, node)| NodeDiagnostics {
+                    node_id: (*node_id).into(),
+                    interview_stage: format!("{:?}", node.interview_stage),
+                    status: format!("{:?}", node.status),
+                    listening: node.protocol_data.listening,
+                    frequent_listening: node.protocol_data.frequent_listening.is_some(),
+                    endpoints: node
+                        .endpoints
+                        .iter()
+                        .map(|(index, endpoint)| EndpointDiagnostics {
+                            index: match index {
+                                EndpointIndex::Root => 0,
+                                EndpointIndex::Endpoint(index) => *index,
+                            },
+                            command_classes: endpoint
+                                .cc_info
+                                .iter()
+                                .map(|(cc, info)| CommandClassDiagnostics {
+                                    name: cc.to_string(),
+                                    id: *cc as u16,
+                                    supported: info.supported,
+                                    controlled: info.controlled,
+                                    secure: info.secure,
+                                    version: info.version,
+                                })
+                                .collect(),
+                        })
+                        .collect(),
+                    crc16_errors: node.statistics.crc16_errors,
+                })
+                .collect()
+        });
+
+        let awaited_ccs = self.awaited_cc_statistics();
+        let awaited_commands = self.awaited_command_statistics();
+        let queue = QueueDiagnostics {
+            serial_api_commands: self
+                .serial_api
+                .pending_commands()
+                .await
+                .iter()
+                .map(|function_type| format!("{:?}", function_type))
+                .collect(),
+            awaited_ccs: awaited_ccs.active,
+            awaited_commands: awaited_commands.active,
+            obsolete_awaited_removed: awaited_ccs.obsolete_removed
+                + awaited_commands.obsolete_removed,
+            scheduled_commands: self.export_scheduled_commands().len(),
+        };
+
+        let rate_limiter = self.rate_limiter_statistics();
+        let statistics = StatisticsDiagnostics {
+            checksum_errors: self.serial_api.statistics().checksum_errors,
+            frames_sent: rate_limiter.frames_sent,
+            frames_deferred: rate_limiter.frames_deferred,
+            time_deferred_ms: rate_limiter.time_deferred.as_millis() as u64,
+        };
+
+        let unknown_commands = self
+            .unknown_commands()
+            .into_iter()
+            .map(|unknown| UnknownCommandDiagnostics {
+                command: unknown.command.to_string(),
+                count: unknown.count,
+                samples: unknown
+                    .samples
+                    .iter()
+                    .map(|sample| format!("0x{}", hex::encode(sample)))
+                    .collect(),
+            })
+            .collect();
+
+        let formatter = DefaultFormatter::new();
+        let logs = self.serial_api.storage.recent_logs().inspect(|logs| {
+            logs.iter()
+                .map(|(log, level)| {
+                    formatter
+                        .format_log(log, *level)
+                        .into_iter()
+                        .map(|s| s.string)
+                        .collect::<String>()
+                        .trim_end()
+                        .to_string()
+                })
+                .collect()
+        });
+
+        DiagnosticDump {
+            controller,
+            nodes,
+            queue,
+            statistics,
+            unknown_commands,
+            logs,
+        }
+    }
+}
+
+fn controller_diagnostics(controller: &ControllerStorage) -> ControllerDiagnostics {
+    ControllerDiagnostics {
+        home_id: controller.home_id.to_string(),
+        own_node_id: controller.own_node_id.into(),
+        suc_node_id: controller.suc_node_id.map(|id| id.into()),
+        fingerprint: format!("{:?}", controller.fingerprint),
+        library_type: format!("{:?}", controller.library_type),
+        protocol_version: controller.protocol_version.to_string(),
+        sdk_version: controller.sdk_version.to_string(),
+        role: format!("{:?}", controller.role),
+        is_suc: controller.is_suc,
+        is_sis: controller.is_sis,
+        rf_region: controller.rf_region.map(|region| format!("{:?}", region)),
+        powerlevel: controller
+            .powerlevel
+            .map(|powerlevel| format!("{:?}", powerlevel)),
+        supported_function_types: controller
+            .supported_function_types
+            .iter()
+            .map(|function_type| format!("{:?}", function_type))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+
+    #[test]
+    fn test_diagnostic_dump() {
+        let controller = MockController::new();
+        let dump = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                let mut node = NodeStorage::new(mock_protocol_data(true));
+                node.statistics.crc16_errors = 2;
+                node.endpoints
+                    .get_mut(&EndpointIndex::Root)
+                    .unwrap()
+                    .cc_info
+                    .insert(
+                        CommandClasses::Basic,
+                        CommandClassInfo {
+                            supported: true,
+                            controlled: false,
+                            secure: false,
+                            version: 2,
+                        },
+                    );
+                nodes.insert(NodeId::new(2u8), node);
+            });
+            driver.driver_log().info(|| "creating a diagnostic dump");
+            driver.diagnostic_dump().await
+        });
+
+        assert!(dump.controller.is_none());
+        assert_eq!(dump.nodes.len(), 1);
+        assert_eq!(dump.nodes[0].crc16_errors, 2);
+        let basic = &dump.nodes[0].endpoints[0].command_classes[0];
+        assert_eq!((basic.id, basic.version), (0x20, 2));
+        assert!(dump.queue.serial_api_commands.is_empty());
+        assert!(
+            dump.logs
+                .iter()
+                .any(|log| log.contains("creating a diagnostic dump"))
+        );
+
+        let json = dump.to_json();
+        assert!(json.contains("\"crc16_errors\": 2"));
+    }
+}
@@ -1,7 +1,10 @@
 use zwave_pal::prelude::*;
-use super::Driver;
+use super::{Cancelled, Driver};
+use core::time::Duration;
 use thiserror::Error;
 use typed_builder::TypedBuilder;
+use zwave_cc::prelude::CCEncodingError;
+use zwave_core::prelude::*;
 use zwave_serial::command::Command;
 
 impl Driver {
@@ -10,6 +13,21 @@ impl Driver {
         command: C,
         options: Option<&ExecControllerCommandOptions>,
     ) -> ExecControllerCommandResult<Option<Command>>
+    where
+        C: ExecutableCommand + 'static,
+    {
+        self.exec_controller_command_with_metadata(command, options)
+            .await
+            .result
+    }
+
+    /// Like [`exec_controller_command`](Self::exec_controller_command), but also returns
+    /// timing and transmission information for diagnostics and statistics.
+    pub async fn exec_controller_command_with_metadata<C>(
+        &self,
+        command: C,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ExecControllerCommandOutcome
     where
         C: ExecutableCommand + 'static,
     {
@@ -27,15 +45,32 @@ impl Driver {
         //     )));
         // }
 
-        let result = self.serial_api.execute_serial_api_command(command).await;
+        let callback_timeout = options.and_then(|options| options.callback_timeout);
+        let handshake = options.is_some_and(|options| options.handshake);
+        let result = self
+            .serial_api
+            .execute_serial_api_command(command, callback_timeout, handshake)
+            .await;
         // TODO: Handle retrying etc.
         match result {
-            Ok(SerialApiMachineResult::Success(command)) => Ok(command),
-            Ok(result) => Err(result.into()),
-            Err(e) => Err(ExecControllerCommandError::Unexpected(format!(
-                "unexpected error in execute_serial_api_command: {:?}",
-                e
-            ))),
+            Ok(SerialApiCommandResult {
+                result: SerialApiMachineResult::Success(command),
+                metadata,
+            }) => ExecControllerCommandOutcome {
+                result: Ok(command),
+                metadata,
+            },
+            Ok(SerialApiCommandResult { result, metadata }) => ExecControllerCommandOutcome {
+                result: Err(result.into()),
+                metadata,
+            },
+            Err(e) => ExecControllerCommandOutcome {
+                result: Err(ExecControllerCommandError::Unexpected(format!(
+                    "unexpected error in execute_serial_api_command: {:?}",
+                    e
+                ))),
+                metadata: SerialApiCommandMetadata::default(),
+            },
         }
     }
 }
@@ -46,11 +81,26 @@ pub struct ExecControllerCommandOptions {
     // /// Setting this to `false` is is useful if the capabilities haven't been determined yet. Default: `true`
     // #[builder(default = true)]
     // enforce_support: bool,
+    /// How long to wait for the callback of the command. Uses the default timeout if not set.
+    #[builder(default, setter(strip_option))]
+    pub callback_timeout: Option<Duration>,
+    /// Whether the command is part of a time-critical handshake, e.g. a nonce exchange.
+    /// Handshakes are executed before all other queued commands. Default: false
+    #[builder(default)]
+    pub handshake: bool,
 }
 
 /// The low-level result of a controller command execution.
 pub type ExecControllerCommandResult<T> = Result<T, ExecControllerCommandError>;
 
+/// The low-level result of a controller command execution, along with
+/// timing and transmission information about it.
+#[derive(Debug)]
+pub struct ExecControllerCommandOutcome {
+    pub result: ExecControllerCommandResult<Option<Command>>,
+    pub metadata: SerialApiCommandMetadata,
+}
+
 #[derive(Error, Debug)]
 /// Defines the possible low-level errors for a controller command execution
 pub enum ExecControllerCommandError {
@@ -103,17 +153,36 @@ pub enum ControllerCommandError {
     Failure,
     #[error("Command was unsuccessful")]
     Unsuccessful,
+    #[error("The controller is busy")]
+    Busy,
     #[error("Command not supported: {0}")]
     Unsupported(String),
     #[error("Unexpected error: {0}")]
     Unexpected(String),
+    #[error("The setting is not allowed: {0}")]
+    NotAllowed(String),
+    #[error("Node {0} can only be addressed using 16-bit node IDs, but the controller uses 8-bit node IDs")]
+    NodeIdNotAddressable(NodeId),
+    #[error("Another inclusion, exclusion or learn mode is already in progress")]
+    InclusionInProgress,
+    #[error("The inclusion failed")]
+    InclusionFailed,
+    #[error("The exclusion failed")]
+    ExclusionFailed,
+    #[error("The learn mode failed")]
+    LearnModeFailed,
+    #[error("The operation was cancelled")]
+    Cancelled,
+    #[error("The command cannot be sent: {0}")]
+    Encoding(#[from] CCEncodingError),
 }
 
 impl From<ExecControllerCommandError> for ControllerCommandError {
     fn from(value: ExecControllerCommandError) -> Self {
         match value {
+            // The controller cancelled the last attempt, because it was sending something itself
+            ExecControllerCommandError::CAN => ControllerCommandError::Busy,
             ExecControllerCommandError::ACKTimeout
-            | ExecControllerCommandError::CAN
             | ExecControllerCommandError::NAK
             | ExecControllerCommandError::ResponseTimeout
             | ExecControllerCommandError::CallbackTimeout => ControllerCommandError::Failure,
@@ -127,7 +196,16 @@ impl From<ExecControllerCommandError> for ControllerCommandError {
 
 impl From<crate::error::Error> for ControllerCommandError {
     fn from(value: crate::error::Error) -> Self {
-        ControllerCommandError::Unexpected(value.to_string())
+        match value {
+            crate::error::Error::Cancelled => ControllerCommandError::Cancelled,
+            _ => ControllerCommandError::Unexpected(value.to_string()),
+        }
+    }
+}
+
+impl From<Cancelled> for ControllerCommandError {
+    fn from(_: Cancelled) -> Self {
+        ControllerCommandError::Cancelled
     }
 }
 
@@ -146,4 +224,6 @@ macro_rules! expect_controller_command_result {
 }
 pub(crate) use expect_controller_command_result;
 
-use crate::{ExecutableCommand, SerialApiMachineResult};
+use crate::{
+    ExecutableCommand, SerialApiCommandMetadata, SerialApiCommandResult, SerialApiMachineResult,
+};
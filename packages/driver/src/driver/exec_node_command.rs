@@ -1,32 +1,56 @@
 use zwave_pal::prelude::*;
 use core::time::Duration;
 
-use super::ExecControllerCommandError;
-use super::{ControllerCommandError, Driver};
+use super::{
+    CancellableExt, ControllerCommandError, Driver, DriverEvent, SendPriority, TransactionState,
+};
+use crate::{EncryptionPolicy, NodeStatus, RouteStatistics, route_changed};
+use super::{ExecControllerCommandError, ExecControllerCommandOptions};
 use crate::error::Error;
 use thiserror::Error;
 use typed_builder::TypedBuilder;
 use zwave_cc::commandclass::IntoCCSequence;
+use zwave_cc::commandclass::security::SecurityCCCommandEncapsulation;
 use zwave_cc::commandclass::WithAddress;
 use zwave_cc::prelude::*;
 use zwave_core::prelude::*;
 use zwave_serial::command::SendDataRequest;
+use zwave_pal::time::Timer;
 use zwave_serial::prelude::*;
 
 impl Driver {
     pub async fn exec_node_command(
         &self,
         cc: &WithAddress<CC>,
-        _options: Option<&ExecNodeCommandOptions>,
+        options: Option<&ExecNodeCommandOptions>,
     ) -> ExecNodeCommandResult<Option<CC>> {
+        // The CCs of the sequence may be encapsulated, so remember the requested one
+        let requested_cc = cc;
+        // CCs which the node only supports securely must be encapsulated,
+        // unless the node's encryption policy says otherwise
+        let secure = self.needs_s0_encapsulation(cc)?;
         // Create a CC sequence in order to be able to handle CCs that require sequencing
-        let mut sequence = cc.clone().into_cc_sequence();
+        let mut sequence = if secure {
+            let (address, cc) = cc.clone().split();
+            CC::from(SecurityCCCommandEncapsulation::new(cc))
+                .with_address(address)
+                .into_cc_sequence()
+        } else {
+            cc.clone().into_cc_sequence()
+        };
 
         let node_id = match cc.address().destination {
             Destination::Singlecast(node_id) => node_id,
             Destination::Multicast(_) => todo!("Multicast not implemented yet"),
             Destination::Broadcast => NodeId::broadcast(),
         };
+        self.ensure_addressable(node_id)?;
+        // Commands to nodes that are unlikely to receive them may be queued or fail right away
+        self.preflight(node_id, requested_cc)?;
+
+        // Once the node has sent us a nonce, the rest of the sequence is a time-critical
+        // handshake that must not wait behind other frames
+        let mut options = options.cloned().unwrap_or_default();
 
         // For each CC in the sequence, send the CC and handle the reponse if needed
         loop {
@@ -36,78 +60,378 @@ impl Driver {
                 return Ok(None);
             };
 
-            let partial_result = self.exec_node_command_internal(node_id, &cc, None).await?;
+            #[cfg(feature = "metrics")]
+            let started_at = zwave_pal::time::Instant::now();
+            let partial_result = self
+                .exec_node_command_internal(node_id, &cc, Some(&options))
+                .await;
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_node_command(
+                self.label().as_deref(),
+                node_id,
+                &partial_result,
+                started_at.elapsed(),
+            );
+            self.update_node_status(node_id, &partial_result);
+            if partial_result.is_err() {
+                self.repair_routes_if_needed(node_id).await;
+            } else {
+                self.update_neighbors_if_moved(node_id).await;
+            }
+            let partial_result = partial_result?;
 
             if sequence.is_finished() {
-                return Ok(partial_result);
+                // The node goes back to sleep after being told there is nothing more to send
+                if matches!(**requested_cc, CC::WakeUpCCNoMoreInformation(_)) {
+                    self.set_node_status(node_id, NodeStatus::Asleep);
+                }
+                self.storage.track_transition(node_id, requested_cc);
+                self.handle_set_values(node_id, requested_cc);
+                // Return the decrypted response, so it looks the same as an insecure one
+                return Ok(partial_result.map(|response| match response {
+                    CC::SecurityCCCommandEncapsulation(encapsulation) if secure => encapsulation
+                        .into_encapsulated()
+                        .expect("a matching response is always complete"),
+                    other => other,
+                }));
             }
 
             if let Some(cc) = &partial_result {
+                if matches!(cc, CC::SecurityCCNonceReport(_)) {
+                    options.priority = SendPriority::Handshake;
+                }
                 sequence.handle_response(cc);
             }
         }
     }
 
+    /// Whether the given CC must be sent with S0 encapsulation. By default, this is the case if
+    /// the target endpoint only supports it securely. The node's encryption policy may override
+    /// this, in which case an error is returned if a command must be encrypted, but cannot be.
+    fn needs_s0_encapsulation(&self, cc: &WithAddress<CC>) -> ExecNodeCommandResult<bool> {
+        let Destination::Singlecast(node_id) = cc.address().destination else {
+            return Ok(false);
+        };
+        // Commands of the Security CC itself are encapsulated by its API where necessary
+        if cc.cc_id() == CommandClasses::Security {
+            return Ok(false);
+        }
+
+        let has_key = self.storage.security_manager().is_some();
+        let (policy, supports_s0, secure_only) = self.storage.nodes().inspect(|nodes| {
+            let Some(node) = nodes.get(&node_id) else {
+                return (EncryptionPolicy::default(), false, false);
+            };
+            let cc_info = |index, cc_id| {
+                node.endpoints
+                    .get(&index)
+                    .and_then(|endpoint| endpoint.cc_info.get(&cc_id))
+            };
+            (
+                node.encryption_policy.for_cc(cc.cc_id()),
+                cc_info(EndpointIndex::Root, CommandClasses::Security)
+                    .is_some_and(|info| info.supported),
+                cc_info(cc.address().endpoint_index, cc.cc_id()).is_some_and(|info| info.secure),
+            )
+        });
+
+        match policy {
+            EncryptionPolicy::Automatic => Ok(has_key && secure_only),
+            EncryptionPolicy::Opportunistic => Ok(has_key && (supports_s0 || secure_only)),
+            EncryptionPolicy::Required => {
+                let reason = if !has_key {
+                    "no S0 network key is configured"
+                } else if !supports_s0 && !secure_only {
+                    "the node does not support S0"
+                } else {
+                    return Ok(true);
+                };
+                self.node_log(node_id, cc.address().endpoint_index)
+                    .error(|| format!("{} must be sent securely, but {}", cc.cc_id(), reason));
+                Err(ExecNodeCommandError::EncryptionImpossible(reason))
+            }
+            EncryptionPolicy::Never => Ok(false),
+        }
+    }
+
+    /// Inspects the protocol information of the given node, if the node is known
+    pub(crate) fn inspect_node_protocol_data<R>(
+        &self,
+        node_id: NodeId,
+        inspect: impl FnOnce(&NodeInformationProtocolData) -> R,
+    ) -> Option<R> {
+        self.storage
+            .nodes()
+            .inspect(|nodes| nodes.get(&node_id).map(|node| inspect(&node.protocol_data)))
+    }
+
+    /// Derives the node's status from the outcome of a command sent to it
+    pub(super) fn update_node_status<T>(&self, node_id: NodeId, result: &ExecNodeCommandResult<T>) {
+        let Some(can_sleep) = self.inspect_node_protocol_data(node_id, |data| {
+            !data.listening && data.frequent_listening.is_none()
+        }) else {
+            return;
+        };
+        let acknowledged = match result {
+            // A timeout means the node acknowledged the command, but did not respond
+            Ok(_) | Err(ExecNodeCommandError::NodeTimeout) => true,
+            Err(ExecNodeCommandError::NodeNoAck) => false,
+            // This says nothing about the node
+            Err(
+                ExecNodeCommandError::Controller(_)
+                | ExecNodeCommandError::Encoding(_)
+                | ExecNodeCommandError::EncryptionImpossible(_)
+                | ExecNodeCommandError::Queued
+                | ExecNodeCommandError::NodeUnreachable,
+            ) => return,
+        };
+        self.record_reachability(node_id, acknowledged);
+        let status = match (can_sleep, acknowledged) {
+            (true, true) => NodeStatus::Awake,
+            (true, false) => NodeStatus::Asleep,
+            (false, true) => NodeStatus::Alive,
+            (false, false) => NodeStatus::Dead,
+        };
+        self.set_node_status(node_id, status);
+    }
+
+    /// Changes the status of a node and notifies the application if it changed
+    pub(crate) fn set_node_status(&self, node_id: NodeId, status: NodeStatus) {
+        let changed = self.storage.nodes().update(|nodes| {
+            let Some(node) = nodes.get_mut(&node_id) else {
+                return false;
+            };
+            let changed = node.status != status;
+            node.status = status;
+            changed
+        });
+        if changed {
+            self.node_log(node_id, EndpointIndex::Root)
+                .info(|| format!("the node is now {:?}", status).to_lowercase());
+            self.emit_event(DriverEvent::NodeStatusChanged { node_id, status });
+        }
+    }
+
+    /// Updates the node's statistics with the outcome of a transmission
+    /// and remembers how it was transmitted
+    fn record_transmission(
+        &self,
+        node_id: NodeId,
+        acknowledged: bool,
+        report: Option<TransmitReport>,
+    ) {
+        if let Some(report) = &report {
+            self.node_log(node_id, EndpointIndex::Root).debug(|| {
+                let own_node_id = self.serial_api.storage.own_node_id();
+                format!("route: {}", report.routing_attempt(own_node_id, node_id))
+            });
+        }
+        let route_changed = self.storage.nodes().update(|nodes| {
+            let Some(node) = nodes.get_mut(&node_id) else {
+                return false;
+            };
+            let statistics = &mut node.statistics;
+            statistics.commands_sent += 1;
+            if !acknowledged {
+                statistics.commands_failed += 1;
+            }
+            let Some(report) = report else {
+                return false;
+            };
+            let changed = node
+                .last_transmit_report
+                .as_ref()
+                .is_some_and(|previous| route_changed(previous, &report));
+            if changed {
+                statistics.route_changes += 1;
+            }
+            statistics.last_route = Some(RouteStatistics::from(&report));
+            node.last_transmit_report = Some(report);
+            changed
+        });
+        if route_changed {
+            self.record_route_change(node_id);
+        }
+    }
+
     fn get_cc_encoding_context(&self, destination_node_id: NodeId) -> CCEncodingContext {
         CCEncodingContext::builder()
-            .own_node_id(self.serial_api.storage.own_node_id().get())
+            .own_node_id(self.serial_api.storage.own_node_id())
             .node_id(destination_node_id)
-            .security_manager(self.storage.security_manager().cloned())
-            .security_manager2(self.storage.security_manager2().cloned())
+            .security(self.storage.security_managers())
+            .security_class(self.highest_security_class(destination_node_id))
             .build()
     }
 
-    async fn exec_node_command_internal(
+    /// Returns the highest security class that was negotiated with the given node, if any.
+    /// Only S0 is supported so far, which a node uses once it reported secure CCs.
+    pub fn highest_security_class(&self, node_id: NodeId) -> Option<SecurityClass> {
+        self.storage.security_manager()?;
+        self.storage.nodes().inspect(|nodes| {
+            let node = nodes.get(&node_id)?;
+            node.endpoints
+                .values()
+                .any(|endpoint| endpoint.cc_info.values().any(|info| info.secure))
+                .then_some(SecurityClass::S0Legacy)
+        })
+    }
+
+    pub(super) async fn exec_node_command_internal(
         &self,
         node_id: NodeId,
         cc: &CC,
-        _options: Option<&ExecNodeCommandOptions>,
+        options: Option<&ExecNodeCommandOptions>,
     ) -> ExecNodeCommandResult<Option<CC>> {
-        // FIXME: In some cases, the nodes' responses are received BEFORE
-        // the controller callback is received. We don't handle this case yet.
+        // In some cases, the nodes' responses are received BEFORE the controller callback.
+        // Start waiting for the response before sending the command, so it does not get lost.
+        let awaited_cc_response = cc.expects_response().then(|| {
+            let cc = cc.clone().with_destination(node_id.into());
+            self.register_awaited_cc(
+                Box::new(move |recv| test_cc_response(&cc, recv)),
+                Some(Duration::from_secs(10)),
+            )
+        });
+
+        // Broadcasts cannot be acknowledged
+        let mut transmit_options = if node_id == NodeId::broadcast() {
+            TransmitOptions::default_no_ack()
+        } else {
+            self.routing_transmit_options(node_id)
+        };
+        let mut controller_options = ExecControllerCommandOptions::default();
+
+        // FLiRS nodes must be woken up with a beam, which delays every transmission attempt
+        let beam = options.and_then(|options| options.beam).or_else(|| {
+            self.inspect_node_protocol_data(node_id, |data| data.frequent_listening)
+                .flatten()
+        });
+        if let Some(beam) = beam {
+            self.node_log(node_id, EndpointIndex::Root).debug(|| {
+                format!("waking up the node with a {} beam", beam)
+            });
+            // Explorer frames are not beamed, so they cannot reach FLiRS nodes
+            transmit_options = transmit_options.explore(false);
+            controller_options.callback_timeout = Some(BEAMED_SEND_DATA_CALLBACK_TIMEOUT);
+        }
+
+        // The transaction is visible in the queue until it is done. Until it is sent, the
+        // application may change its priority or remove it.
+        let priority = match cc {
+            CC::SecurityCCNonceGet(_) | CC::SecurityCCNonceReport(_) => SendPriority::Handshake,
+            _ => options.map(|options| options.priority).unwrap_or_default(),
+        };
+        let transaction = self.begin_transaction(node_id, cc.cc_id(), cc.cc_command(), priority);
+        let (send_slot, cancel_handle) = async {
+            // Handshakes must not be held back, since the nonces expire quickly
+            if priority != SendPriority::Handshake {
+                self.wait_for_command_delay(node_id, cc.expects_response())
+                    .await;
+            }
+            self.wait_for_send_slot(|| self.transaction_priority(transaction.id))
+                .await;
+            Ok::<_, ControllerCommandError>(())
+        }
+        .cancellable();
+        self.set_transaction_cancel_handle(transaction.id, cancel_handle);
+        send_slot.await?;
+        self.set_transaction_state(transaction.id, TransactionState::Sending);
+        controller_options.handshake =
+            self.transaction_priority(transaction.id) == SendPriority::Handshake;
 
         let ctx = self.get_cc_encoding_context(node_id);
-        let serialized = cc.clone().as_raw(&ctx);
+        let frames = cc
+            .try_as_raw(&ctx)
+            .and_then(|raw| self.split_into_frames(node_id, raw, &ctx))
+            .inspect_err(|e| {
+                self.node_log(node_id, EndpointIndex::Root)
+                    .error(|| format!("cannot send the command: {}", e));
+            })?;
+        if let Some(security_class) = cc.security_class(&ctx) {
+            self.node_log(node_id, EndpointIndex::Root).debug(|| {
+                let encapsulation: Vec<_> = cc
+                    .encapsulation_chain()
+                    .iter()
+                    .map(|cc| cc.to_string())
+                    .collect();
+                format!(
+                    "sending with security class {} (encapsulation: {})",
+                    security_class,
+                    encapsulation.join(" > ")
+                )
+            });
+        }
 
-        let controller_command = SendDataRequest::builder()
-            .node_id(node_id)
-            .command(serialized.into())
-            .build();
+        // Commands that are too large for a single frame are sent in segments
+        for serialized in frames {
+            // The controller rejects commands while it is busy, e.g. because its transmit queue is
+            // full. Give it some time before sending the command again.
+            let mut attempts = 1;
+            let controller_command_result = loop {
+                let controller_command = SendDataRequest::builder()
+                    .node_id(node_id)
+                    .command(serialized.clone().into())
+                    .transmit_options(transmit_options)
+                    .build();
 
-        let controller_command_result =
-            self.exec_controller_command(controller_command, None).await;
+                match self
+                    .exec_controller_command(controller_command, Some(&controller_options))
+                    .await
+                {
+                    Err(ExecControllerCommandError::ResponseNOK(Command::SendDataResponse(_)))
+                        if attempts < MAX_SEND_DATA_ATTEMPTS =>
+                    {
+                        self.node_log(node_id, EndpointIndex::Root).debug(|| {
+                            format!(
+                                "the controller is busy, retrying in {} ms...",
+                                CONTROLLER_BUSY_DELAY.as_millis()
+                            )
+                        });
+                        Timer::after(CONTROLLER_BUSY_DELAY).await;
+                        attempts += 1;
+                    }
+                    result => break result,
+                }
+            };
+            self.record_command_sent(node_id, cc.expects_response());
 
-        match controller_command_result {
-            Ok(Some(Command::SendDataResponse(_))) | Ok(Some(Command::SendDataCallback(_))) => {
-                // All good, this is expected
-            }
-            Err(ExecControllerCommandError::ResponseNOK(Command::SendDataResponse(resp))) => {
-                todo!("Handle failed SendData response")
-            }
-            Err(ExecControllerCommandError::CallbackNOK(Command::SendDataCallback(cb))) => {
-                // FIXME: Use callback information in statistics
-                // FIXME: This is not necessarily NoAck, it could be Fail too
-                return Err(ExecNodeCommandError::NodeNoAck);
-            }
-            other => {
-                panic!("Unexpected command response {:?} to SendDataRequest", other);
+            match controller_command_result {
+                Ok(Some(Command::SendDataResponse(_))) => {
+                    // All good, this is expected
+                }
+                Ok(Some(Command::SendDataCallback(cb))) => {
+                    self.record_transmit_status(node_id, cb.transmit_status);
+                    self.record_transmission(node_id, true, cb.transmit_report);
+                }
+                Err(ExecControllerCommandError::ResponseNOK(Command::SendDataResponse(_))) => {
+                    self.node_log(node_id, EndpointIndex::Root).warn(|| {
+                        format!(
+                            "the controller did not accept the command after {} attempts",
+                            MAX_SEND_DATA_ATTEMPTS
+                        )
+                    });
+                    return Err(ControllerCommandError::Busy.into());
+                }
+                Err(ExecControllerCommandError::CallbackNOK(Command::SendDataCallback(cb))) => {
+                    self.record_transmit_status(node_id, cb.transmit_status);
+                    self.record_transmission(node_id, false, cb.transmit_report);
+                    // Routing failures (Fail, NoRoute) are reported as NoAck too, but tracked
+                    // separately, so the routes to the node can be repaired
+                    return Err(ExecNodeCommandError::NodeNoAck);
+                }
+                // e.g. a controller that was busy during all attempts
+                Err(e) => return Err(ControllerCommandError::from(e).into()),
+                other => {
+                    panic!("Unexpected command response {:?} to SendDataRequest", other);
+                }
             }
         }
 
-        if !cc.expects_response() {
+        let Some(awaited_cc_response) = awaited_cc_response else {
             return Ok(None);
-        }
-
-        let awaited_cc_response = {
-            let cc = cc.clone().with_destination(node_id.into());
-            self.await_cc(
-                Box::new(move |recv| test_cc_response(&cc, recv)),
-                Some(Duration::from_secs(10)),
-            )
-            .await
         };
 
-        match awaited_cc_response {
+        self.set_transaction_state(transaction.id, TransactionState::WaitingForResponse);
+        match awaited_cc_response.try_await().await {
             Ok(recv) => Ok(Some(recv.unwrap())),
             Err(Error::Timeout) => Err(ExecNodeCommandError::NodeTimeout),
             Err(_) => {
@@ -117,8 +441,24 @@ impl Driver {
     }
 }
 
+/// How long to wait for the SendData callback when the target node needs to be woken up with a beam
+const BEAMED_SEND_DATA_CALLBACK_TIMEOUT: Duration = Duration::from_secs(65);
+/// How often a command is passed to the controller before giving up, if it is busy
+const MAX_SEND_DATA_ATTEMPTS: u8 = 3;
+/// How long to wait before passing a command to the controller again, if it is busy
+const CONTROLLER_BUSY_DELAY: Duration = Duration::from_millis(500);
+
 #[derive(TypedBuilder, Default, Clone)]
-pub struct ExecNodeCommandOptions {}
+pub struct ExecNodeCommandOptions {
+    /// Wakes up the node with the given beam before sending the command. By default,
+    /// this is determined from the node's protocol info.
+    #[builder(default, setter(strip_option))]
+    pub beam: Option<Beam>,
+    /// How urgent the command is, in case outgoing frames need to be delayed
+    /// because of the rate limit
+    #[builder(default)]
+    pub priority: SendPriority,
+}
 
 /// The result of a node command execution
 pub type ExecNodeCommandResult<T> = Result<T, ExecNodeCommandError>;
@@ -132,6 +472,14 @@ pub enum ExecNodeCommandError {
     NodeNoAck,
     #[error("Timed out waiting for a response from the node")]
     NodeTimeout,
+    #[error("The command cannot be sent: {0}")]
+    Encoding(#[from] CCEncodingError),
+    #[error("The command must be sent securely, but {0}")]
+    EncryptionImpossible(&'static str),
+    #[error("The node is asleep, the command will be sent when it wakes up")]
+    Queued,
+    #[error("The node is dead and repeatedly failed to acknowledge commands")]
+    NodeUnreachable,
 }
 
 /// Tests if the given CC response is the expected CC response to the given CC request
@@ -152,3 +500,342 @@ where
         false
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use crate::{Controller, EndpointLike, EndpointStorage, NodeStorage};
+    use zwave_cc::commandclass::NoOperationCC;
+    use zwave_cc::commandclass::basic::BasicCCSet;
+    use zwave_cc::commandclass::controller_replication::ControllerReplicationCCTransferGroupName;
+    use zwave_core::security::{NetworkKey, SecurityManager, SecurityManagerOptions};
+
+    /// The response and callback to a successful SendData request
+    fn mock_controller() -> MockController {
+        MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        })
+    }
+
+    fn protocol_data(frequent_listening: Option<Beam>) -> NodeInformationProtocolData {
+        NodeInformationProtocolData {
+            frequent_listening,
+            generic_device_class: 0x40,
+            specific_device_class: Some(0x03),
+            ..mock_protocol_data(false)
+        }
+    }
+
+    async fn send_no_operation(
+        driver: &Driver,
+        node_id: u8,
+        options: Option<&ExecNodeCommandOptions>,
+    ) -> ExecNodeCommandResult<Option<CC>> {
+        let cc = CC::from(NoOperationCC {}).with_destination(NodeId::new(node_id).into());
+        driver.exec_node_command(&cc, options).await
+    }
+
+    /// Returns the transmit options of all SendData requests the controller received
+    fn sent_transmit_options(controller: &MockController) -> Vec<u8> {
+        controller
+            .received()
+            .iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            // The transmit options are followed by the callback ID
+            .map(|cmd| cmd.payload[cmd.payload.len() - 2])
+            .collect()
+    }
+
+    #[test]
+    fn test_flirs_nodes_are_beamed() {
+        let controller = mock_controller();
+        run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(
+                    NodeId::new(2u8),
+                    NodeStorage::new(protocol_data(Some(Beam::Beam1000ms))),
+                );
+                nodes.insert(NodeId::new(3u8), NodeStorage::new(protocol_data(None)));
+            });
+
+            send_no_operation(&driver, 2, None).await.unwrap();
+            send_no_operation(&driver, 3, None).await.unwrap();
+        });
+
+        let beamed = TransmitOptions::default().explore(false).as_bytes()[0];
+        let default = TransmitOptions::default().as_bytes()[0];
+        assert_eq!(sent_transmit_options(&controller), vec![beamed, default]);
+    }
+
+    #[test]
+    fn test_beam_can_be_forced() {
+        let controller = mock_controller();
+        run_with_mock_controller(&controller, |driver| async move {
+            // The driver knows nothing about this node
+            let options = ExecNodeCommandOptions::builder()
+                .beam(Beam::Beam250ms)
+                .build();
+            send_no_operation(&driver, 4, Some(&options)).await.unwrap();
+        });
+
+        let beamed = TransmitOptions::default().explore(false).as_bytes()[0];
+        assert_eq!(sent_transmit_options(&controller), vec![beamed]);
+    }
+
+    /// A controller whose node 2 responds to S0 Nonce Get
+    fn secure_mock_controller() -> MockController {
+        MockController::new().on(FunctionType::SendData, |_, request| {
+            let mut frames = MockController::send_data_ok(request);
+            // Node 2 responds to the Nonce Get
+            if request.payload[2..4] == [0x98, 0x40] {
+                let mut nonce_report = vec![0x00, 0x02, 0x0a, 0x98, 0x80];
+                nonce_report.extend_from_slice(&[0x55; 8]);
+                nonce_report.push(0x00);
+                frames.push(MockController::raw(
+                    CommandType::Request,
+                    FunctionType::ApplicationCommand,
+                    nonce_report,
+                ));
+            }
+            frames
+        })
+    }
+
+    /// Adds node 2, which only supports the Basic CC securely
+    fn add_secure_node(driver: &Driver) {
+        driver
+            .storage
+            .set_security_manager(Some(SecurityManager::new(SecurityManagerOptions {
+                own_node_id: NodeId::new(1u8),
+                network_key: NetworkKey::from([0x11; 16]),
+            })));
+        driver.storage.nodes().update(|nodes| {
+            let mut node = NodeStorage::new(protocol_data(None));
+            node.endpoints
+                .entry(EndpointIndex::Root)
+                .or_insert_with(EndpointStorage::new)
+                .cc_info
+                .insert(
+                    CommandClasses::Basic,
+                    PartialCommandClassInfo::default().supported().secure().into(),
+                );
+            nodes.insert(NodeId::new(2u8), node);
+        });
+    }
+
+    /// The CC commands of all SendData requests
+    fn sent_cc_commands(controller: &MockController) -> Vec<(u8, Option<u8>)> {
+        controller
+            .received()
+            .iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            .map(|cmd| (cmd.payload[2], cmd.payload.get(3).copied()))
+            .collect()
+    }
+
+    #[test]
+    fn test_secure_ccs_are_encapsulated() {
+        let controller = secure_mock_controller();
+        run_with_mock_controller(&controller, |driver| async move {
+            add_secure_node(&driver);
+
+            let cc = CC::from(BasicCCSet::builder().target_value(LevelSet::On).build())
+                .with_destination(NodeId::new(2u8).into());
+            driver.exec_node_command(&cc, None).await.unwrap();
+            // Insecure CCs are sent as-is
+            send_no_operation(&driver, 2, None).await.unwrap();
+        });
+
+        assert_eq!(
+            sent_cc_commands(&controller),
+            vec![(0x98, Some(0x40)), (0x98, Some(0x81)), (0x00, Some(0x25))]
+        );
+    }
+
+    #[test]
+    fn test_s0_handshake_completes_under_load() {
+        let controller = secure_mock_controller();
+        run_with_mock_controller(&controller, |driver| async move {
+            add_secure_node(&driver);
+
+            let cc = CC::from(BasicCCSet::builder().target_value(LevelSet::On).build())
+                .with_destination(NodeId::new(2u8).into());
+            let secure = driver.exec_node_command(&cc, None);
+            let load =
+                futures::future::join_all((0..4).map(|_| send_no_operation(&driver, 3, None)));
+            let (secure, load) = futures::future::join(secure, load).await;
+            secure.unwrap();
+            assert!(load.iter().all(|result| result.is_ok()));
+        });
+
+        // The encapsulated command overtakes the commands that were still queued when the
+        // nonce arrived, instead of waiting behind all of them
+        let noop = (0x00, Some(0x25));
+        assert_eq!(
+            sent_cc_commands(&controller),
+            vec![(0x98, Some(0x40)), noop, noop, (0x98, Some(0x81)), noop, noop]
+        );
+    }
+
+    #[test]
+    fn test_encryption_policy() {
+        let controller = secure_mock_controller();
+        run_with_mock_controller(&controller, |driver| async move {
+            add_secure_node(&driver);
+            let controller = Controller::mock(&driver);
+            let node = controller.node(NodeId::new(2u8)).unwrap();
+
+            // The policy of a CC overrides the automatic selection
+            node.set_cc_encryption_policy(CommandClasses::Basic, Some(EncryptionPolicy::Never));
+            let cc = CC::from(BasicCCSet::builder().target_value(LevelSet::On).build())
+                .with_destination(NodeId::new(2u8).into());
+            driver.exec_node_command(&cc, None).await.unwrap();
+
+            // Nothing is sent if a command must be secure, but the node does not support S0
+            node.set_encryption_policy(EncryptionPolicy::Required);
+            assert!(matches!(
+                send_no_operation(&driver, 2, None).await,
+                Err(ExecNodeCommandError::EncryptionImpossible(_))
+            ));
+
+            node.modify_cc_info(
+                CommandClasses::Security,
+                &PartialCommandClassInfo::default().supported(),
+            );
+            node.set_encryption_policy(EncryptionPolicy::Opportunistic);
+            send_no_operation(&driver, 2, None).await.unwrap();
+            assert_eq!(
+                node.encryption_policy().for_cc(CommandClasses::Basic),
+                EncryptionPolicy::Never
+            );
+        });
+
+        assert_eq!(
+            sent_cc_commands(&controller),
+            vec![(0x20, Some(0x01)), (0x98, Some(0x40)), (0x98, Some(0x81))]
+        );
+    }
+
+    #[test]
+    fn test_encapsulation_without_network_key_fails() {
+        let controller = secure_mock_controller();
+        let result = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(2u8), NodeStorage::new(protocol_data(None)))
+            });
+
+            // No S0 key is configured, so the command cannot be encrypted once the nonce is known
+            let set = BasicCCSet::builder().target_value(LevelSet::On).build();
+            let cc = CC::from(SecurityCCCommandEncapsulation::new(set.into()))
+                .with_destination(NodeId::new(2u8).into());
+            driver.exec_node_command(&cc, None).await
+        });
+
+        assert!(matches!(
+            result,
+            Err(ExecNodeCommandError::Encoding(CCEncodingError::MissingS0Key))
+        ));
+        assert_eq!(sent_cc_commands(&controller), vec![(0x98, Some(0x40))]);
+    }
+
+    #[test]
+    fn test_payload_limit_depends_on_sdk() {
+        let controller = mock_controller();
+        let results = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(2u8), NodeStorage::new(protocol_data(None)))
+            });
+            // 44 bytes in total
+            let cc = CC::from(
+                ControllerReplicationCCTransferGroupName::builder()
+                    .group_id(1)
+                    .name("x".repeat(40))
+                    .build(),
+            )
+            .with_destination(NodeId::new(2u8).into());
+
+            // Without knowing the SDK, the smallest limit is assumed
+            let unknown = driver.exec_node_command(&cc, None).await;
+            // 500 series controllers support larger payloads
+            driver.set_sdk_version(&Version::try_from("6.7").unwrap());
+            let series_500 = driver.exec_node_command(&cc, None).await;
+            (unknown, series_500)
+        });
+
+        assert!(matches!(
+            results.0,
+            Err(ExecNodeCommandError::Encoding(CCEncodingError::PayloadTooLarge {
+                size: 44,
+                max: 40
+            }))
+        ));
+        assert!(matches!(results.1, Ok(None)));
+        assert_eq!(sent_cc_commands(&controller).len(), 1);
+    }
+
+    #[test]
+    fn test_busy_controller_is_retried() {
+        // The controller only accepts every third command
+        let controller = MockController::new().on(FunctionType::SendData, |controller, request| {
+            if controller.received().len() % 3 != 0 {
+                return vec![MockController::raw(
+                    CommandType::Response,
+                    FunctionType::SendData,
+                    vec![0x00],
+                )];
+            }
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            send_no_operation(&driver, 2, None).await.unwrap();
+        });
+        assert_eq!(sent_transmit_options(&controller).len(), 3);
+    }
+
+    #[test]
+    fn test_persistently_busy_controller() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, _| {
+            vec![MockController::raw(
+                CommandType::Response,
+                FunctionType::SendData,
+                vec![0x00],
+            )]
+        });
+        let result = run_with_mock_controller(&controller, |driver| async move {
+            send_no_operation(&driver, 2, None).await
+        });
+        assert!(matches!(
+            result,
+            Err(ExecNodeCommandError::Controller(ControllerCommandError::Busy))
+        ));
+        assert_eq!(
+            sent_transmit_options(&controller).len(),
+            MAX_SEND_DATA_ATTEMPTS as usize
+        );
+    }
+
+    #[test]
+    fn test_can_storm() {
+        let controller = mock_controller();
+        // The controller cancels all attempts to send the first command
+        controller.cancel_next(3);
+        run_with_mock_controller(&controller, |driver| async move {
+            let result = send_no_operation(&driver, 2, None).await;
+            assert!(matches!(
+                result,
+                Err(ExecNodeCommandError::Controller(ControllerCommandError::Busy))
+            ));
+
+            // It was busy, not unresponsive, so it is not reset
+            send_no_operation(&driver, 2, None).await.unwrap();
+        });
+
+        let function_types: Vec<_> = controller
+            .received()
+            .iter()
+            .map(|cmd| cmd.function_type)
+            .collect();
+        assert_eq!(function_types, vec![FunctionType::SendData]);
+    }
+}
@@ -1,10 +1,14 @@
 use zwave_pal::prelude::*;
-use super::{awaited::Predicate, Driver, DriverInput};
+use super::{
+    awaited::{AwaitedRef, AwaitedStatistics, Predicate},
+    Driver, DriverEvent, DriverInput,
+};
 use crate::error::Result;
 use core::time::Duration;
 use zwave_cc::prelude::*;
 use zwave_core::log::Loglevel;
 use zwave_core::prelude::*;
+use zwave_serial::command::Command;
 use zwave_logging::{
     loggers::{controller::ControllerLogger, driver::DriverLogger, node::NodeLogger},
     LocalImmutableLogger, LogInfo,
@@ -23,11 +27,14 @@ impl Driver {
         NodeLogger::new(self, node_id, endpoint)
     }
 
+    /// Passes an input to the actor. If that is not possible, e.g. because the actor stopped,
+    /// the input is dropped together with its callback, which the caller sees as an error.
     fn dispatch(&self, input: DriverInput) {
-        self.cmd_tx
-            .clone()
-            .try_send(input)
-            .expect("Failed to dispatch command");
+        let _ = self.cmd_tx.try_send(input);
+    }
+
+    pub(crate) fn emit_event(&self, event: DriverEvent) {
+        super::emit_event(&self.storage, &self.event_tx, event);
     }
 
     pub(crate) fn init_security_managers(&self) {
@@ -39,15 +46,48 @@ impl Driver {
         predicate: Predicate<WithAddress<CC>>,
         timeout: Option<Duration>,
     ) -> Result<WithAddress<CC>> {
-        let (tx, rx) = zwave_pal::channel::oneshot::channel();
-        let cmd = DriverInput::AwaitCC {
-            predicate,
-            timeout,
-            callback: tx,
-        };
-        self.dispatch(cmd);
+        // Dropping the returned future removes the entry again
+        self.register_awaited_cc(predicate, timeout)
+            .try_await()
+            .await
+    }
+
+    /// Starts waiting for a CC matching the given predicate immediately. Unlike [`Driver::await_cc`],
+    /// matching CCs that are received before the returned reference is awaited are not lost.
+    /// The timeout only starts when the reference is awaited.
+    pub(crate) fn register_awaited_cc(
+        &self,
+        predicate: Predicate<WithAddress<CC>>,
+        timeout: Option<Duration>,
+    ) -> AwaitedRef<WithAddress<CC>> {
+        self.storage.awaited_ccs().add(predicate, timeout)
+    }
+
+    /// Starts waiting for an unsolicited controller command matching the given predicate.
+    /// Like [`Driver::register_awaited_cc`], the timeout only starts when the reference is awaited.
+    pub(crate) fn register_awaited_command(
+        &self,
+        predicate: Predicate<Command>,
+        timeout: Option<Duration>,
+    ) -> AwaitedRef<Command> {
+        self.storage.awaited_commands().add(predicate, timeout)
+    }
+
+    /// How many CCs are being waited for and what happened to the awaited CCs
+    pub fn awaited_cc_statistics(&self) -> AwaitedStatistics {
+        self.storage.awaited_ccs().statistics()
+    }
 
-        rx.await.expect("Failed to receive callback for await_cc")
+    /// How many unsolicited controller commands are being waited for and what happened to the
+    /// awaited commands
+    pub fn awaited_command_statistics(&self) -> AwaitedStatistics {
+        self.storage.awaited_commands().statistics()
+    }
+
+    /// Changes how long a CC or controller command may be registered as awaited without
+    /// actually being awaited. Older entries are considered leaked and removed.
+    pub fn set_awaited_max_age(&self, max_age: Duration) {
+        self.storage.awaited_max_age().set(max_age);
     }
 }
 
@@ -64,3 +104,50 @@ impl LocalImmutableLogger for Driver {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Controller, Node, Ready, SerialApi};
+
+    fn assert_send<T: Send>(_: &T) {}
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_handles_are_send_and_sync() {
+        assert_send_sync::<Driver>();
+        assert_send_sync::<SerialApi>();
+        assert_send_sync::<Controller<'static, Ready>>();
+        assert_send_sync::<Node<'static>>();
+    }
+
+    /// Applications on multi-threaded executors must be able to await the API from any thread.
+    /// This only needs to compile.
+    #[allow(dead_code)]
+    fn assert_futures_are_send(
+        driver: &Driver,
+        controller: &Controller<'_, Ready>,
+        node: &Node<'_>,
+        cc: &WithAddress<CC>,
+    ) {
+        assert_send(&driver.exec_node_command(cc, None));
+        assert_send(&driver.await_cc(Box::new(|_| true), None));
+        assert_send(&driver.run_scheduler());
+        assert_send(&driver.query_node_info(&NodeId::new(2u8)));
+        assert_send(&driver.request_node_neighbor_update(NodeId::new(2u8), None));
+        assert_send(&driver.assign_return_route(NodeId::new(2u8), NodeId::new(1u8), None));
+        assert_send(&Controller::new(driver).interview());
+        assert_send(&controller.include_node(&Default::default()));
+        assert_send(&controller.begin_learn_mode(&Default::default()));
+        assert_send(&controller.interview_nodes(&Default::default()));
+        assert_send(&controller.reassess_security());
+        assert_send(&node.interview());
+        assert_send(&node.ping());
+        assert_send(&node.check_link_quality(&Default::default()));
+        assert_send(&node.reassess_security());
+        assert_send(&node.refresh_values_on_wake_up());
+        assert_send(&controller.run_wake_up_refresh());
+        assert_send(&node.cc_api().basic().get());
+        assert_send(&node.endpoint(1).cc_api().binary_switch().get());
+    }
+}
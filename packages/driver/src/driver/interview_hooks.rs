@@ -0,0 +1,183 @@
+use super::Driver;
+use crate::{EndpointLike, InterviewStage, Node};
+use core::{future::Future, pin::Pin};
+use zwave_cc::values::ValueMetadata;
+use zwave_core::prelude::*;
+use zwave_core::value_id::EndpointValueId;
+use zwave_pal::prelude::*;
+
+/// The future returned by the asynchronous [`InterviewHooks`]
+pub type InterviewHookFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Whether a part of the interview is performed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InterviewAction {
+    #[default]
+    Continue,
+    Skip,
+}
+
+/// Lets applications customize the interview of nodes, e.g. to skip parts of it, to send
+/// additional queries or to hide values. All hooks do nothing by default.
+///
+/// The asynchronous hooks are awaited during the interview, so they can communicate with the
+/// node being interviewed.
+pub trait InterviewHooks: Send + Sync {
+    /// Called before the interview of a node starts or is resumed
+    fn before_node_interview<'a>(&'a self, node: &'a Node<'a>) -> InterviewHookFuture<'a, ()> {
+        let _ = node;
+        Box::pin(async {})
+    }
+
+    /// Called after the interview of a node was completed, before the node is reported as ready
+    fn after_node_interview<'a>(&'a self, node: &'a Node<'a>) -> InterviewHookFuture<'a, ()> {
+        let _ = node;
+        Box::pin(async {})
+    }
+
+    /// Decides whether a stage of the node interview is performed. A skipped stage counts as
+    /// completed, so skipping the node info uses the command classes that are already known.
+    fn before_interview_stage(&self, node: &Node, stage: InterviewStage) -> InterviewAction {
+        let _ = (node, stage);
+        InterviewAction::Continue
+    }
+
+    /// Called before a CC of an endpoint is interviewed. Decides whether it is interviewed.
+    fn before_cc_interview<'a, 'e>(
+        &'a self,
+        endpoint: &'a dyn EndpointLike<'e>,
+        cc: CommandClasses,
+    ) -> InterviewHookFuture<'a, InterviewAction> {
+        let _ = (endpoint, cc);
+        Box::pin(async { InterviewAction::Continue })
+    }
+
+    /// Called after a CC of an endpoint was interviewed
+    fn after_cc_interview<'a, 'e>(
+        &'a self,
+        endpoint: &'a dyn EndpointLike<'e>,
+        cc: CommandClasses,
+    ) -> InterviewHookFuture<'a, ()> {
+        let _ = (endpoint, cc);
+        Box::pin(async {})
+    }
+
+    /// Decides whether a value is created for a supported CC before the node reports it.
+    /// Values that are not created are not announced to the application.
+    fn create_value(&self, value_id: &EndpointValueId, metadata: &ValueMetadata) -> bool {
+        let _ = (value_id, metadata);
+        true
+    }
+}
+
+impl Driver {
+    /// Changes how the interview of nodes is customized. `None` removes the hooks.
+    pub fn set_interview_hooks(&self, hooks: Option<Arc<dyn InterviewHooks>>) {
+        self.storage.interview_hooks().set(hooks);
+    }
+
+    pub(crate) fn interview_hooks(&self) -> Option<Arc<dyn InterviewHooks>> {
+        self.storage.interview_hooks().cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use crate::{Controller, NodeStorage};
+    use zwave_cc::commandclass::BinarySwitchCCValues;
+    use zwave_pal::sync::Locked;
+
+    /// Skips the node info and the Binary Switch CC, hides the target value
+    /// and records which hooks were called
+    struct RecordingHooks {
+        calls: Locked<Vec<String>>,
+    }
+
+    impl RecordingHooks {
+        fn new() -> Self {
+            Self {
+                calls: Locked::new(Vec::new()),
+            }
+        }
+
+        fn record(&self, call: String) {
+            self.calls.update(|calls| calls.push(call));
+        }
+    }
+
+    impl InterviewHooks for RecordingHooks {
+        fn before_node_interview<'a>(&'a self, node: &'a Node<'a>) -> InterviewHookFuture<'a, ()> {
+            Box::pin(async move { self.record(format!("before node {}", node.id())) })
+        }
+
+        fn after_node_interview<'a>(&'a self, node: &'a Node<'a>) -> InterviewHookFuture<'a, ()> {
+            Box::pin(async move { self.record(format!("after node {}", node.id())) })
+        }
+
+        fn before_interview_stage(&self, _node: &Node, stage: InterviewStage) -> InterviewAction {
+            self.record(format!("stage {:?}", stage));
+            match stage {
+                InterviewStage::NodeInfo => InterviewAction::Skip,
+                _ => InterviewAction::Continue,
+            }
+        }
+
+        fn before_cc_interview<'a, 'e>(
+            &'a self,
+            _endpoint: &'a dyn EndpointLike<'e>,
+            cc: CommandClasses,
+        ) -> InterviewHookFuture<'a, InterviewAction> {
+            Box::pin(async move {
+                self.record(format!("before {}", cc));
+                InterviewAction::Skip
+            })
+        }
+
+        fn create_value(&self, value_id: &EndpointValueId, _metadata: &ValueMetadata) -> bool {
+            value_id.value_id() != BinarySwitchCCValues::target_value().id
+        }
+    }
+
+    #[test]
+    fn test_interview_hooks() {
+        let controller = MockController::new();
+        let hooks = Arc::new(RecordingHooks::new());
+        let value_ids = run_with_mock_controller(&controller, |driver| {
+            let hooks = hooks.clone();
+            async move {
+                driver.storage.nodes().update(|nodes| {
+                    nodes.insert(NodeId::new(5u8), NodeStorage::new(mock_protocol_data(true)));
+                });
+                driver.set_interview_hooks(Some(hooks));
+
+                let controller = Controller::mock(&driver);
+                let node = controller.node(NodeId::new(5u8)).unwrap();
+                node.modify_cc_info(
+                    CommandClasses::BinarySwitch,
+                    &PartialCommandClassInfo::default().supported().version(1),
+                );
+                node.interview().await.unwrap();
+                assert_eq!(node.interview_stage(), InterviewStage::Done);
+                driver.defined_value_ids(NodeId::new(5u8))
+            }
+        });
+
+        assert_eq!(
+            hooks.calls.cloned(),
+            vec![
+                "before node 005".to_string(),
+                "stage NodeInfo".to_string(),
+                "stage CommandClasses".to_string(),
+                "before Binary Switch".to_string(),
+                "after node 005".to_string(),
+            ]
+        );
+        // Neither the node info nor the Binary Switch CC were queried
+        assert!(controller.received().is_empty());
+        let value_ids: Vec<_> = value_ids.iter().map(|id| id.value_id()).collect();
+        assert!(value_ids.contains(&BinarySwitchCCValues::current_value().id));
+        assert!(!value_ids.contains(&BinarySwitchCCValues::target_value().id));
+    }
+}
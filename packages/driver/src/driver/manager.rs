@@ -0,0 +1,140 @@
+use super::Driver;
+use alloc::collections::BTreeMap;
+use thiserror::Error;
+use zwave_core::definitions::Id32;
+use zwave_pal::prelude::*;
+use zwave_pal::sync::Locked;
+
+impl Driver {
+    /// Returns the label that distinguishes this driver from others in the same process
+    pub fn label(&self) -> Option<String> {
+        self.serial_api.storage.label().cloned()
+    }
+
+    /// Labels this driver, e.g. with the name of its serial port.
+    /// If the `metrics` feature is enabled, the metrics of this driver are labeled by `controller`.
+    pub fn set_label(&self, label: impl Into<String>) {
+        self.serial_api.storage.label().set(Some(label.into()));
+    }
+
+    /// Returns the home ID of the network, if the controller was already identified
+    pub fn home_id(&self) -> Option<Id32> {
+        let controller = self.storage.controller().cloned()?;
+        Some(controller.inspect(|controller| controller.home_id))
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DriverManagerError {
+    #[error("A driver with the label {0:?} already exists")]
+    DuplicateLabel(String),
+}
+
+/// Keeps track of several drivers that run in the same process, e.g. one per Z-Wave stick.
+///
+/// The drivers are completely independent of each other. Each has its own actors, storage,
+/// log channel and event stream, so the application decides where their logs and events go.
+/// The manager only allows addressing them by their label or the home ID of their network.
+#[derive(Clone)]
+pub struct DriverManager {
+    drivers: Arc<Locked<BTreeMap<String, Driver>>>,
+}
+
+impl Default for DriverManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DriverManager {
+    pub fn new() -> Self {
+        Self {
+            drivers: Arc::new(Locked::new(BTreeMap::new())),
+        }
+    }
+
+    /// Adds a driver under the given label, which is also assigned to the driver
+    pub fn add(&self, label: impl Into<String>, driver: Driver) -> Result<(), DriverManagerError> {
+        let label = label.into();
+        self.drivers.update(|drivers| {
+            if drivers.contains_key(&label) {
+                return Err(DriverManagerError::DuplicateLabel(label));
+            }
+            driver.set_label(label.clone());
+            drivers.insert(label, driver);
+            Ok(())
+        })
+    }
+
+    /// Removes the driver with the given label, so it can be shut down
+    pub fn remove(&self, label: &str) -> Option<Driver> {
+        self.drivers.update(|drivers| drivers.remove(label))
+    }
+
+    pub fn get(&self, label: &str) -> Option<Driver> {
+        self.drivers.inspect(|drivers| drivers.get(label).cloned())
+    }
+
+    /// Returns the driver whose controller is part of the network with the given home ID
+    pub fn get_by_home_id(&self, home_id: Id32) -> Option<Driver> {
+        self.drivers.inspect(|drivers| {
+            drivers
+                .values()
+                .find(|driver| driver.home_id() == Some(home_id))
+                .cloned()
+        })
+    }
+
+    /// Returns the labels of all drivers in alphabetical order
+    pub fn labels(&self) -> Vec<String> {
+        self.drivers
+            .inspect(|drivers| drivers.keys().cloned().collect())
+    }
+
+    /// Returns all drivers with their labels in alphabetical order
+    pub fn drivers(&self) -> Vec<(String, Driver)> {
+        self.drivers.inspect(|drivers| {
+            drivers
+                .iter()
+                .map(|(label, driver)| (label.clone(), driver.clone()))
+                .collect()
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.drivers.inspect(|drivers| drivers.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::mock_driver;
+
+    #[test]
+    fn test_manage_drivers() {
+        let manager = DriverManager::new();
+        manager.add("ttyUSB1", mock_driver().driver).unwrap();
+        manager.add("ttyACM0", mock_driver().driver).unwrap();
+
+        assert_eq!(
+            manager.add("ttyUSB1", mock_driver().driver),
+            Err(DriverManagerError::DuplicateLabel("ttyUSB1".to_string()))
+        );
+        assert_eq!(manager.labels(), vec!["ttyACM0", "ttyUSB1"]);
+        assert_eq!(
+            manager.get("ttyUSB1").and_then(|driver| driver.label()),
+            Some("ttyUSB1".to_string())
+        );
+        // Neither controller was identified yet
+        assert!(manager.get_by_home_id(Id32::from(0xdeadbeef)).is_none());
+
+        assert!(manager.remove("ttyACM0").is_some());
+        assert!(manager.get("ttyACM0").is_none());
+        assert_eq!(manager.len(), 1);
+    }
+}
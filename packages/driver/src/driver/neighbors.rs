@@ -0,0 +1,112 @@
+use super::{ControllerCommandError, ControllerCommandResult, Driver};
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+impl Driver {
+    /// Returns the nodes in direct range of the given node, as last reported by the controller
+    pub fn node_neighbors(&self, node_id: NodeId) -> Option<Vec<NodeId>> {
+        self.storage
+            .nodes()
+            .inspect(|nodes| nodes.get(&node_id).and_then(|node| node.neighbors.clone()))
+    }
+
+    /// Asks the controller for the neighbors of the given node and remembers them
+    pub async fn refresh_node_neighbors(
+        &self,
+        node_id: NodeId,
+    ) -> ControllerCommandResult<Vec<NodeId>> {
+        let supported = self.storage.controller().inspect(|controller| {
+            controller.as_ref().is_some_and(|controller| {
+                controller.inspect(|controller| {
+                    controller
+                        .supported_function_types
+                        .contains(&FunctionType::GetRoutingInfo)
+                })
+            })
+        });
+        if !supported {
+            return Err(ControllerCommandError::Unsupported(
+                "GetRoutingInfo".to_string(),
+            ));
+        }
+
+        let neighbors = self.get_node_neighbors(node_id, None).await?;
+        self.storage.nodes().update(|nodes| {
+            if let Some(node) = nodes.get_mut(&node_id) {
+                node.neighbors = Some(neighbors.clone());
+            }
+        });
+        Ok(neighbors)
+    }
+
+    /// Discards the cached neighbors of the given node, e.g. because they are outdated
+    pub(super) fn forget_node_neighbors(&self, node_id: NodeId) {
+        self.storage.nodes().update(|nodes| {
+            if let Some(node) = nodes.get_mut(&node_id) {
+                node.neighbors = None;
+            }
+        });
+    }
+
+    /// Whether the given node is likely in direct range of the controller, according to its
+    /// cached neighbors. `None` if its neighbors are not known.
+    pub fn is_likely_direct_neighbor(&self, node_id: NodeId) -> Option<bool> {
+        let own_node_id = self.serial_api.storage.own_node_id();
+        self.node_neighbors(node_id)
+            .map(|neighbors| neighbors.contains(&own_node_id))
+    }
+
+    /// Chooses how a command to the given node is routed. Explorer frames flood the network to
+    /// find a new route, which is only worth the airtime if the node is not in direct range.
+    pub(super) fn routing_transmit_options(&self, node_id: NodeId) -> TransmitOptions {
+        let transmit_options = TransmitOptions::default();
+        match self.is_likely_direct_neighbor(node_id) {
+            Some(true) => transmit_options.explore(false),
+            _ => transmit_options,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use zwave_cc::commandclass::{CC, CCAddressable, NoOperationCC};
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_direct_neighbors_are_not_explored() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            let own_node_id = driver.serial_api.storage.own_node_id();
+            let node = NodeStorage::new(mock_protocol_data(true));
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(node_id, node);
+            });
+            assert_eq!(driver.is_likely_direct_neighbor(node_id), None);
+
+            let cc = CC::from(NoOperationCC {}).with_destination(node_id.into());
+            driver.exec_node_command(&cc, None).await.unwrap();
+
+            driver.storage.nodes().update(|nodes| {
+                nodes.get_mut(&node_id).unwrap().neighbors = Some(vec![own_node_id]);
+            });
+            assert_eq!(driver.is_likely_direct_neighbor(node_id), Some(true));
+            driver.exec_node_command(&cc, None).await.unwrap();
+        });
+
+        let transmit_options: Vec<_> = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            // The transmit options are followed by the callback ID
+            .map(|cmd| cmd.payload[cmd.payload.len() - 2])
+            .collect();
+        let default = TransmitOptions::default().as_bytes()[0];
+        let direct = TransmitOptions::default().explore(false).as_bytes()[0];
+        assert_eq!(transmit_options, vec![default, direct]);
+    }
+}
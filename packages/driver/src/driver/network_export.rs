@@ -0,0 +1,1465 @@
+use super::{
+    CalendarSchedule, Driver, PersistedCommand, PersistedCounters, PersistedQueuedCommand,
+    PersistedS2State, PersistedState, Repeat,
+};
+use crate::{
+    EncryptionPolicy, EndpointCCInheritance, EndpointStorage, InterviewStage, LinkQuality,
+    NodeEncryptionPolicy, NodeStorage, NodeUserMetadata, OptimisticUpdates, RefreshOnWakeUp,
+    SecurityKeys, WakeUpRefreshPolicy,
+};
+use bytes::Bytes;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use typed_builder::TypedBuilder;
+use zwave_core::cache::CacheValue;
+use zwave_core::prelude::*;
+use zwave_core::security::{
+    AesCcmNonce, AesKey, MpanState, NetworkKey, PersistedSpan, S2_MPAN_STATE_SIZE,
+    decrypt_aes_128_ccm, encrypt_aes_128_ccm,
+};
+use zwave_core::value_id::{EndpointValueId, ValueId};
+use zwave_pal::prelude::*;
+use zwave_pal::rng::getrandom;
+
+/// The version of the network file format written by this version of the library.
+/// Files with a newer version are rejected, older versions are migrated when importing.
+///
+/// - Version 2 added the per-node settings, the neighbors and link quality of nodes and the
+///   persisted driver state. Nodes from version 1 files get the default settings.
+pub const NETWORK_FILE_VERSION: u32 = 2;
+
+/// What is included when exporting the network state
+#[derive(TypedBuilder, Clone, Default)]
+pub struct NetworkExportOptions {
+    /// The network keys to include in the file. The driver does not keep them after it was
+    /// created, so the application has to pass them again. Default: none
+    #[builder(default, setter(strip_option))]
+    pub security_keys: Option<SecurityKeys>,
+    /// Encrypts the network keys with this key. Without it, they are stored in plain text and the
+    /// file must be kept as secret as the keys themselves. Default: none
+    #[builder(default, setter(into, strip_option))]
+    pub encryption_key: Option<NetworkKey>,
+}
+
+/// How the network state is imported
+#[derive(TypedBuilder, Clone, Default)]
+pub struct NetworkImportOptions {
+    /// Decrypts the network keys, if they were encrypted during the export. Default: none
+    #[builder(default, setter(into, strip_option))]
+    pub encryption_key: Option<NetworkKey>,
+}
+
+/// What was restored from a network file
+#[derive(Default, Clone)]
+pub struct NetworkImport {
+    /// How many nodes were restored
+    pub nodes: usize,
+    /// How many values were restored
+    pub values: usize,
+    /// The network keys contained in the file. They must be passed to [`Driver::new`] when the
+    /// driver is created on the new host.
+    pub security_keys: Option<SecurityKeys>,
+}
+
+#[derive(Error, Debug)]
+/// Defines the possible errors while exporting or importing the network state
+pub enum NetworkFileError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The network file is malformed: {0}")]
+    Malformed(String),
+    #[error("The network file has version {0}, which is not supported by this version")]
+    UnsupportedVersion(u32),
+    #[error(
+        "The network file belongs to the network {file}, but the controller is in {controller}"
+    )]
+    HomeIdMismatch { file: Id32, controller: Id32 },
+    #[error("The network keys are encrypted, but no key to decrypt them was given")]
+    MissingEncryptionKey,
+    #[error("The network keys could not be decrypted")]
+    DecryptionFailed,
+    #[error("Failed to generate random bytes: {0}")]
+    Random(zwave_pal::rng::Error),
+}
+
+impl From<serde_json::Error> for NetworkFileError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Malformed(e.to_string())
+    }
+}
+
+/// A versioned snapshot of everything the driver knows about a network, so it can be moved to
+/// another host without interviewing all nodes again. It is stored as JSON:
+///
+/// - `format_version`: the version of the file format, see [`NETWORK_FILE_VERSION`]
+/// - `home_id`: the home ID of the network as a hex string, if the controller was identified
+/// - `nodes`: the cached node information, see [`NodeFile`]
+/// - `values`: the values the nodes have reported, see [`ValueFile`]
+/// - `security_keys`: the network keys as hex strings, optionally encrypted with AES-128-CCM
+/// - `state`: the state the driver needs to continue after a restart, see [`PersistedStateFile`]
+///
+/// Binary data is always encoded as lowercase hex strings without prefix, durations as
+/// milliseconds.
+///
+/// The status of nodes and the communication statistics are not included. They only describe the
+/// current session and are determined again on the new host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkFile {
+    pub format_version: u32,
+    pub home_id: Option<String>,
+    pub nodes: Vec<NodeFile>,
+    pub values: Vec<ValueFile>,
+    pub security_keys: Option<SecurityKeysFile>,
+    #[serde(default)]
+    pub state: Option<PersistedStateFile>,
+}
+
+/// The cached information about a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeFile {
+    pub node_id: u16,
+    pub interview_stage: InterviewStageFile,
+    /// The node information protocol data, as returned by the controller
+    pub protocol_data: String,
+    pub endpoints: Vec<EndpointFile>,
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub room: Option<String>,
+    pub notes: Option<String>,
+    /// The nodes in direct range of this node, as last reported by the controller
+    #[serde(default)]
+    pub neighbors: Option<Vec<u16>>,
+    /// The result of the last link quality check
+    #[serde(default)]
+    pub link_quality: Option<LinkQualityFile>,
+    #[serde(default)]
+    pub optimistic_updates: Option<OptimisticUpdatesFile>,
+    #[serde(default)]
+    pub wake_up_refresh_policy: Option<WakeUpRefreshPolicyFile>,
+    #[serde(default)]
+    pub encryption_policy: NodeEncryptionPolicyFile,
+    /// Overrides whether the endpoints inherit a CC from the root device
+    #[serde(default)]
+    pub endpoint_cc_inheritance: Vec<CCInheritanceFile>,
+}
+
+/// How far the interview of a node got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InterviewStageFile {
+    None,
+    NodeInfo,
+    CommandClasses,
+    Done,
+}
+
+impl From<InterviewStage> for InterviewStageFile {
+    fn from(stage: InterviewStage) -> Self {
+        match stage {
+            InterviewStage::None => Self::None,
+            InterviewStage::NodeInfo => Self::NodeInfo,
+            InterviewStage::CommandClasses => Self::CommandClasses,
+            InterviewStage::Done => Self::Done,
+        }
+    }
+}
+
+impl From<InterviewStageFile> for InterviewStage {
+    fn from(stage: InterviewStageFile) -> Self {
+        match stage {
+            InterviewStageFile::None => Self::None,
+            InterviewStageFile::NodeInfo => Self::NodeInfo,
+            InterviewStageFile::CommandClasses => Self::CommandClasses,
+            InterviewStageFile::Done => Self::Done,
+        }
+    }
+}
+
+/// See [`LinkQuality`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkQualityFile {
+    pub score: u8,
+    pub pings: u8,
+    pub acknowledged_pings: u8,
+    pub test_frames: Option<u16>,
+    pub acknowledged_test_frames: u16,
+}
+
+/// See [`OptimisticUpdates`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptimisticUpdatesFile {
+    Immediately,
+    AfterSupervision,
+    AfterVerification,
+}
+
+/// See [`WakeUpRefreshPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WakeUpRefreshPolicyFile {
+    pub ccs: Vec<RefreshOnWakeUpFile>,
+    pub time_budget: u64,
+    pub send_no_more_information: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshOnWakeUpFile {
+    pub command_class: u16,
+    pub max_age: Option<u64>,
+}
+
+/// See [`NodeEncryptionPolicy`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeEncryptionPolicyFile {
+    pub default: EncryptionPolicyFile,
+    pub ccs: Vec<CCEncryptionPolicyFile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CCEncryptionPolicyFile {
+    pub command_class: u16,
+    pub policy: EncryptionPolicyFile,
+}
+
+/// See [`EncryptionPolicy`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EncryptionPolicyFile {
+    #[default]
+    Automatic,
+    Opportunistic,
+    Required,
+    Never,
+}
+
+impl From<EncryptionPolicy> for EncryptionPolicyFile {
+    fn from(policy: EncryptionPolicy) -> Self {
+        match policy {
+            EncryptionPolicy::Automatic => Self::Automatic,
+            EncryptionPolicy::Opportunistic => Self::Opportunistic,
+            EncryptionPolicy::Required => Self::Required,
+            EncryptionPolicy::Never => Self::Never,
+        }
+    }
+}
+
+impl From<EncryptionPolicyFile> for EncryptionPolicy {
+    fn from(policy: EncryptionPolicyFile) -> Self {
+        match policy {
+            EncryptionPolicyFile::Automatic => Self::Automatic,
+            EncryptionPolicyFile::Opportunistic => Self::Opportunistic,
+            EncryptionPolicyFile::Required => Self::Required,
+            EncryptionPolicyFile::Never => Self::Never,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CCInheritanceFile {
+    pub command_class: u16,
+    pub inherited: bool,
+}
+
+/// See [`PersistedState`]. The S2 nonce state is as secret as the network keys, so it is stored
+/// together with them in [`PlainSecurityKeysFile`] and only exported if they are.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedStateFile {
+    /// When the state was exported, in seconds since the Unix epoch
+    pub exported_at: Option<u64>,
+    pub callback_id: u8,
+    pub s2_sequence_numbers: Vec<S2SequenceNumberFile>,
+    pub node_infos: Vec<NodeInfoFile>,
+    pub queued_commands: Vec<QueuedCommandFile>,
+    pub scheduled_commands: Vec<ScheduledCommandFile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct S2SequenceNumberFile {
+    pub node_id: u16,
+    pub sequence_number: u8,
+}
+
+/// The node information that was last received from a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInfoFile {
+    pub node_id: u16,
+    /// The node information application data, as sent by the node
+    pub node_info: String,
+}
+
+/// See [`PersistedQueuedCommand`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedCommandFile {
+    pub node_id: u16,
+    pub endpoint: u8,
+    pub payload: String,
+}
+
+/// See [`PersistedCommand`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledCommandFile {
+    pub node_id: u16,
+    pub endpoint: u8,
+    pub payload: String,
+    pub due_in: u64,
+    pub repeat: Option<RepeatFile>,
+}
+
+/// See [`Repeat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RepeatFile {
+    Every { interval: u64 },
+    Calendar { weekdays: u8, hour: u8, minute: u8 },
+}
+
+/// See [`PersistedS2State`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct S2StateFile {
+    pub spans: Vec<SpanFile>,
+    pub peer_mpans: Vec<PeerMpanFile>,
+}
+
+/// See [`PersistedSpan`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpanFile {
+    pub node_id: u16,
+    pub security_class: SecurityClassFile,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerMpanFile {
+    pub node_id: u16,
+    pub group_id: u8,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecurityClassFile {
+    S2Unauthenticated,
+    S2Authenticated,
+    S2AccessControl,
+    S0Legacy,
+}
+
+impl From<SecurityClass> for SecurityClassFile {
+    fn from(class: SecurityClass) -> Self {
+        match class {
+            SecurityClass::S2Unauthenticated => Self::S2Unauthenticated,
+            SecurityClass::S2Authenticated => Self::S2Authenticated,
+            SecurityClass::S2AccessControl => Self::S2AccessControl,
+            SecurityClass::S0Legacy => Self::S0Legacy,
+        }
+    }
+}
+
+impl From<SecurityClassFile> for SecurityClass {
+    fn from(class: SecurityClassFile) -> Self {
+        match class {
+            SecurityClassFile::S2Unauthenticated => Self::S2Unauthenticated,
+            SecurityClassFile::S2Authenticated => Self::S2Authenticated,
+            SecurityClassFile::S2AccessControl => Self::S2AccessControl,
+            SecurityClassFile::S0Legacy => Self::S0Legacy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointFile {
+    pub index: u8,
+    pub command_classes: Vec<CommandClassFile>,
+}
+
+/// An entry in the CC support table of an endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandClassFile {
+    pub id: u16,
+    pub supported: bool,
+    pub controlled: bool,
+    pub secure: bool,
+    pub version: u8,
+}
+
+/// A value that was reported by a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueFile {
+    pub node_id: u16,
+    pub endpoint: u8,
+    pub command_class: u16,
+    pub property: u16,
+    pub property_key: Option<u32>,
+    pub value: ValueDataFile,
+}
+
+/// A cached value. Z-Wave specific values like durations and levels are stored as the byte
+/// that encodes them in commands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum ValueDataFile {
+    Bool(bool),
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Float(f32),
+    String(String),
+    Buffer(String),
+    DurationSet(u8),
+    DurationReport(u8),
+    LevelSet(u8),
+    LevelReport(u8),
+    BinarySet(u8),
+    BinaryReport(u8),
+}
+
+/// The network keys, either in plain text or encrypted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SecurityKeysFile {
+    Plain(PlainSecurityKeysFile),
+    /// The JSON encoded [`PlainSecurityKeysFile`], encrypted with AES-128-CCM
+    Encrypted {
+        nonce: String,
+        ciphertext: String,
+        auth_tag: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlainSecurityKeysFile {
+    pub s0_legacy: Option<String>,
+    pub s2_unauthenticated: Option<String>,
+    pub s2_authenticated: Option<String>,
+    pub s2_access_control: Option<String>,
+    /// The S2 nonce state, see [`PersistedStateFile`]
+    #[serde(default)]
+    pub s2_state: Option<S2StateFile>,
+}
+
+impl NetworkFile {
+    pub fn to_json(&self) -> Result<String, NetworkFileError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, NetworkFileError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl Driver {
+    /// Collects the cached state of the network, so it can be moved to another host
+    pub fn network_file(
+        &self,
+        options: &NetworkExportOptions,
+    ) -> Result<NetworkFile, NetworkFileError> {
+        let nodes = self.storage.nodes().inspect(|nodes| {
+            nodes
+                .iter()
+                .map(|(id, node)| node_file(*id, node))
+                .collect()
+        });
+
+        let mut values: Vec<_> = self.storage.value_cache().inspect(|cache| {
+            cache
+                .iter()
+                .map(|(value_id, value)| ValueFile {
+                    node_id: value_id.node_id().into(),
+                    endpoint: endpoint_to_u8(value_id.endpoint()),
+                    command_class: value_id.command_class() as u16,
+                    property: value_id.property(),
+                    property_key: value_id.property_key(),
+                    value: value_to_file(value),
+                })
+                .collect()
+        });
+        // The cache is unordered, but the files should be comparable
+        values.sort_by_key(|v| {
+            (
+                v.node_id,
+                v.endpoint,
+                v.command_class,
+                v.property,
+                v.property_key,
+            )
+        });
+
+        let state = self.export_state();
+        let security_keys = options
+            .security_keys
+            .as_ref()
+            .map(|keys| {
+                security_keys_to_file(keys, &state.s2_state, options.encryption_key.as_ref())
+            })
+            .transpose()?;
+
+        Ok(NetworkFile {
+            format_version: NETWORK_FILE_VERSION,
+            home_id: self.home_id().map(|home_id| format!("{:08x}", home_id)),
+            nodes,
+            values,
+            security_keys,
+            state: Some(state_to_file(&state)),
+        })
+    }
+
+    /// Writes the cached state of the network to the given file.
+    ///
+    /// The state is written to a temporary file next to it first, which then replaces the file.
+    /// A crash during the export therefore leaves the previous file intact.
+    pub fn export_network(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: &NetworkExportOptions,
+    ) -> Result<(), NetworkFileError> {
+        write_atomically(
+            path.as_ref(),
+            self.network_file(options)?.to_json()?.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// Restores the network state from a file that was created by [`Driver::export_network`].
+    /// The nodes and values in the file replace the ones the driver knows about. Nodes whose
+    /// interview was complete are not interviewed again. The persisted driver state is restored
+    /// like with [`Driver::restore_state`].
+    ///
+    /// If the controller was already identified, it must belong to the same network as the file.
+    pub fn import_network(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: &NetworkImportOptions,
+    ) -> Result<NetworkImport, NetworkFileError> {
+        let file = NetworkFile::from_json(&std::fs::read_to_string(path)?)?;
+        self.restore_network_file(&file, options)
+    }
+
+    /// Restores the network state from a file that was already read
+    pub fn restore_network_file(
+        &self,
+        file: &NetworkFile,
+        options: &NetworkImportOptions,
+    ) -> Result<NetworkImport, NetworkFileError> {
+        if file.format_version > NETWORK_FILE_VERSION {
+            return Err(NetworkFileError::UnsupportedVersion(file.format_version));
+        }
+        if let (Some(home_id), Some(controller)) = (&file.home_id, self.home_id()) {
+            let file_home_id = u32::from_str_radix(home_id, 16)
+                .map_err(|_| malformed(format!("invalid home ID {:?}", home_id)))?;
+            if file_home_id != u32::from(controller) {
+                return Err(NetworkFileError::HomeIdMismatch {
+                    file: file_home_id.into(),
+                    controller,
+                });
+            }
+        }
+
+        // Parse everything before changing anything, so a broken file does not leave a
+        // half-imported network behind
+        let nodes = file
+            .nodes
+            .iter()
+            .map(|node| Ok((NodeId::new(node.node_id), node_from_file(node)?)))
+            .collect::<Result<Vec<_>, NetworkFileError>>()?;
+        let values = file
+            .values
+            .iter()
+            .map(value_from_file)
+            .collect::<Result<Vec<_>, NetworkFileError>>()?;
+        let (security_keys, s2_state) = file
+            .security_keys
+            .as_ref()
+            .map(|keys| security_keys_from_file(keys, options.encryption_key.as_ref()))
+            .transpose()?
+            .unzip();
+        let state = file
+            .state
+            .as_ref()
+            .map(|state| state_from_file(state, s2_state.flatten()))
+            .transpose()?;
+
+        let result = NetworkImport {
+            nodes: nodes.len(),
+            values: values.len(),
+            security_keys,
+        };
+
+        // The values of completely interviewed nodes must exist without a new interview
+        let mut defined_ccs = Vec::new();
+        for (node_id, node) in nodes.iter() {
+            if node.interview_stage != InterviewStage::Done {
+                continue;
+            }
+            for (index, endpoint) in node.endpoints.iter() {
+                for (cc, info) in endpoint.cc_info.iter().filter(|(_, info)| info.supported) {
+                    defined_ccs.push((*node_id, *index, *cc, info.version));
+                }
+            }
+        }
+
+        // The file replaces the known nodes, including their values
+        self.storage.nodes().update(|storage| {
+            storage.clear();
+            storage.extend(nodes);
+        });
+        self.storage.value_cache().update(|cache| {
+            cache.clear();
+            cache.extend(values);
+        });
+        self.storage
+            .value_timestamps()
+            .update(|timestamps| timestamps.clear());
+        self.storage
+            .value_metadata()
+            .update(|metadata| metadata.clear());
+        for (node_id, endpoint, cc, version) in defined_ccs {
+            self.create_cc_values(node_id, endpoint, cc, version);
+        }
+        if let Some((state, elapsed)) = state {
+            self.restore_state(&state, elapsed);
+        }
+
+        self.driver_log().info(|| {
+            format!(
+                "imported {} nodes and {} values from the network file",
+                result.nodes, result.values
+            )
+        });
+        Ok(result)
+    }
+}
+
+fn write_atomically(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // Concurrent exports, also from other processes, must not share a temporary file
+    static EXPORTS: AtomicU32 = AtomicU32::new(0);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the path has no file name",
+        )
+    })?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        EXPORTS.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp_path = dir.join(temp_name);
+
+    let result = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+        return result;
+    }
+
+    // The rename is only durable once the directory entry is
+    #[cfg(unix)]
+    std::fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+fn malformed(reason: String) -> NetworkFileError {
+    NetworkFileError::Malformed(reason)
+}
+
+fn endpoint_to_u8(endpoint: EndpointIndex) -> u8 {
+    match endpoint {
+        EndpointIndex::Root => 0,
+        EndpointIndex::Endpoint(index) => index,
+    }
+}
+
+fn endpoint_from_u8(index: u8) -> EndpointIndex {
+    match index {
+        0 => EndpointIndex::Root,
+        index => EndpointIndex::Endpoint(index),
+    }
+}
+
+fn command_class_from_u16(id: u16) -> Result<CommandClasses, NetworkFileError> {
+    CommandClasses::try_from(id).map_err(|_| malformed(format!("unknown CC {:#06x}", id)))
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, NetworkFileError> {
+    hex::decode(hex).map_err(|_| malformed(format!("invalid hex string {:?}", hex)))
+}
+
+fn node_file(node_id: NodeId, node: &NodeStorage) -> NodeFile {
+    let metadata = &node.user_metadata;
+    NodeFile {
+        node_id: node_id.into(),
+        interview_stage: node.interview_stage.into(),
+        protocol_data: hex::encode(node.protocol_data.as_bytes()),
+        endpoints: node
+            .endpoints
+            .iter()
+            .map(|(index, endpoint)| EndpointFile {
+                index: endpoint_to_u8(*index),
+                command_classes: endpoint
+                    .cc_info
+                    .iter()
+                    .map(|(cc, info)| CommandClassFile {
+                        id: *cc as u16,
+                        supported: info.supported,
+                        controlled: info.controlled,
+                        secure: info.secure,
+                        version: info.version,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        name: metadata.name.clone(),
+        location: metadata.location.clone(),
+        room: metadata.room.clone(),
+        notes: metadata.notes.clone(),
+        neighbors: node
+            .neighbors
+            .as_ref()
+            .map(|neighbors| neighbors.iter().map(|id| (*id).into()).collect()),
+        link_quality: node.link_quality.map(|quality| LinkQualityFile {
+            score: quality.score,
+            pings: quality.pings,
+            acknowledged_pings: quality.acknowledged_pings,
+            test_frames: quality.test_frames,
+            acknowledged_test_frames: quality.acknowledged_test_frames,
+        }),
+        optimistic_updates: node.optimistic_updates.map(|updates| match updates {
+            OptimisticUpdates::Immediately => OptimisticUpdatesFile::Immediately,
+            OptimisticUpdates::AfterSupervision => OptimisticUpdatesFile::AfterSupervision,
+            OptimisticUpdates::AfterVerification => OptimisticUpdatesFile::AfterVerification,
+        }),
+        wake_up_refresh_policy: node.wake_up_refresh_policy.as_ref().map(|policy| {
+            WakeUpRefreshPolicyFile {
+                ccs: policy
+                    .ccs
+                    .iter()
+                    .map(|refresh| RefreshOnWakeUpFile {
+                        command_class: refresh.cc as u16,
+                        max_age: refresh.max_age.map(duration_to_millis),
+                    })
+                    .collect(),
+                time_budget: duration_to_millis(policy.time_budget),
+                send_no_more_information: policy.send_no_more_information,
+            }
+        }),
+        encryption_policy: NodeEncryptionPolicyFile {
+            default: node.encryption_policy.default.into(),
+            ccs: node
+                .encryption_policy
+                .ccs
+                .iter()
+                .map(|(cc, policy)| CCEncryptionPolicyFile {
+                    command_class: *cc as u16,
+                    policy: (*policy).into(),
+                })
+                .collect(),
+        },
+        endpoint_cc_inheritance: node
+            .endpoint_cc_inheritance
+            .overrides
+            .iter()
+            .map(|(cc, inherited)| CCInheritanceFile {
+                command_class: *cc as u16,
+                inherited: *inherited,
+            })
+            .collect(),
+    }
+}
+
+fn node_from_file(file: &NodeFile) -> Result<NodeStorage, NetworkFileError> {
+    let protocol_data =
+        NodeInformationProtocolData::parse(&mut Bytes::from(decode_hex(&file.protocol_data)?))
+            .map_err(|_| malformed(format!("invalid protocol data of node {}", file.node_id)))?;
+    let mut node = NodeStorage::new(protocol_data);
+    node.interview_stage = file.interview_stage.into();
+    for endpoint in file.endpoints.iter() {
+        let mut storage = EndpointStorage::new();
+        for cc in endpoint.command_classes.iter() {
+            storage.cc_info.insert(
+                command_class_from_u16(cc.id)?,
+                CommandClassInfo {
+                    supported: cc.supported,
+                    controlled: cc.controlled,
+                    secure: cc.secure,
+                    version: cc.version,
+                },
+            );
+        }
+        node.endpoints
+            .insert(endpoint_from_u8(endpoint.index), storage);
+    }
+    node.user_metadata = NodeUserMetadata {
+        name: file.name.clone(),
+        location: file.location.clone(),
+        room: file.room.clone(),
+        notes: file.notes.clone(),
+    };
+    node.neighbors = file
+        .neighbors
+        .as_ref()
+        .map(|neighbors| neighbors.iter().map(|id| NodeId::new(*id)).collect());
+    node.link_quality = file.link_quality.map(|quality| LinkQuality {
+        score: quality.score,
+        pings: quality.pings,
+        acknowledged_pings: quality.acknowledged_pings,
+        test_frames: quality.test_frames,
+        acknowledged_test_frames: quality.acknowledged_test_frames,
+    });
+    node.optimistic_updates = file.optimistic_updates.map(|updates| match updates {
+        OptimisticUpdatesFile::Immediately => OptimisticUpdates::Immediately,
+        OptimisticUpdatesFile::AfterSupervision => OptimisticUpdates::AfterSupervision,
+        OptimisticUpdatesFile::AfterVerification => OptimisticUpdates::AfterVerification,
+    });
+    node.wake_up_refresh_policy = file
+        .wake_up_refresh_policy
+        .as_ref()
+        .map(|policy| -> Result<_, NetworkFileError> {
+            Ok(WakeUpRefreshPolicy {
+                ccs: policy
+                    .ccs
+                    .iter()
+                    .map(|refresh| {
+                        Ok(RefreshOnWakeUp {
+                            cc: command_class_from_u16(refresh.command_class)?,
+                            max_age: refresh.max_age.map(Duration::from_millis),
+                        })
+                    })
+                    .collect::<Result<_, NetworkFileError>>()?,
+                time_budget: Duration::from_millis(policy.time_budget),
+                send_no_more_information: policy.send_no_more_information,
+            })
+        })
+        .transpose()?;
+    node.encryption_policy = NodeEncryptionPolicy {
+        default: file.encryption_policy.default.into(),
+        ccs: file
+            .encryption_policy
+            .ccs
+            .iter()
+            .map(|cc| Ok((command_class_from_u16(cc.command_class)?, cc.policy.into())))
+            .collect::<Result<_, NetworkFileError>>()?,
+    };
+    node.endpoint_cc_inheritance = EndpointCCInheritance {
+        overrides: file
+            .endpoint_cc_inheritance
+            .iter()
+            .map(|cc| Ok((command_class_from_u16(cc.command_class)?, cc.inherited)))
+            .collect::<Result<_, NetworkFileError>>()?,
+    };
+    Ok(node)
+}
+
+fn duration_to_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+fn value_to_file(value: &CacheValue) -> ValueDataFile {
+    let byte = |value: &dyn Serializable| value.as_bytes()[0];
+    match value {
+        CacheValue::Bool(v) => ValueDataFile::Bool(*v),
+        CacheValue::UInt8(v) => ValueDataFile::UInt8(*v),
+        CacheValue::UInt16(v) => ValueDataFile::UInt16(*v),
+        CacheValue::UInt32(v) => ValueDataFile::UInt32(*v),
+        CacheValue::Int8(v) => ValueDataFile::Int8(*v),
+        CacheValue::Int16(v) => ValueDataFile::Int16(*v),
+        CacheValue::Int32(v) => ValueDataFile::Int32(*v),
+        CacheValue::Float(v) => ValueDataFile::Float(*v),
+        CacheValue::String(v) => ValueDataFile::String(v.clone()),
+        CacheValue::Buffer(v) => ValueDataFile::Buffer(hex::encode(v)),
+        CacheValue::DurationSet(v) => ValueDataFile::DurationSet(byte(v)),
+        CacheValue::DurationReport(v) => ValueDataFile::DurationReport(byte(v)),
+        CacheValue::LevelSet(v) => ValueDataFile::LevelSet(byte(v)),
+        CacheValue::LevelReport(v) => ValueDataFile::LevelReport(byte(v)),
+        CacheValue::BinarySet(v) => ValueDataFile::BinarySet(byte(v)),
+        CacheValue::BinaryReport(v) => ValueDataFile::BinaryReport(byte(v)),
+    }
+}
+
+fn value_from_file(file: &ValueFile) -> Result<(EndpointValueId, CacheValue), NetworkFileError> {
+    fn parse<T: Parsable>(byte: u8) -> Result<T, NetworkFileError> {
+        T::parse(&mut Bytes::copy_from_slice(&[byte]))
+            .map_err(|_| malformed(format!("invalid value {:#04x}", byte)))
+    }
+
+    let value = match &file.value {
+        ValueDataFile::Bool(v) => CacheValue::Bool(*v),
+        ValueDataFile::UInt8(v) => CacheValue::UInt8(*v),
+        ValueDataFile::UInt16(v) => CacheValue::UInt16(*v),
+        ValueDataFile::UInt32(v) => CacheValue::UInt32(*v),
+        ValueDataFile::Int8(v) => CacheValue::Int8(*v),
+        ValueDataFile::Int16(v) => CacheValue::Int16(*v),
+        ValueDataFile::Int32(v) => CacheValue::Int32(*v),
+        ValueDataFile::Float(v) => CacheValue::Float(*v),
+        ValueDataFile::String(v) => CacheValue::String(v.clone()),
+        ValueDataFile::Buffer(v) => CacheValue::Buffer(decode_hex(v)?),
+        ValueDataFile::DurationSet(v) => CacheValue::DurationSet(parse(*v)?),
+        ValueDataFile::DurationReport(v) => CacheValue::DurationReport(parse(*v)?),
+        ValueDataFile::LevelSet(v) => CacheValue::LevelSet(parse(*v)?),
+        ValueDataFile::LevelReport(v) => CacheValue::LevelReport(parse(*v)?),
+        ValueDataFile::BinarySet(v) => CacheValue::BinarySet(parse(*v)?),
+        ValueDataFile::BinaryReport(v) => CacheValue::BinaryReport(parse(*v)?),
+    };
+    let value_id = EndpointValueId::new(
+        NodeId::new(file.node_id),
+        endpoint_from_u8(file.endpoint),
+        ValueId::new(
+            command_class_from_u16(file.command_class)?,
+            file.property,
+            file.property_key,
+        ),
+    );
+    Ok((value_id, value))
+}
+
+fn security_keys_to_file(
+    keys: &SecurityKeys,
+    s2_state: &PersistedS2State,
+    encryption_key: Option<&NetworkKey>,
+) -> Result<SecurityKeysFile, NetworkFileError> {
+    let encode = |key: &Option<NetworkKey>| key.as_ref().map(|key| key.to_string());
+    let plain = PlainSecurityKeysFile {
+        s0_legacy: encode(&keys.s0_legacy),
+        s2_unauthenticated: encode(&keys.s2_unauthenticated),
+        s2_authenticated: encode(&keys.s2_authenticated),
+        s2_access_control: encode(&keys.s2_access_control),
+        s2_state: Some(s2_state_to_file(s2_state)),
+    };
+    let Some(encryption_key) = encryption_key else {
+        return Ok(SecurityKeysFile::Plain(plain));
+    };
+
+    let mut nonce = [0u8; 13];
+    getrandom(&mut nonce).map_err(NetworkFileError::Random)?;
+    let plaintext = serde_json::to_vec(&plain)?;
+    let encrypted = encrypt_aes_128_ccm(
+        &AesKey::from(encryption_key),
+        &AesCcmNonce::from(nonce),
+        &plaintext,
+        &[],
+    );
+    Ok(SecurityKeysFile::Encrypted {
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(encrypted.ciphertext),
+        auth_tag: hex::encode(encrypted.auth_tag),
+    })
+}
+
+fn security_keys_from_file(
+    file: &SecurityKeysFile,
+    encryption_key: Option<&NetworkKey>,
+) -> Result<(SecurityKeys, Option<PersistedS2State>), NetworkFileError> {
+    let plain = match file {
+        SecurityKeysFile::Plain(plain) => plain.clone(),
+        SecurityKeysFile::Encrypted {
+            nonce,
+            ciphertext,
+            auth_tag,
+        } => {
+            let encryption_key = encryption_key.ok_or(NetworkFileError::MissingEncryptionKey)?;
+            let nonce: [u8; 13] = decode_hex(nonce)?
+                .try_into()
+                .map_err(|_| malformed("invalid nonce".to_string()))?;
+            let auth_tag: [u8; 8] = decode_hex(auth_tag)?
+                .try_into()
+                .map_err(|_| malformed("invalid auth tag".to_string()))?;
+            let plaintext = decrypt_aes_128_ccm(
+                &AesKey::from(encryption_key),
+                &AesCcmNonce::from(nonce),
+                &decode_hex(ciphertext)?,
+                &[],
+                &auth_tag,
+            )
+            .ok_or(NetworkFileError::DecryptionFailed)?;
+            serde_json::from_slice(&plaintext)?
+        }
+    };
+
+    let decode = |key: &Option<String>| -> Result<Option<NetworkKey>, NetworkFileError> {
+        key.as_deref()
+            .map(|key| {
+                NetworkKey::try_from(decode_hex(key)?)
+                    .map_err(|e| malformed(format!("invalid network key: {}", e)))
+            })
+            .transpose()
+    };
+    let keys = SecurityKeys {
+        s0_legacy: decode(&plain.s0_legacy)?,
+        s2_unauthenticated: decode(&plain.s2_unauthenticated)?,
+        s2_authenticated: decode(&plain.s2_authenticated)?,
+        s2_access_control: decode(&plain.s2_access_control)?,
+    };
+    let s2_state = plain
+        .s2_state
+        .as_ref()
+        .map(s2_state_from_file)
+        .transpose()?;
+    Ok((keys, s2_state))
+}
+
+fn state_to_file(state: &PersistedState) -> PersistedStateFile {
+    PersistedStateFile {
+        exported_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|since_epoch| since_epoch.as_secs()),
+        callback_id: state.counters.callback_id,
+        s2_sequence_numbers: state
+            .counters
+            .s2_sequence_numbers
+            .iter()
+            .map(|(node_id, sequence_number)| S2SequenceNumberFile {
+                node_id: (*node_id).into(),
+                sequence_number: *sequence_number,
+            })
+            .collect(),
+        node_infos: state
+            .node_infos
+            .iter()
+            .map(|(node_id, node_info)| NodeInfoFile {
+                node_id: (*node_id).into(),
+                node_info: hex::encode(node_info.as_bytes()),
+            })
+            .collect(),
+        queued_commands: state
+            .queued_commands
+            .iter()
+            .map(|command| QueuedCommandFile {
+                node_id: command.node_id.into(),
+                endpoint: endpoint_to_u8(command.endpoint),
+                payload: hex::encode(&command.payload),
+            })
+            .collect(),
+        scheduled_commands: state
+            .scheduled_commands
+            .iter()
+            .map(|command| ScheduledCommandFile {
+                node_id: command.node_id.into(),
+                endpoint: endpoint_to_u8(command.endpoint),
+                payload: hex::encode(&command.payload),
+                due_in: duration_to_millis(command.due_in),
+                repeat: command.repeat.map(|repeat| match repeat {
+                    Repeat::Every(interval) => RepeatFile::Every {
+                        interval: duration_to_millis(interval),
+                    },
+                    Repeat::Calendar(calendar) => RepeatFile::Calendar {
+                        weekdays: calendar.weekdays(),
+                        hour: calendar.hour(),
+                        minute: calendar.minute(),
+                    },
+                }),
+            })
+            .collect(),
+    }
+}
+
+/// Returns the persisted state and how much time has passed since it was exported
+fn state_from_file(
+    file: &PersistedStateFile,
+    s2_state: Option<PersistedS2State>,
+) -> Result<(PersistedState, Duration), NetworkFileError> {
+    let node_infos = file
+        .node_infos
+        .iter()
+        .map(|node_info| {
+            let parsed = NodeInformationApplicationData::parse(&mut Bytes::from(decode_hex(
+                &node_info.node_info,
+            )?))
+            .map_err(|_| malformed(format!("invalid node info of node {}", node_info.node_id)))?;
+            Ok((NodeId::new(node_info.node_id), parsed))
+        })
+        .collect::<Result<_, NetworkFileError>>()?;
+    let queued_commands = file
+        .queued_commands
+        .iter()
+        .map(|command| {
+            Ok(PersistedQueuedCommand {
+                node_id: NodeId::new(command.node_id),
+                endpoint: endpoint_from_u8(command.endpoint),
+                payload: decode_hex(&command.payload)?,
+            })
+        })
+        .collect::<Result<_, NetworkFileError>>()?;
+    let scheduled_commands = file
+        .scheduled_commands
+        .iter()
+        .map(|command| {
+            let repeat = match command.repeat {
+                None => None,
+                Some(RepeatFile::Every { interval }) => {
+                    Some(Repeat::Every(Duration::from_millis(interval)))
+                }
+                Some(RepeatFile::Calendar {
+                    weekdays,
+                    hour,
+                    minute,
+                }) => Some(Repeat::Calendar(
+                    CalendarSchedule::new(weekdays, hour, minute).ok_or_else(|| {
+                        malformed(format!(
+                            "invalid calendar schedule {:02}:{:02}",
+                            hour, minute
+                        ))
+                    })?,
+                )),
+            };
+            Ok(PersistedCommand {
+                node_id: NodeId::new(command.node_id),
+                endpoint: endpoint_from_u8(command.endpoint),
+                payload: decode_hex(&command.payload)?,
+                due_in: Duration::from_millis(command.due_in),
+                repeat,
+            })
+        })
+        .collect::<Result<_, NetworkFileError>>()?;
+
+    let state = PersistedState {
+        counters: PersistedCounters {
+            callback_id: file.callback_id,
+            s2_sequence_numbers: file
+                .s2_sequence_numbers
+                .iter()
+                .map(|entry| (NodeId::new(entry.node_id), entry.sequence_number))
+                .collect(),
+        },
+        s2_state: s2_state.unwrap_or_default(),
+        node_infos,
+        queued_commands,
+        scheduled_commands,
+    };
+    // Without a known export time, the restart is assumed to be quick. That is the safe choice
+    // for the counters.
+    let elapsed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .zip(file.exported_at)
+        .and_then(|(now, exported_at)| now.checked_sub(Duration::from_secs(exported_at)))
+        .unwrap_or_default();
+    Ok((state, elapsed))
+}
+
+fn s2_state_to_file(state: &PersistedS2State) -> S2StateFile {
+    S2StateFile {
+        spans: state
+            .spans
+            .iter()
+            .map(|(node_id, span)| SpanFile {
+                node_id: (*node_id).into(),
+                security_class: span.security_class.into(),
+                state: hex::encode(span.state),
+            })
+            .collect(),
+        peer_mpans: state
+            .peer_mpans
+            .iter()
+            .flat_map(|(node_id, mpans)| {
+                mpans.iter().map(move |(group_id, mpan)| PeerMpanFile {
+                    node_id: (*node_id).into(),
+                    group_id: *group_id,
+                    state: hex::encode(mpan),
+                })
+            })
+            .collect(),
+    }
+}
+
+fn s2_state_from_file(file: &S2StateFile) -> Result<PersistedS2State, NetworkFileError> {
+    let mut state = PersistedS2State::default();
+    for span in file.spans.iter() {
+        state.spans.insert(
+            NodeId::new(span.node_id),
+            PersistedSpan {
+                security_class: span.security_class.into(),
+                state: decode_hex(&span.state)?
+                    .try_into()
+                    .map_err(|_| malformed(format!("invalid SPAN of node {}", span.node_id)))?,
+            },
+        );
+    }
+    for mpan in file.peer_mpans.iter() {
+        let mpan_state: [u8; S2_MPAN_STATE_SIZE] = decode_hex(&mpan.state)?
+            .try_into()
+            .map_err(|_| malformed(format!("invalid MPAN of node {}", mpan.node_id)))?;
+        state
+            .peer_mpans
+            .entry(NodeId::new(mpan.node_id))
+            .or_default()
+            .insert(mpan.group_id, MpanState::from(mpan_state));
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Schedule;
+    use crate::serial_api::mock::{
+        MockController, mock_driver, mock_protocol_data, run_with_mock_controller,
+    };
+    use zwave_cc::commandclass::{BinarySwitchCCGet, BinarySwitchCCValues};
+    use zwave_cc::prelude::*;
+    use zwave_core::security::S2_SPAN_STATE_SIZE;
+
+    #[test]
+    fn test_network_file_round_trip() {
+        let node_id = NodeId::new(5u8);
+        let value_id = EndpointValueId::new(
+            node_id,
+            EndpointIndex::Root,
+            BinarySwitchCCValues::current_value().id,
+        );
+
+        let before = mock_driver().driver;
+        before.storage.nodes().update(|nodes| {
+            let mut node = NodeStorage::new(mock_protocol_data(true));
+            node.interview_stage = InterviewStage::Done;
+            node.user_metadata.name = Some("Kitchen light".to_string());
+            node.neighbors = Some(vec![NodeId::new(1u8), NodeId::new(6u8)]);
+            node.optimistic_updates = Some(OptimisticUpdates::AfterVerification);
+            node.encryption_policy
+                .ccs
+                .insert(CommandClasses::DoorLock, EncryptionPolicy::Required);
+            node.endpoint_cc_inheritance
+                .overrides
+                .insert(CommandClasses::Supervision, false);
+            node.endpoints
+                .get_mut(&EndpointIndex::Root)
+                .unwrap()
+                .cc_info
+                .insert(
+                    CommandClasses::BinarySwitch,
+                    CommandClassInfo {
+                        supported: true,
+                        controlled: false,
+                        secure: false,
+                        version: 1,
+                    },
+                );
+            nodes.insert(node_id, node);
+        });
+        before
+            .storage
+            .value_cache()
+            .update(|cache| cache.insert(value_id, CacheValue::BinaryReport(BinaryReport::On)));
+        let s2_state = PersistedS2State {
+            spans: [(
+                node_id,
+                PersistedSpan {
+                    security_class: SecurityClass::S2Authenticated,
+                    state: [7; S2_SPAN_STATE_SIZE],
+                },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        before.restore_s2_state(&s2_state);
+        before.schedule_command(
+            CC::from(BinarySwitchCCGet {}).with_destination(node_id.into()),
+            Schedule::After(Duration::from_secs(60)),
+        );
+
+        let keys = SecurityKeys::builder()
+            .s0_legacy(NetworkKey::new(&[1; 16]))
+            .s2_authenticated(NetworkKey::new(&[2; 16]))
+            .build();
+        let password = NetworkKey::new(&[3; 16]);
+        let json = before
+            .network_file(
+                &NetworkExportOptions::builder()
+                    .security_keys(keys)
+                    .encryption_key(password.clone())
+                    .build(),
+            )
+            .unwrap()
+            .to_json()
+            .unwrap();
+        // The keys must not be readable without the password
+        assert!(!json.contains(&hex::encode([1u8; 16])));
+        let file = NetworkFile::from_json(&json).unwrap();
+
+        let controller = MockController::new();
+        let (imported, node, value, value_ids, state) =
+            run_with_mock_controller(&controller, |after| async move {
+                assert!(matches!(
+                    after.restore_network_file(&file, &NetworkImportOptions::default()),
+                    Err(NetworkFileError::MissingEncryptionKey)
+                ));
+                assert!(after.storage.nodes().inspect(|nodes| nodes.is_empty()));
+
+                // Nodes that are not in the file are removed, including their values
+                let stale_node_id = NodeId::new(7u8);
+                let stale_value_id = EndpointValueId::new(
+                    stale_node_id,
+                    EndpointIndex::Root,
+                    BinarySwitchCCValues::current_value().id,
+                );
+                after.storage.nodes().update(|nodes| {
+                    nodes.insert(stale_node_id, NodeStorage::new(mock_protocol_data(true)))
+                });
+                after.storage.value_cache().update(|cache| {
+                    cache.insert(stale_value_id, CacheValue::BinaryReport(BinaryReport::Off))
+                });
+
+                let imported = after
+                    .restore_network_file(
+                        &file,
+                        &NetworkImportOptions::builder()
+                            .encryption_key(password)
+                            .build(),
+                    )
+                    .unwrap();
+                let node = after.storage.nodes().inspect(|nodes| {
+                    let node = &nodes[&node_id];
+                    (
+                        node.interview_stage,
+                        node.protocol_data.clone(),
+                        node.user_metadata.name.clone(),
+                        node.neighbors.clone(),
+                        node.optimistic_updates,
+                        node.encryption_policy.for_cc(CommandClasses::DoorLock),
+                        node.endpoint_cc_inheritance
+                            .inherits(CommandClasses::Supervision),
+                    )
+                });
+                assert!(
+                    !after
+                        .storage
+                        .nodes()
+                        .inspect(|nodes| nodes.contains_key(&stale_node_id))
+                );
+                let value = after
+                    .storage
+                    .value_cache()
+                    .inspect(|cache| cache.get(&value_id).cloned());
+                assert!(
+                    !after
+                        .storage
+                        .value_cache()
+                        .inspect(|cache| cache.contains_key(&stale_value_id))
+                );
+                (
+                    imported,
+                    node,
+                    value,
+                    after.defined_value_ids(node_id),
+                    after.export_state(),
+                )
+            });
+
+        assert_eq!((imported.nodes, imported.values), (1, 1));
+        let keys = imported.security_keys.unwrap();
+        assert_eq!(keys.s0_legacy, Some(NetworkKey::new(&[1; 16])));
+        assert_eq!(keys.s2_authenticated, Some(NetworkKey::new(&[2; 16])));
+        assert_eq!(keys.s2_unauthenticated, None);
+
+        assert_eq!(
+            node,
+            (
+                InterviewStage::Done,
+                mock_protocol_data(true),
+                Some("Kitchen light".to_string()),
+                Some(vec![NodeId::new(1u8), NodeId::new(6u8)]),
+                Some(OptimisticUpdates::AfterVerification),
+                EncryptionPolicy::Required,
+                false,
+            )
+        );
+        assert_eq!(value, Some(CacheValue::BinaryReport(BinaryReport::On)));
+        // The values are known without interviewing the node again
+        assert!(value_ids.contains(&value_id));
+        // The driver can continue where the other host left off
+        assert_eq!(state.s2_state, s2_state);
+        assert_eq!(state.scheduled_commands.len(), 1);
+    }
+
+    #[test]
+    fn test_export_replaces_the_file() {
+        let driver = mock_driver().driver;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("network.json");
+        std::fs::write(&path, "outdated").unwrap();
+
+        driver
+            .export_network(&path, &NetworkExportOptions::default())
+            .unwrap();
+        assert!(NetworkFile::from_json(&std::fs::read_to_string(&path).unwrap()).is_ok());
+        // No temporary files are left behind
+        let entries: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("network.json")]);
+    }
+
+    #[test]
+    fn test_version_1_nodes_get_the_default_settings() {
+        let before = mock_driver().driver;
+        before.storage.nodes().update(|nodes| {
+            nodes.insert(NodeId::new(5u8), NodeStorage::new(mock_protocol_data(true)))
+        });
+        let mut json: serde_json::Value = serde_json::from_str(
+            &before
+                .network_file(&NetworkExportOptions::default())
+                .unwrap()
+                .to_json()
+                .unwrap(),
+        )
+        .unwrap();
+        json["format_version"] = 1.into();
+        let node = json["nodes"][0].as_object_mut().unwrap();
+        for field in [
+            "neighbors",
+            "link_quality",
+            "optimistic_updates",
+            "wake_up_refresh_policy",
+            "encryption_policy",
+            "endpoint_cc_inheritance",
+        ] {
+            node.remove(field).unwrap();
+        }
+        let file = NetworkFile::from_json(&json.to_string()).unwrap();
+
+        let after = mock_driver().driver;
+        after
+            .restore_network_file(&file, &NetworkImportOptions::default())
+            .unwrap();
+        after.storage.nodes().inspect(|nodes| {
+            let node = &nodes[&NodeId::new(5u8)];
+            assert_eq!(node.neighbors, None);
+            assert_eq!(node.optimistic_updates, None);
+            assert_eq!(node.encryption_policy, NodeEncryptionPolicy::default());
+            assert_eq!(
+                node.endpoint_cc_inheritance,
+                EndpointCCInheritance::default()
+            );
+        });
+    }
+
+    #[test]
+    fn test_newer_versions_are_rejected() {
+        let driver = mock_driver().driver;
+        let mut file = driver
+            .network_file(&NetworkExportOptions::default())
+            .unwrap();
+        file.format_version = NETWORK_FILE_VERSION + 1;
+        assert!(matches!(
+            driver.restore_network_file(&file, &NetworkImportOptions::default()),
+            Err(NetworkFileError::UnsupportedVersion(_))
+        ));
+    }
+}
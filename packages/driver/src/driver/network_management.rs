@@ -0,0 +1,837 @@
+use super::{
+    ControllerCommandError, ControllerCommandResult, Driver, ReplicationGroup, awaited::AwaitedRef,
+    expect_controller_command_result,
+};
+use crate::{CancellableExt, ExecutableCommand, LinkQualityOptions};
+use crate::error::Error;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_serial::command::{
+    AddNodeStatus, AddNodeToNetworkCallback, AddNodeToNetworkRequest, AddNodeType, Command,
+    LearnModeIntent, LearnModeStatus, RemoveNodeFromNetworkCallback, RemoveNodeFromNetworkRequest,
+    RemoveNodeStatus, RemoveNodeType, SetLearnModeCallback, SetLearnModeRequest,
+};
+
+/// How long the controller waits for a node to be found by default
+const DEFAULT_NODE_FOUND_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long each step of the inclusion or exclusion protocol may take by default after a node
+/// was found, as recommended by the Serial API specification
+const DEFAULT_PROTOCOL_TIMEOUT: Duration = Duration::from_secs(76);
+
+/// Whether the controller is currently including or excluding a node, or is being included
+/// into or excluded from another network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InclusionState {
+    #[default]
+    Idle,
+    Including,
+    Excluding,
+    LearnMode,
+}
+
+#[derive(TypedBuilder, Clone)]
+pub struct InclusionOptions {
+    /// Which kind of nodes may be included. Default: any node
+    #[builder(default = AddNodeType::Any)]
+    pub node_type: AddNodeType,
+    /// How long to wait for a node to be found before the inclusion is stopped. Default: 60 s
+    #[builder(default = DEFAULT_NODE_FOUND_TIMEOUT)]
+    pub timeout: Duration,
+    /// How long to wait for each step after a node was found before the inclusion is considered
+    /// stuck and is stopped. Default: 76 s
+    #[builder(default = DEFAULT_PROTOCOL_TIMEOUT)]
+    pub protocol_timeout: Duration,
+    /// Whether the security bootstrapping and the interview of the new node are started
+    /// automatically after it was included. Default: `true`
+    #[builder(default = true)]
+    pub auto_interview: bool,
+    /// The groups that are transferred to a new secondary controller. Default: none
+    #[builder(default, setter(into))]
+    pub replication_groups: Vec<ReplicationGroup>,
+    /// If set, the link quality of the new node is checked before it is interviewed.
+    /// Default: no check
+    #[builder(default, setter(strip_option))]
+    pub link_quality_check: Option<LinkQualityOptions>,
+}
+
+impl Default for InclusionOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+#[derive(TypedBuilder, Clone)]
+pub struct ExclusionOptions {
+    /// Which kind of nodes may be excluded. Default: any node
+    #[builder(default = RemoveNodeType::Any)]
+    pub node_type: RemoveNodeType,
+    /// How long to wait for a node to be found before the exclusion is stopped. Default: 60 s
+    #[builder(default = DEFAULT_NODE_FOUND_TIMEOUT)]
+    pub timeout: Duration,
+    /// How long to wait for each step after a node was found before the exclusion is considered
+    /// stuck and is stopped. Default: 76 s
+    #[builder(default = DEFAULT_PROTOCOL_TIMEOUT)]
+    pub protocol_timeout: Duration,
+}
+
+impl Default for ExclusionOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+#[derive(TypedBuilder, Clone)]
+pub struct LearnModeOptions {
+    /// How the controller joins or leaves the other network. Default: direct
+    #[builder(default = LearnModeIntent::Direct)]
+    pub intent: LearnModeIntent,
+    /// How long to wait for another controller to start the inclusion or exclusion before
+    /// the learn mode is stopped. Default: 60 s
+    #[builder(default = DEFAULT_NODE_FOUND_TIMEOUT)]
+    pub timeout: Duration,
+    /// How long to wait for the other controller to complete the inclusion or exclusion
+    /// after it was started. Default: 76 s
+    #[builder(default = DEFAULT_PROTOCOL_TIMEOUT)]
+    pub protocol_timeout: Duration,
+}
+
+impl Default for LearnModeOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// How the network of the controller changed during learn mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LearnModeResult {
+    /// The controller was included into another network and was assigned the given node ID
+    Joined { own_node_id: NodeId },
+    /// The controller was excluded from its network and now has a network of its own
+    Left,
+}
+
+impl Driver {
+    /// Returns whether the controller is currently including or excluding a node
+    pub fn inclusion_state(&self) -> InclusionState {
+        self.storage.inclusion_state().get()
+    }
+
+    /// Puts the controller into inclusion mode and waits for a node to be added.
+    /// Returns the ID of the new node, or `None` if no node was found in time.
+    ///
+    /// The inclusion is always stopped afterwards, so the controller is idle again when
+    /// this returns, even if the inclusion failed, the controller got stuck or the inclusion
+    /// was cancelled using [`CancellableExt::cancellable`](crate::CancellableExt::cancellable).
+    pub async fn include_node(
+        &self,
+        options: &InclusionOptions,
+    ) -> ControllerCommandResult<Option<NodeId>> {
+        let included = self.include_node_with_info(options).await?;
+        Ok(included.map(|(node_id, _)| node_id))
+    }
+
+    /// Like [`include_node`](Self::include_node), but also returns the node information
+    /// the new node sent during the inclusion
+    pub(crate) async fn include_node_with_info(
+        &self,
+        options: &InclusionOptions,
+    ) -> ControllerCommandResult<Option<(NodeId, Option<NodeInformationApplicationData>)>> {
+        let guard = NetworkManagementGuard::begin(
+            self,
+            InclusionState::Including,
+            AddNodeToNetworkRequest::stop(),
+        )?;
+        let result = self.include_node_internal(options).await;
+        guard.stop().await;
+        result
+    }
+
+    async fn include_node_internal(
+        &self,
+        options: &InclusionOptions,
+    ) -> ControllerCommandResult<Option<(NodeId, Option<NodeInformationApplicationData>)>> {
+        // The status updates can arrive in quick succession,
+        // so we need to register for all of them before starting the inclusion
+        let [node_found, adding, protocol_done] = [
+            options.timeout,
+            options.protocol_timeout,
+            options.protocol_timeout,
+        ]
+        .map(|timeout| {
+            self.register_awaited_command(
+                Box::new(|cmd| matches!(cmd, Command::AddNodeToNetworkCallback(_))),
+                Some(timeout),
+            )
+        });
+
+        self.controller_log().info(|| "starting inclusion...");
+        let request = AddNodeToNetworkRequest::builder()
+            .add_node_type(options.node_type)
+            .build();
+        let response = self.exec_controller_command(request, None).await;
+        expect_controller_command_result!(response, AddNodeToNetworkCallback);
+
+        let inclusion_failed = |message: &'static str| {
+            self.controller_log().warn(|| message);
+            Err(ControllerCommandError::InclusionFailed)
+        };
+
+        match next_add_node_status(node_found).await? {
+            Some(status) if status.status == AddNodeStatus::NodeFound => {}
+            Some(_) => return inclusion_failed("inclusion failed"),
+            None => {
+                self.controller_log()
+                    .info(|| "no node was found in time, stopping inclusion...");
+                return Ok(None);
+            }
+        }
+
+        // From here on, the controller has to make progress. If it does not, the protocol is stuck
+        let (node_id, node_info, is_controller) = match next_add_node_status(adding).await? {
+            Some(status)
+                if matches!(
+                    status.status,
+                    AddNodeStatus::AddingEndNode | AddNodeStatus::AddingController
+                ) =>
+            {
+                let is_controller = status.status == AddNodeStatus::AddingController;
+                (status.node_id, status.node_info, is_controller)
+            }
+            Some(_) => return inclusion_failed("inclusion failed"),
+            None => return inclusion_failed("the inclusion did not continue in time"),
+        };
+
+        match next_add_node_status(protocol_done).await? {
+            Some(status) if status.status == AddNodeStatus::ProtocolDone => {}
+            Some(_) => return inclusion_failed("inclusion failed"),
+            None => return inclusion_failed("the inclusion did not complete in time"),
+        }
+
+        // The node table was replicated by the protocol, but the groups must be transferred
+        // before the inclusion is stopped
+        if is_controller && !options.replication_groups.is_empty() {
+            if let Err(e) = self
+                .replicate_groups(node_id, &options.replication_groups)
+                .await
+            {
+                self.controller_log().warn(|| {
+                    format!("failed to transfer the groups to node {}: {}", node_id, e)
+                });
+            }
+        }
+
+        self.controller_log()
+            .info(|| format!("node {} was included", node_id));
+        Ok(Some((node_id, node_info)))
+    }
+
+    /// Puts the controller into exclusion mode and waits for a node to be removed.
+    /// Returns the ID of the removed node, or `None` if no node was found in time.
+    ///
+    /// The exclusion is always stopped afterwards, so the controller is idle again when
+    /// this returns, even if the exclusion failed or the controller got stuck.
+    pub async fn exclude_node(
+        &self,
+        options: &ExclusionOptions,
+    ) -> ControllerCommandResult<Option<NodeId>> {
+        let guard = NetworkManagementGuard::begin(
+            self,
+            InclusionState::Excluding,
+            RemoveNodeFromNetworkRequest::stop(),
+        )?;
+        let result = self.exclude_node_internal(options).await;
+        guard.stop().await;
+        result
+    }
+
+    async fn exclude_node_internal(
+        &self,
+        options: &ExclusionOptions,
+    ) -> ControllerCommandResult<Option<NodeId>> {
+        let [node_found, removing, done] = [
+            options.timeout,
+            options.protocol_timeout,
+            options.protocol_timeout,
+        ]
+        .map(|timeout| {
+            self.register_awaited_command(
+                Box::new(|cmd| matches!(cmd, Command::RemoveNodeFromNetworkCallback(_))),
+                Some(timeout),
+            )
+        });
+
+        self.controller_log().info(|| "starting exclusion...");
+        let request = RemoveNodeFromNetworkRequest::builder()
+            .remove_node_type(options.node_type)
+            .build();
+        let response = self.exec_controller_command(request, None).await;
+        expect_controller_command_result!(response, RemoveNodeFromNetworkCallback);
+
+        let exclusion_failed = |message: &'static str| {
+            self.controller_log().warn(|| message);
+            Err(ControllerCommandError::ExclusionFailed)
+        };
+
+        match next_remove_node_status(node_found).await? {
+            Some(status) if status.status == RemoveNodeStatus::NodeFound => {}
+            Some(_) => return exclusion_failed("exclusion failed"),
+            None => {
+                self.controller_log()
+                    .info(|| "no node was found in time, stopping exclusion...");
+                return Ok(None);
+            }
+        }
+
+        // From here on, the controller has to make progress. If it does not, the protocol is stuck
+        let node_id = match next_remove_node_status(removing).await? {
+            Some(status)
+                if matches!(
+                    status.status,
+                    RemoveNodeStatus::RemovingEndNode | RemoveNodeStatus::RemovingController
+                ) =>
+            {
+                status.node_id
+            }
+            Some(_) => return exclusion_failed("exclusion failed"),
+            None => return exclusion_failed("the exclusion did not continue in time"),
+        };
+
+        match next_remove_node_status(done).await? {
+            Some(status) if status.status == RemoveNodeStatus::Done => {}
+            Some(_) => return exclusion_failed("exclusion failed"),
+            None => return exclusion_failed("the exclusion did not complete in time"),
+        }
+
+        self.storage.nodes().update(|nodes| nodes.remove(&node_id));
+        self.controller_log()
+            .info(|| format!("node {} was excluded", node_id));
+        Ok(Some(node_id))
+    }
+}
+
+impl Driver {
+    /// Puts the controller into learn mode, so another controller can include it into its network
+    /// or exclude it from its current one. Returns how the network changed, or `None` if nothing
+    /// happened in time or the learn mode was stopped using
+    /// [`stop_learn_mode`](Self::stop_learn_mode).
+    ///
+    /// Like the inclusion, the learn mode is always stopped afterwards. When the network changed,
+    /// the controller must be interviewed again, which
+    /// [`Controller::begin_learn_mode`](crate::Controller::begin_learn_mode) takes care of.
+    pub async fn begin_learn_mode(
+        &self,
+        options: &LearnModeOptions,
+    ) -> ControllerCommandResult<Option<LearnModeResult>> {
+        let guard = NetworkManagementGuard::begin(
+            self,
+            InclusionState::LearnMode,
+            SetLearnModeRequest::stop(),
+        )?;
+        self.storage.replicated_groups().set(BTreeMap::new());
+        let (learn_mode, handle) = self.learn_mode_internal(options).cancellable();
+        self.storage.learn_mode_handle().set(Some(handle));
+        let result = learn_mode.await;
+        self.storage.learn_mode_handle().set(None);
+        guard.stop().await;
+
+        match result {
+            Err(ControllerCommandError::Cancelled) => {
+                self.controller_log().info(|| "learn mode was stopped");
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+
+    /// Stops the learn mode that was started with [`begin_learn_mode`](Self::begin_learn_mode).
+    /// Returns whether the controller was in learn mode.
+    pub fn stop_learn_mode(&self) -> bool {
+        match self.storage.learn_mode_handle().replace(None) {
+            Some(handle) => {
+                handle.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn learn_mode_internal(
+        &self,
+        options: &LearnModeOptions,
+    ) -> ControllerCommandResult<Option<LearnModeResult>> {
+        let [started, done] = [options.timeout, options.protocol_timeout].map(|timeout| {
+            self.register_awaited_command(
+                Box::new(|cmd| matches!(cmd, Command::SetLearnModeCallback(_))),
+                Some(timeout),
+            )
+        });
+
+        self.controller_log().info(|| "starting learn mode...");
+        let request = SetLearnModeRequest::builder()
+            .intent(options.intent)
+            .build();
+        let response = self.exec_controller_command(request, None).await;
+        expect_controller_command_result!(response, SetLearnModeResponse);
+
+        let learn_mode_failed = |message: &'static str| {
+            self.controller_log().warn(|| message);
+            Err(ControllerCommandError::LearnModeFailed)
+        };
+
+        match next_learn_mode_status(started).await? {
+            Some(status) if status.status == LearnModeStatus::Started => {}
+            Some(_) => return learn_mode_failed("learn mode failed"),
+            None => {
+                self.controller_log()
+                    .info(|| "no other controller was found in time, stopping learn mode...");
+                return Ok(None);
+            }
+        }
+
+        let assigned_node_id = match next_learn_mode_status(done).await? {
+            Some(status) if status.status == LearnModeStatus::Done => status.assigned_node_id,
+            Some(_) => return learn_mode_failed("learn mode failed"),
+            None => return learn_mode_failed("the learn mode did not complete in time"),
+        };
+
+        if assigned_node_id == NodeId::unspecified() {
+            self.controller_log()
+                .info(|| "the controller was excluded from its network");
+            Ok(Some(LearnModeResult::Left))
+        } else {
+            self.controller_log().info(|| {
+                format!(
+                    "the controller joined another network as node {}",
+                    assigned_node_id
+                )
+            });
+            Ok(Some(LearnModeResult::Joined {
+                own_node_id: assigned_node_id,
+            }))
+        }
+    }
+
+    /// Forgets everything that is known about the nodes of the previous network
+    pub(crate) fn forget_network(&self) {
+        self.storage.nodes().set(BTreeMap::new());
+        self.storage.value_cache().update(|cache| cache.clear());
+        self.storage
+            .value_timestamps()
+            .update(|timestamps| timestamps.clear());
+        self.storage.pending_values().update(|pending| pending.clear());
+        self.storage.value_metadata().update(|metadata| metadata.clear());
+        self.storage.node_infos().update(|node_infos| node_infos.clear());
+    }
+}
+
+/// Waits for the next status update of the inclusion, returning `None` if none was received in time
+async fn next_add_node_status(
+    awaited: AwaitedRef<Command>,
+) -> ControllerCommandResult<Option<AddNodeToNetworkCallback>> {
+    match awaited.try_await().await {
+        Ok(Command::AddNodeToNetworkCallback(status)) => Ok(Some(status)),
+        Ok(_) => Err(ControllerCommandError::Unexpected(
+            "expected AddNodeToNetworkCallback".to_string(),
+        )),
+        Err(Error::Timeout) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Waits for the next status update of the exclusion, returning `None` if none was received in time
+async fn next_remove_node_status(
+    awaited: AwaitedRef<Command>,
+) -> ControllerCommandResult<Option<RemoveNodeFromNetworkCallback>> {
+    match awaited.try_await().await {
+        Ok(Command::RemoveNodeFromNetworkCallback(status)) => Ok(Some(status)),
+        Ok(_) => Err(ControllerCommandError::Unexpected(
+            "expected RemoveNodeFromNetworkCallback".to_string(),
+        )),
+        Err(Error::Timeout) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Waits for the next status update of the learn mode, returning `None` if none was received in
+/// time
+async fn next_learn_mode_status(
+    awaited: AwaitedRef<Command>,
+) -> ControllerCommandResult<Option<SetLearnModeCallback>> {
+    match awaited.try_await().await {
+        Ok(Command::SetLearnModeCallback(status)) => Ok(Some(status)),
+        Ok(_) => Err(ControllerCommandError::Unexpected(
+            "expected SetLearnModeCallback".to_string(),
+        )),
+        Err(Error::Timeout) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Marks the controller as busy while a node is included or excluded, and makes sure that
+/// the process is stopped and the controller returns to idle afterwards. This also happens
+/// when the future driving the process is dropped before it completes.
+struct NetworkManagementGuard<'a, C: ExecutableCommand + 'static> {
+    driver: &'a Driver,
+    stop_command: Option<C>,
+}
+
+impl<'a, C: ExecutableCommand + 'static> NetworkManagementGuard<'a, C> {
+    fn begin(
+        driver: &'a Driver,
+        state: InclusionState,
+        stop_command: C,
+    ) -> ControllerCommandResult<Self> {
+        let busy = driver.storage.inclusion_state().update(|current| {
+            if *current != InclusionState::Idle {
+                return true;
+            }
+            *current = state;
+            false
+        });
+        if busy {
+            return Err(ControllerCommandError::InclusionInProgress);
+        }
+
+        Ok(Self {
+            driver,
+            stop_command: Some(stop_command),
+        })
+    }
+
+    async fn stop(mut self) {
+        let Some(stop_command) = self.stop_command.take() else {
+            return;
+        };
+        // The stop command has no callback, so it also succeeds if the controller
+        // already finished the process on its own
+        if let Err(e) = self
+            .driver
+            .exec_controller_command(stop_command, None)
+            .await
+        {
+            self.driver
+                .controller_log()
+                .warn(|| format!("failed to stop inclusion or exclusion: {}", e));
+        }
+    }
+}
+
+impl<C: ExecutableCommand + 'static> Drop for NetworkManagementGuard<'_, C> {
+    fn drop(&mut self) {
+        if let Some(stop_command) = self.stop_command.take() {
+            self.driver
+                .serial_api
+                .dispatch_serial_api_command(stop_command, false);
+        }
+        self.driver
+            .storage
+            .inclusion_state()
+            .set(InclusionState::Idle);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::CancellableExt;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use zwave_serial::command_raw::CommandRaw;
+
+    const STOP: u8 = 0x05;
+
+    /// Answers each start request with the given status updates, and stop requests with nothing
+    fn mock_controller(
+        function_type: FunctionType,
+        statuses: &'static [&'static [u8]],
+    ) -> MockController {
+        MockController::new().on(function_type, move |_, request| {
+            if request.payload[0] & 0x0f == STOP {
+                return vec![];
+            }
+            let callback_id = request.payload[1];
+            statuses
+                .iter()
+                .map(|status| {
+                    let mut payload = vec![callback_id];
+                    payload.extend_from_slice(status);
+                    MockController::raw(CommandType::Request, function_type, payload)
+                })
+                .collect()
+        })
+    }
+
+    fn stop_requests(controller: &MockController, function_type: FunctionType) -> Vec<CommandRaw> {
+        controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == function_type && cmd.payload[0] & 0x0f == STOP)
+            .collect()
+    }
+
+    fn short_inclusion_options() -> InclusionOptions {
+        InclusionOptions::builder()
+            .timeout(Duration::from_millis(50))
+            .protocol_timeout(Duration::from_millis(50))
+            .build()
+    }
+
+    #[test]
+    fn test_inclusion_succeeds() {
+        let controller = mock_controller(
+            FunctionType::AddNodeToNetwork,
+            &[
+                &[0x01, 0x00, 0x00],
+                &[0x02, 0x00, 0x00],
+                // Adding end node 5 with a Binary Switch
+                &[0x03, 0x05, 0x04, 0x04, 0x10, 0x01, 0x25],
+                &[0x05, 0x05, 0x00],
+            ],
+        );
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = driver
+                .include_node(&short_inclusion_options())
+                .await
+                .unwrap();
+            assert_eq!(node_id, Some(NodeId::new(5u8)));
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+        });
+
+        let stop = stop_requests(&controller, FunctionType::AddNodeToNetwork);
+        assert_eq!(stop.len(), 1);
+        assert_eq!(&stop[0].payload[..], &[0xc5, 0x00]);
+    }
+
+    #[test]
+    fn test_inclusion_is_stopped_when_no_node_is_found() {
+        let controller = mock_controller(FunctionType::AddNodeToNetwork, &[&[0x01, 0x00, 0x00]]);
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = driver
+                .include_node(&short_inclusion_options())
+                .await
+                .unwrap();
+            assert_eq!(node_id, None);
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+        });
+
+        assert_eq!(
+            stop_requests(&controller, FunctionType::AddNodeToNetwork).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_stuck_inclusion_is_stopped() {
+        // The controller finds a node, but the protocol never continues
+        let controller = mock_controller(
+            FunctionType::AddNodeToNetwork,
+            &[&[0x01, 0x00, 0x00], &[0x02, 0x00, 0x00]],
+        );
+        run_with_mock_controller(&controller, |driver| async move {
+            let result = driver.include_node(&short_inclusion_options()).await;
+            assert!(matches!(
+                result,
+                Err(ControllerCommandError::InclusionFailed)
+            ));
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+
+            // The controller is idle again, so the next attempt is not rejected
+            let result = driver.include_node(&short_inclusion_options()).await;
+            assert!(matches!(
+                result,
+                Err(ControllerCommandError::InclusionFailed)
+            ));
+        });
+
+        assert_eq!(
+            stop_requests(&controller, FunctionType::AddNodeToNetwork).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_failed_inclusion_is_stopped() {
+        let controller = mock_controller(
+            FunctionType::AddNodeToNetwork,
+            &[
+                &[0x01, 0x00, 0x00],
+                &[0x02, 0x00, 0x00],
+                &[0x07, 0x00, 0x00],
+            ],
+        );
+        run_with_mock_controller(&controller, |driver| async move {
+            let result = driver.include_node(&short_inclusion_options()).await;
+            assert!(matches!(
+                result,
+                Err(ControllerCommandError::InclusionFailed)
+            ));
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+        });
+
+        assert_eq!(
+            stop_requests(&controller, FunctionType::AddNodeToNetwork).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_inclusion_is_rejected_while_busy() {
+        let controller = mock_controller(FunctionType::AddNodeToNetwork, &[]);
+        run_with_mock_controller(&controller, |driver| async move {
+            driver
+                .storage
+                .inclusion_state()
+                .set(InclusionState::Excluding);
+            let result = driver.include_node(&short_inclusion_options()).await;
+            assert!(matches!(
+                result,
+                Err(ControllerCommandError::InclusionInProgress)
+            ));
+            // The active exclusion is not affected
+            assert_eq!(driver.inclusion_state(), InclusionState::Excluding);
+        });
+
+        assert!(controller.received().is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_inclusion_is_stopped() {
+        let controller = mock_controller(FunctionType::AddNodeToNetwork, &[&[0x01, 0x00, 0x00]]);
+        run_with_mock_controller(&controller, |driver| async move {
+            let options = InclusionOptions::default();
+            let mut inclusion = Box::pin(driver.include_node(&options));
+            assert!(futures::poll!(inclusion.as_mut()).is_pending());
+            drop(inclusion);
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+
+            // Give the serial API a chance to send the stop command
+            let _ = driver
+                .register_awaited_command(Box::new(|_| false), Some(Duration::from_millis(50)))
+                .try_await()
+                .await;
+        });
+
+        assert_eq!(
+            stop_requests(&controller, FunctionType::AddNodeToNetwork).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_inclusion_can_be_cancelled() {
+        let controller = mock_controller(FunctionType::AddNodeToNetwork, &[&[0x01, 0x00, 0x00]]);
+        run_with_mock_controller(&controller, |driver| async move {
+            let options = InclusionOptions::default();
+            let (inclusion, handle) = driver.include_node(&options).cancellable();
+            let cancel = async {
+                zwave_pal::time::Timer::after(Duration::from_millis(20)).await;
+                handle.cancel();
+            };
+            let (result, ()) = futures::join!(inclusion, cancel);
+            assert!(matches!(result, Err(ControllerCommandError::Cancelled)));
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+
+            // Give the serial API a chance to send the stop command
+            let _ = driver
+                .register_awaited_command(Box::new(|_| false), Some(Duration::from_millis(50)))
+                .try_await()
+                .await;
+        });
+
+        assert_eq!(
+            stop_requests(&controller, FunctionType::AddNodeToNetwork).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_exclusion_succeeds() {
+        let controller = mock_controller(
+            FunctionType::RemoveNodeFromNetwork,
+            &[
+                &[0x01, 0x00, 0x00],
+                &[0x02, 0x00, 0x00],
+                &[0x03, 0x05, 0x00],
+                &[0x06, 0x05, 0x00],
+            ],
+        );
+        run_with_mock_controller(&controller, |driver| async move {
+            let options = ExclusionOptions::builder()
+                .timeout(Duration::from_millis(50))
+                .protocol_timeout(Duration::from_millis(50))
+                .build();
+            let node_id = driver.exclude_node(&options).await.unwrap();
+            assert_eq!(node_id, Some(NodeId::new(5u8)));
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+        });
+
+        let stop = stop_requests(&controller, FunctionType::RemoveNodeFromNetwork);
+        assert_eq!(stop.len(), 1);
+        assert_eq!(&stop[0].payload[..], &[0xc5, 0x00]);
+    }
+
+    /// Answers each start of the learn mode with the given status updates
+    fn learn_mode_controller(statuses: &'static [&'static [u8]]) -> MockController {
+        MockController::new().on(FunctionType::SetLearnMode, move |_, request| {
+            let mut ret = vec![MockController::raw(
+                CommandType::Response,
+                FunctionType::SetLearnMode,
+                vec![0x01],
+            )];
+            if request.payload[0] == LearnModeIntent::Stop as u8 {
+                return ret;
+            }
+            let callback_id = request.payload[1];
+            ret.extend(statuses.iter().map(|status| {
+                let mut payload = vec![callback_id];
+                payload.extend_from_slice(status);
+                MockController::raw(CommandType::Request, FunctionType::SetLearnMode, payload)
+            }));
+            ret
+        })
+    }
+
+    #[test]
+    fn test_learn_mode_joins_network() {
+        let controller = learn_mode_controller(&[&[0x01, 0x00, 0x00], &[0x06, 0x07, 0x00]]);
+        run_with_mock_controller(&controller, |driver| async move {
+            let options = LearnModeOptions::builder()
+                .timeout(Duration::from_millis(50))
+                .protocol_timeout(Duration::from_millis(50))
+                .build();
+            let result = driver.begin_learn_mode(&options).await.unwrap();
+            assert_eq!(
+                result,
+                Some(LearnModeResult::Joined {
+                    own_node_id: NodeId::new(7u8)
+                })
+            );
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+        });
+
+        let stop = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.payload[0] == LearnModeIntent::Stop as u8)
+            .count();
+        assert_eq!(stop, 1);
+    }
+
+    #[test]
+    fn test_learn_mode_can_be_stopped() {
+        let controller = learn_mode_controller(&[]);
+        run_with_mock_controller(&controller, |driver| async move {
+            let options = LearnModeOptions::default();
+            let stop = async {
+                zwave_pal::time::Timer::after(Duration::from_millis(20)).await;
+                assert!(driver.stop_learn_mode());
+            };
+            let (result, ()) = futures::join!(driver.begin_learn_mode(&options), stop);
+            assert_eq!(result.unwrap(), None);
+            assert_eq!(driver.inclusion_state(), InclusionState::Idle);
+            assert!(!driver.stop_learn_mode());
+        });
+    }
+}
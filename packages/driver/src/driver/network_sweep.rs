@@ -0,0 +1,227 @@
+use super::{ControllerCommandResult, Driver, DriverEvent, PingResult, SendPriority};
+use crate::ExecNodeCommandOptions;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use futures::stream::{self, StreamExt};
+use typed_builder::TypedBuilder;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Timer;
+
+#[derive(TypedBuilder, Clone)]
+pub struct SweepOptions {
+    /// How many nodes are pinged at the same time. Default: 4
+    #[builder(default = 4)]
+    pub concurrency: usize,
+}
+
+impl Default for SweepOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// The result of a network sweep
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReachabilityReport {
+    pub nodes: BTreeMap<NodeId, PingResult>,
+}
+
+impl ReachabilityReport {
+    /// Returns the IDs of all nodes that did not acknowledge the ping
+    pub fn unreachable_nodes(&self) -> Vec<NodeId> {
+        self.nodes
+            .iter()
+            .filter(|(_, node)| !node.reachable)
+            .map(|(node_id, _)| *node_id)
+            .collect()
+    }
+}
+
+impl Driver {
+    /// Pings all nodes that do not sleep and reports which of them are reachable.
+    /// The status of each node is updated accordingly.
+    pub async fn sweep_network(
+        &self,
+        options: &SweepOptions,
+    ) -> ControllerCommandResult<ReachabilityReport> {
+        let own_node_id = self.serial_api.storage.own_node_id();
+        let node_ids: Vec<NodeId> = self.storage.nodes().inspect(|nodes| {
+            nodes
+                .iter()
+                .filter(|(node_id, node)| {
+                    **node_id != own_node_id
+                        && (node.protocol_data.listening
+                            || node.protocol_data.frequent_listening.is_some())
+                })
+                .map(|(node_id, _)| *node_id)
+                .collect()
+        });
+
+        self.controller_log()
+            .info(|| format!("pinging {} nodes...", node_ids.len()));
+
+        // Sweeps are background traffic and must not delay other commands
+        let ping_options = ExecNodeCommandOptions::builder()
+            .priority(SendPriority::Poll)
+            .build();
+        let ping_options = &ping_options;
+        let results: Vec<_> = stream::iter(node_ids)
+            .map(|node_id| async move {
+                let result = self.ping_node(node_id, Some(ping_options)).await;
+                (node_id, result)
+            })
+            .buffer_unordered(options.concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut report = ReachabilityReport::default();
+        for (node_id, result) in results {
+            report.nodes.insert(node_id, result?);
+        }
+
+        self.controller_log().info(|| {
+            format!(
+                "{} of {} nodes are reachable",
+                report.nodes.len() - report.unreachable_nodes().len(),
+                report.nodes.len()
+            )
+        });
+
+        Ok(report)
+    }
+
+    /// Sweeps the network in the given interval until an error occurs or it is cancelled.
+    /// The report of each sweep is emitted as a [`DriverEvent::NetworkSwept`] event.
+    pub async fn sweep_network_periodically(
+        &self,
+        options: &SweepOptions,
+        interval: Duration,
+    ) -> ControllerCommandResult<()> {
+        loop {
+            let report = self.sweep_network(options).await?;
+            self.emit_event(DriverEvent::NetworkSwept { report });
+            Timer::after(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{
+        MockController, MockDriver, mock_driver, mock_protocol_data, run_with_mock_controller,
+    };
+    use crate::{CancellableExt, ControllerCommandError, NodeStatus, NodeStorage};
+    use futures::FutureExt;
+
+    const DEAD_NODE_ID: u8 = 3;
+
+    fn mock_controller() -> MockController {
+        MockController::new().on(FunctionType::SendData, |_, request| {
+            let node_id = request.payload[0];
+            if node_id == DEAD_NODE_ID {
+                MockController::send_data(request, TransmitStatus::NoAck)
+            } else {
+                MockController::send_data_ok(request)
+            }
+        })
+    }
+
+    #[test]
+    fn test_sweep_network() {
+        let controller = mock_controller();
+        let (report, statuses) = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(2u8), NodeStorage::new(mock_protocol_data(true)));
+                nodes.insert(
+                    NodeId::new(DEAD_NODE_ID),
+                    NodeStorage::new(mock_protocol_data(true)),
+                );
+                // Sleeping nodes are not pinged
+                nodes.insert(
+                    NodeId::new(4u8),
+                    NodeStorage::new(mock_protocol_data(false)),
+                );
+            });
+
+            let options = SweepOptions::builder().concurrency(2).build();
+            let report = driver.sweep_network(&options).await.unwrap();
+            let statuses: Vec<_> = driver
+                .storage
+                .nodes()
+                .inspect(|nodes| nodes.values().map(|node| node.status).collect());
+            (report, statuses)
+        });
+
+        assert_eq!(
+            report.nodes.keys().copied().collect::<Vec<_>>(),
+            vec![NodeId::new(2u8), NodeId::new(DEAD_NODE_ID)]
+        );
+        assert_eq!(report.unreachable_nodes(), vec![NodeId::new(DEAD_NODE_ID)]);
+        assert!(
+            report
+                .nodes
+                .values()
+                .all(|node| node.transmit_report.is_some())
+        );
+        assert_eq!(
+            statuses,
+            vec![NodeStatus::Alive, NodeStatus::Dead, NodeStatus::Unknown]
+        );
+    }
+
+    #[test]
+    fn test_periodic_sweep_can_be_cancelled() {
+        let controller = mock_controller();
+        let result = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(2u8), NodeStorage::new(mock_protocol_data(true)));
+            });
+
+            let options = SweepOptions::default();
+            let (sweep, handle) = driver
+                .sweep_network_periodically(&options, Duration::from_secs(3600))
+                .cancellable();
+            let cancel = async {
+                Timer::after(Duration::from_millis(50)).await;
+                handle.cancel();
+            };
+            futures::join!(sweep, cancel).0
+        });
+
+        assert!(matches!(result, Err(ControllerCommandError::Cancelled)));
+        // The first sweep completed before the sweeps were cancelled
+        assert!(
+            controller
+                .take_events()
+                .iter()
+                .any(|event| matches!(event, DriverEvent::NetworkSwept { .. }))
+        );
+    }
+
+    #[test]
+    fn test_status_changes_are_emitted() {
+        let MockDriver {
+            driver,
+            mut adapter,
+            ..
+        } = mock_driver();
+        driver.storage.nodes().update(|nodes| {
+            nodes.insert(NodeId::new(2u8), NodeStorage::new(mock_protocol_data(true)));
+        });
+
+        driver.set_node_status(NodeId::new(2u8), NodeStatus::Dead);
+        // Setting the same status again does not emit an event
+        driver.set_node_status(NodeId::new(2u8), NodeStatus::Dead);
+
+        assert!(matches!(
+            adapter.event_rx.recv().now_or_never(),
+            Some(Some(DriverEvent::NodeStatusChanged {
+                status: NodeStatus::Dead,
+                ..
+            }))
+        ));
+        assert!(adapter.event_rx.recv().now_or_never().is_none());
+    }
+}
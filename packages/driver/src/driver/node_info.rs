@@ -0,0 +1,217 @@
+use super::{ControllerCommandError, ControllerCommandResult, Driver};
+use crate::DriverEvent;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Timer;
+
+/// How the driver requests the node information, which often fails on the first attempt
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct NodeInfoQueryOptions {
+    /// How often the node information is requested before giving up. Default: 3
+    #[builder(default = 3)]
+    pub attempts: u8,
+    /// How long to wait before the first retry. Default: 500 ms
+    #[builder(default = Duration::from_millis(500))]
+    pub retry_delay: Duration,
+    /// How much longer each retry waits than the previous one. Default: 2
+    #[builder(default = 2)]
+    pub backoff_factor: u32,
+}
+
+impl Default for NodeInfoQueryOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl Driver {
+    /// Changes how the node information is requested during future interviews
+    pub fn set_node_info_query_options(&self, options: NodeInfoQueryOptions) {
+        self.storage.node_info_query_options().set(options);
+    }
+
+    pub fn node_info_query_options(&self) -> NodeInfoQueryOptions {
+        self.storage.node_info_query_options().get()
+    }
+
+    /// Requests the node information, retrying with an increasing delay if that fails.
+    /// The received information is cached. If it differs from the cached one,
+    /// a [`DriverEvent::NodeInfoChanged`] event is emitted.
+    ///
+    /// If the node cannot be queried at all, the cached information is returned instead.
+    pub async fn query_node_info(
+        &self,
+        node_id: &NodeId,
+    ) -> ControllerCommandResult<NodeInformationApplicationData> {
+        let options = self.node_info_query_options();
+        let log = self.node_log(*node_id, EndpointIndex::Root);
+
+        let mut delay = options.retry_delay;
+        let mut attempt = 1;
+        let result = loop {
+            match self.request_node_info(node_id, None).await {
+                Err(ControllerCommandError::NodeIdNotAddressable(node_id)) => {
+                    return Err(ControllerCommandError::NodeIdNotAddressable(node_id));
+                }
+                Err(_) if attempt < options.attempts => {
+                    log.debug(|| {
+                        format!(
+                            "requesting the node info failed, retrying in {} ms...",
+                            delay.as_millis()
+                        )
+                    });
+                    Timer::after(delay).await;
+                    delay = delay.saturating_mul(options.backoff_factor);
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(node_info) => {
+                let changed = self.storage.node_infos().update(|node_infos| {
+                    node_infos.insert(*node_id, node_info.clone()).as_ref() != Some(&node_info)
+                });
+                if changed {
+                    self.emit_event(DriverEvent::NodeInfoChanged {
+                        node_id: *node_id,
+                        node_info: node_info.clone(),
+                    });
+                }
+                Ok(node_info)
+            }
+            Err(e) => match self.cached_node_info(node_id) {
+                Some(node_info) => {
+                    log.warn(|| {
+                        format!(
+                            "requesting the node info failed {} times, using the cached one",
+                            options.attempts
+                        )
+                    });
+                    Ok(node_info)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Returns the node information that was last received from the given node
+    pub fn cached_node_info(&self, node_id: &NodeId) -> Option<NodeInformationApplicationData> {
+        self.storage
+            .node_infos()
+            .inspect(|node_infos| node_infos.get(node_id).cloned())
+    }
+
+    /// Exports the cached node information of all nodes
+    pub(crate) fn export_node_infos(&self) -> BTreeMap<NodeId, NodeInformationApplicationData> {
+        self.storage.node_infos().cloned()
+    }
+
+    /// Restores previously persisted node information
+    pub(crate) fn restore_node_infos(&self, node_infos: BTreeMap<NodeId, NodeInformationApplicationData>) {
+        self.storage.node_infos().set(node_infos);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use zwave_serial::command_raw::CommandRaw;
+
+    /// Answers a node info request with the node info of a binary switch
+    fn node_info_received(node_id: u8) -> Vec<CommandRaw> {
+        let payload = vec![0x84, node_id, 0x04, 0x04, 0x10, 0x01, 0x25];
+        vec![
+            MockController::raw(
+                CommandType::Response,
+                FunctionType::RequestNodeInfo,
+                vec![0x01],
+            ),
+            MockController::raw(
+                CommandType::Request,
+                FunctionType::ApplicationUpdateRequest,
+                payload,
+            ),
+        ]
+    }
+
+    fn node_info_failed() -> Vec<CommandRaw> {
+        vec![
+            MockController::raw(
+                CommandType::Response,
+                FunctionType::RequestNodeInfo,
+                vec![0x01],
+            ),
+            MockController::raw(
+                CommandType::Request,
+                FunctionType::ApplicationUpdateRequest,
+                vec![0x81, 0x00, 0x00],
+            ),
+        ]
+    }
+
+    fn fast_retries() -> NodeInfoQueryOptions {
+        NodeInfoQueryOptions::builder()
+            .retry_delay(Duration::from_millis(1))
+            .build()
+    }
+
+    #[test]
+    fn test_retry() {
+        let controller =
+            MockController::new().on(FunctionType::RequestNodeInfo, |controller, _| {
+                // Only the last attempt succeeds
+                if controller.received().len() < 3 {
+                    node_info_failed()
+                } else {
+                    node_info_received(2)
+                }
+            });
+        let node_info = run_with_mock_controller(&controller, |driver| async move {
+            driver.set_node_info_query_options(fast_retries());
+            driver.query_node_info(&NodeId::new(2u8)).await
+        })
+        .unwrap();
+
+        assert_eq!(controller.received().len(), 3);
+        assert_eq!(
+            node_info.supported_command_classes,
+            vec![CommandClasses::BinarySwitch]
+        );
+        assert!(matches!(
+            &controller.take_events()[..],
+            [DriverEvent::NodeInfoChanged { node_id, .. }] if *node_id == NodeId::new(2u8)
+        ));
+    }
+
+    #[test]
+    fn test_fall_back_to_cached_node_info() {
+        let controller =
+            MockController::new().on(FunctionType::RequestNodeInfo, |controller, _| {
+                // Only the first query succeeds
+                if controller.received().len() == 1 {
+                    node_info_received(2)
+                } else {
+                    node_info_failed()
+                }
+            });
+        let (cached, exported) = run_with_mock_controller(&controller, |driver| async move {
+            driver.set_node_info_query_options(fast_retries());
+            let node_id = NodeId::new(2u8);
+            let received = driver.query_node_info(&node_id).await.unwrap();
+            let cached = driver.query_node_info(&node_id).await.unwrap();
+            assert_eq!(received, cached);
+            (cached, driver.export_node_infos())
+        });
+
+        assert_eq!(controller.received().len(), 4);
+        assert_eq!(exported.get(&NodeId::new(2u8)), Some(&cached));
+        // Falling back to the cached node info is not a change
+        assert_eq!(controller.take_events().len(), 1);
+    }
+}
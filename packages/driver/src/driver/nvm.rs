@@ -0,0 +1,184 @@
+//! Raw access to the external NVM of 500 series controllers. These are low-level building
+//! blocks for tools that inspect specific NVM regions, not a backup mechanism.
+//! Writing is only available with the `nvm-write` feature, since it can brick the controller.
+
+use super::{
+    ControllerCommandError, ControllerCommandResult, Driver, ExecControllerCommandOptions,
+    expect_controller_command_result,
+};
+use bytes::Bytes;
+use zwave_core::log::Loglevel;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_serial::command::{
+    Command, EXT_NVM_MAX_OFFSET, EXT_NVM_MAX_READ_LENGTH, ExtNVMReadLongBufferRequest,
+    GetNVMIdRequest, GetNVMIdResponse,
+};
+#[cfg(feature = "nvm-write")]
+use zwave_serial::command::{CommandBase, EXT_NVM_MAX_WRITE_LENGTH, ExtNVMWriteLongBufferRequest};
+
+/// Ensures that the given range lies within the NVM and can be transferred in one command
+fn check_nvm_range(
+    offset: u32,
+    length: usize,
+    max_length: u16,
+    nvm_size: Option<u32>,
+) -> ControllerCommandResult<()> {
+    if length == 0 || length > max_length as usize {
+        return Err(ControllerCommandError::NotAllowed(format!(
+            "The length must be between 1 and {} bytes, got {}",
+            max_length, length
+        )));
+    }
+    let end = offset as u64 + length as u64;
+    let limit = nvm_size.unwrap_or(EXT_NVM_MAX_OFFSET + 1) as u64;
+    if end > limit {
+        return Err(ControllerCommandError::NotAllowed(format!(
+            "The range {:#08x}..{:#08x} exceeds the NVM size of {} bytes",
+            offset, end, limit
+        )));
+    }
+    Ok(())
+}
+
+impl Driver {
+    /// Queries which external NVM the controller uses and how large it is
+    pub async fn get_nvm_id(
+        &self,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<GetNVMIdResponse> {
+        self.ensure_nvm_function(FunctionType::GetNVMId)?;
+        self.controller_log().info(|| "querying NVM ID...");
+        let response = self
+            .exec_controller_command(GetNVMIdRequest::default(), options)
+            .await;
+
+        let nvm_id = expect_controller_command_result!(response, GetNVMIdResponse);
+
+        if self.controller_log().level() < Loglevel::Debug {
+            self.controller_log().info(|| {
+                LogPayloadText::new("received NVM ID:").with_nested(nvm_id.to_log_payload())
+            });
+        }
+
+        Ok(nvm_id)
+    }
+
+    /// Reads `length` bytes from the external NVM, starting at `offset`.
+    /// The range must lie within the NVM and fit into a single response.
+    pub async fn read_nvm(
+        &self,
+        offset: u32,
+        length: u16,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<Bytes> {
+        self.ensure_nvm_function(FunctionType::ExtNVMReadLongBuffer)?;
+        let nvm_size = self.get_nvm_id(options).await?.memory_size;
+        check_nvm_range(offset, length as usize, EXT_NVM_MAX_READ_LENGTH, nvm_size)?;
+
+        self.controller_log().info(|| {
+            format!(
+                "reading {} bytes from the NVM at offset {:#08x}...",
+                length, offset
+            )
+        });
+        let response = self
+            .exec_controller_command(
+                ExtNVMReadLongBufferRequest::builder()
+                    .offset(offset)
+                    .length(length)
+                    .build(),
+                options,
+            )
+            .await;
+        let response = expect_controller_command_result!(response, ExtNVMReadLongBufferResponse);
+
+        if response.buffer.len() != length as usize {
+            return Err(ControllerCommandError::Unexpected(format!(
+                "expected {} bytes from the NVM, got {}",
+                length,
+                response.buffer.len()
+            )));
+        }
+
+        Ok(response.buffer)
+    }
+
+    /// Writes the given data to the external NVM, starting at `offset`.
+    /// The range must lie within the NVM and fit into a single request.
+    ///
+    /// **Warning:** Writing to the NVM can corrupt the controller's network data or firmware.
+    #[cfg(feature = "nvm-write")]
+    pub async fn write_nvm(
+        &self,
+        offset: u32,
+        data: Bytes,
+        options: Option<&ExecControllerCommandOptions>,
+    ) -> ControllerCommandResult<()> {
+        self.ensure_nvm_function(FunctionType::ExtNVMWriteLongBuffer)?;
+        let nvm_size = self.get_nvm_id(options).await?.memory_size;
+        check_nvm_range(offset, data.len(), EXT_NVM_MAX_WRITE_LENGTH, nvm_size)?;
+
+        self.controller_log().warn(|| {
+            format!(
+                "writing {} bytes to the NVM at offset {:#08x}...",
+                data.len(),
+                offset
+            )
+        });
+        let response = self
+            .exec_controller_command(
+                ExtNVMWriteLongBufferRequest::builder()
+                    .offset(offset)
+                    .buffer(data)
+                    .build(),
+                options,
+            )
+            .await;
+        let response = expect_controller_command_result!(response, ExtNVMWriteLongBufferResponse);
+
+        if !response.is_ok() {
+            return Err(ControllerCommandError::Unsuccessful);
+        }
+        Ok(())
+    }
+
+    /// Fails if the controller is known not to support the given NVM function
+    fn ensure_nvm_function(&self, function_type: FunctionType) -> ControllerCommandResult<()> {
+        let unsupported = self.storage.controller().inspect(|controller| {
+            controller.as_ref().is_some_and(|controller| {
+                controller.inspect(|controller| {
+                    !controller.supported_function_types.contains(&function_type)
+                })
+            })
+        });
+        if unsupported {
+            return Err(ControllerCommandError::Unsupported(format!(
+                "{:?}",
+                function_type
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_nvm_range() {
+        let size = Some(16 * 1024);
+        assert!(check_nvm_range(0, 64, EXT_NVM_MAX_READ_LENGTH, size).is_ok());
+        assert!(check_nvm_range(16 * 1024 - 64, 64, EXT_NVM_MAX_READ_LENGTH, size).is_ok());
+
+        // Empty or too long
+        assert!(check_nvm_range(0, 0, EXT_NVM_MAX_READ_LENGTH, size).is_err());
+        assert!(check_nvm_range(0, 253, EXT_NVM_MAX_READ_LENGTH, size).is_err());
+        // Beyond the end of the NVM
+        assert!(check_nvm_range(16 * 1024 - 63, 64, EXT_NVM_MAX_READ_LENGTH, size).is_err());
+        // Without a known size, the 24-bit address space is the limit
+        assert!(check_nvm_range(0xff_ffc0, 64, EXT_NVM_MAX_READ_LENGTH, None).is_ok());
+        assert!(check_nvm_range(0xff_ffc1, 64, EXT_NVM_MAX_READ_LENGTH, None).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use super::{Driver, DriverEvent, ValueUpdateKind};
+use zwave_cc::commandclass::{BinarySwitchCCValues, MultilevelSwitchCCValues};
+use zwave_cc::prelude::*;
+use zwave_core::cache::CacheValue;
+use zwave_core::prelude::*;
+use zwave_core::value_id::{EndpointValueId, ValueId};
+use zwave_pal::prelude::*;
+
+/// When the values that are changed by a Set command are updated in the value cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OptimisticUpdates {
+    /// As soon as the node acknowledged the Set command. The update is labeled as optimistic,
+    /// and corrected by the report the node sends after the transition.
+    #[default]
+    Immediately,
+    /// Only after the node confirmed through Supervision that it executed the Set command.
+    ///
+    /// Supervision is not supported yet, so this currently behaves like `AfterVerification`.
+    AfterSupervision,
+    /// Only after the node reported the new values when it was queried after the Set command
+    AfterVerification,
+}
+
+/// Determines the values a Set command changes once the node executed it
+fn expected_values(cc: &CC) -> Vec<(ValueId, CacheValue)> {
+    match cc {
+        CC::BinarySwitchCCSet(set) => {
+            let value = CacheValue::BinaryReport(match set.target_value {
+                BinarySet::Off => BinaryReport::Off,
+                BinarySet::On => BinaryReport::On,
+            });
+            let mut ret = vec![(BinarySwitchCCValues::target_value().id, value.clone())];
+            // Binary switches without a transition duration switch instantly
+            if set.duration.is_none_or(is_instant) {
+                ret.push((BinarySwitchCCValues::current_value().id, value));
+            }
+            ret
+        }
+        CC::MultilevelSwitchCCSet(set) => {
+            // Turning the switch on restores its previous level, which is not known here
+            let LevelSet::Level(level) = set.target_value.to_canonical() else {
+                return Vec::new();
+            };
+            let value = CacheValue::LevelReport(LevelReport::Level(level));
+            let mut ret = vec![(MultilevelSwitchCCValues::target_value().id, value.clone())];
+            // Dimmers usually have a default transition, so only an explicit
+            // duration of zero changes the current value right away
+            if set.duration.is_some_and(is_instant) {
+                ret.push((MultilevelSwitchCCValues::current_value().id, value));
+            }
+            ret
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn is_instant(duration: DurationSet) -> bool {
+    duration.to_duration() == Some(core::time::Duration::ZERO)
+}
+
+impl Driver {
+    /// Changes when the values are updated after Set commands, for all nodes that don't have
+    /// their own setting
+    pub fn set_optimistic_updates(&self, optimistic_updates: OptimisticUpdates) {
+        self.storage.optimistic_updates().set(optimistic_updates);
+    }
+
+    pub fn optimistic_updates(&self) -> OptimisticUpdates {
+        self.storage.optimistic_updates().get()
+    }
+
+    /// Returns when the values of the given node are updated after Set commands
+    fn optimistic_updates_for(&self, node_id: NodeId) -> OptimisticUpdates {
+        self.storage
+            .nodes()
+            .inspect(|nodes| nodes.get(&node_id).and_then(|node| node.optimistic_updates))
+            .unwrap_or_else(|| self.optimistic_updates())
+    }
+
+    /// Updates the values that are changed by a Set command the node acknowledged, or marks them
+    /// as pending until the node confirms them, depending on the configured mode
+    pub(crate) fn handle_set_values(&self, node_id: NodeId, cc: &WithAddress<CC>) {
+        let values = expected_values(cc);
+        if values.is_empty() {
+            return;
+        }
+        let endpoint = cc.address().endpoint_index;
+        let mode = self.optimistic_updates_for(node_id);
+
+        for (value_id, value) in values {
+            let value_id = EndpointValueId::new(node_id, endpoint, value_id);
+            match mode {
+                OptimisticUpdates::Immediately => {
+                    let changed = self.storage.value_cache().update(|cache| {
+                        cache.insert(value_id, value.clone()).as_ref() != Some(&value)
+                    });
+                    if changed {
+                        self.emit_event(DriverEvent::ValueUpdated {
+                            value_id,
+                            value,
+                            kind: ValueUpdateKind::Optimistic,
+                        });
+                    }
+                }
+                OptimisticUpdates::AfterSupervision | OptimisticUpdates::AfterVerification => {
+                    // The verification of the transition resolves the pending value
+                    self.storage
+                        .pending_values()
+                        .update(|pending| pending.insert(value_id, value.clone()));
+                    self.emit_event(DriverEvent::ValuePending { value_id, value });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockDriver, mock_driver, mock_protocol_data};
+    use futures::FutureExt;
+    use zwave_cc::commandclass::{BinarySwitchCCSet, MultilevelSwitchCCSet};
+    use zwave_core::cache::Cache;
+
+    const NODE: u8 = 2;
+
+    fn value_id(value_id: ValueId) -> EndpointValueId {
+        EndpointValueId::new(NodeId::new(NODE), EndpointIndex::Root, value_id)
+    }
+
+    fn binary_set() -> WithAddress<CC> {
+        CC::from(
+            BinarySwitchCCSet::builder()
+                .target_value(BinarySet::On)
+                .build(),
+        )
+        .with_destination(NodeId::new(NODE).into())
+    }
+
+    #[test]
+    fn test_immediate_updates_are_labeled_optimistic() {
+        let MockDriver {
+            driver,
+            mut adapter,
+            ..
+        } = mock_driver();
+        driver.handle_set_values(NodeId::new(NODE), &binary_set());
+
+        let current_value = value_id(BinarySwitchCCValues::current_value().id);
+        assert_eq!(
+            driver.value_cache().read(&current_value),
+            Some(CacheValue::BinaryReport(BinaryReport::On))
+        );
+        assert!(matches!(
+            adapter.event_rx.recv().now_or_never(),
+            Some(Some(DriverEvent::ValueUpdated {
+                kind: ValueUpdateKind::Optimistic,
+                ..
+            }))
+        ));
+
+        // A dimmer with its default duration has not reached the target yet
+        let set = CC::from(
+            MultilevelSwitchCCSet::builder()
+                .target_value(LevelSet::Level(30))
+                .build(),
+        )
+        .with_destination(NodeId::new(NODE).into());
+        driver.handle_set_values(NodeId::new(NODE), &set);
+        let cache = driver.value_cache();
+        assert_eq!(
+            cache.read(&value_id(MultilevelSwitchCCValues::target_value().id)),
+            Some(CacheValue::LevelReport(LevelReport::Level(30)))
+        );
+        assert_eq!(
+            cache.read(&value_id(MultilevelSwitchCCValues::current_value().id)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_node_setting_overrides_driver_setting() {
+        let MockDriver {
+            driver,
+            mut adapter,
+            ..
+        } = mock_driver();
+        driver.storage.nodes().update(|nodes| {
+            let protocol_data = mock_protocol_data(true);
+            let mut node = NodeStorage::new(protocol_data);
+            node.optimistic_updates = Some(OptimisticUpdates::AfterVerification);
+            nodes.insert(NodeId::new(NODE), node);
+        });
+        driver.handle_set_values(NodeId::new(NODE), &binary_set());
+
+        // The values stay as they are until the node reports them
+        let target_value = value_id(BinarySwitchCCValues::target_value().id);
+        assert_eq!(driver.value_cache().read(&target_value), None);
+        assert!(
+            driver
+                .storage
+                .pending_values()
+                .inspect(|pending| pending.contains_key(&target_value))
+        );
+        assert!(matches!(
+            adapter.event_rx.recv().now_or_never(),
+            Some(Some(DriverEvent::ValuePending { value_id, .. })) if value_id == target_value
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use super::{
+    Driver, PersistedCommand, PersistedCounters, PersistedQueuedCommand, PersistedS2State,
+};
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+/// The state the driver needs to continue where it left off after a restart, in addition to
+/// the nodes and their values. The application should export it right before the driver is
+/// stopped, and restore it before the driver is used again.
+///
+/// It contains the S2 nonce state, so it is as secret as the network keys and must be stored
+/// with the same care.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PersistedState {
+    /// The counters the driver uses to identify commands
+    pub counters: PersistedCounters,
+    /// The S2 nonce state established with the nodes
+    pub s2_state: PersistedS2State,
+    /// The node information that was last received from each node
+    pub node_infos: BTreeMap<NodeId, NodeInformationApplicationData>,
+    /// The commands waiting for sleeping nodes to wake up
+    pub queued_commands: Vec<PersistedQueuedCommand>,
+    /// The scheduled commands
+    pub scheduled_commands: Vec<PersistedCommand>,
+}
+
+impl Driver {
+    /// Exports the state the driver needs to continue after a restart, so the application
+    /// can persist it
+    pub fn export_state(&self) -> PersistedState {
+        PersistedState {
+            counters: self.export_counters(),
+            s2_state: self.export_s2_state(),
+            node_infos: self.export_node_infos(),
+            queued_commands: self.export_queued_commands(),
+            scheduled_commands: self.export_scheduled_commands(),
+        }
+    }
+
+    /// Restores a previously exported state. `elapsed` is the time that has passed since the
+    /// export. Commands that cannot be decoded are skipped.
+    pub fn restore_state(&self, state: &PersistedState, elapsed: Duration) {
+        self.restore_counters(&state.counters, elapsed);
+        self.restore_s2_state(&state.s2_state);
+        self.restore_node_infos(state.node_infos.clone());
+        self.restore_queued_commands(&state.queued_commands);
+        self.restore_scheduled_commands(&state.scheduled_commands, elapsed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Schedule;
+    use crate::serial_api::mock::mock_driver;
+    use zwave_cc::commandclass::BasicCCSet;
+    use zwave_cc::prelude::*;
+
+    #[test]
+    fn test_state_survives_restart() {
+        let before = mock_driver().driver;
+        before.storage.node_infos().update(|node_infos| {
+            node_infos.insert(
+                NodeId::new(2u8),
+                NodeInformationApplicationData {
+                    basic_device_type: BasicDeviceType::EndNode,
+                    generic_device_class: 0x10,
+                    specific_device_class: 0x01,
+                    supported_command_classes: vec![CommandClasses::BinarySwitch],
+                    controlled_command_classes: vec![],
+                },
+            )
+        });
+        let cc = CC::from(BasicCCSet {
+            target_value: LevelSet::Level(50),
+        })
+        .with_destination(NodeId::new(2u8).into());
+        before.schedule_command(cc, Schedule::After(Duration::from_secs(60)));
+        let exported = before.export_state();
+        assert_eq!(exported.scheduled_commands.len(), 1);
+
+        let after = mock_driver().driver;
+        after.restore_state(&exported, Duration::from_secs(3600));
+        assert_eq!(after.export_state().node_infos, exported.node_infos);
+        assert_eq!(after.export_state().counters, exported.counters);
+        assert_eq!(after.export_scheduled_commands().len(), 1);
+    }
+}
@@ -0,0 +1,191 @@
+use super::{ControllerCommandError, ControllerCommandResult, Driver};
+use crate::{ExecNodeCommandError, ExecNodeCommandOptions, route_changed};
+use core::time::Duration;
+use zwave_cc::commandclass::{CCAddressable, NoOperationCC};
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
+
+/// How a node reacted to being pinged
+#[derive(Debug, Clone, PartialEq)]
+pub struct PingResult {
+    /// Whether the node acknowledged the ping
+    pub reachable: bool,
+    /// How long it took until the ping was acknowledged or failed
+    pub round_trip_time: Duration,
+    /// How the ping was transmitted, including the route that was used
+    pub transmit_report: Option<TransmitReport>,
+    /// Whether the ping took a different route than the previous command to this node
+    pub route_changed: bool,
+}
+
+impl PingResult {
+    /// The repeaters the ping was routed through. Empty for direct communication.
+    pub fn repeaters(&self) -> Vec<NodeId> {
+        self.transmit_report
+            .as_ref()
+            .map(|report| {
+                report
+                    .repeaters
+                    .iter()
+                    .map(|repeater| NodeId::new(repeater.node_id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The data rate the ping was transmitted with
+    pub fn route_speed(&self) -> Option<ProtocolDataRate> {
+        self.transmit_report
+            .as_ref()
+            .map(|report| report.route_speed)
+    }
+
+    /// Whether explorer frames had to be used to find a route to the node
+    pub fn used_explorer_frames(&self) -> bool {
+        self.transmit_report
+            .as_ref()
+            .is_some_and(|report| report.routing_scheme == RoutingScheme::Explore)
+    }
+}
+
+impl Driver {
+    /// Pings a node using a NoOperation CC and captures how the ping was transmitted.
+    /// The result is also recorded in the node statistics.
+    pub(crate) async fn ping_node(
+        &self,
+        node_id: NodeId,
+        options: Option<&ExecNodeCommandOptions>,
+    ) -> ControllerCommandResult<PingResult> {
+        // Remember the previous route, so the transmit report of this ping can be told apart
+        let previous_report = self.storage.nodes().update(|nodes| {
+            nodes
+                .get_mut(&node_id)
+                .and_then(|node| node.last_transmit_report.take())
+        });
+
+        let cc = NoOperationCC {}.with_destination(node_id.into());
+        let started_at = Instant::now();
+        let result = self.exec_node_command(&cc.into(), options).await;
+        let round_trip_time = Instant::now()
+            .checked_duration_since(started_at)
+            .unwrap_or_default();
+
+        let reachable = match result {
+            Ok(_) | Err(ExecNodeCommandError::NodeTimeout) => true,
+            Err(ExecNodeCommandError::NodeNoAck) => false,
+            Err(ExecNodeCommandError::Controller(e)) => return Err(e),
+            Err(
+                e @ (ExecNodeCommandError::Encoding(_)
+                | ExecNodeCommandError::EncryptionImpossible(_)
+                | ExecNodeCommandError::Queued
+                | ExecNodeCommandError::NodeUnreachable),
+            ) => return Err(ControllerCommandError::Unexpected(e.to_string())),
+        };
+
+        let result = self.storage.nodes().update(|nodes| {
+            let node = nodes.get_mut(&node_id)?;
+            let transmit_report = node.last_transmit_report.clone();
+            let route_changed = match (&previous_report, &transmit_report) {
+                (Some(previous), Some(current)) => route_changed(previous, current),
+                _ => false,
+            };
+            if transmit_report.is_none() {
+                node.last_transmit_report = previous_report;
+            }
+
+            let statistics = &mut node.statistics;
+            if reachable {
+                statistics.pings_acknowledged += 1;
+                statistics.last_round_trip_time = Some(round_trip_time);
+            } else {
+                statistics.pings_failed += 1;
+            }
+            if route_changed {
+                statistics.route_changes += 1;
+            }
+
+            Some(PingResult {
+                reachable,
+                round_trip_time,
+                transmit_report,
+                route_changed,
+            })
+        });
+
+        // The node may have been removed in the meantime
+        Ok(result.unwrap_or(PingResult {
+            reachable,
+            round_trip_time,
+            transmit_report: None,
+            route_changed: false,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+
+    #[test]
+    fn test_ping_captures_route() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            let callback_id = *request.payload.last().unwrap();
+            // Transmit status OK, 20 ms TX time, routed through node 5
+            let mut callback = vec![callback_id, 0x00, 0x00, 0x02, 0x01];
+            // ACK RSSI and repeater RSSIs
+            callback.extend_from_slice(&[0x7f; 5]);
+            // ACK channel, TX channel
+            callback.extend_from_slice(&[0x00, 0x00]);
+            // Routing scheme: explorer frame
+            callback.push(0x07);
+            // Repeaters
+            callback.extend_from_slice(&[0x05, 0x00, 0x00, 0x00]);
+            // 100 kbit/s route speed, 2 routing attempts
+            callback.extend_from_slice(&[0x03, 0x02]);
+            vec![
+                MockController::raw(CommandType::Response, FunctionType::SendData, vec![0x01]),
+                MockController::raw(CommandType::Request, FunctionType::SendData, callback),
+            ]
+        });
+        let (first, second, statistics) =
+            run_with_mock_controller(&controller, |driver| async move {
+                driver.storage.nodes().update(|nodes| {
+                    nodes.insert(NodeId::new(2u8), NodeStorage::new(mock_protocol_data(true)));
+                });
+                let first = driver.ping_node(NodeId::new(2u8), None).await.unwrap();
+                let second = driver.ping_node(NodeId::new(2u8), None).await.unwrap();
+                let statistics = driver
+                    .storage
+                    .nodes()
+                    .inspect(|nodes| nodes[&NodeId::new(2u8)].statistics.clone());
+                (first, second, statistics)
+            });
+
+        assert!(first.reachable);
+        assert_eq!(first.repeaters(), vec![NodeId::new(5u8)]);
+        assert_eq!(
+            first.route_speed(),
+            Some(ProtocolDataRate::ZWave(DataRate::DataRate_100k))
+        );
+        assert!(first.used_explorer_frames());
+        // The route did not change between the pings
+        assert!(!second.route_changed);
+
+        assert_eq!(statistics.pings_acknowledged, 2);
+        assert_eq!(statistics.pings_failed, 0);
+        assert_eq!(statistics.route_changes, 0);
+        assert_eq!(
+            statistics.last_round_trip_time,
+            Some(second.round_trip_time)
+        );
+        assert_eq!(statistics.commands_sent, 2);
+        assert_eq!(statistics.commands_failed, 0);
+        let last_route = statistics.last_route.unwrap();
+        let repeaters: Vec<_> = last_route.repeaters.iter().map(|hop| hop.node_id).collect();
+        assert_eq!(repeaters, vec![NodeId::new(5u8)]);
+        assert_eq!(last_route.transmission_time, Duration::from_millis(20));
+    }
+}
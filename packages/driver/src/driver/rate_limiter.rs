@@ -0,0 +1,353 @@
+use super::Driver;
+use core::time::Duration;
+use zwave_core::definitions::RfRegion;
+use zwave_pal::time::{Instant, Timer};
+
+/// How urgent an outgoing frame is. When the rate limit is reached,
+/// frames with a higher priority are sent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum SendPriority {
+    /// Background traffic like polling, which may be delayed in favor of other commands
+    Poll,
+    /// Commands initiated by the user or application
+    #[default]
+    Normal,
+    /// Parts of a security handshake, e.g. a nonce exchange. Nonces expire quickly,
+    /// so these frames are neither rate limited nor queued behind other frames.
+    Handshake,
+}
+
+/// Limits how many frames may be sent, using a token bucket:
+/// Each frame uses up a token, and a new token becomes available every `interval`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// How many frames may be sent in a burst
+    pub burst: u32,
+    /// The sustained rate, expressed as the time after which another frame may be sent
+    pub interval: Duration,
+    /// How many tokens low-priority frames must leave for other frames
+    pub reserved: u32,
+}
+
+impl RateLimit {
+    /// Returns a rate limit suitable for the given region.
+    ///
+    /// Regions in the SRD bands (e.g. Europe) limit the transmitter to a 1% duty cycle, which
+    /// allows roughly one frame per second on average. The other regions do not impose
+    /// such a limit, so only excessive bursts are prevented there.
+    pub fn for_region(region: RfRegion) -> Self {
+        match region {
+            RfRegion::US
+            | RfRegion::US_LongRange
+            | RfRegion::ANZ
+            | RfRegion::HK
+            | RfRegion::JP
+            | RfRegion::KR => Self {
+                burst: 40,
+                interval: Duration::from_millis(100),
+                reserved: 10,
+            },
+            // When in doubt, assume the stricter limit
+            _ => Self {
+                burst: 30,
+                interval: Duration::from_secs(1),
+                reserved: 8,
+            },
+        }
+    }
+}
+
+/// Statistics about frames that had to wait because of the rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RateLimiterStatistics {
+    /// How many frames passed the rate limiter
+    pub frames_sent: u64,
+    /// How many of those frames had to wait before they could be sent
+    pub frames_deferred: u64,
+    /// How long the deferred frames waited in total
+    pub time_deferred: Duration,
+}
+
+pub(crate) struct RateLimiter {
+    limit: Option<RateLimit>,
+    /// Whether the application configured the limit, which then takes precedence over the region's
+    user_defined: bool,
+    tokens: u32,
+    last_refill: Instant,
+    /// How many high-priority frames are currently waiting for a token
+    waiting: u32,
+    statistics: RateLimiterStatistics,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            limit: None,
+            user_defined: false,
+            tokens: 0,
+            last_refill: Instant::now(),
+            waiting: 0,
+            statistics: RateLimiterStatistics::default(),
+        }
+    }
+
+    pub fn limit(&self) -> Option<RateLimit> {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: Option<RateLimit>, user_defined: bool) {
+        if self.user_defined && !user_defined {
+            return;
+        }
+        self.user_defined = user_defined;
+        self.limit = limit;
+        self.tokens = limit.map_or(0, |limit| limit.burst);
+        self.last_refill = Instant::now();
+    }
+
+    pub fn statistics(&self) -> RateLimiterStatistics {
+        self.statistics
+    }
+
+    /// Takes a token for a frame with the given priority.
+    /// If none is available, returns how long to wait before trying again.
+    pub fn try_acquire(&mut self, priority: SendPriority, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = self.limit else {
+            self.statistics.frames_sent += 1;
+            return Ok(());
+        };
+        if priority == SendPriority::Handshake {
+            self.refill(&limit, now);
+            self.tokens = self.tokens.saturating_sub(1);
+            self.statistics.frames_sent += 1;
+            return Ok(());
+        }
+        self.refill(&limit, now);
+
+        // Low-priority frames must not use up the last tokens, nor overtake waiting frames
+        let required = match priority {
+            SendPriority::Normal | SendPriority::Handshake => 1,
+            SendPriority::Poll => (limit.reserved + 1).min(limit.burst),
+        };
+        let blocked = priority == SendPriority::Poll && self.waiting > 0;
+        if !blocked && self.tokens >= required {
+            self.tokens -= 1;
+            self.statistics.frames_sent += 1;
+            return Ok(());
+        }
+
+        let missing = if blocked { 1 } else { required - self.tokens };
+        let since_refill = now
+            .checked_duration_since(self.last_refill)
+            .unwrap_or_default();
+        Err((limit.interval * missing).saturating_sub(since_refill))
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let Some(elapsed) = now.checked_duration_since(self.last_refill) else {
+            return;
+        };
+        let new_tokens = (elapsed.as_micros() / limit.interval.as_micros().max(1)) as u32;
+        if new_tokens == 0 {
+            return;
+        }
+
+        self.tokens = self.tokens.saturating_add(new_tokens).min(limit.burst);
+        self.last_refill = if self.tokens == limit.burst {
+            now
+        } else {
+            // Keep the time that has passed since the last token became available
+            self.last_refill
+                .checked_add(limit.interval * new_tokens)
+                .unwrap_or(now)
+        };
+    }
+}
+
+impl Driver {
+    /// Limits how many frames the driver sends, or removes the limit if `None` is given.
+    /// This overrides the default limit for the controller's RF region.
+    pub fn set_rate_limit(&self, limit: Option<RateLimit>) {
+        self.storage
+            .rate_limiter()
+            .update(|limiter| limiter.set_limit(limit, true));
+    }
+
+    /// Returns the current limit for outgoing frames
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.storage
+            .rate_limiter()
+            .inspect(|limiter| limiter.limit())
+    }
+
+    /// Returns statistics about frames that were delayed by the rate limit
+    pub fn rate_limiter_statistics(&self) -> RateLimiterStatistics {
+        self.storage
+            .rate_limiter()
+            .inspect(|limiter| limiter.statistics())
+    }
+
+    /// Applies the default rate limit for the given region, unless the application configured one
+    pub(crate) fn apply_region_rate_limit(&self, region: RfRegion) {
+        self.storage
+            .rate_limiter()
+            .update(|limiter| limiter.set_limit(Some(RateLimit::for_region(region)), false));
+    }
+
+    /// Waits until a frame may be sent. The priority is checked again after each wait,
+    /// because it may be changed while the frame is waiting.
+    pub(crate) async fn wait_for_send_slot(&self, priority: impl Fn() -> SendPriority) {
+        let mut deferred: Option<DeferredFrame> = None;
+        loop {
+            let priority = priority();
+            let result = self
+                .storage
+                .rate_limiter()
+                .update(|limiter| limiter.try_acquire(priority, Instant::now()));
+            let Err(wait) = result else {
+                break;
+            };
+
+            match deferred.as_mut() {
+                Some(deferred) => deferred.set_priority(priority),
+                None => deferred = Some(DeferredFrame::new(self, priority)),
+            }
+            Timer::after(wait).await;
+        }
+    }
+}
+
+/// Tracks a frame that waits for the rate limiter. Dropping it updates the statistics,
+/// even if the waiting was cancelled.
+struct DeferredFrame<'a> {
+    driver: &'a Driver,
+    priority: SendPriority,
+    since: Instant,
+}
+
+impl<'a> DeferredFrame<'a> {
+    fn new(driver: &'a Driver, priority: SendPriority) -> Self {
+        driver.storage.rate_limiter().update(|limiter| {
+            limiter.statistics.frames_deferred += 1;
+            if priority > SendPriority::Poll {
+                limiter.waiting += 1;
+            }
+        });
+        Self {
+            driver,
+            priority,
+            since: Instant::now(),
+        }
+    }
+
+    fn set_priority(&mut self, priority: SendPriority) {
+        if priority == self.priority {
+            return;
+        }
+        self.driver.storage.rate_limiter().update(|limiter| {
+            if priority > SendPriority::Poll {
+                limiter.waiting += 1;
+            } else {
+                limiter.waiting = limiter.waiting.saturating_sub(1);
+            }
+        });
+        self.priority = priority;
+    }
+}
+
+impl Drop for DeferredFrame<'_> {
+    fn drop(&mut self) {
+        let waited = Instant::now()
+            .checked_duration_since(self.since)
+            .unwrap_or_default();
+        self.driver.storage.rate_limiter().update(|limiter| {
+            limiter.statistics.time_deferred += waited;
+            if self.priority > SendPriority::Poll {
+                limiter.waiting = limiter.waiting.saturating_sub(1);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn limiter(burst: u32, reserved: u32) -> RateLimiter {
+        let mut limiter = RateLimiter::new();
+        limiter.set_limit(
+            Some(RateLimit {
+                burst,
+                interval: Duration::from_millis(100),
+                reserved,
+            }),
+            true,
+        );
+        limiter
+    }
+
+    #[test]
+    fn test_burst_then_refill() {
+        let mut limiter = limiter(3, 0);
+        let start = limiter.last_refill;
+
+        for _ in 0..3 {
+            assert_eq!(limiter.try_acquire(SendPriority::Normal, start), Ok(()));
+        }
+        let wait = limiter
+            .try_acquire(SendPriority::Normal, start)
+            .unwrap_err();
+        assert_eq!(wait, Duration::from_millis(100));
+
+        let later = start.checked_add(Duration::from_millis(250)).unwrap();
+        assert_eq!(limiter.try_acquire(SendPriority::Normal, later), Ok(()));
+        assert_eq!(limiter.try_acquire(SendPriority::Normal, later), Ok(()));
+        // Half of the next interval has already passed
+        assert_eq!(
+            limiter.try_acquire(SendPriority::Normal, later),
+            Err(Duration::from_millis(50))
+        );
+        assert_eq!(limiter.statistics().frames_sent, 5);
+    }
+
+    #[test]
+    fn test_polling_leaves_reserve() {
+        let mut limiter = limiter(3, 2);
+        let now = limiter.last_refill;
+
+        assert_eq!(limiter.try_acquire(SendPriority::Poll, now), Ok(()));
+        assert!(limiter.try_acquire(SendPriority::Poll, now).is_err());
+        assert_eq!(limiter.try_acquire(SendPriority::Normal, now), Ok(()));
+        assert_eq!(limiter.try_acquire(SendPriority::Normal, now), Ok(()));
+        assert!(limiter.try_acquire(SendPriority::Normal, now).is_err());
+    }
+
+    #[test]
+    fn test_polling_waits_for_waiting_commands() {
+        let mut limiter = limiter(3, 0);
+        let now = limiter.last_refill;
+        limiter.waiting = 1;
+
+        assert!(limiter.try_acquire(SendPriority::Poll, now).is_err());
+        assert_eq!(limiter.try_acquire(SendPriority::Normal, now), Ok(()));
+    }
+
+    #[test]
+    fn test_handshakes_are_not_limited() {
+        let mut limiter = limiter(1, 0);
+        let now = limiter.last_refill;
+
+        assert_eq!(limiter.try_acquire(SendPriority::Normal, now), Ok(()));
+        assert!(limiter.try_acquire(SendPriority::Normal, now).is_err());
+        assert_eq!(limiter.try_acquire(SendPriority::Handshake, now), Ok(()));
+        assert_eq!(limiter.statistics().frames_sent, 2);
+    }
+
+    #[test]
+    fn test_region_limit_does_not_override_user_limit() {
+        let mut limiter = RateLimiter::new();
+        limiter.set_limit(None, true);
+        limiter.set_limit(Some(RateLimit::for_region(RfRegion::EU)), false);
+        assert_eq!(limiter.limit(), None);
+    }
+}
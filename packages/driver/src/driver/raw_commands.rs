@@ -0,0 +1,392 @@
+use super::{Driver, ExecControllerCommandOptions, ExecControllerCommandResult};
+use crate::error::Error;
+use crate::{ExecNodeCommandError, ExecNodeCommandOptions, ExecNodeCommandResult};
+use bytes::{BufMut, Bytes, BytesMut};
+use core::fmt::Debug;
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::NotImplemented as CCNotImplemented;
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_serial::command::NotImplemented;
+use zwave_serial::prelude::*;
+
+/// Tests the raw payload of a response or callback to a raw command
+pub type RawPayloadPredicate = Arc<dyn Fn(&[u8]) -> bool + Sync + Send>;
+
+/// Tests the raw form of a CC received in response to a raw CC
+pub type RawCCPredicate = Arc<dyn Fn(&CCRaw) -> bool + Sync + Send>;
+
+/// Describes how the controller reacts to a raw Serial API command
+#[derive(TypedBuilder, Clone)]
+pub struct RawFunctionExpectations {
+    /// Whether the controller responds to the command. Default: `true`
+    #[builder(default = true)]
+    pub response: bool,
+    /// Whether the controller sends a callback after the response. If so, a callback ID
+    /// is appended to the payload and the callback is expected to start with it. Default: `false`
+    #[builder(default)]
+    pub callback: bool,
+    /// Further restricts which responses are accepted. By default, every response
+    /// with the same function type is.
+    #[builder(default, setter(strip_option))]
+    pub test_response: Option<RawPayloadPredicate>,
+    /// Further restricts which callbacks are accepted. By default, every callback
+    /// with the same function type and callback ID is.
+    #[builder(default, setter(strip_option))]
+    pub test_callback: Option<RawPayloadPredicate>,
+    /// How long to wait for the callback. Uses the default timeout if not set.
+    #[builder(default, setter(strip_option))]
+    pub callback_timeout: Option<Duration>,
+}
+
+impl Default for RawFunctionExpectations {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// A Serial API command that is sent as-is and whose responses are not parsed
+#[derive(Clone)]
+struct RawFunctionRequest {
+    function_type: FunctionType,
+    payload: Bytes,
+    callback_id: Option<u8>,
+    expectations: RawFunctionExpectations,
+}
+
+impl Debug for RawFunctionRequest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RawFunctionRequest")
+            .field("function_type", &self.function_type)
+            .field("payload", &format!("0x{}", hex::encode(&self.payload)))
+            .field("callback_id", &self.callback_id)
+            .finish()
+    }
+}
+
+impl CommandBase for RawFunctionRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandId for RawFunctionRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        self.function_type
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandRequest for RawFunctionRequest {
+    fn expects_response(&self) -> bool {
+        self.expectations.response
+    }
+
+    fn test_response(&self, response: &Command) -> bool {
+        let Command::NotImplemented(response) = response else {
+            return false;
+        };
+        self.expects_response()
+            && response.command_type == CommandType::Response
+            && response.function_type == self.function_type
+            && self
+                .expectations
+                .test_response
+                .as_ref()
+                .is_none_or(|test| test(&response.payload))
+    }
+
+    fn expects_callback(&self) -> bool {
+        self.expectations.callback
+    }
+
+    fn test_callback(&self, callback: &Command) -> bool {
+        let Command::NotImplemented(callback) = callback else {
+            return false;
+        };
+        self.expects_callback()
+            && callback.command_type == CommandType::Request
+            && callback.function_type == self.function_type
+            && callback.payload.first().copied() == self.callback_id
+            && self
+                .expectations
+                .test_callback
+                .as_ref()
+                .is_none_or(|test| test(&callback.payload))
+    }
+
+    fn needs_callback_id(&self) -> bool {
+        self.expectations.callback
+    }
+
+    fn set_callback_id(&mut self, callback_id: Option<u8>) {
+        self.callback_id = callback_id;
+    }
+
+    fn expects_raw_frames(&self) -> bool {
+        true
+    }
+}
+
+impl AsCommandRaw for RawFunctionRequest {
+    fn as_raw(&self, _ctx: &CommandEncodingContext) -> CommandRaw {
+        let mut payload = BytesMut::from(&self.payload[..]);
+        if let Some(callback_id) = self.callback_id {
+            payload.put_u8(callback_id);
+        }
+        CommandRaw {
+            command_type: self.command_type(),
+            function_type: self.function_type,
+            payload: payload.freeze(),
+            checksum: 0, // placeholder
+        }
+    }
+}
+
+impl ToLogPayload for RawFunctionRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new()
+            .with_entry("payload", format!("0x{}", hex::encode(&self.payload)));
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+impl Driver {
+    /// Executes a Serial API command with the given function type and payload without interpreting it.
+    /// Returns the raw payload of the callback, or the response if no callback is expected.
+    ///
+    /// This is meant for protocol exploration. The command goes through the same queue as all
+    /// other commands, so it cannot interfere with them.
+    pub async fn exec_raw_function(
+        &self,
+        function_type: FunctionType,
+        payload: impl Into<Bytes>,
+        expectations: RawFunctionExpectations,
+    ) -> ExecControllerCommandResult<Option<Bytes>> {
+        let options = ExecControllerCommandOptions {
+            callback_timeout: expectations.callback_timeout,
+            ..Default::default()
+        };
+        let command = RawFunctionRequest {
+            function_type,
+            payload: payload.into(),
+            callback_id: None,
+            expectations,
+        };
+
+        let result = self.exec_controller_command(command, Some(&options)).await;
+        let result = result.map(|command| {
+            command.map(|command| match command {
+                Command::NotImplemented(NotImplemented { payload, .. }) => payload,
+                command => {
+                    unreachable!("Raw commands only accept raw responses, got {:?}", command)
+                }
+            })
+        });
+
+        let log = self.driver_log();
+        match &result {
+            Ok(Some(payload)) => log.info(|| {
+                format!(
+                    "raw function {:?} returned 0x{}",
+                    function_type,
+                    hex::encode(payload)
+                )
+            }),
+            Ok(None) => log.info(|| format!("raw function {:?} was executed", function_type)),
+            Err(e) => log.warn(|| format!("raw function {:?} failed: {}", function_type, e)),
+        }
+
+        result
+    }
+
+    /// Sends a CC with the given command and payload to a node without interpreting it.
+    /// If a response predicate is given, waits for a CC from the node that matches it and returns its raw form.
+    pub async fn exec_raw_cc(
+        &self,
+        node_id: NodeId,
+        cc: CCRaw,
+        test_response: Option<RawCCPredicate>,
+        options: Option<&ExecNodeCommandOptions>,
+    ) -> ExecNodeCommandResult<Option<CCRaw>> {
+        self.ensure_addressable(node_id)?;
+
+        let cc_id = cc.cc_id;
+        let own_node_id = self.serial_api.storage.own_node_id();
+        // Like for other CCs, start waiting before sending, so the response does not get lost
+        let awaited_cc_response = test_response.map(|test| {
+            self.register_awaited_cc(
+                Box::new(move |recv| {
+                    if recv.address().source_node_id != node_id || recv.cc_id() != cc_id {
+                        return false;
+                    }
+                    let ctx = CCEncodingContext::builder()
+                        .own_node_id(own_node_id)
+                        .node_id(node_id)
+                        .build();
+                    test(&recv.as_raw(&ctx))
+                }),
+                Some(Duration::from_secs(10)),
+            )
+        });
+
+        let cc = CC::NotImplemented(CCNotImplemented {
+            cc_id,
+            cc_command: cc.cc_command,
+            payload: cc.payload,
+        });
+        let result = self.exec_node_command_internal(node_id, &cc, options).await;
+        self.update_node_status(node_id, &result);
+        result?;
+
+        let log = self.node_log(node_id, EndpointIndex::Root);
+        let Some(awaited_cc_response) = awaited_cc_response else {
+            log.info(|| format!("raw {} CC was sent", cc_id));
+            return Ok(None);
+        };
+
+        match awaited_cc_response.try_await().await {
+            Ok(recv) => {
+                let ctx = CCEncodingContext::builder()
+                    .own_node_id(own_node_id)
+                    .node_id(node_id)
+                    .build();
+                let response = recv.as_raw(&ctx);
+                log.info(|| {
+                    format!(
+                        "raw {} CC was answered with 0x{}",
+                        cc_id,
+                        hex::encode(response.as_bytes())
+                    )
+                });
+                Ok(Some(response))
+            }
+            Err(Error::Timeout) => Err(ExecNodeCommandError::NodeTimeout),
+            Err(_) => {
+                panic!("Unexpected internal error while waiting for CC response");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+
+    #[test]
+    fn test_exec_raw_function() {
+        let controller = MockController::new().on(FunctionType::GetControllerId, |_, _| {
+            vec![MockController::raw(
+                CommandType::Response,
+                FunctionType::GetControllerId,
+                vec![0xde, 0xad, 0xbe, 0xef, 0x01],
+            )]
+        });
+        let response = run_with_mock_controller(&controller, |driver| async move {
+            driver
+                .exec_raw_function(
+                    FunctionType::GetControllerId,
+                    Bytes::new(),
+                    Default::default(),
+                )
+                .await
+                .unwrap()
+        });
+
+        // The response is returned as-is, even though it could be parsed
+        assert_eq!(
+            response.as_deref(),
+            Some(&[0xde, 0xad, 0xbe, 0xef, 0x01][..])
+        );
+    }
+
+    #[test]
+    fn test_exec_raw_function_with_callback() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            let callback_id = *request.payload.last().unwrap();
+            vec![
+                MockController::raw(CommandType::Response, FunctionType::SendData, vec![0x01]),
+                // A callback for a different command must be ignored
+                MockController::raw(
+                    CommandType::Request,
+                    FunctionType::SendData,
+                    vec![callback_id.wrapping_add(1), 0x00],
+                ),
+                MockController::raw(
+                    CommandType::Request,
+                    FunctionType::SendData,
+                    vec![callback_id, 0x01],
+                ),
+            ]
+        });
+        let expectations = RawFunctionExpectations::builder()
+            .callback(true)
+            .test_response(Arc::new(|payload: &[u8]| payload == [0x01]))
+            .build();
+        let callback = run_with_mock_controller(&controller, |driver| async move {
+            driver
+                .exec_raw_function(
+                    FunctionType::SendData,
+                    vec![0x02, 0x01, 0x00, 0x25],
+                    expectations,
+                )
+                .await
+                .unwrap()
+                .unwrap()
+        });
+
+        let request = &controller.received()[0];
+        // The callback ID was appended to the payload
+        assert_eq!(request.payload.len(), 5);
+        assert_eq!(&callback[..], &[request.payload[4], 0x01]);
+    }
+
+    #[test]
+    fn test_exec_raw_cc() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            let mut ret = MockController::send_data_ok(request);
+            // Node 2 responds with a Basic CC Report
+            ret.push(MockController::application_command(2, &[0x20, 0x03, 0x63]));
+            ret
+        });
+        let response = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(2u8), NodeStorage::new(mock_protocol_data(true)));
+            });
+            let cc = CCRaw {
+                cc_id: CommandClasses::Basic,
+                cc_command: Some(0x02),
+                payload: Bytes::new(),
+            };
+            driver
+                .exec_raw_cc(
+                    NodeId::new(2u8),
+                    cc,
+                    Some(Arc::new(|cc: &CCRaw| cc.cc_command == Some(0x03))),
+                    None,
+                )
+                .await
+                .unwrap()
+                .unwrap()
+        });
+
+        // The Basic CC Get was sent as-is
+        let request = &controller.received()[0];
+        assert_eq!(&request.payload[..4], &[0x02, 0x02, 0x20, 0x02]);
+        assert_eq!(response.as_bytes().as_ref(), &[0x20, 0x03, 0x63]);
+    }
+}
@@ -0,0 +1,388 @@
+use super::scheduler::decode_persisted_cc;
+use super::{Driver, DriverActor, DriverEvent, ExecNodeCommandError, ExecNodeCommandResult};
+use crate::NodeStatus;
+use alloc::collections::BTreeMap;
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::{CC, CCEncodingContext, CCId, Destination, WithAddress};
+use zwave_core::definitions::{CommandClasses, EndpointIndex, NodeId};
+use zwave_core::serialize::Serializable;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
+
+/// What happens to commands to nodes that are unlikely to receive them
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct ReachabilityOptions {
+    /// Whether commands to sleeping nodes are queued until the node wakes up, instead of being
+    /// sent right away. Queued commands fail with [`ExecNodeCommandError::Queued`] and are sent
+    /// by [`run_scheduler`](Driver::run_scheduler) after the wakeup. Default: false
+    #[builder(default)]
+    pub queue_for_sleeping_nodes: bool,
+    /// After how many unacknowledged commands in a row commands to a dead node fail right away.
+    /// Pings are still sent, so the node can be found again. Default: never
+    #[builder(default, setter(strip_option))]
+    pub fail_fast_after: Option<u32>,
+}
+
+impl Default for ReachabilityOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// A command waiting for a sleeping node to wake up, in a form the application can persist,
+/// so it is not lost when the driver is restarted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistedQueuedCommand {
+    pub node_id: NodeId,
+    pub endpoint: EndpointIndex,
+    /// The serialized CC, without encapsulation
+    pub payload: Vec<u8>,
+}
+
+/// Whether a queued command can be persisted. Commands of the security and transport CCs
+/// are only valid during the session they were created in, and must not be stored.
+fn is_persistable(cc: &CC) -> bool {
+    !matches!(
+        cc.cc_id(),
+        CommandClasses::Security | CommandClasses::Security2 | CommandClasses::TransportService
+    )
+}
+
+/// What to do with a command before it is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Preflight {
+    Send,
+    Queue,
+    FailFast,
+}
+
+#[derive(Default)]
+pub(crate) struct ReachabilityState {
+    options: ReachabilityOptions,
+    /// How many commands in a row each node did not acknowledge
+    consecutive_failures: BTreeMap<NodeId, u32>,
+    /// The commands that are sent when a sleeping node wakes up
+    wake_up_queue: BTreeMap<NodeId, Vec<WithAddress<CC>>>,
+}
+
+impl ReachabilityState {
+    fn preflight(&self, node_id: NodeId, status: NodeStatus, cc: &CC) -> Preflight {
+        // Pings are meant to find out whether a node is reachable
+        if cc.cc_id() == CommandClasses::NoOperation {
+            return Preflight::Send;
+        }
+        match status {
+            NodeStatus::Asleep if self.options.queue_for_sleeping_nodes => Preflight::Queue,
+            NodeStatus::Dead
+                if self.options.fail_fast_after.is_some_and(|threshold| {
+                    self.consecutive_failures
+                        .get(&node_id)
+                        .is_some_and(|failures| *failures >= threshold)
+                }) =>
+            {
+                Preflight::FailFast
+            }
+            _ => Preflight::Send,
+        }
+    }
+}
+
+impl Driver {
+    /// Changes what happens to commands to nodes that are unlikely to receive them
+    pub fn set_reachability_options(&self, options: ReachabilityOptions) {
+        self.storage
+            .reachability()
+            .update(|state| state.options = options);
+    }
+
+    pub fn reachability_options(&self) -> ReachabilityOptions {
+        self.storage.reachability().inspect(|state| state.options)
+    }
+
+    /// Whether the given node is likely to receive a command sent right now, judging by its
+    /// status. Sleeping nodes are only reachable while they are awake.
+    pub fn is_node_reachable_now(&self, node_id: NodeId) -> bool {
+        let status = self
+            .storage
+            .nodes()
+            .inspect(|nodes| nodes.get(&node_id).map(|node| node.status));
+        !matches!(status, Some(NodeStatus::Asleep | NodeStatus::Dead))
+    }
+
+    /// How many commands to the given node are waiting for it to wake up
+    pub fn queued_command_count(&self, node_id: NodeId) -> usize {
+        self.storage.reachability().inspect(|state| {
+            state
+                .wake_up_queue
+                .get(&node_id)
+                .map_or(0, |queue| queue.len())
+        })
+    }
+
+    /// Exports the commands that are waiting for sleeping nodes to wake up. Commands that are
+    /// only valid in the current session are left out.
+    pub(crate) fn export_queued_commands(&self) -> Vec<PersistedQueuedCommand> {
+        self.storage.reachability().inspect(|state| {
+            state
+                .wake_up_queue
+                .iter()
+                .flat_map(|(node_id, queue)| queue.iter().map(move |cc| (*node_id, cc)))
+                .filter(|(_, cc)| is_persistable(cc))
+                .map(|(node_id, cc)| PersistedQueuedCommand {
+                    node_id,
+                    endpoint: cc.address().endpoint_index,
+                    payload: (**cc)
+                        .clone()
+                        .as_raw(&CCEncodingContext::default())
+                        .as_bytes()
+                        .to_vec(),
+                })
+                .collect()
+        })
+    }
+
+    /// Queues previously exported commands again, so they are sent when their nodes wake up.
+    /// Commands that cannot be decoded or must not be persisted are skipped.
+    /// Returns how many commands were queued.
+    pub(crate) fn restore_queued_commands(&self, commands: &[PersistedQueuedCommand]) -> usize {
+        let restored: Vec<_> = commands
+            .iter()
+            .filter_map(|persisted| {
+                decode_persisted_cc(persisted.node_id, persisted.endpoint, &persisted.payload)
+            })
+            .filter(|cc| is_persistable(cc))
+            .filter_map(|cc| {
+                // Only singlecast commands are queued for sleeping nodes
+                let Destination::Singlecast(node_id) = cc.address().destination else {
+                    return None;
+                };
+                Some((node_id, cc))
+            })
+            .collect();
+        let count = restored.len();
+        self.storage.reachability().update(|state| {
+            for (node_id, cc) in restored {
+                state.wake_up_queue.entry(node_id).or_default().push(cc);
+            }
+        });
+        count
+    }
+
+    /// Decides whether a command is sent to the node, queued until it wakes up, or fails
+    /// right away, because the node is unlikely to receive it
+    pub(super) fn preflight(
+        &self,
+        node_id: NodeId,
+        cc: &WithAddress<CC>,
+    ) -> ExecNodeCommandResult<()> {
+        let Some(status) = self
+            .storage
+            .nodes()
+            .inspect(|nodes| nodes.get(&node_id).map(|node| node.status))
+        else {
+            return Ok(());
+        };
+        let preflight = self
+            .storage
+            .reachability()
+            .inspect(|state| state.preflight(node_id, status, cc));
+        match preflight {
+            Preflight::Send => Ok(()),
+            Preflight::Queue => {
+                self.node_log(node_id, cc.address().endpoint_index)
+                    .info(|| "the node is asleep, sending the command when it wakes up");
+                self.storage.reachability().update(|state| {
+                    state
+                        .wake_up_queue
+                        .entry(node_id)
+                        .or_default()
+                        .push(cc.clone())
+                });
+                Err(ExecNodeCommandError::Queued)
+            }
+            Preflight::FailFast => {
+                self.node_log(node_id, cc.address().endpoint_index)
+                    .warn(|| "the node is dead, not sending the command");
+                Err(ExecNodeCommandError::NodeUnreachable)
+            }
+        }
+    }
+
+    /// Counts the commands in a row the node did not acknowledge
+    pub(super) fn record_reachability(&self, node_id: NodeId, acknowledged: bool) {
+        self.storage.reachability().update(|state| {
+            if acknowledged {
+                state.consecutive_failures.remove(&node_id);
+            } else {
+                *state.consecutive_failures.entry(node_id).or_default() += 1;
+            }
+        });
+    }
+}
+
+impl DriverActor {
+    /// Marks the node as awake and schedules the commands that were queued for it
+    pub(super) fn handle_wake_up_notification(&self, node_id: NodeId) {
+        let status = NodeStatus::Awake;
+        let changed = self.storage.nodes().update(|nodes| {
+            nodes
+                .get_mut(&node_id)
+                .is_some_and(|node| core::mem::replace(&mut node.status, status) != status)
+        });
+        if changed {
+            self.emit_event(DriverEvent::NodeStatusChanged { node_id, status });
+        }
+
+        let queued = self
+            .storage
+            .reachability()
+            .update(|state| state.wake_up_queue.remove(&node_id))
+            .unwrap_or_default();
+        if queued.is_empty() {
+            return;
+        }
+        self.node_log(node_id, EndpointIndex::Root)
+            .info(|| format!("the node woke up, sending {} queued commands", queued.len()));
+        let now = Instant::now();
+        self.storage.scheduler().update(|scheduler| {
+            for cc in queued {
+                scheduler.add(cc, now, None);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use core::time::Duration;
+    use zwave_cc::commandclass::{BasicCCGet, CCAddressable, NoOperationCC, SecurityCCNonceGet};
+    use zwave_core::prelude::*;
+    use zwave_pal::time::Timer;
+
+    #[test]
+    fn test_commands_to_sleeping_nodes_are_queued() {
+        let controller = MockController::new().on(FunctionType::SendData, |controller, request| {
+            let mut ret = MockController::send_data_ok(request);
+            if controller.received().len() == 1 {
+                // Wake Up Notification
+                ret.push(MockController::application_command(2, &[0x84, 0x07]));
+            }
+            ret
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            let mut node = NodeStorage::new(NodeInformationProtocolData {
+                generic_device_class: 0x07,
+                ..mock_protocol_data(false)
+            });
+            node.status = NodeStatus::Asleep;
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(node_id, node);
+            });
+            driver.set_reachability_options(
+                ReachabilityOptions::builder()
+                    .queue_for_sleeping_nodes(true)
+                    .build(),
+            );
+            assert!(!driver.is_node_reachable_now(node_id));
+
+            let cc = CC::from(BasicCCGet {}).with_destination(node_id.into());
+            let result = driver.exec_node_command(&cc, None).await;
+            assert!(matches!(result, Err(ExecNodeCommandError::Queued)));
+            assert_eq!(driver.queued_command_count(node_id), 1);
+
+            // Pings are not queued. The node wakes up afterwards.
+            let ping = CC::from(NoOperationCC {}).with_destination(node_id.into());
+            driver.exec_node_command(&ping, None).await.unwrap();
+            Timer::after(Duration::from_millis(50)).await;
+            assert!(driver.is_node_reachable_now(node_id));
+            assert_eq!(driver.queued_command_count(node_id), 0);
+            // The queued command is sent by the scheduler
+            assert_eq!(driver.storage.scheduler().inspect(|s| s.len()), 1);
+        });
+        assert_eq!(controller.received().len(), 1);
+    }
+
+    #[test]
+    fn test_export_and_restore_queued_commands() {
+        let controller = MockController::new();
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            let get = CC::from(BasicCCGet {}).with_destination(node_id.into());
+            let nonce = CC::from(SecurityCCNonceGet {}).with_destination(node_id.into());
+            driver.storage.reachability().update(|state| {
+                state.wake_up_queue.insert(node_id, vec![get, nonce]);
+            });
+
+            // Security commands are only valid in the current session
+            let exported = driver.export_queued_commands();
+            assert_eq!(
+                exported,
+                vec![PersistedQueuedCommand {
+                    node_id,
+                    endpoint: EndpointIndex::Root,
+                    payload: vec![0x20, 0x02],
+                }]
+            );
+
+            driver
+                .storage
+                .reachability()
+                .update(|state| state.wake_up_queue.clear());
+            let mut persisted = exported.clone();
+            persisted.push(PersistedQueuedCommand {
+                node_id,
+                endpoint: EndpointIndex::Root,
+                payload: vec![0x98, 0x40],
+            });
+            // Broadcasts are not queued for a node, so they are not counted either
+            persisted.push(PersistedQueuedCommand {
+                node_id: NodeId::broadcast(),
+                endpoint: EndpointIndex::Root,
+                payload: vec![0x20, 0x02],
+            });
+            assert_eq!(driver.restore_queued_commands(&persisted), 1);
+            assert_eq!(driver.queued_command_count(node_id), 1);
+            assert_eq!(driver.export_queued_commands(), exported);
+        });
+    }
+
+    #[test]
+    fn test_preflight() {
+        let node_id = NodeId::new(2u8);
+        let get = CC::from(BasicCCGet {});
+        let ping = CC::from(NoOperationCC {});
+        let mut state = ReachabilityState::default();
+        assert_eq!(
+            state.preflight(node_id, NodeStatus::Asleep, &get),
+            Preflight::Send
+        );
+        assert_eq!(
+            state.preflight(node_id, NodeStatus::Dead, &get),
+            Preflight::Send
+        );
+
+        state.options = ReachabilityOptions::builder().fail_fast_after(2).build();
+        state.consecutive_failures.insert(node_id, 1);
+        assert_eq!(
+            state.preflight(node_id, NodeStatus::Dead, &get),
+            Preflight::Send
+        );
+        state.consecutive_failures.insert(node_id, 2);
+        assert_eq!(
+            state.preflight(node_id, NodeStatus::Dead, &get),
+            Preflight::FailFast
+        );
+        assert_eq!(
+            state.preflight(node_id, NodeStatus::Dead, &ping),
+            Preflight::Send
+        );
+        assert_eq!(
+            state.preflight(node_id, NodeStatus::Alive, &get),
+            Preflight::Send
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use super::{ControllerCommandResult, Driver, DriverActor, expect_controller_command_result};
+use zwave_cc::commandclass::{
+    ControllerReplicationCCTransferGroup, ControllerReplicationCCTransferGroupName,
+};
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_serial::command::{
+    Command, ReplicationCommandCompleteRequest, ReplicationSendDataRequest,
+};
+
+/// A group of nodes that is transferred from the including controller to a secondary controller
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplicationGroup {
+    pub id: u8,
+    pub name: Option<String>,
+    pub nodes: Vec<NodeId>,
+}
+
+impl Driver {
+    /// Returns the groups the including controller transferred to this controller
+    /// during the last learn mode
+    pub fn replicated_groups(&self) -> Vec<ReplicationGroup> {
+        self.storage
+            .replicated_groups()
+            .inspect(|groups| groups.values().cloned().collect())
+    }
+
+    /// Transfers the given groups to a controller that is currently being included
+    pub(crate) async fn replicate_groups(
+        &self,
+        node_id: NodeId,
+        groups: &[ReplicationGroup],
+    ) -> ControllerCommandResult<()> {
+        let mut sequence_number = 0u8;
+        let mut next_sequence_number = || {
+            sequence_number = sequence_number.wrapping_add(1);
+            sequence_number
+        };
+
+        for group in groups {
+            if let Some(name) = &group.name {
+                let cc = ControllerReplicationCCTransferGroupName::builder()
+                    .sequence_number(next_sequence_number())
+                    .group_id(group.id)
+                    .name(name.clone())
+                    .build();
+                self.replication_send_data(node_id, cc.into()).await?;
+            }
+            for member in &group.nodes {
+                let cc = ControllerReplicationCCTransferGroup::builder()
+                    .sequence_number(next_sequence_number())
+                    .group_id(group.id)
+                    .node_id(*member)
+                    .build();
+                self.replication_send_data(node_id, cc.into()).await?;
+            }
+        }
+
+        self.controller_log()
+            .info(|| format!("transferred {} groups to node {}", groups.len(), node_id));
+        Ok(())
+    }
+
+    async fn replication_send_data(&self, node_id: NodeId, cc: CC) -> ControllerCommandResult<()> {
+        let ctx = CCEncodingContext::builder()
+            .own_node_id(self.serial_api.storage.own_node_id())
+            .node_id(node_id)
+            .build();
+        let request = ReplicationSendDataRequest::builder()
+            .node_id(node_id)
+            .command(cc.try_as_raw(&ctx)?)
+            .build();
+        let response = self.exec_controller_command(request, None).await;
+        expect_controller_command_result!(response, ReplicationSendDataCallback);
+        Ok(())
+    }
+}
+
+impl DriverActor {
+    /// Remembers the groups the including controller transfers during learn mode,
+    /// and tells the controller that each transfer was handled
+    pub(super) fn handle_replication_cc(&self, cc: &WithAddress<CC>) {
+        self.storage
+            .replicated_groups()
+            .update(|groups| match &**cc {
+                CC::ControllerReplicationCCTransferGroup(transfer) => {
+                    let group =
+                        groups
+                            .entry(transfer.group_id)
+                            .or_insert_with(|| ReplicationGroup {
+                                id: transfer.group_id,
+                                ..Default::default()
+                            });
+                    if !group.nodes.contains(&transfer.node_id) {
+                        group.nodes.push(transfer.node_id);
+                    }
+                }
+                CC::ControllerReplicationCCTransferGroupName(transfer) => {
+                    let group =
+                        groups
+                            .entry(transfer.group_id)
+                            .or_insert_with(|| ReplicationGroup {
+                                id: transfer.group_id,
+                                ..Default::default()
+                            });
+                    group.name = Some(transfer.name.clone());
+                }
+                _ => {}
+            });
+
+        // The including controller waits until the transfer was handled
+        self.serial_api
+            .dispatch_serial_api_command(ReplicationCommandCompleteRequest::default(), false);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use crate::{InclusionOptions, LearnModeOptions};
+    use core::time::Duration;
+
+    #[test]
+    fn test_groups_are_received_during_learn_mode() {
+        let controller = MockController::new().on(FunctionType::SetLearnMode, |_, request| {
+            let response = MockController::raw(
+                CommandType::Response,
+                FunctionType::SetLearnMode,
+                vec![0x01],
+            );
+            if request.payload[0] == 0x00 {
+                return vec![response];
+            }
+            let status = |status: &[u8]| {
+                let mut payload = vec![request.payload[1]];
+                payload.extend_from_slice(status);
+                MockController::raw(CommandType::Request, FunctionType::SetLearnMode, payload)
+            };
+            vec![
+                response,
+                status(&[0x01, 0x00, 0x00]),
+                // Group 1 is called "Lights" and contains nodes 3 and 4
+                MockController::application_command(
+                    1,
+                    &[0x21, 0x32, 0x01, 0x01, b'L', b'i', b'g', b'h', b't', b's'],
+                ),
+                MockController::application_command(1, &[0x21, 0x31, 0x02, 0x01, 0x03]),
+                MockController::application_command(1, &[0x21, 0x31, 0x03, 0x01, 0x04]),
+                status(&[0x06, 0x07, 0x00]),
+            ]
+        });
+        let groups = run_with_mock_controller(&controller, |driver| async move {
+            let options = LearnModeOptions::builder()
+                .timeout(Duration::from_millis(50))
+                .protocol_timeout(Duration::from_millis(50))
+                .build();
+            driver.begin_learn_mode(&options).await.unwrap();
+            driver.replicated_groups()
+        });
+
+        assert_eq!(
+            groups,
+            vec![ReplicationGroup {
+                id: 1,
+                name: Some("Lights".to_string()),
+                nodes: vec![NodeId::new(3u8), NodeId::new(4u8)],
+            }]
+        );
+        let completed = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::ReplicationCommandComplete)
+            .count();
+        assert_eq!(completed, 3);
+    }
+
+    #[test]
+    fn test_groups_are_transferred_to_new_controllers() {
+        let controller = MockController::new()
+            .on(FunctionType::AddNodeToNetwork, |_, request| {
+                if request.payload[0] & 0x0f == 0x05 {
+                    return vec![];
+                }
+                [
+                    &[0x01, 0x00, 0x00][..],
+                    &[0x02, 0x00, 0x00],
+                    // Adding controller 5
+                    &[0x04, 0x05, 0x03, 0x02, 0x02, 0x01],
+                    &[0x05, 0x05, 0x00],
+                ]
+                .into_iter()
+                .map(|status| {
+                    let mut payload = vec![request.payload[1]];
+                    payload.extend_from_slice(status);
+                    MockController::raw(
+                        CommandType::Request,
+                        FunctionType::AddNodeToNetwork,
+                        payload,
+                    )
+                })
+                .collect()
+            })
+            .on(FunctionType::ReplicationSendData, |_, request| {
+                let callback_id = *request.payload.last().unwrap();
+                vec![
+                    MockController::raw(
+                        CommandType::Response,
+                        FunctionType::ReplicationSendData,
+                        vec![0x01],
+                    ),
+                    MockController::raw(
+                        CommandType::Request,
+                        FunctionType::ReplicationSendData,
+                        vec![callback_id, 0x00],
+                    ),
+                ]
+            });
+        run_with_mock_controller(&controller, |driver| async move {
+            let options = InclusionOptions::builder()
+                .timeout(Duration::from_millis(50))
+                .protocol_timeout(Duration::from_millis(50))
+                .replication_groups(vec![ReplicationGroup {
+                    id: 2,
+                    name: None,
+                    nodes: vec![NodeId::new(3u8)],
+                }])
+                .build();
+            let node_id = driver.include_node(&options).await.unwrap();
+            assert_eq!(node_id, Some(NodeId::new(5u8)));
+        });
+
+        let transfers: Vec<_> = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::ReplicationSendData)
+            .collect();
+        assert_eq!(transfers.len(), 1);
+        // Node 5, 5 bytes of Controller Replication CC: Transfer Group 2 with node 3
+        assert_eq!(
+            &transfers[0].payload[..7],
+            &[0x05, 0x05, 0x21, 0x31, 0x01, 0x02, 0x03]
+        );
+    }
+}
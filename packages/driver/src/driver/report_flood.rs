@@ -0,0 +1,269 @@
+use super::{Driver, DriverActor, DriverEvent};
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::NotImplemented as CCNotImplemented;
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
+
+/// When the driver considers a node to flood the network with reports, e.g. a broken sensor
+/// that sends dozens of reports per second. Reports beyond the limit are discarded.
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct ReportFloodOptions {
+    /// Whether the reports of flooding nodes are limited. Default: true
+    #[builder(default = true)]
+    pub enabled: bool,
+    /// How many commands a node may send per window. Default: 30
+    #[builder(default = 30)]
+    pub max_reports: u32,
+    /// The time window in which the commands of a node are counted. Default: 10 s
+    #[builder(default = Duration::from_secs(10))]
+    pub window: Duration,
+    /// Whether the mitigation configured for a node is sent when it starts flooding. Default: true
+    #[builder(default = true)]
+    pub auto_mitigate: bool,
+}
+
+impl Default for ReportFloodOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// How many commands a node sent in the current window
+#[derive(Debug, Clone, Copy)]
+struct ReportRate {
+    window_start: Instant,
+    reports: u32,
+    flooding: bool,
+    /// How many commands were discarded since the node started flooding
+    discarded: u64,
+}
+
+/// What to do with a command after counting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReportRateOutcome {
+    Accepted,
+    /// The node stopped flooding. The command is accepted.
+    FloodEnded {
+        discarded: u64,
+    },
+    /// The node exceeded the limit. The command is discarded.
+    FloodStarted,
+    Discarded,
+}
+
+impl ReportRateOutcome {
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Self::Accepted | Self::FloodEnded { .. })
+    }
+}
+
+/// Counts the commands received from each node and remembers how to make them report less often
+#[derive(Default)]
+pub(crate) struct ReportFloodTracker {
+    rates: BTreeMap<NodeId, ReportRate>,
+    mitigations: BTreeMap<NodeId, CCRaw>,
+}
+
+impl ReportFloodTracker {
+    fn record(
+        &mut self,
+        node_id: NodeId,
+        now: Instant,
+        options: &ReportFloodOptions,
+    ) -> ReportRateOutcome {
+        let rate = self.rates.entry(node_id).or_insert(ReportRate {
+            window_start: now,
+            reports: 0,
+            flooding: false,
+            discarded: 0,
+        });
+
+        let mut ended = None;
+        let window_over = rate
+            .window_start
+            .checked_add(options.window)
+            .is_none_or(|end| now >= end);
+        if window_over {
+            // A whole window within the limit ends the flood
+            if rate.flooding && rate.reports <= options.max_reports {
+                rate.flooding = false;
+                ended = Some(core::mem::take(&mut rate.discarded));
+            }
+            rate.window_start = now;
+            rate.reports = 0;
+        }
+
+        rate.reports = rate.reports.saturating_add(1);
+        if rate.reports <= options.max_reports {
+            return match ended {
+                Some(discarded) => ReportRateOutcome::FloodEnded { discarded },
+                None => ReportRateOutcome::Accepted,
+            };
+        }
+
+        rate.discarded += 1;
+        if rate.flooding {
+            ReportRateOutcome::Discarded
+        } else {
+            rate.flooding = true;
+            ReportRateOutcome::FloodStarted
+        }
+    }
+}
+
+impl Driver {
+    /// Changes when nodes are considered to flood the network with reports
+    pub fn set_report_flood_options(&self, options: ReportFloodOptions) {
+        self.storage.report_flood_options().set(options);
+    }
+
+    pub fn report_flood_options(&self) -> ReportFloodOptions {
+        self.storage.report_flood_options().get()
+    }
+
+    /// Configures a command that makes the given node report less often, e.g. a Configuration
+    /// Set that raises a report threshold. It is sent when the node starts flooding the network.
+    /// `None` removes it.
+    pub fn set_report_flood_mitigation(&self, node_id: NodeId, mitigation: Option<CCRaw>) {
+        self.storage
+            .report_flood()
+            .update(|tracker| match mitigation {
+                Some(cc) => tracker.mitigations.insert(node_id, cc),
+                None => tracker.mitigations.remove(&node_id),
+            });
+    }
+
+    /// Whether the given node currently floods the network, so some of its reports are discarded
+    pub fn is_node_flooding(&self, node_id: NodeId) -> bool {
+        self.storage.report_flood().inspect(|tracker| {
+            tracker
+                .rates
+                .get(&node_id)
+                .is_some_and(|rate| rate.flooding)
+        })
+    }
+}
+
+impl DriverActor {
+    /// Counts a command received from the given node and decides whether to process it
+    pub(super) fn record_report(&self, node_id: NodeId) -> ReportRateOutcome {
+        let options = self.storage.report_flood_options().get();
+        if !options.enabled {
+            return ReportRateOutcome::Accepted;
+        }
+
+        let (outcome, mitigation) = self.storage.report_flood().update(|tracker| {
+            let outcome = tracker.record(node_id, Instant::now(), &options);
+            let mitigation = (outcome == ReportRateOutcome::FloodStarted && options.auto_mitigate)
+                .then(|| tracker.mitigations.get(&node_id).cloned())
+                .flatten();
+            (outcome, mitigation)
+        });
+
+        let log = self.node_log(node_id, EndpointIndex::Root);
+        match outcome {
+            ReportRateOutcome::FloodStarted => {
+                log.warn(|| {
+                    format!(
+                        "the node sent more than {} commands within {} s, discarding the excess",
+                        options.max_reports,
+                        options.window.as_secs()
+                    )
+                });
+                self.emit_event(DriverEvent::ReportFloodStarted { node_id });
+            }
+            ReportRateOutcome::FloodEnded { discarded } => {
+                log.info(|| {
+                    format!(
+                        "the node stopped flooding, {} commands were discarded",
+                        discarded
+                    )
+                });
+                self.emit_event(DriverEvent::ReportFloodEnded { node_id, discarded });
+            }
+            _ => {}
+        }
+
+        if let Some(mitigation) = mitigation {
+            log.info(|| "sending the command to make the node report less often");
+            let cc = CC::NotImplemented(CCNotImplemented {
+                cc_id: mitigation.cc_id,
+                cc_command: mitigation.cc_command,
+                payload: mitigation.payload,
+            })
+            .with_destination(node_id.into());
+            let now = Instant::now();
+            self.storage
+                .scheduler()
+                .update(|scheduler| scheduler.add(cc, now, None));
+        }
+
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let node_id = NodeId::new(2u8);
+        let options = ReportFloodOptions::builder()
+            .max_reports(2)
+            .window(Duration::from_secs(1))
+            .build();
+        let mut tracker = ReportFloodTracker::default();
+        let start = Instant::now();
+        let at = |millis| start.checked_add(Duration::from_millis(millis)).unwrap();
+
+        assert_eq!(
+            tracker.record(node_id, at(0), &options),
+            ReportRateOutcome::Accepted
+        );
+        assert_eq!(
+            tracker.record(node_id, at(100), &options),
+            ReportRateOutcome::Accepted
+        );
+        assert_eq!(
+            tracker.record(node_id, at(200), &options),
+            ReportRateOutcome::FloodStarted
+        );
+        assert_eq!(
+            tracker.record(node_id, at(300), &options),
+            ReportRateOutcome::Discarded
+        );
+
+        // Other nodes are counted separately
+        assert_eq!(
+            tracker.record(NodeId::new(3u8), at(300), &options),
+            ReportRateOutcome::Accepted
+        );
+
+        // The next window is still flooded, but the limit applies again
+        for millis in [1000, 1100] {
+            assert_eq!(
+                tracker.record(node_id, at(millis), &options),
+                ReportRateOutcome::Accepted
+            );
+        }
+        assert_eq!(
+            tracker.record(node_id, at(1200), &options),
+            ReportRateOutcome::Discarded
+        );
+
+        // A calm window ends the flood
+        assert_eq!(
+            tracker.record(node_id, at(2000), &options),
+            ReportRateOutcome::Accepted
+        );
+        assert_eq!(
+            tracker.record(node_id, at(3000), &options),
+            ReportRateOutcome::FloodEnded { discarded: 3 }
+        );
+    }
+}
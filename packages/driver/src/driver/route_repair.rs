@@ -0,0 +1,408 @@
+use super::{Driver, DriverEvent};
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
+
+/// When the driver repairs the routes to nodes it repeatedly fails to reach due to routing
+/// problems, i.e. when the controller reports the transmit status "Fail" or "No route"
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct RouteRepairOptions {
+    /// Whether the routes are repaired automatically. Default: true
+    #[builder(default = true)]
+    pub enabled: bool,
+    /// After how many consecutive routing failures the routes to a node are repaired. Default: 3
+    #[builder(default = 3)]
+    pub failure_threshold: u8,
+    /// How long to wait before the routes to the same node are repaired again. Default: 15 min
+    #[builder(default = Duration::from_secs(15 * 60))]
+    pub cooldown: Duration,
+    /// After how many route changes since its last neighbor update a node is considered to have
+    /// been moved, so it discovers its neighbors again. Default: 5
+    #[builder(default = 5)]
+    pub moved_threshold: u32,
+}
+
+impl Default for RouteRepairOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// The outcome of an attempt to repair the routes to a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteRepairResult {
+    /// Whether the node discovered its neighbors
+    pub neighbors_updated: bool,
+    /// Whether the node was assigned a new return route to the controller
+    pub return_route_assigned: bool,
+}
+
+impl RouteRepairResult {
+    pub fn success(&self) -> bool {
+        self.neighbors_updated && self.return_route_assigned
+    }
+}
+
+/// How well the routes to a node work and how often they had to be repaired
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RouteHealth {
+    /// How many commands in a row failed due to routing problems
+    pub consecutive_failures: u8,
+    /// How often the routes were repaired
+    pub repairs: u32,
+    /// How many of the repairs succeeded
+    pub successful_repairs: u32,
+    /// When the routes were last repaired
+    pub last_repair_at: Option<Instant>,
+    /// The outcome of the last repair
+    pub last_repair: Option<RouteRepairResult>,
+    /// How often the route to the node changed since it last discovered its neighbors
+    pub route_changes_since_neighbor_update: u32,
+    /// When the node last discovered its neighbors
+    pub last_neighbor_update_at: Option<Instant>,
+}
+
+/// Whether the given transmit status indicates that the node could not be reached
+/// because of a routing problem
+fn is_routing_failure(status: TransmitStatus) -> bool {
+    matches!(status, TransmitStatus::Fail | TransmitStatus::NoRoute)
+}
+
+/// Whether the given point in time is less than the cooldown ago
+fn is_cooling_down(since: Option<Instant>, cooldown: Duration, now: Instant) -> bool {
+    since
+        .and_then(|at| at.checked_add(cooldown))
+        .is_some_and(|until| now < until)
+}
+
+impl Driver {
+    /// Changes when the routes to unreachable nodes are repaired automatically
+    pub fn set_route_repair_options(&self, options: RouteRepairOptions) {
+        self.storage.route_repair_options().set(options);
+    }
+
+    pub fn route_repair_options(&self) -> RouteRepairOptions {
+        self.storage.route_repair_options().get()
+    }
+
+    /// Returns how well the routes to the given node work, if a command was sent to it
+    pub fn route_health(&self, node_id: NodeId) -> Option<RouteHealth> {
+        self.storage
+            .route_health()
+            .inspect(|health| health.get(&node_id).copied())
+    }
+
+    /// Remembers whether a command could be routed to the given node
+    pub(super) fn record_transmit_status(&self, node_id: NodeId, status: TransmitStatus) {
+        if node_id == NodeId::broadcast() {
+            return;
+        }
+        self.storage.route_health().update(|health| {
+            let health = health.entry(node_id).or_default();
+            if is_routing_failure(status) {
+                health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+            } else if status == TransmitStatus::Ok {
+                health.consecutive_failures = 0;
+            }
+        });
+    }
+
+    /// Repairs the routes to the given node if it could not be reached too often in a row
+    pub(super) async fn repair_routes_if_needed(&self, node_id: NodeId) {
+        let options = self.route_repair_options();
+        if !options.enabled {
+            return;
+        }
+        // Sleeping nodes cannot update their neighbors
+        if self.can_sleep(node_id) {
+            return;
+        }
+
+        let now = Instant::now();
+        let failures = self.storage.route_health().update(|health| {
+            let health = health.get_mut(&node_id)?;
+            if health.consecutive_failures < options.failure_threshold {
+                return None;
+            }
+            if is_cooling_down(health.last_repair_at, options.cooldown, now) {
+                return None;
+            }
+            // Remember the attempt right away, so concurrent commands don't repair again
+            health.last_repair_at = Some(now);
+            Some(health.consecutive_failures)
+        });
+        let Some(failures) = failures else {
+            return;
+        };
+
+        self.repair_routes(node_id, failures).await;
+    }
+
+    /// Remembers that a command to the given node took a different route than the one before it
+    pub(super) fn record_route_change(&self, node_id: NodeId) {
+        self.storage.route_health().update(|health| {
+            let health = health.entry(node_id).or_default();
+            health.route_changes_since_neighbor_update += 1;
+        });
+    }
+
+    /// Lets the given node discover its neighbors again if its route changed so often that it was
+    /// probably moved to a different location
+    pub(super) async fn update_neighbors_if_moved(&self, node_id: NodeId) {
+        let options = self.route_repair_options();
+        if !options.enabled || self.can_sleep(node_id) {
+            return;
+        }
+
+        let now = Instant::now();
+        let route_changes = self.storage.route_health().update(|health| {
+            let health = health.get_mut(&node_id)?;
+            if health.route_changes_since_neighbor_update < options.moved_threshold
+                || is_cooling_down(health.last_neighbor_update_at, options.cooldown, now)
+            {
+                return None;
+            }
+            // Remember the attempt right away, so concurrent commands don't update again
+            health.last_neighbor_update_at = Some(now);
+            Some(core::mem::take(&mut health.route_changes_since_neighbor_update))
+        });
+        let Some(route_changes) = route_changes else {
+            return;
+        };
+
+        self.node_log(node_id, EndpointIndex::Root).info(|| {
+            format!(
+                "the route to the node changed {} times, it was probably moved. \
+                 Updating its neighbors...",
+                route_changes
+            )
+        });
+        if self.request_node_neighbor_update(node_id, None).await.is_ok() {
+            self.neighbors_updated(node_id).await;
+        }
+    }
+
+    /// Refreshes the cached neighbors after the given node discovered them again
+    async fn neighbors_updated(&self, node_id: NodeId) {
+        self.storage.route_health().update(|health| {
+            let health = health.entry(node_id).or_default();
+            health.route_changes_since_neighbor_update = 0;
+            health.last_neighbor_update_at = Some(Instant::now());
+        });
+        if self.refresh_node_neighbors(node_id).await.is_err() {
+            self.forget_node_neighbors(node_id);
+        }
+    }
+
+    fn can_sleep(&self, node_id: NodeId) -> bool {
+        self.inspect_node_protocol_data(node_id, |data| {
+            !data.listening && data.frequent_listening.is_none()
+        })
+        .unwrap_or(true)
+    }
+
+    /// Lets the node discover its neighbors and assigns it a new return route to the controller
+    async fn repair_routes(&self, node_id: NodeId, failures: u8) {
+        let log = self.node_log(node_id, EndpointIndex::Root);
+        log.warn(|| {
+            format!(
+                "the node could not be reached {} times in a row due to routing problems, \
+                 repairing its routes...",
+                failures
+            )
+        });
+        self.emit_event(DriverEvent::RouteRepairStarted { node_id, failures });
+
+        let neighbors_updated = self
+            .request_node_neighbor_update(node_id, None)
+            .await
+            .is_ok();
+        if neighbors_updated {
+            self.neighbors_updated(node_id).await;
+        }
+        let own_node_id = self.serial_api.storage.own_node_id();
+        let return_route_assigned = self
+            .assign_return_route(node_id, own_node_id, None)
+            .await
+            .is_ok();
+        let result = RouteRepairResult {
+            neighbors_updated,
+            return_route_assigned,
+        };
+
+        self.storage.route_health().update(|health| {
+            let health = health.entry(node_id).or_default();
+            health.repairs += 1;
+            if result.success() {
+                health.successful_repairs += 1;
+                health.consecutive_failures = 0;
+            }
+            health.last_repair = Some(result);
+        });
+
+        if result.success() {
+            log.info(|| "the routes were repaired");
+        } else {
+            log.warn(|| "repairing the routes failed");
+        }
+        self.emit_event(DriverEvent::RouteRepairFinished { node_id, result });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use crate::{ExecNodeCommandError, NodeStorage};
+    use zwave_cc::commandclass::{CCAddressable, NoOperationCC};
+
+    const NODE_ID: u8 = 2;
+
+    fn mock_controller() -> MockController {
+        MockController::new()
+            .on(FunctionType::SendData, |_, request| {
+                MockController::send_data(request, TransmitStatus::Fail)
+            })
+            .on(FunctionType::RequestNodeNeighborUpdate, |_, request| {
+                let callback_id = *request.payload.last().unwrap();
+                // Update started, then done
+                [0x21, 0x22]
+                    .into_iter()
+                    .map(|status| {
+                        MockController::raw(
+                            CommandType::Request,
+                            FunctionType::RequestNodeNeighborUpdate,
+                            vec![callback_id, status],
+                        )
+                    })
+                    .collect()
+            })
+            .on(FunctionType::AssignReturnRoute, |_, request| {
+                let callback_id = *request.payload.last().unwrap();
+                vec![
+                    MockController::raw(
+                        CommandType::Response,
+                        FunctionType::AssignReturnRoute,
+                        vec![0x01],
+                    ),
+                    MockController::raw(
+                        CommandType::Request,
+                        FunctionType::AssignReturnRoute,
+                        vec![callback_id, 0x00],
+                    ),
+                ]
+            })
+    }
+
+    #[test]
+    fn test_routes_are_repaired_after_repeated_failures() {
+        let controller = mock_controller();
+        let health = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(
+                    NodeId::new(NODE_ID),
+                    NodeStorage::new(mock_protocol_data(true)),
+                );
+            });
+
+            let cc = NoOperationCC {}.with_destination(NodeId::new(NODE_ID).into());
+            for _ in 0..4 {
+                let result = driver.exec_node_command(&cc.clone().into(), None).await;
+                assert!(matches!(result, Err(ExecNodeCommandError::NodeNoAck)));
+            }
+            driver.route_health(NodeId::new(NODE_ID)).unwrap()
+        });
+
+        // The routes were repaired once after the third failure
+        assert_eq!(health.repairs, 1);
+        assert_eq!(health.successful_repairs, 1);
+        assert_eq!(health.consecutive_failures, 1);
+        let received: Vec<_> = controller
+            .received()
+            .iter()
+            .map(|cmd| cmd.function_type)
+            .filter(|function_type| *function_type != FunctionType::SendData)
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                FunctionType::RequestNodeNeighborUpdate,
+                FunctionType::AssignReturnRoute
+            ]
+        );
+
+        let events = controller.take_events();
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, DriverEvent::RouteRepairStarted { failures: 3, .. }))
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DriverEvent::RouteRepairFinished { result, .. } if result.success()
+        )));
+    }
+
+    #[test]
+    fn test_route_repair_can_be_disabled() {
+        let controller = mock_controller();
+        run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(
+                    NodeId::new(NODE_ID),
+                    NodeStorage::new(mock_protocol_data(true)),
+                );
+            });
+            driver.set_route_repair_options(RouteRepairOptions::builder().enabled(false).build());
+
+            let cc = NoOperationCC {}.with_destination(NodeId::new(NODE_ID).into());
+            for _ in 0..3 {
+                let _ = driver.exec_node_command(&cc.clone().into(), None).await;
+            }
+            assert_eq!(
+                driver
+                    .route_health(NodeId::new(NODE_ID))
+                    .map(|health| health.consecutive_failures),
+                Some(3)
+            );
+        });
+
+        assert!(
+            controller
+                .received()
+                .iter()
+                .all(|cmd| cmd.function_type == FunctionType::SendData)
+        );
+    }
+
+    #[test]
+    fn test_moved_nodes_update_their_neighbors() {
+        let controller = mock_controller();
+        let health = run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(NODE_ID);
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(node_id, NodeStorage::new(mock_protocol_data(true)));
+            });
+
+            for _ in 0..4 {
+                driver.record_route_change(node_id);
+            }
+            driver.update_neighbors_if_moved(node_id).await;
+            driver.record_route_change(node_id);
+            driver.update_neighbors_if_moved(node_id).await;
+            driver.route_health(node_id).unwrap()
+        });
+
+        // The neighbors were updated once after the fifth route change
+        assert_eq!(health.route_changes_since_neighbor_update, 0);
+        assert!(health.last_neighbor_update_at.is_some());
+        let received: Vec<_> = controller
+            .received()
+            .iter()
+            .map(|cmd| cmd.function_type)
+            .collect();
+        assert_eq!(received, vec![FunctionType::RequestNodeNeighborUpdate]);
+    }
+}
@@ -0,0 +1,96 @@
+use super::Driver;
+use alloc::collections::BTreeMap;
+use zwave_core::prelude::*;
+use zwave_core::security::{MpanState, PersistedSpan};
+
+/// The S2 nonce state the driver has established with the nodes. Persisting it lets the driver
+/// continue to communicate with secure nodes after a quick restart, instead of resynchronizing
+/// the nonces with every node.
+///
+/// This state is as secret as the network keys. Anyone who knows it can predict the nonces
+/// used with the nodes, so it must be stored with the same care.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PersistedS2State {
+    /// The SPANs that were established with each node
+    pub spans: BTreeMap<NodeId, PersistedSpan>,
+    /// The MPANs that are used to decrypt multicasts from each node, by group ID
+    pub peer_mpans: BTreeMap<NodeId, BTreeMap<u8, MpanState>>,
+}
+
+impl Driver {
+    /// Exports the S2 nonce state. This should happen right before the driver is stopped,
+    /// because every further secure command changes the state.
+    pub(crate) fn export_s2_state(&self) -> PersistedS2State {
+        match self.storage.security_manager2() {
+            Some(sec_man) => PersistedS2State {
+                spans: sec_man.export_spans(),
+                peer_mpans: sec_man.export_peer_mpans(),
+            },
+            None => self.storage.pending_s2_state().cloned(),
+        }
+    }
+
+    /// Restores a previously exported S2 nonce state. If the nodes have changed their state in
+    /// the meantime, the nonces are resynchronized as usual.
+    pub(crate) fn restore_s2_state(&self, state: &PersistedS2State) {
+        // The security manager may not have been created yet. If so, it picks up the state later.
+        match self.storage.security_manager2() {
+            Some(sec_man) => {
+                sec_man.restore_spans(&state.spans);
+                sec_man.restore_peer_mpans(&state.peer_mpans);
+            }
+            None => self.storage.pending_s2_state().set(state.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::mock_driver;
+    use zwave_core::security::{
+        EntropyInput, MPANTableEntry, S2_ENTROPY_INPUT_SIZE, S2_MPAN_STATE_SIZE, SecurityManager2,
+        SecurityManager2Storage,
+    };
+    use zwave_pal::prelude::*;
+
+    fn security_manager() -> SecurityManager2 {
+        let sec_man = SecurityManager2::new(Arc::new(SecurityManager2Storage::new()));
+        sec_man.set_key(SecurityClass::S2Authenticated, [1u8; 16]);
+        sec_man
+    }
+
+    #[test]
+    fn test_s2_state_survives_restart() {
+        let before = security_manager();
+        let ei: EntropyInput = [2; S2_ENTROPY_INPUT_SIZE].into();
+        before.initialize_span(NodeId::new(2u8), SecurityClass::S2Authenticated, ei, ei);
+        before.store_peer_mpan(
+            NodeId::new(2u8),
+            1,
+            MPANTableEntry::MPAN {
+                current_mpan: [3; S2_MPAN_STATE_SIZE].into(),
+            },
+        );
+        let first = mock_driver().driver;
+        first.storage.set_security_manager2(Some(before.clone()));
+        let exported = first.export_s2_state();
+
+        // The state can be restored before the security manager is created
+        let pending = mock_driver().driver;
+        pending.restore_s2_state(&exported);
+        assert_eq!(pending.export_s2_state(), exported);
+
+        let second = mock_driver().driver;
+        let after = security_manager();
+        second.storage.set_security_manager2(Some(after.clone()));
+        second.restore_s2_state(&exported);
+        assert_eq!(second.export_s2_state(), exported);
+
+        // Both sides generate the same nonces, so no resynchronization is necessary
+        assert_eq!(
+            after.next_nonce(NodeId::new(2u8), false),
+            before.next_nonce(NodeId::new(2u8), false)
+        );
+    }
+}
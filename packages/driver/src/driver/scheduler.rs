@@ -0,0 +1,559 @@
+use super::{Driver, DriverEvent, ExecNodeCommandError};
+use alloc::collections::BTreeMap;
+use bytes::Bytes;
+use core::time::Duration;
+use futures::stream::{self, StreamExt};
+use zwave_cc::prelude::*;
+use zwave_core::parse::Parsable;
+use zwave_core::prelude::*;
+use zwave_pal::channel::{Receiver, Sender};
+use zwave_pal::prelude::*;
+use zwave_pal::time::{DateTime, Instant, MaybeSleep, Timer, WallClock};
+
+/// How long a scheduled command may take to be sent before it is considered failed
+const SCHEDULED_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// When a scheduled command should be sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// Once, at the given point in time
+    At(Instant),
+    /// Once, after the given time has passed
+    After(Duration),
+    /// Repeatedly, every time the given interval has passed
+    Every(Duration),
+    /// Repeatedly, at the given time of day in the local time zone.
+    /// This needs the system time, so the command is not scheduled if it is unknown.
+    Calendar(CalendarSchedule),
+}
+
+/// A time of day on selected days of the week, like a weekly cron entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarSchedule {
+    weekdays: u8,
+    hour: u8,
+    minute: u8,
+}
+
+impl CalendarSchedule {
+    /// At the given time on the selected days of the week. The days are a bitmask,
+    /// from bit 0 (Monday) to bit 6 (Sunday).
+    ///
+    /// Returns `None` if the hour is greater than 23 or the minute is greater than 59.
+    pub fn new(weekdays: u8, hour: u8, minute: u8) -> Option<Self> {
+        (hour <= 23 && minute <= 59).then_some(Self {
+            weekdays: weekdays & 0b0111_1111,
+            hour,
+            minute,
+        })
+    }
+
+    /// Every day at the given time. Returns `None` if the time is invalid.
+    pub fn daily(hour: u8, minute: u8) -> Option<Self> {
+        Self::new(0b0111_1111, hour, minute)
+    }
+
+    /// The days of the week as a bitmask, from bit 0 (Monday) to bit 6 (Sunday)
+    pub fn weekdays(&self) -> u8 {
+        self.weekdays
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Returns how long it is from the given local time until the next occurrence that is
+    /// more than `min_secs` seconds away, or `None` if no day of the week is selected
+    fn time_until_next(&self, local: &DateTime, min_secs: u64) -> Option<Duration> {
+        const DAY: u64 = 24 * 60 * 60;
+        let now = local.hour as u64 * 3600 + local.minute as u64 * 60 + local.second as u64;
+        let at = self.hour as u64 * 3600 + self.minute as u64 * 60;
+        // The weekday of the wall clock goes from 1 (Monday) to 7 (Sunday)
+        let today = local.weekday.saturating_sub(1) as u64;
+        (0..=7u64).find_map(|days| {
+            let weekday = (today + days) % 7;
+            if self.weekdays & (1 << weekday) == 0 {
+                return None;
+            }
+            let secs = (days * DAY + at).checked_sub(now)?;
+            (secs > min_secs).then(|| Duration::from_secs(secs))
+        })
+    }
+
+    /// Determines when the command is due next by reading the system time
+    fn next_due(&self, now: Instant, min_secs: u64) -> Option<Instant> {
+        let clock = WallClock::now()?;
+        now.checked_add(self.time_until_next(&clock.local, min_secs)?)
+    }
+}
+
+/// How a scheduled command is repeated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// Every time the given interval has passed
+    Every(Duration),
+    /// At the next time of day that matches the calendar schedule
+    Calendar(CalendarSchedule),
+}
+
+/// Identifies a scheduled command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScheduledCommandId(u32);
+
+impl From<ScheduledCommandId> for u32 {
+    fn from(id: ScheduledCommandId) -> Self {
+        id.0
+    }
+}
+
+/// A scheduled command in a form the application can persist.
+///
+/// Since instants cannot be stored, only the time until the command is due is exported.
+/// When restoring, the application must pass how much time has passed since the export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistedCommand {
+    pub node_id: NodeId,
+    pub endpoint: EndpointIndex,
+    /// The serialized CC, without encapsulation
+    pub payload: Vec<u8>,
+    /// How long after the export the command was due
+    pub due_in: Duration,
+    /// How the command is repeated, if at all
+    pub repeat: Option<Repeat>,
+}
+
+struct ScheduledCommand {
+    cc: WithAddress<CC>,
+    due: Instant,
+    repeat: Option<Repeat>,
+}
+
+pub(crate) struct Scheduler {
+    commands: BTreeMap<ScheduledCommandId, ScheduledCommand>,
+    next_id: u32,
+    /// The nodes whose clocks are checked periodically, and when they are checked next
+    clock_checks: BTreeMap<NodeId, Instant>,
+    wakeup_tx: Sender<()>,
+    /// Taken by the task that executes the scheduled commands
+    wakeup_rx: Option<Receiver<()>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        let (wakeup_tx, wakeup_rx) = zwave_pal::channel::channel(1);
+        Self {
+            commands: BTreeMap::new(),
+            next_id: 1,
+            clock_checks: BTreeMap::new(),
+            wakeup_tx,
+            wakeup_rx: Some(wakeup_rx),
+        }
+    }
+
+    pub(super) fn add(
+        &mut self,
+        cc: WithAddress<CC>,
+        due: Instant,
+        repeat: Option<Repeat>,
+    ) -> ScheduledCommandId {
+        let id = ScheduledCommandId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.commands
+            .insert(id, ScheduledCommand { cc, due, repeat });
+        // The scheduler may need to wake up earlier than planned
+        let _ = self.wakeup_tx.try_send(());
+        id
+    }
+
+    pub(super) fn remove(&mut self, id: ScheduledCommandId) -> bool {
+        self.commands.remove(&id).is_some()
+    }
+
+    /// Schedules the next check whether the clock of the given node needs to be set
+    pub(super) fn add_clock_check(&mut self, node_id: NodeId, due: Instant) {
+        self.clock_checks.insert(node_id, due);
+        let _ = self.wakeup_tx.try_send(());
+    }
+
+    #[cfg(test)]
+    pub(super) fn due(&self, id: ScheduledCommandId) -> Option<Instant> {
+        self.commands.get(&id).map(|cmd| cmd.due)
+    }
+
+    #[cfg(test)]
+    pub(super) fn len(&self) -> usize {
+        self.commands.len() + self.clock_checks.len()
+    }
+
+    /// Returns the commands that are due and reschedules repeating ones
+    fn take_due(&mut self, now: Instant) -> Vec<(ScheduledCommandId, WithAddress<CC>)> {
+        let due: Vec<_> = self
+            .commands
+            .iter()
+            .filter(|(_, cmd)| cmd.due <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        due.into_iter()
+            .filter_map(|id| {
+                let cmd = self.commands.get_mut(&id)?;
+                let cc = cmd.cc.clone();
+                let next = match cmd.repeat {
+                    Some(Repeat::Every(interval)) => now.checked_add(interval),
+                    // The command was just sent, so skip the occurrence in this minute
+                    Some(Repeat::Calendar(calendar)) => calendar.next_due(now, 60),
+                    None => None,
+                };
+                match next {
+                    Some(next) => cmd.due = next,
+                    None => {
+                        self.commands.remove(&id);
+                    }
+                }
+                Some((id, cc))
+            })
+            .collect()
+    }
+
+    /// Returns the nodes whose clocks are due to be checked. They must be scheduled again.
+    fn take_due_clock_checks(&mut self, now: Instant) -> Vec<NodeId> {
+        let due: Vec<_> = self
+            .clock_checks
+            .iter()
+            .filter(|(_, due)| **due <= now)
+            .map(|(node_id, _)| *node_id)
+            .collect();
+        for node_id in &due {
+            self.clock_checks.remove(node_id);
+        }
+        due
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.commands
+            .values()
+            .map(|cmd| cmd.due)
+            .chain(self.clock_checks.values().copied())
+            .min()
+    }
+
+    fn export(&self, now: Instant) -> Vec<PersistedCommand> {
+        self.commands
+            .values()
+            .filter_map(|cmd| {
+                let node_id = match cmd.cc.address().destination {
+                    Destination::Singlecast(node_id) => node_id,
+                    Destination::Broadcast => NodeId::broadcast(),
+                    // Multicast commands are not persisted
+                    Destination::Multicast(_) => return None,
+                };
+                let payload = (*cmd.cc).clone().as_raw(&CCEncodingContext::default());
+                Some(PersistedCommand {
+                    node_id,
+                    endpoint: cmd.cc.address().endpoint_index,
+                    payload: payload.as_bytes().to_vec(),
+                    due_in: cmd.due.checked_duration_since(now).unwrap_or_default(),
+                    repeat: cmd.repeat,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Decodes a CC that was persisted without encapsulation and addresses it to the given node.
+/// Returns `None` if the CC cannot be decoded.
+pub(super) fn decode_persisted_cc(
+    node_id: NodeId,
+    endpoint: EndpointIndex,
+    payload: &[u8],
+) -> Option<WithAddress<CC>> {
+    let cc = CCRaw::parse(&mut Bytes::copy_from_slice(payload))
+        .ok()
+        .and_then(|raw| CC::try_from_raw(raw, CCParsingContext::default()).ok())?;
+    let destination = if node_id == NodeId::broadcast() {
+        Destination::Broadcast
+    } else {
+        Destination::Singlecast(node_id)
+    };
+    Some(
+        cc.with_destination(destination)
+            .with_endpoint_index(endpoint),
+    )
+}
+
+impl Driver {
+    /// Schedules a command to be sent later, or repeatedly. The commands are only sent
+    /// while [`run_scheduler`](Self::run_scheduler) is running.
+    ///
+    /// Returns `None` if a calendar schedule has no next occurrence, because it selects no day
+    /// of the week or the system time is unknown.
+    pub fn schedule_command(
+        &self,
+        cc: WithAddress<CC>,
+        schedule: Schedule,
+    ) -> Option<ScheduledCommandId> {
+        let now = Instant::now();
+        let (due, repeat) = match schedule {
+            Schedule::At(at) => (at, None),
+            Schedule::After(delay) => (now.checked_add(delay).unwrap_or(now), None),
+            Schedule::Every(interval) => (
+                now.checked_add(interval).unwrap_or(now),
+                Some(Repeat::Every(interval)),
+            ),
+            Schedule::Calendar(calendar) => {
+                let Some(due) = calendar.next_due(now, 0) else {
+                    self.driver_log().warn(|| {
+                        "cannot schedule the command, the calendar schedule has no next occurrence"
+                    });
+                    return None;
+                };
+                (due, Some(Repeat::Calendar(calendar)))
+            }
+        };
+        Some(
+            self.storage
+                .scheduler()
+                .update(|scheduler| scheduler.add(cc, due, repeat)),
+        )
+    }
+
+    /// Cancels a scheduled command. Returns whether the command was still scheduled.
+    pub fn cancel_scheduled_command(&self, id: ScheduledCommandId) -> bool {
+        self.storage
+            .scheduler()
+            .update(|scheduler| scheduler.remove(id))
+    }
+
+    /// Exports all scheduled commands
+    pub(crate) fn export_scheduled_commands(&self) -> Vec<PersistedCommand> {
+        self.storage
+            .scheduler()
+            .inspect(|scheduler| scheduler.export(Instant::now()))
+    }
+
+    /// Schedules previously exported commands again. `elapsed` is the time that has
+    /// passed since the export. Commands that were due in the meantime are sent immediately.
+    /// Commands that cannot be decoded are skipped.
+    pub(crate) fn restore_scheduled_commands(
+        &self,
+        commands: &[PersistedCommand],
+        elapsed: Duration,
+    ) -> Vec<ScheduledCommandId> {
+        let now = Instant::now();
+        commands
+            .iter()
+            .filter_map(|persisted| {
+                let cc =
+                    decode_persisted_cc(persisted.node_id, persisted.endpoint, &persisted.payload)?;
+                let due_in = persisted.due_in.saturating_sub(elapsed);
+                let due = now.checked_add(due_in).unwrap_or(now);
+                Some(
+                    self.storage
+                        .scheduler()
+                        .update(|scheduler| scheduler.add(cc, due, persisted.repeat)),
+                )
+            })
+            .collect()
+    }
+
+    /// Sends the scheduled commands when they are due. This must be running for scheduled
+    /// commands to be sent, and must only be run once per driver instance.
+    ///
+    /// For each command, a [`DriverEvent::ScheduledCommandSent`] or
+    /// [`DriverEvent::ScheduledCommandFailed`] event is emitted.
+    pub async fn run_scheduler(&self) {
+        let mut wakeup_rx = self
+            .storage
+            .scheduler()
+            .update(|scheduler| scheduler.wakeup_rx.take())
+            .expect("The scheduler is already running");
+
+        loop {
+            let now = Instant::now();
+            let due = self
+                .storage
+                .scheduler()
+                .update(|scheduler| scheduler.take_due(now));
+
+            // Send the due commands concurrently and give up after a while,
+            // so an unreachable node does not hold up the other commands
+            stream::iter(due)
+                .for_each_concurrent(None, |(id, cc)| async move {
+                    let result = zwave_pal::select_biased! {
+                        result = self.exec_node_command(&cc, None) => result.map(|_| ()),
+                        _ = Timer::after(SCHEDULED_COMMAND_TIMEOUT) => {
+                            Err(ExecNodeCommandError::NodeTimeout)
+                        },
+                    };
+                    match result {
+                        Ok(()) => self.emit_event(DriverEvent::ScheduledCommandSent { id }),
+                        Err(error) => {
+                            self.driver_log().warn(|| {
+                                format!("failed to send scheduled command {:?}: {}", id, error)
+                            });
+                            self.emit_event(DriverEvent::ScheduledCommandFailed { id, error })
+                        }
+                    }
+                })
+                .await;
+
+            let clock_checks = self
+                .storage
+                .scheduler()
+                .update(|scheduler| scheduler.take_due_clock_checks(now));
+            for node_id in clock_checks {
+                self.check_clock(node_id).await;
+            }
+
+            let next_due = self
+                .storage
+                .scheduler()
+                .inspect(|scheduler| scheduler.next_due());
+            let sleep = MaybeSleep::new(next_due.map(|due| {
+                due.checked_duration_since(Instant::now())
+                    .unwrap_or_default()
+            }));
+            zwave_pal::select_biased! {
+                _ = wakeup_rx.recv() => {},
+                _ = sleep => {},
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use zwave_cc::commandclass::{BasicCCSet, NoOperationCC};
+
+    fn basic_set(node_id: u8) -> WithAddress<CC> {
+        CC::from(BasicCCSet {
+            target_value: LevelSet::Level(50),
+        })
+        .with_destination(NodeId::new(node_id).into())
+    }
+
+    #[test]
+    fn test_repeating_commands_are_rescheduled() {
+        let mut scheduler = Scheduler::new();
+        let start = Instant::now();
+        let interval = Duration::from_secs(10);
+        let once = scheduler.add(basic_set(2), start, None);
+        let repeating = scheduler.add(basic_set(3), start, Some(Repeat::Every(interval)));
+
+        let due: Vec<_> = scheduler
+            .take_due(start)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(due, vec![once, repeating]);
+        assert_eq!(scheduler.next_due(), start.checked_add(interval));
+        assert!(scheduler.take_due(start).is_empty());
+
+        assert!(scheduler.remove(repeating));
+        assert_eq!(scheduler.next_due(), None);
+    }
+
+    #[test]
+    fn test_calendar_schedule() {
+        // Wednesday, 06:30:15
+        let local = DateTime {
+            year: 2024,
+            month: 5,
+            day: 15,
+            weekday: 3,
+            hour: 6,
+            minute: 30,
+            second: 15,
+        };
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+
+        // Later today
+        let daily = CalendarSchedule::daily(7, 30).unwrap();
+        assert_eq!(
+            daily.time_until_next(&local, 0),
+            Some(hours(1) - Duration::from_secs(15))
+        );
+        // Already passed today, so tomorrow
+        let daily = CalendarSchedule::daily(6, 0).unwrap();
+        assert_eq!(
+            daily.time_until_next(&local, 0),
+            Some(hours(24) - Duration::from_secs(30 * 60 + 15))
+        );
+        // Only on Mondays, so next week
+        let mondays = CalendarSchedule::new(0b0000_0001, 6, 30).unwrap();
+        assert_eq!(
+            mondays.time_until_next(&local, 0),
+            Some(hours(5 * 24) - Duration::from_secs(15))
+        );
+        // Only on Wednesdays, but the occurrence in this minute was just sent
+        let wednesdays = CalendarSchedule::new(0b0000_0100, 6, 31).unwrap();
+        assert_eq!(
+            wednesdays.time_until_next(&local, 60),
+            Some(hours(7 * 24) + Duration::from_secs(45))
+        );
+        // No day selected
+        let never = CalendarSchedule::new(0, 6, 30).unwrap();
+        assert_eq!(never.time_until_next(&local, 0), None);
+        // Invalid times of day are rejected
+        assert_eq!(CalendarSchedule::daily(24, 0), None);
+        assert_eq!(CalendarSchedule::daily(23, 60), None);
+    }
+
+    #[test]
+    fn test_export_and_restore() {
+        let controller = MockController::new();
+        run_with_mock_controller(&controller, |driver| async move {
+            let cc = basic_set(2).with_endpoint_index(EndpointIndex::Endpoint(1));
+            driver.schedule_command(cc.clone(), Schedule::After(Duration::from_secs(60)));
+
+            let exported = driver.export_scheduled_commands();
+            assert_eq!(exported.len(), 1);
+            assert_eq!(exported[0].node_id, NodeId::new(2u8));
+            assert_eq!(exported[0].endpoint, EndpointIndex::Endpoint(1));
+            assert!(exported[0].due_in <= Duration::from_secs(60));
+
+            let restored = driver.restore_scheduled_commands(&exported, Duration::from_secs(120));
+            assert_eq!(restored.len(), 1);
+            let restored_cc = driver.storage.scheduler().inspect(|scheduler| {
+                scheduler
+                    .commands
+                    .get(&restored[0])
+                    .map(|cmd| cmd.cc.clone())
+            });
+            assert_eq!(restored_cc, Some(cc));
+        });
+    }
+
+    #[test]
+    fn test_due_commands_are_sent() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            let cc = CC::from(NoOperationCC {}).with_destination(NodeId::new(2u8).into());
+            driver.schedule_command(cc.clone(), Schedule::After(Duration::from_millis(20)));
+            let cancelled = driver
+                .schedule_command(cc, Schedule::After(Duration::from_millis(20)))
+                .unwrap();
+            assert!(driver.cancel_scheduled_command(cancelled));
+
+            zwave_pal::select_biased! {
+                _ = driver.run_scheduler() => {},
+                _ = Timer::after(Duration::from_millis(200)) => {},
+            }
+        });
+
+        let sent = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            .count();
+        assert_eq!(sent, 1);
+    }
+}
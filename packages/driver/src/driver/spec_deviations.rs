@@ -0,0 +1,115 @@
+use super::{Driver, DriverActor};
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+/// How often a node sent a CC that deviates from the specification in a tolerated way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecDeviationStatistics {
+    pub node_id: NodeId,
+    pub cc_id: CommandClasses,
+    pub deviation: SpecDeviation,
+    pub count: u64,
+}
+
+impl Driver {
+    /// Changes how strictly received CCs are checked against the specification
+    pub fn set_parsing_strictness(&self, strictness: ParsingStrictness) {
+        self.storage.parsing_strictness().set(strictness);
+    }
+
+    pub fn parsing_strictness(&self) -> ParsingStrictness {
+        self.storage.parsing_strictness().get()
+    }
+
+    /// Returns the deviations from the specification that were tolerated
+    /// in lenient mode, grouped by node and CC
+    pub fn spec_deviations(&self) -> Vec<SpecDeviationStatistics> {
+        self.storage.spec_deviations().inspect(|deviations| {
+            deviations
+                .iter()
+                .map(
+                    |(&(node_id, cc_id, deviation), &count)| SpecDeviationStatistics {
+                        node_id,
+                        cc_id,
+                        deviation,
+                        count,
+                    },
+                )
+                .collect()
+        })
+    }
+}
+
+impl DriverActor {
+    /// Records a deviation from the specification that was tolerated while parsing a CC
+    pub(super) fn handle_spec_deviation(
+        &self,
+        node_id: NodeId,
+        cc_id: CommandClasses,
+        deviation: SpecDeviation,
+    ) {
+        self.storage.spec_deviations().update(|deviations| {
+            *deviations.entry((node_id, cc_id, deviation)).or_default() += 1;
+        });
+        self.node_log(node_id, EndpointIndex::Root).warn(|| {
+            format!(
+                "tolerated a deviation from the specification in a {} CC: {}",
+                cc_id, deviation
+            )
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use core::time::Duration;
+    use zwave_cc::commandclass::NoOperationCC;
+    use zwave_pal::time::Timer;
+
+    /// Lets node 2 send a Basic CC Report with a target value, but without the duration.
+    /// Returns the tolerated deviations.
+    fn receive_report_without_duration(
+        strictness: ParsingStrictness,
+    ) -> Vec<SpecDeviationStatistics> {
+        let controller =
+            MockController::new().on(FunctionType::SendData, move |controller, request| {
+                let mut ret = MockController::send_data_ok(request);
+                if controller.received().len() == 1 {
+                    ret.push(MockController::application_command(
+                        2,
+                        &[0x20, 0x03, 0x00, 0x63],
+                    ));
+                }
+                ret
+            });
+        run_with_mock_controller(&controller, |driver| async move {
+            driver.set_parsing_strictness(strictness);
+            let cc = CC::from(NoOperationCC {}).with_destination(NodeId::new(2u8).into());
+            driver.exec_node_command(&cc, None).await.unwrap();
+            Timer::after(Duration::from_millis(50)).await;
+            driver.spec_deviations()
+        })
+    }
+
+    #[test]
+    fn test_lenient_parsing() {
+        assert_eq!(
+            receive_report_without_duration(ParsingStrictness::Lenient),
+            vec![SpecDeviationStatistics {
+                node_id: NodeId::new(2u8),
+                cc_id: CommandClasses::Basic,
+                deviation: SpecDeviation::MissingDuration,
+                count: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_strict_parsing() {
+        // The CC is rejected instead
+        assert!(receive_report_without_duration(ParsingStrictness::Strict).is_empty());
+    }
+}
@@ -1,10 +1,36 @@
+use super::awaited::{AwaitedRegistry, DEFAULT_AWAITED_MAX_AGE};
+use super::clock_sync::ClockSyncState;
+use super::command_delays::CommandDelayTracker;
+use crate::{CancelHandle, ControllerSettings, ControllerStorage, NodeStorage};
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::time::Duration;
 use hashbrown::HashMap;
+use super::{
+    ControllerIdentity, InclusionState, NodeInfoQueryOptions, OptimisticUpdates,
+    ReplicationGroup, VersionQueryOptions, WakeUpOptions,
+};
+use super::rate_limiter::RateLimiter;
+use super::reachability::ReachabilityState;
+use super::report_flood::{ReportFloodOptions, ReportFloodTracker};
+use super::{RouteHealth, RouteRepairOptions};
+use super::scheduler::Scheduler;
+use super::transitions::TransitionTracker;
+use super::Transactions;
+use super::value_watch::ValueWatchers;
+use super::virtual_endpoints::VirtualEndpoint;
+use super::InterviewHooks;
+use super::PersistedS2State;
+use zwave_cc::commandclass::{CC, SecurityManagers, WithAddress};
+use zwave_cc::spec_deviation::{ParsingStrictness, SpecDeviation};
+use zwave_cc::values::ValueMetadata;
 use zwave_core::{
     cache::CacheValue,
+    definitions::{CommandClasses, NodeId, NodeInformationApplicationData},
     security::{SecurityManager, SecurityManager2},
     value_id::EndpointValueId,
 };
-use zwave_pal::sync::Locked;
+use zwave_pal::{prelude::*, sync::Locked, time::Instant};
+use zwave_serial::command::Command;
 
 /// Internal storage for the driver instance and shared API instances.
 /// Since the driver is meant be used from external (application) code,
@@ -13,16 +39,121 @@ use zwave_pal::sync::Locked;
 /// a mutable reference.
 pub(crate) struct DriverStorage {
     value_cache: Locked<HashMap<EndpointValueId, CacheValue>>,
-    security_manager: Locked<Option<SecurityManager>>,
-    security_manager2: Locked<Option<SecurityManager2>>,
+    /// When each value was last reported by its node
+    value_timestamps: Locked<HashMap<EndpointValueId, Instant>>,
+    /// Values of Set commands that were sent, but not confirmed by the nodes yet
+    pending_values: Locked<HashMap<EndpointValueId, CacheValue>>,
+    /// Metadata of the values that are known to exist, including those that were not reported yet
+    value_metadata: Locked<HashMap<EndpointValueId, ValueMetadata>>,
+    /// The application's subscriptions to value updates
+    value_watchers: Locked<ValueWatchers>,
+    /// The nodes in the network. This is shared with the controller API, so the driver
+    /// can take the nodes' capabilities into account when communicating with them.
+    nodes: Arc<Locked<BTreeMap<NodeId, NodeStorage>>>,
+    /// Information about the controller, shared with the controller API once it is interviewed
+    controller: Locked<Option<Arc<Locked<ControllerStorage>>>>,
+    /// The controller settings requested by the application
+    controller_settings: Locked<ControllerSettings>,
+    /// The security managers, shared with every CC context that needs them
+    security_managers: Locked<Arc<SecurityManagers>>,
+    /// Restored S2 sequence numbers, waiting for the S2 security manager to be created
+    pending_s2_sequence_numbers: Locked<BTreeMap<NodeId, u8>>,
+    /// Restored S2 nonce state, waiting for the S2 security manager to be created
+    pending_s2_state: Locked<PersistedS2State>,
+    /// CCs the API handles are waiting for. Entries can be registered before the
+    /// corresponding request is sent, so responses that arrive early are not lost.
+    awaited_ccs: Arc<AwaitedRegistry<WithAddress<CC>>>,
+    /// Unsolicited controller commands the API handles are waiting for, e.g. the
+    /// status updates during inclusion
+    awaited_commands: Arc<AwaitedRegistry<Command>>,
+    /// How long awaited entries may be registered without being awaited
+    awaited_max_age: Locked<Duration>,
+    inclusion_state: Locked<InclusionState>,
+    /// Stops the learn mode that is currently active
+    learn_mode_handle: Locked<Option<CancelHandle>>,
+    /// The groups the including controller transferred during learn mode
+    replicated_groups: Locked<BTreeMap<u8, ReplicationGroup>>,
+    rate_limiter: Locked<RateLimiter>,
+    /// The minimum delays between commands to each node, and when they were last applied
+    command_delays: Locked<CommandDelayTracker>,
+    scheduler: Locked<Scheduler>,
+    /// Which clocks are kept in sync and when they were last set
+    clock_sync: Locked<ClockSyncState>,
+    /// The nodes that are currently being interviewed
+    interviews: Locked<BTreeSet<NodeId>>,
+    /// Verification polls that are scheduled for switches in transition
+    transition_tracker: Locked<TransitionTracker>,
+    wake_up_options: Locked<WakeUpOptions>,
+    version_query_options: Locked<VersionQueryOptions>,
+    node_info_query_options: Locked<NodeInfoQueryOptions>,
+    /// The last node info received from each node, used if a node cannot be queried
+    node_infos: Locked<BTreeMap<NodeId, NodeInformationApplicationData>>,
+    optimistic_updates: Locked<OptimisticUpdates>,
+    /// Devices the application emulates on the controller or its virtual nodes
+    virtual_endpoints: Locked<BTreeMap<NodeId, Arc<dyn VirtualEndpoint>>>,
+    /// Customizes the interview of nodes
+    interview_hooks: Locked<Option<Arc<dyn InterviewHooks>>>,
+    controller_identity: Locked<ControllerIdentity>,
+    parsing_strictness: Locked<ParsingStrictness>,
+    /// How often each node deviated from the specification in a tolerated way
+    spec_deviations: Locked<BTreeMap<(NodeId, CommandClasses, SpecDeviation), u64>>,
+    route_repair_options: Locked<RouteRepairOptions>,
+    /// Routing failures and route repairs of each node
+    route_health: Locked<BTreeMap<NodeId, RouteHealth>>,
+    /// The commands to nodes that are currently being executed
+    transactions: Locked<Transactions>,
+    /// The ID of the last Transport Service session, used to tell datagrams apart
+    transport_service_session: Locked<u8>,
+    /// Recent failures of each node and the commands waiting for sleeping nodes
+    reachability: Locked<ReachabilityState>,
+    report_flood_options: Locked<ReportFloodOptions>,
+    /// How many commands each node sent recently
+    report_flood: Locked<ReportFloodTracker>,
 }
 
 impl DriverStorage {
     pub fn new() -> Self {
         Self {
             value_cache: Locked::new(HashMap::new()),
-            security_manager: Locked::new(None),
-            security_manager2: Locked::new(None),
+            value_timestamps: Locked::new(HashMap::new()),
+            pending_values: Locked::new(HashMap::new()),
+            value_metadata: Locked::new(HashMap::new()),
+            value_watchers: Locked::new(ValueWatchers::default()),
+            nodes: Arc::new(Locked::new(BTreeMap::new())),
+            controller: Locked::new(None),
+            controller_settings: Locked::new(ControllerSettings::default()),
+            security_managers: Locked::new(Arc::new(SecurityManagers::default())),
+            pending_s2_sequence_numbers: Locked::new(BTreeMap::new()),
+            pending_s2_state: Locked::new(PersistedS2State::default()),
+            awaited_ccs: Arc::new(AwaitedRegistry::default()),
+            awaited_commands: Arc::new(AwaitedRegistry::default()),
+            awaited_max_age: Locked::new(DEFAULT_AWAITED_MAX_AGE),
+            inclusion_state: Locked::new(InclusionState::Idle),
+            learn_mode_handle: Locked::new(None),
+            replicated_groups: Locked::new(BTreeMap::new()),
+            rate_limiter: Locked::new(RateLimiter::new()),
+            command_delays: Locked::new(CommandDelayTracker::default()),
+            scheduler: Locked::new(Scheduler::new()),
+            clock_sync: Locked::new(ClockSyncState::default()),
+            interviews: Locked::new(BTreeSet::new()),
+            transition_tracker: Locked::new(TransitionTracker::default()),
+            wake_up_options: Locked::new(WakeUpOptions::default()),
+            version_query_options: Locked::new(VersionQueryOptions::default()),
+            node_info_query_options: Locked::new(NodeInfoQueryOptions::default()),
+            node_infos: Locked::new(BTreeMap::new()),
+            optimistic_updates: Locked::new(OptimisticUpdates::default()),
+            virtual_endpoints: Locked::new(BTreeMap::new()),
+            interview_hooks: Locked::new(None),
+            controller_identity: Locked::new(ControllerIdentity::default()),
+            parsing_strictness: Locked::new(ParsingStrictness::default()),
+            spec_deviations: Locked::new(BTreeMap::new()),
+            route_repair_options: Locked::new(RouteRepairOptions::default()),
+            route_health: Locked::new(BTreeMap::new()),
+            transactions: Locked::new(Transactions::default()),
+            transport_service_session: Locked::new(0),
+            reachability: Locked::new(ReachabilityState::default()),
+            report_flood_options: Locked::new(ReportFloodOptions::default()),
+            report_flood: Locked::new(ReportFloodTracker::default()),
         }
     }
 
@@ -30,11 +161,182 @@ impl DriverStorage {
         &self.value_cache
     }
 
-    pub(crate) fn security_manager(&self) -> &Locked<Option<SecurityManager>> {
-        &self.security_manager
+    pub(crate) fn value_timestamps(&self) -> &Locked<HashMap<EndpointValueId, Instant>> {
+        &self.value_timestamps
+    }
+
+    pub(crate) fn pending_values(&self) -> &Locked<HashMap<EndpointValueId, CacheValue>> {
+        &self.pending_values
+    }
+
+    pub(crate) fn value_metadata(&self) -> &Locked<HashMap<EndpointValueId, ValueMetadata>> {
+        &self.value_metadata
+    }
+
+    pub(crate) fn value_watchers(&self) -> &Locked<ValueWatchers> {
+        &self.value_watchers
+    }
+
+    pub(crate) fn nodes(&self) -> &Arc<Locked<BTreeMap<NodeId, NodeStorage>>> {
+        &self.nodes
+    }
+
+    pub(crate) fn controller(&self) -> &Locked<Option<Arc<Locked<ControllerStorage>>>> {
+        &self.controller
+    }
+
+    pub(crate) fn controller_settings(&self) -> &Locked<ControllerSettings> {
+        &self.controller_settings
+    }
+
+    /// Returns a cheap handle to the security managers for use in CC contexts
+    pub(crate) fn security_managers(&self) -> Arc<SecurityManagers> {
+        self.security_managers.cloned()
+    }
+
+    pub(crate) fn security_manager(&self) -> Option<SecurityManager> {
+        self.security_managers.inspect(|sec| sec.s0.clone())
+    }
+
+    pub(crate) fn set_security_manager(&self, sec_man: Option<SecurityManager>) {
+        self.security_managers
+            .update(|sec| Arc::make_mut(sec).s0 = sec_man);
+    }
+
+    pub(crate) fn security_manager2(&self) -> Option<SecurityManager2> {
+        self.security_managers.inspect(|sec| sec.s2.clone())
+    }
+
+    pub(crate) fn set_security_manager2(&self, sec_man: Option<SecurityManager2>) {
+        self.security_managers
+            .update(|sec| Arc::make_mut(sec).s2 = sec_man);
+    }
+
+    pub(crate) fn pending_s2_sequence_numbers(&self) -> &Locked<BTreeMap<NodeId, u8>> {
+        &self.pending_s2_sequence_numbers
+    }
+
+    pub(crate) fn pending_s2_state(&self) -> &Locked<PersistedS2State> {
+        &self.pending_s2_state
+    }
+
+    pub(crate) fn awaited_ccs(&self) -> &Arc<AwaitedRegistry<WithAddress<CC>>> {
+        &self.awaited_ccs
+    }
+
+    pub(crate) fn awaited_commands(&self) -> &Arc<AwaitedRegistry<Command>> {
+        &self.awaited_commands
+    }
+
+    pub(crate) fn awaited_max_age(&self) -> &Locked<Duration> {
+        &self.awaited_max_age
+    }
+
+    pub(crate) fn inclusion_state(&self) -> &Locked<InclusionState> {
+        &self.inclusion_state
+    }
+
+    pub(crate) fn learn_mode_handle(&self) -> &Locked<Option<CancelHandle>> {
+        &self.learn_mode_handle
+    }
+
+    pub(crate) fn replicated_groups(&self) -> &Locked<BTreeMap<u8, ReplicationGroup>> {
+        &self.replicated_groups
+    }
+
+    pub(crate) fn rate_limiter(&self) -> &Locked<RateLimiter> {
+        &self.rate_limiter
+    }
+
+    pub(crate) fn command_delays(&self) -> &Locked<CommandDelayTracker> {
+        &self.command_delays
+    }
+
+    pub(crate) fn scheduler(&self) -> &Locked<Scheduler> {
+        &self.scheduler
+    }
+
+    pub(crate) fn clock_sync(&self) -> &Locked<ClockSyncState> {
+        &self.clock_sync
+    }
+
+    pub(crate) fn interviews(&self) -> &Locked<BTreeSet<NodeId>> {
+        &self.interviews
+    }
+
+    pub(crate) fn reachability(&self) -> &Locked<ReachabilityState> {
+        &self.reachability
+    }
+
+    pub(crate) fn report_flood_options(&self) -> &Locked<ReportFloodOptions> {
+        &self.report_flood_options
+    }
+
+    pub(crate) fn report_flood(&self) -> &Locked<ReportFloodTracker> {
+        &self.report_flood
+    }
+
+    pub(crate) fn transport_service_session(&self) -> &Locked<u8> {
+        &self.transport_service_session
+    }
+
+    pub(crate) fn transition_tracker(&self) -> &Locked<TransitionTracker> {
+        &self.transition_tracker
+    }
+
+    pub(crate) fn wake_up_options(&self) -> &Locked<WakeUpOptions> {
+        &self.wake_up_options
+    }
+
+    pub(crate) fn version_query_options(&self) -> &Locked<VersionQueryOptions> {
+        &self.version_query_options
+    }
+
+    pub(crate) fn node_info_query_options(&self) -> &Locked<NodeInfoQueryOptions> {
+        &self.node_info_query_options
+    }
+
+    pub(crate) fn node_infos(&self) -> &Locked<BTreeMap<NodeId, NodeInformationApplicationData>> {
+        &self.node_infos
+    }
+
+    pub(crate) fn optimistic_updates(&self) -> &Locked<OptimisticUpdates> {
+        &self.optimistic_updates
+    }
+
+    pub(crate) fn virtual_endpoints(
+        &self,
+    ) -> &Locked<BTreeMap<NodeId, Arc<dyn VirtualEndpoint>>> {
+        &self.virtual_endpoints
+    }
+
+    pub(crate) fn interview_hooks(&self) -> &Locked<Option<Arc<dyn InterviewHooks>>> {
+        &self.interview_hooks
+    }
+
+    pub(crate) fn controller_identity(&self) -> &Locked<ControllerIdentity> {
+        &self.controller_identity
+    }
+
+    pub(crate) fn parsing_strictness(&self) -> &Locked<ParsingStrictness> {
+        &self.parsing_strictness
+    }
+
+    pub(crate) fn spec_deviations(
+        &self,
+    ) -> &Locked<BTreeMap<(NodeId, CommandClasses, SpecDeviation), u64>> {
+        &self.spec_deviations
+    }
+
+    pub(crate) fn route_repair_options(&self) -> &Locked<RouteRepairOptions> {
+        &self.route_repair_options
+    }
+
+    pub(crate) fn route_health(&self) -> &Locked<BTreeMap<NodeId, RouteHealth>> {
+        &self.route_health
     }
 
-    pub(crate) fn security_manager2(&self) -> &Locked<Option<SecurityManager2>> {
-        &self.security_manager2
+    pub(crate) fn transactions(&self) -> &Locked<Transactions> {
+        &self.transactions
     }
 }
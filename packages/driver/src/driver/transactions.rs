@@ -0,0 +1,261 @@
+use super::{CancelHandle, Driver, SendPriority};
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
+
+/// Identifies a pending transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TransactionId(u32);
+
+/// How far a pending transaction has progressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionState {
+    /// The command waits until the rate limit allows sending it
+    WaitingForSendSlot,
+    /// The command was passed to the controller, which transmits it to the node
+    Sending,
+    /// The node received the command, the driver waits for its response
+    WaitingForResponse,
+}
+
+/// A command to a node that has not been completed yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingTransaction {
+    pub id: TransactionId,
+    pub node_id: NodeId,
+    pub cc_id: CommandClasses,
+    pub cc_command: Option<u8>,
+    pub priority: SendPriority,
+    /// How long ago the transaction was started
+    pub age: Duration,
+    pub state: TransactionState,
+}
+
+struct TransactionEntry {
+    node_id: NodeId,
+    cc_id: CommandClasses,
+    cc_command: Option<u8>,
+    priority: SendPriority,
+    started_at: Instant,
+    state: TransactionState,
+    /// Cancels the transaction while it has not been sent yet
+    cancel_handle: Option<CancelHandle>,
+}
+
+#[derive(Default)]
+pub(crate) struct Transactions {
+    entries: BTreeMap<TransactionId, TransactionEntry>,
+    next_id: u32,
+}
+
+impl Transactions {
+    fn add(
+        &mut self,
+        node_id: NodeId,
+        cc_id: CommandClasses,
+        cc_command: Option<u8>,
+        priority: SendPriority,
+    ) -> TransactionId {
+        self.next_id = self.next_id.wrapping_add(1);
+        let id = TransactionId(self.next_id);
+        self.entries.insert(
+            id,
+            TransactionEntry {
+                node_id,
+                cc_id,
+                cc_command,
+                priority,
+                started_at: Instant::now(),
+                state: TransactionState::WaitingForSendSlot,
+                cancel_handle: None,
+            },
+        );
+        id
+    }
+
+    fn snapshot(&self, now: Instant) -> Vec<PendingTransaction> {
+        self.entries
+            .iter()
+            .map(|(id, entry)| PendingTransaction {
+                id: *id,
+                node_id: entry.node_id,
+                cc_id: entry.cc_id,
+                cc_command: entry.cc_command,
+                priority: entry.priority,
+                age: now
+                    .checked_duration_since(entry.started_at)
+                    .unwrap_or_default(),
+                state: entry.state,
+            })
+            .collect()
+    }
+}
+
+/// Keeps a transaction in the queue while the command is executed.
+/// Dropping it removes the transaction, even if the execution was aborted.
+pub(crate) struct TransactionGuard<'a> {
+    driver: &'a Driver,
+    pub(crate) id: TransactionId,
+}
+
+impl Drop for TransactionGuard<'_> {
+    fn drop(&mut self) {
+        self.driver
+            .storage
+            .transactions()
+            .update(|transactions| transactions.entries.remove(&self.id));
+    }
+}
+
+impl Driver {
+    /// Returns the commands to nodes that are currently being executed, oldest first
+    pub fn queue_snapshot(&self) -> Vec<PendingTransaction> {
+        self.storage
+            .transactions()
+            .inspect(|transactions| transactions.snapshot(Instant::now()))
+    }
+
+    /// Removes a transaction that has not been sent yet. Its execution fails with a
+    /// [`Cancelled`](crate::ControllerCommandError::Cancelled) error.
+    /// Returns whether the transaction was removed.
+    pub fn remove_transaction(&self, id: TransactionId) -> bool {
+        let cancel_handle = self.storage.transactions().update(|transactions| {
+            let entry = transactions.entries.get(&id)?;
+            if entry.state != TransactionState::WaitingForSendSlot {
+                return None;
+            }
+            let cancel_handle = entry.cancel_handle.clone()?;
+            transactions.entries.remove(&id);
+            Some(cancel_handle)
+        });
+        let Some(cancel_handle) = cancel_handle else {
+            return false;
+        };
+        cancel_handle.cancel();
+        true
+    }
+
+    /// Changes the priority of a transaction that has not been sent yet.
+    /// Returns whether the transaction was found.
+    pub fn reprioritize_transaction(&self, id: TransactionId, priority: SendPriority) -> bool {
+        self.storage
+            .transactions()
+            .update(|transactions| match transactions.entries.get_mut(&id) {
+                Some(entry) if entry.state == TransactionState::WaitingForSendSlot => {
+                    entry.priority = priority;
+                    true
+                }
+                _ => false,
+            })
+    }
+
+    /// Adds a transaction to the queue, which is removed when the returned guard is dropped
+    pub(crate) fn begin_transaction(
+        &self,
+        node_id: NodeId,
+        cc_id: CommandClasses,
+        cc_command: Option<u8>,
+        priority: SendPriority,
+    ) -> TransactionGuard<'_> {
+        let id = self
+            .storage
+            .transactions()
+            .update(|transactions| transactions.add(node_id, cc_id, cc_command, priority));
+        TransactionGuard { driver: self, id }
+    }
+
+    pub(crate) fn set_transaction_state(&self, id: TransactionId, state: TransactionState) {
+        self.storage.transactions().update(|transactions| {
+            if let Some(entry) = transactions.entries.get_mut(&id) {
+                entry.state = state;
+                if state != TransactionState::WaitingForSendSlot {
+                    entry.cancel_handle = None;
+                }
+            }
+        });
+    }
+
+    pub(crate) fn set_transaction_cancel_handle(&self, id: TransactionId, handle: CancelHandle) {
+        self.storage.transactions().update(|transactions| {
+            if let Some(entry) = transactions.entries.get_mut(&id) {
+                entry.cancel_handle = Some(handle);
+            }
+        });
+    }
+
+    /// The current priority of the given transaction
+    pub(crate) fn transaction_priority(&self, id: TransactionId) -> SendPriority {
+        self.storage.transactions().inspect(|transactions| {
+            transactions
+                .entries
+                .get(&id)
+                .map(|entry| entry.priority)
+                .unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use crate::{ControllerCommandError, ExecNodeCommandError, ExecNodeCommandOptions, RateLimit};
+    use futures::FutureExt;
+    use zwave_cc::commandclass::{CC, CCAddressable, NoOperationCC, WithAddress};
+
+    #[test]
+    fn test_queue_snapshot() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            // Exhaust the rate limit, so the commands wait in the queue
+            driver.set_rate_limit(Some(RateLimit {
+                burst: 1,
+                interval: Duration::from_secs(3600),
+                reserved: 0,
+            }));
+            driver.wait_for_send_slot(|| SendPriority::Normal).await;
+
+            let cc: WithAddress<CC> = NoOperationCC {}
+                .with_destination(NodeId::new(2u8).into())
+                .into();
+            let options = ExecNodeCommandOptions::builder()
+                .priority(SendPriority::Poll)
+                .build();
+            let mut poll = Box::pin(driver.exec_node_command(&cc, Some(&options)));
+            let mut normal = Box::pin(driver.exec_node_command(&cc, None));
+            // Start both commands
+            assert!(poll.as_mut().now_or_never().is_none());
+            assert!(normal.as_mut().now_or_never().is_none());
+
+            let snapshot = driver.queue_snapshot();
+            assert_eq!(snapshot.len(), 2);
+            assert_eq!(snapshot[0].node_id, NodeId::new(2u8));
+            assert_eq!(snapshot[0].cc_id, CommandClasses::NoOperation);
+            assert_eq!(snapshot[0].priority, SendPriority::Poll);
+            assert_eq!(snapshot[0].state, TransactionState::WaitingForSendSlot);
+
+            assert!(driver.reprioritize_transaction(snapshot[0].id, SendPriority::Normal));
+            assert_eq!(driver.queue_snapshot()[0].priority, SendPriority::Normal);
+
+            assert!(driver.remove_transaction(snapshot[1].id));
+            assert!(matches!(
+                normal.await,
+                Err(ExecNodeCommandError::Controller(
+                    ControllerCommandError::Cancelled
+                ))
+            ));
+            assert_eq!(driver.queue_snapshot().len(), 1);
+
+            // Dropping a command removes it from the queue
+            drop(poll);
+            assert!(driver.queue_snapshot().is_empty());
+        });
+
+        // Nothing was sent
+        assert!(controller.received().is_empty());
+    }
+}
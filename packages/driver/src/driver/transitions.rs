@@ -0,0 +1,204 @@
+use super::scheduler::ScheduledCommandId;
+use super::storage::DriverStorage;
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+use zwave_cc::commandclass::{BinarySwitchCCGet, MultilevelSwitchCCGet};
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::time::Instant;
+
+/// How long after the expected end of a transition the state is verified
+const TRANSITION_VERIFY_MARGIN: Duration = Duration::from_secs(1);
+/// The duration that is assumed when the device uses its default duration,
+/// or does not report the remaining duration
+const ASSUMED_TRANSITION_DURATION: Duration = Duration::from_secs(5);
+
+/// What a CC says about the transition of a switch to a new state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransitionState {
+    /// The transition was started, or is still ongoing. `None` means that the duration is unknown.
+    Ongoing(Option<Duration>),
+    /// The switch has reached its target state
+    Finished,
+}
+
+/// Determines the transition state from a CC, and which command to use to verify it.
+/// Returns `None` for CCs that are not related to transitions.
+fn transition_state(cc: &CC) -> Option<(TransitionState, CC)> {
+    use TransitionState::*;
+
+    match cc {
+        CC::BinarySwitchCCSet(set) => Some((
+            Ongoing(set.duration.and_then(|d| d.to_duration())),
+            BinarySwitchCCGet::default().into(),
+        )),
+        CC::MultilevelSwitchCCSet(set) => Some((
+            Ongoing(set.duration.and_then(|d| d.to_duration())),
+            MultilevelSwitchCCGet::default().into(),
+        )),
+        CC::BinarySwitchCCReport(report) => {
+            let ongoing = report
+                .target_value
+                .is_some_and(|target| target != report.current_value);
+            let state = if ongoing {
+                Ongoing(report.duration.and_then(|d| d.to_duration()))
+            } else {
+                Finished
+            };
+            Some((state, BinarySwitchCCGet::default().into()))
+        }
+        CC::MultilevelSwitchCCReport(report) => {
+            let ongoing = report
+                .target_value
+                .is_some_and(|target| target != report.current_value);
+            let state = if ongoing {
+                Ongoing(report.duration.and_then(|d| d.to_duration()))
+            } else {
+                Finished
+            };
+            Some((state, MultilevelSwitchCCGet::default().into()))
+        }
+        _ => None,
+    }
+}
+
+/// Identifies the switch whose transition is tracked
+type TransitionKey = (NodeId, EndpointIndex, CommandClasses);
+
+/// Keeps track of the verification polls that are scheduled for ongoing transitions
+#[derive(Default)]
+pub(crate) struct TransitionTracker {
+    verifications: BTreeMap<TransitionKey, ScheduledCommandId>,
+}
+
+impl DriverStorage {
+    /// Tracks the transition of a switch when a Set was sent to it or a Report was received from it.
+    ///
+    /// While a transition is ongoing, the switch is polled at its expected end, so the cached
+    /// state converges to the actual one. A report of the final state cancels the poll.
+    pub(crate) fn track_transition(&self, node_id: NodeId, cc: &WithAddress<CC>) {
+        let Some((state, verify_cc)) = transition_state(cc) else {
+            return;
+        };
+        let endpoint = cc.address().endpoint_index;
+        let key = (node_id, endpoint, cc.cc_id());
+
+        // A new state replaces the previously expected one
+        let previous = self
+            .transition_tracker()
+            .update(|tracker| tracker.verifications.remove(&key));
+        if let Some(previous) = previous {
+            self.scheduler()
+                .update(|scheduler| scheduler.remove(previous));
+        }
+
+        let TransitionState::Ongoing(duration) = state else {
+            return;
+        };
+        let delay = duration.unwrap_or(ASSUMED_TRANSITION_DURATION) + TRANSITION_VERIFY_MARGIN;
+        let now = Instant::now();
+        let verify_cc = verify_cc
+            .with_destination(node_id.into())
+            .with_endpoint_index(endpoint);
+        let id = self.scheduler().update(|scheduler| {
+            scheduler.add(verify_cc, now.checked_add(delay).unwrap_or(now), None)
+        });
+        self.transition_tracker()
+            .update(|tracker| tracker.verifications.insert(key, id));
+    }
+
+    /// Returns when the transition of the given switch is verified next, if it is being tracked
+    #[cfg(test)]
+    fn transition_verification_due(&self, key: TransitionKey) -> Option<Instant> {
+        let id = self
+            .transition_tracker()
+            .inspect(|tracker| tracker.verifications.get(&key).copied())?;
+        self.scheduler().inspect(|scheduler| scheduler.due(id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zwave_cc::commandclass::{
+        BinarySwitchCCSet, MultilevelSwitchCCReport, MultilevelSwitchCCSet,
+    };
+
+    const NODE: u8 = 2;
+
+    fn key(cc: CommandClasses) -> TransitionKey {
+        (NodeId::new(NODE), EndpointIndex::Root, cc)
+    }
+
+    fn report(current: u8, target: u8, duration: DurationReport) -> WithAddress<CC> {
+        CC::from(MultilevelSwitchCCReport {
+            current_value: LevelReport::Level(current),
+            target_value: Some(LevelReport::Level(target)),
+            duration: Some(duration),
+        })
+        .with_destination(Destination::Singlecast(NodeId::new(1u8)))
+        .with_source_node_id(NodeId::new(NODE))
+    }
+
+    #[test]
+    fn test_set_schedules_verification() {
+        let storage = DriverStorage::new();
+        let started = Instant::now();
+        let set = CC::from(
+            MultilevelSwitchCCSet::builder()
+                .target_value(LevelSet::Level(50))
+                .duration(Some(DurationSet::Seconds(10)))
+                .build(),
+        )
+        .with_destination(NodeId::new(NODE).into());
+        storage.track_transition(NodeId::new(NODE), &set);
+
+        let due = storage
+            .transition_verification_due(key(CommandClasses::MultilevelSwitch))
+            .unwrap();
+        assert!(due - started >= Duration::from_secs(11));
+        assert!(due - started < Duration::from_secs(12));
+
+        // Without a duration, the device default is assumed
+        let set = CC::from(
+            BinarySwitchCCSet::builder()
+                .target_value(BinarySet::On)
+                .build(),
+        )
+        .with_destination(NodeId::new(NODE).into());
+        storage.track_transition(NodeId::new(NODE), &set);
+        let due = storage
+            .transition_verification_due(key(CommandClasses::BinarySwitch))
+            .unwrap();
+        assert!(due - started >= ASSUMED_TRANSITION_DURATION + TRANSITION_VERIFY_MARGIN);
+    }
+
+    #[test]
+    fn test_reports_update_or_cancel_verification() {
+        let storage = DriverStorage::new();
+        let key = key(CommandClasses::MultilevelSwitch);
+
+        storage.track_transition(
+            NodeId::new(NODE),
+            &report(10, 99, DurationReport::Seconds(30)),
+        );
+        let first = storage.transition_verification_due(key).unwrap();
+
+        // An ongoing transition with a shorter remaining duration moves the verification
+        storage.track_transition(
+            NodeId::new(NODE),
+            &report(60, 99, DurationReport::Seconds(2)),
+        );
+        let second = storage.transition_verification_due(key).unwrap();
+        assert!(second < first);
+        assert_eq!(storage.scheduler().inspect(|s| s.len()), 1);
+
+        // Reaching the target cancels it
+        storage.track_transition(
+            NodeId::new(NODE),
+            &report(99, 99, DurationReport::Seconds(0)),
+        );
+        assert_eq!(storage.transition_verification_due(key), None);
+        assert_eq!(storage.scheduler().inspect(|s| s.len()), 0);
+    }
+}
@@ -0,0 +1,157 @@
+use super::Driver;
+use zwave_cc::commandclass::transport_service::{MAX_DATAGRAM_SIZE, segment_datagram};
+use zwave_cc::commandclass_raw::CCRaw;
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+impl Driver {
+    /// Returns how large the CC payload of a SendData command to the given node may be.
+    /// Prefers the size reported by the controller and falls back to what its SDK supports.
+    pub fn max_send_data_payload(&self, node_id: NodeId) -> usize {
+        let queried = self.storage.controller().inspect(|controller| {
+            controller.as_ref().and_then(|controller| {
+                controller.inspect(|controller| {
+                    if controller.long_range_nodes.contains(&node_id) {
+                        controller.max_lr_payload_size
+                    } else {
+                        controller.max_payload_size
+                    }
+                })
+            })
+        });
+        queried
+            .map(usize::from)
+            .unwrap_or_else(|| self.sdk_quirks().max_send_data_payload)
+    }
+
+    /// Whether the given node can receive datagrams that are split into several segments
+    pub fn supports_transport_service(&self, node_id: NodeId) -> bool {
+        self.storage.nodes().inspect(|nodes| {
+            nodes
+                .get(&node_id)
+                .and_then(|node| node.endpoints.get(&EndpointIndex::Root))
+                .and_then(|root| root.cc_info.get(&CommandClasses::TransportService))
+                .is_some_and(|info| info.supported)
+        })
+    }
+
+    /// Splits the serialized CC into the frames that are sent to the given node. The CC includes
+    /// all of its encapsulation, so its length is the final length of the frame. Frames that are
+    /// too large are split into Transport Service segments if the node supports it.
+    pub(super) fn split_into_frames(
+        &self,
+        node_id: NodeId,
+        raw: CCRaw,
+        ctx: &CCEncodingContext,
+    ) -> Result<Vec<CCRaw>, CCEncodingError> {
+        let max = self.max_send_data_payload(node_id);
+        let datagram = raw.as_bytes();
+        let size = datagram.len();
+        if size <= max {
+            return Ok(vec![raw]);
+        }
+        if !self.supports_transport_service(node_id) {
+            return Err(CCEncodingError::PayloadTooLarge { size, max });
+        }
+
+        let session_id = self.storage.transport_service_session().update(|session| {
+            *session = (*session + 1) & 0x0f;
+            *session
+        });
+        let segments = segment_datagram(&datagram, session_id, max).ok_or(
+            CCEncodingError::PayloadTooLarge {
+                size,
+                max: MAX_DATAGRAM_SIZE,
+            },
+        )?;
+        self.node_log(node_id, EndpointIndex::Root).debug(|| {
+            format!(
+                "the command is {} bytes long, sending it in {} segments",
+                size,
+                segments.len()
+            )
+        });
+        Ok(segments.iter().map(|segment| segment.as_raw(ctx)).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use bytes::Bytes;
+    use zwave_cc::commandclass::{CC, CCAddressable, CCEncodingError, NotImplemented};
+    use zwave_core::prelude::*;
+
+    fn node() -> NodeStorage {
+        NodeStorage::new(mock_protocol_data(true))
+    }
+
+    fn large_cc() -> CC {
+        CC::from(NotImplemented {
+            cc_id: CommandClasses::Configuration,
+            cc_command: Some(0x04),
+            payload: Bytes::from(vec![0u8; 60]),
+        })
+    }
+
+    #[test]
+    fn test_large_commands_are_segmented() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            let mut node = node();
+            node.endpoints
+                .get_mut(&EndpointIndex::Root)
+                .unwrap()
+                .cc_info
+                .insert(
+                    CommandClasses::TransportService,
+                    CommandClassInfo {
+                        supported: true,
+                        ..Default::default()
+                    },
+                );
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(node_id, node);
+            });
+
+            let cc = large_cc().with_destination(node_id.into());
+            driver.exec_node_command(&cc, None).await.unwrap();
+        });
+
+        let sent: Vec<_> = controller
+            .received()
+            .into_iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            .collect();
+        // 62 bytes do not fit into 40 byte frames, so they are sent in two segments
+        assert_eq!(sent.len(), 2);
+        // Node ID, payload length, then the Transport Service segment
+        assert_eq!(sent[0].payload[2..4], [0x55, 0xc0]);
+        assert_eq!(sent[1].payload[2..4], [0x55, 0xe0]);
+    }
+
+    #[test]
+    fn test_payload_too_large() {
+        let controller = MockController::new();
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(node_id, node());
+            });
+
+            let cc = large_cc().with_destination(node_id.into());
+            let result = driver.exec_node_command(&cc, None).await;
+            assert!(matches!(
+                result,
+                Err(crate::ExecNodeCommandError::Encoding(
+                    CCEncodingError::PayloadTooLarge { size: 62, max: 40 }
+                ))
+            ));
+        });
+    }
+}
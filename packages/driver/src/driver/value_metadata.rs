@@ -0,0 +1,128 @@
+use super::Driver;
+use crate::DriverEvent;
+use zwave_cc::commandclass::static_cc_values;
+use zwave_cc::values::ValueMetadata;
+use zwave_core::prelude::*;
+use zwave_core::value_id::EndpointValueId;
+use zwave_pal::prelude::*;
+
+impl Driver {
+    /// Returns the metadata of a value that is known to exist, even if it was not reported yet
+    pub fn value_metadata(&self, value_id: &EndpointValueId) -> Option<ValueMetadata> {
+        self.storage
+            .value_metadata()
+            .inspect(|metadata| metadata.get(value_id).cloned())
+    }
+
+    /// Returns the IDs of the values that are known to exist on the given node
+    pub fn defined_value_ids(&self, node_id: NodeId) -> Vec<EndpointValueId> {
+        let mut value_ids: Vec<_> = self.storage.value_metadata().inspect(|metadata| {
+            metadata
+                .keys()
+                .filter(|value_id| value_id.node_id() == node_id)
+                .copied()
+                .collect()
+        });
+        value_ids.sort();
+        value_ids
+    }
+
+    /// Creates the static values of a CC that are marked for automatic creation, so applications
+    /// know about them before the node reports them. Only values that exist in the given
+    /// CC version and on the given endpoint are created, unless the interview hooks veto them.
+    pub(crate) fn create_cc_values(
+        &self,
+        node_id: NodeId,
+        endpoint: EndpointIndex,
+        cc: CommandClasses,
+        version: u8,
+    ) {
+        let hooks = self.interview_hooks();
+        for value in static_cc_values(cc) {
+            let options = &value.options;
+            if !options.auto_create
+                || options.internal
+                || !options.is_defined_for(endpoint, version)
+            {
+                continue;
+            }
+
+            let value_id = EndpointValueId::new(node_id, endpoint, value.id);
+            if hooks
+                .as_ref()
+                .is_some_and(|hooks| !hooks.create_value(&value_id, &value.metadata))
+            {
+                continue;
+            }
+            let created = self.storage.value_metadata().update(|metadata| {
+                if metadata.contains_key(&value_id) {
+                    return false;
+                }
+                metadata.insert(value_id, value.metadata.clone());
+                true
+            });
+            if created {
+                self.emit_event(DriverEvent::ValueAdded {
+                    value_id,
+                    metadata: value.metadata.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockDriver, mock_driver};
+    use futures::FutureExt;
+    use zwave_cc::commandclass::BasicCCValues;
+
+    #[test]
+    fn test_create_cc_values() {
+        let MockDriver {
+            driver,
+            mut adapter,
+            ..
+        } = mock_driver();
+        let node_id = NodeId::new(2u8);
+        let value_id = |value_id| EndpointValueId::new(node_id, EndpointIndex::Root, value_id);
+
+        driver.create_cc_values(node_id, EndpointIndex::Root, CommandClasses::Basic, 1);
+        // The duration requires version 2 and the event is created when it is received
+        assert_eq!(
+            driver.defined_value_ids(node_id),
+            vec![
+                value_id(BasicCCValues::current_value().id),
+                value_id(BasicCCValues::target_value().id),
+                value_id(BasicCCValues::restore_previous().id),
+            ]
+        );
+        assert!(
+            driver
+                .value_metadata(&value_id(BasicCCValues::current_value().id))
+                .is_some()
+        );
+        assert!(matches!(
+            adapter.event_rx.recv().now_or_never(),
+            Some(Some(DriverEvent::ValueAdded { value_id: id, .. }))
+                if id == value_id(BasicCCValues::current_value().id)
+        ));
+
+        // Values that already exist are not created again
+        driver.create_cc_values(node_id, EndpointIndex::Root, CommandClasses::Basic, 2);
+        let events: Vec<_> = core::iter::from_fn(|| adapter.event_rx.recv().now_or_never())
+            .flatten()
+            .collect();
+        assert_eq!(events.len(), 2 + 1);
+
+        // Manufacturer Specific values only exist on the root endpoint
+        driver.create_cc_values(
+            node_id,
+            EndpointIndex::Endpoint(1),
+            CommandClasses::ManufacturerSpecific,
+            2,
+        );
+        assert_eq!(driver.defined_value_ids(node_id).len(), 4);
+    }
+}
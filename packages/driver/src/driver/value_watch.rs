@@ -0,0 +1,267 @@
+use super::{Driver, DriverEvent, ValueUpdateKind, storage::DriverStorage};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use typed_builder::TypedBuilder;
+use zwave_core::cache::CacheValue;
+use zwave_core::definitions::{CommandClasses, EndpointIndex, NodeId};
+use zwave_core::value_id::EndpointValueId;
+use zwave_pal::channel::Sender;
+use zwave_pal::prelude::*;
+
+/// How many value updates a watch buffers before further updates are dropped
+const VALUE_WATCH_CAPACITY: usize = 16;
+
+/// Selects which values a [`ValueWatch`] reports. Unset criteria match every value.
+#[derive(Debug, Clone, PartialEq, TypedBuilder)]
+pub struct ValueFilter {
+    #[builder(default, setter(into, strip_option))]
+    pub node_id: Option<NodeId>,
+    #[builder(default, setter(strip_option))]
+    pub endpoint: Option<EndpointIndex>,
+    #[builder(default, setter(strip_option))]
+    pub command_class: Option<CommandClasses>,
+    #[builder(default, setter(into, strip_option))]
+    pub property: Option<u16>,
+    #[builder(default, setter(strip_option))]
+    pub property_key: Option<u32>,
+    /// Whether the watch starts with the matching values that are already cached
+    #[builder(default)]
+    pub initial_snapshot: bool,
+}
+
+impl Default for ValueFilter {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl ValueFilter {
+    /// Tests if the given value is selected by this filter
+    pub fn matches(&self, value_id: &EndpointValueId) -> bool {
+        self.node_id
+            .is_none_or(|node_id| node_id == value_id.node_id())
+            && self
+                .endpoint
+                .is_none_or(|endpoint| endpoint == value_id.endpoint())
+            && self
+                .command_class
+                .is_none_or(|cc| cc == value_id.command_class())
+            && self
+                .property
+                .is_none_or(|property| property == value_id.property())
+            && self
+                .property_key
+                .is_none_or(|key| Some(key) == value_id.property_key())
+    }
+}
+
+/// A value reported by a [`ValueWatch`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueUpdate {
+    pub value_id: EndpointValueId,
+    pub value: CacheValue,
+    pub kind: ValueUpdateKind,
+}
+
+struct ValueWatcher {
+    id: u32,
+    filter: ValueFilter,
+    tx: Sender<ValueUpdate>,
+}
+
+/// The active value watches, which are notified about value updates
+#[derive(Default)]
+pub(crate) struct ValueWatchers {
+    next_id: u32,
+    watchers: Vec<ValueWatcher>,
+}
+
+impl ValueWatchers {
+    fn add(&mut self, filter: ValueFilter, tx: Sender<ValueUpdate>) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.watchers.push(ValueWatcher { id, filter, tx });
+        id
+    }
+
+    fn remove(&mut self, id: u32) {
+        self.watchers.retain(|watcher| watcher.id != id);
+    }
+
+    /// Forwards value updates to the watches they match.
+    /// Like events, updates are dropped if a watch does not keep up with them.
+    pub(crate) fn dispatch(&mut self, event: &DriverEvent) {
+        let DriverEvent::ValueUpdated {
+            value_id,
+            value,
+            kind,
+        } = event
+        else {
+            return;
+        };
+        self.watchers.retain(|watcher| {
+            if !watcher.filter.matches(value_id) {
+                return true;
+            }
+            let update = ValueUpdate {
+                value_id: *value_id,
+                value: value.clone(),
+                kind: *kind,
+            };
+            !watcher
+                .tx
+                .try_send(update)
+                .is_err_and(|e| e.is_disconnected())
+        });
+    }
+}
+
+/// An asynchronous stream of the value updates matching a [`ValueFilter`].
+/// The watch stops when it is dropped.
+pub struct ValueWatch {
+    id: u32,
+    storage: Arc<DriverStorage>,
+    updates: BoxStream<'static, ValueUpdate>,
+}
+
+impl Stream for ValueWatch {
+    type Item = ValueUpdate;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.updates.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for ValueWatch {
+    fn drop(&mut self) {
+        let id = self.id;
+        self.storage
+            .value_watchers()
+            .update(|watchers| watchers.remove(id));
+    }
+}
+
+impl Driver {
+    /// Watches the values matching the given filter. The returned stream yields every
+    /// update of a matching value, optionally preceded by the cached values.
+    pub fn watch_values(&self, filter: ValueFilter) -> ValueWatch {
+        let (tx, rx) = zwave_pal::channel::channel(VALUE_WATCH_CAPACITY);
+        // Register before taking the snapshot, so no update in between is missed
+        let id = self
+            .storage
+            .value_watchers()
+            .update(|watchers| watchers.add(filter.clone(), tx));
+
+        let mut snapshot = Vec::new();
+        if filter.initial_snapshot {
+            snapshot = self.storage.value_cache().inspect(|cache| {
+                cache
+                    .iter()
+                    .filter(|(value_id, _)| filter.matches(value_id))
+                    .map(|(value_id, value)| ValueUpdate {
+                        value_id: *value_id,
+                        value: value.clone(),
+                        kind: ValueUpdateKind::Snapshot,
+                    })
+                    .collect()
+            });
+            snapshot.sort_by_key(|update| update.value_id);
+        }
+
+        let updates = stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|update| (update, rx))
+        });
+        ValueWatch {
+            id,
+            storage: self.storage.clone(),
+            updates: stream::iter(snapshot).chain(updates).boxed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use crate::{Controller, NodeStorage};
+    use futures::FutureExt;
+    use zwave_core::cache::Cache;
+    use zwave_core::definitions::*;
+    use zwave_core::value_id::ValueId;
+
+    fn value_id(node_id: u8, cc: CommandClasses) -> EndpointValueId {
+        EndpointValueId::new(
+            NodeId::new(node_id),
+            EndpointIndex::Root,
+            ValueId::new(cc, 0u16, None),
+        )
+    }
+
+    fn value_updated(value_id: EndpointValueId, value: u8) -> DriverEvent {
+        DriverEvent::ValueUpdated {
+            value_id,
+            value: CacheValue::from(value),
+            kind: ValueUpdateKind::Reported,
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = ValueFilter::builder()
+            .node_id(NodeId::new(2u8))
+            .command_class(CommandClasses::Basic)
+            .build();
+        assert!(filter.matches(&value_id(2, CommandClasses::Basic)));
+        assert!(!filter.matches(&value_id(3, CommandClasses::Basic)));
+        assert!(!filter.matches(&value_id(2, CommandClasses::BinarySwitch)));
+        assert!(ValueFilter::default().matches(&value_id(3, CommandClasses::BinarySwitch)));
+    }
+
+    #[test]
+    fn test_watch_values() {
+        let controller = MockController::new();
+        run_with_mock_controller(&controller, |driver| async move {
+            let basic = value_id(2, CommandClasses::Basic);
+            driver.value_cache().write(&basic, CacheValue::from(1u8));
+
+            let mut driver_watch = driver.watch_values(
+                ValueFilter::builder()
+                    .command_class(CommandClasses::Basic)
+                    .initial_snapshot(true)
+                    .build(),
+            );
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(3u8), NodeStorage::new(mock_protocol_data(true)));
+            });
+            let controller = Controller::mock(&driver);
+            let node = controller.node(NodeId::new(3u8)).unwrap();
+            let mut node_watch = node.watch_values(ValueFilter::default());
+
+            driver.emit_event(value_updated(basic, 2));
+            let switch = value_id(3, CommandClasses::BinarySwitch);
+            driver.emit_event(value_updated(switch, 3));
+
+            let first = driver_watch.next().await.unwrap();
+            assert_eq!(first.value, CacheValue::from(1u8));
+            assert_eq!(first.kind, ValueUpdateKind::Snapshot);
+            let second = driver_watch.next().await.unwrap();
+            assert_eq!(second.value, CacheValue::from(2u8));
+            assert_eq!(second.kind, ValueUpdateKind::Reported);
+            // The switch value does not match the filter
+            assert!(driver_watch.next().now_or_never().is_none());
+
+            let update = node_watch.next().await.unwrap();
+            assert_eq!(update.value_id, switch);
+
+            // Dropping a watch unregisters it
+            drop(driver_watch);
+            drop(node_watch);
+            let remaining = driver
+                .storage
+                .value_watchers()
+                .inspect(|watchers| watchers.watchers.len());
+            assert_eq!(remaining, 0);
+        });
+    }
+}
@@ -0,0 +1,116 @@
+use super::{Driver, DriverActor};
+use zwave_cc::prelude::*;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+/// A device the application emulates on the controller, e.g. a Thermostat or an Indicator
+/// which association partners can query
+pub trait VirtualEndpoint: Send + Sync {
+    /// Handles a CC a node sent to the emulated device.
+    /// Returns the report to answer with, if the CC is answered.
+    fn handle_cc(&self, source_node_id: NodeId, cc: &CC) -> Option<CC>;
+}
+
+impl Driver {
+    /// Emulates a device on the given node ID, which is either the controller's own node ID,
+    /// or one of its virtual nodes on bridge controllers. CCs that are sent to this node and
+    /// nobody is waiting for are passed to the handler, which can answer them.
+    ///
+    /// Multi Channel encapsulation is not supported yet, so the device is emulated
+    /// on the root endpoint. A previously registered handler is replaced.
+    pub fn register_virtual_endpoint(
+        &self,
+        node_id: NodeId,
+        handler: impl VirtualEndpoint + 'static,
+    ) {
+        self.storage
+            .virtual_endpoints()
+            .update(|endpoints| endpoints.insert(node_id, Arc::new(handler)));
+    }
+
+    /// Stops emulating a device on the given node ID.
+    /// Returns whether a device was emulated.
+    pub fn unregister_virtual_endpoint(&self, node_id: NodeId) -> bool {
+        self.storage
+            .virtual_endpoints()
+            .update(|endpoints| endpoints.remove(&node_id))
+            .is_some()
+    }
+}
+
+impl DriverActor {
+    /// Passes a received CC to the device that is emulated on its destination, and sends the
+    /// answer back to the node. Returns whether the CC was answered.
+    pub(super) fn handle_virtual_endpoint_cc(&self, cc: &WithAddress<CC>) -> bool {
+        let address = cc.address();
+        let Destination::Singlecast(destination) = address.destination else {
+            return false;
+        };
+        let Some(handler) = self
+            .storage
+            .virtual_endpoints()
+            .inspect(|endpoints| endpoints.get(&destination).cloned())
+        else {
+            return false;
+        };
+
+        let source_node_id = address.source_node_id;
+        let Some(response) = handler.handle_cc(source_node_id, cc) else {
+            return false;
+        };
+        self.send_cc(source_node_id, response);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, run_with_mock_controller};
+    use core::time::Duration;
+    use zwave_cc::commandclass::{BasicCCReport, NoOperationCC};
+    use zwave_pal::time::Timer;
+
+    struct EmulatedSwitch;
+
+    impl VirtualEndpoint for EmulatedSwitch {
+        fn handle_cc(&self, _source_node_id: NodeId, cc: &CC) -> Option<CC> {
+            match cc {
+                CC::BasicCCGet(_) => Some(
+                    BasicCCReport {
+                        current_value: LevelReport::Level(42),
+                        target_value: None,
+                        duration: None,
+                    }
+                    .into(),
+                ),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_virtual_endpoint_answers_gets() {
+        // After receiving the first command, node 2 queries the controller
+        let controller = MockController::new().on(FunctionType::SendData, |controller, request| {
+            let mut ret = MockController::send_data_ok(request);
+            if controller.received().len() == 1 {
+                ret.push(MockController::application_command(2, &[0x20, 0x02]));
+            }
+            ret
+        });
+        run_with_mock_controller(&controller, |driver| async move {
+            driver.serial_api.storage.set_own_node_id(NodeId::new(1u8));
+            driver.register_virtual_endpoint(NodeId::new(1u8), EmulatedSwitch);
+            let cc = CC::from(NoOperationCC {}).with_destination(NodeId::new(2u8).into());
+            driver.exec_node_command(&cc, None).await.unwrap();
+            Timer::after(Duration::from_millis(50)).await;
+        });
+
+        // The Basic Report is sent to node 2
+        let sent = controller.received();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].function_type, FunctionType::SendData);
+        assert_eq!(&sent[1].payload[..5], &[0x02, 0x03, 0x20, 0x03, 42]);
+    }
+}
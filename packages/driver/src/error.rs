@@ -1,4 +1,4 @@
-use crate::ControllerCommandError;
+use crate::{Cancelled, ControllerCommandError};
 use thiserror::Error;
 use zwave_pal::prelude::*;
 use zwave_serial::error::Error as SerialPortError;
@@ -15,6 +15,42 @@ pub enum Error {
     Internal,
     #[error("Operation timed out")]
     Timeout,
+    #[error("The operation was cancelled")]
+    Cancelled,
+    #[error("The driver was stopped")]
+    Stopped,
+    #[error("The node is already being interviewed")]
+    InterviewInProgress,
+}
+
+/// The actors that make up the driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActorKind {
+    Driver,
+    SerialApi,
+}
+
+impl core::fmt::Display for ActorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ActorKind::Driver => write!(f, "driver"),
+            ActorKind::SerialApi => write!(f, "Serial API"),
+        }
+    }
+}
+
+/// An error an actor cannot recover from. The actor stops after reporting it.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("The {actor} actor stopped: {reason}")]
+pub struct FatalError {
+    pub actor: ActorKind,
+    pub reason: String,
+}
+
+impl From<Cancelled> for Error {
+    fn from(_: Cancelled) -> Self {
+        Error::Cancelled
+    }
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
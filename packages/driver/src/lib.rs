@@ -4,11 +4,14 @@ extern crate alloc;
 
 use zwave_core::submodule;
 
+pub mod api;
 submodule!(driver);
 pub mod error;
 submodule!(controller);
 submodule!(node);
 submodule!(serial_api);
+#[cfg(feature = "metrics")]
+pub mod metrics;
 
 pub type LogSender =
     zwave_pal::channel::Sender<(zwave_logging::LogInfo, zwave_core::log::Loglevel)>;
@@ -0,0 +1,318 @@
+//! Driver, controller and node statistics, exported through the [`metrics`] facade.
+//!
+//! The driver only records metrics. To expose them, the application installs a recorder, e.g.
+//! `PrometheusBuilder::new().install_recorder()` from `metrics-exporter-prometheus`, and serves
+//! what its handle renders. Calling [`describe`] after installing the recorder adds units and
+//! help texts to the exported metrics.
+//!
+//! When several drivers run in the same process, give each of them a label with
+//! [`Driver::set_label`](crate::Driver::set_label). Their metrics are then labeled by `controller`.
+
+use crate::{
+    ExecNodeCommandError, ExecNodeCommandResult, FrameStage, SerialApiCommandMetadata,
+    SerialApiMachineResult, UnknownCommandType,
+};
+use core::slice;
+use core::time::Duration;
+use metrics::{
+    Label, Unit, counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram,
+};
+use zwave_core::prelude::*;
+
+/// Number of executed Serial API commands, labeled by `function` and `result`
+pub const SERIAL_API_COMMANDS: &str = "zwave_serial_api_commands_total";
+/// Time from sending a Serial API command until the last expected frame was received, labeled by `function`
+pub const SERIAL_API_COMMAND_DURATION: &str = "zwave_serial_api_command_duration_seconds";
+/// Number of times a Serial API command had to be sent again, labeled by `function`
+pub const SERIAL_API_RETRANSMISSIONS: &str = "zwave_serial_api_retransmissions_total";
+/// Number of NAK frames received from the controller
+pub const SERIAL_API_NAKS: &str = "zwave_serial_api_nak_total";
+/// Number of CAN frames received from the controller
+pub const SERIAL_API_CANS: &str = "zwave_serial_api_can_total";
+/// Number of frames from the controller that were discarded because of an invalid checksum
+pub const SERIAL_API_CHECKSUM_ERRORS: &str = "zwave_serial_api_checksum_errors_total";
+/// Time a frame from the controller spent in a stage until it was handled, labeled by `stage`
+pub const SERIAL_FRAME_LATENCY: &str = "zwave_serial_frame_latency_seconds";
+/// Number of received commands that are not implemented by the library, labeled by `command`
+pub const UNKNOWN_COMMANDS: &str = "zwave_unknown_commands_total";
+/// Number of Serial API commands that are queued or being executed
+pub const SERIAL_API_PENDING_COMMANDS: &str = "zwave_serial_api_pending_commands";
+/// Whether the controller acknowledges commands (1) or is being recovered (0)
+pub const CONTROLLER_RESPONSIVE: &str = "zwave_controller_responsive";
+/// Number of commands sent to nodes, labeled by `node` and `result`
+pub const NODE_COMMANDS: &str = "zwave_node_commands_total";
+/// Time from sending a command to a node until it was completed, labeled by `node`
+pub const NODE_COMMAND_DURATION: &str = "zwave_node_command_duration_seconds";
+/// Whether a node acknowledged the last command sent to it (1) or not (0), labeled by `node`
+pub const NODE_AVAILABLE: &str = "zwave_node_available";
+/// Number of commands from a node that were discarded because of an invalid CRC-16, labeled by `node`
+pub const NODE_CRC16_ERRORS: &str = "zwave_node_crc16_errors_total";
+
+/// Registers units and descriptions for all metrics with the installed recorder
+pub fn describe() {
+    describe_counter!(SERIAL_API_COMMANDS, "Executed Serial API commands");
+    describe_histogram!(
+        SERIAL_API_COMMAND_DURATION,
+        Unit::Seconds,
+        "Duration of Serial API commands"
+    );
+    describe_counter!(
+        SERIAL_API_RETRANSMISSIONS,
+        "Serial API commands that had to be sent again"
+    );
+    describe_counter!(SERIAL_API_NAKS, "NAK frames received from the controller");
+    describe_counter!(SERIAL_API_CANS, "CAN frames received from the controller");
+    describe_counter!(
+        SERIAL_API_CHECKSUM_ERRORS,
+        "Frames from the controller with an invalid checksum"
+    );
+    describe_histogram!(
+        SERIAL_FRAME_LATENCY,
+        Unit::Seconds,
+        "Time frames from the controller spent in each stage until they were handled"
+    );
+    describe_counter!(
+        UNKNOWN_COMMANDS,
+        "Received commands that are not implemented by the library"
+    );
+    describe_gauge!(
+        SERIAL_API_PENDING_COMMANDS,
+        "Serial API commands that are queued or being executed"
+    );
+    describe_gauge!(
+        CONTROLLER_RESPONSIVE,
+        "Whether the controller acknowledges commands"
+    );
+    describe_counter!(NODE_COMMANDS, "Commands sent to nodes");
+    describe_histogram!(
+        NODE_COMMAND_DURATION,
+        Unit::Seconds,
+        "Duration of commands sent to nodes"
+    );
+    describe_gauge!(
+        NODE_AVAILABLE,
+        "Whether a node acknowledged the last command sent to it"
+    );
+    describe_counter!(NODE_CRC16_ERRORS, "Commands from nodes with an invalid CRC-16");
+}
+
+/// Combines the label of the driver, if any, with the labels of a metric
+fn labels(controller: Option<&str>, labels: &[(&'static str, String)]) -> Vec<Label> {
+    controller
+        .map(|controller| Label::new("controller", controller.to_string()))
+        .into_iter()
+        .chain(labels.iter().map(|(key, value)| Label::new(*key, value.clone())))
+        .collect()
+}
+
+pub(crate) fn record_serial_api_command(
+    controller: Option<&str>,
+    function_type: FunctionType,
+    result: &SerialApiMachineResult,
+    metadata: &SerialApiCommandMetadata,
+) {
+    let function = format!("{:?}", function_type);
+    let result = match result {
+        SerialApiMachineResult::Success(_) => "success",
+        SerialApiMachineResult::ACKTimeout => "ack_timeout",
+        SerialApiMachineResult::CAN => "can",
+        SerialApiMachineResult::NAK => "nak",
+        SerialApiMachineResult::ResponseTimeout => "response_timeout",
+        SerialApiMachineResult::ResponseNOK(_) => "response_nok",
+        SerialApiMachineResult::CallbackTimeout => "callback_timeout",
+        SerialApiMachineResult::CallbackNOK(_) => "callback_nok",
+    };
+    let function = ("function", function);
+    counter!(
+        SERIAL_API_COMMANDS,
+        labels(controller, &[function.clone(), ("result", result.to_string())])
+    )
+    .increment(1);
+
+    // The command is complete when the last frame we waited for was received
+    if let Some(duration) = metadata
+        .callback_duration()
+        .or_else(|| metadata.response_duration())
+        .or_else(|| metadata.ack_duration())
+    {
+        histogram!(SERIAL_API_COMMAND_DURATION, labels(controller, slice::from_ref(&function)))
+            .record(duration.as_secs_f64());
+    }
+
+    let retransmissions = metadata.attempts.saturating_sub(1);
+    if retransmissions > 0 {
+        counter!(SERIAL_API_RETRANSMISSIONS, labels(controller, &[function]))
+            .increment(retransmissions as u64);
+    }
+    if metadata.nak_count > 0 {
+        counter!(SERIAL_API_NAKS, labels(controller, &[])).increment(metadata.nak_count as u64);
+    }
+    if metadata.can_count > 0 {
+        counter!(SERIAL_API_CANS, labels(controller, &[])).increment(metadata.can_count as u64);
+    }
+}
+
+pub(crate) fn record_checksum_mismatch(controller: Option<&str>) {
+    counter!(SERIAL_API_CHECKSUM_ERRORS, labels(controller, &[])).increment(1);
+}
+
+pub(crate) fn record_frame_latency(controller: Option<&str>, stage: FrameStage, latency: Duration) {
+    histogram!(
+        SERIAL_FRAME_LATENCY,
+        labels(controller, &[("stage", stage.as_str().to_string())])
+    )
+    .record(latency.as_secs_f64());
+}
+
+pub(crate) fn record_unknown_command(controller: Option<&str>, command: &UnknownCommandType) {
+    counter!(
+        UNKNOWN_COMMANDS,
+        labels(controller, &[("command", command.to_string())])
+    )
+    .increment(1);
+}
+
+pub(crate) fn record_corrupted_command(controller: Option<&str>, node_id: NodeId) {
+    counter!(
+        NODE_CRC16_ERRORS,
+        labels(controller, &[("node", u16::from(node_id).to_string())])
+    )
+    .increment(1);
+}
+
+pub(crate) fn record_controller_responsive(controller: Option<&str>, responsive: bool) {
+    gauge!(CONTROLLER_RESPONSIVE, labels(controller, &[])).set(if responsive { 1.0 } else { 0.0 });
+}
+
+pub(crate) fn record_node_command<T>(
+    controller: Option<&str>,
+    node_id: NodeId,
+    result: &ExecNodeCommandResult<T>,
+    duration: Duration,
+) {
+    let node = ("node", u16::from(node_id).to_string());
+    let (result, available) = match result {
+        Ok(_) => ("success", Some(true)),
+        // A timeout means the node acknowledged the command, but did not respond
+        Err(ExecNodeCommandError::NodeTimeout) => ("timeout", Some(true)),
+        Err(ExecNodeCommandError::NodeNoAck) => ("no_ack", Some(false)),
+        // These say nothing about the node
+        Err(ExecNodeCommandError::Controller(_)) => ("controller_error", None),
+        Err(ExecNodeCommandError::Encoding(_)) => ("encoding_error", None),
+        Err(ExecNodeCommandError::EncryptionImpossible(_)) => ("encryption_impossible", None),
+        Err(ExecNodeCommandError::Queued) => ("queued", None),
+        Err(ExecNodeCommandError::NodeUnreachable) => ("unreachable", None),
+    };
+    counter!(
+        NODE_COMMANDS,
+        labels(controller, &[node.clone(), ("result", result.to_string())])
+    )
+    .increment(1);
+    histogram!(NODE_COMMAND_DURATION, labels(controller, slice::from_ref(&node)))
+        .record(duration.as_secs_f64());
+    if let Some(available) = available {
+        gauge!(NODE_AVAILABLE, labels(controller, &[node])).set(if available { 1.0 } else { 0.0 });
+    }
+}
+
+/// Counts a Serial API command as pending for as long as it is alive
+pub(crate) struct PendingSerialApiCommand(Vec<Label>);
+
+impl PendingSerialApiCommand {
+    pub fn new(controller: Option<&str>) -> Self {
+        let labels = labels(controller, &[]);
+        gauge!(SERIAL_API_PENDING_COMMANDS, labels.clone()).increment(1.0);
+        Self(labels)
+    }
+}
+
+impl Drop for PendingSerialApiCommand {
+    fn drop(&mut self) {
+        gauge!(SERIAL_API_PENDING_COMMANDS, self.0.clone()).decrement(1.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    #[test]
+    fn test_node_availability() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            let node_id = NodeId::new(5u8);
+            let ok: ExecNodeCommandResult<()> = Ok(());
+            let no_ack: ExecNodeCommandResult<()> = Err(ExecNodeCommandError::NodeNoAck);
+            record_node_command(None, node_id, &ok, Duration::from_millis(20));
+            record_node_command(None, node_id, &no_ack, Duration::from_millis(40));
+        });
+
+        let metrics = snapshotter.snapshot().into_vec();
+        let value_of = |name: &str, labels: &[(&str, &str)]| {
+            metrics
+                .iter()
+                .find(|(key, _, _, _)| {
+                    let key = key.key();
+                    key.name() == name
+                        && labels
+                            .iter()
+                            .all(|(k, v)| key.labels().any(|l| l.key() == *k && l.value() == *v))
+                })
+                .map(|(_, _, _, value)| value)
+        };
+
+        assert_eq!(
+            value_of(NODE_AVAILABLE, &[("node", "5")]),
+            Some(&DebugValue::Gauge(0.0.into()))
+        );
+        assert_eq!(
+            value_of(NODE_COMMANDS, &[("node", "5"), ("result", "success")]),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            value_of(NODE_COMMANDS, &[("node", "5"), ("result", "no_ack")]),
+            Some(&DebugValue::Counter(1))
+        );
+        assert!(matches!(
+            value_of(NODE_COMMAND_DURATION, &[("node", "5")]),
+            Some(DebugValue::Histogram(samples)) if samples.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_controller_label() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        metrics::with_local_recorder(&recorder, || {
+            record_controller_responsive(Some("stick1"), true);
+            record_controller_responsive(Some("stick2"), false);
+        });
+
+        let mut gauges: Vec<_> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let controller = key
+                    .key()
+                    .labels()
+                    .find(|l| l.key() == "controller")
+                    .map(|l| l.value().to_string());
+                (controller, value)
+            })
+            .collect();
+        gauges.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        assert_eq!(
+            gauges,
+            vec![
+                (Some("stick1".to_string()), DebugValue::Gauge(1.0.into())),
+                (Some("stick2".to_string()), DebugValue::Gauge(0.0.into())),
+            ]
+        );
+    }
+}
@@ -1,17 +1,29 @@
 use zwave_pal::prelude::*;
 use crate::{
-    Controller, ControllerCommandResult, Driver, EndpointStateRef, ExecNodeCommandError,
-    NodeStateRef, Ready,
+    CommandDelays, Controller, ControllerCommandResult, Driver, DriverEvent, EndpointStateRef,
+    ExecNodeCommandOptions, ExecNodeCommandResult, NodeStateRef, OptimisticUpdates, PingResult,
+    RawCCPredicate, Ready, ValueFilter, ValueWatch, VersionQueryOptions, WakeUpOptions,
 };
+use bytes::Bytes;
 use cache::EndpointValueCache;
-use zwave_cc::commandclass::{CCAddressable, NoOperationCC};
+use core::{future::Future, pin::Pin};
+use zwave_cc::commandclass::{CC, WithAddress};
+use zwave_cc::prelude::CCRaw;
 use zwave_core::{definitions::*, submodule};
 use zwave_logging::loggers::node::NodeLogger;
 
 submodule!(interview);
+submodule!(security_bootstrap);
 submodule!(storage);
 submodule!(cc_api);
+submodule!(values);
+submodule!(link_quality);
+submodule!(wake_up_refresh);
+submodule!(encryption_policy);
+submodule!(cc_inheritance);
 mod cache;
+#[cfg(test)]
+pub(crate) mod mock;
 
 pub struct Node<'a> {
     id: NodeId,
@@ -19,12 +31,24 @@ pub struct Node<'a> {
     controller: &'a Controller<'a, Ready>,
 }
 
-pub trait EndpointLike<'a> {
+/// The future returned by [`EndpointLike::exec_node_command`]
+pub type ExecNodeCommandFuture<'b> =
+    Pin<Box<dyn Future<Output = ExecNodeCommandResult<Option<CC>>> + Send + 'b>>;
+
+pub trait EndpointLike<'a>: Sync {
     fn node_id(&self) -> NodeId;
-    fn get_node(&'a self) -> &'a Node<'a>;
+    /// Returns the root endpoint of the node this endpoint belongs to
+    fn root_endpoint(&'a self) -> &'a dyn EndpointLike<'a>;
     fn index(&self) -> EndpointIndex;
     fn value_cache(&'a self) -> EndpointValueCache<'a>;
 
+    /// Sends a CC to the node and waits for the response, if one is expected
+    fn exec_node_command<'b>(
+        &'b self,
+        cc: &'b WithAddress<CC>,
+        options: Option<&'b ExecNodeCommandOptions>,
+    ) -> ExecNodeCommandFuture<'b>;
+
     fn modify_cc_info(&self, cc: CommandClasses, info: &PartialCommandClassInfo);
     fn remove_cc(&self, cc: CommandClasses);
 
@@ -36,6 +60,13 @@ pub trait EndpointLike<'a> {
 
     fn logger(&self) -> NodeLogger<'_>;
 
+    /// The node ID of the controller, e.g. to receive unsolicited reports
+    fn own_node_id(&self) -> NodeId;
+    /// How the wakeup of sleeping nodes should be configured
+    fn wake_up_options(&self) -> WakeUpOptions;
+    /// How the CC versions should be queried
+    fn version_query_options(&self) -> VersionQueryOptions;
+
     // TODO: Add the rest
 }
 
@@ -80,10 +111,185 @@ impl<'a> Node<'a> {
         &self.protocol_data
     }
 
+    /// The device class of the node, which describes its main functionality
+    pub fn device_class(&self) -> DeviceClass {
+        self.protocol_data.device_class()
+    }
+
     pub fn can_sleep(&self) -> bool {
         !self.protocol_data.listening && self.protocol_data.frequent_listening.is_none()
     }
 
+    /// Whether this node is always listening
+    pub fn is_listening(&self) -> bool {
+        self.protocol_data.listening
+    }
+
+    /// Whether this node is a FLiRS node, which must be woken up with a beam before communicating
+    pub fn is_frequent_listening(&self) -> bool {
+        self.protocol_data.frequent_listening.is_some()
+    }
+
+    /// The wakeup interval of this node, if it is a FLiRS node
+    pub fn frequent_listening(&self) -> Option<Beam> {
+        self.protocol_data.frequent_listening
+    }
+
+    /// Whether the node is reachable, based on the last communication with it
+    pub fn status(&self) -> NodeStatus {
+        self.state().status().unwrap_or_default()
+    }
+
+    /// Returns how the last command to this node was transmitted, including the route
+    pub fn last_transmit_report(&self) -> Option<TransmitReport> {
+        self.state().last_transmit_report()
+    }
+
+    /// Returns the route the last command to this node took, including the speed and attempts
+    pub fn last_routing_attempt(&self) -> Option<RoutingAttempt> {
+        self.last_transmit_report()
+            .map(|report| report.routing_attempt(self.own_node_id(), self.id))
+    }
+
+    /// Returns statistics about the communication with this node
+    pub fn statistics(&self) -> NodeStatistics {
+        self.state().statistics().unwrap_or_default()
+    }
+
+    /// Whether this node supports routing/forwarding messages
+    pub fn is_routing(&self) -> bool {
+        self.protocol_data.routing
+    }
+
+    /// Whether this node can wake up FLiRS nodes
+    pub fn supports_beaming(&self) -> bool {
+        self.protocol_data.beaming
+    }
+
+    pub fn supports_security(&self) -> bool {
+        self.protocol_data.supports_security
+    }
+
+    pub fn supported_data_rates(&self) -> &[DataRate] {
+        &self.protocol_data.supported_data_rates
+    }
+
+    /// The highest data rate this node supports
+    pub fn max_data_rate(&self) -> Option<DataRate> {
+        self.protocol_data.supported_data_rates.iter().max().copied()
+    }
+
+    pub fn node_type(&self) -> NodeType {
+        self.protocol_data.node_type
+    }
+
+    /// When the values of this node are updated after Set commands. Unless it was changed for
+    /// this node, the driver's setting is used.
+    pub fn optimistic_updates(&self) -> OptimisticUpdates {
+        self.state()
+            .optimistic_updates()
+            .unwrap_or_else(|| self.driver().optimistic_updates())
+    }
+
+    /// Watches the values of this node matching the given filter.
+    /// The filter's node ID is replaced with this node's.
+    pub fn watch_values(&self, filter: ValueFilter) -> ValueWatch {
+        let filter = ValueFilter {
+            node_id: Some(self.id),
+            ..filter
+        };
+        self.driver().watch_values(filter)
+    }
+
+    /// Configures the minimum delays between commands to this node. `None` removes them.
+    pub fn set_command_delays(&self, delays: Option<CommandDelays>) {
+        self.driver().set_command_delays(self.id, delays);
+    }
+
+    /// Returns the minimum delays between commands to this node
+    pub fn command_delays(&self) -> CommandDelays {
+        self.driver().command_delays(self.id)
+    }
+
+    /// Returns the nodes in direct range of this node, as last reported by the controller
+    pub fn neighbors(&self) -> Option<Vec<NodeId>> {
+        self.driver().node_neighbors(self.id)
+    }
+
+    /// Whether this node is likely to receive a command sent right now.
+    /// Sleeping nodes are only reachable while they are awake.
+    pub fn is_reachable_now(&self) -> bool {
+        self.driver().is_node_reachable_now(self.id)
+    }
+
+    /// Configures whether the clock of this node is set automatically
+    pub fn set_clock_sync_enabled(&self, enabled: bool) {
+        self.driver().set_clock_sync_enabled(self.id, enabled);
+    }
+
+    /// Whether the clock of this node is set automatically, if the node supports it
+    pub fn clock_sync_enabled(&self) -> bool {
+        self.driver().clock_sync_enabled(self.id)
+    }
+
+    /// Changes when the values of this node are updated after Set commands.
+    /// `None` uses the driver's setting.
+    pub fn set_optimistic_updates(&self, optimistic_updates: Option<OptimisticUpdates>) {
+        self.state().set_optimistic_updates(optimistic_updates);
+    }
+
+    /// The labels the user assigned to this node
+    pub fn user_metadata(&self) -> NodeUserMetadata {
+        self.state().user_metadata().unwrap_or_default()
+    }
+
+    /// Changes the labels the user assigned to this node
+    pub fn set_user_metadata(&self, metadata: NodeUserMetadata) {
+        self.update_user_metadata(|current| *current = metadata);
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.user_metadata().name
+    }
+
+    pub fn set_name(&self, name: Option<&str>) {
+        self.update_user_metadata(|metadata| metadata.name = name.map(String::from));
+    }
+
+    pub fn location(&self) -> Option<String> {
+        self.user_metadata().location
+    }
+
+    pub fn set_location(&self, location: Option<&str>) {
+        self.update_user_metadata(|metadata| metadata.location = location.map(String::from));
+    }
+
+    pub fn room(&self) -> Option<String> {
+        self.user_metadata().room
+    }
+
+    pub fn set_room(&self, room: Option<&str>) {
+        self.update_user_metadata(|metadata| metadata.room = room.map(String::from));
+    }
+
+    pub fn notes(&self) -> Option<String> {
+        self.user_metadata().notes
+    }
+
+    pub fn set_notes(&self, notes: Option<&str>) {
+        self.update_user_metadata(|metadata| metadata.notes = notes.map(String::from));
+    }
+
+    fn update_user_metadata(&self, update: impl FnOnce(&mut NodeUserMetadata)) {
+        if let Some(metadata) = self.state().update_user_metadata(update) {
+            self.driver()
+                .emit_event(DriverEvent::NodeUserMetadataChanged {
+                    node_id: self.id,
+                    metadata,
+                });
+        }
+    }
+
     fn state(&self) -> NodeStateRef<'_> {
         self.controller.node_state(self.id)
     }
@@ -92,17 +298,40 @@ impl<'a> Node<'a> {
         self.state().endpoint(EndpointIndex::Root)
     }
 
-    /// Pings the node and returns whether it responded or not.
-    pub async fn ping(&self) -> ControllerCommandResult<bool> {
+    /// Changes how often the node wakes up. The interval (in seconds) is adjusted to what the
+    /// node supports, and the controller is configured as the destination of the wakeup notifications.
+    pub async fn set_wakeup_interval(&self, interval: u32) -> CCAPIResult<u32> {
+        let api = self.cc_api().wake_up();
+        let interval = api.clamp_interval(interval);
+        api.set_interval(interval, self.controller.own_node_id())
+            .await?;
+        Ok(interval)
+    }
+
+    /// Pings the node and returns whether it responded, how long that took and which route was used.
+    /// The result is also recorded in the node's [statistics](Self::statistics).
+    pub async fn ping(&self) -> ControllerCommandResult<PingResult> {
         // ^ Although this is a node command, the only errors we want to surface are controller errors
-        let cc = NoOperationCC {}.with_destination(self.id.into());
-        let result = self.driver().exec_node_command(&cc.into(), None).await;
-        match result {
-            Ok(_) => Ok(true),
-            Err(ExecNodeCommandError::NodeNoAck) => Ok(false),
-            Err(ExecNodeCommandError::Controller(e)) => Err(e),
-            Err(ExecNodeCommandError::NodeTimeout) => panic!("NoOperation CC should not time out"),
-        }
+        self.driver().ping_node(self.id, None).await
+    }
+
+    /// Sends a CC with the given command and payload to the node, without interpreting it.
+    /// If a response predicate is given, the raw form of the matching response is returned.
+    pub async fn send_raw_cc(
+        &self,
+        cc_id: CommandClasses,
+        cc_command: Option<u8>,
+        payload: impl Into<Bytes>,
+        test_response: Option<RawCCPredicate>,
+    ) -> ExecNodeCommandResult<Option<CCRaw>> {
+        let cc = CCRaw {
+            cc_id,
+            cc_command,
+            payload: payload.into(),
+        };
+        self.driver()
+            .exec_raw_cc(self.id, cc, test_response, None)
+            .await
     }
 }
 
@@ -111,7 +340,7 @@ impl<'a> EndpointLike<'a> for Node<'a> {
         self.id
     }
 
-    fn get_node(&self) -> &Node<'a> {
+    fn root_endpoint(&'a self) -> &'a dyn EndpointLike<'a> {
         // A node IS the root endpoint
         self
     }
@@ -124,6 +353,14 @@ impl<'a> EndpointLike<'a> for Node<'a> {
         EndpointValueCache::new(self, self.driver().value_cache())
     }
 
+    fn exec_node_command<'b>(
+        &'b self,
+        cc: &'b WithAddress<CC>,
+        options: Option<&'b ExecNodeCommandOptions>,
+    ) -> ExecNodeCommandFuture<'b> {
+        Box::pin(self.driver().exec_node_command(cc, options))
+    }
+
     fn modify_cc_info(&self, cc: CommandClasses, info: &PartialCommandClassInfo) {
         self.endpoint_state().merge_command_class_info(cc, info);
     }
@@ -157,6 +394,18 @@ impl<'a> EndpointLike<'a> for Node<'a> {
             .driver()
             .node_log(self.node_id(), self.index())
     }
+
+    fn own_node_id(&self) -> NodeId {
+        self.controller.own_node_id()
+    }
+
+    fn wake_up_options(&self) -> WakeUpOptions {
+        self.controller.driver().wake_up_options()
+    }
+
+    fn version_query_options(&self) -> VersionQueryOptions {
+        self.controller.driver().version_query_options()
+    }
 }
 
 pub struct Endpoint<'a> {
@@ -190,7 +439,7 @@ impl<'a> EndpointLike<'a> for Endpoint<'a> {
         self.node.id()
     }
 
-    fn get_node(&'a self) -> &'a Node<'a> {
+    fn root_endpoint(&'a self) -> &'a dyn EndpointLike<'a> {
         self.node
     }
 
@@ -199,7 +448,15 @@ impl<'a> EndpointLike<'a> for Endpoint<'a> {
     }
 
     fn value_cache(&'a self) -> EndpointValueCache<'a> {
-        EndpointValueCache::new(self, self.get_node().driver().value_cache())
+        EndpointValueCache::new(self, self.node.driver().value_cache())
+    }
+
+    fn exec_node_command<'b>(
+        &'b self,
+        cc: &'b WithAddress<CC>,
+        options: Option<&'b ExecNodeCommandOptions>,
+    ) -> ExecNodeCommandFuture<'b> {
+        Box::pin(self.node.driver().exec_node_command(cc, options))
     }
 
     fn modify_cc_info(&self, cc: CommandClasses, info: &PartialCommandClassInfo) {
@@ -219,7 +476,7 @@ impl<'a> EndpointLike<'a> for Endpoint<'a> {
     }
 
     fn supports_cc(&self, cc: CommandClasses) -> bool {
-        self.state().supports_command_class(cc)
+        self.supports_cc_effectively(cc)
     }
 
     fn controls_cc(&self, cc: CommandClasses) -> bool {
@@ -227,7 +484,7 @@ impl<'a> EndpointLike<'a> for Endpoint<'a> {
     }
 
     fn get_cc_version(&self, cc: CommandClasses) -> Option<u8> {
-        self.state().command_class_version(cc)
+        self.effective_cc_version(cc)
     }
 
     fn logger(&self) -> NodeLogger<'_> {
@@ -235,4 +492,62 @@ impl<'a> EndpointLike<'a> for Endpoint<'a> {
             .driver()
             .node_log(self.node_id(), self.index())
     }
+
+    fn own_node_id(&self) -> NodeId {
+        self.controller.own_node_id()
+    }
+
+    fn wake_up_options(&self) -> WakeUpOptions {
+        self.controller.driver().wake_up_options()
+    }
+
+    fn version_query_options(&self) -> VersionQueryOptions {
+        self.controller.driver().version_query_options()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+
+    #[test]
+    fn test_user_metadata_changes_are_emitted() {
+        let controller = MockController::new();
+        let metadata = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(2u8), NodeStorage::new(mock_protocol_data(true)));
+            });
+            let controller = Controller::mock(&driver);
+            let node = controller.node(NodeId::new(2u8)).unwrap();
+            node.set_name(Some("Ceiling light"));
+            node.set_location(Some("Ground floor"));
+            node.set_room(Some("Kitchen"));
+            node.set_notes(Some("Replace the bulb"));
+            // Setting the same value again is not a change
+            node.set_room(Some("Kitchen"));
+            node.set_notes(None);
+
+            assert_eq!(node.name().as_deref(), Some("Ceiling light"));
+            assert_eq!(node.location().as_deref(), Some("Ground floor"));
+            assert_eq!(node.room().as_deref(), Some("Kitchen"));
+            assert_eq!(node.notes(), None);
+            node.user_metadata()
+        });
+
+        let changes: Vec<_> = controller
+            .take_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                DriverEvent::NodeUserMetadataChanged { node_id, metadata } => {
+                    assert_eq!(node_id, NodeId::new(2u8));
+                    Some(metadata)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(changes.len(), 5);
+        assert_eq!(changes.last(), Some(&metadata));
+    }
 }
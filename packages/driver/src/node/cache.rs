@@ -22,6 +22,12 @@ impl<'a> EndpointValueCache<'a> {
     fn get_value_id(&self, value_id: &ValueId) -> EndpointValueId {
         EndpointValueId::new(self.endpoint.node_id(), self.endpoint.index(), *value_id)
     }
+
+    /// Returns the IDs of the cached values of this endpoint
+    pub fn value_ids(&self) -> Vec<ValueId> {
+        self.driver_value_cache
+            .value_ids(self.endpoint.node_id(), self.endpoint.index())
+    }
 }
 
 impl Cache<ValueId> for EndpointValueCache<'_> {
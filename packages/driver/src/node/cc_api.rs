@@ -1,7 +1,11 @@
-use crate::{ControllerCommandError, Endpoint, EndpointLike, ExecNodeCommandError, Node};
+use crate::{
+    Cancelled, ControllerCommandError, Endpoint, EndpointLike, ExecNodeCommandError, Node,
+};
 use proc_macros::impl_cc_apis;
 use thiserror::Error;
+use zwave_cc::prelude::CCEncodingError;
 use zwave_core::definitions::*;
+use zwave_core::values::{DurationSet, TransitionDuration};
 
 pub trait CCAPI<'a> {
     fn new(endpoint: &'a dyn EndpointLike<'a>) -> Self
@@ -54,6 +58,20 @@ pub enum CCAPIError {
     Controller(ControllerCommandError),
     #[error("The node did not acknowledge the command")]
     NodeNoAck,
+    #[error("The command cannot be sent: {0}")]
+    Encoding(CCEncodingError),
+    #[error("The command must be sent securely, but {0}")]
+    EncryptionImpossible(&'static str),
+    #[error("The node is asleep, the command will be sent when it wakes up")]
+    Queued,
+    #[error("The node is dead and repeatedly failed to acknowledge commands")]
+    NodeUnreachable,
+}
+
+impl From<Cancelled> for CCAPIError {
+    fn from(value: Cancelled) -> Self {
+        Self::Controller(value.into())
+    }
 }
 
 impl From<ExecNodeCommandError> for CCAPIError {
@@ -61,6 +79,12 @@ impl From<ExecNodeCommandError> for CCAPIError {
         match err {
             ExecNodeCommandError::Controller(err) => Self::Controller(err),
             ExecNodeCommandError::NodeNoAck => Self::NodeNoAck,
+            ExecNodeCommandError::Encoding(err) => Self::Encoding(err),
+            ExecNodeCommandError::EncryptionImpossible(reason) => {
+                Self::EncryptionImpossible(reason)
+            }
+            ExecNodeCommandError::Queued => Self::Queued,
+            ExecNodeCommandError::NodeUnreachable => Self::NodeUnreachable,
             ExecNodeCommandError::NodeTimeout => {
                 panic!("Timed out CC API call should have been converted to None")
             }
@@ -68,6 +92,20 @@ impl From<ExecNodeCommandError> for CCAPIError {
     }
 }
 
+/// Encodes a transition duration for a Set command. Durations that cannot be encoded
+/// are clamped to the longest possible duration, which is logged as a warning.
+pub(crate) fn encode_transition_duration(
+    endpoint: &dyn EndpointLike<'_>,
+    duration: TransitionDuration,
+) -> DurationSet {
+    DurationSet::try_from(duration).unwrap_or_else(|e| {
+        endpoint
+            .logger()
+            .warn(|| format!("{}, using {} instead", e, e.clamped));
+        e.clamped
+    })
+}
+
 macro_rules! expect_cc_or_timeout {
     ($actual:expr, $expected:ident) => {
         match $actual {
@@ -25,7 +25,7 @@ impl<'a> CCAPI<'a> for BasicCCAPI<'a> {
 
     async fn interview(&self) -> CCAPIResult<()> {
         let endpoint = self.endpoint;
-        let node = endpoint.get_node();
+        let node = endpoint.root_endpoint();
         let cache = node.value_cache();
         let log = endpoint.logger();
 
@@ -64,23 +64,81 @@ impl<'a> CCAPI<'a> for BasicCCAPI<'a> {
 
 impl BasicCCAPI<'_> {
     pub async fn set(&self, value: LevelSet) -> CCAPIResult<()> {
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
         let cc = BasicCCSet::builder()
             .target_value(value)
             .build()
-            .with_destination(node.id().into());
-        driver.exec_node_command(&cc.into(), None).await?;
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
         Ok(())
     }
 
     pub async fn get(&self) -> CCAPIResult<Option<BasicCCReport>> {
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
-        let cc = BasicCCGet::default().with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+        let cc = BasicCCGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, BasicCCReport);
 
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::mock::{MockNode, MockResponse};
+    use crate::CCAPIError;
+    use futures::executor::block_on;
+    use zwave_cc::commandclass::CC;
+
+    #[test]
+    fn test_get() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::Basic, 2);
+        let report = BasicCCReport {
+            current_value: LevelReport::Level(50),
+            target_value: None,
+            duration: None,
+        };
+        node.respond_with(report.clone());
+
+        let api = BasicCCAPI::new(&node);
+        let response = block_on(api.get()).unwrap();
+        assert_eq!(response, Some(report));
+        node.assert_sent(&[BasicCCGet::default().into()]);
+        node.assert_script_exhausted();
+
+        // Without a scripted response, the node does not respond
+        let response = block_on(api.get()).unwrap();
+        assert_eq!(response, None);
+    }
+
+    #[test]
+    fn test_set() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::Basic, 2);
+        let api = BasicCCAPI::new(&node);
+        block_on(api.set(LevelSet::On)).unwrap();
+
+        node.on(|cc| matches!(cc, CC::BasicCCSet(_)), MockResponse::NoAck);
+        let result = block_on(api.set(LevelSet::Level(0)));
+        assert!(matches!(result, Err(CCAPIError::NodeNoAck)));
+
+        node.assert_sent(&[
+            BasicCCSet::builder().target_value(LevelSet::On).build().into(),
+            BasicCCSet::builder()
+                .target_value(LevelSet::Level(0))
+                .build()
+                .into(),
+        ]);
+    }
+
+    #[test]
+    fn test_interview() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::Basic, 2);
+        node.respond_with(BasicCCReport {
+            current_value: LevelReport::Unknown,
+            target_value: None,
+            duration: None,
+        });
+
+        block_on(BasicCCAPI::new(&node).interview()).unwrap();
+        node.assert_sent_matching(&[&|cc| matches!(cc, CC::BasicCCGet(_))]);
+    }
+}
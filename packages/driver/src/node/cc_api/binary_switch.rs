@@ -1,5 +1,5 @@
 use zwave_pal::prelude::*;
-use crate::expect_cc_or_timeout;
+use crate::{encode_transition_duration, expect_cc_or_timeout};
 use crate::{CCAPIResult, EndpointLike, CCAPI};
 use zwave_cc::commandclass::{binary_switch::*, CCAddressable};
 use zwave_core::prelude::*;
@@ -55,24 +55,57 @@ impl BinarySwitchCCAPI<'_> {
         // and implement the supports_get() method using the zwccapisupp snippet
         // FIXME: get is only supported in singlecast
 
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
-        let cc = BinarySwitchCCGet::default().with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+        let cc = BinarySwitchCCGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, BinarySwitchCCReport);
 
         Ok(response)
     }
 
-    pub async fn set(&self, value: BinarySet, duration: Option<DurationSet>) -> CCAPIResult<()> {
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
+    /// Turns the switch on or off. The transition duration is only supported in version 2.
+    pub async fn set(
+        &self,
+        value: BinarySet,
+        duration: Option<TransitionDuration>,
+    ) -> CCAPIResult<()> {
+        let duration = duration.map(|duration| encode_transition_duration(self.endpoint, duration));
         let cc = BinarySwitchCCSet::builder()
             .target_value(value)
             .duration(duration)
             .build()
-            .with_destination(node.id().into());
-        driver.exec_node_command(&cc.into(), None).await?;
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::mock::MockNode;
+    use core::time::Duration;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_set_clamps_duration() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::BinarySwitch, 2);
+        let api = BinarySwitchCCAPI::new(&node);
+
+        block_on(api.set(BinarySet::On, Some(Duration::from_secs(150).into()))).unwrap();
+        block_on(api.set(BinarySet::Off, Some(Duration::from_secs(3 * 3600).into()))).unwrap();
+
+        node.assert_sent(&[
+            BinarySwitchCCSet::builder()
+                .target_value(BinarySet::On)
+                .duration(Some(DurationSet::Minutes(3)))
+                .build()
+                .into(),
+            // Too long durations are clamped
+            BinarySwitchCCSet::builder()
+                .target_value(BinarySet::Off)
+                .duration(Some(DurationSet::Minutes(127)))
+                .build()
+                .into(),
+        ]);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::expect_cc_or_timeout;
+use crate::{CCAPI, CCAPIResult, EndpointLike};
+use zwave_cc::commandclass::{CCAddressable, installation_maintenance::*};
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+pub struct InstallationMaintenanceCCAPI<'a> {
+    endpoint: &'a dyn EndpointLike<'a>,
+}
+
+impl<'a> CCAPI<'a> for InstallationMaintenanceCCAPI<'a> {
+    fn new(endpoint: &'a dyn EndpointLike<'a>) -> Self
+    where
+        Self: Sized,
+    {
+        Self { endpoint }
+    }
+
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::NetworkManagementInstallationAndMaintenance
+    }
+
+    fn cc_version(&self) -> u8 {
+        3
+    }
+
+    async fn interview(&self) -> CCAPIResult<()> {
+        // Nothing to do
+        Ok(())
+    }
+
+    async fn refresh_values(&self) -> CCAPIResult<()> {
+        // The statistics are queried on demand
+        Ok(())
+    }
+}
+
+impl InstallationMaintenanceCCAPI<'_> {
+    /// Queries the route the node uses to reach the given node
+    pub async fn get_priority_route(
+        &self,
+        node_id: NodeId,
+    ) -> CCAPIResult<Option<InstallationMaintenanceCCPriorityRouteReport>> {
+        let cc = InstallationMaintenanceCCPriorityRouteGet::builder()
+            .node_id(node_id)
+            .build()
+            .with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response =
+            expect_cc_or_timeout!(response, InstallationMaintenanceCCPriorityRouteReport);
+
+        Ok(response)
+    }
+
+    /// Assigns the route the node uses to reach the given node
+    pub async fn set_priority_route(
+        &self,
+        node_id: NodeId,
+        repeaters: Vec<NodeId>,
+        speed: DataRate,
+    ) -> CCAPIResult<()> {
+        let cc = InstallationMaintenanceCCPriorityRouteSet::builder()
+            .node_id(node_id)
+            .repeaters(repeaters)
+            .speed(speed)
+            .build()
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
+        Ok(())
+    }
+
+    /// Queries the statistics the node collected about its communication with the given node
+    pub async fn get_statistics(&self, node_id: NodeId) -> CCAPIResult<Option<LinkStatistics>> {
+        let cc = InstallationMaintenanceCCStatisticsGet::builder()
+            .node_id(node_id)
+            .build()
+            .with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, InstallationMaintenanceCCStatisticsReport);
+
+        Ok(response.map(|report| report.statistics))
+    }
+
+    /// Resets the statistics the node collected
+    pub async fn clear_statistics(&self) -> CCAPIResult<()> {
+        let cc = InstallationMaintenanceCCStatisticsClear::default()
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
+        Ok(())
+    }
+
+    /// Queries the background noise the node measures on each channel
+    pub async fn get_rssi(&self) -> CCAPIResult<Option<Vec<RSSI>>> {
+        let cc = InstallationMaintenanceCCRssiGet::default()
+            .with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, InstallationMaintenanceCCRssiReport);
+
+        Ok(response.map(|report| report.channels))
+    }
+}
@@ -50,10 +50,8 @@ impl<'a> CCAPI<'a> for ManufacturerSpecificCCAPI<'a> {
 
 impl ManufacturerSpecificCCAPI<'_> {
     pub async fn get(&self) -> CCAPIResult<Option<ManufacturerSpecificCCReport>> {
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
-        let cc = ManufacturerSpecificCCGet::default().with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+        let cc = ManufacturerSpecificCCGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, ManufacturerSpecificCCReport);
 
         Ok(response)
@@ -69,15 +67,49 @@ impl ManufacturerSpecificCCAPI<'_> {
     ) -> CCAPIResult<Option<Vec<u8>>> {
         cc_api_assert_supported!(self, get_device_specific);
 
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
         let cc = ManufacturerSpecificCCDeviceSpecificGet::builder()
             .device_id_type(device_id_type)
             .build()
-            .with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+            .with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, ManufacturerSpecificCCDeviceSpecificReport);
 
         Ok(response.map(|r| r.device_id))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::mock::MockNode;
+    use crate::CCAPIError;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_get_device_specific() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::ManufacturerSpecific, 1);
+        let api = ManufacturerSpecificCCAPI::new(&node);
+
+        // Version 1 does not support Device Specific Get
+        let result = block_on(api.get_device_specific(DeviceIdType::SerialNumber));
+        assert!(matches!(result, Err(CCAPIError::NotSupported { .. })));
+        node.assert_sent(&[]);
+
+        node.modify_cc_info(
+            CommandClasses::ManufacturerSpecific,
+            &PartialCommandClassInfo::default().version(2),
+        );
+        node.respond_with(
+            ManufacturerSpecificCCDeviceSpecificReport::builder()
+                .device_id_type(DeviceIdType::SerialNumber)
+                .device_id(vec![0x12, 0x34])
+                .build(),
+        );
+        let result = block_on(api.get_device_specific(DeviceIdType::SerialNumber)).unwrap();
+        assert_eq!(result, Some(vec![0x12, 0x34]));
+        node.assert_sent(&[ManufacturerSpecificCCDeviceSpecificGet::builder()
+            .device_id_type(DeviceIdType::SerialNumber)
+            .build()
+            .into()]);
+    }
+}
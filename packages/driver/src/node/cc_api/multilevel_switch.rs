@@ -0,0 +1,115 @@
+use crate::{CCAPI, CCAPIResult, EndpointLike};
+use crate::{encode_transition_duration, expect_cc_or_timeout};
+use zwave_cc::commandclass::{CCAddressable, multilevel_switch::*};
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+
+pub struct MultilevelSwitchCCAPI<'a> {
+    endpoint: &'a dyn EndpointLike<'a>,
+}
+
+impl<'a> CCAPI<'a> for MultilevelSwitchCCAPI<'a> {
+    fn new(endpoint: &'a dyn EndpointLike<'a>) -> Self
+    where
+        Self: Sized,
+    {
+        Self { endpoint }
+    }
+
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::MultilevelSwitch
+    }
+
+    fn cc_version(&self) -> u8 {
+        4
+    }
+
+    async fn interview(&self) -> CCAPIResult<()> {
+        let log = self.endpoint.logger();
+
+        log.info(|| "interviewing Multilevel Switch CC...");
+
+        // Try to query the current state
+        self.refresh_values().await?;
+
+        Ok(())
+    }
+
+    async fn refresh_values(&self) -> CCAPIResult<()> {
+        let log = self.endpoint.logger();
+
+        log.info(|| "querying Multilevel Switch state...");
+
+        if let Some(response) = self.get().await? {
+            log.info(|| format!("received Multilevel Switch CC state: {:?}", response));
+        }
+
+        Ok(())
+    }
+}
+
+impl MultilevelSwitchCCAPI<'_> {
+    pub async fn get(&self) -> CCAPIResult<Option<MultilevelSwitchCCReport>> {
+        // Test support for this command:
+        // cc_api_assert_supported!(self, get);
+        // and implement the supports_get() method using the zwccapisupp snippet
+        // FIXME: get is only supported in singlecast
+
+        let cc = MultilevelSwitchCCGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, MultilevelSwitchCCReport);
+
+        Ok(response)
+    }
+
+    /// Sets the switch to the given level. The transition duration is only supported in version 2 and higher.
+    pub async fn set(
+        &self,
+        value: LevelSet,
+        duration: Option<TransitionDuration>,
+    ) -> CCAPIResult<()> {
+        let duration = duration.map(|duration| encode_transition_duration(self.endpoint, duration));
+        let cc = MultilevelSwitchCCSet::builder()
+            .target_value(value)
+            .duration(duration)
+            .build()
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::mock::MockNode;
+    use core::time::Duration;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_set_clamps_duration() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::MultilevelSwitch, 2);
+        let api = MultilevelSwitchCCAPI::new(&node);
+
+        block_on(api.set(LevelSet::Level(99), Some(Duration::from_secs(150).into()))).unwrap();
+        block_on(api.set(
+            LevelSet::Level(0),
+            Some(Duration::from_secs(3 * 3600).into()),
+        ))
+        .unwrap();
+
+        node.assert_sent(&[
+            MultilevelSwitchCCSet::builder()
+                .target_value(LevelSet::Level(99))
+                .duration(Some(DurationSet::Minutes(3)))
+                .build()
+                .into(),
+            // Too long durations are clamped
+            MultilevelSwitchCCSet::builder()
+                .target_value(LevelSet::Level(0))
+                .duration(Some(DurationSet::Minutes(127)))
+                .build()
+                .into(),
+        ]);
+    }
+}
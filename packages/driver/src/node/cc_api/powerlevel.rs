@@ -0,0 +1,83 @@
+use crate::expect_cc_or_timeout;
+use crate::{CCAPI, CCAPIResult, EndpointLike};
+use zwave_cc::commandclass::{CCAddressable, powerlevel::*};
+use zwave_core::prelude::*;
+
+pub struct PowerlevelCCAPI<'a> {
+    endpoint: &'a dyn EndpointLike<'a>,
+}
+
+impl<'a> CCAPI<'a> for PowerlevelCCAPI<'a> {
+    fn new(endpoint: &'a dyn EndpointLike<'a>) -> Self
+    where
+        Self: Sized,
+    {
+        Self { endpoint }
+    }
+
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::Powerlevel
+    }
+
+    fn cc_version(&self) -> u8 {
+        1
+    }
+
+    async fn interview(&self) -> CCAPIResult<()> {
+        // Nothing to do
+        Ok(())
+    }
+
+    async fn refresh_values(&self) -> CCAPIResult<()> {
+        // Nothing that requires refreshing
+        Ok(())
+    }
+}
+
+impl PowerlevelCCAPI<'_> {
+    pub async fn get(&self) -> CCAPIResult<Option<PowerlevelCCReport>> {
+        let cc = PowerlevelCCGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, PowerlevelCCReport);
+
+        Ok(response)
+    }
+
+    /// Reduces the powerlevel of the node for the given number of seconds
+    pub async fn set(&self, powerlevel: NodePowerlevel, timeout: u8) -> CCAPIResult<()> {
+        let cc = PowerlevelCCSet::builder()
+            .powerlevel(powerlevel)
+            .timeout(timeout)
+            .build()
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
+        Ok(())
+    }
+
+    /// Instructs the node to send test frames with the given powerlevel to another node.
+    /// The result can be queried with [`get_node_test_result`](Self::get_node_test_result).
+    pub async fn test_node(
+        &self,
+        test_node_id: NodeId,
+        powerlevel: NodePowerlevel,
+        test_frame_count: u16,
+    ) -> CCAPIResult<()> {
+        let cc = PowerlevelCCTestNodeSet::builder()
+            .test_node_id(test_node_id)
+            .powerlevel(powerlevel)
+            .test_frame_count(test_frame_count)
+            .build()
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
+        Ok(())
+    }
+
+    pub async fn get_node_test_result(&self) -> CCAPIResult<Option<PowerlevelCCTestNodeReport>> {
+        let cc =
+            PowerlevelCCTestNodeGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, PowerlevelCCTestNodeReport);
+
+        Ok(response)
+    }
+}
@@ -1,8 +1,8 @@
 use zwave_pal::prelude::*;
 use crate::expect_cc_or_timeout;
-use crate::{CCAPIResult, EndpointLike, CCAPI};
-use zwave_cc::commandclass::{security::*, CCAddressable};
-use zwave_core::security::S0Nonce;
+use crate::{CCAPIResult, EndpointLike, ExecNodeCommandResult, CCAPI};
+use zwave_cc::commandclass::{security::*, CCAddressable, CC};
+use zwave_core::security::{NetworkKey, S0Nonce};
 use zwave_core::prelude::*;
 
 pub struct SecurityCCAPI<'a> {
@@ -32,7 +32,7 @@ impl<'a> CCAPI<'a> for SecurityCCAPI<'a> {
 
     async fn interview(&self) -> CCAPIResult<()> {
         let endpoint = self.endpoint;
-        let node = endpoint.get_node();
+        let node = endpoint.root_endpoint();
         let cache = node.value_cache();
         let log = endpoint.logger();
 
@@ -48,17 +48,98 @@ impl<'a> CCAPI<'a> for SecurityCCAPI<'a> {
 }
 
 impl SecurityCCAPI<'_> {
+    /// Sends a command encapsulated in a SecurityCCCommandEncapsulation
+    /// and returns the decrypted response
+    async fn exec_encapsulated(&self, cc: CC) -> ExecNodeCommandResult<Option<CC>> {
+        let cc = SecurityCCCommandEncapsulation::new(cc)
+            .with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await?;
+        Ok(response.map(|response| match response {
+            CC::SecurityCCCommandEncapsulation(encapsulation) => encapsulation
+                .into_encapsulated()
+                .expect("a matching response is always complete"),
+            other => other,
+        }))
+    }
+
+    /// Queries the security schemes supported by the node. This is the first step of the
+    /// S0 bootstrapping and is the only command which is sent without encryption.
+    pub async fn get_scheme(&self) -> CCAPIResult<Option<SecurityCCSchemeReport>> {
+        let cc = SecurityCCSchemeGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, SecurityCCSchemeReport);
+
+        Ok(response)
+    }
+
+    /// Sends the network key to the node and returns whether the node verified it.
+    /// During the S0 bootstrapping, this must be encrypted with the temporary key.
+    pub async fn set_network_key(&self, network_key: &NetworkKey) -> CCAPIResult<bool> {
+        let cc = SecurityCCNetworkKeySet::builder()
+            .network_key(network_key.clone())
+            .build();
+        let response = self.exec_encapsulated(cc.into()).await;
+        let response = expect_cc_or_timeout!(response, SecurityCCNetworkKeyVerify);
+
+        Ok(response.is_some())
+    }
+
+    /// Queries the CCs which the node only supports or controls securely
+    pub async fn get_supported_commands(
+        &self,
+    ) -> CCAPIResult<Option<SecurityCCCommandsSupportedReport>> {
+        let cc = SecurityCCCommandsSupportedGet::default();
+        let response = self.exec_encapsulated(cc.into()).await;
+        let response = expect_cc_or_timeout!(response, SecurityCCCommandsSupportedReport);
+
+        // FIXME: Collect the remaining reports if the node sends more than one
+        Ok(response)
+    }
+
     pub async fn get_nonce(&self) -> CCAPIResult<Option<S0Nonce>> {
         // Optional: Test support for this command:
         // cc_api_assert_supported!(self, get);
         // and implement the supports_get() method using the zwccapisupp snippet
 
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
-        let cc = SecurityCCNonceGet::default().with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+        let cc = SecurityCCNonceGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, SecurityCCNonceReport);
 
         Ok(response.map(|r| r.nonce))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::mock::MockNode;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_get_supported_commands_is_encapsulated() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::Security, 1);
+        let report = SecurityCCCommandsSupportedReport::builder()
+            .supported_command_classes(vec![CommandClasses::DoorLock])
+            .build();
+        node.respond_with(SecurityCCCommandEncapsulation::new(report.clone().into()));
+
+        let response = block_on(SecurityCCAPI::new(&node).get_supported_commands()).unwrap();
+
+        assert_eq!(response, Some(report));
+        node.assert_sent(&[SecurityCCCommandEncapsulation::new(
+            SecurityCCCommandsSupportedGet::default().into(),
+        )
+        .into()]);
+    }
+
+    #[test]
+    fn test_set_network_key_without_verify() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::Security, 1);
+
+        // The node does not respond, because it could not decrypt the command
+        let network_key = NetworkKey::from([0x11; 16]);
+        let verified = block_on(SecurityCCAPI::new(&node).set_network_key(&network_key)).unwrap();
+
+        assert!(!verified);
+    }
+}
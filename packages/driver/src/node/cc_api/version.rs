@@ -3,6 +3,7 @@ use crate::{cc_api_assert_supported, expect_cc_or_timeout, get_implemented_versi
 use crate::{CCAPIResult, EndpointLike, CCAPI};
 use zwave_cc::commandclass::{version::*, CCAddressable};
 use zwave_core::{cache::CacheExt, prelude::*};
+use zwave_pal::time::Timer;
 
 pub struct VersionCCAPI<'a> {
     endpoint: &'a dyn EndpointLike<'a>,
@@ -30,7 +31,7 @@ impl<'a> CCAPI<'a> for VersionCCAPI<'a> {
 
     async fn interview(&self) -> CCAPIResult<()> {
         let endpoint = self.endpoint;
-        let node = endpoint.get_node();
+        let node = endpoint.root_endpoint();
         let cache = node.value_cache();
         let log = endpoint.logger();
 
@@ -49,7 +50,11 @@ impl<'a> CCAPI<'a> for VersionCCAPI<'a> {
 
         // On the root endpoint, query the VersionCC version and static version information
         if endpoint.index() == EndpointIndex::Root {
-            api.query_cc_version(CommandClasses::Version).await?;
+            if node.get_cc_version(CommandClasses::Version) > Some(0) {
+                log.info(|| "version of the Version CC is already known");
+            } else {
+                api.query_cc_version(CommandClasses::Version).await?;
+            }
             // TODO: When we use CC versions to check support for features,
             // we might have to update the version after this call
 
@@ -59,18 +64,33 @@ impl<'a> CCAPI<'a> for VersionCCAPI<'a> {
             }
         }
 
-        // Query all other CC versions
+        // Query the versions of all other CCs the endpoint advertises
+        let options = endpoint.version_query_options();
+        let mut queried: usize = 0;
         for cc in endpoint.supported_command_classes() {
             // Skip Version CC itself which we already queried
             if cc == CommandClasses::Version {
                 continue;
             }
-            // Skip the query of endpoint CCs that are also supported by the root device
-            if endpoint.index() > EndpointIndex::Root && node.get_cc_version(cc) > Some(0) {
+            // Skip CCs whose version is already known, e.g. from a previous interview.
+            // This includes endpoint CCs that are also supported by the root device.
+            if endpoint.get_cc_version(cc) > Some(0) || node.get_cc_version(cc) > Some(0) {
+                continue;
+            }
+            // There is no point in knowing the version of CCs we cannot use
+            if get_implemented_version(cc).is_none() {
+                log.info(|| format!("skipping query for not yet implemented CC {}", cc));
                 continue;
             }
 
+            // Give other commands a chance between batches of queries
+            if queried > 0 && queried % options.batch_size.max(1) == 0 {
+                if let Some(delay) = options.batch_delay {
+                    Timer::after(delay).await;
+                }
+            }
             api.query_cc_version(cc).await?;
+            queried += 1;
         }
 
         // On the root device, query Version CC capabilities
@@ -109,11 +129,6 @@ impl VersionCCAPI<'_> {
     async fn query_cc_version(&self, cc: CommandClasses) -> CCAPIResult<()> {
         let log = self.endpoint.logger();
 
-        if get_implemented_version(cc).is_none() {
-            log.info(|| format!("skipping query for not yet implemented CC {}", cc));
-            return Ok(());
-        }
-
         log.info(|| format!("querying version for CC {}...", cc));
         if let Some(version) = self.get_cc_version(cc).await? {
             if version > 0 {
@@ -145,23 +160,19 @@ impl VersionCCAPI<'_> {
 
 impl VersionCCAPI<'_> {
     pub async fn get(&self) -> CCAPIResult<Option<VersionCCReport>> {
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
-        let cc = VersionCCGet::default().with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+        let cc = VersionCCGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, VersionCCReport);
 
         Ok(response)
     }
 
     pub async fn get_cc_version(&self, cc: CommandClasses) -> CCAPIResult<Option<u8>> {
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
         let cc = VersionCCCommandClassGet::builder()
             .requested_cc(cc)
             .build()
-            .with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+            .with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, VersionCCCommandClassReport);
 
         Ok(response.map(|r| r.version))
@@ -174,10 +185,8 @@ impl VersionCCAPI<'_> {
     pub async fn get_capabilities(&self) -> CCAPIResult<Option<VersionCCCapabilitiesReport>> {
         cc_api_assert_supported!(self, get_capabilities);
 
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
-        let cc = VersionCCCapabilitiesGet::default().with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+        let cc = VersionCCCapabilitiesGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, VersionCCCapabilitiesReport);
 
         Ok(response)
@@ -190,12 +199,171 @@ impl VersionCCAPI<'_> {
     }
 
     pub async fn get_zwave_software(&self) -> CCAPIResult<Option<VersionCCZWaveSoftwareReport>> {
-        let node = self.endpoint.get_node();
-        let driver = node.driver();
-        let cc = VersionCCZWaveSoftwareGet::default().with_destination(node.id().into());
-        let response = driver.exec_node_command(&cc.into(), None).await;
+        let cc = VersionCCZWaveSoftwareGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
         let response = expect_cc_or_timeout!(response, VersionCCZWaveSoftwareReport);
 
         Ok(response)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::VersionQueryOptions;
+    use crate::node::mock::{MockNode, MockResponse};
+    use futures::executor::block_on;
+    use zwave_cc::commandclass::CC;
+
+    fn cc_version_report(cc: CommandClasses, version: u8) -> VersionCCCommandClassReport {
+        VersionCCCommandClassReport::builder()
+            .requested_cc(cc)
+            .version(version)
+            .build()
+    }
+
+    #[test]
+    fn test_interview_root() {
+        // The versions of the advertised CCs are not known yet
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::Version, 0)
+            .with_cc(CommandClasses::Basic, 0)
+            .with_cc(CommandClasses::BinarySwitch, 0);
+
+        node.respond_with(cc_version_report(CommandClasses::Version, 3));
+        node.respond_with(
+            VersionCCReport::builder()
+                .library_type(ZWaveLibraryType::EnhancedSlave)
+                .protocol_version(Version {
+                    major: 7,
+                    minor: 18,
+                    patch: None,
+                })
+                .firmware_versions(vec![Version {
+                    major: 1,
+                    minor: 2,
+                    patch: None,
+                }])
+                .hardware_version(None)
+                .build(),
+        );
+        node.respond_with(cc_version_report(CommandClasses::Basic, 2));
+        node.on(
+            |cc| matches!(cc, CC::VersionCCCommandClassGet(_)),
+            MockResponse::Timeout,
+        );
+        node.respond_with(
+            VersionCCCapabilitiesReport::builder()
+                .supports_zwave_software_get(false)
+                .build(),
+        );
+
+        block_on(VersionCCAPI::new(&node).interview()).unwrap();
+
+        node.assert_sent(&[
+            VersionCCCommandClassGet::builder()
+                .requested_cc(CommandClasses::Version)
+                .build()
+                .into(),
+            VersionCCGet::default().into(),
+            VersionCCCommandClassGet::builder()
+                .requested_cc(CommandClasses::Basic)
+                .build()
+                .into(),
+            VersionCCCommandClassGet::builder()
+                .requested_cc(CommandClasses::BinarySwitch)
+                .build()
+                .into(),
+            VersionCCCapabilitiesGet::default().into(),
+        ]);
+        node.assert_script_exhausted();
+
+        assert_eq!(node.get_cc_version(CommandClasses::Version), Some(3));
+        assert_eq!(node.get_cc_version(CommandClasses::Basic), Some(2));
+        // Timed out queries are assumed to be version 1
+        assert_eq!(node.get_cc_version(CommandClasses::BinarySwitch), Some(1));
+    }
+
+    #[test]
+    fn test_interview_removes_unsupported_cc() {
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::Version, 1)
+            .with_cc(CommandClasses::BinarySwitch, 0);
+        node.respond_with(cc_version_report(CommandClasses::BinarySwitch, 0));
+
+        block_on(VersionCCAPI::new(&node).interview()).unwrap();
+
+        assert!(!node.supports_cc(CommandClasses::BinarySwitch));
+    }
+
+    #[test]
+    fn test_interview_endpoint() {
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::Version, 3)
+            .with_cc(CommandClasses::Basic, 2)
+            .with_endpoint_cc(1, CommandClasses::Basic, 1)
+            .with_endpoint_cc(1, CommandClasses::BinarySwitch, 0);
+        node.respond_with(cc_version_report(CommandClasses::BinarySwitch, 2));
+
+        let endpoint = node.endpoint(1);
+        block_on(VersionCCAPI::new(&endpoint).interview()).unwrap();
+
+        // CCs that are also supported by the root device are not queried again,
+        // and all queries go to the root device
+        node.assert_sent_matching(&[&|cc| {
+            matches!(
+                cc,
+                CC::VersionCCCommandClassGet(get)
+                    if *get == VersionCCCommandClassGet::builder()
+                        .requested_cc(CommandClasses::BinarySwitch)
+                        .build()
+            )
+        }]);
+        node.assert_script_exhausted();
+    }
+
+    #[test]
+    fn test_interview_skips_known_and_unimplemented_ccs() {
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::Version, 1)
+            // Known from a previous interview
+            .with_cc(CommandClasses::Basic, 2)
+            // Not implemented by the library
+            .with_cc(CommandClasses::Clock, 0)
+            .with_cc(CommandClasses::BinarySwitch, 0);
+        node.respond_with(cc_version_report(CommandClasses::BinarySwitch, 2));
+
+        block_on(VersionCCAPI::new(&node).interview()).unwrap();
+
+        node.assert_sent(&[
+            VersionCCGet::default().into(),
+            VersionCCCommandClassGet::builder()
+                .requested_cc(CommandClasses::BinarySwitch)
+                .build()
+                .into(),
+        ]);
+        assert_eq!(node.get_cc_version(CommandClasses::Basic), Some(2));
+        assert_eq!(node.get_cc_version(CommandClasses::Clock), Some(0));
+    }
+
+    #[test]
+    fn test_interview_pauses_between_batches() {
+        let delay = core::time::Duration::from_millis(20);
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::Version, 1)
+            .with_cc(CommandClasses::Basic, 0)
+            .with_cc(CommandClasses::BinarySwitch, 0)
+            .with_version_query_options(
+                VersionQueryOptions::builder()
+                    .batch_size(1)
+                    .batch_delay(delay)
+                    .build(),
+            );
+
+        let started_at = zwave_pal::time::Instant::now();
+        block_on(VersionCCAPI::new(&node).interview()).unwrap();
+
+        // One pause between the two queries
+        assert!(zwave_pal::time::Instant::now() - started_at >= delay);
+    }
+}
@@ -0,0 +1,270 @@
+use zwave_pal::prelude::*;
+use crate::{cc_api_assert_supported, expect_cc_or_timeout};
+use crate::{CCAPIResult, EndpointLike, CCAPI};
+use zwave_cc::commandclass::{wake_up::*, CCAddressable};
+use zwave_core::cache::{Cache, CacheExt, CacheValue};
+use zwave_core::prelude::*;
+
+pub struct WakeUpCCAPI<'a> {
+    endpoint: &'a dyn EndpointLike<'a>,
+}
+
+impl<'a> CCAPI<'a> for WakeUpCCAPI<'a> {
+    fn new(endpoint: &'a dyn EndpointLike<'a>) -> Self
+    where
+        Self: Sized,
+    {
+        Self { endpoint }
+    }
+
+    fn cc_id(&self) -> CommandClasses {
+        CommandClasses::WakeUp
+    }
+
+    fn cc_version(&self) -> u8 {
+        3
+    }
+
+    fn interview_depends_on(&self) -> &'static [CommandClasses] {
+        &[CommandClasses::Version]
+    }
+
+    async fn interview(&self) -> CCAPIResult<()> {
+        let endpoint = self.endpoint;
+        let log = endpoint.logger();
+
+        log.info(|| "interviewing Wake Up CC...");
+
+        let mut capabilities = None;
+        if self.supports_get_interval_capabilities() == Some(true) {
+            log.info(|| "retrieving wakeup capabilities from the device...");
+            capabilities = self.get_interval_capabilities().await?;
+            if let Some(capabilities) = &capabilities {
+                log.info(|| format!("received wakeup capabilities: {:?}", capabilities));
+            }
+        }
+
+        log.info(|| "retrieving wakeup interval from the device...");
+        let current = self.get_interval().await?;
+        if let Some(current) = &current {
+            log.info(|| format!("received wakeup configuration: {:?}", current));
+        }
+
+        let options = endpoint.wake_up_options();
+        if options.keep_device_config {
+            log.info(|| "leaving the wakeup configuration of the device untouched");
+            return Ok(());
+        }
+
+        // Make sure the node notifies us when it wakes up
+        let own_node_id = endpoint.own_node_id();
+        let interval = match &capabilities {
+            Some(capabilities) => capabilities.clamp_interval(options.interval),
+            None => self.clamp_interval(options.interval),
+        };
+        if current.is_some_and(|current| {
+            current.wake_up_interval == interval && current.controller_node_id == own_node_id
+        }) {
+            log.info(|| "wakeup configuration is already up to date");
+            return Ok(());
+        }
+
+        log.info(|| {
+            format!(
+                "configuring wakeup destination node {} and interval {} seconds...",
+                own_node_id, interval
+            )
+        });
+        self.set_interval(interval, own_node_id).await?;
+
+        Ok(())
+    }
+
+    async fn refresh_values(&self) -> CCAPIResult<()> {
+        let log = self.endpoint.logger();
+
+        log.info(|| "querying wakeup interval...");
+
+        if let Some(response) = self.get_interval().await? {
+            log.info(|| format!("received wakeup configuration: {:?}", response));
+        }
+
+        Ok(())
+    }
+}
+
+impl WakeUpCCAPI<'_> {
+    /// Returns the supported wakeup interval closest to the given one,
+    /// based on the capabilities reported by the node
+    pub fn clamp_interval(&self, interval: u32) -> u32 {
+        let cache = self.endpoint.value_cache();
+        let min = cache.read_u32(&WakeUpCCValues::min_wake_up_interval().id);
+        let max = cache.read_u32(&WakeUpCCValues::max_wake_up_interval().id);
+        let steps = cache.read_u32(&WakeUpCCValues::wake_up_interval_steps().id);
+
+        match (min, max) {
+            (Some(min), Some(max)) => WakeUpCCIntervalCapabilitiesReport::builder()
+                .min_wake_up_interval(min)
+                .max_wake_up_interval(max)
+                .default_wake_up_interval(interval)
+                .wake_up_interval_steps(steps.unwrap_or_default())
+                .build()
+                .clamp_interval(interval),
+            // Without capabilities, the node has to accept every interval it can represent
+            _ => interval.min(WAKE_UP_INTERVAL_MAX),
+        }
+    }
+
+    pub async fn get_interval(&self) -> CCAPIResult<Option<WakeUpCCIntervalReport>> {
+        let cc = WakeUpCCIntervalGet::default().with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, WakeUpCCIntervalReport);
+
+        Ok(response)
+    }
+
+    pub fn supports_set_interval(&self) -> Option<bool> {
+        Some(self.endpoint.supports_cc(self.cc_id()))
+    }
+
+    /// Configures how often the node wakes up, and which node it notifies when it does.
+    /// The interval is sent as-is, see [`clamp_interval`](Self::clamp_interval).
+    pub async fn set_interval(
+        &self,
+        interval: u32,
+        controller_node_id: NodeId,
+    ) -> CCAPIResult<()> {
+        cc_api_assert_supported!(self, set_interval);
+
+        let cc = WakeUpCCIntervalSet::builder()
+            .wake_up_interval(interval)
+            .controller_node_id(controller_node_id)
+            .build()
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
+
+        // The node does not confirm the new configuration, so remember it ourselves
+        let mut cache = self.endpoint.value_cache();
+        cache.write(
+            &WakeUpCCValues::wake_up_interval().id,
+            CacheValue::from(interval),
+        );
+        cache.write(
+            &WakeUpCCValues::controller_node_id().id,
+            CacheValue::from(u16::from(controller_node_id)),
+        );
+
+        Ok(())
+    }
+
+    pub fn supports_get_interval_capabilities(&self) -> Option<bool> {
+        self.endpoint.get_cc_version(self.cc_id()).map(|v| v >= 2)
+    }
+
+    pub async fn get_interval_capabilities(
+        &self,
+    ) -> CCAPIResult<Option<WakeUpCCIntervalCapabilitiesReport>> {
+        cc_api_assert_supported!(self, get_interval_capabilities);
+
+        let cc = WakeUpCCIntervalCapabilitiesGet::default()
+            .with_destination(self.endpoint.node_id().into());
+        let response = self.endpoint.exec_node_command(&cc.into(), None).await;
+        let response = expect_cc_or_timeout!(response, WakeUpCCIntervalCapabilitiesReport);
+
+        Ok(response)
+    }
+
+    /// Tells the node that it may go back to sleep
+    pub async fn send_no_more_information(&self) -> CCAPIResult<()> {
+        let cc = WakeUpCCNoMoreInformation::default()
+            .with_destination(self.endpoint.node_id().into());
+        self.endpoint.exec_node_command(&cc.into(), None).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::WakeUpOptions;
+    use crate::node::mock::{MOCK_OWN_NODE_ID, MockNode};
+    use futures::executor::block_on;
+    use zwave_cc::commandclass::CC;
+
+    fn capabilities() -> WakeUpCCIntervalCapabilitiesReport {
+        WakeUpCCIntervalCapabilitiesReport::builder()
+            .min_wake_up_interval(600)
+            .max_wake_up_interval(86400)
+            .default_wake_up_interval(43200)
+            .wake_up_interval_steps(600)
+            .build()
+    }
+
+    #[test]
+    fn test_interview_configures_wakeup() {
+        let node = MockNode::new(2u8).with_cc(CommandClasses::WakeUp, 2);
+        node.respond_with(capabilities());
+        node.respond_with(
+            WakeUpCCIntervalReport::builder()
+                .wake_up_interval(43200)
+                .controller_node_id(0u8)
+                .build(),
+        );
+
+        block_on(WakeUpCCAPI::new(&node).interview()).unwrap();
+
+        node.assert_sent(&[
+            WakeUpCCIntervalCapabilitiesGet::default().into(),
+            WakeUpCCIntervalGet::default().into(),
+            // The default interval of 1 hour is a multiple of the steps
+            WakeUpCCIntervalSet::builder()
+                .wake_up_interval(3600)
+                .controller_node_id(MOCK_OWN_NODE_ID)
+                .build()
+                .into(),
+        ]);
+        node.assert_script_exhausted();
+        assert_eq!(
+            node.value_cache()
+                .read_u32(&WakeUpCCValues::wake_up_interval().id),
+            Some(3600)
+        );
+    }
+
+    #[test]
+    fn test_interview_clamps_interval() {
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::WakeUp, 2)
+            .with_wake_up_options(WakeUpOptions::builder().interval(100).build());
+        node.respond_with(capabilities());
+
+        block_on(WakeUpCCAPI::new(&node).interview()).unwrap();
+
+        node.assert_sent_matching(&[
+            &|cc| matches!(cc, CC::WakeUpCCIntervalCapabilitiesGet(_)),
+            &|cc| matches!(cc, CC::WakeUpCCIntervalGet(_)),
+            &|cc| {
+                matches!(cc, CC::WakeUpCCIntervalSet(set) if set.wake_up_interval == 600)
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_interview_keeps_device_config() {
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::WakeUp, 1)
+            .with_wake_up_options(WakeUpOptions::builder().keep_device_config(true).build());
+        node.respond_with(
+            WakeUpCCIntervalReport::builder()
+                .wake_up_interval(300)
+                .controller_node_id(5u8)
+                .build(),
+        );
+
+        block_on(WakeUpCCAPI::new(&node).interview()).unwrap();
+
+        // Version 1 does not support querying the capabilities
+        node.assert_sent(&[WakeUpCCIntervalGet::default().into()]);
+        node.assert_script_exhausted();
+    }
+}
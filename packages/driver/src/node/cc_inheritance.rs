@@ -0,0 +1,132 @@
+use super::{Endpoint, EndpointLike, Node};
+use alloc::collections::BTreeMap;
+use zwave_core::prelude::*;
+
+/// The CCs an endpoint supports through the root device without advertising them.
+/// Commands to endpoints are encapsulated by the root device, so its support for
+/// encapsulation and transport CCs applies to all of its endpoints.
+const INHERITED_CCS: &[CommandClasses] = &[
+    CommandClasses::CRC16Encapsulation,
+    CommandClasses::MultiCommand,
+    CommandClasses::Security,
+    CommandClasses::Security2,
+    CommandClasses::Supervision,
+    CommandClasses::TransportService,
+];
+
+/// Which CCs the endpoints of a node inherit from the root device
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EndpointCCInheritance {
+    /// Overrides whether individual CCs are inherited, e.g. to work around devices
+    /// whose endpoints do not behave according to the specification
+    pub overrides: BTreeMap<CommandClasses, bool>,
+}
+
+impl EndpointCCInheritance {
+    /// Whether endpoints support the given CC if the root device supports it
+    pub fn inherits(&self, cc: CommandClasses) -> bool {
+        self.overrides
+            .get(&cc)
+            .copied()
+            .unwrap_or_else(|| INHERITED_CCS.contains(&cc))
+    }
+}
+
+impl Node<'_> {
+    /// Which CCs the endpoints of this node inherit from the root device
+    pub fn endpoint_cc_inheritance(&self) -> EndpointCCInheritance {
+        self.state().endpoint_cc_inheritance().unwrap_or_default()
+    }
+
+    /// Changes whether the endpoints of this node inherit the given CC from the root device.
+    /// `None` restores the default.
+    pub fn set_endpoint_cc_inheritance(&self, cc: CommandClasses, inherit: Option<bool>) {
+        self.state()
+            .update_endpoint_cc_inheritance(|current| match inherit {
+                Some(inherit) => {
+                    current.overrides.insert(cc, inherit);
+                }
+                None => {
+                    current.overrides.remove(&cc);
+                }
+            });
+    }
+}
+
+impl Endpoint<'_> {
+    /// Whether the endpoint supports the given CC only because the root device does
+    fn inherits_cc(&self, cc: CommandClasses) -> bool {
+        self.node.endpoint_cc_inheritance().inherits(cc) && self.node.supports_cc(cc)
+    }
+
+    /// Whether the endpoint supports the given CC, either itself or through the root device
+    pub(super) fn supports_cc_effectively(&self, cc: CommandClasses) -> bool {
+        self.state().supports_command_class(cc) || self.inherits_cc(cc)
+    }
+
+    /// The version of the given CC on this endpoint. Unless it is known for the endpoint,
+    /// the endpoint implements the same version as the root device.
+    pub(super) fn effective_cc_version(&self, cc: CommandClasses) -> Option<u8> {
+        let own = self.state().command_class_version(cc);
+        if own > Some(0) || !self.supports_cc_effectively(cc) {
+            return own;
+        }
+        self.node.get_cc_version(cc).filter(|v| *v > 0).or(own)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use crate::{Controller, NodeStorage};
+
+    #[test]
+    fn test_endpoints_inherit_ccs_from_root() {
+        let controller = MockController::new();
+        run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                let protocol_data = NodeInformationProtocolData {
+                    supports_security: true,
+                    ..mock_protocol_data(true)
+                };
+                nodes.insert(NodeId::new(2u8), NodeStorage::new(protocol_data));
+            });
+            let controller = Controller::mock(&driver);
+            let node = controller.node(NodeId::new(2u8)).unwrap();
+            for cc in [CommandClasses::Supervision, CommandClasses::BinarySwitch] {
+                node.modify_cc_info(
+                    cc,
+                    &PartialCommandClassInfo::default().supported().version(2),
+                );
+            }
+            let endpoint = node.endpoint(1);
+            endpoint.modify_cc_info(
+                CommandClasses::MultilevelSwitch,
+                &PartialCommandClassInfo::default().supported(),
+            );
+
+            // Encapsulation CCs are inherited, application CCs are not
+            assert!(endpoint.supports_cc(CommandClasses::Supervision));
+            assert_eq!(
+                endpoint.get_cc_version(CommandClasses::Supervision),
+                Some(2)
+            );
+            assert!(!endpoint.supports_cc(CommandClasses::BinarySwitch));
+            // Only the advertised CCs are listed
+            assert_eq!(
+                endpoint.supported_command_classes(),
+                vec![CommandClasses::MultilevelSwitch]
+            );
+
+            // Overrides take precedence over the default rules
+            node.set_endpoint_cc_inheritance(CommandClasses::Supervision, Some(false));
+            node.set_endpoint_cc_inheritance(CommandClasses::BinarySwitch, Some(true));
+            assert!(!endpoint.supports_cc(CommandClasses::Supervision));
+            assert!(endpoint.supports_cc(CommandClasses::BinarySwitch));
+
+            node.set_endpoint_cc_inheritance(CommandClasses::Supervision, None);
+            assert!(endpoint.supports_cc(CommandClasses::Supervision));
+        });
+    }
+}
@@ -0,0 +1,60 @@
+use super::Node;
+use alloc::collections::BTreeMap;
+use zwave_core::prelude::*;
+
+/// Whether commands to a node are encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncryptionPolicy {
+    /// Only the CCs the node supports exclusively in a secure way are encrypted
+    #[default]
+    Automatic,
+    /// Commands are encrypted whenever the node supports it and a network key is configured.
+    /// Otherwise they are sent without encryption.
+    Opportunistic,
+    /// Commands must be encrypted. Sending them fails if that is not possible.
+    Required,
+    /// Commands are never encrypted, even if the node only supports them securely
+    Never,
+}
+
+/// Which commands to a node are encrypted, overriding the automatic selection
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NodeEncryptionPolicy {
+    /// The policy for all CCs that have no policy of their own
+    pub default: EncryptionPolicy,
+    /// The policies for individual CCs
+    pub ccs: BTreeMap<CommandClasses, EncryptionPolicy>,
+}
+
+impl NodeEncryptionPolicy {
+    /// The policy for commands of the given CC
+    pub fn for_cc(&self, cc: CommandClasses) -> EncryptionPolicy {
+        self.ccs.get(&cc).copied().unwrap_or(self.default)
+    }
+}
+
+impl Node<'_> {
+    /// Which commands to this node are encrypted
+    pub fn encryption_policy(&self) -> NodeEncryptionPolicy {
+        self.state().encryption_policy().unwrap_or_default()
+    }
+
+    /// Changes whether commands to this node are encrypted, except for CCs with their own policy
+    pub fn set_encryption_policy(&self, policy: EncryptionPolicy) {
+        self.state()
+            .update_encryption_policy(|current| current.default = policy);
+    }
+
+    /// Changes whether commands of the given CC are encrypted. `None` uses the node's policy.
+    pub fn set_cc_encryption_policy(&self, cc: CommandClasses, policy: Option<EncryptionPolicy>) {
+        self.state()
+            .update_encryption_policy(|current| match policy {
+                Some(policy) => {
+                    current.ccs.insert(cc, policy);
+                }
+                None => {
+                    current.ccs.remove(&cc);
+                }
+            });
+    }
+}
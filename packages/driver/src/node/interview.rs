@@ -1,4 +1,8 @@
-use crate::{Endpoint, EndpointLike, Node, error::Result, interview_cc, interview_depends_on};
+use crate::{
+    CCAPIResult, Driver, DriverEvent, Endpoint, EndpointLike, InterviewAction, Node,
+    error::{Error, Result},
+    interview_cc, interview_depends_on,
+};
 use alloc::collections::{BTreeMap, BTreeSet};
 use core::fmt::Write;
 use zwave_core::definitions::*;
@@ -20,9 +24,43 @@ pub enum InterviewStage {
     Done,
 }
 
+/// Marks a node as being interviewed until it is dropped, even if the interview is cancelled
+struct InterviewGuard<'a> {
+    driver: &'a Driver,
+    node_id: NodeId,
+}
+
+impl<'a> InterviewGuard<'a> {
+    /// Returns `None` if the node is already being interviewed
+    fn acquire(driver: &'a Driver, node_id: NodeId) -> Option<Self> {
+        driver
+            .storage
+            .interviews()
+            .update(|interviews| interviews.insert(node_id))
+            .then_some(Self { driver, node_id })
+    }
+}
+
+impl Drop for InterviewGuard<'_> {
+    fn drop(&mut self) {
+        self.driver
+            .storage
+            .interviews()
+            .update(|interviews| interviews.remove(&self.node_id));
+    }
+}
+
 impl<'a> Node<'a> {
+    /// Interviews the node, starting at the current interview stage. If the interview fails or is
+    /// cancelled, calling this again resumes it where it left off.
+    /// Fails if the node is already being interviewed.
     pub async fn interview(&self) -> Result<()> {
         let log = self.logger();
+        // Interviewing a node twice at the same time would mix up its endpoints and stages
+        let Some(_guard) = InterviewGuard::acquire(self.driver(), self.id) else {
+            log.warn(|| "the node is already being interviewed");
+            return Err(Error::InterviewInProgress);
+        };
         log.info(|| {
             format!(
                 "Beginning interview - current stage: {:?}",
@@ -30,15 +68,27 @@ impl<'a> Node<'a> {
             )
         });
 
+        let hooks = self.driver().interview_hooks();
+        if self.interview_stage() != InterviewStage::Done {
+            if let Some(hooks) = &hooks {
+                hooks.before_node_interview(self).await;
+            }
+        }
+
         if self.interview_stage() == InterviewStage::None {
             self.set_interview_stage(InterviewStage::NodeInfo);
         }
 
         if self.interview_stage() == InterviewStage::NodeInfo {
-            // Query the node info and save supported CCs
-            let node_info = self.driver().request_node_info(&self.id, None).await?;
-            for cc in node_info.supported_command_classes {
-                self.modify_cc_info(cc, &PartialCommandClassInfo::default().supported());
+            if !self.skips_interview_stage(InterviewStage::NodeInfo) {
+                // Query the node info and save supported and controlled CCs
+                let node_info = self.driver().query_node_info(&self.id).await?;
+                for cc in node_info.supported_command_classes {
+                    self.modify_cc_info(cc, &PartialCommandClassInfo::default().supported());
+                }
+                for cc in node_info.controlled_command_classes {
+                    self.modify_cc_info(cc, &PartialCommandClassInfo::default().controlled());
+                }
             }
 
             // Done, advance to the next stage
@@ -46,12 +96,35 @@ impl<'a> Node<'a> {
         }
 
         if self.interview_stage() == InterviewStage::CommandClasses {
-            self.interview_ccs().await?;
+            if !self.skips_interview_stage(InterviewStage::CommandClasses) {
+                self.interview_ccs().await?;
+            }
+
+            self.set_interview_stage(InterviewStage::Done);
+            self.driver().start_clock_sync(self.id).await;
+            if let Some(hooks) = &hooks {
+                hooks.after_node_interview(self).await;
+            }
+            log.info(|| "interview completed");
+            self.driver()
+                .emit_event(DriverEvent::NodeReady { node_id: self.id });
         }
 
         Ok(())
     }
 
+    /// Whether the interview hooks skip the given stage of the interview
+    fn skips_interview_stage(&self, stage: InterviewStage) -> bool {
+        let skip = self.driver().interview_hooks().is_some_and(|hooks| {
+            hooks.before_interview_stage(self, stage) == InterviewAction::Skip
+        });
+        if skip {
+            self.logger()
+                .info(|| format!("skipping the interview stage {:?}", stage));
+        }
+        skip
+    }
+
     async fn interview_ccs(&self) -> Result<()> {
         let log = self.logger();
 
@@ -64,7 +137,7 @@ impl<'a> Node<'a> {
         }
 
         if self.supports_cc(CommandClasses::ManufacturerSpecific) {
-            interview_cc(self, CommandClasses::ManufacturerSpecific)
+            interview_cc_with_hooks(self.driver(), self, CommandClasses::ManufacturerSpecific)
                 .await
                 // FIXME: Handle errors
                 .unwrap();
@@ -75,17 +148,32 @@ impl<'a> Node<'a> {
         // or list of supported CCs, we need to add it here manually, so its version can get queried.
 
         if self.supports_cc(CommandClasses::Version) {
-            interview_cc(self, CommandClasses::Version).await.unwrap();
+            interview_cc_with_hooks(self.driver(), self, CommandClasses::Version)
+                .await
+                .unwrap();
             // FIXME: Load device config file, apply CC related compat flags
         }
 
         if self.supports_cc(CommandClasses::WakeUp) {
-            // TODO
+            // Without a configured wakeup interval, the node keeps its defaults, but is still usable
+            if let Err(e) =
+                interview_cc_with_hooks(self.driver(), self, CommandClasses::WakeUp).await
+            {
+                log.warn(|| format!("failed to interview the Wake Up CC: {}", e));
+            }
         }
 
-        // FIXME:
-        // Don't offer or interview the Basic CC if any actuator CC is supported - except if the config files forbid us
-        // to map the Basic CC to other CCs or expose Basic Set as an event
+        // Don't offer or interview the Basic CC if any actuator CC is supported or the device class
+        // does not allow it. Reports of the Basic CC are mapped to the CC the device class defines.
+        // FIXME: Keep it if the config files forbid us to map the Basic CC to other CCs
+        // or expose Basic Set as an event
+        if self.supports_cc(CommandClasses::Basic) && !should_use_basic_cc(self) {
+            log.info(|| "hiding the Basic CC in favor of other CCs");
+            self.modify_cc_info(
+                CommandClasses::Basic,
+                &PartialCommandClassInfo::default().not_supported(),
+            );
+        }
 
         // Figure out when to interview which CC.
         //
@@ -147,7 +235,9 @@ impl<'a> Node<'a> {
 
         // Interview CCs that should be interviewed before endpoints
         for cc in root_interviews_before_endpoints {
-            interview_cc(self, cc).await.unwrap();
+            interview_cc_with_hooks(self.driver(), self, cc)
+                .await
+                .unwrap();
         }
 
         // Interview all endpoints
@@ -159,9 +249,13 @@ impl<'a> Node<'a> {
 
         // Interview CCs that should be interviewed after endpoints
         for cc in root_interviews_after_endpoints {
-            interview_cc(self, cc).await.unwrap();
+            interview_cc_with_hooks(self.driver(), self, cc)
+                .await
+                .unwrap();
         }
 
+        create_cc_values(self.driver(), self);
+
         Ok(())
     }
 }
@@ -179,7 +273,9 @@ impl<'a> Endpoint<'a> {
         }
 
         if self.supports_cc(CommandClasses::Version) {
-            interview_cc(self, CommandClasses::Version).await.unwrap();
+            interview_cc_with_hooks(self.node.driver(), self, CommandClasses::Version)
+                .await
+                .unwrap();
         }
 
         // FIXME: Modify supported CCs before further interview - see Z-Wave JS
@@ -204,13 +300,58 @@ impl<'a> Endpoint<'a> {
         });
 
         for cc in interview_order {
-            interview_cc(self, cc).await.unwrap();
+            interview_cc_with_hooks(self.node.driver(), self, cc)
+                .await
+                .unwrap();
         }
 
+        create_cc_values(self.node.driver(), self);
+
         Ok(())
     }
 }
 
+/// Interviews a CC of the given endpoint, unless the interview hooks skip it
+async fn interview_cc_with_hooks<'a>(
+    driver: &Driver,
+    endpoint: &'a dyn EndpointLike<'a>,
+    cc: CommandClasses,
+) -> CCAPIResult<()> {
+    let hooks = driver.interview_hooks();
+    if let Some(hooks) = &hooks {
+        if hooks.before_cc_interview(endpoint, cc).await == InterviewAction::Skip {
+            endpoint
+                .logger()
+                .info(|| format!("skipping the interview of the {} CC", cc));
+            return Ok(());
+        }
+    }
+
+    interview_cc(endpoint, cc).await?;
+
+    if let Some(hooks) = &hooks {
+        hooks.after_cc_interview(endpoint, cc).await;
+    }
+    Ok(())
+}
+
+/// Creates the values of the supported CCs, so they are known before the node reports them
+fn create_cc_values<'a>(driver: &Driver, endpoint: &'a dyn EndpointLike<'a>) {
+    for cc in endpoint.supported_command_classes() {
+        // Without the Version CC, we can only assume version 1
+        let version = endpoint.get_cc_version(cc).unwrap_or(1);
+        driver.create_cc_values(endpoint.node_id(), endpoint.index(), cc, version);
+    }
+}
+
+/// The Basic CC must only be used if the device class allows it and no actuator CC is supported
+fn should_use_basic_cc(node: &Node) -> bool {
+    node.device_class().generic.may_support_basic_cc()
+        && !CommandClasses::actuator_ccs()
+            .iter()
+            .any(|cc| node.supports_cc(*cc))
+}
+
 fn determine_interview_order<'a>(
     endpoint: &'a dyn EndpointLike<'a>,
     except: &[CommandClasses],
@@ -271,3 +412,39 @@ fn determine_interview_order<'a>(
 
     sorted.into_iter()
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use crate::{Controller, NodeStorage};
+
+    #[test]
+    fn test_nodes_are_not_interviewed_twice_at_once() {
+        let controller = MockController::new();
+        run_with_mock_controller(&controller, |driver| async move {
+            let node_id = NodeId::new(2u8);
+            // The node supports no CCs, so its interview needs no communication
+            let mut node = NodeStorage::new(mock_protocol_data(true));
+            node.interview_stage = InterviewStage::CommandClasses;
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(node_id, node);
+            });
+            let controller = Controller::mock(&driver);
+            let node = controller.node(node_id).unwrap();
+
+            let guard = InterviewGuard::acquire(&driver, node_id).unwrap();
+            assert!(matches!(
+                node.interview().await,
+                Err(Error::InterviewInProgress)
+            ));
+            assert_eq!(node.interview_stage(), InterviewStage::CommandClasses);
+
+            drop(guard);
+            node.interview().await.unwrap();
+            assert_eq!(node.interview_stage(), InterviewStage::Done);
+            // The guard is released after the interview
+            assert!(InterviewGuard::acquire(&driver, node_id).is_some());
+        });
+    }
+}
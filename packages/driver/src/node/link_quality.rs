@@ -0,0 +1,197 @@
+use super::Node;
+use crate::{CCAPIError, CCAPIResult, DriverEvent, EndpointLike};
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::powerlevel::{NodePowerlevel, PowerlevelTestStatus};
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::{Instant, Timer};
+
+/// How the link quality of a node is checked
+#[derive(Debug, Clone, Copy, PartialEq, TypedBuilder)]
+pub struct LinkQualityOptions {
+    /// How many pings are sent to the node. Default: 5
+    #[builder(default = 5)]
+    pub pings: u8,
+    /// How many Powerlevel test frames the node sends to the controller, if it supports
+    /// Powerlevel CC. Default: 10
+    #[builder(default = 10)]
+    pub test_frames: u16,
+    /// How long to wait for the node to finish sending the test frames. Default: 10 s
+    #[builder(default = Duration::from_secs(10))]
+    pub test_timeout: Duration,
+    /// Below which score the link quality is considered marginal. Default: 80
+    #[builder(default = 80)]
+    pub marginal_score: u8,
+}
+
+impl Default for LinkQualityOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// How reliably a node could be communicated with during a link quality check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkQuality {
+    /// The percentage of frames that reached their destination in the worse direction,
+    /// from 0 (unreachable) to 100 (no frame was lost)
+    pub score: u8,
+    /// How many pings were sent to the node
+    pub pings: u8,
+    /// How many of the pings the node acknowledged
+    pub acknowledged_pings: u8,
+    /// How many test frames the node sent to the controller, or `None`
+    /// if the node does not support Powerlevel CC
+    pub test_frames: Option<u16>,
+    /// How many of the test frames the controller acknowledged
+    pub acknowledged_test_frames: u16,
+}
+
+/// How often the result of the powerlevel test is queried while it is in progress
+const TEST_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn percentage(acknowledged: u16, total: u16) -> u8 {
+    if total == 0 {
+        return 100;
+    }
+    (acknowledged.min(total) as u32 * 100 / total as u32) as u8
+}
+
+impl Node<'_> {
+    /// The result of the last link quality check
+    pub fn link_quality(&self) -> Option<LinkQuality> {
+        self.state().link_quality()
+    }
+
+    /// Checks how reliably the node can be reached by pinging it and, if it supports
+    /// Powerlevel CC, letting it send test frames to the controller.
+    ///
+    /// The result is remembered. If the link quality is marginal,
+    /// a [`DriverEvent::MarginalLinkQuality`] event is emitted.
+    pub async fn check_link_quality(
+        &self,
+        options: &LinkQualityOptions,
+    ) -> CCAPIResult<LinkQuality> {
+        let log = self.logger();
+        log.info(|| "checking the link quality...");
+
+        let mut acknowledged_pings = 0;
+        for _ in 0..options.pings {
+            let result = self.ping().await.map_err(CCAPIError::Controller)?;
+            if result.reachable {
+                acknowledged_pings += 1;
+            }
+        }
+        let mut score = percentage(acknowledged_pings as u16, options.pings as u16);
+
+        let (test_frames, acknowledged_test_frames) =
+            if self.supports_cc(CommandClasses::Powerlevel) && acknowledged_pings > 0 {
+                let acknowledged = self.test_frames_to_controller(options).await?;
+                score = score.min(percentage(acknowledged, options.test_frames));
+                (Some(options.test_frames), acknowledged)
+            } else {
+                (None, 0)
+            };
+
+        let link_quality = LinkQuality {
+            score,
+            pings: options.pings,
+            acknowledged_pings,
+            test_frames,
+            acknowledged_test_frames,
+        };
+        self.state().set_link_quality(link_quality);
+
+        if score < options.marginal_score {
+            log.warn(|| format!("the link quality is marginal (score {})", score));
+            self.driver().emit_event(DriverEvent::MarginalLinkQuality {
+                node_id: self.id(),
+                link_quality,
+            });
+        } else {
+            log.info(|| format!("the link quality is good (score {})", score));
+        }
+
+        Ok(link_quality)
+    }
+
+    /// Lets the node send test frames to the controller and returns how many were acknowledged
+    async fn test_frames_to_controller(&self, options: &LinkQualityOptions) -> CCAPIResult<u16> {
+        let api = self.cc_api().powerlevel();
+        api.test_node(
+            self.own_node_id(),
+            NodePowerlevel::NormalPower,
+            options.test_frames,
+        )
+        .await?;
+
+        let deadline = Instant::now().checked_add(options.test_timeout);
+        loop {
+            let Some(report) = api.get_node_test_result().await? else {
+                return Ok(0);
+            };
+            if report.status != PowerlevelTestStatus::InProgress
+                || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return Ok(report.acknowledged_frames);
+            }
+            Timer::after(TEST_POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+    use crate::{Controller, NodeStorage};
+
+    #[test]
+    fn test_marginal_link_quality() {
+        // Node 5 acknowledges every ping, but only 7 of its 10 test frames reach the controller
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            let mut ret = MockController::send_data_ok(request);
+            if request.payload[2..4] == [0x73, 0x05] {
+                ret.push(MockController::application_command(
+                    5,
+                    &[0x73, 0x06, 0x01, 0x01, 0x00, 0x07],
+                ));
+            }
+            ret
+        });
+        let link_quality = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(NodeId::new(5u8), NodeStorage::new(mock_protocol_data(true)));
+            });
+            let controller = Controller::mock(&driver);
+            let node = controller.node(NodeId::new(5u8)).unwrap();
+            node.modify_cc_info(
+                CommandClasses::Powerlevel,
+                &PartialCommandClassInfo::default().supported(),
+            );
+
+            let link_quality = node
+                .check_link_quality(&LinkQualityOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(node.link_quality(), Some(link_quality));
+            link_quality
+        });
+
+        assert_eq!(
+            link_quality,
+            LinkQuality {
+                score: 70,
+                pings: 5,
+                acknowledged_pings: 5,
+                test_frames: Some(10),
+                acknowledged_test_frames: 7,
+            }
+        );
+        assert!(controller.take_events().iter().any(|event| matches!(
+            event,
+            DriverEvent::MarginalLinkQuality { node_id, .. } if *node_id == NodeId::new(5u8)
+        )));
+    }
+}
@@ -0,0 +1,426 @@
+//! A mock node for testing the CC APIs without a driver or serial port.
+//!
+//! The responses of the node are scripted in advance. Every CC that is sent to the node is
+//! recorded, so tests can assert which commands were sent in which order.
+
+use super::{
+    EndpointLike, ExecNodeCommandFuture, cache::EndpointValueCache, storage::EndpointStorage,
+};
+use crate::{
+    ExecNodeCommandError, ExecNodeCommandOptions, ExecNodeCommandResult, VersionQueryOptions,
+    WakeUpOptions, cache::ValueCache, storage::DriverStorage,
+};
+use alloc::collections::{BTreeMap, VecDeque};
+use zwave_cc::commandclass::{CC, CCBase, CCId, WithAddress};
+use zwave_core::{log::Loglevel, prelude::*};
+use zwave_logging::{LocalImmutableLogger, LogInfo, loggers::node::NodeLogger};
+use zwave_pal::{prelude::*, sync::Locked};
+
+/// The node ID of the controller the mock node belongs to
+pub(crate) const MOCK_OWN_NODE_ID: u8 = 1;
+
+/// How the mock node reacts to a CC that was sent to it
+pub(crate) enum MockResponse {
+    /// The node acknowledges the command and responds with the given CC
+    Respond(CC),
+    /// The node acknowledges the command, but does not respond
+    Timeout,
+    /// The node does not acknowledge the command
+    NoAck,
+}
+
+struct ScriptedResponse {
+    predicate: Box<dyn Fn(&CC) -> bool + Send + Sync>,
+    response: MockResponse,
+}
+
+pub(crate) struct MockNode {
+    id: NodeId,
+    endpoints: Locked<BTreeMap<EndpointIndex, EndpointStorage>>,
+    storage: Arc<DriverStorage>,
+    script: Locked<VecDeque<ScriptedResponse>>,
+    sent: Locked<Vec<WithAddress<CC>>>,
+}
+
+impl MockNode {
+    pub fn new(id: impl Into<NodeId>) -> Self {
+        let mut endpoints = BTreeMap::new();
+        endpoints.insert(EndpointIndex::Root, EndpointStorage::new());
+
+        Self {
+            id: id.into(),
+            endpoints: Locked::new(endpoints),
+            storage: Arc::new(DriverStorage::new()),
+            script: Locked::new(VecDeque::new()),
+            sent: Locked::new(Vec::new()),
+        }
+    }
+
+    pub fn endpoint(&self, index: u8) -> MockEndpoint<'_> {
+        MockEndpoint { node: self, index }
+    }
+
+    /// Changes how the wakeup of the node is configured during the interview
+    pub fn with_wake_up_options(self, options: WakeUpOptions) -> Self {
+        self.storage.wake_up_options().set(options);
+        self
+    }
+
+    /// Changes how the CC versions of the node are queried during the interview
+    pub fn with_version_query_options(self, options: VersionQueryOptions) -> Self {
+        self.storage.version_query_options().set(options);
+        self
+    }
+
+    /// Marks the given CC as supported by the root endpoint in the given version
+    pub fn with_cc(self, cc: CommandClasses, version: u8) -> Self {
+        self.modify_cc_info(
+            cc,
+            &PartialCommandClassInfo::default()
+                .supported()
+                .version(version),
+        );
+        self
+    }
+
+    /// Marks the given CC as supported by an endpoint in the given version
+    pub fn with_endpoint_cc(self, endpoint: u8, cc: CommandClasses, version: u8) -> Self {
+        self.endpoint(endpoint).modify_cc_info(
+            cc,
+            &PartialCommandClassInfo::default()
+                .supported()
+                .version(version),
+        );
+        self
+    }
+
+    /// Answers the next CC the given response is valid for
+    pub fn respond_with(&self, response: impl Into<CC>) {
+        let response: CC = response.into();
+        let expected = response.clone();
+        self.on(
+            move |cc| {
+                cc.expects_response()
+                    && cc.cc_id() == expected.cc_id()
+                    && cc.test_response(&expected)
+            },
+            MockResponse::Respond(response),
+        );
+    }
+
+    /// Defines how the node reacts to the next CC that matches the predicate.
+    /// Scripted responses are consumed in order. CCs that match none of them are acknowledged,
+    /// but not answered.
+    pub fn on(
+        &self,
+        predicate: impl Fn(&CC) -> bool + Send + Sync + 'static,
+        response: MockResponse,
+    ) {
+        self.script.update(|script| {
+            script.push_back(ScriptedResponse {
+                predicate: Box::new(predicate),
+                response,
+            })
+        });
+    }
+
+    /// Returns all CCs that were sent to the node so far
+    pub fn sent(&self) -> Vec<CC> {
+        self.sent
+            .inspect(|sent| sent.iter().map(|cc| (**cc).clone()).collect())
+    }
+
+    /// Asserts that exactly the given CCs were sent to the node, in this order
+    pub fn assert_sent(&self, expected: &[CC]) {
+        assert_eq!(
+            self.sent(),
+            expected,
+            "unexpected CCs were sent to the node"
+        );
+    }
+
+    /// Asserts that the CCs that were sent to the node match the given predicates, in this order
+    pub fn assert_sent_matching(&self, expected: &[&dyn Fn(&CC) -> bool]) {
+        let sent = self.sent();
+        assert_eq!(
+            sent.len(),
+            expected.len(),
+            "expected {} CCs to be sent, got {:#?}",
+            expected.len(),
+            sent
+        );
+        for (index, (cc, predicate)) in sent.iter().zip(expected).enumerate() {
+            assert!(predicate(cc), "unexpected CC #{}: {:#?}", index, cc);
+        }
+    }
+
+    /// Asserts that all scripted responses were used
+    pub fn assert_script_exhausted(&self) {
+        let remaining = self.script.inspect(|script| script.len());
+        assert_eq!(
+            remaining, 0,
+            "{} scripted responses were not used",
+            remaining
+        );
+    }
+
+    /// Returns what is known about the given CC of the root endpoint
+    pub fn cc_info(&self, cc: CommandClasses) -> Option<CommandClassInfo> {
+        self.endpoint_cc_info(EndpointIndex::Root, |cc_info| cc_info.get(&cc).cloned())
+            .flatten()
+    }
+
+    fn endpoint_cc_info<R>(
+        &self,
+        index: EndpointIndex,
+        inspect: impl FnOnce(&BTreeMap<CommandClasses, CommandClassInfo>) -> R,
+    ) -> Option<R> {
+        self.endpoints.inspect(|endpoints| {
+            endpoints
+                .get(&index)
+                .map(|endpoint| inspect(&endpoint.cc_info))
+        })
+    }
+
+    fn merge_cc_info(
+        &self,
+        index: EndpointIndex,
+        cc: CommandClasses,
+        info: &PartialCommandClassInfo,
+    ) {
+        self.endpoints.update(|endpoints| {
+            endpoints
+                .entry(index)
+                .or_insert_with(EndpointStorage::new)
+                .cc_info
+                .entry(cc)
+                .and_modify(|cc_info| cc_info.merge(info))
+                .or_insert_with(|| info.into());
+        });
+    }
+
+    fn remove_cc_info(&self, index: EndpointIndex, cc: CommandClasses) {
+        self.endpoints.update(|endpoints| {
+            if let Some(endpoint) = endpoints.get_mut(&index) {
+                endpoint.cc_info.remove(&cc);
+            }
+        });
+    }
+
+    fn exec(&self, cc: &WithAddress<CC>) -> ExecNodeCommandResult<Option<CC>> {
+        self.sent.update(|sent| sent.push(cc.clone()));
+
+        let response = self.script.update(|script| {
+            let index = script.iter().position(|r| (r.predicate)(cc))?;
+            script.remove(index).map(|r| r.response)
+        });
+
+        match response {
+            Some(MockResponse::NoAck) => Err(ExecNodeCommandError::NodeNoAck),
+            // Like the driver, only return responses that match the request
+            Some(MockResponse::Respond(response))
+                if cc.expects_response() && cc.test_response(&response) =>
+            {
+                Ok(Some(response))
+            }
+            _ if cc.expects_response() => Err(ExecNodeCommandError::NodeTimeout),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<'a> EndpointLike<'a> for MockNode {
+    fn node_id(&self) -> NodeId {
+        self.id
+    }
+
+    fn root_endpoint(&'a self) -> &'a dyn EndpointLike<'a> {
+        self
+    }
+
+    fn index(&self) -> EndpointIndex {
+        EndpointIndex::Root
+    }
+
+    fn value_cache(&'a self) -> EndpointValueCache<'a> {
+        EndpointValueCache::new(self, ValueCache::new(&self.storage))
+    }
+
+    fn exec_node_command<'b>(
+        &'b self,
+        cc: &'b WithAddress<CC>,
+        _options: Option<&'b ExecNodeCommandOptions>,
+    ) -> ExecNodeCommandFuture<'b> {
+        Box::pin(async move { self.exec(cc) })
+    }
+
+    fn modify_cc_info(&self, cc: CommandClasses, info: &PartialCommandClassInfo) {
+        self.merge_cc_info(EndpointIndex::Root, cc, info);
+    }
+
+    fn remove_cc(&self, cc: CommandClasses) {
+        self.remove_cc_info(EndpointIndex::Root, cc);
+    }
+
+    fn supported_command_classes(&self) -> Vec<CommandClasses> {
+        supported_command_classes(self, EndpointIndex::Root)
+    }
+
+    fn controlled_command_classes(&self) -> Vec<CommandClasses> {
+        controlled_command_classes(self, EndpointIndex::Root)
+    }
+
+    fn supports_cc(&self, cc: CommandClasses) -> bool {
+        supports_cc(self, EndpointIndex::Root, cc)
+    }
+
+    fn controls_cc(&self, cc: CommandClasses) -> bool {
+        controls_cc(self, EndpointIndex::Root, cc)
+    }
+
+    fn get_cc_version(&self, cc: CommandClasses) -> Option<u8> {
+        get_cc_version(self, EndpointIndex::Root, cc)
+    }
+
+    fn logger(&self) -> NodeLogger<'_> {
+        NodeLogger::new(self, self.id, EndpointIndex::Root)
+    }
+
+    fn own_node_id(&self) -> NodeId {
+        MOCK_OWN_NODE_ID.into()
+    }
+
+    fn wake_up_options(&self) -> WakeUpOptions {
+        self.storage.wake_up_options().get()
+    }
+
+    fn version_query_options(&self) -> VersionQueryOptions {
+        self.storage.version_query_options().get()
+    }
+}
+
+impl LocalImmutableLogger for MockNode {
+    fn log(&self, _log: LogInfo, _level: Loglevel) {
+        // Tests do not inspect the logs of mock nodes, so they are discarded
+    }
+
+    fn log_level(&self) -> Loglevel {
+        Loglevel::Silly
+    }
+
+    fn set_log_level(&self, _level: Loglevel) {}
+}
+
+pub(crate) struct MockEndpoint<'a> {
+    node: &'a MockNode,
+    index: u8,
+}
+
+impl<'a> EndpointLike<'a> for MockEndpoint<'a> {
+    fn node_id(&self) -> NodeId {
+        self.node.id
+    }
+
+    fn root_endpoint(&'a self) -> &'a dyn EndpointLike<'a> {
+        self.node
+    }
+
+    fn index(&self) -> EndpointIndex {
+        EndpointIndex::Endpoint(self.index)
+    }
+
+    fn value_cache(&'a self) -> EndpointValueCache<'a> {
+        EndpointValueCache::new(self, ValueCache::new(&self.node.storage))
+    }
+
+    fn exec_node_command<'b>(
+        &'b self,
+        cc: &'b WithAddress<CC>,
+        _options: Option<&'b ExecNodeCommandOptions>,
+    ) -> ExecNodeCommandFuture<'b> {
+        Box::pin(async move { self.node.exec(cc) })
+    }
+
+    fn modify_cc_info(&self, cc: CommandClasses, info: &PartialCommandClassInfo) {
+        self.node.merge_cc_info(self.index(), cc, info);
+    }
+
+    fn remove_cc(&self, cc: CommandClasses) {
+        self.node.remove_cc_info(self.index(), cc);
+    }
+
+    fn supported_command_classes(&self) -> Vec<CommandClasses> {
+        supported_command_classes(self.node, self.index())
+    }
+
+    fn controlled_command_classes(&self) -> Vec<CommandClasses> {
+        controlled_command_classes(self.node, self.index())
+    }
+
+    fn supports_cc(&self, cc: CommandClasses) -> bool {
+        supports_cc(self.node, self.index(), cc)
+    }
+
+    fn controls_cc(&self, cc: CommandClasses) -> bool {
+        controls_cc(self.node, self.index(), cc)
+    }
+
+    fn get_cc_version(&self, cc: CommandClasses) -> Option<u8> {
+        get_cc_version(self.node, self.index(), cc)
+    }
+
+    fn logger(&self) -> NodeLogger<'_> {
+        NodeLogger::new(self.node, self.node.id, self.index())
+    }
+
+    fn own_node_id(&self) -> NodeId {
+        self.node.own_node_id()
+    }
+
+    fn wake_up_options(&self) -> WakeUpOptions {
+        self.node.wake_up_options()
+    }
+
+    fn version_query_options(&self) -> VersionQueryOptions {
+        self.node.version_query_options()
+    }
+}
+
+fn supported_command_classes(node: &MockNode, index: EndpointIndex) -> Vec<CommandClasses> {
+    node.endpoint_cc_info(index, |cc_info| {
+        cc_info
+            .iter()
+            .filter_map(|(cc, info)| if info.supported { Some(*cc) } else { None })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn controlled_command_classes(node: &MockNode, index: EndpointIndex) -> Vec<CommandClasses> {
+    node.endpoint_cc_info(index, |cc_info| {
+        cc_info
+            .iter()
+            .filter_map(|(cc, info)| if info.controlled { Some(*cc) } else { None })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+fn supports_cc(node: &MockNode, index: EndpointIndex, cc: CommandClasses) -> bool {
+    node.endpoint_cc_info(index, |cc_info| {
+        cc_info.get(&cc).is_some_and(|info| info.supported)
+    })
+    .unwrap_or(false)
+}
+
+fn controls_cc(node: &MockNode, index: EndpointIndex, cc: CommandClasses) -> bool {
+    node.endpoint_cc_info(index, |cc_info| {
+        cc_info.get(&cc).is_some_and(|info| info.controlled)
+    })
+    .unwrap_or(false)
+}
+
+fn get_cc_version(node: &MockNode, index: EndpointIndex, cc: CommandClasses) -> Option<u8> {
+    node.endpoint_cc_info(index, |cc_info| cc_info.get(&cc).map(|info| info.version))
+        .flatten()
+}
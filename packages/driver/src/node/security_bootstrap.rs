@@ -0,0 +1,139 @@
+use crate::{CCAPIError, CCAPIResult, CCAPIs, EndpointLike, Node};
+use core::future::Future;
+use core::time::Duration;
+use thiserror::Error;
+use zwave_core::definitions::*;
+use zwave_core::security::NetworkKey;
+use zwave_pal::time::Timer;
+
+/// Each step of the S0 bootstrapping must be completed within this time,
+/// otherwise the node aborts the inclusion
+const S0_INCLUSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The network key is sent to the node encrypted with a temporary key of all zeros
+const S0_TEMP_KEY: [u8; 16] = [0; 16];
+
+#[derive(Error, Debug)]
+/// Defines the possible errors during the security bootstrapping
+pub enum SecurityBootstrapError {
+    #[error("The node did not respond to the {0} command")]
+    NoResponse(&'static str),
+    #[error("The inclusion timer elapsed during the {0} command")]
+    Timeout(&'static str),
+    #[error(transparent)]
+    CCAPI(#[from] CCAPIError),
+}
+
+impl Node<'_> {
+    /// Exchanges the network keys with a newly included node, so its secure command classes
+    /// can be interviewed. Does nothing if the node does not support security.
+    pub(crate) async fn bootstrap_security(&self) -> Result<(), SecurityBootstrapError> {
+        if !self.supports_cc(CommandClasses::Security) {
+            return Ok(());
+        }
+
+        let log = self.logger();
+        let Some(sec_man) = self.driver().storage.security_manager() else {
+            log.warn(|| {
+                "the node supports S0, but no S0 network key is configured - it will be included insecurely"
+            });
+            return Ok(());
+        };
+
+        log.info(|| "bootstrapping S0 security...");
+        let api = self.cc_api().security();
+
+        with_s0_timer("SchemeGet", api.get_scheme())
+            .await?
+            .ok_or(SecurityBootstrapError::NoResponse("SchemeGet"))?;
+
+        // Until the node knows the network key, it can only decrypt commands
+        // that were encrypted with the temporary key
+        sec_man.set_temp_key(self.id, &NetworkKey::from(S0_TEMP_KEY));
+        let verified =
+            with_s0_timer("NetworkKeySet", api.set_network_key(&sec_man.network_key())).await;
+        sec_man.delete_temp_key(self.id);
+        if !verified? {
+            return Err(SecurityBootstrapError::NoResponse("NetworkKeySet"));
+        }
+
+        let responded = with_s0_timer("CommandsSupportedGet", query_secure_ccs(self)).await?;
+        if !responded {
+            return Err(SecurityBootstrapError::NoResponse("CommandsSupportedGet"));
+        }
+
+        log.info(|| "S0 security bootstrapping completed");
+        Ok(())
+    }
+
+    /// Queries again which CCs the node only supports securely and updates the cached CC
+    /// information, e.g. after the S0 network key was added to an existing network.
+    /// Returns whether the node communicates securely.
+    pub async fn reassess_security(&self) -> CCAPIResult<bool> {
+        let has_key = self.driver().storage.security_manager().is_some();
+        if !has_key || !self.supports_cc(CommandClasses::Security) {
+            return Ok(false);
+        }
+        query_secure_ccs(self).await
+    }
+}
+
+/// Queries which CCs the node only supports or controls securely and marks them as secure.
+/// Returns `false` if the node did not respond, e.g. because it does not know the network key.
+async fn query_secure_ccs<'a>(endpoint: &'a dyn EndpointLike<'a>) -> CCAPIResult<bool> {
+    let Some(report) = CCAPIs::new(endpoint).security().get_supported_commands().await? else {
+        return Ok(false);
+    };
+    for cc in report.supported_command_classes {
+        endpoint.modify_cc_info(cc, &PartialCommandClassInfo::default().supported().secure());
+    }
+    for cc in report.controlled_command_classes {
+        endpoint.modify_cc_info(
+            cc,
+            &PartialCommandClassInfo::default().controlled().secure(),
+        );
+    }
+    Ok(true)
+}
+
+/// Runs a step of the S0 bootstrapping, which fails if it is not completed
+/// within the S0 inclusion timer
+async fn with_s0_timer<T>(
+    command: &'static str,
+    step: impl Future<Output = CCAPIResult<T>>,
+) -> Result<T, SecurityBootstrapError> {
+    zwave_pal::select_biased! {
+        result = step => Ok(result?),
+        _ = Timer::after(S0_INCLUSION_TIMEOUT) => Err(SecurityBootstrapError::Timeout(command)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::mock::MockNode;
+    use futures::executor::block_on;
+    use zwave_cc::commandclass::{
+        SecurityCCCommandEncapsulation, SecurityCCCommandsSupportedReport,
+    };
+
+    #[test]
+    fn test_query_secure_ccs() {
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::Security, 1)
+            .with_cc(CommandClasses::DoorLock, 2);
+        let report = SecurityCCCommandsSupportedReport::builder()
+            .supported_command_classes(vec![CommandClasses::DoorLock])
+            .build();
+        node.respond_with(SecurityCCCommandEncapsulation::new(report.into()));
+
+        assert!(block_on(query_secure_ccs(&node)).unwrap());
+        let door_lock = node.cc_info(CommandClasses::DoorLock).unwrap();
+        assert!(door_lock.secure);
+        // The rest of the information is kept
+        assert_eq!(door_lock.version, 2);
+
+        // A node that does not know the network key does not respond
+        assert!(!block_on(query_secure_ccs(&node)).unwrap());
+    }
+}
@@ -1,6 +1,11 @@
-use crate::InterviewStage;
+use crate::{
+    EndpointCCInheritance, InterviewStage, LinkQuality, NodeEncryptionPolicy, OptimisticUpdates,
+    WakeUpRefreshPolicy,
+};
 use alloc::collections::BTreeMap;
+use core::time::Duration;
 use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
 
 #[derive(Debug)]
 /// Internal storage for a node instance. Since this is meant be used from both library and external
@@ -10,6 +15,23 @@ pub(crate) struct NodeStorage {
     pub(crate) interview_stage: InterviewStage,
     pub(crate) protocol_data: NodeInformationProtocolData,
     pub(crate) endpoints: BTreeMap<EndpointIndex, EndpointStorage>,
+    pub(crate) user_metadata: NodeUserMetadata,
+    pub(crate) status: NodeStatus,
+    /// How the last command to this node was transmitted
+    pub(crate) last_transmit_report: Option<TransmitReport>,
+    /// The nodes in direct range of this node, as last reported by the controller
+    pub(crate) neighbors: Option<Vec<NodeId>>,
+    pub(crate) statistics: NodeStatistics,
+    /// Overrides the driver's setting for when values are updated after Set commands
+    pub(crate) optimistic_updates: Option<OptimisticUpdates>,
+    /// The result of the last link quality check
+    pub(crate) link_quality: Option<LinkQuality>,
+    /// Which values are refreshed when the node wakes up
+    pub(crate) wake_up_refresh_policy: Option<WakeUpRefreshPolicy>,
+    /// Which commands to the node are encrypted
+    pub(crate) encryption_policy: NodeEncryptionPolicy,
+    /// Which CCs the endpoints inherit from the root device
+    pub(crate) endpoint_cc_inheritance: EndpointCCInheritance,
 }
 
 impl NodeStorage {
@@ -22,10 +44,114 @@ impl NodeStorage {
             interview_stage: InterviewStage::None,
             protocol_data,
             endpoints,
+            user_metadata: NodeUserMetadata::default(),
+            status: NodeStatus::Unknown,
+            last_transmit_report: None,
+            neighbors: None,
+            statistics: NodeStatistics::default(),
+            optimistic_updates: None,
+            link_quality: None,
+            wake_up_refresh_policy: None,
+            encryption_policy: NodeEncryptionPolicy::default(),
+            endpoint_cc_inheritance: EndpointCCInheritance::default(),
         }
     }
 }
 
+/// Whether a node is reachable, as far as the driver knows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeStatus {
+    /// The node was not communicated with yet
+    #[default]
+    Unknown,
+    /// The node can sleep and did not acknowledge the last command
+    Asleep,
+    /// The node can sleep, but acknowledged the last command
+    Awake,
+    /// The node should be listening, but did not acknowledge the last command
+    Dead,
+    /// The node should be listening and acknowledged the last command
+    Alive,
+}
+
+/// Statistics about the communication with a node
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NodeStatistics {
+    /// How many commands were transmitted to the node
+    pub commands_sent: u64,
+    /// How many commands the node did not acknowledge
+    pub commands_failed: u64,
+    /// How many commands from this node were discarded, because their CRC-16 checksum did not match
+    pub crc16_errors: u64,
+    /// How many pings the node acknowledged
+    pub pings_acknowledged: u64,
+    /// How many pings the node did not acknowledge
+    pub pings_failed: u64,
+    /// How often a command took a different route than the command before it
+    pub route_changes: u64,
+    /// The round-trip time of the last acknowledged ping
+    pub last_round_trip_time: Option<Duration>,
+    /// The route the last command to the node took
+    pub last_route: Option<RouteStatistics>,
+}
+
+/// A repeater on the route to a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteHop {
+    pub node_id: NodeId,
+    /// The RSSI of the acknowledgement this repeater received
+    pub ack_rssi: Option<RSSI>,
+}
+
+/// How a command was routed to a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteStatistics {
+    /// The repeaters between the controller and the node. Empty for direct communication.
+    pub repeaters: Vec<RouteHop>,
+    pub speed: ProtocolDataRate,
+    /// The RSSI of the acknowledgement the controller received
+    pub ack_rssi: Option<RSSI>,
+    /// How long the transmission took, including retransmissions
+    pub transmission_time: Duration,
+}
+
+impl From<&TransmitReport> for RouteStatistics {
+    fn from(report: &TransmitReport) -> Self {
+        Self {
+            repeaters: report
+                .repeaters
+                .iter()
+                .map(|repeater| RouteHop {
+                    node_id: NodeId::new(repeater.node_id),
+                    ack_rssi: repeater.ack_rssi,
+                })
+                .collect(),
+            speed: report.route_speed,
+            ack_rssi: report.ack_rssi,
+            transmission_time: Duration::from_millis(report.tx_ticks as u64 * 10),
+        }
+    }
+}
+
+/// Tests whether two commands were transmitted over different repeaters
+pub(crate) fn route_changed(previous: &TransmitReport, current: &TransmitReport) -> bool {
+    previous
+        .repeaters
+        .iter()
+        .map(|repeater| repeater.node_id)
+        .ne(current.repeaters.iter().map(|repeater| repeater.node_id))
+}
+
+/// Labels the user assigned to a node. These are stored by the driver,
+/// independently of whether the node supports storing them itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeUserMetadata {
+    pub name: Option<String>,
+    pub location: Option<String>,
+    pub room: Option<String>,
+    pub notes: Option<String>,
+}
+
 #[derive(Debug)]
 /// Internal storage for an endpoint instance. Since this is meant be used from both library and external
 /// (application) code, in several locations at once, often simultaneously, we need to use
@@ -0,0 +1,98 @@
+use super::EndpointLike;
+use zwave_cc::commandclass::{dynamic_cc_values, static_cc_values};
+use zwave_cc::values::{CCValueOptions, ValueMetadata};
+use zwave_core::value_id::EndpointValueId;
+use zwave_pal::prelude::*;
+
+/// Enumerates the values that may exist on the given endpoint according to its supported CCs
+/// and their versions, with their metadata, so they can be listed before they are reported.
+///
+/// Values with a dynamic property are included if their instances can be determined from
+/// the cached values, e.g. the firmware versions of additional chips.
+pub fn possible_values<'a>(
+    endpoint: &'a dyn EndpointLike<'a>,
+) -> Vec<(EndpointValueId, ValueMetadata)> {
+    let node_id = endpoint.node_id();
+    let index = endpoint.index();
+    let reported = endpoint.value_cache().value_ids();
+
+    let mut ret = Vec::new();
+    for cc in endpoint.supported_command_classes() {
+        let version = endpoint.get_cc_version(cc).unwrap_or(1);
+        let is_defined =
+            |options: &CCValueOptions| !options.internal && options.is_defined_for(index, version);
+
+        let static_values = static_cc_values(cc)
+            .into_iter()
+            .filter(|value| is_defined(&value.options))
+            .map(|value| (value.id, value.metadata.clone()));
+        let dynamic_values = dynamic_cc_values(cc, &reported)
+            .into_iter()
+            .filter(|(_, options)| is_defined(options))
+            .map(|(value, _)| (value.id, value.metadata));
+
+        ret.extend(
+            static_values
+                .chain(dynamic_values)
+                .map(|(value_id, metadata)| {
+                    (EndpointValueId::new(node_id, index, value_id), metadata)
+                }),
+        );
+    }
+    ret.sort_by_key(|(value_id, _)| *value_id);
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::node::mock::MockNode;
+    use zwave_cc::commandclass::{ManufacturerSpecificCCValues, VersionCCValues};
+    use zwave_core::cache::{Cache, CacheValue};
+    use zwave_core::prelude::*;
+    use zwave_core::value_id::ValueId;
+
+    fn value_ids<'a>(endpoint: &'a dyn EndpointLike<'a>) -> Vec<ValueId> {
+        possible_values(endpoint)
+            .into_iter()
+            .map(|(value_id, _)| value_id.value_id())
+            .collect()
+    }
+
+    #[test]
+    fn test_possible_values() {
+        let node = MockNode::new(2u8)
+            .with_cc(CommandClasses::Version, 2)
+            .with_cc(CommandClasses::ManufacturerSpecific, 1)
+            .with_endpoint_cc(1, CommandClasses::Version, 2);
+
+        let mut expected = vec![
+            ManufacturerSpecificCCValues::manufacturer_id().id,
+            ManufacturerSpecificCCValues::product_type().id,
+            ManufacturerSpecificCCValues::product_id().id,
+            VersionCCValues::firmware_version().eval((0,)).id,
+            VersionCCValues::library_type().id,
+            VersionCCValues::protocol_version().id,
+            VersionCCValues::hardware_version().id,
+        ];
+        expected.sort();
+        assert_eq!(value_ids(&node), expected);
+
+        // Firmware versions of additional chips exist once they are reported
+        let chip_1 = VersionCCValues::firmware_version().eval((1,));
+        node.value_cache()
+            .write(&chip_1.id, CacheValue::from("1.2".to_string()));
+        let values = possible_values(&node);
+        let (_, metadata) = values
+            .iter()
+            .find(|(value_id, _)| value_id.value_id() == chip_1.id)
+            .expect("the firmware version of chip 1 should exist");
+        assert!(matches!(
+            metadata,
+            ValueMetadata::String(meta) if meta.common.label.as_deref() == Some("Firmware version (chip #1)")
+        ));
+
+        // The Version CC values only exist on the root endpoint
+        assert!(value_ids(&node.endpoint(1)).is_empty());
+    }
+}
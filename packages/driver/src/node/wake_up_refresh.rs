@@ -0,0 +1,277 @@
+use super::{EndpointLike, Node};
+use crate::error::Result;
+use crate::{CCAPIResult, Controller, InterviewStage, Ready, refresh_cc_values};
+use core::time::Duration;
+use typed_builder::TypedBuilder;
+use zwave_cc::commandclass::CC;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
+
+/// Refreshes the values of a CC when a sleeping node wakes up
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefreshOnWakeUp {
+    pub cc: CommandClasses,
+    /// Only refresh the values once they are older than this.
+    /// `None` refreshes them on every wakeup.
+    pub max_age: Option<Duration>,
+}
+
+impl RefreshOnWakeUp {
+    /// Refreshes the values of the given CC on every wakeup
+    pub fn always(cc: CommandClasses) -> Self {
+        Self { cc, max_age: None }
+    }
+
+    /// Refreshes the values of the given CC once they are older than `max_age`
+    pub fn older_than(cc: CommandClasses, max_age: Duration) -> Self {
+        Self {
+            cc,
+            max_age: Some(max_age),
+        }
+    }
+}
+
+/// Which values of a sleeping node are refreshed when it wakes up
+#[derive(Debug, Clone, PartialEq, TypedBuilder)]
+pub struct WakeUpRefreshPolicy {
+    /// The CCs whose values are refreshed, in this order. Default: none
+    #[builder(default, setter(into))]
+    pub ccs: Vec<RefreshOnWakeUp>,
+    /// How long the node is kept awake to refresh values, so it can go back to sleep quickly.
+    /// CCs that were not refreshed in time are refreshed on the next wakeup. Default: 5 s
+    #[builder(default = Duration::from_secs(5))]
+    pub time_budget: Duration,
+    /// Whether the node is sent back to sleep after refreshing the values. Default: true
+    #[builder(default = true)]
+    pub send_no_more_information: bool,
+}
+
+impl Default for WakeUpRefreshPolicy {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// Which values were refreshed after a node woke up
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WakeUpRefreshResult {
+    /// The CCs whose values were refreshed
+    pub refreshed: Vec<CommandClasses>,
+    /// The CCs whose values were outdated, but could not be refreshed within the time budget
+    pub deferred: Vec<CommandClasses>,
+}
+
+impl Node<'_> {
+    /// Which values are refreshed when this node wakes up, if any
+    pub fn wake_up_refresh_policy(&self) -> Option<WakeUpRefreshPolicy> {
+        self.state().wake_up_refresh_policy()
+    }
+
+    /// Changes which values are refreshed when this node wakes up. `None` refreshes nothing.
+    /// The policy is applied while [`Controller::run_wake_up_refresh`] is running.
+    pub fn set_wake_up_refresh_policy(&self, policy: Option<WakeUpRefreshPolicy>) {
+        self.state().set_wake_up_refresh_policy(policy);
+    }
+
+    /// Refreshes the outdated values of the root endpoint according to the node's policy, as
+    /// long as the time budget allows it. Afterwards, the node is sent back to sleep.
+    /// This is meant to be called right after the node woke up.
+    pub async fn refresh_values_on_wake_up(&self) -> CCAPIResult<WakeUpRefreshResult> {
+        let mut result = WakeUpRefreshResult::default();
+        let Some(policy) = self.wake_up_refresh_policy() else {
+            return Ok(result);
+        };
+        let log = self.logger();
+        let deadline = Instant::now().checked_add(policy.time_budget);
+
+        for rule in policy.ccs.iter() {
+            if !self.supports_cc(rule.cc) || !self.values_outdated(rule) {
+                continue;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                result.deferred.push(rule.cc);
+                continue;
+            }
+            refresh_cc_values(self, rule.cc).await?;
+            result.refreshed.push(rule.cc);
+        }
+
+        if !result.deferred.is_empty() {
+            log.info(|| {
+                format!(
+                    "the time budget was used up, refreshing {:?} on the next wakeup",
+                    result.deferred
+                )
+            });
+        }
+
+        if policy.send_no_more_information && self.supports_cc(CommandClasses::WakeUp) {
+            self.cc_api().wake_up().send_no_more_information().await?;
+        }
+
+        Ok(result)
+    }
+
+    /// Whether the node has not reported the values of the given CC recently enough
+    fn values_outdated(&self, rule: &RefreshOnWakeUp) -> bool {
+        let Some(max_age) = rule.max_age else {
+            return true;
+        };
+        let node_id = self.id();
+        let oldest = self
+            .driver()
+            .storage
+            .value_timestamps()
+            .inspect(|timestamps| {
+                timestamps
+                    .iter()
+                    .filter(|(value_id, _)| {
+                        value_id.node_id() == node_id
+                            && value_id.endpoint() == EndpointIndex::Root
+                            && value_id.command_class() == rule.cc
+                    })
+                    .map(|(_, timestamp)| *timestamp)
+                    .min()
+            });
+        // Values that were never reported are outdated
+        oldest
+            .and_then(|timestamp| Instant::now().checked_duration_since(timestamp))
+            .is_none_or(|age| age > max_age)
+    }
+}
+
+impl Controller<'_, Ready> {
+    /// Refreshes the values of sleeping nodes according to their
+    /// [wakeup refresh policy](Node::set_wake_up_refresh_policy) whenever they wake up.
+    /// This must be running for the policies to be applied, and must only be run once per
+    /// driver instance.
+    ///
+    /// Nodes whose interview is not complete are ignored, because their interview continues
+    /// when they wake up.
+    pub async fn run_wake_up_refresh(&self) -> Result<()> {
+        loop {
+            let nodes = self.driver().storage.nodes().clone();
+            let notification = self
+                .driver()
+                .register_awaited_cc(
+                    Box::new(move |cc| {
+                        if !matches!(cc.as_ref(), CC::WakeUpCCNotification(_)) {
+                            return false;
+                        }
+                        nodes.inspect(|nodes| {
+                            nodes.get(&cc.address().source_node_id).is_some_and(|node| {
+                                node.interview_stage == InterviewStage::Done
+                                    && node.wake_up_refresh_policy.is_some()
+                            })
+                        })
+                    }),
+                    None,
+                )
+                .try_await()
+                .await?;
+
+            let node_id = notification.address().source_node_id;
+            let Some(node) = self.node(node_id) else {
+                continue;
+            };
+            match node.refresh_values_on_wake_up().await {
+                Ok(result) => node
+                    .logger()
+                    .info(|| format!("refreshed {:?} after the wakeup", result.refreshed)),
+                Err(e) => node
+                    .logger()
+                    .warn(|| format!("refreshing the values after the wakeup failed: {}", e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NodeStorage;
+    use crate::serial_api::mock::{MockController, mock_protocol_data, run_with_mock_controller};
+
+    #[test]
+    fn test_refresh_outdated_values_on_wake_up() {
+        // Node 5 answers Binary Switch Get with "on"
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            let mut ret = MockController::send_data_ok(request);
+            if request.payload[2..4] == [0x25, 0x02] {
+                ret.push(MockController::application_command(5, &[0x25, 0x03, 0xff]));
+            }
+            ret
+        });
+        let results = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(
+                    NodeId::new(5u8),
+                    NodeStorage::new(mock_protocol_data(false)),
+                );
+            });
+            let controller = Controller::mock(&driver);
+            let node = controller.node(NodeId::new(5u8)).unwrap();
+            for cc in [CommandClasses::BinarySwitch, CommandClasses::WakeUp] {
+                node.modify_cc_info(cc, &PartialCommandClassInfo::default().supported());
+            }
+            node.set_wake_up_refresh_policy(Some(
+                WakeUpRefreshPolicy::builder()
+                    .ccs(vec![RefreshOnWakeUp::older_than(
+                        CommandClasses::BinarySwitch,
+                        Duration::from_secs(3600),
+                    )])
+                    .build(),
+            ));
+
+            // The first wakeup refreshes the unknown value, the second one finds it up to date
+            let first = node.refresh_values_on_wake_up().await.unwrap();
+            let second = node.refresh_values_on_wake_up().await.unwrap();
+            (first, second)
+        });
+
+        assert_eq!(results.0.refreshed, vec![CommandClasses::BinarySwitch]);
+        assert_eq!(results.1, WakeUpRefreshResult::default());
+
+        let sent: Vec<_> = controller
+            .received()
+            .iter()
+            .filter(|cmd| cmd.function_type == FunctionType::SendData)
+            .map(|cmd| (cmd.payload[2], cmd.payload[3]))
+            .collect();
+        // Binary Switch Get, then Wake Up No More Information after each wakeup
+        assert_eq!(sent, vec![(0x25, 0x02), (0x84, 0x08), (0x84, 0x08)]);
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let controller = MockController::new().on(FunctionType::SendData, |_, request| {
+            MockController::send_data_ok(request)
+        });
+        let result = run_with_mock_controller(&controller, |driver| async move {
+            driver.storage.nodes().update(|nodes| {
+                nodes.insert(
+                    NodeId::new(5u8),
+                    NodeStorage::new(mock_protocol_data(false)),
+                );
+            });
+            let controller = Controller::mock(&driver);
+            let node = controller.node(NodeId::new(5u8)).unwrap();
+            node.modify_cc_info(
+                CommandClasses::BinarySwitch,
+                &PartialCommandClassInfo::default().supported(),
+            );
+            node.set_wake_up_refresh_policy(Some(
+                WakeUpRefreshPolicy::builder()
+                    .ccs(vec![RefreshOnWakeUp::always(CommandClasses::BinarySwitch)])
+                    .time_budget(Duration::ZERO)
+                    .build(),
+            ));
+            node.refresh_values_on_wake_up().await.unwrap()
+        });
+
+        assert_eq!(result.deferred, vec![CommandClasses::BinarySwitch]);
+        // The node does not support Wake Up CC, so nothing was sent
+        assert!(controller.received().is_empty());
+    }
+}
@@ -1,22 +1,31 @@
-use crate::error::Result;
+use crate::error::{FatalError, Result};
 use crate::LogSender;
+use alloc::collections::VecDeque;
+use core::time::Duration;
 use zwave_pal::prelude::*;
 use storage::SerialApiStorage;
 use zwave_core::log::Loglevel;
 use zwave_core::prelude::*;
 use zwave_core::submodule;
-use zwave_core::wrapping_counter::WrappingCounter;
 use zwave_logging::LogInfo;
 use zwave_pal::channel::{Receiver, Sender};
 use zwave_pal::time::Instant;
 use zwave_serial::frame::{RawSerialFrame, SerialFrame};
 use zwave_serial::prelude::*;
 
-submodule!(serial_api_machine);
+// The state machine is an implementation detail. Only the metadata it collects is public.
+mod serial_api_machine;
+pub use serial_api_machine::SerialApiCommandMetadata;
+pub(crate) use serial_api_machine::*;
 submodule!(handle);
 submodule!(actor);
+submodule!(unknown_commands);
+submodule!(latency);
 mod storage;
 
+#[cfg(test)]
+pub(crate) mod mock;
+
 type SerialFrameReceiver = Receiver<RawSerialFrame>;
 type SerialFrameSender = Sender<RawSerialFrame>;
 
@@ -31,11 +40,17 @@ impl<T> ExecutableCommand for T where T: CommandRequest + AsCommandRaw {}
 
 struct SerialApiCommandState {
     command: Box<dyn ExecutableCommand>,
+    /// The serialized command, kept for retransmission
+    frame: RawSerialFrame,
     timeout: Option<Instant>,
+    /// Whether the command needs to be retransmitted once the timeout elapses
+    retransmit_pending: bool,
     expects_response: bool,
     expects_callback: bool,
+    callback_timeout: Duration,
     machine: SerialApiMachine,
-    callback: Option<zwave_pal::channel::oneshot::Sender<Result<SerialApiMachineResult>>>,
+    metadata: SerialApiCommandMetadata,
+    callback: Option<zwave_pal::channel::oneshot::Sender<Result<SerialApiCommandResult>>>,
 }
 
 /// An actor to interact with the Serial API in a sans-io fashion:
@@ -57,16 +72,27 @@ pub struct SerialApiActor {
 
     /// The serial API command that's currently being executed
     serial_api_command: Option<SerialApiCommandState>,
+    /// Commands that were received while another command was being executed
+    queued_commands: VecDeque<SerialApiInput>,
+    /// Handshake commands that were received while another command was being executed.
+    /// These are executed before the other queued commands.
+    queued_handshakes: VecDeque<SerialApiInput>,
+    /// While the controller is busy, queued commands are not started before this time
+    queue_paused_until: Option<Instant>,
 
     // Some context that's needed for encoding and decoding commands
     storage: Arc<SerialApiStorage>,
-    callback_id: WrappingCounter<u8>,
+
+    /// Whether the controller stopped acknowledging commands and we are trying to recover it
+    controller_unresponsive: bool,
+
+    /// Why the actor stops, once it should
+    exit: Option<core::result::Result<(), FatalError>>,
 }
 
 pub struct SerialApiAdapter {
     pub serial_in: SerialFrameSender,
     pub serial_out: SerialFrameReceiver,
-    pub input_tx: SerialApiInputSender,
     pub event_rx: SerialApiEventReceiver,
 }
 
@@ -88,7 +114,6 @@ impl SerialApi {
         let adapter = SerialApiAdapter {
             serial_in: serial_in_tx,
             serial_out: serial_out_rx,
-            input_tx: input_tx.clone(),
             event_rx,
         };
 
@@ -105,36 +130,66 @@ impl SerialApi {
             input_rx,
             event_tx,
             serial_api_command: None,
+            queued_commands: VecDeque::new(),
+            queued_handshakes: VecDeque::new(),
+            queue_paused_until: None,
             storage,
-            callback_id: WrappingCounter::new(),
+            controller_unresponsive: false,
+            exit: None,
         };
 
         (handle, actor, adapter)
     }
 }
 
-pub enum SerialApiInput {
-    /// Transmit the given frame
-    Transmit {
-        frame: SerialFrame,
-    },
+pub(crate) enum SerialApiInput {
     /// Notify the application that a frame was received
     Receive {
         frame: SerialFrame,
+        /// When the frame was read from the serial port
+        received_at: Instant,
     },
     /// Execute the given command and return the result once it's done
     ExecCommand {
         command: Box<dyn ExecutableCommand>,
-        callback: zwave_pal::channel::oneshot::Sender<Result<SerialApiMachineResult>>,
+        /// How long to wait for the callback. Uses the default timeout if `None`.
+        callback_timeout: Option<Duration>,
+        /// Whether the command is part of a handshake, e.g. a nonce exchange. Those are
+        /// time-critical and executed before all other queued commands.
+        handshake: bool,
+        callback: zwave_pal::channel::oneshot::Sender<Result<SerialApiCommandResult>>,
     },
     /// Log the given message
     Log {
         log: LogInfo,
         level: Loglevel,
     },
+    /// Return the function types of the command that's currently being executed,
+    /// followed by the queued ones
+    GetPendingCommands {
+        callback: zwave_pal::channel::oneshot::Sender<Vec<FunctionType>>,
+    },
+    /// Stop the actor. Pending commands are dropped, so their callers receive an error.
+    Stop,
 }
 
 pub enum SerialApiEvent {
     /// A command was received that does not belong to the currently executed command
     Unsolicited { command: Command },
+    /// The controller repeatedly failed to acknowledge a command. A soft reset was attempted to recover it.
+    ControllerUnresponsive,
+    /// The controller acknowledged a command again after being unresponsive
+    ControllerRecovered,
+    /// A frame with an invalid checksum was received from the controller and discarded.
+    /// This indicates a problem with the serial connection.
+    ChecksumMismatch { data: Vec<u8> },
+}
+
+/// Statistics about the communication with the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SerialApiStatistics {
+    /// How many frames were discarded because their checksum did not match
+    pub checksum_errors: u64,
+    /// How long received frames spent in each stage until they were handled
+    pub frame_latencies: FrameLatencies,
 }
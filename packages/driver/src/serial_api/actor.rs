@@ -1,8 +1,10 @@
 use zwave_pal::prelude::*;
 use super::{
-    SerialApiActor, SerialApiCommandState, SerialApiEvent, SerialApiInput, SerialApiMachine,
-    SerialApiMachineCondition, SerialApiMachineInput, SerialApiMachineState,
+    FrameStage, SerialApiActor, SerialApiCommandMetadata, SerialApiCommandResult,
+    SerialApiCommandState, SerialApiEvent, SerialApiInput, SerialApiMachine,
+    SerialApiMachineCondition, SerialApiMachineInput, SerialApiMachineState, UnknownCommandType,
 };
+use crate::error::{ActorKind, FatalError};
 use core::time::Duration;
 use zwave_core::prelude::*;
 use zwave_core::state_machine::{StateMachine, StateMachineTransition};
@@ -13,11 +15,22 @@ use zwave_logging::{
     Direction, LocalImmutableLogger, LogInfo,
 };
 use zwave_pal::time::Instant;
+use zwave_serial::command::{NotImplemented, SoftResetRequest};
 use zwave_serial::frame::{ControlFlow, RawSerialFrame, SerialFrame};
 use zwave_serial::prelude::*;
 
+/// How often a command is sent to the controller before giving up, if it is not acknowledged
+const MAX_SEND_ATTEMPTS: u8 = 3;
+/// How long to wait for the controller to acknowledge a command
+const ACK_TIMEOUT: Duration = Duration::from_millis(1600);
+/// How long to wait for a callback, unless the command specifies otherwise
+const DEFAULT_CALLBACK_TIMEOUT: Duration = Duration::from_millis(30000);
+/// How long queued commands are held back after the controller signaled that it is busy
+const CONTROLLER_BUSY_PAUSE: Duration = Duration::from_millis(500);
+
 impl SerialApiActor {
-    pub async fn run(&mut self) {
+    /// Runs the actor until it is stopped or encounters an error it cannot recover from
+    pub async fn run(&mut self) -> core::result::Result<(), FatalError> {
         {
             let driver_logger = self.driver_log();
             driver_logger.logo();
@@ -26,19 +39,25 @@ impl SerialApiActor {
         }
 
         loop {
+            if let Some(exit) = self.exit.take() {
+                return exit;
+            }
+
             // We may or may not have a timeout to wait for. Construct a MaybeSleep to deal with this.
-            let serial_api_timeout_duration = self
-                .serial_api_command
-                .as_ref()
-                .and_then(|cmd| cmd.timeout)
-                .and_then(|i| i.checked_duration_since(Instant::now()));
+            // Without a current command, this is the end of the pause of the queue.
+            let serial_api_timeout_duration = match &self.serial_api_command {
+                Some(cmd) => cmd.timeout,
+                None => self.queue_paused_until,
+            }
+            .map(|i| i.saturating_duration_since(Instant::now()));
             let serial_api_sleep = MaybeSleep::new(serial_api_timeout_duration);
 
             zwave_pal::select_biased! {
                 // Handle incoming frames
                 frame = self.serial_in.recv() => {
-                    if let Some(frame) = frame {
-                        self.handle_serial_frame(frame);
+                    match frame {
+                        Some(frame) => self.handle_serial_frame(frame),
+                        None => self.escalate("the serial port was closed".to_string()),
                     }
                 },
                 // before inputs
@@ -49,7 +68,7 @@ impl SerialApiActor {
                 },
                 // before timeouts
                 _ = serial_api_sleep => {
-                    self.try_advance_serial_api_machine(SerialApiMachineInput::Timeout);
+                    self.handle_timeout();
                 }
             }
         }
@@ -71,25 +90,33 @@ impl SerialApiActor {
     /// This should typically be handled before any other events,
     /// so the Z-Wave module can go back to do what it was doing
     pub fn handle_serial_frame(&mut self, frame: RawSerialFrame) {
+        let received_at = Instant::now();
         match frame {
             RawSerialFrame::ControlFlow(byte) => {
                 self.serial_log().control_flow(byte, Direction::Inbound);
                 self.queue_input(SerialApiInput::Receive {
                     frame: SerialFrame::ControlFlow(byte),
+                    received_at,
                 });
             }
             RawSerialFrame::Data(mut bytes) => {
                 self.serial_log().data(&bytes, Direction::Inbound);
+                let data = bytes.clone();
                 // Try to parse the frame
                 match CommandRaw::parse(&mut bytes) {
                     Ok(raw) => {
                         // The first step of parsing was successful, ACK the frame
                         self.queue_transmit(RawSerialFrame::ControlFlow(ControlFlow::ACK));
+                        self.record_frame_latency(FrameStage::Ack, received_at, Instant::now());
                         self.queue_input(SerialApiInput::Receive {
                             frame: SerialFrame::Command(raw),
+                            received_at,
                         });
                     }
-                    Err(_e) => {
+                    Err(e) => {
+                        if e.is_checksum_mismatch() {
+                            self.handle_checksum_mismatch(&data);
+                        }
                         // Parsing failed, this means we've received garbage after all
                         // Try to re-synchronize with the Z-Wave module
                         self.queue_transmit(RawSerialFrame::ControlFlow(ControlFlow::NAK));
@@ -104,21 +131,91 @@ impl SerialApiActor {
         }
     }
 
+    fn handle_checksum_mismatch(&mut self, data: &[u8]) {
+        self.driver_log().warn(|| {
+            format!(
+                "discarding frame with invalid checksum: 0x{}",
+                hex::encode(data)
+            )
+        });
+        self.storage
+            .statistics()
+            .update(|statistics| statistics.checksum_errors += 1);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_checksum_mismatch(self.storage.label().cloned().as_deref());
+        self.queue_event(SerialApiEvent::ChecksumMismatch {
+            data: data.to_vec(),
+        });
+    }
+
+    /// Records how long a received frame took to be parsed and returns when that was done
+    fn record_frame_parsed(&self, received_at: Instant) -> Instant {
+        let parsed_at = Instant::now();
+        self.record_frame_latency(FrameStage::Parse, received_at, parsed_at);
+        parsed_at
+    }
+
+    fn record_frame_dispatched(&self, received_at: Instant, parsed_at: Instant) {
+        let dispatched_at = Instant::now();
+        self.record_frame_latency(FrameStage::Dispatch, parsed_at, dispatched_at);
+        self.record_frame_latency(FrameStage::Total, received_at, dispatched_at);
+    }
+
+    fn record_frame_latency(&self, stage: FrameStage, start: Instant, end: Instant) {
+        let latency = end.checked_duration_since(start).unwrap_or_default();
+        self.storage
+            .statistics()
+            .update(|statistics| statistics.frame_latencies.record(stage, latency));
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_frame_latency(
+            self.storage.label().cloned().as_deref(),
+            stage,
+            latency,
+        );
+    }
+
+    fn handle_unknown_command(&self, command: &NotImplemented) {
+        let command_type = UnknownCommandType::Command {
+            command_type: command.command_type,
+            function_type: command.function_type,
+        };
+        if self
+            .storage
+            .record_unknown_command(command_type, &command.payload)
+        {
+            self.driver_log().warn(|| {
+                format!(
+                    "received a command that is not implemented: {}, payload: 0x{}",
+                    command_type,
+                    hex::encode(&command.payload)
+                )
+            });
+        }
+    }
+
     /// Passes an input that the driver needs to handle
     fn handle_input(&mut self, input: SerialApiInput) {
         match input {
-            SerialApiInput::Transmit { frame } => {
-                self.queue_transmit(frame.into());
+            SerialApiInput::Receive { frame, received_at } => {
+                self.handle_frame(frame, received_at);
             }
-            SerialApiInput::Receive { frame } => {
-                self.handle_frame(frame);
+            SerialApiInput::ExecCommand { handshake, .. }
+                if self.serial_api_command.is_some() || self.queue_paused_until.is_some() =>
+            {
+                // Only one command can be executed at a time. Continue with this one when
+                // the current one is done and the controller is no longer busy.
+                if handshake {
+                    self.queued_handshakes.push_back(input);
+                } else {
+                    self.queued_commands.push_back(input);
+                }
             }
             SerialApiInput::ExecCommand {
                 mut command,
+                callback_timeout,
                 callback,
+                ..
             } => {
-                // FIXME: handle busy state
-
                 // Set up state machine and interpreter
                 let machine = SerialApiMachine::new();
 
@@ -130,58 +227,86 @@ impl SerialApiActor {
                 let expects_response = command.expects_response();
                 let expects_callback = command.expects_callback();
 
-                let ctx = CommandEncodingContext::builder()
-                    .own_node_id(self.storage.own_node_id().get())
-                    .node_id_type(self.storage.node_id_type().get())
-                    .sdk_version(self.storage.sdk_version().get())
-                    .build();
+                let ctx = self.storage.command_context();
                 let raw = command.as_raw(&ctx);
-                let frame = SerialFrame::Command(raw);
+                let frame: RawSerialFrame = SerialFrame::Command(raw).into();
 
                 self.controller_log()
                     .command(command.as_ref(), Direction::Outbound);
 
                 self.serial_api_command = Some(SerialApiCommandState {
                     command,
+                    frame: frame.clone(),
                     timeout: None,
+                    retransmit_pending: false,
                     expects_response,
                     expects_callback,
+                    callback_timeout: callback_timeout.unwrap_or(DEFAULT_CALLBACK_TIMEOUT),
                     machine,
+                    metadata: SerialApiCommandMetadata {
+                        sent_at: Some(Instant::now()),
+                        attempts: 1,
+                        ..Default::default()
+                    },
                     callback: Some(callback),
                 });
-                self.queue_transmit(frame.into());
+                self.queue_transmit(frame);
 
                 self.try_advance_serial_api_machine(SerialApiMachineInput::Start);
             }
+            SerialApiInput::GetPendingCommands { callback } => {
+                let current = self
+                    .serial_api_command
+                    .as_ref()
+                    .map(|state| state.command.function_type());
+                let queued = self.queued_handshakes.iter().chain(self.queued_commands.iter());
+                let queued = queued.filter_map(|input| match input {
+                    SerialApiInput::ExecCommand { command, .. } => Some(command.function_type()),
+                    _ => None,
+                });
+                let _ = callback.send(current.into_iter().chain(queued).collect());
+            }
             SerialApiInput::Log { log, level } => {
-                self.log_queue
-                    .try_send((log, level))
-                    .expect("Failed to log message");
+                #[cfg(feature = "diagnostics")]
+                self.storage.record_log(&log, level);
+                // Like our own logs, these are dropped if the logger does not keep up
+                let _ = self.log_queue.try_send((log, level));
+            }
+            SerialApiInput::Stop => {
+                self.driver_log().info(|| "stopping the Serial API actor");
+                self.exit.get_or_insert(Ok(()));
             }
         }
     }
 
-    fn handle_frame(&mut self, frame: SerialFrame) {
+    fn handle_frame(&mut self, frame: SerialFrame, received_at: Instant) {
         match frame {
             SerialFrame::ControlFlow(control_flow) => {
                 // Forward control flow frames to the state machine if it's waiting for an ACK
-                if let Some(SerialApiCommandState { machine, .. }) = &self.serial_api_command {
-                    if *machine.state() == SerialApiMachineState::WaitingForACK {
-                        let handled = match control_flow {
-                            ControlFlow::ACK => {
-                                self.try_advance_serial_api_machine(SerialApiMachineInput::ACK)
-                            }
-                            ControlFlow::NAK => {
-                                self.try_advance_serial_api_machine(SerialApiMachineInput::NAK)
+                if self.is_waiting_for_ack() {
+                    match control_flow {
+                        ControlFlow::ACK => {
+                            if self.controller_unresponsive {
+                                self.controller_unresponsive = false;
+                                #[cfg(feature = "metrics")]
+                                crate::metrics::record_controller_responsive(
+                                    self.storage.label().cloned().as_deref(),
+                                    true,
+                                );
+                                self.driver_log()
+                                    .info(|| "The controller is responsive again");
+                                self.queue_event(SerialApiEvent::ControllerRecovered);
                             }
-                            ControlFlow::CAN => {
-                                self.try_advance_serial_api_machine(SerialApiMachineInput::CAN)
-                            }
-                        };
-                        if handled {
-                            return;
+                            self.try_advance_serial_api_machine(SerialApiMachineInput::ACK);
+                        }
+                        ControlFlow::NAK => {
+                            self.handle_transmit_failure(SerialApiMachineInput::NAK);
+                        }
+                        ControlFlow::CAN => {
+                            self.handle_transmit_failure(SerialApiMachineInput::CAN);
                         }
                     }
+                    return;
                 }
 
                 // TODO: What else to do with this frame?
@@ -189,13 +314,28 @@ impl SerialApiActor {
                 return;
             }
             SerialFrame::Command(raw) => {
+                // Commands that were sent in raw form expect their responses unparsed too
+                if self
+                    .serial_api_command
+                    .as_ref()
+                    .is_some_and(|state| state.command.expects_raw_frames())
+                {
+                    let cmd = Command::NotImplemented(NotImplemented {
+                        command_type: raw.command_type,
+                        function_type: raw.function_type,
+                        payload: raw.payload.clone(),
+                    });
+                    if let Some(input) = self.expected_input(&cmd) {
+                        let parsed_at = self.record_frame_parsed(received_at);
+                        self.try_advance_serial_api_machine(input);
+                        self.record_frame_dispatched(received_at, parsed_at);
+                        return;
+                    }
+                }
+
                 // Try to convert it into an actual command
                 let cmd = {
-                    let ctx = CommandParsingContext::builder()
-                        .own_node_id(self.storage.own_node_id().get())
-                        .node_id_type(self.storage.node_id_type().get())
-                        .sdk_version(self.storage.sdk_version().get())
-                        .build();
+                    let ctx = self.storage.command_context();
                     match zwave_serial::command::Command::try_from_raw(raw, ctx) {
                         Ok(cmd) => cmd,
                         Err(_e) => {
@@ -204,58 +344,180 @@ impl SerialApiActor {
                         }
                     }
                 };
+                let parsed_at = self.record_frame_parsed(received_at);
+
+                if let Command::NotImplemented(unknown) = &cmd {
+                    self.handle_unknown_command(unknown);
+                }
 
                 // Check if this is an expected response or callback
-                if let Some(SerialApiCommandState {
-                    command,
-                    machine,
-                    ..
-                }) = &self.serial_api_command
-                {
-                    let input = match machine.state() {
-                        SerialApiMachineState::WaitingForResponse
-                            if command.test_response(&cmd) =>
-                        {
-                            if cmd.is_ok() {
-                                Some(SerialApiMachineInput::Response(cmd.clone()))
-                            } else {
-                                Some(SerialApiMachineInput::ResponseNOK(cmd.clone()))
-                            }
-                        }
-                        SerialApiMachineState::WaitingForCallback
-                            if command.test_callback(&cmd) =>
-                        {
-                            if cmd.is_ok() {
-                                Some(SerialApiMachineInput::Callback(cmd.clone()))
-                            } else {
-                                Some(SerialApiMachineInput::CallbackNOK(cmd.clone()))
-                            }
-                        }
-                        _ => None,
-                    };
-                    if let Some(input) = input {
-                        self.try_advance_serial_api_machine(input);
-                        return;
-                    }
+                if let Some(input) = self.expected_input(&cmd) {
+                    self.try_advance_serial_api_machine(input);
+                    self.record_frame_dispatched(received_at, parsed_at);
+                    return;
                 }
 
                 // Not expected. Logging must happen upstream, so embedded CCs can be decoded
                 self.queue_event(SerialApiEvent::Unsolicited { command: cmd });
+                self.record_frame_dispatched(received_at, parsed_at);
             }
             // Not much we can do with a raw frame at this point
-            _ => {
-                todo!("handle received frame: {:?}", frame);
+            SerialFrame::Raw(data) => {
+                self.serial_log().discarded(&data);
             }
         }
     }
 
+    /// Returns the state machine input if the given command is the response or callback
+    /// the current command is waiting for
+    fn expected_input(&self, cmd: &Command) -> Option<SerialApiMachineInput> {
+        let SerialApiCommandState {
+            command, machine, ..
+        } = self.serial_api_command.as_ref()?;
+
+        match machine.state() {
+            SerialApiMachineState::WaitingForResponse if command.test_response(cmd) => {
+                if cmd.is_ok() {
+                    Some(SerialApiMachineInput::Response(cmd.clone()))
+                } else {
+                    Some(SerialApiMachineInput::ResponseNOK(cmd.clone()))
+                }
+            }
+            SerialApiMachineState::WaitingForCallback if command.test_callback(cmd) => {
+                if cmd.is_ok() {
+                    Some(SerialApiMachineInput::Callback(cmd.clone()))
+                } else {
+                    Some(SerialApiMachineInput::CallbackNOK(cmd.clone()))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the current command was sent and is waiting to be acknowledged
+    fn is_waiting_for_ack(&self) -> bool {
+        self.serial_api_command.as_ref().is_some_and(|cmd| {
+            !cmd.retransmit_pending && *cmd.machine.state() == SerialApiMachineState::WaitingForACK
+        })
+    }
+
+    fn handle_timeout(&mut self) {
+        let Some(cmd) = &mut self.serial_api_command else {
+            // The controller had some time to finish what it was doing
+            self.queue_paused_until = None;
+            self.start_next_command();
+            return;
+        };
+
+        if cmd.retransmit_pending {
+            cmd.retransmit_pending = false;
+            cmd.timeout = Instant::now().checked_add(ACK_TIMEOUT);
+            cmd.metadata.sent_at = Some(Instant::now());
+            cmd.metadata.attempts += 1;
+            let frame = cmd.frame.clone();
+            self.queue_transmit(frame);
+        } else if self.is_waiting_for_ack() {
+            self.handle_transmit_failure(SerialApiMachineInput::Timeout);
+        } else {
+            self.try_advance_serial_api_machine(SerialApiMachineInput::Timeout);
+        }
+    }
+
+    /// Handles a NAK, CAN or missing ACK for the current command by scheduling a retransmission.
+    /// When all attempts failed, the command fails and we try to recover the controller.
+    fn handle_transmit_failure(&mut self, input: SerialApiMachineInput) {
+        let Some(cmd) = &mut self.serial_api_command else {
+            return;
+        };
+
+        match input {
+            SerialApiMachineInput::NAK => cmd.metadata.nak_count += 1,
+            SerialApiMachineInput::CAN => cmd.metadata.can_count += 1,
+            _ => {}
+        }
+
+        let attempts = cmd.metadata.attempts;
+        // A CAN means that the controller is busy sending us a frame. It is responsive,
+        // but should not receive the next command right away.
+        let busy = cmd.metadata.can_count == attempts;
+        if input == SerialApiMachineInput::CAN {
+            self.queue_paused_until = Instant::now().checked_add(CONTROLLER_BUSY_PAUSE);
+        }
+
+        if attempts < MAX_SEND_ATTEMPTS {
+            // Wait a bit before retransmitting, as documented in the Serial API specification
+            let delay = Duration::from_millis(100 + 1000 * (attempts as u64 - 1));
+            cmd.timeout = Instant::now().checked_add(delay);
+            cmd.retransmit_pending = true;
+            self.driver_log().verbose(|| {
+                format!(
+                    "The controller did not acknowledge the command ({:?}), retrying in {} ms...",
+                    input,
+                    delay.as_millis()
+                )
+            });
+            return;
+        }
+
+        self.try_advance_serial_api_machine(input);
+        if busy {
+            self.driver_log().warn(|| {
+                format!(
+                    "The controller was busy during all {} attempts to send a command",
+                    MAX_SEND_ATTEMPTS
+                )
+            });
+        } else {
+            self.recover_unresponsive_controller();
+        }
+    }
+
+    /// Tries to get an unresponsive controller back into a working state by
+    /// re-synchronizing the serial communication and soft-resetting it.
+    fn recover_unresponsive_controller(&mut self) {
+        if self.controller_unresponsive {
+            // Recovery is already in progress
+            return;
+        }
+        self.controller_unresponsive = true;
+        // The controller is reset anyways, so there's no point in waiting for it to be less busy
+        self.queue_paused_until = None;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_controller_responsive(
+            self.storage.label().cloned().as_deref(),
+            false,
+        );
+
+        self.driver_log().warn(|| {
+            format!(
+                "The controller did not acknowledge a command after {} attempts, attempting to recover it with a soft reset...",
+                MAX_SEND_ATTEMPTS
+            )
+        });
+        self.queue_event(SerialApiEvent::ControllerUnresponsive);
+
+        self.queue_transmit(RawSerialFrame::ControlFlow(ControlFlow::NAK));
+        let ctx = self.storage.command_context();
+        let soft_reset = SoftResetRequest::default();
+        self.controller_log()
+            .command(&soft_reset, Direction::Outbound);
+        self.queue_transmit(SerialFrame::Command(soft_reset.as_raw(&ctx)).into());
+    }
+
     // Passes the input to the running serial API machine and returns whether it was handled
     fn try_advance_serial_api_machine(&mut self, input: SerialApiMachineInput) -> bool {
+        #[cfg(feature = "metrics")]
+        let function_type = self
+            .serial_api_command
+            .as_ref()
+            .map(|state| state.command.function_type());
         let Some(SerialApiCommandState {
             ref mut timeout,
             expects_response,
             expects_callback,
+            callback_timeout,
             ref mut machine,
+            ref mut metadata,
             ref mut callback,
             ..
         }) = self.serial_api_command
@@ -278,26 +540,53 @@ impl SerialApiActor {
             return false;
         };
 
+        // Remember when and how the Z-Wave module reacted
+        let now = Instant::now();
+        match input {
+            SerialApiMachineInput::ACK => metadata.ack_at = Some(now),
+            SerialApiMachineInput::Response(_) | SerialApiMachineInput::ResponseNOK(_) => {
+                metadata.response_at = Some(now)
+            }
+            SerialApiMachineInput::Callback(_) | SerialApiMachineInput::CallbackNOK(_) => {
+                metadata.callback_at = Some(now)
+            }
+            _ => {}
+        }
+
         // Transition to the new state
         machine.transition(transition.new_state());
 
         match machine.state() {
             SerialApiMachineState::WaitingForACK => {
-                *timeout = Instant::now().checked_add(Duration::from_millis(1600));
+                *timeout = Instant::now().checked_add(ACK_TIMEOUT);
             }
             // FIXME: Set better timeouts
             SerialApiMachineState::WaitingForResponse => {
                 *timeout = Instant::now().checked_add(Duration::from_millis(10000));
             }
             SerialApiMachineState::WaitingForCallback => {
-                *timeout = Instant::now().checked_add(Duration::from_millis(30000));
+                *timeout = Instant::now().checked_add(callback_timeout);
             }
             SerialApiMachineState::Done(result) => {
-                callback
-                    .take()
-                    .expect("Serial API command callback already consumed")
-                    .send(Ok(result.clone()))
-                    .expect("Failed to send Serial API command result");
+                #[cfg(feature = "metrics")]
+                if let Some(function_type) = function_type {
+                    crate::metrics::record_serial_api_command(
+                        self.storage.label().cloned().as_deref(),
+                        function_type,
+                        result,
+                        metadata,
+                    );
+                }
+                let Some(callback) = callback.take() else {
+                    self.escalate("the callback of a command was already consumed".to_string());
+                    return true;
+                };
+                // The caller may no longer be interested in the result,
+                // e.g. if it was dispatched without awaiting it
+                let _ = callback.send(Ok(SerialApiCommandResult {
+                    result: result.clone(),
+                    metadata: core::mem::take(metadata),
+                }));
                 self.serial_api_command = None;
             }
             _ => {}
@@ -313,9 +602,27 @@ impl SerialApiActor {
             self.controller_log().command(&cmd, Direction::Inbound);
         }
 
+        if self.serial_api_command.is_none() {
+            self.start_next_command();
+        }
+
         true
     }
 
+    /// Starts the next queued command, unless the queue is paused. Handshakes go first.
+    fn start_next_command(&mut self) {
+        if self.queue_paused_until.is_some() {
+            return;
+        }
+        let next = self
+            .queued_handshakes
+            .pop_front()
+            .or_else(|| self.queued_commands.pop_front());
+        if let Some(next) = next {
+            self.handle_input(next);
+        }
+    }
+
     fn queue_transmit(&mut self, frame: RawSerialFrame) {
         match &frame {
             RawSerialFrame::Data(data) => {
@@ -327,32 +634,47 @@ impl SerialApiActor {
             _ => {}
         }
 
-        self.serial_out
-            .try_send(frame)
-            .expect("failed to queue frame for transmit");
+        if let Err(e) = self.serial_out.try_send(frame) {
+            self.escalate(format!("failed to queue frame for transmit: {}", e));
+        }
+    }
+
+    fn queue_input(&mut self, input: SerialApiInput) {
+        if let Err(e) = self.input_tx.try_send(input) {
+            self.escalate(format!("failed to queue serial API input: {}", e));
+        }
     }
 
-    fn queue_input(&self, input: SerialApiInput) {
-        self.input_tx
-            .clone()
-            .try_send(input)
-            .expect("Failed to queue serial API input");
+    fn queue_event(&mut self, event: SerialApiEvent) {
+        if let Err(e) = self.event_tx.try_send(event) {
+            self.escalate(format!("failed to queue serial API event: {}", e));
+        }
     }
 
-    fn queue_event(&self, event: SerialApiEvent) {
-        self.event_tx
-            .clone()
-            .try_send(event)
-            .expect("Failed to queue serial API event");
+    /// Reports an error the actor cannot recover from to the supervisor and stops the actor
+    fn escalate(&mut self, reason: String) {
+        let error = FatalError {
+            actor: ActorKind::SerialApi,
+            reason,
+        };
+        self.driver_log().error(|| error.to_string());
+        if let Some(supervisor) = self.storage.supervisor().cloned() {
+            let _ = supervisor.try_send(error.clone());
+        }
+        if !matches!(self.exit, Some(Err(_))) {
+            self.exit = Some(Err(error));
+        }
     }
 
-    fn get_next_callback_id(&mut self) -> u8 {
-        self.callback_id.increment()
+    fn get_next_callback_id(&self) -> u8 {
+        self.storage.callback_id().update(|counter| counter.increment())
     }
 }
 
 impl LocalImmutableLogger for SerialApiActor {
     fn log(&self, log: LogInfo, level: Loglevel) {
+        #[cfg(feature = "diagnostics")]
+        self.storage.record_log(&log, level);
         let _ = self.log_queue.clone().try_send((log, level));
     }
 
@@ -364,3 +686,269 @@ impl LocalImmutableLogger for SerialApiActor {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{SerialApi, SerialApiMachineResult};
+    use futures::FutureExt;
+    use futures::executor::block_on;
+    use zwave_pal::channel::oneshot;
+    use zwave_serial::command::{GetControllerVersionRequest, GetControllerVersionResponse};
+
+    fn exec_command(
+        actor: &mut SerialApiActor,
+    ) -> oneshot::Receiver<crate::error::Result<SerialApiCommandResult>> {
+        exec_command_in_lane(actor, false)
+    }
+
+    fn exec_command_in_lane(
+        actor: &mut SerialApiActor,
+        handshake: bool,
+    ) -> oneshot::Receiver<crate::error::Result<SerialApiCommandResult>> {
+        let (callback, result) = oneshot::channel();
+        actor.handle_input(SerialApiInput::ExecCommand {
+            command: Box::new(GetControllerVersionRequest::default()),
+            callback_timeout: None,
+            handshake,
+            callback,
+        });
+        result
+    }
+
+    #[test]
+    fn test_result_metadata() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (_serial_api, mut actor, _adapter) = SerialApi::new(log_tx);
+        let result = exec_command(&mut actor);
+
+        actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::ACK), Instant::now());
+        let response = GetControllerVersionResponse {
+            library_type: ZWaveLibraryType::StaticController,
+            library_version: "Z-Wave 7.18".to_string(),
+        };
+        let ctx = CommandEncodingContext::builder().build();
+        actor.handle_frame(SerialFrame::Command(response.as_raw(&ctx)), Instant::now());
+
+        let SerialApiCommandResult { result, metadata } = block_on(result).unwrap().unwrap();
+        assert!(matches!(result, SerialApiMachineResult::Success(Some(_))));
+        assert_eq!(metadata.attempts, 1);
+        assert_eq!(metadata.nak_count, 0);
+        assert_eq!(metadata.can_count, 0);
+        assert!(metadata.ack_duration().is_some());
+        assert!(metadata.response_duration() >= metadata.ack_duration());
+        assert_eq!(metadata.callback_duration(), None);
+    }
+
+    #[test]
+    fn test_retransmit_on_nak() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (_serial_api, mut actor, mut adapter) = SerialApi::new(log_tx);
+        let result = exec_command(&mut actor);
+        let frame = block_on(adapter.serial_out.recv()).unwrap();
+
+        actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::NAK), Instant::now());
+        // The command is sent again after a short delay
+        actor.handle_timeout();
+        assert_eq!(block_on(adapter.serial_out.recv()), Some(frame));
+
+        actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::ACK), Instant::now());
+        let response = GetControllerVersionResponse {
+            library_type: ZWaveLibraryType::StaticController,
+            library_version: "Z-Wave 7.18".to_string(),
+        };
+        let ctx = CommandEncodingContext::builder().build();
+        actor.handle_frame(SerialFrame::Command(response.as_raw(&ctx)), Instant::now());
+
+        let SerialApiCommandResult { result, metadata } = block_on(result).unwrap().unwrap();
+        assert!(matches!(result, SerialApiMachineResult::Success(Some(_))));
+        assert_eq!(metadata.attempts, 2);
+        assert_eq!(metadata.nak_count, 1);
+        assert!(!actor.controller_unresponsive);
+    }
+
+    #[test]
+    fn test_busy_controller_pauses_queue() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (_serial_api, mut actor, mut adapter) = SerialApi::new(log_tx);
+        let result = exec_command(&mut actor);
+        let _next_result = exec_command(&mut actor);
+
+        for _ in 1..MAX_SEND_ATTEMPTS {
+            actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::CAN), Instant::now());
+            actor.handle_timeout();
+        }
+        actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::CAN), Instant::now());
+
+        let SerialApiCommandResult { result, metadata } = block_on(result).unwrap().unwrap();
+        assert_eq!(result, SerialApiMachineResult::CAN);
+        assert_eq!(metadata.can_count, MAX_SEND_ATTEMPTS);
+        // The controller is busy, but not unresponsive
+        assert!(!actor.controller_unresponsive);
+        assert!(adapter.event_rx.recv().now_or_never().is_none());
+
+        let frame = block_on(adapter.serial_out.recv()).unwrap();
+        for _ in 1..MAX_SEND_ATTEMPTS {
+            assert_eq!(block_on(adapter.serial_out.recv()), Some(frame.clone()));
+        }
+        // The next command waits until the controller had some time
+        assert!(adapter.serial_out.recv().now_or_never().is_none());
+        actor.handle_timeout();
+        assert!(adapter.serial_out.recv().now_or_never().is_some());
+    }
+
+    #[test]
+    fn test_handshakes_go_first() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (_serial_api, mut actor, _adapter) = SerialApi::new(log_tx);
+        let first = exec_command(&mut actor);
+        let mut queued = exec_command(&mut actor);
+        let mut handshake = exec_command_in_lane(&mut actor, true);
+
+        let response = GetControllerVersionResponse {
+            library_type: ZWaveLibraryType::StaticController,
+            library_version: "Z-Wave 7.18".to_string(),
+        };
+        let ctx = CommandEncodingContext::builder().build();
+        let respond = |actor: &mut SerialApiActor| {
+            actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::ACK), Instant::now());
+            actor.handle_frame(SerialFrame::Command(response.as_raw(&ctx)), Instant::now());
+        };
+
+        respond(&mut actor);
+        assert!(block_on(first).unwrap().is_ok());
+        // The handshake overtakes the command that was queued before it
+        respond(&mut actor);
+        assert!((&mut handshake).now_or_never().is_some());
+        assert!((&mut queued).now_or_never().is_none());
+        respond(&mut actor);
+        assert!(block_on(queued).unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (serial_api, mut actor, mut adapter) = SerialApi::new(log_tx);
+
+        // GetControllerVersion request with an invalid checksum (should be 0xe9)
+        let data = vec![0x01, 0x03, 0x00, 0x15, 0x00];
+        actor.handle_serial_frame(RawSerialFrame::Data(data.clone().into()));
+
+        assert_eq!(
+            block_on(adapter.serial_out.recv()),
+            Some(RawSerialFrame::ControlFlow(ControlFlow::NAK))
+        );
+        assert!(matches!(
+            block_on(adapter.event_rx.recv()),
+            Some(SerialApiEvent::ChecksumMismatch { data: received }) if received == data
+        ));
+        assert_eq!(serial_api.statistics().checksum_errors, 1);
+    }
+
+    #[test]
+    fn test_frame_latencies() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (serial_api, mut actor, mut adapter) = SerialApi::new(log_tx);
+
+        let response = GetControllerVersionResponse {
+            library_type: ZWaveLibraryType::StaticController,
+            library_version: "Z-Wave 7.18".to_string(),
+        };
+        let ctx = CommandEncodingContext::builder().build();
+        actor.handle_serial_frame(SerialFrame::Command(response.as_raw(&ctx)).into());
+        assert_eq!(
+            block_on(adapter.serial_out.recv()),
+            Some(RawSerialFrame::ControlFlow(ControlFlow::ACK))
+        );
+
+        // Only the ACK is written before the frame is handled
+        let latencies = serial_api.statistics().frame_latencies;
+        assert_eq!(latencies.ack.count(), 1);
+        assert_eq!(latencies.total.count(), 0);
+
+        let input = block_on(actor.input_rx.recv()).unwrap();
+        actor.handle_input(input);
+        assert!(matches!(
+            block_on(adapter.event_rx.recv()),
+            Some(SerialApiEvent::Unsolicited { .. })
+        ));
+
+        let latencies = serial_api.statistics().frame_latencies;
+        assert_eq!(latencies.parse.count(), 1);
+        assert_eq!(latencies.dispatch.count(), 1);
+        assert_eq!(latencies.total.count(), 1);
+        assert!(latencies.total.max() >= latencies.parse.max());
+    }
+
+    #[test]
+    fn test_controller_unresponsive() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (_serial_api, mut actor, mut adapter) = SerialApi::new(log_tx);
+        let result = exec_command(&mut actor);
+
+        actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::NAK), Instant::now());
+        actor.handle_timeout();
+        actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::CAN), Instant::now());
+        actor.handle_timeout();
+        // No ACK for the last attempt
+        actor.handle_timeout();
+
+        let SerialApiCommandResult { result, metadata } = block_on(result).unwrap().unwrap();
+        assert_eq!(result, SerialApiMachineResult::ACKTimeout);
+        assert_eq!(metadata.attempts, MAX_SEND_ATTEMPTS);
+        assert_eq!(metadata.nak_count, 1);
+        assert_eq!(metadata.can_count, 1);
+
+        assert!(matches!(
+            block_on(adapter.event_rx.recv()),
+            Some(SerialApiEvent::ControllerUnresponsive)
+        ));
+        // 3 attempts, followed by a NAK and a soft reset
+        for _ in 0..MAX_SEND_ATTEMPTS {
+            block_on(adapter.serial_out.recv()).unwrap();
+        }
+        assert_eq!(
+            block_on(adapter.serial_out.recv()),
+            Some(RawSerialFrame::ControlFlow(ControlFlow::NAK))
+        );
+        let ctx = CommandEncodingContext::builder().build();
+        assert_eq!(
+            block_on(adapter.serial_out.recv()),
+            Some(SerialFrame::Command(SoftResetRequest::default().as_raw(&ctx)).into())
+        );
+
+        // The next acknowledged command means the controller has recovered
+        let _result = exec_command(&mut actor);
+        actor.handle_frame(SerialFrame::ControlFlow(ControlFlow::ACK), Instant::now());
+        assert!(matches!(
+            block_on(adapter.event_rx.recv()),
+            Some(SerialApiEvent::ControllerRecovered)
+        ));
+    }
+
+    #[test]
+    fn test_fatal_errors_are_escalated() {
+        let (log_tx, _log_rx) = zwave_pal::channel::channel(16);
+        let (serial_api, mut actor, adapter) = SerialApi::new(log_tx);
+        let (supervisor, mut supervision_rx) = zwave_pal::channel::channel(1);
+        serial_api.storage.supervisor().set(Some(supervisor));
+
+        // Without the adapter, the command cannot be transmitted
+        drop(adapter);
+        let result = exec_command(&mut actor);
+
+        let error = block_on(actor.run()).unwrap_err();
+        assert_eq!(error.actor, ActorKind::SerialApi);
+        assert_eq!(block_on(supervision_rx.recv()), Some(error));
+
+        // Pending and future commands fail instead of waiting forever
+        drop(actor);
+        assert!(block_on(result).is_err());
+        let result = block_on(serial_api.execute_serial_api_command(
+            GetControllerVersionRequest::default(),
+            None,
+            false,
+        ));
+        assert!(matches!(result, Err(crate::error::Error::Stopped)));
+    }
+}
@@ -1,30 +1,80 @@
-use super::serial_api_machine::SerialApiMachineResult;
-use super::{ExecutableCommand, SerialApi, SerialApiInput};
-use crate::error::Result;
+use super::serial_api_machine::SerialApiCommandResult;
+use super::{
+    ExecutableCommand, SerialApi, SerialApiInput, SerialApiStatistics, UnknownCommandStatistics,
+};
+use crate::error::{Error, Result};
+use core::time::Duration;
 use zwave_pal::prelude::*;
+use zwave_core::definitions::FunctionType;
 use zwave_core::log::Loglevel;
 use zwave_logging::{LocalImmutableLogger, LogInfo};
 
 impl SerialApi {
+    /// Passes an input to the actor. If that is not possible, e.g. because the actor stopped,
+    /// the input is dropped together with its callback, which the caller sees as an error.
     pub(crate) fn dispatch(&self, input: SerialApiInput) {
-        self.input_tx
-            .clone()
-            .try_send(input)
-            .expect("Failed to dispatch command");
+        let _ = self.input_tx.try_send(input);
     }
 
-    pub async fn execute_serial_api_command<C>(&self, command: C) -> Result<SerialApiMachineResult>
+    /// Executes a command and returns the result once it's done. Handshake commands
+    /// are executed before all other queued commands.
+    pub(crate) async fn execute_serial_api_command<C>(
+        &self,
+        command: C,
+        callback_timeout: Option<Duration>,
+        handshake: bool,
+    ) -> Result<SerialApiCommandResult>
     where
         C: ExecutableCommand + 'static,
     {
         let (tx, rx) = zwave_pal::channel::oneshot::channel();
         let cmd = SerialApiInput::ExecCommand {
             command: Box::new(command),
+            callback_timeout,
+            handshake,
             callback: tx,
         };
+        #[cfg(feature = "metrics")]
+        let _pending =
+            crate::metrics::PendingSerialApiCommand::new(self.storage.label().cloned().as_deref());
         self.dispatch(cmd);
 
-        rx.await.expect("Failed to receive command result")
+        rx.await.unwrap_or(Err(Error::Stopped))
+    }
+
+    /// Returns the function types of the commands that are waiting to be executed,
+    /// starting with the one that's currently being executed
+    pub async fn pending_commands(&self) -> Vec<FunctionType> {
+        let (tx, rx) = zwave_pal::channel::oneshot::channel();
+        self.dispatch(SerialApiInput::GetPendingCommands { callback: tx });
+        rx.await.unwrap_or_default()
+    }
+
+    /// Returns statistics about the communication with the controller
+    pub fn statistics(&self) -> SerialApiStatistics {
+        self.storage.statistics().get()
+    }
+
+    /// Returns how often commands were received that are not implemented by the library, most frequent first
+    pub fn unknown_commands(&self) -> Vec<UnknownCommandStatistics> {
+        self.storage
+            .unknown_commands()
+            .inspect(|unknown| unknown.statistics())
+    }
+
+    /// Queues a command for execution without waiting for the result.
+    /// This can be used where awaiting is not possible, e.g. during cleanup in `Drop`.
+    pub(crate) fn dispatch_serial_api_command<C>(&self, command: C, handshake: bool)
+    where
+        C: ExecutableCommand + 'static,
+    {
+        let (tx, _rx) = zwave_pal::channel::oneshot::channel();
+        self.dispatch(SerialApiInput::ExecCommand {
+            command: Box::new(command),
+            callback_timeout: None,
+            handshake,
+            callback: tx,
+        });
     }
 }
 
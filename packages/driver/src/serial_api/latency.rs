@@ -0,0 +1,166 @@
+use core::time::Duration;
+
+/// Upper bounds of the histogram buckets in microseconds.
+/// Longer durations are counted in an additional overflow bucket.
+const BUCKET_BOUNDS_US: [u64; 11] = [
+    50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000,
+];
+const BUCKET_COUNT: usize = BUCKET_BOUNDS_US.len() + 1;
+
+/// Distribution of durations, counted in fixed, roughly exponential buckets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyHistogram {
+    counts: [u64; BUCKET_COUNT],
+    sum: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros();
+        let bucket = BUCKET_BOUNDS_US
+            .iter()
+            .position(|&bound| micros <= bound as u128)
+            .unwrap_or(BUCKET_COUNT - 1);
+        self.counts[bucket] += 1;
+        self.sum = self.sum.saturating_add(latency);
+        self.max = self.max.max(latency);
+    }
+
+    /// How many durations were recorded
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The average of the recorded durations
+    pub fn mean(&self) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        Some(Duration::from_nanos(
+            (self.sum.as_nanos() / count as u128) as u64,
+        ))
+    }
+
+    /// The longest recorded duration
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the upper bound of each bucket with the number of durations counted in it.
+    /// The overflow bucket has no upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        BUCKET_BOUNDS_US
+            .iter()
+            .map(|&bound| Some(Duration::from_micros(bound)))
+            .chain(core::iter::once(None))
+            .zip(self.counts.iter().copied())
+    }
+
+    /// Estimates the given percentile (0-100) as the upper bound of the bucket it falls into.
+    /// Durations in the overflow bucket are estimated with the maximum.
+    pub fn percentile(&self, percentile: u8) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = (count * percentile.min(100) as u64).div_ceil(100).max(1);
+        let mut seen = 0;
+        for (bound, bucket_count) in self.buckets() {
+            seen += bucket_count;
+            if seen >= rank {
+                return Some(bound.map_or(self.max, |bound| bound.min(self.max)));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+/// A stage of handling a frame that was received from the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FrameStage {
+    Ack,
+    Parse,
+    Dispatch,
+    Total,
+}
+
+impl FrameStage {
+    #[cfg(feature = "metrics")]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ack => "ack",
+            Self::Parse => "parse",
+            Self::Dispatch => "dispatch",
+            Self::Total => "total",
+        }
+    }
+}
+
+/// How long received frames spend in each stage of the Serial API pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameLatencies {
+    /// From reading a frame until the ACK is written
+    pub ack: LatencyHistogram,
+    /// From reading a frame until it is parsed into a command,
+    /// including the time it waits in the input queue
+    pub parse: LatencyHistogram,
+    /// From parsing a command until it is passed to the pending command or the driver
+    pub dispatch: LatencyHistogram,
+    /// From reading a frame until it is dispatched
+    pub total: LatencyHistogram,
+}
+
+impl FrameLatencies {
+    pub(crate) fn record(&mut self, stage: FrameStage, latency: Duration) {
+        let histogram = match stage {
+            FrameStage::Ack => &mut self.ack,
+            FrameStage::Parse => &mut self.parse,
+            FrameStage::Dispatch => &mut self.dispatch,
+            FrameStage::Total => &mut self.total,
+        };
+        histogram.record(latency);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zwave_pal::prelude::*;
+
+    #[test]
+    fn test_histogram() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.mean(), None);
+        assert_eq!(histogram.percentile(50), None);
+
+        for micros in [40, 80, 90, 300, 200_000] {
+            histogram.record(Duration::from_micros(micros));
+        }
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.max(), Duration::from_micros(200_000));
+        assert_eq!(histogram.mean(), Some(Duration::from_micros(40_102)));
+
+        let buckets: Vec<_> = histogram
+            .buckets()
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        assert_eq!(
+            buckets,
+            vec![
+                (Some(Duration::from_micros(50)), 1),
+                (Some(Duration::from_micros(100)), 2),
+                (Some(Duration::from_micros(500)), 1),
+                (None, 1),
+            ]
+        );
+
+        assert_eq!(histogram.percentile(50), Some(Duration::from_micros(100)));
+        assert_eq!(histogram.percentile(80), Some(Duration::from_micros(500)));
+        assert_eq!(
+            histogram.percentile(100),
+            Some(Duration::from_micros(200_000))
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! A mock serial port with a simulated controller behind it, for testing the driver end-to-end.
+//!
+//! The controller acknowledges every command it receives and answers it using the handler that
+//! was registered for the command's function type. Commands without a handler are only
+//! acknowledged. All received commands are recorded, so tests can inspect the raw bytes.
+//! To simulate a busy controller, it can cancel a number of frames instead.
+
+use crate::{Driver, DriverActor, DriverAdapter, DriverEvent, DriverInput, LogReceiver};
+use crate::{SecurityKeys, SerialApi, SerialApiActor, SerialApiAdapter, SerialApiEvent};
+use bytes::Bytes;
+use core::future::Future;
+use futures::FutureExt;
+use futures::executor::LocalPool;
+use futures::task::LocalSpawnExt;
+use zwave_core::prelude::*;
+use zwave_pal::{prelude::*, sync::Locked};
+use zwave_serial::command_raw::CommandRaw;
+use zwave_serial::frame::{ControlFlow, RawSerialFrame, SerialFrame};
+use zwave_serial::prelude::*;
+
+type MockHandler = Box<dyn Fn(&MockController, &CommandRaw) -> Vec<CommandRaw>>;
+
+pub(crate) struct MockController {
+    node_id_type: Locked<NodeIdType>,
+    handlers: Vec<(FunctionType, MockHandler)>,
+    received: Locked<Vec<CommandRaw>>,
+    events: Locked<Vec<DriverEvent>>,
+    /// How many of the next frames are answered with a CAN
+    frames_to_cancel: Locked<usize>,
+}
+
+impl MockController {
+    pub fn new() -> Self {
+        Self {
+            node_id_type: Locked::new(NodeIdType::NodeId8Bit),
+            handlers: Vec::new(),
+            received: Locked::new(Vec::new()),
+            events: Locked::new(Vec::new()),
+            frames_to_cancel: Locked::new(0),
+        }
+    }
+
+    /// Answers all commands with the given function type using the handler
+    pub fn on(
+        mut self,
+        function_type: FunctionType,
+        handler: impl Fn(&MockController, &CommandRaw) -> Vec<CommandRaw> + 'static,
+    ) -> Self {
+        self.handlers.push((function_type, Box::new(handler)));
+        self
+    }
+
+    /// Answers the next `count` data frames with a CAN, like a controller that is busy
+    pub fn cancel_next(&self, count: usize) {
+        self.frames_to_cancel.set(count);
+    }
+
+    /// The node ID type the simulated controller currently uses
+    pub fn node_id_type(&self) -> NodeIdType {
+        self.node_id_type.get()
+    }
+
+    pub fn set_node_id_type(&self, node_id_type: NodeIdType) {
+        self.node_id_type.set(node_id_type);
+    }
+
+    /// Serializes a command like the simulated controller would
+    pub fn encode(&self, command: &impl AsCommandRaw) -> CommandRaw {
+        let ctx = CommandEncodingContext::builder()
+            .own_node_id(NodeId::new(1u8))
+            .node_id_type(self.node_id_type())
+            .build();
+        command.as_raw(&ctx)
+    }
+
+    /// Creates a command with the given raw payload
+    pub fn raw(
+        command_type: CommandType,
+        function_type: FunctionType,
+        payload: impl Into<Bytes>,
+    ) -> CommandRaw {
+        CommandRaw {
+            command_type,
+            function_type,
+            payload: payload.into(),
+            // The checksum is computed during serialization
+            checksum: 0,
+        }
+    }
+
+    /// Answers a SendData request like a controller that transmitted the command successfully
+    pub fn send_data_ok(request: &CommandRaw) -> Vec<CommandRaw> {
+        Self::send_data(request, TransmitStatus::Ok)
+    }
+
+    /// Answers a SendData request with the given transmit status
+    pub fn send_data(request: &CommandRaw, status: TransmitStatus) -> Vec<CommandRaw> {
+        let callback_id = *request.payload.last().unwrap();
+        // Transmit status, followed by an empty transmit report
+        let mut callback = vec![callback_id, status as u8];
+        callback.extend_from_slice(&[0; 15]);
+        // 9.6 kbit/s route speed, 1 routing attempt
+        callback.extend_from_slice(&[0x01, 0x01]);
+        vec![
+            Self::raw(CommandType::Response, FunctionType::SendData, vec![0x01]),
+            Self::raw(CommandType::Request, FunctionType::SendData, callback),
+        ]
+    }
+
+    /// Creates an unsolicited command which contains the given raw CC, sent by the given node
+    pub fn application_command(source_node_id: u8, cc: &[u8]) -> CommandRaw {
+        let mut payload = vec![0x00, source_node_id, cc.len() as u8];
+        payload.extend_from_slice(cc);
+        // RSSI not available
+        payload.push(0x00);
+        Self::raw(
+            CommandType::Request,
+            FunctionType::ApplicationCommand,
+            payload,
+        )
+    }
+
+    /// Returns all commands the controller received so far
+    pub fn received(&self) -> Vec<CommandRaw> {
+        self.received.inspect(|received| received.clone())
+    }
+
+    /// Returns the events the driver emitted since this was last called
+    pub fn take_events(&self) -> Vec<DriverEvent> {
+        self.events.update(core::mem::take)
+    }
+
+    fn handle_frame(&self, frame: RawSerialFrame) -> Vec<RawSerialFrame> {
+        let RawSerialFrame::Data(mut data) = frame else {
+            // Control flow frames need no answer
+            return Vec::new();
+        };
+        let cancel = self.frames_to_cancel.update(|count| {
+            let cancel = *count > 0;
+            *count = count.saturating_sub(1);
+            cancel
+        });
+        if cancel {
+            return vec![RawSerialFrame::ControlFlow(ControlFlow::CAN)];
+        }
+        let Ok(command) = CommandRaw::parse(&mut data) else {
+            return vec![RawSerialFrame::ControlFlow(ControlFlow::NAK)];
+        };
+        self.received.update(|received| received.push(command.clone()));
+
+        let mut ret = vec![RawSerialFrame::ControlFlow(ControlFlow::ACK)];
+        if let Some((_, handler)) = self
+            .handlers
+            .iter()
+            .find(|(function_type, _)| *function_type == command.function_type)
+        {
+            ret.extend(
+                handler(self, &command)
+                    .into_iter()
+                    .map(|cmd| SerialFrame::Command(cmd).into()),
+            );
+        }
+        ret
+    }
+}
+
+/// Protocol data of a routing end node, which the tests use for simulated nodes
+pub(crate) fn mock_protocol_data(listening: bool) -> NodeInformationProtocolData {
+    NodeInformationProtocolData {
+        listening,
+        frequent_listening: None,
+        routing: true,
+        supported_data_rates: [DataRate::DataRate_100k].into_iter().collect(),
+        protocol_version: ProtocolVersion::V6,
+        optional_functionality: true,
+        node_type: NodeType::EndNode,
+        supports_security: false,
+        beaming: true,
+        basic_device_type: BasicDeviceType::RoutingEndNode,
+        generic_device_class: 0x10,
+        specific_device_class: Some(0x01),
+    }
+}
+
+/// A driver and the parts of it that are normally run by the application.
+/// None of the actors are running, so tests can drive them by hand.
+pub(crate) struct MockDriver {
+    pub driver: Driver,
+    pub actor: DriverActor,
+    pub adapter: DriverAdapter,
+    pub serial_api_actor: SerialApiActor,
+    pub serial_api_adapter: SerialApiAdapter,
+    pub log_rx: LogReceiver,
+}
+
+/// Creates a driver without network keys, which is not connected to a controller
+pub(crate) fn mock_driver() -> MockDriver {
+    let (log_tx, log_rx) = zwave_pal::channel::channel(16);
+    let (serial_api, serial_api_actor, serial_api_adapter) = SerialApi::new(log_tx.clone());
+    let (driver, actor, adapter) = Driver::new(&serial_api, log_tx, SecurityKeys::default());
+    MockDriver {
+        driver,
+        actor,
+        adapter,
+        serial_api_actor,
+        serial_api_adapter,
+        log_rx,
+    }
+}
+
+/// Runs the given test with a driver that is connected to the mock controller
+pub(crate) fn run_with_mock_controller<F, Fut>(controller: &MockController, test: F) -> Fut::Output
+where
+    F: FnOnce(Driver) -> Fut,
+    Fut: Future,
+{
+    let MockDriver {
+        driver,
+        actor: mut driver_actor,
+        adapter: mut driver_adapter,
+        mut serial_api_actor,
+        serial_api_adapter,
+        log_rx,
+    } = mock_driver();
+
+    let mut pool = LocalPool::new();
+    let spawner = pool.spawner();
+    spawner
+        .spawn_local(async move {
+            let _ = serial_api_actor.run().await;
+        })
+        .expect("failed to spawn the serial API actor");
+    spawner
+        .spawn_local(async move {
+            let _ = driver_actor.run().await;
+        })
+        .expect("failed to spawn the driver actor");
+
+    pool.run_until(async {
+        let output = zwave_pal::select_biased! {
+            _ = pump(controller, log_rx, &mut driver_adapter, serial_api_adapter) => {
+                panic!("the mock controller stopped unexpectedly")
+            },
+            output = test(driver) => output,
+        };
+        // Also record the events that were emitted right before the test finished
+        while let Some(Some(event)) = driver_adapter.event_rx.recv().now_or_never() {
+            controller.events.update(|events| events.push(event));
+        }
+        output
+    })
+}
+
+/// Moves data between the mock controller, the serial API and the driver
+async fn pump(
+    controller: &MockController,
+    mut log_rx: LogReceiver,
+    driver_adapter: &mut DriverAdapter,
+    mut serial_api_adapter: SerialApiAdapter,
+) {
+    loop {
+        zwave_pal::select_biased! {
+            frame = serial_api_adapter.serial_out.recv() => {
+                let Some(frame) = frame else {
+                    break;
+                };
+                for frame in controller.handle_frame(frame) {
+                    if serial_api_adapter.serial_in.try_send(frame).is_err() {
+                        return;
+                    }
+                }
+            },
+            event = serial_api_adapter.event_rx.recv() => {
+                match event {
+                    Some(SerialApiEvent::Unsolicited { command }) => {
+                        let _ = driver_adapter
+                            .input_tx
+                            .try_send(DriverInput::Unsolicited { command });
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            },
+            event = driver_adapter.event_rx.recv() => {
+                match event {
+                    Some(event) => controller.events.update(|events| events.push(event)),
+                    None => break,
+                }
+            },
+            log = log_rx.recv() => {
+                // Logs are discarded
+                if log.is_none() {
+                    break;
+                }
+            },
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use zwave_pal::prelude::*;
+use core::time::Duration;
 use zwave_core::state_machine;
 use zwave_core::state_machine::StateMachine;
+use zwave_pal::time::Instant;
 use zwave_serial::prelude::*;
 
 #[allow(clippy::upper_case_acronyms)]
@@ -16,6 +18,53 @@ pub enum SerialApiMachineResult {
     CallbackNOK(Command),
 }
 
+/// The result of a Serial API command, including metadata about its execution
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerialApiCommandResult {
+    pub result: SerialApiMachineResult,
+    pub metadata: SerialApiCommandMetadata,
+}
+
+/// Timing and transmission information about the execution of a Serial API command
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SerialApiCommandMetadata {
+    /// When the command was last sent to the Z-Wave module
+    pub sent_at: Option<Instant>,
+    /// When the command was acknowledged by the Z-Wave module
+    pub ack_at: Option<Instant>,
+    /// When the response to the command was received
+    pub response_at: Option<Instant>,
+    /// When the callback for the command was received
+    pub callback_at: Option<Instant>,
+    /// How often the command was sent to the Z-Wave module
+    pub attempts: u8,
+    /// How many CAN frames were received while waiting for an ACK
+    pub can_count: u8,
+    /// How many NAK frames were received while waiting for an ACK
+    pub nak_count: u8,
+}
+
+impl SerialApiCommandMetadata {
+    /// Returns the time between sending the command and receiving the ACK
+    pub fn ack_duration(&self) -> Option<Duration> {
+        Self::since_sent(self.sent_at, self.ack_at)
+    }
+
+    /// Returns the time between sending the command and receiving the response
+    pub fn response_duration(&self) -> Option<Duration> {
+        Self::since_sent(self.sent_at, self.response_at)
+    }
+
+    /// Returns the time between sending the command and receiving the callback
+    pub fn callback_duration(&self) -> Option<Duration> {
+        Self::since_sent(self.sent_at, self.callback_at)
+    }
+
+    fn since_sent(sent_at: Option<Instant>, at: Option<Instant>) -> Option<Duration> {
+        at?.checked_duration_since(sent_at?)
+    }
+}
+
 state_machine! { SerialApiMachine {
     State = {
         Initial,
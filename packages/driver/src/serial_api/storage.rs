@@ -1,32 +1,131 @@
+use super::{SerialApiStatistics, UnknownCommandType, UnknownCommands};
+use crate::error::FatalError;
+use bytes::Bytes;
+#[cfg(feature = "diagnostics")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "diagnostics")]
+use zwave_core::log::Loglevel;
 use zwave_core::prelude::*;
+use zwave_core::wrapping_counter::WrappingCounter;
+#[cfg(feature = "diagnostics")]
+use zwave_logging::LogInfo;
+use zwave_pal::channel::Sender;
 use zwave_pal::sync::Locked;
+use zwave_serial::command::CommandContext;
+
+/// How many log entries are kept for diagnostic dumps
+#[cfg(feature = "diagnostics")]
+const RECENT_LOGS_CAPACITY: usize = 200;
 
 /// Storage shared between the Serial API and driver actors, containing information
 /// that is needed to correctly parse and serialize commands.
 pub(crate) struct SerialApiStorage {
-    own_node_id: Locked<NodeId>,
-    node_id_type: Locked<NodeIdType>,
-    sdk_version: Locked<Option<Version>>,
+    command_context: Locked<CommandContext>,
+    statistics: Locked<SerialApiStatistics>,
+    callback_id: Locked<WrappingCounter<u8>>,
+    unknown_commands: Locked<UnknownCommands>,
+    /// Distinguishes this controller from others that are used by the same process
+    label: Locked<Option<String>>,
+    /// Where the Serial API actor reports errors it cannot recover from
+    supervisor: Locked<Option<Sender<FatalError>>>,
+    /// The most recent log entries of the Serial API and the driver
+    #[cfg(feature = "diagnostics")]
+    recent_logs: Locked<VecDeque<(LogInfo, Loglevel)>>,
 }
 
 impl SerialApiStorage {
     pub fn new(node_id_type: NodeIdType) -> Self {
         Self {
-            own_node_id: Locked::new(NodeId::unspecified()),
-            node_id_type: Locked::new(node_id_type),
-            sdk_version: Locked::new(None),
+            command_context: Locked::new(
+                CommandContext::builder()
+                    .own_node_id(NodeId::unspecified())
+                    .node_id_type(node_id_type)
+                    .build(),
+            ),
+            statistics: Locked::new(SerialApiStatistics::default()),
+            callback_id: Locked::new(WrappingCounter::new()),
+            unknown_commands: Locked::new(UnknownCommands::default()),
+            label: Locked::new(None),
+            supervisor: Locked::new(None),
+            #[cfg(feature = "diagnostics")]
+            recent_logs: Locked::new(VecDeque::with_capacity(RECENT_LOGS_CAPACITY)),
         }
     }
 
-    pub(crate) fn own_node_id(&self) -> &Locked<NodeId> {
-        &self.own_node_id
+    /// Returns a copy of the context for parsing and serializing commands
+    pub(crate) fn command_context(&self) -> CommandContext {
+        self.command_context.get()
+    }
+
+    pub(crate) fn own_node_id(&self) -> NodeId {
+        self.command_context.inspect(|ctx| ctx.own_node_id)
+    }
+
+    pub(crate) fn set_own_node_id(&self, own_node_id: NodeId) {
+        self.command_context
+            .update(|ctx| ctx.own_node_id = own_node_id);
+    }
+
+    pub(crate) fn node_id_type(&self) -> NodeIdType {
+        self.command_context.inspect(|ctx| ctx.node_id_type)
+    }
+
+    pub(crate) fn set_node_id_type(&self, node_id_type: NodeIdType) {
+        self.command_context
+            .update(|ctx| ctx.node_id_type = node_id_type);
+    }
+
+    pub(crate) fn set_sdk_version(&self, sdk_version: Option<Version>) {
+        self.command_context
+            .update(|ctx| ctx.sdk_version = sdk_version);
+    }
+
+    pub(crate) fn statistics(&self) -> &Locked<SerialApiStatistics> {
+        &self.statistics
+    }
+
+    pub(crate) fn callback_id(&self) -> &Locked<WrappingCounter<u8>> {
+        &self.callback_id
+    }
+
+    pub(crate) fn unknown_commands(&self) -> &Locked<UnknownCommands> {
+        &self.unknown_commands
+    }
+
+    pub(crate) fn label(&self) -> &Locked<Option<String>> {
+        &self.label
+    }
+
+    pub(crate) fn supervisor(&self) -> &Locked<Option<Sender<FatalError>>> {
+        &self.supervisor
+    }
+
+    /// Counts a received command that is not implemented.
+    /// Returns whether a warning should be logged for it.
+    pub(crate) fn record_unknown_command(
+        &self,
+        command: UnknownCommandType,
+        payload: &Bytes,
+    ) -> bool {
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_unknown_command(self.label.cloned().as_deref(), &command);
+        self.unknown_commands
+            .update(|unknown| unknown.record(command, payload))
     }
 
-    pub(crate) fn node_id_type(&self) -> &Locked<NodeIdType> {
-        &self.node_id_type
+    #[cfg(feature = "diagnostics")]
+    pub(crate) fn recent_logs(&self) -> &Locked<VecDeque<(LogInfo, Loglevel)>> {
+        &self.recent_logs
     }
 
-    pub(crate) fn sdk_version(&self) -> &Locked<Option<Version>> {
-        &self.sdk_version
+    /// Remembers a log entry, so it can be included in diagnostic dumps
+    #[cfg(feature = "diagnostics")]
+    pub(crate) fn record_log(&self, log: &LogInfo, level: Loglevel) {
+        self.recent_logs.update(|logs| {
+            if logs.len() == RECENT_LOGS_CAPACITY {
+                logs.pop_front();
+            }
+            logs.push_back((log.clone(), level));
+        });
     }
 }
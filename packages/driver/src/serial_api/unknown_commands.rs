@@ -0,0 +1,164 @@
+use bytes::Bytes;
+use core::fmt::Display;
+use core::time::Duration;
+use zwave_core::prelude::*;
+use zwave_pal::prelude::*;
+use zwave_pal::time::Instant;
+
+/// How often a warning is logged for each type of unknown command
+const UNKNOWN_COMMAND_WARNING_INTERVAL: Duration = Duration::from_secs(60);
+/// How many payloads are kept for each type of unknown command
+const MAX_UNKNOWN_COMMAND_SAMPLES: usize = 5;
+
+/// Identifies a type of command that was received, but is not implemented by the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownCommandType {
+    /// A Serial API command
+    Command {
+        command_type: CommandType,
+        function_type: FunctionType,
+    },
+    /// A command of a command class
+    CC {
+        cc_id: CommandClasses,
+        cc_command: Option<u8>,
+    },
+}
+
+impl Display for UnknownCommandType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Command {
+                command_type,
+                function_type,
+            } => write!(f, "{:?} {:?}", function_type, command_type),
+            Self::CC { cc_id, cc_command } => {
+                write!(f, "{} CC", cc_id)?;
+                if let Some(cc_command) = cc_command {
+                    write!(f, ", command 0x{:02x}", cc_command)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// How often a type of unknown command was received, including some example payloads
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownCommandStatistics {
+    pub command: UnknownCommandType,
+    pub count: u64,
+    /// The payloads of the first received commands of this type
+    pub samples: Vec<Bytes>,
+}
+
+/// Keeps track of the commands that could not be parsed because they are not implemented,
+/// so the missing implementations can be prioritized
+#[derive(Default)]
+pub(crate) struct UnknownCommands {
+    entries: Vec<(UnknownCommandStatistics, Instant)>,
+}
+
+impl UnknownCommands {
+    /// Records a received unknown command. Returns whether a warning should be logged for it,
+    /// which happens at most once per interval for each type.
+    pub(crate) fn record(&mut self, command: UnknownCommandType, payload: &Bytes) -> bool {
+        let now = Instant::now();
+        let Some((statistics, last_warning)) = self
+            .entries
+            .iter_mut()
+            .find(|(statistics, _)| statistics.command == command)
+        else {
+            self.entries.push((
+                UnknownCommandStatistics {
+                    command,
+                    count: 1,
+                    samples: vec![payload.clone()],
+                },
+                now,
+            ));
+            return true;
+        };
+
+        statistics.count += 1;
+        if statistics.samples.len() < MAX_UNKNOWN_COMMAND_SAMPLES
+            && !statistics.samples.contains(payload)
+        {
+            statistics.samples.push(payload.clone());
+        }
+
+        if now - *last_warning >= UNKNOWN_COMMAND_WARNING_INTERVAL {
+            *last_warning = now;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the statistics of all unknown commands, most frequent first
+    pub(crate) fn statistics(&self) -> Vec<UnknownCommandStatistics> {
+        let mut ret: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(statistics, _)| statistics.clone())
+            .collect();
+        ret.sort_by_key(|statistics| core::cmp::Reverse(statistics.count));
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_warnings_are_rate_limited() {
+        let mut unknown = UnknownCommands::default();
+        let basic = UnknownCommandType::CC {
+            cc_id: CommandClasses::Basic,
+            cc_command: Some(0x05),
+        };
+        let clock = UnknownCommandType::CC {
+            cc_id: CommandClasses::Clock,
+            cc_command: None,
+        };
+
+        assert!(unknown.record(basic, &Bytes::from_static(&[0x01])));
+        assert!(!unknown.record(basic, &Bytes::from_static(&[0x02])));
+        assert!(!unknown.record(basic, &Bytes::from_static(&[0x01])));
+        // Other types are warned about separately
+        assert!(unknown.record(clock, &Bytes::new()));
+
+        assert_eq!(
+            unknown.statistics(),
+            vec![
+                UnknownCommandStatistics {
+                    command: basic,
+                    count: 3,
+                    samples: vec![Bytes::from_static(&[0x01]), Bytes::from_static(&[0x02])],
+                },
+                UnknownCommandStatistics {
+                    command: clock,
+                    count: 1,
+                    samples: vec![Bytes::new()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_samples_are_limited() {
+        let mut unknown = UnknownCommands::default();
+        let command = UnknownCommandType::Command {
+            command_type: CommandType::Request,
+            function_type: FunctionType::ApplicationUpdateRequest,
+        };
+        for i in 0..10u8 {
+            unknown.record(command, &Bytes::copy_from_slice(&[i]));
+        }
+
+        let statistics = unknown.statistics();
+        assert_eq!(statistics[0].count, 10);
+        assert_eq!(statistics[0].samples.len(), MAX_UNKNOWN_COMMAND_SAMPLES);
+    }
+}
@@ -0,0 +1,57 @@
+#ifndef ZWAVE_FFI_H
+#define ZWAVE_FFI_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum ZWaveStatus {
+    ZWAVE_STATUS_OK = 0,
+    ZWAVE_STATUS_INVALID_ARGUMENT = 1,
+    ZWAVE_STATUS_NOT_READY = 2,
+    ZWAVE_STATUS_NOT_SUPPORTED = 3,
+    ZWAVE_STATUS_FAILED = 4,
+    ZWAVE_STATUS_DISCONNECTED = 5,
+} ZWaveStatus;
+
+typedef struct ZWaveDriver ZWaveDriver;
+
+/* All callbacks are invoked from the driver thread. */
+typedef struct ZWaveCallbacks {
+    void *user_data;
+    /* Required: write the given data to the serial port */
+    void (*on_serial_write)(void *user_data, const uint8_t *data, size_t len);
+    /* Optional: receives JSON-serialized events. If NULL, events are queued for zwave_driver_poll_event */
+    void (*on_event)(void *user_data, const char *event);
+} ZWaveCallbacks;
+
+/* options_json may be NULL, e.g. {"logLevel":"debug","securityKeys":{"S0_Legacy":"<hex>"}} */
+ZWaveDriver *zwave_driver_create(const char *options_json, ZWaveCallbacks callbacks);
+/* Waits for the driver thread to finish. When called from a callback, it cannot wait for its own
+ * thread, which ends after the callback returns instead. Until then, callbacks may still be invoked. */
+void zwave_driver_destroy(ZWaveDriver *driver);
+
+ZWaveStatus zwave_driver_start(ZWaveDriver *driver);
+ZWaveStatus zwave_driver_push_serial_data(ZWaveDriver *driver, const uint8_t *data, size_t len);
+/* Returns NULL if no event is queued. Free the result with zwave_string_free */
+char *zwave_driver_poll_event(ZWaveDriver *driver);
+
+/* value_id_json: {"commandClass":37,"endpoint":0,"property":1,"propertyKey":null} */
+ZWaveStatus zwave_node_get_value(ZWaveDriver *driver, uint16_t node_id, const char *value_id_json, char **value_json);
+ZWaveStatus zwave_node_set_value(ZWaveDriver *driver, uint16_t node_id, const char *value_id_json, const char *value_json);
+
+/* Run in the background. The outcome is reported by the "inclusion finished"/"inclusion failed"
+ * and "exclusion finished"/"exclusion failed" events */
+ZWaveStatus zwave_controller_begin_inclusion(ZWaveDriver *driver);
+ZWaveStatus zwave_controller_begin_exclusion(ZWaveDriver *driver);
+
+void zwave_string_free(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ZWAVE_FFI_H */
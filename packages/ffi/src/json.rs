@@ -0,0 +1,426 @@
+use serde_json::{Map, Value, json};
+use zwave_core::cache::CacheValue;
+use zwave_core::log::Loglevel;
+use zwave_core::prelude::*;
+use zwave_core::security::NetworkKey;
+use zwave_core::value_id::{EndpointValueId, ValueId};
+use zwave_driver::error::FatalError;
+use zwave_driver::{DriverEvent, SecurityKeys, ValueUpdateKind};
+
+/// Options for creating a driver instance, as passed to `zwave_driver_create`.
+///
+/// ```json
+/// {
+///     "logLevel": "debug",
+///     "securityKeys": { "S0_Legacy": "0102030405060708090a0b0c0d0e0f10" }
+/// }
+/// ```
+pub struct FfiOptions {
+    pub log_level: Loglevel,
+    pub security_keys: SecurityKeys,
+}
+
+pub fn parse_options(json: Option<&str>) -> Result<FfiOptions, String> {
+    let value: Value = match json {
+        Some(json) => serde_json::from_str(json).map_err(|e| e.to_string())?,
+        None => Value::Null,
+    };
+
+    let log_level = match value.get("logLevel").and_then(Value::as_str) {
+        None => Loglevel::Info,
+        Some(level) => parse_loglevel(level)?,
+    };
+
+    let mut security_keys = SecurityKeys::default();
+    if let Some(keys) = value.get("securityKeys").and_then(Value::as_object) {
+        for (name, key) in keys {
+            let key = parse_network_key(key).map_err(|e| format!("{name}: {e}"))?;
+            match name.as_str() {
+                "S0_Legacy" => security_keys.s0_legacy = Some(key),
+                "S2_Unauthenticated" => security_keys.s2_unauthenticated = Some(key),
+                "S2_Authenticated" => security_keys.s2_authenticated = Some(key),
+                "S2_AccessControl" => security_keys.s2_access_control = Some(key),
+                _ => return Err(format!("unknown security class {name}")),
+            }
+        }
+    }
+
+    Ok(FfiOptions {
+        log_level,
+        security_keys,
+    })
+}
+
+fn parse_loglevel(level: &str) -> Result<Loglevel, String> {
+    match level {
+        "error" => Ok(Loglevel::Error),
+        "warn" => Ok(Loglevel::Warn),
+        "info" => Ok(Loglevel::Info),
+        "verbose" => Ok(Loglevel::Verbose),
+        "debug" => Ok(Loglevel::Debug),
+        "silly" => Ok(Loglevel::Silly),
+        _ => Err(format!("unknown log level {level}")),
+    }
+}
+
+fn parse_network_key(key: &Value) -> Result<NetworkKey, String> {
+    let key = key.as_str().ok_or("network keys must be hex strings")?;
+    let key = hex::decode(key).map_err(|e| e.to_string())?;
+    NetworkKey::try_from(key).map_err(|e| e.to_string())
+}
+
+/// Parses a value ID of the form `{ "commandClass": 37, "endpoint": 0, "property": 1, "propertyKey": null }`.
+/// Returns the endpoint index and the value ID.
+pub fn parse_value_id(json: &str) -> Result<(u8, ValueId), String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+    let cc = value
+        .get("commandClass")
+        .and_then(Value::as_u64)
+        .ok_or("missing commandClass")?;
+    let cc = u16::try_from(cc)
+        .ok()
+        .and_then(|cc| CommandClasses::try_from(cc).ok())
+        .ok_or_else(|| format!("unknown command class {cc}"))?;
+    let endpoint = match value.get("endpoint").and_then(Value::as_u64) {
+        // Z-Wave nodes have at most 127 endpoints
+        Some(endpoint @ 0..=127) => endpoint as u8,
+        Some(endpoint) => return Err(format!("invalid endpoint {endpoint}")),
+        None => 0,
+    };
+    let property = value
+        .get("property")
+        .and_then(Value::as_u64)
+        .and_then(|p| u16::try_from(p).ok())
+        .ok_or("missing or invalid property")?;
+    let property_key = match value.get("propertyKey") {
+        None | Some(Value::Null) => None,
+        Some(key) => Some(
+            key.as_u64()
+                .and_then(|k| u32::try_from(k).ok())
+                .ok_or("invalid propertyKey")?,
+        ),
+    };
+
+    Ok((endpoint, ValueId::new(cc, property, property_key)))
+}
+
+pub fn cache_value_to_json(value: &CacheValue) -> Value {
+    match value {
+        CacheValue::Bool(v) => json!(v),
+        CacheValue::UInt8(v) => json!(v),
+        CacheValue::UInt16(v) => json!(v),
+        CacheValue::UInt32(v) => json!(v),
+        CacheValue::Int8(v) => json!(v),
+        CacheValue::Int16(v) => json!(v),
+        CacheValue::Int32(v) => json!(v),
+        CacheValue::Float(v) => json!(v),
+        CacheValue::String(v) => json!(v),
+        CacheValue::Buffer(v) => json!(hex::encode(v)),
+        CacheValue::DurationSet(v) => json!(v.to_string()),
+        CacheValue::DurationReport(v) => json!(v.to_string()),
+        CacheValue::LevelSet(v) => json!(v.to_string()),
+        CacheValue::LevelReport(LevelReport::Level(level)) => json!(level),
+        CacheValue::LevelReport(LevelReport::Unknown) => Value::Null,
+        CacheValue::BinarySet(v) => json!(bool::from(*v)),
+        CacheValue::BinaryReport(v) => match Option::<bool>::from(*v) {
+            Some(v) => json!(v),
+            None => Value::Null,
+        },
+    }
+}
+
+/// Builds the JSON representation of an event that is passed to the application
+pub fn event(name: &str, args: Map<String, Value>) -> String {
+    let mut ret = Map::new();
+    ret.insert("event".into(), json!(name));
+    ret.extend(args);
+    Value::Object(ret).to_string()
+}
+
+/// Formats a value ID like it is passed to `zwave_node_get_value`, including the node ID
+fn endpoint_value_id_to_json(value_id: &EndpointValueId) -> Value {
+    let endpoint = match value_id.endpoint() {
+        EndpointIndex::Root => 0,
+        EndpointIndex::Endpoint(index) => index,
+    };
+    json!({
+        "nodeId": u16::from(value_id.node_id()),
+        "commandClass": value_id.command_class() as u16,
+        "endpoint": endpoint,
+        "property": value_id.property(),
+        "propertyKey": value_id.property_key(),
+    })
+}
+
+fn node_ids_to_json(node_ids: impl IntoIterator<Item = NodeId>) -> Value {
+    json!(node_ids.into_iter().map(u16::from).collect::<Vec<_>>())
+}
+
+/// Returns the name and the arguments of the event that is passed to the application
+/// for the given driver event
+pub fn driver_event(event: &DriverEvent) -> (&'static str, Map<String, Value>) {
+    let (name, args) = match event {
+        DriverEvent::ValueUpdated {
+            value_id,
+            value,
+            kind,
+        } => {
+            let kind = match kind {
+                ValueUpdateKind::Reported => "reported",
+                ValueUpdateKind::Optimistic => "optimistic",
+                ValueUpdateKind::Snapshot => "snapshot",
+            };
+            (
+                "value updated",
+                json!({
+                    "valueId": endpoint_value_id_to_json(value_id),
+                    "value": cache_value_to_json(value),
+                    "kind": kind,
+                }),
+            )
+        }
+        DriverEvent::ValuePending { value_id, value } => (
+            "value pending",
+            json!({
+                "valueId": endpoint_value_id_to_json(value_id),
+                "value": cache_value_to_json(value),
+            }),
+        ),
+        DriverEvent::ValueAdded { value_id, metadata } => (
+            "value added",
+            json!({
+                "valueId": endpoint_value_id_to_json(value_id),
+                "stateful": metadata.is_stateful(),
+            }),
+        ),
+        DriverEvent::ValueNotification { value_id, value } => (
+            "value notification",
+            json!({
+                "valueId": endpoint_value_id_to_json(value_id),
+                "value": cache_value_to_json(value),
+            }),
+        ),
+        DriverEvent::NodeUserMetadataChanged { node_id, metadata } => (
+            "node user metadata changed",
+            json!({
+                "nodeId": u16::from(*node_id),
+                "name": metadata.name,
+                "location": metadata.location,
+                "room": metadata.room,
+                "notes": metadata.notes,
+            }),
+        ),
+        DriverEvent::JoinedNetwork {
+            home_id,
+            own_node_id,
+        } => (
+            "joined network",
+            json!({
+                "homeId": home_id.to_string(),
+                "ownNodeId": u16::from(*own_node_id),
+            }),
+        ),
+        DriverEvent::LeftNetwork { home_id } => {
+            ("left network", json!({ "homeId": home_id.to_string() }))
+        }
+        DriverEvent::NodeAdded { node_id } => {
+            ("node added", json!({ "nodeId": u16::from(*node_id) }))
+        }
+        DriverEvent::NodeReady { node_id } => {
+            ("node ready", json!({ "nodeId": u16::from(*node_id) }))
+        }
+        DriverEvent::NetworkReady { deferred_nodes } => (
+            "network ready",
+            json!({ "deferredNodes": node_ids_to_json(deferred_nodes.iter().copied()) }),
+        ),
+        DriverEvent::NodeInfoChanged { node_id, node_info } => {
+            let ccs = |ccs: &[CommandClasses]| ccs.iter().map(|cc| *cc as u16).collect::<Vec<_>>();
+            (
+                "node info changed",
+                json!({
+                    "nodeId": u16::from(*node_id),
+                    "genericDeviceClass": node_info.generic_device_class,
+                    "specificDeviceClass": node_info.specific_device_class,
+                    "supportedCCs": ccs(&node_info.supported_command_classes),
+                    "controlledCCs": ccs(&node_info.controlled_command_classes),
+                }),
+            )
+        }
+        DriverEvent::MarginalLinkQuality {
+            node_id,
+            link_quality,
+        } => (
+            "marginal link quality",
+            json!({
+                "nodeId": u16::from(*node_id),
+                "score": link_quality.score,
+                "pings": link_quality.pings,
+                "acknowledgedPings": link_quality.acknowledged_pings,
+                "testFrames": link_quality.test_frames,
+                "acknowledgedTestFrames": link_quality.acknowledged_test_frames,
+            }),
+        ),
+        DriverEvent::NodeStatusChanged { node_id, status } => (
+            "node status changed",
+            json!({
+                "nodeId": u16::from(*node_id),
+                "status": format!("{:?}", status).to_lowercase(),
+            }),
+        ),
+        DriverEvent::CorruptedCommand { node_id, data } => (
+            "corrupted command",
+            json!({
+                "nodeId": u16::from(*node_id),
+                "data": hex::encode(data),
+            }),
+        ),
+        DriverEvent::RouteRepairStarted { node_id, failures } => (
+            "route repair started",
+            json!({
+                "nodeId": u16::from(*node_id),
+                "failures": failures,
+            }),
+        ),
+        DriverEvent::RouteRepairFinished { node_id, result } => (
+            "route repair finished",
+            json!({
+                "nodeId": u16::from(*node_id),
+                "neighborsUpdated": result.neighbors_updated,
+                "returnRouteAssigned": result.return_route_assigned,
+            }),
+        ),
+        DriverEvent::NetworkSwept { report } => (
+            "network swept",
+            json!({
+                "reachableNodes": node_ids_to_json(
+                    report
+                        .nodes
+                        .iter()
+                        .filter(|(_, node)| node.reachable)
+                        .map(|(node_id, _)| *node_id)
+                ),
+                "unreachableNodes": node_ids_to_json(report.unreachable_nodes()),
+            }),
+        ),
+        DriverEvent::ScheduledCommandSent { id } => {
+            ("scheduled command sent", json!({ "id": u32::from(*id) }))
+        }
+        DriverEvent::ScheduledCommandFailed { id, error } => (
+            "scheduled command failed",
+            json!({
+                "id": u32::from(*id),
+                "error": error.to_string(),
+            }),
+        ),
+        DriverEvent::ReportFloodStarted { node_id } => (
+            "report flood started",
+            json!({ "nodeId": u16::from(*node_id) }),
+        ),
+        DriverEvent::ReportFloodEnded { node_id, discarded } => (
+            "report flood ended",
+            json!({
+                "nodeId": u16::from(*node_id),
+                "discarded": discarded,
+            }),
+        ),
+        DriverEvent::FatalError { error } => return ("error", fatal_error(error)),
+    };
+    (name, into_args(args))
+}
+
+/// Returns the arguments of the `error` event for an error that stopped the driver
+pub fn fatal_error(error: &FatalError) -> Map<String, Value> {
+    into_args(json!({
+        "error": error.to_string(),
+        "fatal": true,
+    }))
+}
+
+fn into_args(args: Value) -> Map<String, Value> {
+    match args {
+        Value::Object(args) => args,
+        _ => unreachable!("event arguments are always objects"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_value_id() {
+        let (endpoint, value_id) =
+            parse_value_id(r#"{ "commandClass": 32, "property": 1 }"#).unwrap();
+        assert_eq!(endpoint, 0);
+        assert_eq!(value_id, ValueId::new(CommandClasses::Basic, 1u16, None));
+
+        let (endpoint, value_id) = parse_value_id(
+            r#"{ "commandClass": 114, "endpoint": 2, "property": 3, "propertyKey": 1 }"#,
+        )
+        .unwrap();
+        assert_eq!(endpoint, 2);
+        assert_eq!(
+            value_id,
+            ValueId::new(CommandClasses::ManufacturerSpecific, 3u16, Some(1))
+        );
+
+        assert!(parse_value_id(r#"{ "property": 1 }"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options = parse_options(Some(
+            r#"{ "logLevel": "debug", "securityKeys": { "S0_Legacy": "0102030405060708090a0b0c0d0e0f10" } }"#,
+        ))
+        .unwrap();
+        assert_eq!(options.log_level, Loglevel::Debug);
+        assert!(options.security_keys.s0_legacy.is_some());
+        assert!(options.security_keys.s2_access_control.is_none());
+
+        assert!(parse_options(Some(r#"{ "securityKeys": { "S0_Legacy": "0102" } }"#)).is_err());
+        assert!(parse_options(None).is_ok());
+    }
+
+    #[test]
+    fn test_driver_event() {
+        let value_id = EndpointValueId::new(
+            NodeId::new(5u8),
+            EndpointIndex::Endpoint(2),
+            ValueId::new(CommandClasses::BinarySwitch, 1u16, None),
+        );
+        let (name, args) = driver_event(&DriverEvent::ValueUpdated {
+            value_id,
+            value: CacheValue::Bool(true),
+            kind: ValueUpdateKind::Reported,
+        });
+        assert_eq!(name, "value updated");
+        assert_eq!(
+            Value::Object(args),
+            json!({
+                "valueId": {
+                    "nodeId": 5,
+                    "commandClass": 0x25,
+                    "endpoint": 2,
+                    "property": 1,
+                    "propertyKey": null,
+                },
+                "value": true,
+                "kind": "reported",
+            })
+        );
+
+        let (name, args) = driver_event(&DriverEvent::FatalError {
+            error: FatalError {
+                actor: zwave_driver::error::ActorKind::SerialApi,
+                reason: "the serial port was closed".to_string(),
+            },
+        });
+        assert_eq!(name, "error");
+        assert_eq!(args["fatal"], json!(true));
+        assert_eq!(
+            args["error"],
+            json!("The Serial API actor stopped: the serial port was closed")
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! A C ABI for embedding the Z-Wave driver in non-Rust applications.
+//!
+//! The driver runs on its own thread. The application is responsible for the serial port:
+//! Data read from the port is passed to [`zwave_driver_push_serial_data`], data that needs to be
+//! written is passed to the `on_serial_write` callback.
+//!
+//! Complex types are exchanged as JSON-serialized, NUL-terminated UTF-8 strings.
+//! Strings returned by this library must be freed with [`zwave_string_free`].
+//!
+//! See `include/zwave_ffi.h` for the corresponding C declarations.
+
+use core::ffi::{c_char, c_void};
+use runtime::{FfiRequest, FfiRuntime};
+use std::ffi::{CStr, CString};
+use zwave_core::prelude::*;
+
+mod json;
+mod runtime;
+
+/// Status codes returned by the C API
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZWaveStatus {
+    Ok = 0,
+    /// One of the arguments was a null pointer, not valid UTF-8 or not valid JSON
+    InvalidArgument = 1,
+    /// The driver has not been started or is not ready yet
+    NotReady = 2,
+    /// The requested operation is not supported (yet)
+    NotSupported = 3,
+    /// The operation was attempted, but failed
+    Failed = 4,
+    /// The driver thread is no longer running
+    Disconnected = 5,
+}
+
+/// Callbacks the application can register when creating the driver.
+/// All callbacks are invoked from the driver thread.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ZWaveCallbacks {
+    /// Opaque pointer that is passed to each callback
+    pub user_data: *mut c_void,
+    /// Called with data that must be written to the serial port. Required.
+    pub on_serial_write: Option<extern "C" fn(user_data: *mut c_void, data: *const u8, len: usize)>,
+    /// Called with each JSON-serialized event. The string is only valid during the call.
+    /// If this is not set, events are queued and can be retrieved using [`zwave_driver_poll_event`].
+    pub on_event: Option<extern "C" fn(user_data: *mut c_void, event: *const c_char)>,
+}
+
+/// An opaque handle to a driver instance
+pub struct ZWaveDriver {
+    runtime: FfiRuntime,
+}
+
+unsafe fn str_from_ptr<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().ok()
+}
+
+fn string_into_ptr(value: String) -> *mut c_char {
+    CString::new(value)
+        .map(CString::into_raw)
+        .unwrap_or(core::ptr::null_mut())
+}
+
+/// Creates a new driver instance and starts its thread. Returns null on failure.
+///
+/// # Safety
+/// `options_json` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_driver_create(
+    options_json: *const c_char,
+    callbacks: ZWaveCallbacks,
+) -> *mut ZWaveDriver {
+    if callbacks.on_serial_write.is_none() {
+        return core::ptr::null_mut();
+    }
+    let options_json = if options_json.is_null() {
+        None
+    } else {
+        match unsafe { str_from_ptr(options_json) } {
+            Some(json) => Some(json),
+            None => return core::ptr::null_mut(),
+        }
+    };
+    let Ok(options) = json::parse_options(options_json) else {
+        return core::ptr::null_mut();
+    };
+
+    match FfiRuntime::spawn(options, callbacks) {
+        Ok(runtime) => Box::into_raw(Box::new(ZWaveDriver { runtime })),
+        Err(_) => core::ptr::null_mut(),
+    }
+}
+
+/// Stops the driver and frees all associated resources.
+///
+/// Waits for the driver thread to finish, unless it is called from a callback on that thread.
+/// In that case, the thread ends after the callback returns, and the callbacks may still be
+/// invoked until then.
+///
+/// # Safety
+/// `driver` must be null or a pointer returned by [`zwave_driver_create`] that was not destroyed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_driver_destroy(driver: *mut ZWaveDriver) {
+    if !driver.is_null() {
+        drop(unsafe { Box::from_raw(driver) });
+    }
+}
+
+/// Starts the driver by interviewing the controller.
+/// Emits a `driver ready` event on success or an `error` event on failure.
+///
+/// # Safety
+/// `driver` must be a valid driver handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_driver_start(driver: *mut ZWaveDriver) -> ZWaveStatus {
+    let Some(driver) = (unsafe { driver.as_ref() }) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    driver.runtime.request(FfiRequest::Start)
+}
+
+/// Passes data that was read from the serial port to the driver.
+///
+/// # Safety
+/// `driver` must be a valid driver handle and `data` must point to at least `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_driver_push_serial_data(
+    driver: *mut ZWaveDriver,
+    data: *const u8,
+    len: usize,
+) -> ZWaveStatus {
+    let Some(driver) = (unsafe { driver.as_ref() }) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    if data.is_null() {
+        return ZWaveStatus::InvalidArgument;
+    }
+    let data = unsafe { core::slice::from_raw_parts(data, len) };
+    driver.runtime.push_serial_data(data)
+}
+
+/// Returns the next queued event as a JSON string, or null if there is none.
+/// Events are only queued if no `on_event` callback was registered.
+///
+/// # Safety
+/// `driver` must be a valid driver handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_driver_poll_event(driver: *mut ZWaveDriver) -> *mut c_char {
+    let Some(driver) = (unsafe { driver.as_ref() }) else {
+        return core::ptr::null_mut();
+    };
+    match driver.runtime.poll_event() {
+        Some(event) => string_into_ptr(event),
+        None => core::ptr::null_mut(),
+    }
+}
+
+/// Reads a value from the value cache. On success, `*value_json` is set to the JSON-serialized
+/// value, or null if the value is not known.
+///
+/// # Safety
+/// `driver` must be a valid driver handle, `value_id_json` must point to a NUL-terminated string
+/// and `value_json` must be a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_node_get_value(
+    driver: *mut ZWaveDriver,
+    node_id: u16,
+    value_id_json: *const c_char,
+    value_json: *mut *mut c_char,
+) -> ZWaveStatus {
+    let Some(driver) = (unsafe { driver.as_ref() }) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    let Some(Ok((endpoint, value_id))) =
+        (unsafe { str_from_ptr(value_id_json) }).map(json::parse_value_id)
+    else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    if value_json.is_null() {
+        return ZWaveStatus::InvalidArgument;
+    }
+
+    let (reply, result) = async_channel::bounded(1);
+    let status = driver.runtime.request(FfiRequest::GetValue {
+        node_id: NodeId::new(node_id),
+        endpoint,
+        value_id,
+        reply,
+    });
+    if status != ZWaveStatus::Ok {
+        return status;
+    }
+
+    match result.recv_blocking() {
+        Ok(Ok(value)) => {
+            let value = value.map_or(core::ptr::null_mut(), |v| string_into_ptr(v.to_string()));
+            unsafe { *value_json = value };
+            ZWaveStatus::Ok
+        }
+        Ok(Err(status)) => status,
+        Err(_) => ZWaveStatus::Disconnected,
+    }
+}
+
+/// Sets a value on a node and blocks until the command was sent.
+///
+/// # Safety
+/// `driver` must be a valid driver handle, `value_id_json` and `value_json` must point to
+/// NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_node_set_value(
+    driver: *mut ZWaveDriver,
+    node_id: u16,
+    value_id_json: *const c_char,
+    value_json: *const c_char,
+) -> ZWaveStatus {
+    let Some(driver) = (unsafe { driver.as_ref() }) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    let Some(Ok((endpoint, value_id))) =
+        (unsafe { str_from_ptr(value_id_json) }).map(json::parse_value_id)
+    else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    let Some(Ok(value)) = (unsafe { str_from_ptr(value_json) }).map(serde_json::from_str) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+
+    let (reply, result) = async_channel::bounded(1);
+    let status = driver.runtime.request(FfiRequest::SetValue {
+        node_id: NodeId::new(node_id),
+        endpoint,
+        value_id,
+        value,
+        reply,
+    });
+    if status != ZWaveStatus::Ok {
+        return status;
+    }
+
+    result.recv_blocking().unwrap_or(ZWaveStatus::Disconnected)
+}
+
+/// Starts the inclusion of a new node in the background. Fails if another inclusion or
+/// exclusion is in progress.
+/// Emits an `inclusion finished` event with the ID of the new node, which is `null` if no node
+/// was found in time, or an `inclusion failed` event.
+///
+/// # Safety
+/// `driver` must be a valid driver handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_controller_begin_inclusion(driver: *mut ZWaveDriver) -> ZWaveStatus {
+    let Some(driver) = (unsafe { driver.as_ref() }) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    let (reply, result) = async_channel::bounded(1);
+    let status = driver.runtime.request(FfiRequest::BeginInclusion { reply });
+    if status != ZWaveStatus::Ok {
+        return status;
+    }
+
+    result.recv_blocking().unwrap_or(ZWaveStatus::Disconnected)
+}
+
+/// Starts the exclusion of a node in the background. Fails if another inclusion or exclusion
+/// is in progress.
+/// Emits an `exclusion finished` event with the ID of the removed node, which is `null` if no
+/// node was found in time, or an `exclusion failed` event.
+///
+/// # Safety
+/// `driver` must be a valid driver handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_controller_begin_exclusion(driver: *mut ZWaveDriver) -> ZWaveStatus {
+    let Some(driver) = (unsafe { driver.as_ref() }) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    let (reply, result) = async_channel::bounded(1);
+    let status = driver.runtime.request(FfiRequest::BeginExclusion { reply });
+    if status != ZWaveStatus::Ok {
+        return status;
+    }
+
+    result.recv_blocking().unwrap_or(ZWaveStatus::Disconnected)
+}
+
+/// Frees a string that was returned by this library.
+///
+/// # Safety
+/// `value` must be null or a string returned by this library that was not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn zwave_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::Value;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+    use std::time::{Duration, Instant};
+
+    /// The node information of node 5, sent by the controller
+    const NODE_INFO_FRAME: &str = "010a00498405040410012509";
+    const ACK: u8 = 0x06;
+
+    /// Stands in for the serial port and the application. The driver thread may invoke the
+    /// callbacks until it ends, so instances are leaked.
+    #[derive(Default)]
+    struct Application {
+        written: Mutex<Vec<u8>>,
+        driver: AtomicPtr<ZWaveDriver>,
+        destroyed: AtomicBool,
+    }
+
+    extern "C" fn on_serial_write(user_data: *mut c_void, data: *const u8, len: usize) {
+        let app = unsafe { &*(user_data as *const Application) };
+        let data = unsafe { core::slice::from_raw_parts(data, len) };
+        app.written.lock().unwrap().extend_from_slice(data);
+    }
+
+    extern "C" fn destroy_on_event(user_data: *mut c_void, _event: *const c_char) {
+        let app = unsafe { &*(user_data as *const Application) };
+        let driver = app.driver.swap(core::ptr::null_mut(), Ordering::SeqCst);
+        if !driver.is_null() {
+            unsafe { zwave_driver_destroy(driver) };
+            app.destroyed.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn create(
+        app: &'static Application,
+        on_event: Option<extern "C" fn(*mut c_void, *const c_char)>,
+    ) -> *mut ZWaveDriver {
+        let options = CString::new(r#"{ "logLevel": "silly" }"#).unwrap();
+        let callbacks = ZWaveCallbacks {
+            user_data: app as *const Application as *mut c_void,
+            on_serial_write: Some(on_serial_write),
+            on_event,
+        };
+        let driver = unsafe { zwave_driver_create(options.as_ptr(), callbacks) };
+        assert!(!driver.is_null());
+        driver
+    }
+
+    fn push_serial_data(driver: *mut ZWaveDriver, hex: &str) -> ZWaveStatus {
+        let data = hex::decode(hex).unwrap();
+        unsafe { zwave_driver_push_serial_data(driver, data.as_ptr(), data.len()) }
+    }
+
+    /// Waits up to 5 seconds for the condition to become true
+    fn wait_for(mut condition: impl FnMut() -> bool) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if condition() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn test_driver_lifecycle() {
+        let app: &'static Application = Box::leak(Box::default());
+        let driver = create(app, None);
+
+        assert_eq!(push_serial_data(driver, NODE_INFO_FRAME), ZWaveStatus::Ok);
+        // The driver acknowledges the frame
+        assert!(wait_for(|| app.written.lock().unwrap().contains(&ACK)));
+
+        // Without an event callback, the events are queued
+        let mut events = Vec::new();
+        assert!(wait_for(|| {
+            let event = unsafe { zwave_driver_poll_event(driver) };
+            if !event.is_null() {
+                let json = unsafe { CStr::from_ptr(event) }
+                    .to_str()
+                    .unwrap()
+                    .to_owned();
+                events.push(serde_json::from_str::<Value>(&json).unwrap());
+                unsafe { zwave_string_free(event) };
+            }
+            !events.is_empty()
+        }));
+        assert!(events.iter().all(|event| event["event"].is_string()));
+
+        unsafe { zwave_driver_destroy(driver) };
+    }
+
+    #[test]
+    fn test_destroy_from_callback() {
+        let app: &'static Application = Box::leak(Box::default());
+        let driver = create(app, Some(destroy_on_event));
+        app.driver.store(driver, Ordering::SeqCst);
+
+        // Receiving the frame is logged, which emits an event
+        assert_eq!(push_serial_data(driver, NODE_INFO_FRAME), ZWaveStatus::Ok);
+        assert!(wait_for(|| app.destroyed.load(Ordering::SeqCst)));
+    }
+}
@@ -0,0 +1,506 @@
+use crate::json::{self, FfiOptions};
+use futures::future::{self, FutureExt, LocalBoxFuture};
+use serde_json::{Map, Value, json};
+use smol::LocalExecutor;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use zwave_core::cache::Cache;
+use zwave_core::prelude::*;
+use zwave_core::value_id::ValueId;
+use zwave_driver::error::FatalError;
+use zwave_driver::{
+    Controller, ControllerCommandResult, Driver, DriverAdapter, DriverEvent, DriverInput,
+    EndpointLike, ExclusionOptions, InclusionOptions, InclusionState, LogReceiver, Ready,
+    SerialApiAdapter, SerialApiEvent,
+};
+use zwave_logging::LogFormatter;
+use zwave_logging::formatters::DefaultFormatter;
+use zwave_serial::frame::RawSerialFrame;
+use zwave_serial::serialport::FrameCodec;
+
+use crate::{ZWaveCallbacks, ZWaveStatus};
+
+/// Requests from the C API that need to be handled on the runtime thread
+pub(crate) enum FfiRequest {
+    Start,
+    GetValue {
+        node_id: NodeId,
+        endpoint: u8,
+        value_id: ValueId,
+        reply: async_channel::Sender<Result<Option<Value>, ZWaveStatus>>,
+    },
+    SetValue {
+        node_id: NodeId,
+        endpoint: u8,
+        value_id: ValueId,
+        value: Value,
+        reply: async_channel::Sender<ZWaveStatus>,
+    },
+    BeginInclusion {
+        reply: async_channel::Sender<ZWaveStatus>,
+    },
+    BeginExclusion {
+        reply: async_channel::Sender<ZWaveStatus>,
+    },
+}
+
+/// The callbacks registered by the application. The application guarantees that the
+/// user data pointer may be used from the runtime thread.
+#[derive(Clone, Copy)]
+struct SendCallbacks(ZWaveCallbacks);
+unsafe impl Send for SendCallbacks {}
+
+/// Owns the thread running the driver and the channels to communicate with it
+pub(crate) struct FfiRuntime {
+    codec: Mutex<FrameCodec>,
+    serial_in_tx: async_channel::Sender<RawSerialFrame>,
+    request_tx: async_channel::Sender<FfiRequest>,
+    events: Arc<Mutex<VecDeque<String>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FfiRuntime {
+    pub fn spawn(options: FfiOptions, callbacks: ZWaveCallbacks) -> std::io::Result<Self> {
+        let (serial_in_tx, serial_in_rx) = async_channel::unbounded();
+        let (request_tx, request_rx) = async_channel::unbounded();
+        let events = Arc::new(Mutex::new(VecDeque::new()));
+
+        let thread_events = events.clone();
+        let callbacks = SendCallbacks(callbacks);
+        let thread = thread::Builder::new()
+            .name("zwave-driver".into())
+            .spawn(move || {
+                let callbacks = callbacks;
+                let emitter = EventEmitter {
+                    callbacks: callbacks.0,
+                    events: thread_events,
+                    fatal_error_emitted: Cell::new(false),
+                };
+                let local = LocalExecutor::new();
+                smol::block_on(local.run(run(&local, options, emitter, serial_in_rx, request_rx)));
+            })?;
+
+        Ok(Self {
+            codec: Mutex::new(FrameCodec::new()),
+            serial_in_tx,
+            request_tx,
+            events,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn push_serial_data(&self, data: &[u8]) -> ZWaveStatus {
+        let mut codec = self.codec.lock().expect("codec lock poisoned");
+        codec.push_bytes(data);
+        while let Some(frame) = codec.try_decode() {
+            if self.serial_in_tx.try_send(frame).is_err() {
+                return ZWaveStatus::Disconnected;
+            }
+        }
+        ZWaveStatus::Ok
+    }
+
+    pub fn request(&self, request: FfiRequest) -> ZWaveStatus {
+        match self.request_tx.try_send(request) {
+            Ok(()) => ZWaveStatus::Ok,
+            Err(_) => ZWaveStatus::Disconnected,
+        }
+    }
+
+    pub fn poll_event(&self) -> Option<String> {
+        self.events.lock().expect("event lock poisoned").pop_front()
+    }
+}
+
+impl Drop for FfiRuntime {
+    fn drop(&mut self) {
+        // Closing the channels ends the runtime loop
+        self.serial_in_tx.close();
+        self.request_tx.close();
+        if let Some(thread) = self.thread.take() {
+            // When the driver is destroyed from a callback, this runs on the driver thread,
+            // which cannot wait for itself. It ends after the callback returns.
+            if thread.thread().id() != thread::current().id() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+struct EventEmitter {
+    callbacks: ZWaveCallbacks,
+    events: Arc<Mutex<VecDeque<String>>>,
+    fatal_error_emitted: Cell<bool>,
+}
+
+impl EventEmitter {
+    fn emit(&self, name: &str, args: Map<String, Value>) {
+        let event = json::event(name, args);
+        if let Some(on_event) = self.callbacks.on_event {
+            if let Ok(event) = std::ffi::CString::new(event) {
+                on_event(self.callbacks.user_data, event.as_ptr());
+            }
+        } else {
+            self.events
+                .lock()
+                .expect("event lock poisoned")
+                .push_back(event);
+        }
+    }
+
+    /// Tells the application that the driver stopped. Both the driver event and the result of
+    /// the driver task report this, but the application only needs to hear about it once.
+    fn emit_fatal_error(&self, error: &FatalError) {
+        if !self.fatal_error_emitted.replace(true) {
+            self.emit("error", json::fatal_error(error));
+        }
+    }
+
+    fn write_serial(&self, frame: RawSerialFrame) -> bool {
+        let Some(on_serial_write) = self.callbacks.on_serial_write else {
+            return false;
+        };
+        let data = frame.as_bytes();
+        on_serial_write(self.callbacks.user_data, data.as_ptr(), data.len());
+        true
+    }
+}
+
+async fn run(
+    local: &LocalExecutor<'_>,
+    options: FfiOptions,
+    emitter: EventEmitter,
+    serial_in_rx: async_channel::Receiver<RawSerialFrame>,
+    request_rx: async_channel::Receiver<FfiRequest>,
+) {
+    let (log_tx, log_rx) = zwave_pal::channel::channel(16);
+    let (serial_api, mut serial_api_actor, serial_api_adapter) =
+        zwave_driver::SerialApi::new(log_tx.clone());
+    let (driver, mut driver_actor, driver_adapter) =
+        Driver::new(&serial_api, log_tx, options.security_keys);
+
+    let emitter = Rc::new(emitter);
+    let mut driver_task = local.spawn(async move { driver_actor.run().await });
+    let serial_api_task = local.spawn(async move { serial_api_actor.run().await });
+    let pump_task = local.spawn(pump(
+        emitter.clone(),
+        options.log_level,
+        serial_in_rx,
+        log_rx,
+        driver_adapter,
+        serial_api_adapter,
+    ));
+
+    zwave_pal::select_biased! {
+        result = &mut driver_task => {
+            // The driver only stops on its own if it cannot recover from an error.
+            // Requests that are still pending or sent later fail with `Disconnected`.
+            request_rx.close();
+            if let Err(error) = result {
+                emitter.emit_fatal_error(&error);
+            }
+        },
+        _ = handle_requests(&driver, &emitter, &request_rx) => {
+            let _ = driver_task.cancel().await;
+        },
+    }
+
+    let _ = pump_task.cancel().await;
+    let _ = serial_api_task.cancel().await;
+}
+
+async fn handle_requests(
+    driver: &Driver,
+    emitter: &EventEmitter,
+    request_rx: &async_channel::Receiver<FfiRequest>,
+) {
+    // Until the application starts the driver, there is no controller to work with
+    while let Ok(request) = request_rx.recv().await {
+        match request {
+            FfiRequest::Start => break,
+            FfiRequest::GetValue { reply, .. } => {
+                let _ = reply.send(Err(ZWaveStatus::NotReady)).await;
+            }
+            FfiRequest::SetValue { reply, .. }
+            | FfiRequest::BeginInclusion { reply }
+            | FfiRequest::BeginExclusion { reply } => {
+                let _ = reply.send(ZWaveStatus::NotReady).await;
+            }
+        }
+    }
+    if request_rx.is_closed() {
+        return;
+    }
+
+    let controller = match Controller::new(driver).interview().await {
+        Ok(controller) => controller,
+        Err(e) => {
+            let mut args = Map::new();
+            args.insert("error".into(), json!(e.to_string()));
+            emitter.emit("error", args);
+            return;
+        }
+    };
+
+    let mut args = Map::new();
+    args.insert("homeId".into(), json!(controller.home_id().to_string()));
+    args.insert(
+        "ownNodeId".into(),
+        json!(u16::from(controller.own_node_id())),
+    );
+    args.insert(
+        "nodes".into(),
+        json!(
+            controller
+                .nodes()
+                .iter()
+                .map(|n| u16::from(n.id()))
+                .collect::<Vec<_>>()
+        ),
+    );
+    emitter.emit("driver ready", args);
+
+    // Inclusion and exclusion take a while, so other requests are handled in the meantime
+    let mut network_management: Option<LocalBoxFuture<'_, ()>> = None;
+    loop {
+        let request = zwave_pal::select_biased! {
+            request = request_rx.recv() => request,
+            _ = async {
+                match network_management.as_mut() {
+                    Some(operation) => operation.await,
+                    None => future::pending().await,
+                }
+            } => {
+                network_management = None;
+                continue;
+            },
+        };
+        let Ok(request) = request else {
+            break;
+        };
+
+        match request {
+            FfiRequest::Start => {}
+            FfiRequest::GetValue {
+                node_id,
+                endpoint,
+                value_id,
+                reply,
+            } => {
+                let result = get_value(&controller, node_id, endpoint, &value_id);
+                let _ = reply.send(result).await;
+            }
+            FfiRequest::SetValue {
+                node_id,
+                endpoint,
+                value_id,
+                value,
+                reply,
+            } => {
+                let result = set_value(&controller, node_id, endpoint, &value_id, &value).await;
+                let _ = reply.send(result).await;
+            }
+            FfiRequest::BeginInclusion { reply } | FfiRequest::BeginExclusion { reply }
+                if network_management.is_some()
+                    || controller.inclusion_state() != InclusionState::Idle =>
+            {
+                let _ = reply.send(ZWaveStatus::Failed).await;
+            }
+            FfiRequest::BeginInclusion { reply } => {
+                network_management = Some(include_node(&controller, emitter).boxed_local());
+                let _ = reply.send(ZWaveStatus::Ok).await;
+            }
+            FfiRequest::BeginExclusion { reply } => {
+                network_management = Some(exclude_node(&controller, emitter).boxed_local());
+                let _ = reply.send(ZWaveStatus::Ok).await;
+            }
+        }
+    }
+}
+
+/// Includes a node and tells the application about the outcome
+async fn include_node(controller: &Controller<'_, Ready>, emitter: &EventEmitter) {
+    let result = controller.include_node(&InclusionOptions::default()).await;
+    emit_network_management_result(emitter, "inclusion", result);
+}
+
+/// Excludes a node and tells the application about the outcome
+async fn exclude_node(controller: &Controller<'_, Ready>, emitter: &EventEmitter) {
+    let result = controller.exclude_node(&ExclusionOptions::default()).await;
+    emit_network_management_result(emitter, "exclusion", result);
+}
+
+/// Emits `<operation> finished` with the ID of the affected node, which is `null` if no node was
+/// found, or `<operation> failed` with the error
+fn emit_network_management_result(
+    emitter: &EventEmitter,
+    operation: &str,
+    result: ControllerCommandResult<Option<NodeId>>,
+) {
+    let mut args = Map::new();
+    match result {
+        Ok(node_id) => {
+            args.insert("nodeId".into(), json!(node_id.map(u16::from)));
+            emitter.emit(&format!("{operation} finished"), args);
+        }
+        Err(e) => {
+            args.insert("error".into(), json!(e.to_string()));
+            emitter.emit(&format!("{operation} failed"), args);
+        }
+    }
+}
+
+fn get_value(
+    controller: &Controller<'_, Ready>,
+    node_id: NodeId,
+    endpoint: u8,
+    value_id: &ValueId,
+) -> Result<Option<Value>, ZWaveStatus> {
+    let node = controller
+        .node(node_id)
+        .ok_or(ZWaveStatus::InvalidArgument)?;
+    let value = if endpoint == 0 {
+        node.value_cache().read(value_id)
+    } else {
+        node.endpoint(endpoint).value_cache().read(value_id)
+    };
+    Ok(value.as_ref().map(json::cache_value_to_json))
+}
+
+async fn set_value(
+    controller: &Controller<'_, Ready>,
+    node_id: NodeId,
+    endpoint: u8,
+    value_id: &ValueId,
+    value: &Value,
+) -> ZWaveStatus {
+    use zwave_cc::commandclass::{basic::BasicCCValues, binary_switch::BinarySwitchCCValues};
+
+    let Some(node) = controller.node(node_id) else {
+        return ZWaveStatus::InvalidArgument;
+    };
+    let endpoint_ref;
+    let api = if endpoint == 0 {
+        node.cc_api()
+    } else {
+        endpoint_ref = node.endpoint(endpoint);
+        endpoint_ref.cc_api()
+    };
+
+    // Only the values that have a CC API to set them are supported for now
+    let result = if BasicCCValues::target_value().is(value_id) {
+        let Some(level) = value
+            .as_u64()
+            .and_then(|v| u8::try_from(v).ok())
+            .and_then(|v| LevelSet::try_from(v).ok())
+        else {
+            return ZWaveStatus::InvalidArgument;
+        };
+        api.basic().set(level).await
+    } else if BinarySwitchCCValues::target_value().is(value_id) {
+        let Some(target) = value.as_bool() else {
+            return ZWaveStatus::InvalidArgument;
+        };
+        api.binary_switch().set(target.into(), None).await
+    } else {
+        return ZWaveStatus::NotSupported;
+    };
+
+    match result {
+        Ok(()) => ZWaveStatus::Ok,
+        Err(_) => ZWaveStatus::Failed,
+    }
+}
+
+/// Moves data between the serial API, the driver and the application
+async fn pump(
+    emitter: Rc<EventEmitter>,
+    log_level: zwave_core::log::Loglevel,
+    serial_in_rx: async_channel::Receiver<RawSerialFrame>,
+    mut log_rx: LogReceiver,
+    mut driver_adapter: DriverAdapter,
+    mut serial_api_adapter: SerialApiAdapter,
+) {
+    let formatter = DefaultFormatter::new();
+
+    loop {
+        zwave_pal::select_biased! {
+            serial_in = serial_in_rx.recv() => {
+                let Ok(frame) = serial_in else {
+                    // Application stopped the driver => quit
+                    break;
+                };
+                if serial_api_adapter.serial_in.try_send(frame).is_err() {
+                    break;
+                }
+            },
+            serial_out = serial_api_adapter.serial_out.recv() => {
+                let Some(frame) = serial_out else {
+                    break;
+                };
+                if !emitter.write_serial(frame) {
+                    break;
+                }
+            },
+            event = serial_api_adapter.event_rx.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                match event {
+                    SerialApiEvent::Unsolicited { command } => {
+                        if driver_adapter
+                            .input_tx
+                            .try_send(DriverInput::Unsolicited { command })
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    SerialApiEvent::ControllerUnresponsive => {
+                        emitter.emit("controller unresponsive", Map::new());
+                    }
+                    SerialApiEvent::ControllerRecovered => {
+                        emitter.emit("controller recovered", Map::new());
+                    }
+                    SerialApiEvent::ChecksumMismatch { data } => {
+                        let mut args = Map::new();
+                        args.insert("data".into(), json!(hex::encode(data)));
+                        emitter.emit("checksum mismatch", args);
+                    }
+                }
+            },
+            event = driver_adapter.event_rx.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                match event {
+                    DriverEvent::FatalError { error } => emitter.emit_fatal_error(&error),
+                    event => {
+                        let (name, args) = json::driver_event(&event);
+                        emitter.emit(name, args);
+                    }
+                }
+            },
+            log = log_rx.recv() => {
+                let Some((log, level)) = log else {
+                    break;
+                };
+                if level > log_level {
+                    continue;
+                }
+                let message: String = formatter
+                    .format_log(&log, level)
+                    .into_iter()
+                    .map(|s| s.string)
+                    .collect();
+                let mut args = Map::new();
+                args.insert("level".into(), json!(format!("{:?}", level).to_lowercase()));
+                args.insert("message".into(), json!(message.trim_end()));
+                emitter.emit("log", args);
+            }
+        }
+    }
+}
@@ -5,5 +5,6 @@ use zwave_core::submodule;
 submodule!(definitions);
 #[cfg(feature = "std")]
 pub mod formatters;
+submodule!(log_parser);
 pub mod loggers;
 mod util;
@@ -0,0 +1,346 @@
+use crate::Direction;
+use bytes::Bytes;
+use core::time::Duration;
+use thiserror::Error;
+use zwave_pal::prelude::*;
+use zwave_serial::frame::{ControlFlow, RawSerialFrame};
+
+/// A serial frame that was read back from a log
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerialLogEntry {
+    /// The time that passed between the first entry of the log and this one
+    pub elapsed: Duration,
+    pub direction: Direction,
+    pub frame: RawSerialFrame,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SerialLogParseError {
+    #[error("Line {line}: invalid hex data")]
+    InvalidHex { line: usize },
+    #[error("Line {line}: expected {expected} bytes, got {actual}")]
+    LengthMismatch {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+enum PendingKind {
+    Data,
+    Discarded,
+}
+
+/// A data entry which may continue on the following lines
+struct PendingEntry {
+    line: usize,
+    elapsed: Duration,
+    direction: Direction,
+    kind: PendingKind,
+    hex: String,
+    expected_len: Option<usize>,
+}
+
+impl PendingEntry {
+    fn finish(self) -> Result<SerialLogEntry, SerialLogParseError> {
+        let data = hex::decode(&self.hex)
+            .map_err(|_| SerialLogParseError::InvalidHex { line: self.line })?;
+        if let Some(expected) = self.expected_len {
+            if data.len() != expected {
+                return Err(SerialLogParseError::LengthMismatch {
+                    line: self.line,
+                    expected,
+                    actual: data.len(),
+                });
+            }
+        }
+
+        let data = Bytes::from(data);
+        let frame = match self.kind {
+            PendingKind::Data => RawSerialFrame::Data(data),
+            PendingKind::Discarded => RawSerialFrame::Garbage(data),
+        };
+        Ok(SerialLogEntry {
+            elapsed: self.elapsed,
+            direction: self.direction,
+            frame,
+        })
+    }
+}
+
+/// Parses the serial frames from a log that was written with the
+/// [DefaultFormatter](crate::formatters::DefaultFormatter), so they can be replayed.
+///
+/// All other log entries are skipped. The log may contain colors, and long frames may be
+/// wrapped across multiple lines.
+pub fn parse_serial_log(log: &str) -> Result<Vec<SerialLogEntry>, SerialLogParseError> {
+    let mut ret = Vec::new();
+    let mut pending: Option<PendingEntry> = None;
+    let mut clock = LogClock::default();
+
+    for (index, line) in log.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_ansi_codes(line);
+
+        // Wrapped lines of the previous entry are indented
+        if line.starts_with(char::is_whitespace) {
+            if let Some(pending) = pending.as_mut() {
+                let (content, secondary_tag) = split_secondary_tag(line.trim());
+                pending.hex.push_str(content);
+                if let Some(len) = secondary_tag.and_then(parse_byte_count) {
+                    pending.expected_len = Some(len);
+                }
+            }
+            continue;
+        }
+
+        // Any other line ends the previous entry
+        if let Some(pending) = pending.take() {
+            ret.push(pending.finish()?);
+        }
+
+        let mut parts = line.splitn(3, ' ');
+        let (Some(timestamp), Some("SERIAL"), Some(rest)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let elapsed = clock.elapsed(timestamp);
+
+        let (direction, rest) = match rest.chars().next() {
+            Some('«') => (Direction::Inbound, &rest['«'.len_utf8()..]),
+            Some('»') => (Direction::Outbound, &rest['»'.len_utf8()..]),
+            _ => (Direction::None, rest),
+        };
+        let (message, secondary_tag) = split_secondary_tag(rest.trim());
+
+        let control_flow = match message {
+            "[ACK]" => Some(ControlFlow::ACK),
+            "[NAK]" => Some(ControlFlow::NAK),
+            "[CAN]" => Some(ControlFlow::CAN),
+            _ => None,
+        };
+        if let Some(control_flow) = control_flow {
+            ret.push(SerialLogEntry {
+                elapsed,
+                direction,
+                frame: RawSerialFrame::ControlFlow(control_flow),
+            });
+            continue;
+        }
+
+        let (kind, hex) = if let Some(hex) = message.strip_prefix("0x") {
+            (PendingKind::Data, hex)
+        } else if let Some(hex) = message.strip_prefix("[DISCARDED] invalid data: 0x") {
+            (PendingKind::Discarded, hex)
+        } else {
+            continue;
+        };
+        pending = Some(PendingEntry {
+            line: line_number,
+            elapsed,
+            direction,
+            kind,
+            hex: hex.to_string(),
+            expected_len: secondary_tag.and_then(parse_byte_count),
+        });
+    }
+
+    if let Some(pending) = pending {
+        ret.push(pending.finish()?);
+    }
+
+    Ok(ret)
+}
+
+/// Removes the escape sequences that are used to color the log
+fn strip_ansi_codes(line: &str) -> String {
+    let mut ret = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip everything up to and including the final byte of the sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            ret.push(c);
+        }
+    }
+    ret
+}
+
+/// Splits a trailing secondary tag like `(5 bytes)` from the rest of the line
+fn split_secondary_tag(line: &str) -> (&str, Option<&str>) {
+    if let Some(without_paren) = line.strip_suffix(')') {
+        if let Some(start) = without_paren.rfind('(') {
+            return (
+                without_paren[..start].trim_end(),
+                Some(&without_paren[start + 1..]),
+            );
+        }
+    }
+    (line, None)
+}
+
+fn parse_byte_count(tag: &str) -> Option<usize> {
+    tag.strip_suffix(" bytes")?.parse().ok()
+}
+
+/// Turns the timestamps of the log into the time that passed since the first entry
+#[derive(Default)]
+struct LogClock {
+    first: Option<Duration>,
+    last: Duration,
+    /// Added to the time of day after the log crossed midnight
+    offset: Duration,
+}
+
+impl LogClock {
+    fn elapsed(&mut self, timestamp: &str) -> Duration {
+        let Some(mut time) = parse_timestamp(timestamp) else {
+            // Keep the time of the previous entry
+            return self.last;
+        };
+        let first = *self.first.get_or_insert(time);
+
+        time += self.offset;
+        if time < self.last + first {
+            self.offset += Duration::from_secs(24 * 60 * 60);
+            time += Duration::from_secs(24 * 60 * 60);
+        }
+        self.last = time - first;
+        self.last
+    }
+}
+
+/// Parses the timestamps of the formatter. With `std`, these are RFC 3339 timestamps like
+/// `2024-01-01T12:34:56.789Z`, of which the time of day is used. With `embassy`, they contain
+/// the time since boot like `123.456s`.
+fn parse_timestamp(timestamp: &str) -> Option<Duration> {
+    if let Some(since_boot) = timestamp.strip_suffix('s') {
+        let (secs, millis) = since_boot.split_once('.')?;
+        return Some(
+            Duration::from_secs(secs.parse().ok()?) + Duration::from_millis(millis.parse().ok()?),
+        );
+    }
+
+    let (_date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut parts = time.splitn(3, ':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let (secs, millis) = parts.next()?.split_once('.')?;
+    let secs: u64 = secs.parse().ok()?;
+    let millis: u64 = millis.parse().ok()?;
+    Some(Duration::from_millis(
+        ((hours * 60 + minutes) * 60 + secs) * 1000 + millis,
+    ))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::LogFormatter;
+    use crate::LogInfo;
+    use crate::formatters::DefaultFormatter;
+    use zwave_core::log::{LogPayload, Loglevel};
+
+    fn format(log: LogInfo) -> String {
+        DefaultFormatter::new()
+            .format_log(&log, Loglevel::Debug)
+            .iter()
+            .map(|f| f.string.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_formatted_log() {
+        // Long enough to be wrapped across multiple lines
+        let long_frame: Vec<u8> = (0..60).collect();
+        let mut log = String::new();
+        log += &format(
+            LogInfo::builder()
+                .label("SERIAL")
+                .direction(Direction::Outbound)
+                .secondary_tag("6 bytes".into())
+                .payload(LogPayload::Text("0x0103000220fe".into()))
+                .build(),
+        );
+        log += &format(
+            LogInfo::builder()
+                .label("SERIAL")
+                .direction(Direction::Inbound)
+                .primary_tags(vec!["ACK".into()])
+                .secondary_tag("0x06".into())
+                .payload(LogPayload::empty())
+                .build(),
+        );
+        log += &format(
+            LogInfo::builder()
+                .label("DRIVER")
+                .payload(LogPayload::Text("0x1234".into()))
+                .build(),
+        );
+        log += &format(
+            LogInfo::builder()
+                .label("SERIAL")
+                .direction(Direction::Inbound)
+                .secondary_tag(format!("{} bytes", long_frame.len()).into())
+                .payload(LogPayload::Text(
+                    format!("0x{}", hex::encode(&long_frame)).into(),
+                ))
+                .build(),
+        );
+        assert!(log.lines().count() > 4, "the long frame should be wrapped");
+
+        let frames: Vec<_> = parse_serial_log(&log)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.direction, entry.frame))
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                (
+                    Direction::Outbound,
+                    RawSerialFrame::Data(Bytes::from_static(&[0x01, 0x03, 0x00, 0x02, 0x20, 0xfe]))
+                ),
+                (
+                    Direction::Inbound,
+                    RawSerialFrame::ControlFlow(ControlFlow::ACK)
+                ),
+                (Direction::Inbound, RawSerialFrame::Data(long_frame.into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_colored_log_with_timestamps() {
+        let log = "\
+\u{1b}[2m2024-01-01T23:59:59.500Z\u{1b}[0m \u{1b}[48;5;8mSERIAL\u{1b}[0m » 0x01030002fe   (5 bytes)
+2024-01-02T00:00:00.250Z SERIAL « [DISCARDED] invalid data: 0xaabb   (2 bytes)";
+
+        let entries = parse_serial_log(log).unwrap();
+        assert_eq!(entries[0].elapsed, Duration::ZERO);
+        assert_eq!(entries[1].elapsed, Duration::from_millis(750));
+        assert_eq!(
+            entries[1].frame,
+            RawSerialFrame::Garbage(Bytes::from_static(&[0xaa, 0xbb]))
+        );
+    }
+
+    #[test]
+    fn test_truncated_frames_are_rejected() {
+        let log = "2024-01-01T00:00:00.000Z SERIAL » 0x0103   (5 bytes)";
+        assert_eq!(
+            parse_serial_log(log),
+            Err(SerialLogParseError::LengthMismatch {
+                line: 1,
+                expected: 5,
+                actual: 2
+            })
+        );
+    }
+}
@@ -7,11 +7,11 @@ use zwave_pal::prelude::*;
 use zwave_serial::command::CommandId;
 
 pub struct ControllerLogger<'a> {
-    inner: &'a dyn LocalImmutableLogger,
+    inner: &'a (dyn LocalImmutableLogger + Sync),
 }
 
 impl<'a> ControllerLogger<'a> {
-    pub fn new(inner: &'a dyn LocalImmutableLogger) -> Self {
+    pub fn new(inner: &'a (dyn LocalImmutableLogger + Sync)) -> Self {
         Self { inner }
     }
 
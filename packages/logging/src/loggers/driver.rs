@@ -3,7 +3,7 @@ use zwave_core::log::{LogPayload, Loglevel};
 use zwave_pal::prelude::*;
 
 pub struct DriverLogger<'a> {
-    inner: &'a dyn LocalImmutableLogger,
+    inner: &'a (dyn LocalImmutableLogger + Sync),
 }
 
 const LOGO: &str = "\
@@ -15,7 +15,7 @@ const LOGO: &str = "\
 ";
 
 impl<'a> DriverLogger<'a> {
-    pub fn new(inner: &'a dyn LocalImmutableLogger) -> Self {
+    pub fn new(inner: &'a (dyn LocalImmutableLogger + Sync)) -> Self {
         Self { inner }
     }
 
@@ -9,12 +9,12 @@ use zwave_serial::command::CommandId;
 pub struct NodeLogger<'a> {
     node_id: NodeId,
     endpoint: EndpointIndex,
-    inner: &'a dyn LocalImmutableLogger,
+    inner: &'a (dyn LocalImmutableLogger + Sync),
 }
 
 impl<'a> NodeLogger<'a> {
     pub fn new(
-        inner: &'a dyn LocalImmutableLogger,
+        inner: &'a (dyn LocalImmutableLogger + Sync),
         node_id: NodeId,
         endpoint: EndpointIndex,
     ) -> Self {
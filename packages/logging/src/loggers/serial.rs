@@ -4,13 +4,13 @@ use zwave_pal::prelude::*;
 use zwave_serial::frame::ControlFlow;
 
 pub struct SerialLogger<'a> {
-    inner: &'a dyn LocalImmutableLogger,
+    inner: &'a (dyn LocalImmutableLogger + Sync),
 }
 
 const SERIAL_LOGLEVEL: Loglevel = Loglevel::Debug;
 
 impl<'a> SerialLogger<'a> {
-    pub fn new(inner: &'a dyn LocalImmutableLogger) -> Self {
+    pub fn new(inner: &'a (dyn LocalImmutableLogger + Sync)) -> Self {
         Self { inner }
     }
 
@@ -30,6 +30,11 @@ mod std_impl {
         pub fn send(self, value: T) -> Result<(), T> {
             self.inner.send(value)
         }
+
+        /// Whether the receiver was dropped, so sending a value is pointless
+        pub fn is_canceled(&self) -> bool {
+            self.inner.is_canceled()
+        }
     }
 
     pub struct Receiver<T> {
@@ -80,6 +85,11 @@ mod embassy_impl {
                 embassy_sync::channel::TrySendError::Full(v) => v,
             })
         }
+
+        /// Whether the receiver was dropped, so sending a value is pointless
+        pub fn is_canceled(&self) -> bool {
+            Arc::strong_count(&self.inner) == 1
+        }
     }
 
     /// A oneshot receiver backed by an embassy capacity-1 channel.
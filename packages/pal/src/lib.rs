@@ -37,6 +37,8 @@ pub mod prelude {
 /// without adding a direct dependency. On embassy builds, the `custom` feature
 /// is enabled — applications must call `zwave_pal::rng::register_custom_getrandom!`
 /// to provide an RNG implementation.
+/// On `wasm32-unknown-unknown`, the `js` feature is enabled so the browser's
+/// `crypto.getRandomValues` is used.
 pub use getrandom as rng;
 
 // Re-exports needed by the select_biased! macro.
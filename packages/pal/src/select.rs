@@ -1,9 +1,9 @@
 /// A `select_biased!` macro that works on both std (via `futures::select_biased!`)
 /// and embassy (via `embassy_futures::select`).
 ///
-/// Supports 2, 3, and 4-branch variants. The first branch has highest priority.
-/// The embassy backend maps to `select2`/`select3`/`select4` which caps us at
-/// 4 branches. Add a new arm here if a 5th is ever needed.
+/// Supports 2 to 5-branch variants. The first branch has highest priority.
+/// The embassy backend maps to `select`/`select3`/`select4`, and nests `select4` in `select`
+/// for 5 branches. Add a new arm here if a 6th is ever needed.
 ///
 /// Usage:
 /// ```ignore
@@ -49,6 +49,18 @@ macro_rules! select_biased {
             $p4 = ($f4).fuse() => $h4,
         }
     }};
+
+    // 5-branch variant
+    ($p1:pat = $f1:expr => $h1:expr, $p2:pat = $f2:expr => $h2:expr, $p3:pat = $f3:expr => $h3:expr, $p4:pat = $f4:expr => $h4:expr, $p5:pat = $f5:expr => $h5:expr $(,)?) => {{
+        use $crate::__reexport_futures::FutureExt;
+        $crate::__reexport_futures::select_biased! {
+            $p1 = ($f1).fuse() => $h1,
+            $p2 = ($f2).fuse() => $h2,
+            $p3 = ($f3).fuse() => $h3,
+            $p4 = ($f4).fuse() => $h4,
+            $p5 = ($f5).fuse() => $h5,
+        }
+    }};
 }
 
 // =============================================================================
@@ -84,4 +96,28 @@ macro_rules! select_biased {
             $crate::__reexport_embassy_futures::select::Either4::Fourth($p4) => $h4,
         }
     }};
+
+    // 5-branch variant: select4 is polled first, so the first four branches keep their priority
+    ($p1:pat = $f1:expr => $h1:expr, $p2:pat = $f2:expr => $h2:expr, $p3:pat = $f3:expr => $h3:expr, $p4:pat = $f4:expr => $h4:expr, $p5:pat = $f5:expr => $h5:expr $(,)?) => {{
+        match $crate::__reexport_embassy_futures::select::select(
+            $crate::__reexport_embassy_futures::select::select4($f1, $f2, $f3, $f4),
+            $f5,
+        )
+        .await
+        {
+            $crate::__reexport_embassy_futures::select::Either::First(
+                $crate::__reexport_embassy_futures::select::Either4::First($p1),
+            ) => $h1,
+            $crate::__reexport_embassy_futures::select::Either::First(
+                $crate::__reexport_embassy_futures::select::Either4::Second($p2),
+            ) => $h2,
+            $crate::__reexport_embassy_futures::select::Either::First(
+                $crate::__reexport_embassy_futures::select::Either4::Third($p3),
+            ) => $h3,
+            $crate::__reexport_embassy_futures::select::Either::First(
+                $crate::__reexport_embassy_futures::select::Either4::Fourth($p4),
+            ) => $h4,
+            $crate::__reexport_embassy_futures::select::Either::Second($p5) => $h5,
+        }
+    }};
 }
@@ -7,9 +7,13 @@ use core::time::Duration;
 // Instant
 // =============================================================================
 
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
 pub use std::time::Instant;
 
+// std::time::Instant::now() panics on wasm32-unknown-unknown
+#[cfg(all(feature = "std", target_arch = "wasm32", target_os = "unknown"))]
+pub use web_time::Instant;
+
 #[cfg(feature = "embassy")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Instant(embassy_time::Instant);
@@ -110,6 +114,68 @@ impl Clone for Timestamp {
     }
 }
 
+// =============================================================================
+// Wall clock
+// =============================================================================
+
+/// A calendar date and time of day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    /// The day of the week, from 1 (Monday) to 7 (Sunday)
+    pub weekday: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+/// The current time of day, both in the local time zone and in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallClock {
+    pub local: DateTime,
+    pub utc: DateTime,
+    /// How many minutes the local time is ahead of UTC. Changes with daylight saving time.
+    pub utc_offset_minutes: i32,
+}
+
+#[cfg(feature = "std")]
+impl WallClock {
+    /// Reads the system clock
+    pub fn now() -> Option<Self> {
+        use chrono::{Datelike, Timelike};
+
+        fn date_time<T: Datelike + Timelike>(t: &T) -> DateTime {
+            DateTime {
+                year: t.year().clamp(0, u16::MAX as i32) as u16,
+                month: t.month() as u8,
+                day: t.day() as u8,
+                weekday: t.weekday().number_from_monday() as u8,
+                hour: t.hour() as u8,
+                minute: t.minute() as u8,
+                second: t.second().min(59) as u8,
+            }
+        }
+
+        let local = chrono::Local::now();
+        let utc = local.to_utc();
+        Some(Self {
+            local: date_time(&local),
+            utc: date_time(&utc),
+            utc_offset_minutes: local.offset().local_minus_utc() / 60,
+        })
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl WallClock {
+    /// Embedded targets have no system clock to read
+    pub fn now() -> Option<Self> {
+        None
+    }
+}
+
 // =============================================================================
 // Timer
 // =============================================================================
@@ -73,20 +73,24 @@ pub(crate) fn impl_derive_cc_values(
                     // Only return Option-typed values if they are not None
                     Ok(quote! {
                         if let Some(#field_name) = self.#field_name {
-                            ret.push((
-                                #value_name().id,
-                                CacheValue::from(#field_name)
-                            ));
+                            if #value_name().options.stateful == stateful {
+                                ret.push((
+                                    #value_name().id,
+                                    CacheValue::from(#field_name)
+                                ));
+                            }
                         }
                     })
                 }
                 _ => {
                     // Return values with other types as-is
                     Ok(quote! {
-                        ret.push((
-                            #value_name().id,
-                            CacheValue::from(self.#field_name)
-                        ));
+                        if #value_name().options.stateful == stateful {
+                            ret.push((
+                                #value_name().id,
+                                CacheValue::from(self.#field_name)
+                            ));
+                        }
                     })
                 }
             }
@@ -103,6 +107,16 @@ pub(crate) fn impl_derive_cc_values(
     Ok(quote! {
         impl CCValues for #name {
             fn to_values(&self) -> Vec<(ValueId, CacheValue)> {
+                let stateful = true;
+                let mut ret = Vec::new();
+
+                #( #values )*
+
+                ret
+            }
+
+            fn to_events(&self) -> Vec<(ValueId, CacheValue)> {
+                let stateful = false;
                 let mut ret = Vec::new();
 
                 #( #values )*
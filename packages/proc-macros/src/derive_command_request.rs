@@ -0,0 +1,242 @@
+use quote::{ToTokens, format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::{DeriveInput, Expr, Ident, Token, parse::Error, spanned::Spanned};
+
+/// What a command expects from the controller, either a response or a callback
+#[derive(Default)]
+struct Expectation {
+    expected: bool,
+    /// The command variant to expect. Without it, any command with the same function type matches.
+    variant: Option<Ident>,
+    /// Fields that must be equal in the command and the expected variant
+    matching_fields: Vec<Ident>,
+    /// Additional condition for the expected variant
+    condition: Option<Expr>,
+}
+
+impl Expectation {
+    fn is_customized(&self) -> bool {
+        self.variant.is_some()
+    }
+
+    fn validate(&self, kind: &str, ast: &DeriveInput) -> Result<(), Error> {
+        if self.variant.is_none() && (!self.matching_fields.is_empty() || self.condition.is_some())
+        {
+            return Err(Error::new(
+                ast.span(),
+                format!("`{kind}_matches` and `{kind}_if` require `{kind} = Variant`"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Generates the body of the test function for an expected variant.
+    /// `binding` is the name of the tested command, which is shadowed by the matched variant.
+    fn test_fn_body(&self, binding: &Ident, check_callback_id: bool) -> impl ToTokens {
+        let variant = self.variant.as_ref().expect("only called for variants");
+        let callback_id_check = check_callback_id.then(|| {
+            quote! {
+                if self.needs_callback_id() && #binding.callback_id() != self.callback_id() {
+                    return false;
+                }
+            }
+        });
+        let mut checks: Vec<_> = self
+            .matching_fields
+            .iter()
+            .map(|field| quote! { self.#field == #binding.#field })
+            .collect();
+        if let Some(condition) = &self.condition {
+            checks.push(quote! { (#condition) });
+        }
+        let result = if checks.is_empty() {
+            quote!(true)
+        } else {
+            quote!(#(#checks)&&*)
+        };
+
+        quote! {
+            #callback_id_check
+            let Command::#variant(#binding) = #binding else {
+                return false;
+            };
+            #result
+        }
+    }
+}
+
+#[derive(Default)]
+struct CommandRequestAttrs {
+    response: Expectation,
+    callback: Expectation,
+    /// Condition for expecting the callback, if any
+    expects_callback: Option<Expr>,
+    /// Whether the command has a `callback_id` field that the driver must assign
+    callback_id: bool,
+    /// Condition for needing a callback ID, if any
+    needs_callback_id: Option<Expr>,
+    raw_frames: bool,
+}
+
+fn parse_attrs(ast: &DeriveInput) -> Result<CommandRequestAttrs, Error> {
+    let mut attrs = CommandRequestAttrs::default();
+
+    for attr in ast
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("command_request"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let parse_variant = |expectation: &mut Expectation| -> Result<(), Error> {
+                expectation.expected = true;
+                if meta.input.peek(Token![=]) {
+                    expectation.variant = Some(meta.value()?.parse()?);
+                }
+                Ok(())
+            };
+            let parse_fields = |expectation: &mut Expectation| -> Result<(), Error> {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let fields = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                expectation.matching_fields.extend(fields);
+                Ok(())
+            };
+
+            if meta.path.is_ident("response") {
+                parse_variant(&mut attrs.response)
+            } else if meta.path.is_ident("response_matches") {
+                parse_fields(&mut attrs.response)
+            } else if meta.path.is_ident("response_if") {
+                attrs.response.condition = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("callback") {
+                parse_variant(&mut attrs.callback)
+            } else if meta.path.is_ident("callback_matches") {
+                parse_fields(&mut attrs.callback)
+            } else if meta.path.is_ident("callback_if") {
+                attrs.callback.condition = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("expects_callback") {
+                attrs.expects_callback = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("callback_id") {
+                attrs.callback_id = true;
+                Ok(())
+            } else if meta.path.is_ident("needs_callback_id") {
+                attrs.needs_callback_id = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("raw_frames") {
+                attrs.raw_frames = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported #[command_request] attribute"))
+            }
+        })?;
+    }
+
+    attrs.response.validate("response", ast)?;
+    attrs.callback.validate("callback", ast)?;
+    if attrs.expects_callback.is_some() && !attrs.callback.expected {
+        return Err(Error::new(
+            ast.span(),
+            "`expects_callback` requires `callback`",
+        ));
+    }
+    if attrs.needs_callback_id.is_some() && !attrs.callback_id {
+        return Err(Error::new(
+            ast.span(),
+            "`needs_callback_id` requires `callback_id`",
+        ));
+    }
+
+    Ok(attrs)
+}
+
+pub(crate) fn impl_derive_command_request(
+    ast: &DeriveInput,
+) -> Result<proc_macro::TokenStream, Error> {
+    if !matches!(ast.data, syn::Data::Struct(_)) {
+        return Err(Error::new(
+            ast.span(),
+            "#[derive(CommandRequest)] is only supported for structs",
+        ));
+    }
+
+    let attrs = parse_attrs(ast)?;
+    let name = &ast.ident;
+
+    let expects_response = attrs.response.expected;
+    let test_response = attrs.response.is_customized().then(|| {
+        let body = attrs
+            .response
+            .test_fn_body(&format_ident!("response"), false);
+        quote! {
+            fn test_response(&self, response: &Command) -> bool {
+                #body
+            }
+        }
+    });
+
+    let expects_callback = match (&attrs.expects_callback, attrs.callback.expected) {
+        (Some(condition), _) => quote!(#condition),
+        (None, expected) => quote!(#expected),
+    };
+    let test_callback = attrs.callback.is_customized().then(|| {
+        let body = attrs
+            .callback
+            .test_fn_body(&format_ident!("callback"), attrs.callback_id);
+        quote! {
+            fn test_callback(&self, callback: &Command) -> bool {
+                if !self.expects_callback() {
+                    return false;
+                }
+                #body
+            }
+        }
+    });
+
+    let callback_id_fns = attrs.callback_id.then(|| {
+        let needs_callback_id = match &attrs.needs_callback_id {
+            Some(condition) => quote!(#condition),
+            None => quote!(true),
+        };
+        quote! {
+            fn needs_callback_id(&self) -> bool {
+                #needs_callback_id
+            }
+
+            fn set_callback_id(&mut self, callback_id: Option<u8>) {
+                self.callback_id = callback_id;
+            }
+        }
+    });
+
+    let raw_frames_fn = attrs.raw_frames.then(|| {
+        quote! {
+            fn expects_raw_frames(&self) -> bool {
+                true
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl CommandRequest for #name {
+            fn expects_response(&self) -> bool {
+                #expects_response
+            }
+
+            #test_response
+
+            fn expects_callback(&self) -> bool {
+                #expects_callback
+            }
+
+            #test_callback
+
+            #callback_id_fns
+
+            #raw_frames_fn
+        }
+    }
+    .into())
+}
@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 
 use derive_cc_values::impl_derive_cc_values;
+use derive_command_request::impl_derive_command_request;
 use derive_try_from_repr::try_from_repr_for_enum;
 use impl_cc_apis::CCAPIInfoExtractor;
 use impl_cc_enum::{CCInfo, CCInfoExtractor};
@@ -13,6 +14,7 @@ use syn::{DeriveInput, parse_macro_input, visit};
 use util::{parse_dirname_from_macro_input, parse_files_in_dir};
 
 mod derive_cc_values;
+mod derive_command_request;
 mod derive_try_from_repr;
 mod impl_cc_apis;
 mod impl_cc_enum;
@@ -110,13 +112,20 @@ pub fn impl_command_enum(input: TokenStream) -> TokenStream {
         impl Command {
             // Implement conversion from a raw command to the correct variant
             pub fn try_from_raw(raw: CommandRaw, ctx: CommandParsingContext) -> zwave_core::parse::ParseResult<Self> {
+                // We parse commands that are sent by the controller
+                Self::try_from_raw_with_origin(raw, ctx, MessageOrigin::Controller)
+            }
+
+            // Like try_from_raw, but also for commands sent by the host, e.g. when decoding logs
+            pub fn try_from_raw_with_origin(
+                raw: CommandRaw,
+                ctx: CommandParsingContext,
+                expected_origin: MessageOrigin,
+            ) -> zwave_core::parse::ParseResult<Self> {
                 let command_type = raw.command_type;
                 let function_type = raw.function_type;
                 let mut payload = raw.payload;
-                // We parse commands that are sent by the controller
-                let expected_origin = MessageOrigin::Controller;
 
-                // ...and hope that Rust optimizes the match arms with origin Host away
                 let ret = match (command_type, function_type, expected_origin) {
                     #( #impl_try_from_command_raw_match_arms ),*
                     _ => Err(zwave_core::parse::ParseError::not_implemented("Unknown combination of command_type, function_type and origin")),
@@ -235,6 +244,15 @@ pub fn impl_cc_enum(input: TokenStream) -> TokenStream {
         }
     });
 
+    let arbitrary_variants = ccs.iter().map(|c| {
+        let cc_name = c.cc_name;
+        quote! {
+            if let Some(s) = <#cc_name as CCArbitrary>::arbitrary(encapsulated.clone()) {
+                ret.push(s.prop_map(Self::#cc_name).boxed());
+            }
+        }
+    });
+
     let tokens = quote! {
         // Import all CC modules, so we don't have to do it manually
         #(#submodule_imports)*
@@ -312,6 +330,21 @@ pub fn impl_cc_enum(input: TokenStream) -> TokenStream {
         // Simplify conversions from WithAddress<Variant> to WithAddress<CC>
         #(#from_with_address_impls)*
 
+        // Collect the strategies for the property-based round-trip tests.
+        // Every CC must implement CCArbitrary, so new CCs are covered automatically.
+        #[cfg(test)]
+        impl CC {
+            pub(crate) fn arbitrary_variants(
+                encapsulated: Option<proptest::strategy::BoxedStrategy<CC>>,
+            ) -> Vec<proptest::strategy::BoxedStrategy<CC>> {
+                use crate::arbitrary::CCArbitrary;
+                use proptest::strategy::Strategy;
+
+                let mut ret = Vec::new();
+                #(#arbitrary_variants)*
+                ret
+            }
+        }
     };
 
     TokenStream::from(tokens)
@@ -355,6 +388,14 @@ pub fn impl_cc_apis(input: TokenStream) -> TokenStream {
         }
     });
 
+    let refresh_values_match_arms = ccs.iter().map(|(m, c)| {
+        let module = format_ident!("{}", m);
+        let cc_id = c.cc_id;
+        quote! {
+            #cc_id => CCAPIs::new(endpoint).#module().refresh_values().await,
+        }
+    });
+
     let implemented_version_match_arms = ccs.iter().map(|(_, c)| {
         let cc_id = c.cc_id;
         let cc_version = c.cc_version;
@@ -394,6 +435,16 @@ pub fn impl_cc_apis(input: TokenStream) -> TokenStream {
             }
         }
 
+        pub async fn refresh_cc_values<'a>(endpoint: &'a dyn EndpointLike<'a>, cc: CommandClasses) -> CCAPIResult<()> {
+            match cc {
+                #( #refresh_values_match_arms )*
+                _ => {
+                    // No values to refresh
+                    Ok(())
+                }
+            }
+        }
+
         /// Returns the version of the given CC this library implements
         pub fn get_implemented_version(cc: CommandClasses) -> Option<u8> {
             match cc {
@@ -437,3 +488,37 @@ pub fn derive_cc_values(input: TokenStream) -> TokenStream {
         Err(error) => error.to_compile_error().into(),
     }
 }
+
+/// Implements `CommandRequest` from declarative annotations:
+///
+/// ```ignore
+/// #[derive(CommandRequest)]
+/// #[command_request(
+///     // Expects a response with the same function type...
+///     response,
+///     // ...or a specific variant, optionally with fields that must match
+///     // and an additional condition, in which the variant is bound to `response`
+///     response = SerialApiSetupResponse,
+///     response_matches(command),
+///     response_if = response.is_ok(),
+///     // The same for callbacks
+///     callback = RequestNodeNeighborUpdateCallback,
+///     callback_matches(node_id),
+///     callback_if = callback.status != NodeNeighborUpdateStatus::UpdateStarted,
+///     // Only expect the callback under a condition
+///     expects_callback = self.callback_id.is_some(),
+///     // The driver assigns the `callback_id` field, which callbacks must match
+///     callback_id,
+///     needs_callback_id = self.expects_callback(),
+///     // Test responses and callbacks before parsing them
+///     raw_frames,
+/// )]
+/// ```
+#[proc_macro_derive(CommandRequest, attributes(command_request))]
+pub fn derive_command_request(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match impl_derive_command_request(&input) {
+        Ok(output) => output,
+        Err(error) => error.to_compile_error().into(),
+    }
+}
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use crate::quirks::SdkQuirks;
 use crate::util::with_hex_fmt;
 use bytes::Bytes;
 use core::fmt::Debug;
@@ -11,29 +12,32 @@ use zwave_pal::prelude::*;
 submodule!(application);
 submodule!(capability);
 submodule!(misc);
+submodule!(nvm);
 submodule!(transport);
 submodule!(network_mgmt);
 
-#[derive(Default, TypedBuilder)]
+/// Information about the controller that is needed to parse and serialize commands.
+/// It is cheap to copy, so the driver keeps it up to date in one place
+/// and copies it for each frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, TypedBuilder)]
 #[builder(field_defaults(default))]
-pub struct CommandEncodingContext {
-    own_node_id: NodeId,
-    #[builder(default, setter(into))]
-    sdk_version: Option<Version>,
-    node_id_type: NodeIdType,
-}
-
-#[derive(Default, TypedBuilder)]
-#[builder(field_defaults(default))]
-pub struct CommandParsingContext {
+pub struct CommandContext {
     pub own_node_id: NodeId,
     #[builder(default, setter(into))]
     pub sdk_version: Option<Version>,
     pub node_id_type: NodeIdType,
-    // #[builder(default, setter(into))]
-    // pub security_manager: Option<SecurityManager>,
 }
 
+impl CommandContext {
+    /// The quirks of the controller's SDK, which influence how commands are encoded
+    pub fn quirks(&self) -> SdkQuirks {
+        SdkQuirks::for_sdk_version(self.sdk_version)
+    }
+}
+
+pub type CommandEncodingContext = CommandContext;
+pub type CommandParsingContext = CommandContext;
+
 pub trait CommandParsable
 where
     Self: Sized + CommandBase,
@@ -102,6 +106,12 @@ pub trait CommandRequest: CommandId {
         false
     }
     fn set_callback_id(&mut self, _callback_id: Option<u8>) {}
+
+    /// Whether responses and callbacks to this command should be tested before parsing them,
+    /// in the form of [`Command::NotImplemented`]
+    fn expects_raw_frames(&self) -> bool {
+        false
+    }
 }
 
 pub trait AsCommandRaw {
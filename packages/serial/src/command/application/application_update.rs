@@ -6,6 +6,7 @@ use zwave_core::{parse::{
     combinators::map_res,
 }};
 use zwave_core::prelude::*;
+use zwave_core::serialize;
 
 #[derive(Debug, Copy, Clone, PartialEq, TryFromRepr)]
 #[repr(u8)]
@@ -164,8 +165,47 @@ impl CommandParsable for ApplicationUpdateRequest {
 }
 
 impl SerializableWith<&CommandEncodingContext> for ApplicationUpdateRequest {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: ApplicationUpdateRequest::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::bytes::{be_u8, be_u32};
+
+        be_u8(self.update_type as u8).serialize(output);
+        match &self.payload {
+            ApplicationUpdateRequestPayload::SucIdChanged
+            | ApplicationUpdateRequestPayload::RoutingPending
+            | ApplicationUpdateRequestPayload::NodeInfoRequestDone
+            | ApplicationUpdateRequestPayload::NodeInfoRequestFailed
+            | ApplicationUpdateRequestPayload::SmartStartIncludedNodeInfoReceived => {}
+
+            ApplicationUpdateRequestPayload::NodeInfoReceived {
+                node_id,
+                application_data,
+            }
+            | ApplicationUpdateRequestPayload::NodeAdded {
+                node_id,
+                application_data,
+            } => {
+                node_id.serialize(output, ctx.node_id_type);
+                application_data.serialize(output);
+            }
+            ApplicationUpdateRequestPayload::NodeRemoved { node_id } => {
+                node_id.serialize(output, ctx.node_id_type);
+            }
+
+            ApplicationUpdateRequestPayload::SmartStartHomeIdReceived {
+                node_id,
+                nwi_home_id,
+                application_data,
+            }
+            | ApplicationUpdateRequestPayload::SmartStartHomeIdReceivedLR {
+                node_id,
+                nwi_home_id,
+                application_data,
+            } => {
+                node_id.serialize(output, ctx.node_id_type);
+                be_u32(*nwi_home_id).serialize(output);
+                application_data.serialize(output);
+            }
+        }
     }
 }
 
@@ -174,3 +214,36 @@ impl ToLogPayload for ApplicationUpdateRequest {
         LogPayloadText::new("TODO: implement ToLogPayload for ApplicationUpdateRequest").into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::ApplicationUpdateRequest, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    use super::{ApplicationUpdateRequestPayload, ApplicationUpdateType};
+
+    #[test]
+    fn test_node_info_roundtrip() {
+        let raw = vec![
+            0x84, // node info received
+            0x07, // node ID
+            0x05, // length of the node information
+            0x04, 0x10, 0x01, // device classes
+            0x25, 0x27, // supported CCs
+        ];
+        let cmd = ApplicationUpdateRequest::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.update_type, ApplicationUpdateType::NodeInfoReceived);
+        let ApplicationUpdateRequestPayload::NodeInfoReceived { node_id, .. } = &cmd.payload else {
+            panic!("expected node info, got {:?}", cmd.payload);
+        };
+        assert_eq!(*node_id, NodeId::new(7u8));
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
@@ -8,6 +8,7 @@ use zwave_core::parse::{
     combinators::map, multi::variable_length_cc_list,
 };
 use zwave_core::prelude::*;
+use zwave_core::serialize;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SerialApiStartedRequest {
@@ -62,8 +63,39 @@ impl CommandParsable for SerialApiStartedRequest {
 }
 
 impl SerializableWith<&CommandEncodingContext> for SerialApiStartedRequest {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: SerialApiStartedRequest::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::{
+            bits::bits,
+            bytes::{be_u8, slice},
+        };
+
+        let mut cc_list = BytesMut::new();
+        for cc in &self.supported_command_classes {
+            cc.serialize(&mut cc_list);
+        }
+        if !self.controlled_command_classes.is_empty() {
+            be_u8(COMMAND_CLASS_SUPPORT_CONTROL_MARK).serialize(&mut cc_list);
+            for cc in &self.controlled_command_classes {
+                cc.serialize(&mut cc_list);
+            }
+        }
+
+        self.wake_up_reason.serialize(output);
+        be_u8(if self.watchdog_enabled { 0x01 } else { 0x00 }).serialize(output);
+        bits(move |bo| {
+            self.is_listening.write(bo);
+            u7::new(0).write(bo);
+        })
+        .serialize(output);
+        be_u8(self.generic_device_class).serialize(output);
+        be_u8(self.specific_device_class).serialize(output);
+        be_u8(cc_list.len() as u8).serialize(output);
+        slice(cc_list).serialize(output);
+        bits(move |bo| {
+            u7::new(0).write(bo);
+            self.supports_long_range.write(bo);
+        })
+        .serialize(output);
     }
 }
 
@@ -85,3 +117,32 @@ impl ToLogPayload for SerialApiStartedRequest {
             .into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::SerialApiStartedRequest, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let raw = hex::decode("0001800201045e86ef2001").unwrap();
+        let cmd = SerialApiStartedRequest::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.wake_up_reason, SerialApiWakeUpReason::Reset);
+        assert!(cmd.watchdog_enabled);
+        assert!(cmd.is_listening);
+        assert_eq!(
+            cmd.supported_command_classes,
+            vec![CommandClasses::ZWavePlusInfo, CommandClasses::Version]
+        );
+        assert_eq!(cmd.controlled_command_classes, vec![CommandClasses::Basic]);
+        assert!(cmd.supports_long_range);
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
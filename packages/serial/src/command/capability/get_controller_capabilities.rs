@@ -1,11 +1,14 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use ux::{u1, u3};
 use zwave_core::parse::bits::{self, bool};
 use zwave_core::prelude::*;
+use zwave_core::serialize;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetControllerCapabilitiesRequest {}
 
 impl CommandId for GetControllerCapabilitiesRequest {
@@ -24,16 +27,6 @@ impl CommandId for GetControllerCapabilitiesRequest {
 
 impl CommandBase for GetControllerCapabilitiesRequest {}
 
-impl CommandRequest for GetControllerCapabilitiesRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetControllerCapabilitiesRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -96,8 +89,21 @@ impl CommandParsable for GetControllerCapabilitiesResponse {
 }
 
 impl SerializableWith<&CommandEncodingContext> for GetControllerCapabilitiesResponse {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: GetControllerCapabilitiesResponse::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bits::bits;
+
+        let other_network = !self.started_this_network;
+        let secondary = self.role == ControllerRole::Secondary;
+
+        bits(move |bo| {
+            u3::new(0).write(bo);
+            self.is_suc.write(bo);
+            u1::new(0).write(bo);
+            self.sis_present.write(bo);
+            other_network.write(bo);
+            secondary.write(bo);
+        })
+        .serialize(output);
     }
 }
 
@@ -111,3 +117,28 @@ impl ToLogPayload for GetControllerCapabilitiesResponse {
             .into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetControllerCapabilitiesResponse, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_roundtrip() {
+        // SUC, SIS present, secondary controller in another network
+        let raw = [0b0001_0111];
+        let cmd = GetControllerCapabilitiesResponse::parse(
+            &mut Bytes::copy_from_slice(&raw),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.role, ControllerRole::Secondary);
+        assert!(!cmd.started_this_network);
+        assert!(cmd.sis_present);
+        assert!(cmd.is_suc);
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
@@ -1,11 +1,13 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use zwave_core::serialize;
 use zwave_core::parse::bytes::be_u32;
 use zwave_core::prelude::*;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetControllerIdRequest {}
 
 impl CommandId for GetControllerIdRequest {
@@ -24,16 +26,6 @@ impl CommandId for GetControllerIdRequest {
 
 impl CommandBase for GetControllerIdRequest {}
 
-impl CommandRequest for GetControllerIdRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetControllerIdRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -1,6 +1,7 @@
 use zwave_pal::prelude::*;
 use crate::{command::CommandId, prelude::*};
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use zwave_core::serialize;
 use zwave_core::parse::{
     bytes::complete::{literal, take_while1},
@@ -8,7 +9,8 @@ use zwave_core::parse::{
 };
 use zwave_core::prelude::*;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetControllerVersionRequest {}
 
 impl CommandId for GetControllerVersionRequest {
@@ -27,16 +29,6 @@ impl CommandId for GetControllerVersionRequest {
 
 impl CommandBase for GetControllerVersionRequest {}
 
-impl CommandRequest for GetControllerVersionRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetControllerVersionRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -2,13 +2,16 @@ use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
 use hex::ToHex;
+use proc_macros::CommandRequest;
 use zwave_core::parse::{
     bytes::{be_u16, be_u8, complete::take},
     combinators::{cond, map, opt},
 };
 use zwave_core::prelude::*;
+use zwave_core::serialize;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetProtocolVersionRequest {}
 
 impl CommandId for GetProtocolVersionRequest {
@@ -27,16 +30,6 @@ impl CommandId for GetProtocolVersionRequest {
 
 impl CommandBase for GetProtocolVersionRequest {}
 
-impl CommandRequest for GetProtocolVersionRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetProtocolVersionRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -115,8 +108,26 @@ impl CommandParsable for GetProtocolVersionResponse {
 }
 
 impl SerializableWith<&CommandEncodingContext> for GetProtocolVersionResponse {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: GetProtocolVersionResponse::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::{be_u8, be_u16, slice};
+
+        self.protocol_type.serialize(output);
+        be_u8(self.version.major).serialize(output);
+        be_u8(self.version.minor).serialize(output);
+        be_u8(self.version.patch.unwrap_or(0)).serialize(output);
+
+        // The commit hash is only parsed after the build number
+        if let Some(app_framework_build_number) = self.app_framework_build_number {
+            be_u16(app_framework_build_number).serialize(output);
+            if let Some(git_commit_hash) = &self.git_commit_hash {
+                // All zeroes mean that there is no hash
+                let mut raw = [0u8; 16];
+                if hex::decode_to_slice(git_commit_hash, &mut raw).is_err() {
+                    raw = [0u8; 16];
+                }
+                slice(raw).serialize(output);
+            }
+        }
     }
 }
 
@@ -134,3 +145,33 @@ impl ToLogPayload for GetProtocolVersionResponse {
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetProtocolVersionResponse, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_response_roundtrip() {
+        let mut raw = vec![
+            0x00, // Z-Wave
+            0x07, 0x12, 0x01, // version 7.18.1
+            0x00, 0x7b, // build number
+        ];
+        raw.extend_from_slice(&[0xab; 16]);
+        let cmd = GetProtocolVersionResponse::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.app_framework_build_number, Some(123));
+        assert_eq!(
+            cmd.git_commit_hash.as_deref(),
+            Some("ab".repeat(16).as_str())
+        );
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
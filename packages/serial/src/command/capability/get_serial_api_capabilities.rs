@@ -1,6 +1,7 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use zwave_core::parse::multi::fixed_length_bitmask_u8;
 use zwave_core::log::ToLogPayload;
 use zwave_core::parse::{
@@ -8,11 +9,13 @@ use zwave_core::parse::{
     combinators::map,
 };
 use zwave_core::prelude::*;
+use zwave_core::serialize;
 
 const NUM_FUNCTIONS: usize = 256;
 const NUM_FUNCTION_BYTES: usize = NUM_FUNCTIONS / 8;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetSerialApiCapabilitiesRequest {}
 
 impl GetSerialApiCapabilitiesRequest {}
@@ -33,16 +36,6 @@ impl CommandId for GetSerialApiCapabilitiesRequest {
 
 impl CommandBase for GetSerialApiCapabilitiesRequest {}
 
-impl CommandRequest for GetSerialApiCapabilitiesRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetSerialApiCapabilitiesRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -115,8 +108,23 @@ impl CommandParsable for GetSerialApiCapabilitiesResponse {
 }
 
 impl SerializableWith<&CommandEncodingContext> for GetSerialApiCapabilitiesResponse {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: GetSerialApiCapabilitiesResponse::serialize() not implemented");
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::{be_u8, be_u16, slice};
+
+        be_u8(self.firmware_version.major).serialize(output);
+        be_u8(self.firmware_version.minor).serialize(output);
+        be_u16(self.manufacturer_id.into()).serialize(output);
+        be_u16(self.product_type.into()).serialize(output);
+        be_u16(self.product_id.into()).serialize(output);
+
+        // Function type 1 is the first bit
+        let indices: Vec<usize> = self
+            .supported_function_types
+            .iter()
+            .map(|function_type| *function_type as usize - 1)
+            .collect();
+        let bitmask = zwave_core::bitvec::build_bitmask(&indices, NUM_FUNCTION_BYTES * 8);
+        slice(&bitmask).serialize(output);
     }
 }
 
@@ -139,3 +147,38 @@ impl ToLogPayload for GetSerialApiCapabilitiesResponse {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetSerialApiCapabilitiesResponse, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_response_roundtrip() {
+        let mut raw = vec![
+            0x01, 0x02, // firmware version
+            0x00, 0x86, // manufacturer ID
+            0x00, 0x01, // product type
+            0x00, 0x5a, // product ID
+        ];
+        let mut bitmask = [0u8; 32];
+        // GetSerialApiInitData (0x02) and SendData (0x13)
+        bitmask[0] = 0b0000_0010;
+        bitmask[2] = 0b0000_0100;
+        raw.extend_from_slice(&bitmask);
+
+        let cmd = GetSerialApiCapabilitiesResponse::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            cmd.supported_function_types,
+            vec![FunctionType::GetSerialApiInitData, FunctionType::SendData]
+        );
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
@@ -1,6 +1,7 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use ux::u4;
 use zwave_core::parse::{
     bits::{self, bool},
@@ -10,7 +11,8 @@ use zwave_core::parse::{
 use zwave_core::prelude::*;
 use zwave_core::serialize;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetSerialApiInitDataRequest {}
 
 impl CommandId for GetSerialApiInitDataRequest {
@@ -29,16 +31,6 @@ impl CommandId for GetSerialApiInitDataRequest {
 
 impl CommandBase for GetSerialApiInitDataRequest {}
 
-impl CommandRequest for GetSerialApiInitDataRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetSerialApiInitDataRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -62,9 +54,13 @@ impl ToLogPayload for GetSerialApiInitDataRequest {
 pub struct GetSerialApiInitDataResponse {
     pub api_version: ZWaveApiVersion,
     pub chip_type: Option<ChipType>,
+    /// Whether the module runs the controller or the end node (formerly slave) API
     pub node_type: NodeType,
+    /// Whether the controller is primary or secondary
     pub role: ControllerRole,
+    /// Whether the controller is the SIS (SUC ID server) of the network
     pub is_sis: bool,
+    /// Whether the module supports the timer functions of the Serial API
     pub supports_timers: bool,
     pub node_ids: Vec<NodeId>,
 }
@@ -88,17 +84,17 @@ impl CommandBase for GetSerialApiInitDataResponse {}
 impl CommandParsable for GetSerialApiInitDataResponse {
     fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         let api_version = ZWaveApiVersion::parse(i)?;
-        let (_reserved, is_sis, is_primary, supports_timers, node_type) =
+        let (_reserved, is_sis, is_secondary, supports_timers, node_type) =
             bits::bits((u4::parse, bool, bool, bool, NodeType::parse)).parse(i)?;
         let node_ids = variable_length_bitmask_u8(i, 1)?;
         let chip_type = opt(ChipType::parse).parse(i)?;
         Ok(Self {
             api_version,
             is_sis,
-            role: if is_primary {
-                ControllerRole::Primary
-            } else {
+            role: if is_secondary {
                 ControllerRole::Secondary
+            } else {
+                ControllerRole::Primary
             },
             supports_timers,
             node_type,
@@ -118,14 +114,14 @@ impl SerializableWith<&CommandEncodingContext> for GetSerialApiInitDataResponse
             .filter_map(|n| if *n < 256u16 { Some((*n).into()) } else { None })
             .collect();
 
-        let is_primary = self.role == ControllerRole::Primary;
+        let is_secondary = self.role == ControllerRole::Secondary;
 
         self.api_version.serialize(output);
         bits(move |bo| {
             let reserved = u4::new(0);
             reserved.write(bo);
             self.is_sis.write(bo);
-            is_primary.write(bo);
+            is_secondary.write(bo);
             self.supports_timers.write(bo);
             self.node_type.write(bo);
         })
@@ -183,7 +179,7 @@ mod test {
             &raw,
             vec![
                 10,          // API version
-                0b0000_1110, // Capabilities,
+                0b0000_1010, // Capabilities,
                 2,           // bitmask length
                 0b1000_1001, // node 1, 4, 8
                 0b0000_0010, // node 10
@@ -198,7 +194,7 @@ mod test {
     fn test_parse() {
         let input: Vec<u8> = vec![
             10,          // API version
-            0b0000_1110, // Capabilities,
+            0b0000_1010, // Capabilities,
             2,           // bitmask length
             0b1000_1001, // node 1, 4, 8
             0b0000_0010, // node 10
@@ -220,4 +216,27 @@ mod test {
                 .unwrap();
         assert_eq!(actual, expected)
     }
+
+    #[test]
+    fn test_parse_capabilities() {
+        let input: Vec<u8> = vec![
+            5,           // API version
+            0b0000_0101, // Capabilities,
+            0,           // bitmask length
+        ];
+        let mut input = Bytes::from(input);
+        let expected = GetSerialApiInitDataResponse {
+            api_version: ZWaveApiVersion::Legacy(5),
+            is_sis: false,
+            role: ControllerRole::Secondary,
+            supports_timers: false,
+            node_type: NodeType::EndNode,
+            node_ids: vec![],
+            chip_type: None,
+        };
+        let actual =
+            GetSerialApiInitDataResponse::parse(&mut input, CommandParsingContext::default())
+                .unwrap();
+        assert_eq!(actual, expected)
+    }
 }
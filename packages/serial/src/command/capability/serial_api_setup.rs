@@ -1,6 +1,8 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use zwave_core::bitvec::build_bitmask;
 use zwave_core::parse::multi::fixed_length_bitmask_u8;
 use zwave_core::parse::parser_not_implemented;
 use zwave_core::parse::{
@@ -96,7 +98,8 @@ impl From<SerialApiSetupCommand> for u8 {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response = SerialApiSetupResponse, response_matches(command))]
 pub struct SerialApiSetupRequest {
     command: SerialApiSetupCommand,
     payload: SerialApiSetupRequestPayload,
@@ -228,23 +231,6 @@ impl CommandId for SerialApiSetupRequest {
 
 impl CommandBase for SerialApiSetupRequest {}
 
-impl CommandRequest for SerialApiSetupRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn test_response(&self, response: &Command) -> bool {
-        if let Command::SerialApiSetupResponse(res) = response {
-            return self.command == res.command;
-        }
-        false
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for SerialApiSetupRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         parser_not_implemented("ERROR: SerialApiSetupRequest::parse() not implemented")
@@ -433,13 +419,12 @@ impl CommandParsable for SerialApiSetupResponse {
                     // According to the Host API specification, the first bit (bit 0) should be GetSupportedCommands
                     // However, in Z-Wave SDK < 7.19.1, the entire bitmask is shifted by 1 bit and
                     // GetSupportedCommands is encoded in the second bit (bit 1)
-                    let start_value: u8 =
-                        if ctx.sdk_version < Some(Version::try_from("7.19.1").unwrap()) {
-                            SerialApiSetupCommand::Unsupported
-                        } else {
-                            SerialApiSetupCommand::GetSupportedCommands
-                        }
-                        .into();
+                    let start_value: u8 = if ctx.quirks().serial_api_setup_bitmask_shifted {
+                        SerialApiSetupCommand::Unsupported
+                    } else {
+                        SerialApiSetupCommand::GetSupportedCommands
+                    }
+                    .into();
 
                     map(
                         move |i: &mut Bytes| fixed_length_bitmask_u8(i, start_value, i.len()),
@@ -551,8 +536,90 @@ impl CommandParsable for SerialApiSetupResponse {
 }
 
 impl SerializableWith<&CommandEncodingContext> for SerialApiSetupResponse {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: SerialApiSetupResponse::serialize() not implemented");
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::{
+            bytes::{be_i8, be_i16, be_u8, slice},
+            sequence::tuple,
+        };
+
+        self.command.serialize(output);
+        match self.payload {
+            SerialApiSetupResponsePayload::Unsupported(_)
+            | SerialApiSetupResponsePayload::Unknown(_) => {
+                // No payload
+            }
+
+            SerialApiSetupResponsePayload::GetSupportedCommands { ref commands } => {
+                // The first byte is the single byte power-of-2 bitmask, followed by the extended bitmask
+                let legacy_bitmask = commands
+                    .iter()
+                    .map(|x| u8::from(*x))
+                    .filter(|x| x.is_power_of_two())
+                    .fold(0u8, |acc, x| acc | x);
+
+                // Mirror the bitmask shift of Z-Wave SDK < 7.19.1 (see parse)
+                let start_value: u8 = if ctx.quirks().serial_api_setup_bitmask_shifted {
+                    SerialApiSetupCommand::Unsupported
+                } else {
+                    SerialApiSetupCommand::GetSupportedCommands
+                }
+                .into();
+                let indizes = commands
+                    .iter()
+                    .map(|x| u8::from(*x))
+                    .filter(|x| *x >= start_value)
+                    .map(|x| (x - start_value) as usize)
+                    .collect::<Vec<_>>();
+                let bit_len = indizes.iter().max().unwrap_or(&0) + 1;
+                let extended_bitmask = build_bitmask(&indizes, bit_len);
+
+                tuple((be_u8(legacy_bitmask), slice(extended_bitmask))).serialize(output)
+            }
+
+            SerialApiSetupResponsePayload::SetTxStatusReport { success }
+            | SerialApiSetupResponsePayload::SetPowerlevel { success }
+            | SerialApiSetupResponsePayload::SetRFRegion { success }
+            | SerialApiSetupResponsePayload::SetNodeIDType { success }
+            | SerialApiSetupResponsePayload::SetLRMaximumTxPower { success }
+            | SerialApiSetupResponsePayload::SetPowerlevel16Bit { success } => {
+                be_u8(if success { 0x01 } else { 0x00 }).serialize(output)
+            }
+
+            SerialApiSetupResponsePayload::GetPowerlevel {
+                powerlevel:
+                    Powerlevel {
+                        tx_power: tx_power_dbm,
+                        measured_at_0_dbm,
+                    },
+            } => tuple((
+                // The values are represented as a multiple of 0.1 dBm
+                be_i8(crate::util::round_f32(tx_power_dbm * 10.0) as i8),
+                be_i8(crate::util::round_f32(measured_at_0_dbm * 10.0) as i8),
+            ))
+            .serialize(output),
+            SerialApiSetupResponsePayload::GetPowerlevel16Bit {
+                powerlevel:
+                    Powerlevel {
+                        tx_power: tx_power_dbm,
+                        measured_at_0_dbm,
+                    },
+            } => tuple((
+                // The values are represented as a multiple of 0.1 dBm
+                be_i16(crate::util::round_f32(tx_power_dbm * 10.0) as i16),
+                be_i16(crate::util::round_f32(measured_at_0_dbm * 10.0) as i16),
+            ))
+            .serialize(output),
+            SerialApiSetupResponsePayload::GetLRMaximumTxPower { max_power } => {
+                // The value is represented as a multiple of 0.1 dBm
+                be_i16(crate::util::round_f32(max_power * 10.0) as i16).serialize(output)
+            }
+
+            SerialApiSetupResponsePayload::GetMaximumPayloadSize { size }
+            | SerialApiSetupResponsePayload::GetLRMaximumPayloadSize { size } => {
+                be_u8(size).serialize(output)
+            }
+            SerialApiSetupResponsePayload::GetRFRegion { region } => region.serialize(output),
+        }
     }
 }
 
@@ -600,3 +667,61 @@ impl ToLogPayload for SerialApiSetupResponse {
         ret.extend(additional).into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        command::{
+            SerialApiSetupCommand, SerialApiSetupRequest, SerialApiSetupResponse,
+            SerialApiSetupResponsePayload,
+        },
+        prelude::*,
+    };
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_supported_commands_roundtrip() {
+        let cmd = SerialApiSetupResponse {
+            command: SerialApiSetupCommand::GetSupportedCommands,
+            payload: SerialApiSetupResponsePayload::GetSupportedCommands {
+                commands: vec![
+                    SerialApiSetupCommand::GetSupportedCommands,
+                    SerialApiSetupCommand::SetTxStatusReport,
+                    SerialApiSetupCommand::GetPowerlevel16Bit,
+                    SerialApiSetupCommand::GetRFRegion,
+                ],
+            },
+        };
+
+        for sdk_version in ["7.18.0", "7.19.1"] {
+            let sdk_version = Version::try_from(sdk_version).unwrap();
+            let ctx = CommandEncodingContext::builder()
+                .sdk_version(sdk_version)
+                .build();
+            let mut raw = Bytes::from(cmd.as_bytes(&ctx).to_vec());
+
+            let ctx = CommandParsingContext::builder()
+                .sdk_version(sdk_version)
+                .build();
+            let parsed = SerialApiSetupResponse::parse(&mut raw, ctx).unwrap();
+            assert_eq!(parsed, cmd);
+        }
+    }
+
+    #[test]
+    fn test_response_matches_command() {
+        let request = SerialApiSetupRequest::set_tx_status_report(true);
+        let response = |command| {
+            Command::SerialApiSetupResponse(SerialApiSetupResponse {
+                command,
+                payload: SerialApiSetupResponsePayload::SetTxStatusReport { success: true },
+            })
+        };
+
+        assert!(request.expects_response());
+        assert!(request.test_response(&response(SerialApiSetupCommand::SetTxStatusReport)));
+        assert!(!request.test_response(&response(SerialApiSetupCommand::SetPowerlevel)));
+        assert!(!request.expects_callback());
+    }
+}
@@ -1,10 +1,12 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use zwave_core::parse::combinators::opt;
 use zwave_core::prelude::*;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetBackgroundRssiRequest {}
 
 impl CommandId for GetBackgroundRssiRequest {
@@ -23,16 +25,6 @@ impl CommandId for GetBackgroundRssiRequest {
 
 impl CommandBase for GetBackgroundRssiRequest {}
 
-impl CommandRequest for GetBackgroundRssiRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetBackgroundRssiRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -89,8 +81,10 @@ impl CommandParsable for GetBackgroundRssiResponse {
 }
 
 impl SerializableWith<&CommandEncodingContext> for GetBackgroundRssiResponse {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: GetBackgroundRssiResponse::serialize() not implemented");
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        self.rssi_channel_0.serialize(output);
+        self.rssi_channel_1.serialize(output);
+        self.rssi_channel_2.serialize(output);
     }
 }
 
@@ -106,3 +100,24 @@ impl ToLogPayload for GetBackgroundRssiResponse {
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetBackgroundRssiResponse, prelude::*};
+    use bytes::Bytes;
+
+    #[test]
+    fn test_roundtrip() {
+        for raw in [vec![0xa5, 0xa6], vec![0xa5, 0xa6, 0x7f]] {
+            let cmd = GetBackgroundRssiResponse::parse(
+                &mut Bytes::from(raw.clone()),
+                CommandParsingContext::default(),
+            )
+            .unwrap();
+            assert_eq!(cmd.rssi_channel_2.is_some(), raw.len() == 3);
+
+            let ctx = CommandEncodingContext::default();
+            assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use zwave_core::parse::{bytes::be_u8, combinators::opt};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+const AUTO_CHANNEL_SELECTION_SUPPORTED: u8 = 0x10;
+const AUTO_CHANNEL_SELECTION_ACTIVE: u8 = 0x20;
+
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
+pub struct GetLongRangeChannelRequest {}
+
+impl CommandId for GetLongRangeChannelRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetLongRangeChannel
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for GetLongRangeChannelRequest {}
+
+impl CommandParsable for GetLongRangeChannelRequest {
+    fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetLongRangeChannelRequest {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for GetLongRangeChannelRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetLongRangeChannelResponse {
+    pub channel: LongRangeChannel,
+    pub supports_auto_channel_selection: bool,
+    pub auto_channel_selection_active: bool,
+}
+
+impl CommandId for GetLongRangeChannelResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetLongRangeChannel
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for GetLongRangeChannelResponse {}
+
+impl CommandParsable for GetLongRangeChannelResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let channel = LongRangeChannel::parse(i)?;
+        // Older firmwares do not report the auto channel selection capabilities
+        let flags = opt(be_u8).parse(i)?.unwrap_or(0);
+
+        Ok(Self {
+            channel,
+            supports_auto_channel_selection: flags & AUTO_CHANNEL_SELECTION_SUPPORTED != 0,
+            auto_channel_selection_active: flags & AUTO_CHANNEL_SELECTION_ACTIVE != 0,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetLongRangeChannelResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        let mut flags = 0u8;
+        if self.supports_auto_channel_selection {
+            flags |= AUTO_CHANNEL_SELECTION_SUPPORTED;
+        }
+        if self.auto_channel_selection_active {
+            flags |= AUTO_CHANNEL_SELECTION_ACTIVE;
+        }
+
+        self.channel.serialize(output);
+        be_u8(flags).serialize(output);
+    }
+}
+
+impl ToLogPayload for GetLongRangeChannelResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("channel", self.channel.to_string())
+            .with_entry(
+                "supports auto channel selection",
+                self.supports_auto_channel_selection,
+            )
+            .with_entry(
+                "auto channel selection active",
+                self.auto_channel_selection_active,
+            )
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetLongRangeChannelResponse, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_parse() {
+        let mut input = Bytes::from(vec![0x02, 0b0001_0000]);
+        let expected = GetLongRangeChannelResponse {
+            channel: LongRangeChannel::B,
+            supports_auto_channel_selection: true,
+            auto_channel_selection_active: false,
+        };
+        let actual =
+            GetLongRangeChannelResponse::parse(&mut input, CommandParsingContext::default())
+                .unwrap();
+        assert_eq!(actual, expected)
+    }
+
+    #[test]
+    fn test_parse_without_flags() {
+        let mut input = Bytes::from(vec![0x01]);
+        let expected = GetLongRangeChannelResponse {
+            channel: LongRangeChannel::A,
+            supports_auto_channel_selection: false,
+            auto_channel_selection_active: false,
+        };
+        let actual =
+            GetLongRangeChannelResponse::parse(&mut input, CommandParsingContext::default())
+                .unwrap();
+        assert_eq!(actual, expected)
+    }
+
+    #[test]
+    fn test_serialize() {
+        let cmd = GetLongRangeChannelResponse {
+            channel: LongRangeChannel::Auto,
+            supports_auto_channel_selection: true,
+            auto_channel_selection_active: true,
+        };
+        let ctx = CommandEncodingContext::default();
+        let raw = Into::<Command>::into(cmd).as_bytes(&ctx);
+        assert_eq!(&raw, vec![0xff, 0b0011_0000].as_slice())
+    }
+}
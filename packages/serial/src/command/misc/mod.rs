@@ -1,5 +1,7 @@
 use zwave_core::submodule;
 
 submodule!(get_background_rssi);
+submodule!(get_long_range_channel);
+submodule!(set_long_range_channel);
 submodule!(set_rf_receive_mode);
 submodule!(soft_reset);
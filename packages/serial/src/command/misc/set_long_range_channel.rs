@@ -0,0 +1,98 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{bytes::be_u8, combinators::map};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response)]
+pub struct SetLongRangeChannelRequest {
+    channel: LongRangeChannel,
+}
+
+impl CommandId for SetLongRangeChannelRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SetLongRangeChannel
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for SetLongRangeChannelRequest {}
+
+impl CommandParsable for SetLongRangeChannelRequest {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let channel = LongRangeChannel::parse(i)?;
+        Ok(Self { channel })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SetLongRangeChannelRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        self.channel.serialize(output);
+    }
+}
+
+impl ToLogPayload for SetLongRangeChannelRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("channel", self.channel.to_string())
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetLongRangeChannelResponse {
+    success: bool,
+}
+
+impl CommandId for SetLongRangeChannelResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SetLongRangeChannel
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for SetLongRangeChannelResponse {
+    fn is_ok(&self) -> bool {
+        self.success
+    }
+}
+
+impl CommandParsable for SetLongRangeChannelResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let success = map(be_u8, |x| x > 0).parse(i)?;
+        Ok(Self { success })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SetLongRangeChannelResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(if self.success { 1 } else { 0 }).serialize(output);
+    }
+}
+
+impl ToLogPayload for SetLongRangeChannelResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("success", self.success)
+            .into()
+    }
+}
@@ -1,12 +1,14 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use typed_builder::TypedBuilder;
 use zwave_core::parse::{bytes::be_u8, combinators::map};
 use zwave_core::prelude::*;
 use zwave_core::serialize;
 
-#[derive(Default, Debug, Clone, PartialEq, TypedBuilder)]
+#[derive(Default, Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response)]
 pub struct SetRfReceiveModeRequest {
     // Whether the Z-Wave module's RF receiver should be enabled
     enabled: bool,
@@ -28,16 +30,6 @@ impl CommandId for SetRfReceiveModeRequest {
 
 impl CommandBase for SetRfReceiveModeRequest {}
 
-impl CommandRequest for SetRfReceiveModeRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for SetRfReceiveModeRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // FIXME: SetRfReceiveModeRequest::parse() not implemented
@@ -93,8 +85,9 @@ impl CommandParsable for SetRfReceiveModeResponse {
 }
 
 impl SerializableWith<&CommandEncodingContext> for SetRfReceiveModeResponse {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: SetRfReceiveModeResponse::serialize() not implemented");
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(if self.success { 1 } else { 0 }).serialize(output);
     }
 }
 
@@ -105,3 +98,24 @@ impl ToLogPayload for SetRfReceiveModeResponse {
             .into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::SetRfReceiveModeResponse, prelude::*};
+    use bytes::Bytes;
+
+    #[test]
+    fn test_response_roundtrip() {
+        for raw in [[0x00], [0x01]] {
+            let cmd = SetRfReceiveModeResponse::parse(
+                &mut Bytes::copy_from_slice(&raw),
+                CommandParsingContext::default(),
+            )
+            .unwrap();
+            assert_eq!(cmd.is_ok(), raw[0] == 0x01);
+
+            let ctx = CommandEncodingContext::default();
+            assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+        }
+    }
+}
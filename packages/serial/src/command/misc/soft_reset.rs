@@ -1,8 +1,9 @@
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use zwave_core::prelude::*;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
 pub struct SoftResetRequest {}
 
 impl CommandId for SoftResetRequest {
@@ -21,16 +22,6 @@ impl CommandId for SoftResetRequest {
 
 impl CommandBase for SoftResetRequest {}
 
-impl CommandRequest for SoftResetRequest {
-    fn expects_response(&self) -> bool {
-        false
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for SoftResetRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -0,0 +1,378 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CommandRequest, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{
+    bytes::be_u8,
+    combinators::{context, map_res},
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+const OPTION_HIGH_POWER: u8 = 0x80;
+const OPTION_NETWORK_WIDE: u8 = 0x40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum AddNodeType {
+    Any = 0x01,
+    Controller = 0x02,
+    EndNode = 0x03,
+    Existing = 0x04,
+    Stop = 0x05,
+    StopControllerReplication = 0x06,
+    SmartStart = 0x09,
+}
+
+impl Display for AddNodeType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AddNodeType::Any => write!(f, "Any"),
+            AddNodeType::Controller => write!(f, "Controller"),
+            AddNodeType::EndNode => write!(f, "End node"),
+            AddNodeType::Existing => write!(f, "Existing"),
+            AddNodeType::Stop => write!(f, "Stop"),
+            AddNodeType::StopControllerReplication => write!(f, "Stop controller replication"),
+            AddNodeType::SmartStart => write!(f, "Smart Start"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum AddNodeStatus {
+    LearnReady = 0x01,
+    NodeFound = 0x02,
+    AddingEndNode = 0x03,
+    AddingController = 0x04,
+    ProtocolDone = 0x05,
+    Done = 0x06,
+    Failed = 0x07,
+    NotPrimary = 0x23,
+}
+
+impl Display for AddNodeStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AddNodeStatus::LearnReady => write!(f, "Ready"),
+            AddNodeStatus::NodeFound => write!(f, "Node found"),
+            AddNodeStatus::AddingEndNode => write!(f, "Adding end node"),
+            AddNodeStatus::AddingController => write!(f, "Adding controller"),
+            AddNodeStatus::ProtocolDone => write!(f, "Protocol done"),
+            AddNodeStatus::Done => write!(f, "Done"),
+            AddNodeStatus::Failed => write!(f, "Failed"),
+            AddNodeStatus::NotPrimary => write!(f, "Not primary"),
+        }
+    }
+}
+
+impl Parsable for AddNodeStatus {
+    fn parse(i: &mut Bytes) -> ParseResult<Self> {
+        context("AddNodeStatus", map_res(be_u8, Self::try_from)).parse(i)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(
+    // Stopping is done without a callback, so it also works when the controller is not including
+    callback,
+    expects_callback = self.add_node_type != AddNodeType::Stop,
+    callback_id,
+    needs_callback_id = self.expects_callback(),
+)]
+pub struct AddNodeToNetworkRequest {
+    add_node_type: AddNodeType,
+    #[builder(default = true)]
+    high_power: bool,
+    #[builder(default = true)]
+    network_wide: bool,
+    #[builder(setter(skip), default)]
+    callback_id: Option<u8>,
+}
+
+impl AddNodeToNetworkRequest {
+    /// Creates a request to stop the inclusion process, which the controller does not answer
+    pub fn stop() -> Self {
+        Self::builder().add_node_type(AddNodeType::Stop).build()
+    }
+}
+
+impl CommandId for AddNodeToNetworkRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::AddNodeToNetwork
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for AddNodeToNetworkRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for AddNodeToNetworkRequest {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let mode = be_u8(i)?;
+        let callback_id = be_u8(i)?;
+        let add_node_type = AddNodeType::try_from(mode & 0x0f)?;
+        Ok(Self {
+            add_node_type,
+            high_power: mode & OPTION_HIGH_POWER != 0,
+            network_wide: mode & OPTION_NETWORK_WIDE != 0,
+            callback_id: Some(callback_id),
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for AddNodeToNetworkRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        let mut mode = self.add_node_type as u8;
+        if self.high_power {
+            mode |= OPTION_HIGH_POWER;
+        }
+        if self.network_wide {
+            mode |= OPTION_NETWORK_WIDE;
+        }
+        be_u8(mode).serialize(output);
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+    }
+}
+
+impl ToLogPayload for AddNodeToNetworkRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new()
+            .with_entry("action", self.add_node_type.to_string())
+            .with_entry("high power", self.high_power)
+            .with_entry("network wide", self.network_wide);
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddNodeToNetworkCallback {
+    callback_id: Option<u8>,
+    pub status: AddNodeStatus,
+    pub node_id: NodeId,
+    pub node_info: Option<NodeInformationApplicationData>,
+}
+
+impl CommandId for AddNodeToNetworkCallback {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::AddNodeToNetwork
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for AddNodeToNetworkCallback {
+    fn is_ok(&self) -> bool {
+        !matches!(
+            self.status,
+            AddNodeStatus::Failed | AddNodeStatus::NotPrimary
+        )
+    }
+
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for AddNodeToNetworkCallback {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let callback_id = be_u8(i)?;
+        let status = AddNodeStatus::parse(i)?;
+        // The node ID and node information are only included in some of the status updates
+        let node_id = if i.is_empty() {
+            NodeId::unspecified()
+        } else {
+            NodeId::parse(i, ctx.node_id_type)?
+        };
+        let node_info = if i.first().is_some_and(|len| *len > 0) {
+            Some(NodeInformationApplicationData::parse(i)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            callback_id: Some(callback_id),
+            status,
+            node_id,
+            node_info,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for AddNodeToNetworkCallback {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+        be_u8(self.status as u8).serialize(output);
+        // Like the controller, include an empty node ID and node information if there are none
+        self.node_id.serialize(output, ctx.node_id_type);
+        match &self.node_info {
+            Some(node_info) => node_info.serialize(output),
+            None => be_u8(0).serialize(output),
+        }
+    }
+}
+
+impl ToLogPayload for AddNodeToNetworkCallback {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret = ret.with_entry("status", self.status.to_string());
+        if self.node_id != NodeId::unspecified() {
+            ret = ret.with_entry("node ID", self.node_id.to_string());
+        }
+        if let Some(node_info) = &self.node_info {
+            ret = ret.with_entry(
+                "supported CCs",
+                node_info
+                    .supported_command_classes
+                    .iter()
+                    .map(|cc| cc.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            );
+            if !node_info.controlled_command_classes.is_empty() {
+                ret = ret.with_entry(
+                    "controlled CCs",
+                    node_info
+                        .controlled_command_classes
+                        .iter()
+                        .map(|cc| cc.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+            }
+        }
+        ret.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::AddNodeToNetworkCallback, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    use super::{AddNodeStatus, AddNodeToNetworkRequest, AddNodeType};
+
+    #[test]
+    fn test_serialize_request() {
+        let mut cmd = AddNodeToNetworkRequest::builder()
+            .add_node_type(AddNodeType::Any)
+            .build();
+        cmd.set_callback_id(Some(0x12));
+        let ctx = CommandEncodingContext::default();
+        let raw = Into::<Command>::into(cmd).as_bytes(&ctx);
+        assert_eq!(&raw, vec![0xc1, 0x12].as_slice());
+
+        let raw = Into::<Command>::into(AddNodeToNetworkRequest::stop()).as_bytes(&ctx);
+        assert_eq!(&raw, vec![0xc5, 0x00].as_slice());
+    }
+
+    #[test]
+    fn test_parse_callback() {
+        let input: Vec<u8> = vec![
+            0x12, // callback ID
+            0x03, // adding end node
+            0x05, // node ID
+            0x05, // length
+            0x04, // basic device type
+            0x10, // generic device class
+            0x01, // specific device class
+            0x25, // Binary Switch CC
+            0x86, // Version CC
+        ];
+        let mut input = Bytes::from(input);
+        let actual =
+            AddNodeToNetworkCallback::parse(&mut input, CommandParsingContext::default()).unwrap();
+        assert_eq!(actual.callback_id(), Some(0x12));
+        assert_eq!(actual.status, AddNodeStatus::AddingEndNode);
+        assert_eq!(actual.node_id, NodeId::new(5u8));
+        assert_eq!(
+            actual.node_info.unwrap().supported_command_classes,
+            vec![CommandClasses::BinarySwitch, CommandClasses::Version]
+        );
+    }
+
+    #[test]
+    fn test_parse_callback_with_controlled_ccs() {
+        let input: Vec<u8> = vec![
+            0x12, // callback ID
+            0x03, // adding end node
+            0x05, // node ID
+            0x07, // length
+            0x04, // basic device type
+            0x10, // generic device class
+            0x01, // specific device class
+            0x25, // Binary Switch CC
+            0xef, // support/control mark
+            0x20, // Basic CC
+            0x86, // Version CC
+        ];
+        let mut input = Bytes::from(input);
+        let node_info = AddNodeToNetworkCallback::parse(&mut input, CommandParsingContext::default())
+            .unwrap()
+            .node_info
+            .unwrap();
+        assert_eq!(
+            node_info.supported_command_classes,
+            vec![CommandClasses::BinarySwitch]
+        );
+        assert_eq!(
+            node_info.controlled_command_classes,
+            vec![CommandClasses::Basic, CommandClasses::Version]
+        );
+    }
+
+    #[test]
+    fn test_callback_roundtrip() {
+        let ctx = CommandEncodingContext::default();
+        for raw in [
+            vec![0x12, 0x03, 0x05, 0x06, 0x04, 0x10, 0x01, 0x25, 0xef, 0x20],
+            vec![0x12, 0x02, 0x00, 0x00],
+        ] {
+            let cmd = AddNodeToNetworkCallback::parse(
+                &mut Bytes::from(raw.clone()),
+                CommandParsingContext::default(),
+            )
+            .unwrap();
+            assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_parse_callback_without_node_info() {
+        let mut input = Bytes::from(vec![0x12, 0x02, 0x00, 0x00]);
+        let actual =
+            AddNodeToNetworkCallback::parse(&mut input, CommandParsingContext::default()).unwrap();
+        assert_eq!(actual.status, AddNodeStatus::NodeFound);
+        assert_eq!(actual.node_id, NodeId::unspecified());
+        assert_eq!(actual.node_info, None);
+    }
+}
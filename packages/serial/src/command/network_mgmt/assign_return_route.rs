@@ -0,0 +1,180 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{bytes::be_u8, combinators::map};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+#[derive(Default, Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response, callback, callback_id)]
+pub struct AssignReturnRouteRequest {
+    /// The node that receives the return route
+    node_id: NodeId,
+    /// The node the return route leads to
+    destination_node_id: NodeId,
+    #[builder(setter(skip), default)]
+    callback_id: Option<u8>,
+}
+
+impl CommandId for AssignReturnRouteRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::AssignReturnRoute
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for AssignReturnRouteRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for AssignReturnRouteRequest {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let node_id = NodeId::parse(i, ctx.node_id_type)?;
+        let destination_node_id = NodeId::parse(i, ctx.node_id_type)?;
+        let callback_id = be_u8(i)?;
+        Ok(Self {
+            node_id,
+            destination_node_id,
+            callback_id: Some(callback_id),
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for AssignReturnRouteRequest {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        self.node_id.serialize(output, ctx.node_id_type);
+        self.destination_node_id.serialize(output, ctx.node_id_type);
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+    }
+}
+
+impl ToLogPayload for AssignReturnRouteRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new()
+            .with_entry("node ID", self.node_id.to_string())
+            .with_entry("destination node ID", self.destination_node_id.to_string());
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssignReturnRouteResponse {
+    was_executed: bool,
+}
+
+impl CommandId for AssignReturnRouteResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::AssignReturnRoute
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for AssignReturnRouteResponse {
+    fn is_ok(&self) -> bool {
+        self.was_executed
+    }
+}
+
+impl CommandParsable for AssignReturnRouteResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let was_executed = map(be_u8, |x| x > 0).parse(i)?;
+        Ok(Self { was_executed })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for AssignReturnRouteResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(if self.was_executed { 0x01 } else { 0x00 }).serialize(output)
+    }
+}
+
+impl ToLogPayload for AssignReturnRouteResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("was executed", self.was_executed)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssignReturnRouteCallback {
+    callback_id: Option<u8>,
+    pub transmit_status: TransmitStatus,
+}
+
+impl CommandId for AssignReturnRouteCallback {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::AssignReturnRoute
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for AssignReturnRouteCallback {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+
+    fn is_ok(&self) -> bool {
+        self.transmit_status == TransmitStatus::Ok
+    }
+}
+
+impl CommandParsable for AssignReturnRouteCallback {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let callback_id = be_u8(i)?;
+        let transmit_status = TransmitStatus::parse(i)?;
+        Ok(Self {
+            callback_id: Some(callback_id),
+            transmit_status,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for AssignReturnRouteCallback {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+        tuple((be_u8(self.callback_id.unwrap_or(0)), self.transmit_status)).serialize(output)
+    }
+}
+
+impl ToLogPayload for AssignReturnRouteCallback {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.with_entry("transmit status", self.transmit_status.to_string())
+            .into()
+    }
+}
@@ -0,0 +1,203 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_core::bitvec::{build_bitmask, iter_ones};
+use zwave_core::parse::{bytes::be_u8, combinators::map, multi::length_data};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+/// The first node ID that is reserved for Long Range nodes
+const FIRST_LONG_RANGE_NODE_ID: u16 = 256;
+/// How many bytes of the Long Range node bitmask are transmitted per segment
+const BITMASK_SEGMENT_SIZE: u16 = 128;
+
+/// Returns the first node ID that is contained in the given segment of the Long Range node bitmask
+fn segment_offset(segment_number: u8) -> u16 {
+    FIRST_LONG_RANGE_NODE_ID + segment_number as u16 * BITMASK_SEGMENT_SIZE * 8
+}
+
+#[derive(Default, Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response)]
+pub struct GetLongRangeNodesRequest {
+    /// Which segment of the Long Range node bitmask to request
+    #[builder(default)]
+    segment_number: u8,
+}
+
+impl CommandId for GetLongRangeNodesRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetLongRangeNodes
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for GetLongRangeNodesRequest {}
+
+impl CommandParsable for GetLongRangeNodesRequest {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let segment_number = be_u8(i)?;
+        Ok(Self { segment_number })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetLongRangeNodesRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(self.segment_number).serialize(output);
+    }
+}
+
+impl ToLogPayload for GetLongRangeNodesRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("segment number", self.segment_number)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetLongRangeNodesResponse {
+    /// Whether the controller has more segments of the bitmask
+    pub more_nodes: bool,
+    pub segment_number: u8,
+    pub node_ids: Vec<NodeId>,
+}
+
+impl CommandId for GetLongRangeNodesResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetLongRangeNodes
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for GetLongRangeNodesResponse {}
+
+impl CommandParsable for GetLongRangeNodesResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let more_nodes = map(be_u8, |x| x > 0).parse(i)?;
+        let segment_number = be_u8(i)?;
+        let bitmask = length_data(be_u8).parse(i)?;
+
+        let offset = segment_offset(segment_number);
+        let node_ids = iter_ones(&bitmask)
+            .map(|index| NodeId::new(offset + index as u16))
+            .collect();
+
+        Ok(Self {
+            more_nodes,
+            segment_number,
+            node_ids,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetLongRangeNodesResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::{be_u8, slice};
+
+        let offset = segment_offset(self.segment_number);
+        let indizes = self
+            .node_ids
+            .iter()
+            .filter_map(|n| u16::from(*n).checked_sub(offset))
+            .map(|index| index as usize)
+            .filter(|index| *index < BITMASK_SEGMENT_SIZE as usize * 8)
+            .collect::<Vec<_>>();
+        let bit_len = indizes.iter().max().map_or(0, |max| max + 1);
+        let bitmask = build_bitmask(&indizes, bit_len);
+
+        be_u8(if self.more_nodes { 1 } else { 0 }).serialize(output);
+        be_u8(self.segment_number).serialize(output);
+        be_u8(bitmask.len() as u8).serialize(output);
+        slice(&bitmask).serialize(output);
+    }
+}
+
+impl ToLogPayload for GetLongRangeNodesResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("segment number", self.segment_number)
+            .with_entry("more nodes", self.more_nodes)
+            .with_entry(
+                "Long Range nodes",
+                self.node_ids
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetLongRangeNodesResponse, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_serialize() {
+        let cmd = GetLongRangeNodesResponse {
+            more_nodes: true,
+            segment_number: 1,
+            node_ids: vec![1280u16, 1283, 1289]
+                .into_iter()
+                .map(NodeId::new)
+                .collect(),
+        };
+        let ctx = CommandEncodingContext::default();
+        let raw = Into::<Command>::into(cmd).as_bytes(&ctx);
+        assert_eq!(
+            &raw,
+            vec![
+                0x01,        // more nodes
+                0x01,        // segment number
+                2,           // bitmask length
+                0b0000_1001, // node 1280, 1283
+                0b0000_0010, // node 1289
+            ]
+            .as_slice()
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        let input: Vec<u8> = vec![
+            0x00,        // no more nodes
+            0x00,        // segment number
+            3,           // bitmask length
+            0b0000_0011, // node 256, 257
+            0b0000_0000,
+            0b1000_0000, // node 279
+        ];
+        let mut input = Bytes::from(input);
+        let expected = GetLongRangeNodesResponse {
+            more_nodes: false,
+            segment_number: 0,
+            node_ids: vec![256u16, 257, 279]
+                .into_iter()
+                .map(NodeId::new)
+                .collect(),
+        };
+        let actual =
+            GetLongRangeNodesResponse::parse(&mut input, CommandParsingContext::default()).unwrap();
+        assert_eq!(actual, expected)
+    }
+}
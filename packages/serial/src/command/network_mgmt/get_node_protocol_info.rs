@@ -1,9 +1,11 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use zwave_core::prelude::*;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetNodeProtocolInfoRequest {
     pub node_id: NodeId,
 }
@@ -24,16 +26,6 @@ impl CommandId for GetNodeProtocolInfoRequest {
 
 impl CommandBase for GetNodeProtocolInfoRequest {}
 
-impl CommandRequest for GetNodeProtocolInfoRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetNodeProtocolInfoRequest {
     fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
         let node_id = NodeId::parse(i, ctx.node_id_type)?;
@@ -84,8 +76,8 @@ impl CommandParsable for GetNodeProtocolInfoResponse {
 }
 
 impl SerializableWith<&CommandEncodingContext> for GetNodeProtocolInfoResponse {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: GetNodeProtocolInfoResponse::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        self.protocol_info.serialize(output)
     }
 }
 
@@ -128,3 +120,31 @@ impl ToLogPayload for GetNodeProtocolInfoResponse {
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetNodeProtocolInfoResponse, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_response_roundtrip() {
+        let raw = vec![
+            0xd3, // listening, routing, 40k, V6
+            0x9c, // optional functionality, beaming, end node, specific device class
+            0x01, // 100k
+            0x04, 0x10, 0x01, // device classes
+        ];
+        let cmd = GetNodeProtocolInfoResponse::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert!(cmd.protocol_info.listening);
+        assert_eq!(cmd.protocol_info.protocol_version, ProtocolVersion::V6);
+        assert_eq!(cmd.protocol_info.specific_device_class, Some(0x01));
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{bytes::be_u8, multi::fixed_length_bitmask_u8};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+/// The length of the node bitmask in the response, which covers all classic node IDs
+const NODE_BITMASK_LEN: usize = 29;
+
+/// Asks the controller which nodes are neighbors of the given node
+#[derive(Default, Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response)]
+pub struct GetRoutingInfoRequest {
+    pub node_id: NodeId,
+    /// Whether to leave out neighbors that cannot act as repeaters
+    #[builder(default)]
+    pub remove_non_repeaters: bool,
+    /// Whether to leave out neighbors whose links are known to be bad
+    #[builder(default)]
+    pub remove_bad_links: bool,
+}
+
+impl CommandId for GetRoutingInfoRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetRoutingInfo
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for GetRoutingInfoRequest {}
+
+impl CommandParsable for GetRoutingInfoRequest {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let node_id = NodeId::parse(i, ctx.node_id_type)?;
+        let remove_non_repeaters = be_u8(i)? > 0;
+        let remove_bad_links = be_u8(i)? > 0;
+        let _reserved = be_u8(i)?;
+        Ok(Self {
+            node_id,
+            remove_non_repeaters,
+            remove_bad_links,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetRoutingInfoRequest {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+
+        self.node_id.serialize(output, ctx.node_id_type);
+        tuple((
+            be_u8(self.remove_non_repeaters as u8),
+            be_u8(self.remove_bad_links as u8),
+            be_u8(0),
+        ))
+        .serialize(output);
+    }
+}
+
+impl ToLogPayload for GetRoutingInfoRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("node ID", self.node_id.to_string())
+            .with_entry("remove non-repeaters", self.remove_non_repeaters)
+            .with_entry("remove bad links", self.remove_bad_links)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetRoutingInfoResponse {
+    pub neighbors: Vec<NodeId>,
+}
+
+impl CommandId for GetRoutingInfoResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetRoutingInfo
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for GetRoutingInfoResponse {}
+
+impl CommandParsable for GetRoutingInfoResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let neighbors = fixed_length_bitmask_u8(i, 1, NODE_BITMASK_LEN)?;
+        Ok(Self {
+            neighbors: neighbors.into_iter().map(NodeId::new).collect(),
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetRoutingInfoResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::slice;
+
+        let indices: Vec<usize> = self
+            .neighbors
+            .iter()
+            .map(|node_id| u16::from(*node_id) as usize - 1)
+            .collect();
+        let bitmask = zwave_core::bitvec::build_bitmask(&indices, NODE_BITMASK_LEN * 8);
+        slice(&bitmask).serialize(output);
+    }
+}
+
+impl ToLogPayload for GetRoutingInfoResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        let neighbors = self
+            .neighbors
+            .iter()
+            .map(|node_id| node_id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        LogPayloadDict::new()
+            .with_entry("neighbors", neighbors)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetRoutingInfoResponse, prelude::*};
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_parse_neighbors() {
+        let mut input = vec![0u8; 29];
+        // Nodes 1, 3 and 10
+        input[0] = 0b0000_0101;
+        input[1] = 0b0000_0010;
+        let cmd =
+            GetRoutingInfoResponse::parse(&mut input.into(), CommandParsingContext::default())
+                .unwrap();
+        assert_eq!(
+            cmd.neighbors,
+            vec![1u8, 3, 10]
+                .into_iter()
+                .map(NodeId::new)
+                .collect::<Vec<_>>()
+        );
+    }
+}
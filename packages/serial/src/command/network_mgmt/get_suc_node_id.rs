@@ -1,9 +1,11 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use zwave_core::prelude::*;
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
 pub struct GetSucNodeIdRequest {}
 
 impl CommandId for GetSucNodeIdRequest {
@@ -22,16 +24,6 @@ impl CommandId for GetSucNodeIdRequest {
 
 impl CommandBase for GetSucNodeIdRequest {}
 
-impl CommandRequest for GetSucNodeIdRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        false
-    }
-}
-
 impl CommandParsable for GetSucNodeIdRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         // No payload
@@ -93,7 +85,6 @@ impl SerializableWith<&CommandEncodingContext> for GetSucNodeIdResponse {
     }
 }
 
-
 impl ToLogPayload for GetSucNodeIdResponse {
     fn to_log_payload(&self) -> LogPayload {
         if let Some(suc_node_id) = self.suc_node_id {
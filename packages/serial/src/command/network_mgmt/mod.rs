@@ -4,3 +4,11 @@ submodule!(get_suc_node_id);
 submodule!(set_suc_node_id);
 submodule!(get_node_protocol_info);
 submodule!(request_node_info);
+submodule!(get_long_range_nodes);
+submodule!(add_node_to_network);
+submodule!(remove_node_from_network);
+submodule!(set_learn_mode);
+submodule!(assign_return_route);
+submodule!(request_node_neighbor_update);
+submodule!(send_node_information);
+submodule!(get_routing_info);
@@ -0,0 +1,262 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CommandRequest, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{
+    bytes::be_u8,
+    combinators::{context, map_res},
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+const OPTION_HIGH_POWER: u8 = 0x80;
+const OPTION_NETWORK_WIDE: u8 = 0x40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum RemoveNodeType {
+    Any = 0x01,
+    Controller = 0x02,
+    EndNode = 0x03,
+    Stop = 0x05,
+}
+
+impl Display for RemoveNodeType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RemoveNodeType::Any => write!(f, "Any"),
+            RemoveNodeType::Controller => write!(f, "Controller"),
+            RemoveNodeType::EndNode => write!(f, "End node"),
+            RemoveNodeType::Stop => write!(f, "Stop"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum RemoveNodeStatus {
+    LearnReady = 0x01,
+    NodeFound = 0x02,
+    RemovingEndNode = 0x03,
+    RemovingController = 0x04,
+    Done = 0x06,
+    Failed = 0x07,
+}
+
+impl Display for RemoveNodeStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RemoveNodeStatus::LearnReady => write!(f, "Ready"),
+            RemoveNodeStatus::NodeFound => write!(f, "Node found"),
+            RemoveNodeStatus::RemovingEndNode => write!(f, "Removing end node"),
+            RemoveNodeStatus::RemovingController => write!(f, "Removing controller"),
+            RemoveNodeStatus::Done => write!(f, "Done"),
+            RemoveNodeStatus::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+impl Parsable for RemoveNodeStatus {
+    fn parse(i: &mut Bytes) -> ParseResult<Self> {
+        context("RemoveNodeStatus", map_res(be_u8, Self::try_from)).parse(i)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(
+    // Stopping is done without a callback, so it also works when the controller is not excluding
+    callback,
+    expects_callback = self.remove_node_type != RemoveNodeType::Stop,
+    callback_id,
+    needs_callback_id = self.expects_callback(),
+)]
+pub struct RemoveNodeFromNetworkRequest {
+    remove_node_type: RemoveNodeType,
+    #[builder(default = true)]
+    high_power: bool,
+    #[builder(default = true)]
+    network_wide: bool,
+    #[builder(setter(skip), default)]
+    callback_id: Option<u8>,
+}
+
+impl RemoveNodeFromNetworkRequest {
+    /// Creates a request to stop the exclusion process, which the controller does not answer
+    pub fn stop() -> Self {
+        Self::builder()
+            .remove_node_type(RemoveNodeType::Stop)
+            .build()
+    }
+}
+
+impl CommandId for RemoveNodeFromNetworkRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::RemoveNodeFromNetwork
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for RemoveNodeFromNetworkRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for RemoveNodeFromNetworkRequest {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let mode = be_u8(i)?;
+        let callback_id = be_u8(i)?;
+        let remove_node_type = RemoveNodeType::try_from(mode & 0x0f)?;
+        Ok(Self {
+            remove_node_type,
+            high_power: mode & OPTION_HIGH_POWER != 0,
+            network_wide: mode & OPTION_NETWORK_WIDE != 0,
+            callback_id: Some(callback_id),
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for RemoveNodeFromNetworkRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        let mut mode = self.remove_node_type as u8;
+        if self.high_power {
+            mode |= OPTION_HIGH_POWER;
+        }
+        if self.network_wide {
+            mode |= OPTION_NETWORK_WIDE;
+        }
+        be_u8(mode).serialize(output);
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+    }
+}
+
+impl ToLogPayload for RemoveNodeFromNetworkRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new()
+            .with_entry("action", self.remove_node_type.to_string())
+            .with_entry("high power", self.high_power)
+            .with_entry("network wide", self.network_wide);
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoveNodeFromNetworkCallback {
+    callback_id: Option<u8>,
+    pub status: RemoveNodeStatus,
+    pub node_id: NodeId,
+}
+
+impl CommandId for RemoveNodeFromNetworkCallback {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::RemoveNodeFromNetwork
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for RemoveNodeFromNetworkCallback {
+    fn is_ok(&self) -> bool {
+        self.status != RemoveNodeStatus::Failed
+    }
+
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for RemoveNodeFromNetworkCallback {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let callback_id = be_u8(i)?;
+        let status = RemoveNodeStatus::parse(i)?;
+        // The node ID is only included in some of the status updates. The node information
+        // that may follow is not needed to remove the node.
+        let node_id = if i.is_empty() {
+            NodeId::unspecified()
+        } else {
+            NodeId::parse(i, ctx.node_id_type)?
+        };
+        i.clear();
+
+        Ok(Self {
+            callback_id: Some(callback_id),
+            status,
+            node_id,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for RemoveNodeFromNetworkCallback {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+        be_u8(self.status as u8).serialize(output);
+        self.node_id.serialize(output, ctx.node_id_type);
+        // The node information is discarded when parsing, so it is always empty
+        be_u8(0).serialize(output);
+    }
+}
+
+impl ToLogPayload for RemoveNodeFromNetworkCallback {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret = ret.with_entry("status", self.status.to_string());
+        if self.node_id != NodeId::unspecified() {
+            ret = ret.with_entry("node ID", self.node_id.to_string());
+        }
+        ret.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::RemoveNodeFromNetworkCallback, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    use super::RemoveNodeStatus;
+
+    #[test]
+    fn test_callback_roundtrip() {
+        let raw = vec![
+            0x12, // callback ID
+            0x06, // done
+            0x05, // node ID
+            0x00, // no node information
+        ];
+        let cmd = RemoveNodeFromNetworkCallback::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.status, RemoveNodeStatus::Done);
+        assert_eq!(cmd.node_id, NodeId::new(5u8));
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
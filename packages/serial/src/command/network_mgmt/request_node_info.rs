@@ -1,12 +1,19 @@
-use crate::command::ApplicationUpdateRequestPayload;
+use crate::command::{ApplicationUpdateRequest, ApplicationUpdateRequestPayload};
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use typed_builder::TypedBuilder;
 use zwave_core::serialize;
 use zwave_core::parse::{bytes::be_u8, combinators::map};
 use zwave_core::prelude::*;
 
-#[derive(Default, Debug, Clone, PartialEq, TypedBuilder)]
+#[derive(Default, Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(
+    response,
+    // The callback for this comes in an ApplicationUpdateRequest
+    callback = ApplicationUpdateRequest,
+    callback_if = self.is_node_info_callback(callback),
+)]
 pub struct RequestNodeInfoRequest {
     node_id: NodeId,
 }
@@ -15,6 +22,16 @@ impl RequestNodeInfoRequest {
     pub fn new(node_id: NodeId) -> Self {
         Self { node_id }
     }
+
+    fn is_node_info_callback(&self, update: &ApplicationUpdateRequest) -> bool {
+        match &update.payload {
+            ApplicationUpdateRequestPayload::NodeInfoReceived { node_id, .. } => {
+                node_id == &self.node_id
+            }
+            ApplicationUpdateRequestPayload::NodeInfoRequestFailed => true,
+            _ => false,
+        }
+    }
 }
 
 impl CommandId for RequestNodeInfoRequest {
@@ -33,31 +50,6 @@ impl CommandId for RequestNodeInfoRequest {
 
 impl CommandBase for RequestNodeInfoRequest {}
 
-impl CommandRequest for RequestNodeInfoRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        true
-    }
-
-    fn test_callback(&self, callback: &Command) -> bool {
-        // The callback for this comes in an ApplicationUpdateRequest
-        let Command::ApplicationUpdateRequest(callback) = callback else {
-            return false;
-        };
-
-        match &callback.payload {
-            ApplicationUpdateRequestPayload::NodeInfoReceived { node_id, .. } => {
-                node_id == &self.node_id
-            }
-            ApplicationUpdateRequestPayload::NodeInfoRequestFailed => true,
-            _ => false,
-        }
-    }
-}
-
 impl CommandParsable for RequestNodeInfoRequest {
     fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
         let node_id = NodeId::parse(i, ctx.node_id_type)?;
@@ -0,0 +1,190 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CommandRequest, TryFromRepr};
+use zwave_core::parse::{
+    bytes::be_u8,
+    combinators::{context, map_res},
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum NodeNeighborUpdateStatus {
+    UpdateStarted = 0x21,
+    UpdateDone = 0x22,
+    UpdateFailed = 0x23,
+}
+
+impl Display for NodeNeighborUpdateStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NodeNeighborUpdateStatus::UpdateStarted => write!(f, "Started"),
+            NodeNeighborUpdateStatus::UpdateDone => write!(f, "Done"),
+            NodeNeighborUpdateStatus::UpdateFailed => write!(f, "Failed"),
+        }
+    }
+}
+
+impl Parsable for NodeNeighborUpdateStatus {
+    fn parse(i: &mut Bytes) -> ParseResult<Self> {
+        context("NodeNeighborUpdateStatus", map_res(be_u8, Self::try_from)).parse(i)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(
+    // The controller first reports that the update was started. Only the final status
+    // is interesting.
+    callback = RequestNodeNeighborUpdateCallback,
+    callback_id,
+    callback_if = callback.status != NodeNeighborUpdateStatus::UpdateStarted,
+)]
+pub struct RequestNodeNeighborUpdateRequest {
+    node_id: NodeId,
+    callback_id: Option<u8>,
+}
+
+impl RequestNodeNeighborUpdateRequest {
+    pub fn new(node_id: NodeId) -> Self {
+        Self {
+            node_id,
+            callback_id: None,
+        }
+    }
+}
+
+impl CommandId for RequestNodeNeighborUpdateRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::RequestNodeNeighborUpdate
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for RequestNodeNeighborUpdateRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for RequestNodeNeighborUpdateRequest {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let node_id = NodeId::parse(i, ctx.node_id_type)?;
+        let callback_id = be_u8(i)?;
+        Ok(Self {
+            node_id,
+            callback_id: Some(callback_id),
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for RequestNodeNeighborUpdateRequest {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        self.node_id.serialize(output, ctx.node_id_type);
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+    }
+}
+
+impl ToLogPayload for RequestNodeNeighborUpdateRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new().with_entry("node ID", self.node_id.to_string());
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestNodeNeighborUpdateCallback {
+    callback_id: Option<u8>,
+    pub status: NodeNeighborUpdateStatus,
+}
+
+impl CommandId for RequestNodeNeighborUpdateCallback {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::RequestNodeNeighborUpdate
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for RequestNodeNeighborUpdateCallback {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+
+    fn is_ok(&self) -> bool {
+        self.status != NodeNeighborUpdateStatus::UpdateFailed
+    }
+}
+
+impl CommandParsable for RequestNodeNeighborUpdateCallback {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let callback_id = be_u8(i)?;
+        let status = NodeNeighborUpdateStatus::parse(i)?;
+        Ok(Self {
+            callback_id: Some(callback_id),
+            status,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for RequestNodeNeighborUpdateCallback {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+        be_u8(self.status as u8).serialize(output);
+    }
+}
+
+impl ToLogPayload for RequestNodeNeighborUpdateCallback {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.with_entry("status", self.status.to_string()).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_callback_after_update_started() {
+        let mut request = RequestNodeNeighborUpdateRequest::new(NodeId::new(2u8));
+        assert!(request.needs_callback_id());
+        request.set_callback_id(Some(7));
+
+        let callback = |callback_id, status| {
+            Command::RequestNodeNeighborUpdateCallback(RequestNodeNeighborUpdateCallback {
+                callback_id: Some(callback_id),
+                status,
+            })
+        };
+
+        assert!(!request.expects_response());
+        assert!(!request.test_callback(&callback(7, NodeNeighborUpdateStatus::UpdateStarted)));
+        assert!(request.test_callback(&callback(7, NodeNeighborUpdateStatus::UpdateDone)));
+        assert!(!request.test_callback(&callback(8, NodeNeighborUpdateStatus::UpdateDone)));
+    }
+}
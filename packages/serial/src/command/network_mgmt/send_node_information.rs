@@ -0,0 +1,212 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{bytes::be_u8, combinators::map};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+/// Instructs the controller to send its node information frame to a node or all nodes
+#[derive(Default, Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response, callback, callback_id)]
+pub struct SendNodeInformationRequest {
+    /// The node that receives the node information. Can be the broadcast node ID.
+    destination_node_id: NodeId,
+    #[builder(default)]
+    transmit_options: TransmitOptions,
+    #[builder(setter(skip), default)]
+    callback_id: Option<u8>,
+}
+
+impl CommandId for SendNodeInformationRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SendNodeInformation
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for SendNodeInformationRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for SendNodeInformationRequest {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let destination_node_id = NodeId::parse(i, ctx.node_id_type)?;
+        let transmit_options = TransmitOptions::parse(i)?;
+        let callback_id = be_u8(i)?;
+        Ok(Self {
+            destination_node_id,
+            transmit_options,
+            callback_id: Some(callback_id),
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SendNodeInformationRequest {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        self.destination_node_id.serialize(output, ctx.node_id_type);
+        self.transmit_options.serialize(output);
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+    }
+}
+
+impl ToLogPayload for SendNodeInformationRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let destination = if self.destination_node_id == NodeId::broadcast() {
+            "broadcast".to_string()
+        } else {
+            self.destination_node_id.to_string()
+        };
+        let mut ret = LogPayloadDict::new()
+            .with_entry("destination", destination)
+            .with_entry("transmit options", self.transmit_options.to_string());
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SendNodeInformationResponse {
+    was_sent: bool,
+}
+
+impl CommandId for SendNodeInformationResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SendNodeInformation
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for SendNodeInformationResponse {
+    fn is_ok(&self) -> bool {
+        self.was_sent
+    }
+}
+
+impl CommandParsable for SendNodeInformationResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let was_sent = map(be_u8, |x| x > 0).parse(i)?;
+        Ok(Self { was_sent })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SendNodeInformationResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(if self.was_sent { 0x01 } else { 0x00 }).serialize(output)
+    }
+}
+
+impl ToLogPayload for SendNodeInformationResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("was sent", self.was_sent)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SendNodeInformationCallback {
+    callback_id: Option<u8>,
+    pub transmit_status: TransmitStatus,
+}
+
+impl CommandId for SendNodeInformationCallback {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SendNodeInformation
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for SendNodeInformationCallback {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+
+    fn is_ok(&self) -> bool {
+        self.transmit_status == TransmitStatus::Ok
+    }
+}
+
+impl CommandParsable for SendNodeInformationCallback {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let callback_id = be_u8(i)?;
+        let transmit_status = TransmitStatus::parse(i)?;
+        Ok(Self {
+            callback_id: Some(callback_id),
+            transmit_status,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SendNodeInformationCallback {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+        tuple((be_u8(self.callback_id.unwrap_or(0)), self.transmit_status)).serialize(output)
+    }
+}
+
+impl ToLogPayload for SendNodeInformationCallback {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.with_entry("transmit status", self.transmit_status.to_string())
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::SendNodeInformationRequest, prelude::*};
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_serialize_broadcast() {
+        let mut cmd = SendNodeInformationRequest::builder()
+            .destination_node_id(NodeId::broadcast())
+            .transmit_options(TransmitOptions::new())
+            .build();
+        cmd.set_callback_id(Some(0x07));
+        let ctx = CommandEncodingContext::default();
+        let raw = Into::<Command>::into(cmd).as_bytes(&ctx);
+        assert_eq!(
+            &raw,
+            vec![
+                0xff, // broadcast
+                0x00, // no transmit options
+                0x07, // callback ID
+            ]
+            .as_slice()
+        )
+    }
+}
@@ -0,0 +1,288 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use core::fmt::Display;
+use proc_macros::{CommandRequest, TryFromRepr};
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{
+    bytes::be_u8,
+    combinators::{context, map, map_res},
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum LearnModeIntent {
+    Stop = 0x00,
+    /// Joins or leaves a network, depending on whether the controller is part of one.
+    /// The other controller must be in direct range.
+    Direct = 0x01,
+    /// Joins a network through the whole network
+    NetworkWideInclusion = 0x02,
+    /// Leaves a network through the whole network
+    NetworkWideExclusion = 0x03,
+}
+
+impl Display for LearnModeIntent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LearnModeIntent::Stop => write!(f, "Stop"),
+            LearnModeIntent::Direct => write!(f, "Direct"),
+            LearnModeIntent::NetworkWideInclusion => write!(f, "Network-wide inclusion"),
+            LearnModeIntent::NetworkWideExclusion => write!(f, "Network-wide exclusion"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromRepr)]
+#[repr(u8)]
+pub enum LearnModeStatus {
+    Started = 0x01,
+    Done = 0x06,
+    Failed = 0x07,
+}
+
+impl Display for LearnModeStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LearnModeStatus::Started => write!(f, "Started"),
+            LearnModeStatus::Done => write!(f, "Done"),
+            LearnModeStatus::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+impl Parsable for LearnModeStatus {
+    fn parse(i: &mut Bytes) -> ParseResult<Self> {
+        context("LearnModeStatus", map_res(be_u8, Self::try_from)).parse(i)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(
+    response,
+    // The status updates can take minutes, so they are awaited separately.
+    // Without a callback ID, the controller does not send them.
+    callback_id,
+    needs_callback_id = self.intent != LearnModeIntent::Stop,
+)]
+pub struct SetLearnModeRequest {
+    intent: LearnModeIntent,
+    #[builder(setter(skip), default)]
+    callback_id: Option<u8>,
+}
+
+impl SetLearnModeRequest {
+    /// Creates a request to stop the learn mode
+    pub fn stop() -> Self {
+        Self::builder().intent(LearnModeIntent::Stop).build()
+    }
+}
+
+impl CommandId for SetLearnModeRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SetLearnMode
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for SetLearnModeRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for SetLearnModeRequest {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let intent = map_res(be_u8, LearnModeIntent::try_from).parse(i)?;
+        let callback_id = be_u8(i)?;
+        Ok(Self {
+            intent,
+            callback_id: Some(callback_id),
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SetLearnModeRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::{bytes::be_u8, sequence::tuple};
+
+        tuple((
+            be_u8(self.intent as u8),
+            be_u8(self.callback_id.unwrap_or(0)),
+        ))
+        .serialize(output)
+    }
+}
+
+impl ToLogPayload for SetLearnModeRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new().with_entry("intent", self.intent.to_string());
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetLearnModeResponse {
+    success: bool,
+}
+
+impl CommandId for SetLearnModeResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SetLearnMode
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for SetLearnModeResponse {
+    fn is_ok(&self) -> bool {
+        self.success
+    }
+}
+
+impl CommandParsable for SetLearnModeResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let success = map(be_u8, |x| x > 0).parse(i)?;
+        Ok(Self { success })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SetLearnModeResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(if self.success { 0x01 } else { 0x00 }).serialize(output)
+    }
+}
+
+impl ToLogPayload for SetLearnModeResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("success", self.success)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetLearnModeCallback {
+    callback_id: Option<u8>,
+    pub status: LearnModeStatus,
+    /// The node ID the controller was assigned. This is 0 when the controller left a network.
+    pub assigned_node_id: NodeId,
+}
+
+impl CommandId for SetLearnModeCallback {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::SetLearnMode
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for SetLearnModeCallback {
+    fn is_ok(&self) -> bool {
+        self.status != LearnModeStatus::Failed
+    }
+
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for SetLearnModeCallback {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let callback_id = be_u8(i)?;
+        let status = LearnModeStatus::parse(i)?;
+        let assigned_node_id = if i.is_empty() {
+            NodeId::unspecified()
+        } else {
+            NodeId::parse(i, ctx.node_id_type)?
+        };
+        // The status message that may follow is not needed
+        i.clear();
+
+        Ok(Self {
+            callback_id: Some(callback_id),
+            status,
+            assigned_node_id,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for SetLearnModeCallback {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+        be_u8(self.status as u8).serialize(output);
+        self.assigned_node_id.serialize(output, ctx.node_id_type);
+        // The status message is discarded when parsing, so it is always empty
+        be_u8(0).serialize(output);
+    }
+}
+
+impl ToLogPayload for SetLearnModeCallback {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret = ret.with_entry("status", self.status.to_string());
+        if self.assigned_node_id != NodeId::unspecified() {
+            ret = ret.with_entry("assigned node ID", self.assigned_node_id.to_string());
+        }
+        ret.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::SetLearnModeCallback, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    use super::LearnModeStatus;
+
+    #[test]
+    fn test_callback_roundtrip() {
+        let raw = vec![
+            0x03, // callback ID
+            0x06, // done
+            0x02, // assigned node ID
+            0x00, // no status message
+        ];
+        let cmd = SetLearnModeCallback::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.status, LearnModeStatus::Done);
+        assert_eq!(cmd.assigned_node_id, NodeId::new(2u8));
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
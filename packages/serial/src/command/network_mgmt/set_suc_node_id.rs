@@ -1,12 +1,19 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use typed_builder::TypedBuilder;
 use zwave_core::parse::{bytes::be_u8, combinators::map, parser_not_implemented};
 use zwave_core::prelude::*;
 use zwave_core::serialize;
 
-#[derive(Default, Debug, Clone, PartialEq, TypedBuilder)]
+#[derive(Default, Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(
+    response,
+    callback,
+    expects_callback = self.suc_node_id == self.own_node_id,
+    callback_id,
+)]
 pub struct SetSucNodeIdRequest {
     // Needed for knowing whether a callback is expected
     own_node_id: NodeId,
@@ -39,24 +46,6 @@ impl CommandBase for SetSucNodeIdRequest {
     }
 }
 
-impl CommandRequest for SetSucNodeIdRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        self.suc_node_id == self.own_node_id
-    }
-
-    fn needs_callback_id(&self) -> bool {
-        true
-    }
-
-    fn set_callback_id(&mut self, callback_id: Option<u8>) {
-        self.callback_id = callback_id;
-    }
-}
-
 impl CommandParsable for SetSucNodeIdRequest {
     fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
         parser_not_implemented("ERROR: SetSucNodeIdRequest::parse() not implemented")
@@ -181,8 +170,11 @@ impl CommandParsable for SetSucNodeIdCallback {
 }
 
 impl SerializableWith<&CommandEncodingContext> for SetSucNodeIdCallback {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: SetSucNodeIdCallback::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+        be_u8(if self.success { 0x05 } else { 0x06 }).serialize(output);
     }
 }
 
@@ -193,3 +185,24 @@ impl ToLogPayload for SetSucNodeIdCallback {
             .into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::SetSucNodeIdCallback, prelude::*};
+    use bytes::Bytes;
+
+    #[test]
+    fn test_callback_roundtrip() {
+        for raw in [[0x02, 0x05], [0x02, 0x06]] {
+            let cmd = SetSucNodeIdCallback::parse(
+                &mut Bytes::copy_from_slice(&raw),
+                CommandParsingContext::default(),
+            )
+            .unwrap();
+            assert_eq!(cmd.is_ok(), raw[1] == 0x05);
+
+            let ctx = CommandEncodingContext::default();
+            assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+        }
+    }
+}
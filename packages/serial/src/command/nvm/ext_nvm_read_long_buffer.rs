@@ -0,0 +1,127 @@
+use crate::prelude::*;
+use crate::util::with_hex_fmt;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_core::parse::bytes::{be_u16, be_u24, rest};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+/// Reads a buffer from the external NVM of a 500 series controller
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response)]
+pub struct ExtNVMReadLongBufferRequest {
+    offset: u32,
+    length: u16,
+}
+
+impl CommandId for ExtNVMReadLongBufferRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ExtNVMReadLongBuffer
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for ExtNVMReadLongBufferRequest {}
+
+impl CommandParsable for ExtNVMReadLongBufferRequest {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let offset = be_u24(i)?;
+        let length = be_u16(i)?;
+        Ok(Self { offset, length })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ExtNVMReadLongBufferRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::{be_u16, be_u24};
+
+        be_u24(self.offset).serialize(output);
+        be_u16(self.length).serialize(output);
+    }
+}
+
+impl ToLogPayload for ExtNVMReadLongBufferRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("offset", format!("{:#08x}", self.offset))
+            .with_entry("length", self.length)
+            .into()
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ExtNVMReadLongBufferResponse {
+    pub buffer: Bytes,
+}
+
+impl core::fmt::Debug for ExtNVMReadLongBufferResponse {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ExtNVMReadLongBufferResponse")
+            .field("buffer", &with_hex_fmt(&self.buffer))
+            .finish()
+    }
+}
+
+impl CommandId for ExtNVMReadLongBufferResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ExtNVMReadLongBuffer
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for ExtNVMReadLongBufferResponse {}
+
+impl CommandParsable for ExtNVMReadLongBufferResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let buffer = rest(i)?;
+        Ok(Self { buffer })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ExtNVMReadLongBufferResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::slice;
+        slice(&self.buffer).serialize(output);
+    }
+}
+
+impl ToLogPayload for ExtNVMReadLongBufferResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("buffer", format!("0x{}", hex::encode(&self.buffer)))
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::ExtNVMReadLongBufferRequest, prelude::*};
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_serialize() {
+        let cmd = ExtNVMReadLongBufferRequest::builder()
+            .offset(0x01_2345)
+            .length(64)
+            .build();
+        let ctx = CommandEncodingContext::default();
+        let raw = Into::<Command>::into(cmd).as_bytes(&ctx);
+        assert_eq!(&raw, [0x01, 0x23, 0x45, 0x00, 0x40].as_slice());
+    }
+}
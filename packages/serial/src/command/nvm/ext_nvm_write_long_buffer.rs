@@ -0,0 +1,121 @@
+use crate::prelude::*;
+use crate::util::with_hex_fmt;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_core::parse::{
+    bytes::{be_u8, be_u16, be_u24, complete::take},
+    combinators::map,
+};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+/// Writes a buffer to the external NVM of a 500 series controller
+#[derive(Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response)]
+pub struct ExtNVMWriteLongBufferRequest {
+    offset: u32,
+    #[builder(setter(into))]
+    buffer: Bytes,
+}
+
+impl core::fmt::Debug for ExtNVMWriteLongBufferRequest {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ExtNVMWriteLongBufferRequest")
+            .field("offset", &self.offset)
+            .field("buffer", &with_hex_fmt(&self.buffer))
+            .finish()
+    }
+}
+
+impl CommandId for ExtNVMWriteLongBufferRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ExtNVMWriteLongBuffer
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for ExtNVMWriteLongBufferRequest {}
+
+impl CommandParsable for ExtNVMWriteLongBufferRequest {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let offset = be_u24(i)?;
+        let length = be_u16(i)?;
+        let buffer = take(length).parse(i)?;
+        Ok(Self { offset, buffer })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ExtNVMWriteLongBufferRequest {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::{be_u16, be_u24, slice};
+
+        be_u24(self.offset).serialize(output);
+        be_u16(self.buffer.len() as u16).serialize(output);
+        slice(&self.buffer).serialize(output);
+    }
+}
+
+impl ToLogPayload for ExtNVMWriteLongBufferRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("offset", format!("{:#08x}", self.offset))
+            .with_entry("buffer", format!("0x{}", hex::encode(&self.buffer)))
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtNVMWriteLongBufferResponse {
+    success: bool,
+}
+
+impl CommandId for ExtNVMWriteLongBufferResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ExtNVMWriteLongBuffer
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for ExtNVMWriteLongBufferResponse {
+    fn is_ok(&self) -> bool {
+        self.success
+    }
+}
+
+impl CommandParsable for ExtNVMWriteLongBufferResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let success = map(be_u8, |x| x > 0).parse(i)?;
+        Ok(Self { success })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ExtNVMWriteLongBufferResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(if self.success { 1 } else { 0 }).serialize(output);
+    }
+}
+
+impl ToLogPayload for ExtNVMWriteLongBufferResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("success", self.success)
+            .into()
+    }
+}
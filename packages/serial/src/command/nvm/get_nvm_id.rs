@@ -0,0 +1,153 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use zwave_core::parse::bytes::be_u8;
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+/// The memory size code of an NVM whose size is unknown
+const NVM_SIZE_UNKNOWN: u8 = 0xff;
+
+#[derive(Default, Debug, Clone, PartialEq, CommandRequest)]
+#[command_request(response)]
+pub struct GetNVMIdRequest {}
+
+impl CommandId for GetNVMIdRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetNVMId
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for GetNVMIdRequest {}
+
+impl CommandParsable for GetNVMIdRequest {
+    fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        // No payload
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetNVMIdRequest {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for GetNVMIdRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+/// Identifies the external NVM of the controller
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetNVMIdResponse {
+    /// The JEDEC manufacturer ID of the NVM
+    pub manufacturer_id: u8,
+    /// The manufacturer-specific memory type of the NVM
+    pub memory_type: u8,
+    /// The size of the NVM in bytes, if known
+    pub memory_size: Option<u32>,
+}
+
+impl CommandId for GetNVMIdResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::GetNVMId
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandBase for GetNVMIdResponse {}
+
+impl CommandParsable for GetNVMIdResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let _reserved = be_u8(i)?;
+        let manufacturer_id = be_u8(i)?;
+        let memory_type = be_u8(i)?;
+        // The size is encoded as the base 2 logarithm of the number of bytes
+        let memory_size = match be_u8(i)? {
+            NVM_SIZE_UNKNOWN => None,
+            size => 1u32.checked_shl(size as u32),
+        };
+        Ok(Self {
+            manufacturer_id,
+            memory_type,
+            memory_size,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for GetNVMIdResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        let memory_size = match self.memory_size {
+            Some(size) if size.is_power_of_two() => size.trailing_zeros() as u8,
+            _ => NVM_SIZE_UNKNOWN,
+        };
+        be_u8(0).serialize(output);
+        be_u8(self.manufacturer_id).serialize(output);
+        be_u8(self.memory_type).serialize(output);
+        be_u8(memory_size).serialize(output);
+    }
+}
+
+impl ToLogPayload for GetNVMIdResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("manufacturer ID", format!("{:#04x}", self.manufacturer_id))
+            .with_entry("memory type", format!("{:#04x}", self.memory_type))
+            .with_entry(
+                "memory size",
+                match self.memory_size {
+                    Some(size) => format!("{} bytes", size),
+                    None => "unknown".to_string(),
+                },
+            )
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::GetNVMIdResponse, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_parse() {
+        let mut input = Bytes::from_static(&[
+            0x00, // reserved
+            0xef, // manufacturer ID
+            0x30, // memory type
+            0x11, // memory size: 128 KiB
+        ]);
+        let expected = GetNVMIdResponse {
+            manufacturer_id: 0xef,
+            memory_type: 0x30,
+            memory_size: Some(128 * 1024),
+        };
+        let actual = GetNVMIdResponse::parse(&mut input, CommandParsingContext::default()).unwrap();
+        assert_eq!(actual, expected);
+
+        let ctx = CommandEncodingContext::default();
+        let raw = Into::<Command>::into(actual).as_bytes(&ctx);
+        assert_eq!(&raw, [0x00, 0xef, 0x30, 0x11].as_slice());
+    }
+}
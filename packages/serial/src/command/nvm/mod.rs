@@ -0,0 +1,12 @@
+use zwave_core::submodule;
+
+submodule!(ext_nvm_read_long_buffer);
+submodule!(ext_nvm_write_long_buffer);
+submodule!(get_nvm_id);
+
+/// The highest offset that can be addressed in the external NVM (24 bits)
+pub const EXT_NVM_MAX_OFFSET: u32 = 0xff_ffff;
+/// How many bytes can be read at once, so the response fits into a single frame
+pub const EXT_NVM_MAX_READ_LENGTH: u16 = 252;
+/// How many bytes can be written at once, so the request fits into a single frame
+pub const EXT_NVM_MAX_WRITE_LENGTH: u16 = 247;
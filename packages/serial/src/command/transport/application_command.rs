@@ -8,6 +8,7 @@ use zwave_core::parse::{
     multi::length_value,
 };
 use zwave_core::prelude::*;
+use zwave_core::serialize;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ApplicationCommandRequest {
@@ -85,8 +86,23 @@ impl CommandParsable for ApplicationCommandRequest {
 }
 
 impl SerializableWith<&CommandEncodingContext> for ApplicationCommandRequest {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: ApplicationCommandRequest::serialize() not implemented");
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::{bytes::be_u8, bytes::slice};
+
+        // The CC was sent by the source node to us
+        let ccctx = CCEncodingContext::builder()
+            .own_node_id(self.address.source_node_id)
+            .node_id(ctx.own_node_id)
+            .build();
+        let payload = self.command.as_ref().as_raw(&ccctx).as_bytes();
+
+        self.frame_info.serialize(output);
+        self.address
+            .source_node_id
+            .serialize(output, ctx.node_id_type);
+        be_u8(payload.len() as u8).serialize(output);
+        slice(&payload).serialize(output);
+        self.rssi.serialize(output);
     }
 }
 
@@ -120,3 +136,31 @@ impl ToLogPayload for ApplicationCommandRequest {
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::ApplicationCommandRequest, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let raw = vec![
+            0x00, // singlecast
+            0x05, // source node ID
+            0x03, // CC length
+            0x20, 0x03, 0x63, // Basic CC Report
+            0xc4, // RSSI
+        ];
+        let cmd = ApplicationCommandRequest::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.address.source_node_id, NodeId::new(5u8));
+        assert_eq!(cmd.rssi, Some(RSSI::Measured(-60)));
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
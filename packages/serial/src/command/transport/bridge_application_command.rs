@@ -10,6 +10,7 @@ use zwave_core::parse::{
     multi::length_value,
 };
 use zwave_core::prelude::*;
+use zwave_core::serialize;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BridgeApplicationCommandRequest {
@@ -97,8 +98,35 @@ impl CommandParsable for BridgeApplicationCommandRequest {
 }
 
 impl SerializableWith<&CommandEncodingContext> for BridgeApplicationCommandRequest {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: BridgeApplicationCommandRequest::serialize() not implemented");
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::{
+            bytes::{be_u8, slice},
+            sequence::bitmask_u8,
+        };
+
+        let (destination_node_id, multicast_node_ids) = match &self.address.destination {
+            Destination::Singlecast(node_id) => (*node_id, Vec::new()),
+            Destination::Broadcast => (NodeId::broadcast(), Vec::new()),
+            Destination::Multicast(node_ids) => (
+                ctx.own_node_id,
+                node_ids.iter().map(|node_id| u8::from(*node_id)).collect(),
+            ),
+        };
+        let ccctx = CCEncodingContext::builder()
+            .own_node_id(self.address.source_node_id)
+            .node_id(destination_node_id)
+            .build();
+        let payload = self.command.as_ref().as_raw(&ccctx).as_bytes();
+
+        self.frame_info.serialize(output);
+        destination_node_id.serialize(output, ctx.node_id_type);
+        self.address
+            .source_node_id
+            .serialize(output, ctx.node_id_type);
+        be_u8(payload.len() as u8).serialize(output);
+        slice(&payload).serialize(output);
+        bitmask_u8(&multicast_node_ids, 1).serialize(output);
+        self.rssi.serialize(output);
     }
 }
 
@@ -132,3 +160,35 @@ impl ToLogPayload for BridgeApplicationCommandRequest {
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{command::BridgeApplicationCommandRequest, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let raw = vec![
+            0x00, // singlecast
+            0x02, // destination node ID
+            0x05, // source node ID
+            0x02, // CC length
+            0x20, 0x02, // Basic CC Get
+            0x00, // no multicast node IDs
+            0xc4, // RSSI
+        ];
+        let cmd = BridgeApplicationCommandRequest::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            cmd.address.destination,
+            Destination::Singlecast(NodeId::new(2u8))
+        );
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
@@ -1,5 +1,6 @@
 use zwave_core::submodule;
 
 submodule!(send_data);
+submodule!(replication);
 submodule!(application_command);
 submodule!(bridge_application_command);
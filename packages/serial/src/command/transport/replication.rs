@@ -0,0 +1,259 @@
+use crate::prelude::*;
+use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
+use typed_builder::TypedBuilder;
+use zwave_cc::prelude::*;
+use zwave_core::parse::{bytes::be_u8, combinators::map, multi::length_value};
+use zwave_core::prelude::*;
+use zwave_core::serialize;
+use zwave_pal::prelude::*;
+
+/// Sends a command to a controller that is being included, e.g. to transfer groups to it
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response, callback, expects_callback = self.callback_id.is_some(), callback_id)]
+pub struct ReplicationSendDataRequest {
+    #[builder(setter(into))]
+    pub node_id: NodeId,
+    /// The CC to send. It must be serialized beforehand, so encoding errors surface
+    /// before the command is queued.
+    pub command: CCRaw,
+    #[builder(setter(skip), default)]
+    pub callback_id: Option<u8>,
+    #[builder(default)]
+    pub transmit_options: TransmitOptions,
+}
+
+impl CommandId for ReplicationSendDataRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ReplicationSendData
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for ReplicationSendDataRequest {
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandParsable for ReplicationSendDataRequest {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
+        let node_id = NodeId::parse(i, ctx.node_id_type)?;
+        let cc_raw = length_value(be_u8, CCRaw::parse).parse(i)?;
+        let transmit_options = TransmitOptions::parse(i)?;
+        let callback_id = be_u8(i)?;
+
+        Ok(Self {
+            node_id,
+            callback_id: Some(callback_id),
+            transmit_options,
+            command: cc_raw,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ReplicationSendDataRequest {
+    fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
+        use serialize::{bytes::be_u8, bytes::slice};
+
+        let payload = self.command.as_bytes();
+
+        self.node_id.serialize(output, ctx.node_id_type);
+        be_u8(payload.len() as u8).serialize(output);
+        slice(&payload).serialize(output);
+        self.transmit_options.serialize(output);
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+    }
+}
+
+impl ToLogPayload for ReplicationSendDataRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new()
+            .with_entry("node ID", self.node_id.to_string())
+            .with_entry("transmit options", self.transmit_options.to_string());
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret.into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicationSendDataResponse {
+    was_sent: bool,
+}
+
+impl CommandBase for ReplicationSendDataResponse {
+    fn is_ok(&self) -> bool {
+        self.was_sent
+    }
+}
+
+impl CommandId for ReplicationSendDataResponse {
+    fn command_type(&self) -> CommandType {
+        CommandType::Response
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ReplicationSendData
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandParsable for ReplicationSendDataResponse {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let was_sent = map(be_u8, |x| x > 0).parse(i)?;
+        Ok(Self { was_sent })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ReplicationSendDataResponse {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+        be_u8(if self.was_sent { 0x01 } else { 0x00 }).serialize(output);
+    }
+}
+
+impl ToLogPayload for ReplicationSendDataResponse {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayloadDict::new()
+            .with_entry("was sent", self.was_sent)
+            .into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicationSendDataCallback {
+    callback_id: Option<u8>,
+    pub transmit_status: TransmitStatus,
+}
+
+impl CommandBase for ReplicationSendDataCallback {
+    fn is_ok(&self) -> bool {
+        self.transmit_status == TransmitStatus::Ok
+    }
+
+    fn callback_id(&self) -> Option<u8> {
+        self.callback_id
+    }
+}
+
+impl CommandId for ReplicationSendDataCallback {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ReplicationSendData
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Controller
+    }
+}
+
+impl CommandParsable for ReplicationSendDataCallback {
+    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        let callback_id = be_u8(i)?;
+        let transmit_status = TransmitStatus::parse(i)?;
+        // Unlike SendData, there is no transmit report
+
+        Ok(Self {
+            callback_id: Some(callback_id),
+            transmit_status,
+        })
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ReplicationSendDataCallback {
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+        self.transmit_status.serialize(output);
+    }
+}
+
+impl ToLogPayload for ReplicationSendDataCallback {
+    fn to_log_payload(&self) -> LogPayload {
+        let mut ret = LogPayloadDict::new();
+        if let Some(callback_id) = self.callback_id {
+            ret = ret.with_entry("callback ID", callback_id);
+        }
+        ret = ret.with_entry("transmit status", self.transmit_status.to_string());
+        ret.into()
+    }
+}
+
+/// Tells the controller that a replication command that was received was handled,
+/// so it can acknowledge it to the including controller
+#[derive(Debug, Clone, PartialEq, Default, CommandRequest)]
+pub struct ReplicationCommandCompleteRequest {}
+
+impl CommandId for ReplicationCommandCompleteRequest {
+    fn command_type(&self) -> CommandType {
+        CommandType::Request
+    }
+
+    fn function_type(&self) -> FunctionType {
+        FunctionType::ReplicationCommandComplete
+    }
+
+    fn origin(&self) -> MessageOrigin {
+        MessageOrigin::Host
+    }
+}
+
+impl CommandBase for ReplicationCommandCompleteRequest {}
+
+impl CommandParsable for ReplicationCommandCompleteRequest {
+    fn parse(_i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+        Ok(Self {})
+    }
+}
+
+impl SerializableWith<&CommandEncodingContext> for ReplicationCommandCompleteRequest {
+    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        // No payload
+    }
+}
+
+impl ToLogPayload for ReplicationCommandCompleteRequest {
+    fn to_log_payload(&self) -> LogPayload {
+        LogPayload::empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::ReplicationSendDataCallback, prelude::*};
+    use bytes::Bytes;
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_callback_roundtrip() {
+        let raw = vec![
+            0x07, // callback ID
+            0x01, // no ACK
+        ];
+        let cmd = ReplicationSendDataCallback::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.transmit_status, TransmitStatus::NoAck);
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
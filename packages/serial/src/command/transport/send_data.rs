@@ -1,20 +1,24 @@
 use zwave_pal::prelude::*;
 use crate::prelude::*;
 use bytes::{Bytes, BytesMut};
+use proc_macros::CommandRequest;
 use typed_builder::TypedBuilder;
 use zwave_cc::{commandclass::CcOrRaw, prelude::*};
 use zwave_core::parse::{
     bytes::be_u8,
-    combinators::map,
+    combinators::{cond, map},
     multi::length_value,
 };
 use zwave_core::prelude::*;
 use zwave_core::serialize;
 
-#[derive(Debug, Clone, PartialEq, TypedBuilder)]
+#[derive(Debug, Clone, PartialEq, TypedBuilder, CommandRequest)]
+#[command_request(response, callback, expects_callback = self.callback_id.is_some(), callback_id)]
 pub struct SendDataRequest {
     #[builder(setter(into))]
     pub node_id: NodeId,
+    /// The CC to send. CCs that need the security managers to be serialized, e.g. S0
+    /// encapsulation, must be serialized beforehand using [`CC::try_as_raw`].
     pub command: CcOrRaw,
     #[builder(setter(skip), default)]
     pub callback_id: Option<u8>,
@@ -42,24 +46,6 @@ impl CommandBase for SendDataRequest {
     }
 }
 
-impl CommandRequest for SendDataRequest {
-    fn expects_response(&self) -> bool {
-        true
-    }
-
-    fn expects_callback(&self) -> bool {
-        self.callback_id.is_some()
-    }
-
-    fn needs_callback_id(&self) -> bool {
-        true
-    }
-
-    fn set_callback_id(&mut self, callback_id: Option<u8>) {
-        self.callback_id = callback_id;
-    }
-}
-
 impl CommandParsable for SendDataRequest {
     fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
         let node_id = NodeId::parse(i, ctx.node_id_type)?;
@@ -86,21 +72,11 @@ impl SerializableWith<&CommandEncodingContext> for SendDataRequest {
     fn serialize(&self, output: &mut BytesMut, ctx: &CommandEncodingContext) {
         use serialize::{bytes::be_u8, bytes::slice};
 
-        // TODO: Figure out if we should handle serialization errors elsewhere
-        // let error_msg = format!("Serializing command {:?} should not fail", &self.command);
-
-        let CcOrRaw::Raw(cc_raw) = &self.command else {
-            panic!("Sending a SendDataRequest requires the CC to be serialized already");
-        };
-
-        // let command = self.command.clone();
-        // let ccctx = CCEncodingContext::builder()
-        //     .own_node_id(ctx.own_node_id)
-        //     .node_id(self.node_id)
-        //     .build();
-        // let payload = command.as_raw(&ccctx).as_bytes();
-
-        let payload = cc_raw.as_bytes();
+        let ccctx = CCEncodingContext::builder()
+            .own_node_id(ctx.own_node_id)
+            .node_id(self.node_id)
+            .build();
+        let payload = self.command.as_raw(&ccctx).as_bytes();
 
         self.node_id.serialize(output, ctx.node_id_type);
         be_u8(payload.len() as u8).serialize(output);
@@ -176,8 +152,9 @@ impl ToLogPayload for SendDataResponse {
 #[derive(Debug, Clone, PartialEq)]
 pub struct SendDataCallback {
     callback_id: Option<u8>,
-    transmit_status: TransmitStatus,
-    transmit_report: TransmitReport,
+    pub transmit_status: TransmitStatus,
+    /// Controllers with SDK < 6.60 do not report how the command was transmitted
+    pub transmit_report: Option<TransmitReport>,
 }
 
 impl CommandBase for SendDataCallback {
@@ -205,10 +182,14 @@ impl CommandId for SendDataCallback {
 }
 
 impl CommandParsable for SendDataCallback {
-    fn parse(i: &mut Bytes, _ctx: CommandParsingContext) -> ParseResult<Self> {
+    fn parse(i: &mut Bytes, ctx: CommandParsingContext) -> ParseResult<Self> {
         let callback_id = be_u8(i)?;
         let transmit_status = TransmitStatus::parse(i)?;
-        let transmit_report = TransmitReport::parse(i, transmit_status != TransmitStatus::NoAck)?;
+        let transmit_report = cond(
+            ctx.quirks().send_data_callback_has_transmit_report,
+            |i: &mut Bytes| TransmitReport::parse(i, transmit_status != TransmitStatus::NoAck),
+        )
+        .parse(i)?;
 
         Ok(Self {
             callback_id: Some(callback_id),
@@ -219,8 +200,12 @@ impl CommandParsable for SendDataCallback {
 }
 
 impl SerializableWith<&CommandEncodingContext> for SendDataCallback {
-    fn serialize(&self, _output: &mut BytesMut, _ctx: &CommandEncodingContext) {
-        todo!("ERROR: SendDataCallback::serialize() not implemented")
+    fn serialize(&self, output: &mut BytesMut, _ctx: &CommandEncodingContext) {
+        use serialize::bytes::be_u8;
+
+        be_u8(self.callback_id.unwrap_or(0)).serialize(output);
+        self.transmit_status.serialize(output);
+        self.transmit_report.serialize(output);
     }
 }
 
@@ -231,17 +216,73 @@ impl ToLogPayload for SendDataCallback {
             ret = ret.with_entry("callback ID", callback_id);
         }
 
-        ret = ret
-            .with_entry(
-                "transmit status",
-                format!(
-                    "{:?}, took {} ms",
-                    self.transmit_status,
-                    self.transmit_report.tx_ticks * 10
-                ),
-            )
-            .extend(self.transmit_report.to_log_dict());
+        if let Some(transmit_report) = &self.transmit_report {
+            ret = ret
+                .with_entry(
+                    "transmit status",
+                    format!(
+                        "{}, took {} ms",
+                        self.transmit_status,
+                        transmit_report.tx_ticks * 10
+                    ),
+                )
+                .extend(transmit_report.to_log_dict());
+        } else {
+            ret = ret.with_entry("transmit status", self.transmit_status.to_string());
+        }
 
         ret.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        command::{SendDataCallback, SendDataRequest},
+        prelude::*,
+    };
+    use bytes::Bytes;
+    use zwave_cc::commandclass::{BasicCCGet, CC};
+    use zwave_core::prelude::*;
+
+    #[test]
+    fn test_request_with_cc() {
+        let cmd = SendDataRequest::builder()
+            .node_id(5u8)
+            .command(CC::from(BasicCCGet::default()).into())
+            .build();
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(
+            &Into::<Command>::into(cmd).as_bytes(&ctx),
+            [
+                0x05, // node ID
+                0x02, // CC length
+                0x20, 0x02, // Basic CC Get
+                0x25, // ACK, auto route, explore
+                0x00, // no callback ID
+            ]
+            .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_callback_roundtrip() {
+        let mut raw = vec![
+            0x04, // callback ID
+            0x00, // OK
+        ];
+        // Transmit report of a direct transmission
+        raw.extend_from_slice(&[0x00, 0x02, 0x00, 0xb5, 0x7f, 0x7f, 0x7f, 0x7f]);
+        raw.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x03, 0x01]);
+        let cmd = SendDataCallback::parse(
+            &mut Bytes::from(raw.clone()),
+            CommandParsingContext::default(),
+        )
+        .unwrap();
+        assert_eq!(cmd.transmit_status, TransmitStatus::Ok);
+        assert_eq!(cmd.transmit_report.as_ref().unwrap().tx_ticks, 2);
+
+        let ctx = CommandEncodingContext::default();
+        assert_eq!(&Into::<Command>::into(cmd).as_bytes(&ctx), raw.as_slice());
+    }
+}
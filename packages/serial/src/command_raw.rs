@@ -13,7 +13,7 @@ use zwave_core::{
             complete::{literal, skip, take},
         },
         combinators::peek,
-        validate,
+        validate_checksum,
     },
 };
 use zwave_pal::prelude::*;
@@ -66,7 +66,7 @@ impl Parsable for CommandRaw {
         let checksum = be_u8(i)?;
 
         let expected_checksum = command_checksum(&raw_data);
-        validate(
+        validate_checksum(
             checksum == expected_checksum,
             format!(
                 "checksum mismatch: expected {:#04x}, got {:#04x}",
@@ -145,7 +145,7 @@ fn test_parse_invalid_checksum() {
     match result {
         Ok(_) => panic!("Expected an error"),
         Err(ParseError::Incomplete(_)) => panic!("Expected a parser error"),
-        Err(_) => (),
+        Err(e) => assert!(e.is_checksum_mismatch()),
     }
 }
 
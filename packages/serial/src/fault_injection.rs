@@ -0,0 +1,307 @@
+//! Fault injection for exercising the queue, retry and timeout logic deterministically.
+//!
+//! [`FaultInjectingBinding`] wraps another [`SerialBinding`] and tampers with the frames it
+//! receives from the controller. The faults are configured through a [`FaultInjector`],
+//! which can be cloned and changed while the binding is in use.
+
+use crate::binding::SerialBinding;
+use crate::error::Result;
+use crate::frame::{ControlFlow, RawSerialFrame};
+use bytes::{Bytes, BytesMut};
+use core::num::NonZeroU32;
+use core::time::Duration;
+use zwave_core::definitions::CommandType;
+use zwave_pal::prelude::*;
+use zwave_pal::sync::Locked;
+use zwave_pal::time::{Instant, Timer};
+
+/// Which faults to inject into the frames received from the controller
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FaultInjectionConfig {
+    /// Drop every Nth ACK
+    pub drop_ack_every: Option<NonZeroU32>,
+    /// Corrupt the checksum of every Nth response
+    pub corrupt_response_every: Option<NonZeroU32>,
+    /// How long to hold back requests (callbacks and unsolicited commands) from the controller
+    pub callback_delay: Option<Duration>,
+}
+
+#[derive(Default)]
+struct FaultInjectionState {
+    config: FaultInjectionConfig,
+    acks_seen: u32,
+    responses_seen: u32,
+}
+
+/// What to do with a received frame
+enum Fault {
+    None,
+    Drop,
+    Corrupt,
+    Delay(Duration),
+}
+
+impl FaultInjectionState {
+    fn fault_for(&mut self, frame: &RawSerialFrame) -> Fault {
+        match frame {
+            RawSerialFrame::ControlFlow(ControlFlow::ACK) => {
+                self.acks_seen = self.acks_seen.wrapping_add(1);
+                if is_nth(self.acks_seen, self.config.drop_ack_every) {
+                    return Fault::Drop;
+                }
+            }
+            RawSerialFrame::Data(data) => match command_type(data) {
+                Some(CommandType::Response) => {
+                    self.responses_seen = self.responses_seen.wrapping_add(1);
+                    if is_nth(self.responses_seen, self.config.corrupt_response_every) {
+                        return Fault::Corrupt;
+                    }
+                }
+                Some(CommandType::Request) => {
+                    if let Some(delay) = self.config.callback_delay {
+                        return Fault::Delay(delay);
+                    }
+                }
+                None => {}
+            },
+            _ => {}
+        }
+        Fault::None
+    }
+}
+
+fn is_nth(count: u32, every: Option<NonZeroU32>) -> bool {
+    every.is_some_and(|n| count % n.get() == 0)
+}
+
+/// Reads the command type of a data frame: SOF, length, type, function, ..., checksum
+fn command_type(data: &Bytes) -> Option<CommandType> {
+    data.get(2).and_then(|&byte| CommandType::try_from(byte).ok())
+}
+
+/// Flips the bits of the checksum, so the frame is rejected by the serial API
+fn corrupt(data: Bytes) -> Bytes {
+    let mut data = BytesMut::from(data);
+    if let Some(checksum) = data.last_mut() {
+        *checksum = !*checksum;
+    }
+    data.freeze()
+}
+
+/// Shared handle to change the injected faults at runtime
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    state: Arc<Locked<FaultInjectionState>>,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultInjectionConfig) -> Self {
+        Self {
+            state: Arc::new(Locked::new(FaultInjectionState {
+                config,
+                ..Default::default()
+            })),
+        }
+    }
+
+    pub fn config(&self) -> FaultInjectionConfig {
+        self.state.inspect(|state| state.config)
+    }
+
+    /// Replaces the injected faults and restarts counting frames
+    pub fn configure(&self, config: FaultInjectionConfig) {
+        self.state.update(|state| {
+            *state = FaultInjectionState {
+                config,
+                ..Default::default()
+            }
+        });
+    }
+
+    /// Stops injecting faults
+    pub fn disable(&self) {
+        self.configure(FaultInjectionConfig::default());
+    }
+
+    fn fault_for(&self, frame: &RawSerialFrame) -> Fault {
+        self.state.update(|state| state.fault_for(frame))
+    }
+}
+
+/// A [`SerialBinding`] that injects faults into the frames received by another binding
+pub struct FaultInjectingBinding<B> {
+    inner: B,
+    injector: FaultInjector,
+    /// A frame that is being held back, and when to release it.
+    /// Kept here so a cancelled read does not lose the frame.
+    delayed: Option<(Instant, RawSerialFrame)>,
+}
+
+impl<B: SerialBinding> FaultInjectingBinding<B> {
+    pub fn new(inner: B, injector: FaultInjector) -> Self {
+        Self {
+            inner,
+            injector,
+            delayed: None,
+        }
+    }
+
+    pub fn injector(&self) -> &FaultInjector {
+        &self.injector
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    async fn read_impl(&mut self) -> Option<RawSerialFrame> {
+        loop {
+            if let Some((release_at, _)) = &self.delayed {
+                let remaining = release_at
+                    .checked_duration_since(Instant::now())
+                    .unwrap_or_default();
+                if !remaining.is_zero() {
+                    Timer::after(remaining).await;
+                }
+                return self.delayed.take().map(|(_, frame)| frame);
+            }
+
+            let frame = self.inner.read().await?;
+            match self.injector.fault_for(&frame) {
+                Fault::None => return Some(frame),
+                Fault::Drop => continue,
+                Fault::Corrupt => {
+                    let RawSerialFrame::Data(data) = frame else {
+                        return Some(frame);
+                    };
+                    return Some(RawSerialFrame::Data(corrupt(data)));
+                }
+                Fault::Delay(delay) => {
+                    self.delayed = Some((Instant::now() + delay, frame));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: SerialBinding + Send> SerialBinding for FaultInjectingBinding<B> {
+    fn write(
+        &mut self,
+        frame: RawSerialFrame,
+    ) -> impl core::future::Future<Output = Result<()>> + Send {
+        self.inner.write(frame)
+    }
+
+    fn read(&mut self) -> impl core::future::Future<Output = Option<RawSerialFrame>> + Send {
+        self.read_impl()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: SerialBinding> SerialBinding for FaultInjectingBinding<B> {
+    fn write(&mut self, frame: RawSerialFrame) -> impl core::future::Future<Output = Result<()>> {
+        self.inner.write(frame)
+    }
+
+    fn read(&mut self) -> impl core::future::Future<Output = Option<RawSerialFrame>> {
+        self.read_impl()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::executor::block_on;
+
+    /// Replays a fixed sequence of frames
+    struct ScriptedBinding {
+        frames: Vec<RawSerialFrame>,
+    }
+
+    impl SerialBinding for ScriptedBinding {
+        async fn write(&mut self, _frame: RawSerialFrame) -> Result<()> {
+            Ok(())
+        }
+
+        async fn read(&mut self) -> Option<RawSerialFrame> {
+            if self.frames.is_empty() {
+                None
+            } else {
+                Some(self.frames.remove(0))
+            }
+        }
+    }
+
+    fn read_all(binding: &mut impl SerialBinding) -> Vec<RawSerialFrame> {
+        block_on(async {
+            let mut frames = Vec::new();
+            while let Some(frame) = binding.read().await {
+                frames.push(frame);
+            }
+            frames
+        })
+    }
+
+    const ACK: RawSerialFrame = RawSerialFrame::ControlFlow(ControlFlow::ACK);
+
+    fn response() -> RawSerialFrame {
+        // GetControllerId response
+        RawSerialFrame::Data(Bytes::from_static(&[
+            0x01, 0x08, 0x01, 0x20, 0xca, 0xfe, 0xba, 0xbe, 0x01, 0x00,
+        ]))
+    }
+
+    #[test]
+    fn test_drop_every_nth_ack() {
+        let injector = FaultInjector::new(FaultInjectionConfig {
+            drop_ack_every: NonZeroU32::new(2),
+            ..Default::default()
+        });
+        let mut binding = FaultInjectingBinding::new(
+            ScriptedBinding {
+                frames: vec![ACK, ACK, ACK, ACK, ACK],
+            },
+            injector,
+        );
+        assert_eq!(read_all(&mut binding), vec![ACK, ACK, ACK]);
+    }
+
+    #[test]
+    fn test_corrupt_response() {
+        let injector = FaultInjector::new(FaultInjectionConfig {
+            corrupt_response_every: NonZeroU32::new(1),
+            ..Default::default()
+        });
+        let mut binding = FaultInjectingBinding::new(
+            ScriptedBinding {
+                frames: vec![ACK, response()],
+            },
+            injector,
+        );
+        let frames = read_all(&mut binding);
+        assert_eq!(frames[0], ACK);
+        let RawSerialFrame::Data(data) = &frames[1] else {
+            panic!("expected a data frame");
+        };
+        assert_eq!(data.last(), Some(&0xff));
+    }
+
+    #[test]
+    fn test_reconfigure_at_runtime() {
+        let injector = FaultInjector::new(FaultInjectionConfig {
+            drop_ack_every: NonZeroU32::new(1),
+            ..Default::default()
+        });
+        let mut binding = FaultInjectingBinding::new(
+            ScriptedBinding {
+                frames: vec![ACK, ACK],
+            },
+            injector.clone(),
+        );
+        block_on(async {
+            injector.disable();
+            assert_eq!(binding.read().await, Some(ACK));
+        });
+    }
+}
@@ -7,6 +7,15 @@ pub mod command;
 pub mod command_raw;
 pub mod error;
 pub mod frame;
+pub mod quirks;
 pub mod serialport;
 
+#[cfg(any(test, feature = "fault-injection"))]
+pub mod fault_injection;
+
+#[cfg(feature = "list-ports")]
+mod ports;
+#[cfg(feature = "list-ports")]
+pub use ports::*;
+
 pub mod prelude;
@@ -0,0 +1,201 @@
+use thiserror::Error;
+
+/// A serial port that is available on this system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortInfo {
+    /// The path or name under which the port can be opened
+    pub path: String,
+    /// Information about the USB device, if the port is connected via USB
+    pub usb: Option<UsbInfo>,
+    /// The Z-Wave stick this port belongs to, if it could be identified
+    pub known_stick: Option<KnownStick>,
+}
+
+/// Identifies the USB device behind a serial port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsbInfo {
+    pub vid: u16,
+    pub pid: u16,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StickVendor {
+    Aeotec,
+    Zooz,
+    SiliconLabs,
+}
+
+/// A Z-Wave stick that was recognized by its USB IDs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownStick {
+    pub vendor: StickVendor,
+    /// The sticks that use these USB IDs
+    pub name: &'static str,
+}
+
+/// USB IDs of known Z-Wave sticks. Several vendors use the same USB-to-serial chips,
+/// so the vendor is refined using the manufacturer and product strings where possible.
+const KNOWN_STICKS: &[(u16, u16, KnownStick)] = &[
+    (
+        0x0658,
+        0x0200,
+        KnownStick {
+            vendor: StickVendor::Aeotec,
+            name: "Aeotec Z-Stick Gen5 / Zooz ZST10 (500 series)",
+        },
+    ),
+    (
+        0x10c4,
+        0xea60,
+        KnownStick {
+            vendor: StickVendor::SiliconLabs,
+            name: "Silicon Labs CP210x based stick (e.g. Zooz ZST10 700)",
+        },
+    ),
+    (
+        0x1a86,
+        0x55d4,
+        KnownStick {
+            vendor: StickVendor::Aeotec,
+            name: "Aeotec Z-Stick 7 / Zooz ZST39 (700/800 series)",
+        },
+    ),
+];
+
+/// Returns which Z-Wave stick the given USB device is, if it is a known one
+pub fn identify_stick(usb: &UsbInfo) -> Option<KnownStick> {
+    let (_, _, stick) = KNOWN_STICKS
+        .iter()
+        .find(|(vid, pid, _)| *vid == usb.vid && *pid == usb.pid)?;
+
+    let describes = |needle: &str| {
+        [&usb.manufacturer, &usb.product]
+            .into_iter()
+            .flatten()
+            .any(|s| s.to_lowercase().contains(needle))
+    };
+    let vendor = if describes("zooz") {
+        StickVendor::Zooz
+    } else if describes("aeotec") {
+        StickVendor::Aeotec
+    } else {
+        stick.vendor
+    };
+
+    Some(KnownStick { vendor, ..*stick })
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DetectPortError {
+    #[error("Failed to enumerate serial ports: {0}")]
+    Enumeration(String),
+    #[error("No Z-Wave stick found")]
+    NoStickFound,
+    #[error("Found multiple Z-Wave sticks: {}", .0.join(", "))]
+    Ambiguous(Vec<String>),
+}
+
+/// Lists all serial ports on this system, including USB information where available
+pub fn list_ports() -> Result<Vec<PortInfo>, DetectPortError> {
+    let ports =
+        serialport::available_ports().map_err(|e| DetectPortError::Enumeration(e.to_string()))?;
+
+    Ok(ports
+        .into_iter()
+        .map(|port| {
+            let usb = match port.port_type {
+                serialport::SerialPortType::UsbPort(usb) => Some(UsbInfo {
+                    vid: usb.vid,
+                    pid: usb.pid,
+                    manufacturer: usb.manufacturer,
+                    product: usb.product,
+                    serial_number: usb.serial_number,
+                }),
+                _ => None,
+            };
+            let known_stick = usb.as_ref().and_then(identify_stick);
+            PortInfo {
+                path: port.port_name,
+                usb,
+                known_stick,
+            }
+        })
+        .collect())
+}
+
+/// Picks the only known Z-Wave stick among the given ports
+pub fn select_stick(ports: &[PortInfo]) -> Result<&PortInfo, DetectPortError> {
+    let mut sticks = ports.iter().filter(|port| port.known_stick.is_some());
+    match (sticks.next(), sticks.next()) {
+        (None, _) => Err(DetectPortError::NoStickFound),
+        (Some(stick), None) => Ok(stick),
+        (Some(_), Some(_)) => Err(DetectPortError::Ambiguous(
+            ports
+                .iter()
+                .filter(|port| port.known_stick.is_some())
+                .map(|port| port.path.clone())
+                .collect(),
+        )),
+    }
+}
+
+/// Returns the path of the only Z-Wave stick connected to this system
+pub fn auto_detect_port() -> Result<String, DetectPortError> {
+    let ports = list_ports()?;
+    select_stick(&ports).map(|port| port.path.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn usb_port(path: &str, vid: u16, pid: u16, product: Option<&str>) -> PortInfo {
+        let usb = UsbInfo {
+            vid,
+            pid,
+            manufacturer: None,
+            product: product.map(String::from),
+            serial_number: None,
+        };
+        PortInfo {
+            path: path.to_string(),
+            known_stick: identify_stick(&usb),
+            usb: Some(usb),
+        }
+    }
+
+    #[test]
+    fn test_identify_stick() {
+        let gen5 = usb_port("/dev/ttyACM0", 0x0658, 0x0200, None);
+        assert_eq!(gen5.known_stick.unwrap().vendor, StickVendor::Aeotec);
+
+        let zst39 = usb_port("/dev/ttyACM1", 0x1a86, 0x55d4, Some("ZOOZ ZST39 LR"));
+        assert_eq!(zst39.known_stick.unwrap().vendor, StickVendor::Zooz);
+
+        let unknown = usb_port("/dev/ttyUSB0", 0x0403, 0x6001, None);
+        assert_eq!(unknown.known_stick, None);
+    }
+
+    #[test]
+    fn test_select_stick() {
+        let other = usb_port("/dev/ttyUSB0", 0x0403, 0x6001, None);
+        let stick = usb_port("/dev/ttyACM0", 0x0658, 0x0200, None);
+        let second = usb_port("/dev/ttyACM1", 0x10c4, 0xea60, None);
+
+        assert_eq!(
+            select_stick(std::slice::from_ref(&other)),
+            Err(DetectPortError::NoStickFound)
+        );
+        assert_eq!(select_stick(&[other.clone(), stick.clone()]), Ok(&stick));
+        assert_eq!(
+            select_stick(&[stick, other, second]),
+            Err(DetectPortError::Ambiguous(vec![
+                "/dev/ttyACM0".to_string(),
+                "/dev/ttyACM1".to_string()
+            ]))
+        );
+    }
+}
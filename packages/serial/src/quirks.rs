@@ -0,0 +1,153 @@
+use crate::command::SerialApiSetupCommand;
+use zwave_core::prelude::*;
+
+const fn version(major: u8, minor: u8, patch: u8) -> Version {
+    Version {
+        major,
+        minor,
+        patch: Some(patch),
+    }
+}
+
+/// The first SDK of the 500 series
+const SDK_500_SERIES: Version = version(6, 50, 0);
+/// The first SDK whose SendData callbacks include a transmit report
+const SDK_EXTENDED_TX_REPORT: Version = version(6, 60, 0);
+/// The first SDK of the 700 series
+const SDK_700_SERIES: Version = version(7, 0, 0);
+/// The first SDK that encodes the supported Serial API setup commands as specified
+const SDK_FIXED_SETUP_BITMASK: Version = version(7, 19, 1);
+
+/// The Serial API setup commands a 500 series controller supports
+/// without being able to report them
+const SERIAL_API_SETUP_COMMANDS_500_SERIES: &[SerialApiSetupCommand] =
+    &[SerialApiSetupCommand::SetTxStatusReport];
+
+/// Deviations of a controller's Serial API from the Host API specification,
+/// depending on the SDK its firmware was built with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdkQuirks {
+    /// Whether the extended bitmask of supported Serial API setup commands is shifted by 1 bit
+    pub serial_api_setup_bitmask_shifted: bool,
+    /// The Serial API setup commands which the controller supports, but cannot report
+    /// because it does not implement `GetSupportedCommands`.
+    /// `None` if the supported commands can be queried.
+    pub implicit_serial_api_setup_commands: Option<&'static [SerialApiSetupCommand]>,
+    /// Whether SendData callbacks include a transmit report after the transmit status
+    pub send_data_callback_has_transmit_report: bool,
+    /// The maximum size of the CC payload of a SendData command in bytes
+    pub max_send_data_payload: usize,
+}
+
+impl SdkQuirks {
+    /// Looks up the quirks of the given SDK version.
+    /// If the version is unknown, the most conservative assumptions are made.
+    pub fn for_sdk_version(sdk_version: Option<Version>) -> Self {
+        let Some(sdk_version) = sdk_version else {
+            return Self {
+                serial_api_setup_bitmask_shifted: true,
+                implicit_serial_api_setup_commands: None,
+                send_data_callback_has_transmit_report: true,
+                max_send_data_payload: 40,
+            };
+        };
+
+        let implicit_serial_api_setup_commands =
+            if sdk_version >= SDK_500_SERIES && sdk_version < SDK_700_SERIES {
+                Some(SERIAL_API_SETUP_COMMANDS_500_SERIES)
+            } else {
+                None
+            };
+
+        Self {
+            serial_api_setup_bitmask_shifted: sdk_version < SDK_FIXED_SETUP_BITMASK,
+            implicit_serial_api_setup_commands,
+            send_data_callback_has_transmit_report: sdk_version >= SDK_EXTENDED_TX_REPORT,
+            // 300 and 400 series controllers reserve more space for routing information
+            max_send_data_payload: if sdk_version >= SDK_500_SERIES {
+                46
+            } else {
+                40
+            },
+        }
+    }
+}
+
+impl Default for SdkQuirks {
+    fn default() -> Self {
+        Self::for_sdk_version(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::command::{CommandBase, CommandParsable, CommandParsingContext, SendDataCallback};
+    use bytes::Bytes;
+
+    fn quirks_of(sdk_version: &str) -> SdkQuirks {
+        SdkQuirks::for_sdk_version(Some(Version::try_from(sdk_version).unwrap()))
+    }
+
+    #[test]
+    fn test_400_series() {
+        let quirks = quirks_of("6.10.0");
+        assert!(quirks.serial_api_setup_bitmask_shifted);
+        assert_eq!(quirks.implicit_serial_api_setup_commands, None);
+        assert!(!quirks.send_data_callback_has_transmit_report);
+        assert_eq!(quirks.max_send_data_payload, 40);
+    }
+
+    #[test]
+    fn test_500_series() {
+        let quirks = quirks_of("6.51.10");
+        assert!(!quirks.send_data_callback_has_transmit_report);
+        assert_eq!(
+            quirks.implicit_serial_api_setup_commands,
+            Some(SERIAL_API_SETUP_COMMANDS_500_SERIES)
+        );
+        assert_eq!(quirks.max_send_data_payload, 46);
+
+        let quirks = quirks_of("6.81.6");
+        assert!(quirks.serial_api_setup_bitmask_shifted);
+        assert!(quirks.send_data_callback_has_transmit_report);
+        assert_eq!(
+            quirks.implicit_serial_api_setup_commands,
+            Some(SERIAL_API_SETUP_COMMANDS_500_SERIES)
+        );
+    }
+
+    #[test]
+    fn test_700_series() {
+        let quirks = quirks_of("7.18.0");
+        assert!(quirks.serial_api_setup_bitmask_shifted);
+        assert_eq!(quirks.implicit_serial_api_setup_commands, None);
+        assert!(quirks.send_data_callback_has_transmit_report);
+        assert_eq!(quirks.max_send_data_payload, 46);
+
+        let quirks = quirks_of("7.19.1");
+        assert!(!quirks.serial_api_setup_bitmask_shifted);
+        assert_eq!(quirks.implicit_serial_api_setup_commands, None);
+    }
+
+    #[test]
+    fn test_send_data_callback_without_transmit_report() {
+        let ctx = CommandParsingContext::builder()
+            .sdk_version(Version::try_from("6.51.10").unwrap())
+            .build();
+        // Callback ID and transmit status only
+        let mut raw = Bytes::from_static(&[0x01, 0x00]);
+        let callback = SendDataCallback::parse(&mut raw, ctx).unwrap();
+        assert_eq!(callback.callback_id(), Some(0x01));
+        assert_eq!(callback.transmit_status, TransmitStatus::Ok);
+        assert_eq!(callback.transmit_report, None);
+    }
+
+    #[test]
+    fn test_unknown_sdk() {
+        let quirks = SdkQuirks::default();
+        assert!(quirks.serial_api_setup_bitmask_shifted);
+        assert!(quirks.send_data_callback_has_transmit_report);
+        assert_eq!(quirks.max_send_data_payload, 40);
+    }
+}